@@ -1,26 +1,72 @@
+use crate::audio_track_ui::AudioTrackPromptState;
 use crate::bundled::ensure_bundled_tools;
+use crate::chapter_ui::ChapterListState;
+use crate::cloud_files::{fetch_cloud_files, is_cloud_only};
+use crate::crop_ui::CropPromptState;
+use crate::diagnostics::{self, BUG_REPORT_LOG_WINDOW, DEFAULT_BUG_REPORT_NAME};
 use crate::download::{
-    ensure_deno, ensure_yt_dlp, read_clipboard_text, run_download, DownloadEvent, ProcessTracker,
-    ProgressUpdate, CANCELLED_ERROR,
+    AudioReplacement, CancellationToken, CollisionPrompt, DownloadEvent, DownloadJob,
+    DownloadStage, DryRunReport, FailedJobs, FailureKind, JobQueue, JobRecord, KnownError,
+    MuteEvent, ProxyEvent, QualityMode, STALE_STAGING_AGE, STALL_RETRY_LIMIT, build_dry_run,
+    clear_job_record, dry_run_login_args, ensure_deno, ensure_yt_dlp, generate_proxies,
+    load_interrupted_job, netrc_login_args, read_clipboard_text, remove_stale_login_files,
+    remove_stale_staging_dirs, replace_audio_files, reported_progress, run_download,
 };
-use crate::fs_utils::{delete_download_file, is_executable, load_mp4_files};
-use crate::mac_input_source::{current_mode, InputMode};
+use crate::event_stream::{self, EventStreamServer, StreamEvent};
+use crate::file_list::{
+    DateGroup, FileSort, ListedFile, local_now, ordered_hit_paths, selected_in_order,
+    selection_range, sorted_order,
+};
+use crate::format_ui::FormatPromptState;
+use crate::fs_utils::{delete_download_file, ensure_dir, load_mp4_files};
+use crate::i18n::{self, Msg, tr, tr_fmt};
+use crate::mac_apple_event::{self, AppUrlRequest};
+use crate::mac_dock;
+use crate::mac_file_dialog::{choose_file, choose_save_path};
+use crate::mac_finder_tags::{read_finder_tags, split_rating, write_finder_tags};
+use crate::mac_hotkey;
+use crate::mac_input_source::{InputMode, current_mode, select_mode, select_source};
 use crate::mac_menu;
+use crate::mac_notification;
+use crate::mac_status_item::{self, StatusItemAction};
 use crate::mac_window;
-use crate::paths::{search_index_db_path, yt_dlp_path};
-use crate::search_index::{SearchEngine, SearchHit, SearchRequest, SearchSort};
-use crate::settings::{load_cookie_args, save_settings, SettingsData};
+use crate::network;
+use crate::osc::{self, OscArg, OscBridge, OscCommand};
+use crate::paths::{log_dir, proxy_dir, search_index_db_path, yt_dlp_path};
+use crate::platform::{is_executable, open_with_default_app, reveal_in_file_manager};
+use crate::playlist::{DEFAULT_PLAYLIST_NAME, write_playlist};
+use crate::power;
+use crate::preview_ui::PreviewPromptState;
+use crate::search_index::{
+    self, SearchCursor, SearchEngine, SearchHit, SearchPage, SearchRequest, SearchSort, SkipRules,
+    backup_due,
+};
+use crate::settings::{
+    DownloadPreset, INDEX_BACKUP_KEEP, JsRuntime, SearchInputMode, SettingsData, load_cookie_args,
+    load_index_backup, load_js_runtime, load_site_login, load_stall_timeout, save_settings,
+};
 use crate::settings_ui;
+use crate::shortcuts::{ShortcutAction, ShortcutBindings, Shortcuts};
+use crate::subscriptions::SubscriptionPoller;
+use crate::tag_ui::TagEditorState;
 use crate::theme::apply_theme;
+use crate::toast_ui::{ToastKind, ToastState};
 use crate::ui;
-use crate::{app_logger::AppLogger, log_ui::LogUiState};
+use crate::watch_folder::WatchFolder;
+use crate::{
+    app_logger::{AppLogger, JobStatus, LogLevel, LogSource},
+    log_ui::LogUiState,
+};
 use drag::{DragItem, Image, Options};
 use eframe::egui;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{mpsc, Arc};
+use std::sync::{Arc, mpsc};
 use std::thread;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
+use url::Url;
 
 pub fn run() -> eframe::Result<()> {
     let settings = SettingsData::load();
@@ -39,6 +85,7 @@ pub fn run() -> eframe::Result<()> {
         ..Default::default()
     };
 
+    mac_apple_event::install_event_handlers();
     eframe::run_native(
         "VJDownloader",
         options,
@@ -46,43 +93,267 @@ pub fn run() -> eframe::Result<()> {
     )
 }
 
+const SEARCH_PAGE_SIZE: usize = 200;
+const SEARCH_HISTORY_LIMIT: usize = 20;
+// 貼り付けの結果を進捗パネルに表示しておく時間
+pub(crate) const PASTE_FEEDBACK_DURATION: Duration = Duration::from_millis(2500);
+// この件数を超えるダウンロードをまとめてキャンセルする場合は、確認してから行う。
+const CANCEL_ALL_CONFIRM_THRESHOLD: usize = 3;
+// 検索インデックスのバックアップが必要かを確かめる間隔
+const INDEX_BACKUP_CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+// メニューの「最近のダウンロード」に並べる件数
+const RECENT_MENU_LIMIT: usize = 10;
+
 #[derive(Clone)]
 struct SearchJob {
     seq: u64,
     request: SearchRequest,
+    append: bool,
 }
 
 struct SearchJobResult {
     seq: u64,
-    result: Result<Vec<SearchHit>, String>,
+    append: bool,
+    result: Result<SearchPage, String>,
+}
+
+// ダウンロード一覧の並びを作ったときの絞り込み（小文字にした文字列）と並び順
+#[derive(Debug, PartialEq, Eq)]
+struct DownloadsOrderKey {
+    filter: String,
+    session_only: bool,
+    sort: Option<FileSort>,
+}
+
+// キーボード選択の対象となる一覧。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum FileListKind {
+    Downloads,
+    Search,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct FileSelection {
+    pub(crate) list: FileListKind,
+    // キーボード操作や範囲選択の起点となる行
+    pub(crate) path: PathBuf,
+    // Cmd/Shift+クリックで複数選択した行（path を含む）
+    pub(crate) paths: Vec<PathBuf>,
+}
+
+// 一覧の行で受けたクリックやメニューの操作。検索結果は描画中に一覧から取り出しているため、
+// 描画を終えて一覧を戻してから適用する。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum PendingRowAction {
+    Select(PathBuf),
+    Toggle(PathBuf),
+    Extend(PathBuf),
+    Remove(PathBuf),
+    ExportPlaylist,
+    FetchCloud,
+    ReplaceAudio(AudioReplacement),
+    ReplaceAudioWithTrack,
+    GenerateProxies,
+    EditTags,
+}
+
+// 以前にダウンロード済みのためスキップした項目
+#[derive(Clone, Debug)]
+pub(crate) struct AlreadyDownloaded {
+    pub(crate) url: String,
+    // 保存したファイル。yt-dlp のダウンロードアーカイブで分かった場合は None。
+    pub(crate) path: Option<PathBuf>,
+}
+
+// 取得を終え、次のダウンロードと並行して変換を続けている項目
+pub(crate) struct BackgroundConversion {
+    // すべてキャンセルした場合は None にして、一時停止中でもキューへ戻さない。
+    job: Option<DownloadJob>,
+    job_id: Option<u64>,
+    pub(crate) url: String,
+    rx: mpsc::Receiver<DownloadEvent>,
+    active_flag: Arc<AtomicBool>,
+    cancel: CancellationToken,
+    detected_error: Option<KnownError>,
+    pub(crate) message: String,
+    pub(crate) progress: Option<f32>,
+}
+
+// 選んだ動画から低解像度のプロキシを作っている間の状態
+pub(crate) struct ProxyBatch {
+    rx: mpsc::Receiver<ProxyEvent>,
+    cancel: CancellationToken,
+}
+
+// クラウドにだけあるファイルを取得している間の状態
+pub(crate) struct CloudFetch {
+    rx: mpsc::Receiver<(PathBuf, Result<(), String>)>,
+    fetched: usize,
+}
+
+// 選んだ動画の音声を外す・差し替えたコピーを作っている間の状態
+pub(crate) struct MuteBatch {
+    rx: mpsc::Receiver<MuteEvent>,
+    cancel: CancellationToken,
+}
+
+// Cmd+V で貼り付けた内容の受け付け結果
+#[derive(Clone, Debug)]
+pub(crate) struct PasteFeedback {
+    pub(crate) message: String,
+    pub(crate) accepted: bool,
+    pub(crate) shown_at: Instant,
 }
 
 pub struct DownloaderApp {
     pub(crate) download_dir: PathBuf,
-    pub(crate) downloaded_files: Vec<PathBuf>,
+    pub(crate) downloaded_files: Vec<ListedFile>,
+    // ダウンロード一覧の絞り込み。ファイル名の一部と、この起動中に保存したファイルだけを表示するか。
+    pub(crate) downloads_filter: String,
+    pub(crate) downloads_session_only: bool,
+    // 列見出しで選んだダウンロード一覧・検索結果の並び順。None は元の並び（新しい順）。
+    pub(crate) downloads_sort: Option<FileSort>,
+    pub(crate) search_sort: Option<FileSort>,
+    // 検索結果を search_order_sort で並べた行の位置。毎フレーム並べ直さないよう、結果か並び順が変わった時だけ作り直す。
+    pub(crate) search_order: Vec<usize>,
+    search_order_sort: Option<FileSort>,
+    // 絞り込みと並び順を適用したダウンロード一覧の行の位置と、作ったときの絞り込み・並び順。一覧を読み直すと None にする。
+    downloads_order: Vec<usize>,
+    downloads_order_key: Option<DownloadsOrderKey>,
+    // 畳んでいるダウンロード一覧の日付の区切り
+    pub(crate) collapsed_date_groups: HashSet<DateGroup>,
+    // この起動中に保存先へ現れたファイル
+    pub(crate) session_downloads: HashSet<PathBuf>,
+    // 起動した時刻。これより後に更新されたファイルを、この起動中に保存したファイルとみなす。
+    session_started: SystemTime,
     pub(crate) download_in_progress: bool,
+    pub(crate) download_queue: JobQueue,
+    // 失敗したダウンロード。エラーと一緒に表示し、同じ指定でやり直せるようにする。
+    pub(crate) failed_jobs: FailedJobs,
+    // 次に追加するダウンロードの画質方式（キューの各項目は追加時の値を保持する）
+    pub(crate) quality_mode: QualityMode,
+    // オンの間は追加したダウンロードを実行せず、実行されるコマンドを表示する（この起動中だけ）
+    pub(crate) dry_run: bool,
+    // 表示中のドライランの結果
+    pub(crate) dry_run_report: Option<DryRunReport>,
+    // 次に追加するダウンロードだけ保存先を変える場合のフォルダ。None は設定の保存先。
+    pub(crate) download_dir_override: Option<PathBuf>,
+    // 最近使った保存先（新しい順）
+    pub(crate) recent_download_dirs: Vec<String>,
+    // 設定で割り当てたメイン画面のショートカット
+    pub(crate) shortcuts: Shortcuts,
+    // ツールバーで切り替えられるプロファイル名と、選択中のプロファイル名（空欄なら未選択）
+    pub(crate) profile_names: Vec<String>,
+    pub(crate) active_profile: String,
+    // ダウンロード画面で切り替えられるクッキーのアカウント名と、選択中のアカウント名（空欄なら既定）
+    pub(crate) cookie_account_names: Vec<String>,
+    // メイン画面のプリセットボタン（名前が空欄のものは表示しない）
+    pub(crate) presets: Vec<DownloadPreset>,
+    pub(crate) active_cookie_account: String,
+    pub(crate) queue_paused: bool,
+    // 接続確認でネットワークに届かなかった場合に立つ
+    pub(crate) offline: bool,
+    // バッテリー駆動中にインデックスを控えめにするか（設定）と、今控えめにしているか
+    battery_saver: bool,
+    low_power: bool,
+    current_download: Option<DownloadJob>,
+    // 実行中のダウンロードが取得を終えて変換に移った場合に立つ。待機中の項目があれば変換を裏へ回す。
+    current_downloaded: bool,
+    // 裏で変換を続けているダウンロード
+    pub(crate) conversions: Vec<BackgroundConversion>,
+    // 起動してから開始したダウンロードの数と、実行中のダウンロードの番号（ログに付ける）
+    download_count: u64,
+    current_job_id: Option<u64>,
     pub(crate) progress_message: String,
-    pub(crate) progress_value: f32,
+    // 進捗バーの割合。None は割合が分からない間（インジケータをアニメーション表示する）。
+    pub(crate) progress_value: Option<f32>,
     pub(crate) progress_visible: bool,
+    pub(crate) paste_feedback: Option<PasteFeedback>,
     pub(crate) download_active_flag: Arc<AtomicBool>,
-    pub(crate) cancel_flag: Option<Arc<AtomicBool>>,
-    pub(crate) process_tracker: Option<ProcessTracker>,
+    pub(crate) cancel_token: Option<CancellationToken>,
     pub(crate) rx: Option<mpsc::Receiver<DownloadEvent>>,
     pub(crate) last_scan: Instant,
+    // 保存先の一覧を検索インデックスから読んでいる場合の、読んだ時点の変更番号。None はフォルダを直接読む。
+    downloads_index_seq: Option<u64>,
     pub(crate) refresh_needed: bool,
     pub(crate) settings_ui: settings_ui::SettingsUiState,
     pub(crate) log_ui: LogUiState,
     pub(crate) status_logs: AppLogger,
+    pub(crate) toasts: ToastState,
     pub(crate) pending_window_resize: Option<egui::Vec2>,
     pub(crate) did_snap: bool,
     pub(crate) current_window_size: Option<egui::Vec2>,
     pub(crate) download_panel_width: f32,
     pub(crate) search_panel_width: f32,
+    // 検索を別ウィンドウに切り離しているか。ウィンドウの大きさは終了時に保存する。
+    pub(crate) search_detached: bool,
+    pub(crate) search_window_size: egui::Vec2,
+    // 切り離した検索ウィンドウが次に描かれた時に、検索欄へフォーカスを移す
+    pub(crate) pending_search_focus: bool,
+    // 検索欄にフォーカスしたときに切り替える入力モードと、フォーカスが外れた時に戻す入力ソースの ID
+    pub(crate) search_input_mode: SearchInputMode,
+    restore_input_source: Option<String>,
+    // 入力ソースの切り替えをステータスへ表示するか
+    pub(crate) input_source_notify: bool,
     pub(crate) search_query: String,
     pub(crate) search_results: Vec<SearchHit>,
+    pub(crate) search_next_cursor: Option<SearchCursor>,
+    pub(crate) search_loading_more: bool,
     pub(crate) search_error: Option<String>,
+    pub(crate) search_history: Vec<String>,
+    pub(crate) search_history_hovered: bool,
+    pub(crate) file_selection: Option<FileSelection>,
+    pub(crate) scroll_to_selection: bool,
+    pub(crate) tag_editor: Option<TagEditorState>,
+    // 作成中のプロキシと、インデックスに記録した元の動画からプロキシへの対応
+    pub(crate) proxy_batch: Option<ProxyBatch>,
+    proxy_links: HashMap<PathBuf, PathBuf>,
+    // 音声を外したコピーを作成中の場合の状態
+    pub(crate) mute_batch: Option<MuteBatch>,
+    // クラウドにだけあるファイルを取得中の場合の状態と、ドラッグしようとした時に取得を始めるか
+    pub(crate) cloud_fetch: Option<CloudFetch>,
+    pub(crate) cloud_fetch_on_drag: bool,
+    // 変換中に検出した黒帯の切り取り確認
+    pub(crate) crop_prompt: Option<CropPromptState>,
+    // ダウンロード前の音声トラックの選択
+    pub(crate) audio_track_prompt: Option<AudioTrackPromptState>,
+    pub(crate) format_prompt: Option<FormatPromptState>,
+    // タイムスタンプの一覧で分割してダウンロードする指定の入力
+    pub(crate) chapter_list: Option<ChapterListState>,
+    // ダウンロード前の動画情報の確認
+    pub(crate) preview_prompt: Option<PreviewPromptState>,
+    // 保存先に同名のファイルがある場合の扱いの確認
+    pub(crate) collision_prompt: Option<CollisionPrompt>,
+    // 表示中の黒帯・同名ファイルの確認を出したダウンロードの番号
+    prompt_job_id: Option<u64>,
+    // 別の確認を表示中のため、閉じられるまで待たせている確認
+    deferred_prompts: VecDeque<(Option<u64>, DownloadEvent)>,
+    // 直前のダウンロードがダウンロード済みでスキップされた場合の表示
+    pub(crate) already_downloaded: Option<AlreadyDownloaded>,
+    // 実行中のダウンロードで yt-dlp が出した、設定で対処できるエラー
+    detected_error: Option<KnownError>,
+    // 対処できる理由で失敗した場合に、対処の操作を案内する
+    pub(crate) error_dialog: Option<KnownError>,
+    // すべてキャンセルの確認待ち。値はキャンセルされる件数。
+    pub(crate) cancel_all_prompt: Option<usize>,
+    // 前回の起動で異常終了したダウンロード。再開するかを確認する。
+    pub(crate) interrupted_job: Option<JobRecord>,
     pub(crate) search_engine: Option<SearchEngine>,
     pub(crate) search_roots_sync_error: Option<String>,
+    // 実行中の検索インデックスのバックアップ。手動で始めた場合は終わったことを通知で知らせる。
+    index_backup: Option<(mpsc::Receiver<Result<PathBuf, String>>, bool)>,
+    next_index_backup_check: Instant,
+    osc: Option<OscBridge>,
+    event_stream: Option<EventStreamServer>,
+    watch_folder: Option<WatchFolder>,
+    // 購読したチャンネル・プレイリストの確認と、見つけた動画を待機キューへ追加するか
+    subscriptions: Option<SubscriptionPoller>,
+    subscription_auto_queue: bool,
+    published_script_status: String,
+    published_dock_state: Option<(Option<i32>, usize)>,
+    published_status_item: Option<(Option<i32>, usize, bool)>,
+    published_recent_downloads: Vec<PathBuf>,
+    notifications_enabled: bool,
     search_job_tx: Option<mpsc::Sender<SearchJob>>,
     search_result_rx: Option<mpsc::Receiver<SearchJobResult>>,
     search_request_seq: u64,
@@ -97,6 +368,7 @@ impl DownloaderApp {
     fn new(cc: &eframe::CreationContext<'_>) -> Self {
         apply_theme(&cc.egui_ctx);
         let settings = SettingsData::load();
+        i18n::set_language(settings.language);
         let window_width = settings.window_width.parse::<f32>().unwrap_or(860.0);
         let download_dir = PathBuf::from(settings.download_dir.trim());
         let download_panel_width = settings
@@ -107,20 +379,32 @@ impl DownloaderApp {
             .search_panel_width
             .parse::<f32>()
             .unwrap_or(window_width * 0.5);
+        let search_window_size = egui::vec2(
+            settings.search_window_width.parse::<f32>().unwrap_or(380.0),
+            settings
+                .search_window_height
+                .parse::<f32>()
+                .unwrap_or(1000.0),
+        );
+        network::configure(settings.check_connection, &settings.yt_dlp_extra_args);
+        search_index::set_read_finder_tags(settings.search_read_finder_tags);
+        search_index::set_skip_rules(settings.index_skip_rules());
+        // 起動時の全走査より前に、バッテリー駆動中かを確かめておく。
+        search_index::set_low_power(settings.battery_saver && power::check_on_battery());
         let search_engine = SearchEngine::new(search_index_db_path()).ok();
         let mut search_roots_sync_error = None;
 
         if let Some(engine) = search_engine.as_ref() {
-            let root_paths = settings
-                .search_roots
-                .iter()
-                .map(PathBuf::from)
-                .collect::<Vec<_>>();
-            if let Err(err) = engine.sync_roots(&root_paths) {
+            let root_paths = index_roots(&settings.search_roots, &download_dir);
+            if let Err(err) = engine.sync_roots(&root_paths, &link_following_roots(&settings)) {
                 search_roots_sync_error = Some(err);
             }
-            let _ = engine.reindex_all_async();
+            let _ = engine.reindex_all_when_powered();
         }
+        let proxy_links = search_engine
+            .as_ref()
+            .and_then(|engine| engine.proxy_links().ok())
+            .unwrap_or_default();
 
         let (search_job_tx, search_result_rx) = if let Some(engine) = search_engine.clone() {
             let (job_tx, job_rx) = mpsc::channel::<SearchJob>();
@@ -134,29 +418,108 @@ impl DownloaderApp {
         let mut app = Self {
             download_dir,
             downloaded_files: Vec::new(),
+            downloads_filter: String::new(),
+            downloads_session_only: false,
+            downloads_sort: None,
+            search_sort: None,
+            search_order: Vec::new(),
+            search_order_sort: None,
+            downloads_order: Vec::new(),
+            downloads_order_key: None,
+            collapsed_date_groups: HashSet::new(),
+            session_downloads: HashSet::new(),
+            session_started: SystemTime::now(),
             download_in_progress: false,
-            progress_message: "待機中...".to_string(),
-            progress_value: 0.0,
+            download_queue: JobQueue::default(),
+            failed_jobs: FailedJobs::default(),
+            quality_mode: QualityMode::default(),
+            dry_run: false,
+            dry_run_report: None,
+            download_dir_override: None,
+            recent_download_dirs: settings.recent_download_dirs.clone(),
+            shortcuts: Shortcuts::default(),
+            profile_names: Vec::new(),
+            active_profile: String::new(),
+            cookie_account_names: Vec::new(),
+            presets: Vec::new(),
+            active_cookie_account: String::new(),
+            queue_paused: false,
+            offline: false,
+            battery_saver: settings.battery_saver,
+            low_power: false,
+            current_download: None,
+            current_downloaded: false,
+            conversions: Vec::new(),
+            progress_message: tr(Msg::Waiting).to_string(),
+            progress_value: Some(0.0),
             progress_visible: false,
+            paste_feedback: None,
             download_active_flag: Arc::new(AtomicBool::new(false)),
-            cancel_flag: None,
-            process_tracker: None,
+            cancel_token: None,
             rx: None,
             last_scan: Instant::now() - Duration::from_secs(5),
+            downloads_index_seq: None,
             refresh_needed: true,
             settings_ui: settings_ui::SettingsUiState::new(),
             log_ui: LogUiState::new(),
+            toasts: ToastState::default(),
             status_logs: AppLogger::new(),
+            download_count: 0,
+            current_job_id: None,
             pending_window_resize: None,
             did_snap: false,
             current_window_size: None,
             download_panel_width,
             search_panel_width,
+            search_detached: settings.search_window_detached,
+            search_window_size,
+            pending_search_focus: false,
+            search_input_mode: settings.search_input_mode,
+            restore_input_source: None,
+            input_source_notify: settings.input_source_notify,
             search_query: String::new(),
             search_results: Vec::new(),
+            search_next_cursor: None,
+            search_loading_more: false,
             search_error: None,
+            search_history: settings.search_history.clone(),
+            search_history_hovered: false,
+            file_selection: None,
+            scroll_to_selection: false,
+            tag_editor: None,
+            proxy_batch: None,
+            proxy_links,
+            mute_batch: None,
+            cloud_fetch: None,
+            cloud_fetch_on_drag: settings.cloud_fetch_on_drag,
+            crop_prompt: None,
+            audio_track_prompt: None,
+            format_prompt: None,
+            chapter_list: None,
+            preview_prompt: None,
+            collision_prompt: None,
+            prompt_job_id: None,
+            deferred_prompts: VecDeque::new(),
+            already_downloaded: None,
+            detected_error: None,
+            error_dialog: None,
+            cancel_all_prompt: None,
+            interrupted_job: load_interrupted_job(),
             search_engine,
             search_roots_sync_error,
+            osc: None,
+            event_stream: None,
+            watch_folder: None,
+            subscriptions: None,
+            subscription_auto_queue: settings.subscription_auto_queue,
+            index_backup: None,
+            // 起動直後の読み込みと重ならないよう、最初の確認は少し待つ。
+            next_index_backup_check: Instant::now() + Duration::from_secs(60),
+            published_script_status: String::new(),
+            published_dock_state: None,
+            published_status_item: None,
+            published_recent_downloads: Vec::new(),
+            notifications_enabled: false,
             search_job_tx,
             search_result_rx,
             search_request_seq: 0,
@@ -167,30 +530,229 @@ impl DownloaderApp {
             cursor_resync_until: None,
         };
 
+        app.status_logs.write_to_dir(&log_dir());
         mac_menu::install_settings_menu();
+        app.apply_shortcuts(&settings.shortcuts);
         mac_window::apply_app_icon_from_icns();
+        app.register_summon_hotkey(&cc.egui_ctx, &settings.hotkey_summon);
+        mac_apple_event::attach_repaint_context(&cc.egui_ctx);
+        app.restart_osc(&cc.egui_ctx, &settings);
+        app.restart_event_stream(&settings);
+        app.restart_watch_folder(&cc.egui_ctx, &settings);
+        app.restart_subscriptions(&cc.egui_ctx, &settings);
+        app.sync_profiles(&settings);
+        app.sync_presets(&settings);
+        app.sync_cookie_accounts(&settings);
+        app.apply_notification_setting(&cc.egui_ctx, settings.notifications_enabled);
+        app.apply_status_item_setting(&cc.egui_ctx, settings.status_item_enabled);
+        if let Some(engine) = app.search_engine.clone() {
+            mac_apple_event::set_script_search(Box::new(move |query, limit| {
+                let request = search_request_for_query(query, limit, None);
+                engine
+                    .search(&request)
+                    .map(|page| page.hits.into_iter().map(|hit| hit.path).collect())
+            }));
+        }
 
         if let Err(err) = ensure_bundled_tools() {
-            app.push_status(format!("同梱ツールの配置に失敗しました: {err}"));
+            app.push_error(tr_fmt(Msg::BundledToolsFailed, &[&err]));
         }
+        app.remove_stale_staging_dirs(&settings);
+        remove_stale_login_files();
 
+        network::start_connectivity_monitor(&cc.egui_ctx);
+        power::start_power_monitor(&cc.egui_ctx);
         thread::spawn(|| {
+            // オフライン時は自動取得を試みない（接続確認の結果はUIにも反映される）。
+            if !network::check_online() {
+                return;
+            }
             let _ = ensure_yt_dlp(None);
-            let _ = ensure_deno(None);
+            // Deno 以外のランタイムを選んでいる場合は取得しない。
+            if load_js_runtime() == JsRuntime::Deno {
+                let _ = ensure_deno(None);
+            }
         });
 
         if app.search_engine.is_none() {
-            app.search_error = Some("検索エンジンの初期化に失敗しました。".to_string());
+            app.search_error = Some(tr(Msg::SearchInitFailed).to_string());
         }
         if let Some(err) = app.search_roots_sync_error.clone() {
-            app.search_error = Some(format!("検索対象フォルダの同期に失敗しました: {err}"));
+            app.search_error = Some(tr_fmt(Msg::SearchSyncFailed, &[&err]));
+        }
+        if let Some(err) = app.search_error.clone() {
+            app.push_toast(ToastKind::Error, err);
         }
 
         app
     }
 
+    // 呼び出しショートカットを登録し直す。失敗時はステータスに表示する。
+    pub(crate) fn register_summon_hotkey(&mut self, ctx: &egui::Context, raw: &str) {
+        if let Err(err) = mac_hotkey::register_summon_hotkey(raw, ctx) {
+            self.push_error(tr_fmt(Msg::HotkeyRegisterFailed, &[&err]));
+        }
+    }
+
+    // メイン画面のショートカットを設定に合わせ、アプリメニューのキー表示も揃える。
+    pub(crate) fn apply_shortcuts(&mut self, bindings: &ShortcutBindings) {
+        self.shortcuts = bindings.shortcuts();
+        mac_menu::set_menu_shortcuts(
+            self.shortcuts.get(ShortcutAction::OpenSettings),
+            self.shortcuts.get(ShortcutAction::OpenLogs),
+        );
+    }
+
+    // OSC の受信を設定に合わせて開始し直す。無効なら停止する。
+    pub(crate) fn restart_osc(&mut self, ctx: &egui::Context, settings: &SettingsData) {
+        // 同じポートを開き直せるよう、先に古い受信を止める。
+        self.osc = None;
+        if !settings.osc_enabled {
+            return;
+        }
+        let result = osc::parse_listen_port(&settings.osc_port).and_then(|port| {
+            let feedback = osc::parse_feedback_target(&settings.osc_feedback)?;
+            OscBridge::start(port, settings.osc_allow_lan, feedback, ctx.clone())
+        });
+        match result {
+            Ok(bridge) => self.osc = Some(bridge),
+            Err(err) => self.push_error(tr_fmt(Msg::OscStartFailed, &[&err])),
+        }
+    }
+
+    // WebSocket 配信を設定に合わせて開始し直す。無効なら停止する。
+    pub(crate) fn restart_event_stream(&mut self, settings: &SettingsData) {
+        // 同じポートを開き直せるよう、先に古い配信を止める。
+        self.event_stream = None;
+        if !settings.event_stream_enabled {
+            return;
+        }
+        let result =
+            event_stream::parse_event_stream_port(&settings.event_stream_port).and_then(|port| {
+                EventStreamServer::start(
+                    port,
+                    event_stream::parse_allowed_origins(&settings.event_stream_origins),
+                )
+            });
+        match result {
+            Ok(server) => {
+                server.publish(StreamEvent::Queue {
+                    queued: self.download_queue.len(),
+                });
+                self.event_stream = Some(server);
+            }
+            Err(err) => self.push_error(tr_fmt(Msg::EventStreamStartFailed, &[&err])),
+        }
+    }
+
+    // 監視フォルダを設定に合わせて開始し直す。フォルダが変わらない場合は監視を続ける。
+    pub(crate) fn restart_watch_folder(&mut self, ctx: &egui::Context, settings: &SettingsData) {
+        let dir = settings.watch_folder_dir.trim();
+        if !settings.watch_folder_enabled || dir.is_empty() {
+            self.watch_folder = None;
+            return;
+        }
+        let dir = PathBuf::from(dir);
+        if self
+            .watch_folder
+            .as_ref()
+            .is_some_and(|watch| watch.dir() == dir)
+        {
+            return;
+        }
+        self.watch_folder = None;
+        match WatchFolder::start(dir, ctx.clone()) {
+            Ok(watch) => self.watch_folder = Some(watch),
+            Err(err) => self.push_error(err),
+        }
+    }
+
+    // 購読元か間隔が変わった場合だけ確認をやり直す。
+    pub(crate) fn restart_subscriptions(&mut self, ctx: &egui::Context, settings: &SettingsData) {
+        self.subscription_auto_queue = settings.subscription_auto_queue;
+        let sources = &settings.subscription_sources;
+        if !settings.subscriptions_enabled || sources.is_empty() {
+            self.subscriptions = None;
+            return;
+        }
+        let interval = Duration::from_secs(u64::from(settings.subscription_interval_minutes) * 60);
+        if self
+            .subscriptions
+            .as_ref()
+            .is_some_and(|poller| poller.matches(sources, interval))
+        {
+            return;
+        }
+        self.subscriptions = None;
+        self.subscriptions = Some(SubscriptionPoller::start(
+            sources.clone(),
+            interval,
+            ctx.clone(),
+        ));
+    }
+
+    // 待ち件数の変化を OSC と WebSocket へ通知する。
+    fn publish_queue_length(&self) {
+        let queued = self.download_queue.len();
+        self.send_osc_feedback("/vjd/queue", &[OscArg::Int(queued as i64)]);
+        if let Some(server) = self.event_stream.as_ref() {
+            server.publish(StreamEvent::Queue { queued });
+        }
+    }
+
+    fn send_osc_feedback(&self, address: &str, args: &[OscArg]) {
+        if let Some(bridge) = self.osc.as_ref() {
+            bridge.send_feedback(address, args);
+        }
+    }
+
     pub(crate) fn push_status(&mut self, message: impl Into<String>) {
-        self.status_logs.push(message);
+        self.status_logs.push(LogLevel::Info, message);
+    }
+
+    // 処理は続けられたが、結果が期待どおりでない場合のログ。
+    pub(crate) fn push_warning(&mut self, message: impl Into<String>) {
+        self.status_logs.push(LogLevel::Warn, message);
+    }
+
+    pub(crate) fn push_error(&mut self, message: impl Into<String>) {
+        self.status_logs.push(LogLevel::Error, message);
+    }
+
+    // 画面右下に通知を出す。ログにも残す場合は push_status と併用する。
+    pub(crate) fn push_toast(&mut self, kind: ToastKind, message: impl Into<String>) {
+        self.toasts.push(kind, message);
+    }
+
+    // 実行中のダウンロードの番号を付けてログへ出す。
+    fn push_job_log(&mut self, level: LogLevel, message: impl Into<String>) {
+        self.push_log_for(self.current_job_id, level, message);
+    }
+
+    fn push_log_for(&mut self, job_id: Option<u64>, level: LogLevel, message: impl Into<String>) {
+        self.status_logs
+            .push_record(level, LogSource::App, job_id, message);
+    }
+
+    // ログフォルダを Finder で開く。まだ無い場合は作ってから開く。
+    fn open_log_folder(&mut self) {
+        let dir = log_dir();
+        if let Err(err) = ensure_dir(&dir).and_then(|_| open_with_default_app(&dir)) {
+            self.push_error(tr_fmt(Msg::OpenLogFolderFailed, &[&err]));
+        }
+    }
+
+    // バグレポートの ZIP を書き出す。保存先は保存パネルで選ぶ（既定はデスクトップ）。
+    pub(crate) fn export_diagnostic_bundle(&mut self) {
+        let desktop = dirs::desktop_dir();
+        let Some(dest) = choose_save_path(DEFAULT_BUG_REPORT_NAME, desktop.as_deref()) else {
+            return;
+        };
+        let recent_log = self.build_recent_log_snapshot(BUG_REPORT_LOG_WINDOW);
+        match diagnostics::export_bundle(&dest, &recent_log) {
+            Ok(()) => self.push_status(tr_fmt(Msg::BugReportExported, &[&dest.to_string_lossy()])),
+            Err(err) => self.push_error(tr_fmt(Msg::BugReportExportFailed, &[&err])),
+        }
     }
 
     pub(crate) fn clear_logs(&mut self) {
@@ -203,256 +765,2031 @@ impl DownloaderApp {
 
     pub(crate) fn start_download_from_clipboard(&mut self) {
         let Some(url) = read_clipboard_text() else {
+            self.push_toast(ToastKind::Error, tr(Msg::ClipboardEmpty));
             return;
         };
+        self.enqueue_download(url);
+    }
 
-        if !self.is_tools_ready() {
-            self.push_status(
-                "初回セットアップが必要です。設定から自動セットアップを行ってください。"
-                    .to_string(),
-            );
-            self.settings_ui.open_initial_setup();
+    // クリップボードの URL を、プリセットの画質方式・エンコード方式・高さの上限でダウンロードする。
+    pub(crate) fn start_download_with_preset(&mut self, index: usize) {
+        let Some(preset) = self.presets.get(index).cloned() else {
             return;
-        }
-
-        let output_dir = self.download_dir.clone();
-        let cookie_args = load_cookie_args();
-        let (tx, rx) = mpsc::channel();
-        self.rx = Some(rx);
-        self.download_in_progress = true;
-        self.download_active_flag.store(true, Ordering::Relaxed);
-        let cancel_flag = Arc::new(AtomicBool::new(false));
-        let tracker = ProcessTracker::new();
-        self.cancel_flag = Some(cancel_flag.clone());
-        self.process_tracker = Some(tracker.clone());
-
-        self.push_status(format!("Downloading to {}", output_dir.to_string_lossy()));
-
-        let active_flag = self.download_active_flag.clone();
-        thread::spawn(move || {
-            run_download(
-                url,
-                output_dir,
-                cookie_args,
-                tx,
-                active_flag,
-                cancel_flag,
-                tracker,
-            )
-        });
+        };
+        let Some(url) = read_clipboard_text() else {
+            self.push_toast(ToastKind::Error, tr(Msg::ClipboardEmpty));
+            return;
+        };
+        let mut job = self.new_download_job(url, false);
+        job.quality = preset.quality;
+        job.preset = Some(preset);
+        self.enqueue_job(job);
     }
 
-    pub(crate) fn request_cancel_download(&mut self) {
-        if let Some(flag) = self.cancel_flag.as_ref() {
-            flag.store(true, Ordering::Relaxed);
+    // Cmd+V で貼り付けた URL をキューへ追加し、結果を進捗パネルに表示する。
+    pub(crate) fn enqueue_pasted_url(&mut self, text: &str) {
+        let text = text.trim();
+        let is_url = Url::parse(text).is_ok_and(|url| matches!(url.scheme(), "http" | "https"));
+        if !is_url {
+            self.paste_feedback = Some(PasteFeedback {
+                message: tr(Msg::PasteNotUrl).to_string(),
+                accepted: false,
+                shown_at: Instant::now(),
+            });
+            return;
         }
-        if let Some(tracker) = self.process_tracker.as_ref() {
-            tracker.terminate_all();
+        // ツールの準備ができていない場合は、セットアップ画面を開くだけになる。
+        let ready = self.is_tools_ready();
+        self.enqueue_download(text.to_string());
+        if ready {
+            self.paste_feedback = Some(PasteFeedback {
+                message: tr_fmt(Msg::PastedUrlAdded, &[&text]),
+                accepted: true,
+                shown_at: Instant::now(),
+            });
         }
-        self.progress_message = "キャンセル中...".to_string();
-        self.progress_value = -1.0;
-        self.progress_visible = true;
     }
 
-    pub(crate) fn delete_download(&mut self, path: &Path) {
-        match delete_download_file(path) {
-            Ok(()) => {
-                self.refresh_needed = true;
-            }
-            Err(err) => self.push_status(format!("削除に失敗しました: {err}")),
-        }
+    // クリップボードの URL で取得できる形式を一覧し、選んでからダウンロードする。
+    pub(crate) fn start_download_choosing_format(&mut self) {
+        let Some(url) = read_clipboard_text() else {
+            self.push_toast(ToastKind::Error, tr(Msg::ClipboardEmpty));
+            return;
+        };
+        let job = self.new_download_job(url, true);
+        self.enqueue_job(job);
     }
 
-    pub(crate) fn start_native_drag(&mut self, frame: &eframe::Frame, path: &Path) {
-        let path = match path.canonicalize() {
-            Ok(path) => path,
-            Err(err) => {
-                self.push_status(format!("ドラッグ対象の取得に失敗しました: {err}"));
-                return;
-            }
-        };
+    // URL とタイムスタンプの一覧を入力するウィンドウを開く。
+    pub(crate) fn open_chapter_list(&mut self) {
+        self.chapter_list = Some(ChapterListState::from_clipboard());
+    }
 
-        let icon_path = match drag_fallback_preview_icon_path() {
-            Some(path) => path,
-            None => {
-                self.push_status("ドラッグ用フォールバックアイコンが見つかりません。".to_string());
-                return;
-            }
-        };
+    // 保存後に貼り付けた一覧のチャプターごとに分割する指定で、URL をダウンロードする。
+    pub(crate) fn start_download_split_by_timestamps(&mut self, url: String, timestamps: String) {
+        let mut job = self.new_download_job(url, false);
+        job.split_timestamps = Some(timestamps);
+        self.enqueue_job(job);
+    }
 
-        if let Err(err) = drag::start_drag(
-            frame,
-            DragItem::Files(vec![path]),
-            Image::File(icon_path),
-            |_result, _position| {},
-            Options::default(),
-        ) {
-            self.push_status(format!("ドラッグ開始に失敗しました: {err}"));
+    pub(crate) fn enqueue_download(&mut self, url: String) {
+        let job = self.new_download_job(url, false);
+        self.enqueue_job(job);
+    }
+
+    // 画質・保存先・アカウントは追加した時点の選択を使う。
+    fn new_download_job(&self, url: String, choose_format: bool) -> DownloadJob {
+        DownloadJob {
+            url,
+            quality: self.quality_mode,
+            output_dir: self.download_dir_override.clone(),
+            cookie_account: self.active_cookie_account.clone(),
+            choose_format,
+            resume_staging_dir: None,
+            priority: false,
+            preset: None,
+            source_file: None,
+            split_timestamps: None,
+            stall_retries: 0,
         }
     }
 
-    pub(crate) fn mark_search_dirty(&mut self) {
-        self.search_dirty = true;
+    // 監視フォルダの動画を、設定の保存先へ変換して移すジョブにする。
+    fn new_import_job(&self, path: PathBuf) -> DownloadJob {
+        let mut job = self.new_download_job(path.to_string_lossy().to_string(), false);
+        job.output_dir = None;
+        job.source_file = Some(path);
+        job
     }
 
-    pub(crate) fn sync_search_roots(&mut self, roots: &[String]) -> Result<(), String> {
-        let Some(engine) = self.search_engine.as_ref() else {
-            return Err(
-                "検索エンジンが初期化されていません。アプリを再起動してください。".to_string(),
-            );
+    // 異常終了したダウンロードを、作業フォルダから再開するか最初からやり直す。
+    pub(crate) fn recover_interrupted_job(&mut self, job: JobRecord, resume: bool) {
+        clear_job_record();
+        let resume_staging_dir = if resume && job.can_resume() {
+            job.staging_dir
+        } else {
+            job.discard_staging_dir();
+            None
         };
-        let paths = roots.iter().map(PathBuf::from).collect::<Vec<_>>();
-        engine.sync_roots(&paths)?;
-        self.search_roots_sync_error = None;
-        self.search_dirty = true;
-        Ok(())
+        self.enqueue_job(DownloadJob {
+            url: job.url,
+            quality: job.quality,
+            output_dir: Some(job.output_dir),
+            cookie_account: self.active_cookie_account.clone(),
+            choose_format: false,
+            resume_staging_dir,
+            priority: false,
+            preset: None,
+            source_file: None,
+            split_timestamps: None,
+            stall_retries: 0,
+        });
     }
 
-    pub(crate) fn request_reindex_all(&mut self) -> Result<(), String> {
-        let Some(engine) = self.search_engine.as_ref() else {
-            return Err("検索エンジンが初期化されていません。".to_string());
-        };
-        engine.reindex_all_async()?;
-        self.search_dirty = true;
-        Ok(())
+    // 異常終了したダウンロードを再開せず、作業フォルダも削除する。
+    pub(crate) fn discard_interrupted_job(&mut self, job: JobRecord) {
+        clear_job_record();
+        job.discard_staging_dir();
+        self.push_warning(tr_fmt(Msg::InterruptedJobDiscarded, &[&job.url]));
     }
 
-    fn poll_download_events(&mut self) {
-        let mut events = Vec::new();
-        if let Some(rx) = self.rx.as_ref() {
-            while let Ok(event) = rx.try_recv() {
-                events.push(event);
-            }
+    // ダウンロード中なら待機キューへ追加し、そうでなければすぐに開始する。
+    fn enqueue_job(&mut self, job: DownloadJob) {
+        // ドライランはツールが無くても、組み立てたコマンドを確かめられるようにする。
+        if self.dry_run {
+            self.show_dry_run(&job);
+            return;
         }
-
-        let mut done = None;
-        for event in events {
-            match event {
-                DownloadEvent::Log(line) => self.push_status(line),
-                DownloadEvent::Progress(update) => self.handle_progress_update(update),
-                DownloadEvent::Done(result, elapsed) => done = Some((result, elapsed)),
-            }
+        if !self.is_tools_ready() {
+            self.push_warning(tr(Msg::SetupRequired).to_string());
+            self.settings_ui.open_initial_setup();
+            return;
         }
 
-        if let Some((result, elapsed)) = done {
-            match result {
-                Ok(()) => self.push_status(format!("Download completed. Total time: {elapsed}")),
-                Err(err) if err == CANCELLED_ERROR => {
-                    self.push_status("ダウンロードをキャンセルしました。".to_string())
-                }
-                Err(err) => self.push_status(format!("Download failed: {err}")),
-            }
-            self.download_in_progress = false;
-            self.download_active_flag.store(false, Ordering::Relaxed);
-            self.rx = None;
-            self.cancel_flag = None;
-            self.process_tracker = None;
-            self.refresh_needed = true;
+        if self.download_in_progress || self.queue_paused || self.offline {
+            let url = job.url.clone();
+            self.download_queue.push_back(job);
+            let msg = if self.offline && !self.download_in_progress {
+                Msg::QueuedOffline
+            } else {
+                Msg::Queued
+            };
+            self.push_status(tr_fmt(msg, &[&self.download_queue.len(), &url]));
+            self.publish_queue_length();
+            return;
         }
+        self.start_download(job);
     }
 
-    fn refresh_downloads_if_needed(&mut self) {
-        if self.refresh_needed || self.last_scan.elapsed() >= Duration::from_secs(2) {
-            self.downloaded_files = load_mp4_files(&self.download_dir);
-            self.last_scan = Instant::now();
-            self.refresh_needed = false;
+    fn start_download(&mut self, job: DownloadJob) {
+        self.current_download = Some(job.clone());
+        self.current_downloaded = false;
+        self.already_downloaded = None;
+        self.detected_error = None;
+        self.download_count += 1;
+        self.current_job_id = Some(self.download_count);
+        self.status_logs.begin_job(self.download_count, &job.url);
+        let output_dir = job
+            .output_dir
+            .clone()
+            .unwrap_or_else(|| self.download_dir.clone());
+        self.remember_download_dir(&output_dir);
+        let cancel = CancellationToken::new();
+        let auth_args = self.load_auth_args(&job, Some(&cancel));
+        let (tx, rx) = mpsc::channel();
+        self.rx = Some(rx);
+        self.download_in_progress = true;
+        self.download_active_flag.store(true, Ordering::Relaxed);
+        self.cancel_token = Some(cancel.clone());
+
+        self.push_job_log(
+            LogLevel::Info,
+            format!("Downloading to {}", output_dir.to_string_lossy()),
+        );
+
+        let active_flag = self.download_active_flag.clone();
+        thread::spawn(move || run_download(job, output_dir, auth_args, tx, active_flag, cancel));
+    }
+
+    // クッキーとサイトのログインの引数。パスワードはここで初めてキーチェーンから読み出し、
+    // ダウンロードを終えたら消す netrc ファイルへ書く。試すだけの場合（cancel が無い）はファイルを作らない。
+    fn load_auth_args(
+        &mut self,
+        job: &DownloadJob,
+        cancel: Option<&CancellationToken>,
+    ) -> Vec<String> {
+        let mut auth_args = load_cookie_args(&job.cookie_account);
+        let login_args = load_site_login(&job.url).and_then(|login| match (login, cancel) {
+            (Some(login), Some(cancel)) => netrc_login_args(&login, cancel),
+            (Some(_), None) => Ok(dry_run_login_args()),
+            (None, _) => Ok(Vec::new()),
+        });
+        match login_args {
+            Ok(args) => auth_args.extend(args),
+            Err(err) => self.push_warning(tr_fmt(Msg::LoginSkipped, &[&err])),
         }
+        auth_args
     }
 
-    fn handle_progress_update(&mut self, update: ProgressUpdate) {
-        if update.visible {
-            self.progress_message = update.message;
-            self.progress_value = update.progress;
-            self.progress_visible = true;
-        } else {
-            self.progress_message = "待機中...".to_string();
-            self.progress_value = 0.0;
-            self.progress_visible = false;
+    // ダウンロードを始めずに、実行されるはずのコマンドを組み立てて表示する。
+    fn show_dry_run(&mut self, job: &DownloadJob) {
+        let output_dir = job
+            .output_dir
+            .clone()
+            .unwrap_or_else(|| self.download_dir.clone());
+        let auth_args = self.load_auth_args(job, None);
+        self.push_status(tr_fmt(Msg::DryRunLogged, &[&job.url]));
+        self.dry_run_report = Some(build_dry_run(job, &output_dir, &auth_args));
+    }
+
+    pub(crate) fn request_cancel_download(&mut self) {
+        if let Some(cancel) = self.cancel_token.as_ref() {
+            cancel.cancel();
         }
+        self.progress_message = tr(Msg::Cancelling).to_string();
+        self.progress_value = None;
+        self.progress_visible = true;
     }
 
-    fn is_yt_dlp_ready(&self) -> bool {
-        let path = yt_dlp_path();
-        path.exists() && is_executable(&path)
+    // 実行中・変換中・待機中のダウンロードの件数
+    pub(crate) fn active_job_count(&self) -> usize {
+        self.download_queue.len() + usize::from(self.download_in_progress) + self.conversions.len()
     }
 
-    fn is_tools_ready(&self) -> bool {
-        self.is_yt_dlp_ready()
+    // 裏で変換中の項目を止める。止めた項目は一時停止中でもキューへ戻さない。
+    pub(crate) fn cancel_conversion(&mut self, index: usize) {
+        if let Some(conversion) = self.conversions.get_mut(index) {
+            conversion.job = None;
+            conversion.cancel.cancel();
+            conversion.message = tr(Msg::Cancelling).to_string();
+            conversion.progress = None;
+        }
     }
 
-    fn poll_input_mode_change(&mut self) {
-        let Some(mode) = current_mode() else {
+    // 件数が多い場合は確認を挟んでから、すべてのダウンロードをキャンセルする。
+    pub(crate) fn request_cancel_all(&mut self) {
+        let count = self.active_job_count();
+        if count > CANCEL_ALL_CONFIRM_THRESHOLD {
+            self.cancel_all_prompt = Some(count);
             return;
-        };
+        }
+        self.cancel_all_downloads();
+    }
 
-        if self.last_input_mode.is_none() {
-            self.last_input_mode = Some(mode);
+    // 待機キューを空にし、実行中のダウンロードを止める。
+    pub(crate) fn cancel_all_downloads(&mut self) {
+        self.cancel_all_prompt = None;
+        let count = self.active_job_count();
+        if count == 0 {
             return;
         }
+        self.download_queue.clear();
+        self.publish_queue_length();
+        if self.download_in_progress {
+            // 一時停止中やオフラインでも、止めた項目をキューへ戻さない。
+            self.current_download = None;
+            self.request_cancel_download();
+        }
+        for index in 0..self.conversions.len() {
+            self.cancel_conversion(index);
+        }
+        self.push_status(tr_fmt(Msg::CancelledAll, &[&count]));
+    }
 
-        if self.last_input_mode.as_ref() == Some(&mode) {
+    // 失敗したダウンロードを、追加した時と同じ画質・保存先・アカウントでやり直す。
+    pub(crate) fn retry_failed_job(&mut self, index: usize) {
+        let Some(failed) = self.failed_jobs.take(index) else {
             return;
-        }
+        };
+        let mut job = failed.job;
+        // 作業フォルダは失敗時に片付けているため、最初からやり直す。
+        job.resume_staging_dir = None;
+        job.priority = false;
+        job.stall_retries = 0;
+        self.enqueue_job(job);
+    }
 
-        self.last_input_mode = Some(mode.clone());
-        match mode {
-            InputMode::Japanese => self.push_status("日本語になりました".to_string()),
-            InputMode::English => self.push_status("英字になりました".to_string()),
-            InputMode::Other(name) => {
-                self.push_status(format!("入力ソースが変更されました: {name}"))
-            }
-        }
+    // 待機中の項目をドラッグで並べ替える。
+    pub(crate) fn move_queued_job(&mut self, from: usize, to: usize) {
+        self.download_queue.move_job(from, to);
     }
 
-    fn submit_search_if_needed(&mut self) {
-        if !self.search_dirty {
+    // 待機中の項目を「次に実行」にする、または元に戻す。
+    pub(crate) fn set_queued_job_priority(&mut self, index: usize, priority: bool) {
+        let Some(url) = self.download_queue.get(index).map(|job| job.url.clone()) else {
             return;
+        };
+        self.download_queue.set_priority(index, priority);
+        if priority {
+            self.push_status(tr_fmt(Msg::QueueMarkedNextUp, &[&url]));
         }
+    }
 
-        if self.search_query.trim().is_empty() {
-            self.search_results.clear();
-            let has_persistent_search_error =
-                self.search_engine.is_none() || self.search_roots_sync_error.is_some();
-            if !has_persistent_search_error {
-                self.search_error = None;
+    // 待機キューの一時停止と再開を切り替える。一時停止時は実行中のダウンロードを止め、キューの先頭へ戻す。
+    pub(crate) fn toggle_pause_all(&mut self) {
+        if self.queue_paused {
+            self.queue_paused = false;
+            self.push_status(tr(Msg::QueueResumed).to_string());
+            if !self.download_in_progress
+                && !self.offline
+                && let Some(next) = self.download_queue.pop_next()
+            {
+                self.publish_queue_length();
+                self.start_download(next);
             }
-            self.search_dirty = false;
             return;
         }
+        self.queue_paused = true;
+        self.push_status(tr(Msg::QueuePaused).to_string());
+        if self.download_in_progress {
+            self.request_cancel_download();
+        }
+    }
 
-        let Some(tx) = self.search_job_tx.as_ref() else {
-            return;
-        };
+    // 一覧の行をクリックで選択する。
+    pub(crate) fn select_file(&mut self, list: FileListKind, path: &Path) {
+        self.file_selection = Some(FileSelection {
+            list,
+            path: path.to_path_buf(),
+            paths: vec![path.to_path_buf()],
+        });
+    }
 
-        self.search_request_seq = self.search_request_seq.saturating_add(1);
-        let seq = self.search_request_seq;
-        let sort = if self.search_query.trim().is_empty() {
-            SearchSort::ModifiedDesc
-        } else {
-            SearchSort::NameAsc
-        };
-        let request = SearchRequest {
-            query: self.search_query.clone(),
-            limit: 200,
-            sort,
-            ..Default::default()
+    // Cmd+クリックで行を選択に追加・解除する。別の一覧の行なら選択し直す。
+    pub(crate) fn toggle_file_selection(&mut self, list: FileListKind, path: &Path) {
+        let Some(selection) = self
+            .file_selection
+            .as_mut()
+            .filter(|selection| selection.list == list)
+        else {
+            self.select_file(list, path);
+            return;
         };
-
-        if tx.send(SearchJob { seq, request }).is_ok() {
-            self.search_dirty = false;
+        if let Some(index) = selection.paths.iter().position(|item| item == path) {
+            selection.paths.remove(index);
+            match selection.paths.last() {
+                Some(last) if selection.path == path => selection.path = last.clone(),
+                Some(_) => {}
+                None => self.file_selection = None,
+            }
         } else {
-            self.search_error =
-                Some("検索ワーカーにリクエストを送信できませんでした。".to_string());
+            selection.paths.push(path.to_path_buf());
+            selection.path = path.to_path_buf();
         }
     }
 
-    fn poll_search_results(&mut self) {
-        let Some(rx) = self.search_result_rx.as_ref() else {
+    // Shift+クリックで起点の行からクリックした行までを選択する。
+    pub(crate) fn extend_file_selection(&mut self, list: FileListKind, path: &Path) {
+        let paths = self.file_list_paths(list);
+        let range = self
+            .file_selection
+            .as_ref()
+            .filter(|selection| selection.list == list)
+            .and_then(|selection| selection_range(&paths, &selection.path, path));
+        let (Some(range), Some(selection)) = (range, self.file_selection.as_mut()) else {
+            self.select_file(list, path);
             return;
         };
+        selection.paths = range;
+    }
 
-        let mut latest_result = None;
-        while let Ok(result) = rx.try_recv() {
+    // 一覧の行で受けた操作を適用する。
+    pub(crate) fn apply_row_action(&mut self, list: FileListKind, action: PendingRowAction) {
+        match action {
+            PendingRowAction::Select(path) => self.select_file(list, &path),
+            PendingRowAction::Toggle(path) => self.toggle_file_selection(list, &path),
+            PendingRowAction::Extend(path) => self.extend_file_selection(list, &path),
+            PendingRowAction::Remove(path) => self.delete_download(&path),
+            PendingRowAction::ExportPlaylist => self.export_playlist(),
+            PendingRowAction::FetchCloud => self.start_cloud_fetch_for_selection(),
+            PendingRowAction::ReplaceAudio(replacement) => self.start_replace_audio(replacement),
+            PendingRowAction::ReplaceAudioWithTrack => self.start_replace_audio_with_track(),
+            PendingRowAction::GenerateProxies => self.start_proxy_generation(),
+            PendingRowAction::EditTags => self.open_tag_editor(),
+        }
+    }
+
+    fn clear_file_selection(&mut self, list: FileListKind) {
+        if self
+            .file_selection
+            .as_ref()
+            .is_some_and(|selection| selection.list == list)
+        {
+            self.file_selection = None;
+        }
+    }
+
+    pub(crate) fn is_file_selected(&self, list: FileListKind, path: &Path) -> bool {
+        self.file_selection.as_ref().is_some_and(|selection| {
+            selection.list == list && selection.paths.iter().any(|item| item == path)
+        })
+    }
+
+    // 矢印キーで選択行を移動する。未選択時は検索結果（なければダウンロード一覧）の先頭を選ぶ。
+    pub(crate) fn move_file_selection(&mut self, delta: isize) {
+        let list = match self.file_selection.as_ref() {
+            Some(selection) => selection.list,
+            None if !self.search_results.is_empty() => FileListKind::Search,
+            None => FileListKind::Downloads,
+        };
+        let paths = self.file_list_paths(list);
+        if paths.is_empty() {
+            return;
+        }
+
+        let current = self
+            .file_selection
+            .as_ref()
+            .and_then(|selection| paths.iter().position(|path| *path == selection.path));
+        let next = match current {
+            Some(index) => index.saturating_add_signed(delta).min(paths.len() - 1),
+            None => 0,
+        };
+        self.select_file(list, &paths[next]);
+        self.scroll_to_selection = true;
+    }
+
+    // 検索欄から下矢印で検索結果の先頭へ移る。
+    pub(crate) fn select_first_search_result(&mut self) -> bool {
+        let Some(hit) = self.search_results.first() else {
+            return false;
+        };
+        let path = PathBuf::from(&hit.path);
+        self.select_file(FileListKind::Search, &path);
+        self.scroll_to_selection = true;
+        true
+    }
+
+    pub(crate) fn selected_file_path(&self) -> Option<PathBuf> {
+        let selection = self.file_selection.as_ref()?;
+        self.file_list_paths(selection.list)
+            .into_iter()
+            .find(|path| *path == selection.path)
+    }
+
+    // 選択中の行を一覧の並び順で返す。
+    pub(crate) fn selected_file_paths(&self) -> Vec<PathBuf> {
+        let Some(selection) = self.file_selection.as_ref() else {
+            return Vec::new();
+        };
+        selected_in_order(self.file_list_paths(selection.list), &selection.paths)
+    }
+
+    // 選択中の行（未選択なら表示中の一覧全体）をプレイリストとして書き出す。
+    pub(crate) fn export_playlist(&mut self) {
+        let mut paths = self.selected_file_paths();
+        if paths.is_empty() {
+            let list = if self.search_results.is_empty() {
+                FileListKind::Downloads
+            } else {
+                FileListKind::Search
+            };
+            paths = self.file_list_paths(list);
+        }
+        if paths.is_empty() {
+            self.push_status(tr(Msg::NothingToExport).to_string());
+            return;
+        }
+        let Some(dest) = choose_save_path(DEFAULT_PLAYLIST_NAME, Some(&self.download_dir)) else {
+            return;
+        };
+        match write_playlist(&dest, &paths) {
+            Ok(count) => self.push_status(tr_fmt(
+                Msg::PlaylistExported,
+                &[&count, &dest.to_string_lossy()],
+            )),
+            Err(err) => self.push_error(tr_fmt(Msg::PlaylistExportFailed, &[&err])),
+        }
+    }
+
+    // 選択中の行のタグ編集を開く。先頭ファイルの現在のFinderタグを初期値にする。
+    pub(crate) fn open_tag_editor(&mut self) {
+        let paths = self.selected_file_paths();
+        let Some(first) = paths.first() else {
+            self.push_status(tr(Msg::SelectFilesToTag).to_string());
+            return;
+        };
+        let (error, tags) = match read_finder_tags(first) {
+            Ok(tags) => (None, tags),
+            Err(err) => (Some(tr_fmt(Msg::FinderTagsReadFailed, &[&err])), Vec::new()),
+        };
+        let (others, rating) = split_rating(&tags);
+        self.tag_editor = Some(TagEditorState {
+            paths,
+            tags_text: others.join(", "),
+            rating,
+            error,
+        });
+    }
+
+    // 編集したタグと評価を Finder タグへ書き出し、検索インデックスにも反映する。
+    pub(crate) fn apply_tag_editor(&mut self) {
+        let Some(editor) = self.tag_editor.as_mut() else {
+            return;
+        };
+        let names = editor.tag_names();
+        let mut failures = Vec::new();
+        for path in &editor.paths {
+            if let Err(err) = write_finder_tags(path, &names) {
+                failures.push(format!("{}: {err}", path.to_string_lossy()));
+                continue;
+            }
+            if let Some(engine) = self.search_engine.as_ref() {
+                let _ = engine.set_file_tags(path, &names);
+            }
+        }
+        if !failures.is_empty() {
+            editor.error = Some(tr_fmt(Msg::FinderTagsWriteFailed, &[&failures.join(" / ")]));
+            return;
+        }
+        let count = editor.paths.len();
+        self.tag_editor = None;
+        self.search_dirty = true;
+        self.push_status(tr_fmt(Msg::TagsApplied, &[&count]));
+    }
+
+    // 選択中の行から低解像度のプロキシを作り始める。作成中の場合は何もしない。
+    pub(crate) fn start_proxy_generation(&mut self) {
+        if self.proxy_batch.is_some() {
+            return;
+        }
+        let paths = self.selected_file_paths();
+        if paths.is_empty() {
+            self.push_status(tr(Msg::SelectFilesForProxies).to_string());
+            return;
+        }
+        // 初めて作る場合は、できたプロキシが検索できるようフォルダをインデックスへ加える。
+        let dir = proxy_dir();
+        if !dir.is_dir() && ensure_dir(&dir).is_ok() && self.search_engine.is_some() {
+            if let Err(err) = self.sync_search_roots(&SettingsData::load()) {
+                self.push_error(tr_fmt(Msg::SearchSyncFailed, &[&err]));
+            }
+        }
+        let (tx, rx) = mpsc::channel();
+        let cancel = CancellationToken::new();
+        let worker_cancel = cancel.clone();
+        self.push_status(tr_fmt(Msg::ProxiesStarted, &[&paths.len()]));
+        thread::spawn(move || generate_proxies(paths, dir, tx, worker_cancel));
+        self.proxy_batch = Some(ProxyBatch { rx, cancel });
+    }
+
+    pub(crate) fn cancel_proxy_generation(&mut self) {
+        if let Some(batch) = self.proxy_batch.as_ref() {
+            batch.cancel.cancel();
+        }
+    }
+
+    // 選択中の行から音声を外す・差し替えたコピーを作り始める。作成中の場合は何もしない。
+    pub(crate) fn start_replace_audio(&mut self, replacement: AudioReplacement) {
+        if self.mute_batch.is_some() {
+            return;
+        }
+        let paths = self.selected_file_paths();
+        if paths.is_empty() {
+            self.push_status(tr(Msg::SelectFilesToMute).to_string());
+            return;
+        }
+        let (tx, rx) = mpsc::channel();
+        let cancel = CancellationToken::new();
+        let worker_cancel = cancel.clone();
+        self.push_status(tr_fmt(Msg::ReplaceAudioStarted, &[&paths.len()]));
+        thread::spawn(move || replace_audio_files(paths, replacement, tx, worker_cancel));
+        self.mute_batch = Some(MuteBatch { rx, cancel });
+    }
+
+    // 差し替える音声ファイル（クリック音など）を選んでから始める。選ばなかった場合は何もしない。
+    pub(crate) fn start_replace_audio_with_track(&mut self) {
+        if self.mute_batch.is_some() {
+            return;
+        }
+        if let Some(track) = choose_file(None) {
+            self.start_replace_audio(AudioReplacement::Track(track));
+        }
+    }
+
+    pub(crate) fn cancel_replace_audio(&mut self) {
+        if let Some(batch) = self.mute_batch.as_ref() {
+            batch.cancel.cancel();
+        }
+    }
+
+    // 音声を差し替えたコピーの進み具合と結果を表示する。すべて終えたら件数を知らせる。
+    fn poll_mute_results(&mut self) {
+        let Some(batch) = self.mute_batch.as_ref() else {
+            return;
+        };
+        let events = batch.rx.try_iter().collect::<Vec<_>>();
+        for event in events {
+            match event {
+                MuteEvent::Started { index, total } => {
+                    self.push_status(tr_fmt(Msg::ReplaceAudioProgress, &[&(index + 1), &total]));
+                }
+                MuteEvent::Created { output, .. } => {
+                    self.refresh_needed = true;
+                    self.push_status(tr_fmt(Msg::MutedCopyCreated, &[&output.to_string_lossy()]));
+                }
+                MuteEvent::Failed { original, error } => {
+                    self.push_error(tr_fmt(
+                        Msg::MutedCopyFailed,
+                        &[&original.to_string_lossy(), &error],
+                    ));
+                }
+                MuteEvent::Finished { created, cancelled } => {
+                    self.mute_batch = None;
+                    let (kind, message) = if cancelled {
+                        (
+                            ToastKind::Info,
+                            tr_fmt(Msg::ReplaceAudioCancelled, &[&created]),
+                        )
+                    } else {
+                        (
+                            ToastKind::Success,
+                            tr_fmt(Msg::MutedCopiesDone, &[&created]),
+                        )
+                    };
+                    self.push_status(message.clone());
+                    self.push_toast(kind, message);
+                    return;
+                }
+            }
+        }
+    }
+
+    // 選択中の行のうち、クラウドにだけあるファイルを取得し始める。
+    pub(crate) fn start_cloud_fetch_for_selection(&mut self) {
+        let paths = self.selected_file_paths();
+        if paths.is_empty() {
+            self.push_status(tr(Msg::SelectCloudFiles).to_string());
+            return;
+        }
+        self.start_cloud_fetch(paths);
+    }
+
+    // 取得中の場合は何もしない。取得済みのファイルは読まずに飛ばす。
+    fn start_cloud_fetch(&mut self, paths: Vec<PathBuf>) {
+        if self.cloud_fetch.is_some() {
+            return;
+        }
+        let (tx, rx) = mpsc::channel();
+        self.push_status(tr_fmt(Msg::CloudFetchStarted, &[&paths.len()]));
+        thread::spawn(move || fetch_cloud_files(paths, tx));
+        self.cloud_fetch = Some(CloudFetch { rx, fetched: 0 });
+    }
+
+    // 取得できたファイルをインデックスへ反映し、すべて終えたら件数を知らせる。
+    fn poll_cloud_fetch(&mut self) {
+        let Some(batch) = self.cloud_fetch.as_mut() else {
+            return;
+        };
+        let mut fetched = Vec::new();
+        let mut messages = Vec::new();
+        let finished = loop {
+            match batch.rx.try_recv() {
+                Ok((path, Ok(()))) => {
+                    batch.fetched += 1;
+                    fetched.push(path);
+                }
+                Ok((path, Err(err))) => messages.push(tr_fmt(
+                    Msg::CloudFetchFailed,
+                    &[&path.to_string_lossy(), &err],
+                )),
+                Err(mpsc::TryRecvError::Empty) => break false,
+                Err(mpsc::TryRecvError::Disconnected) => break true,
+            }
+        };
+        let count = batch.fetched;
+        if !fetched.is_empty() {
+            if let Some(engine) = self.search_engine.as_ref()
+                && let Err(err) = engine.reindex_files(&fetched)
+            {
+                messages.push(tr_fmt(Msg::SearchSyncFailed, &[&err]));
+            }
+            self.search_dirty = true;
+            self.refresh_needed = true;
+        }
+        for message in messages {
+            self.push_error(message);
+        }
+        if finished {
+            self.cloud_fetch = None;
+            self.push_toast(ToastKind::Success, tr_fmt(Msg::CloudFetchDone, &[&count]));
+        }
+    }
+
+    // プロキシ作成の進み具合を表示し、できたプロキシを元の動画に紐付ける。
+    fn poll_proxy_events(&mut self) {
+        let Some(batch) = self.proxy_batch.as_ref() else {
+            return;
+        };
+        let events = batch.rx.try_iter().collect::<Vec<_>>();
+        for event in events {
+            match event {
+                ProxyEvent::Started { index, total } => {
+                    self.push_status(tr_fmt(Msg::ProxyProgress, &[&(index + 1), &total]));
+                }
+                ProxyEvent::Created { original, proxy } => {
+                    if let Some(engine) = self.search_engine.as_ref()
+                        && let Err(err) = engine.link_proxy(&original, &proxy)
+                    {
+                        self.push_error(tr_fmt(Msg::ProxyLinkFailed, &[&err]));
+                    }
+                    self.proxy_links.insert(original, proxy);
+                }
+                ProxyEvent::Failed { original, error } => {
+                    self.push_error(tr_fmt(
+                        Msg::ProxyFailed,
+                        &[&original.to_string_lossy(), &error],
+                    ));
+                }
+                ProxyEvent::Finished { created, cancelled } => {
+                    self.proxy_batch = None;
+                    self.search_dirty = true;
+                    let (kind, message) = if cancelled {
+                        (ToastKind::Info, tr_fmt(Msg::ProxiesCancelled, &[&created]))
+                    } else {
+                        (ToastKind::Success, tr_fmt(Msg::ProxiesDone, &[&created]))
+                    };
+                    self.push_status(message.clone());
+                    self.push_toast(kind, message);
+                    return;
+                }
+            }
+        }
+    }
+
+    // メニューバーのステータス項目の表示設定を反映する。
+    pub(crate) fn apply_status_item_setting(&mut self, ctx: &egui::Context, enabled: bool) {
+        mac_status_item::set_status_item_enabled(ctx, enabled);
+        // 作り直した項目へ現在の状態を表示し直す。
+        self.published_status_item = None;
+    }
+
+    // 完了通知の設定を反映する。有効にした時点で通知の許可を求める。
+    pub(crate) fn apply_notification_setting(&mut self, ctx: &egui::Context, enabled: bool) {
+        self.notifications_enabled = enabled;
+        if enabled {
+            mac_notification::install_notifications(ctx);
+        }
+    }
+
+    // ウィンドウが非アクティブな間に完了・失敗した場合は通知センターへ知らせる。
+    fn notify_download_result(&self, result: &Result<(), FailureKind>) {
+        if !self.notifications_enabled || self.last_focus_state != Some(false) {
+            return;
+        }
+        match result {
+            Ok(()) => {
+                // 保存先で最も新しい mp4 を今回のダウンロード結果とみなす。
+                let latest = load_mp4_files(&self.download_dir).into_iter().next();
+                let body = latest
+                    .as_ref()
+                    .and_then(|file| file.path.file_name())
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| tr(Msg::DownloadFinishedBody).to_string());
+                mac_notification::post_notification(
+                    tr(Msg::DownloadFinishedTitle),
+                    &body,
+                    latest.as_ref().map(|file| file.path.as_path()),
+                );
+            }
+            Err(FailureKind::Cancelled) => {}
+            Err(kind) => mac_notification::post_notification(
+                tr(Msg::DownloadFailedTitle),
+                kind.message(),
+                None,
+            ),
+        }
+    }
+
+    // 「インデックス時にFinderタグを読む」設定を反映する。有効にした時は全体を読み直す。
+    pub(crate) fn apply_finder_tag_reading(&mut self, enabled: bool) {
+        let newly_enabled = enabled && !search_index::reads_finder_tags();
+        search_index::set_read_finder_tags(enabled);
+        if newly_enabled && let Err(err) = self.request_reindex_all() {
+            let message = tr_fmt(Msg::ReindexFailed, &[&err]);
+            self.push_error(message.clone());
+            self.push_toast(ToastKind::Error, message);
+        }
+    }
+
+    // 除外の決まりが変わった場合は全体を走査し直し、新しく除外した動画をインデックスから消す。
+    pub(crate) fn apply_index_skip_rules(&mut self, rules: SkipRules) {
+        if search_index::set_skip_rules(rules)
+            && let Err(err) = self.request_reindex_all()
+        {
+            let message = tr_fmt(Msg::ReindexFailed, &[&err]);
+            self.push_error(message.clone());
+            self.push_toast(ToastKind::Error, message);
+        }
+    }
+
+    pub(crate) fn reveal_selected_file(&mut self) {
+        let Some(path) = self.selected_file_path() else {
+            return;
+        };
+        if let Err(err) = reveal_in_file_manager(&path) {
+            self.push_error(tr_fmt(Msg::RevealInFinderFailed, &[&err]));
+        }
+    }
+
+    fn file_list_paths(&self, list: FileListKind) -> Vec<PathBuf> {
+        match list {
+            FileListKind::Downloads => self
+                .download_groups()
+                .into_iter()
+                .filter(|(group, _)| {
+                    group.is_none_or(|group| !self.collapsed_date_groups.contains(&group))
+                })
+                .flat_map(|(_, files)| files)
+                .map(|file| file.path)
+                .collect(),
+            FileListKind::Search if self.search_order_is_current() => {
+                ordered_hit_paths(&self.search_results, &self.search_order)
+            }
+            FileListKind::Search => ordered_hit_paths(
+                &self.search_results,
+                &sorted_order(&self.search_results, self.search_sort),
+            ),
+        }
+    }
+
+    fn search_order_is_current(&self) -> bool {
+        self.search_order.len() == self.search_results.len()
+            && self.search_order_sort == self.search_sort
+    }
+
+    // 検索結果か並び順が変わっていれば、行の並びを作り直す。
+    // 追加読み込みで結果が増えた場合も、並び順に従って全体を並べ直す。
+    pub(crate) fn refresh_search_order(&mut self) {
+        if self.search_order_is_current() {
+            return;
+        }
+        self.search_order = sorted_order(&self.search_results, self.search_sort);
+        self.search_order_sort = self.search_sort;
+    }
+
+    fn current_downloads_order_key(&self) -> DownloadsOrderKey {
+        DownloadsOrderKey {
+            filter: self.downloads_filter.trim().to_lowercase(),
+            session_only: self.downloads_session_only,
+            sort: self.downloads_sort,
+        }
+    }
+
+    // 絞り込みと並び順を適用した、downloaded_files の行の位置。ファイル名は大文字・小文字を区別せずに比べる。
+    fn build_downloads_order(&self, key: &DownloadsOrderKey) -> Vec<usize> {
+        let files = self
+            .downloaded_files
+            .iter()
+            .enumerate()
+            .filter(|(_, file)| !key.session_only || self.session_downloads.contains(&file.path))
+            .filter(|(_, file)| {
+                key.filter.is_empty()
+                    || file.path.file_name().is_some_and(|name| {
+                        name.to_string_lossy().to_lowercase().contains(&key.filter)
+                    })
+            })
+            .collect::<Vec<_>>();
+        let listed = files.iter().map(|(_, file)| *file).collect::<Vec<_>>();
+        sorted_order(&listed, key.sort)
+            .into_iter()
+            .map(|index| files[index].0)
+            .collect()
+    }
+
+    // 一覧・絞り込み・並び順のどれかが変わっていれば、ダウンロード一覧の並びを作り直す。
+    pub(crate) fn refresh_downloads_order(&mut self) {
+        let key = self.current_downloads_order_key();
+        if self.downloads_order_key.as_ref() == Some(&key) {
+            return;
+        }
+        self.downloads_order = self.build_downloads_order(&key);
+        self.downloads_order_key = Some(key);
+    }
+
+    // 絞り込みと並び順を適用したダウンロード一覧。作り直す前なら、その場で並べる。
+    pub(crate) fn filtered_downloads(&self) -> Vec<ListedFile> {
+        let key = self.current_downloads_order_key();
+        let fresh;
+        let order = if self.downloads_order_key.as_ref() == Some(&key) {
+            &self.downloads_order
+        } else {
+            fresh = self.build_downloads_order(&key);
+            &fresh
+        };
+        order
+            .iter()
+            .map(|index| self.downloaded_files[*index].clone())
+            .collect()
+    }
+
+    // 一覧を更新日の区切りごとに分ける。列見出しで並べ替えている間は区切らず、区切りを None にする。
+    pub(crate) fn download_groups(&self) -> Vec<(Option<DateGroup>, Vec<ListedFile>)> {
+        let files = self.filtered_downloads();
+        if self.downloads_sort.is_some() {
+            return vec![(None, files)];
+        }
+        let now = local_now();
+        let mut groups = DateGroup::ALL.map(|group| (group, Vec::new()));
+        for file in files {
+            let group = DateGroup::of(file.modified_time, now);
+            if let Some((_, files)) = groups.iter_mut().find(|(item, _)| *item == group) {
+                files.push(file);
+            }
+        }
+        groups
+            .into_iter()
+            .filter(|(_, files)| !files.is_empty())
+            .map(|(group, files)| (Some(group), files))
+            .collect()
+    }
+
+    // 日付の区切りを畳む・開く。
+    pub(crate) fn toggle_date_group(&mut self, group: DateGroup) {
+        if !self.collapsed_date_groups.remove(&group) {
+            self.collapsed_date_groups.insert(group);
+        }
+    }
+
+    pub(crate) fn delete_download(&mut self, path: &Path) {
+        match delete_download_file(path) {
+            Ok(()) => {
+                self.refresh_needed = true;
+                let name = path
+                    .file_name()
+                    .unwrap_or(path.as_os_str())
+                    .to_string_lossy();
+                self.push_toast(ToastKind::Success, tr_fmt(Msg::FileDeleted, &[&name]));
+            }
+            Err(err) => {
+                let message = tr_fmt(Msg::DeleteFailed, &[&err]);
+                self.push_error(message.clone());
+                self.push_toast(ToastKind::Error, message);
+            }
+        }
+    }
+
+    pub(crate) fn start_native_drag(&mut self, frame: &eframe::Frame, path: &Path) {
+        // 元の動画のドライブが外れている場合や、クラウドにだけある場合は、作っておいたプロキシをドラッグする。
+        let cloud_only = is_cloud_only(path);
+        let path = match self.proxy_links.get(path).cloned() {
+            Some(proxy) if !path.exists() => {
+                self.push_status(tr_fmt(Msg::UsingProxy, &[&proxy.to_string_lossy()]));
+                proxy
+            }
+            Some(proxy) if cloud_only => {
+                self.push_status(tr_fmt(Msg::UsingProxyForCloud, &[&proxy.to_string_lossy()]));
+                proxy
+            }
+            // 中身が無いまま渡すと、受け取った側（VDMX など）で読み込みに失敗するためドラッグしない。
+            _ if cloud_only => {
+                let name = path.to_string_lossy().to_string();
+                if self.cloud_fetch_on_drag && self.cloud_fetch.is_none() {
+                    self.start_cloud_fetch(vec![path.to_path_buf()]);
+                    self.push_status(tr_fmt(Msg::CloudFetchOnDrag, &[&name]));
+                } else {
+                    self.push_warning(tr_fmt(Msg::CloudOnlyDragBlocked, &[&name]));
+                }
+                return;
+            }
+            _ => path.to_path_buf(),
+        };
+        let path = match path.canonicalize() {
+            Ok(path) => path,
+            Err(err) => {
+                self.push_error(tr_fmt(Msg::DragItemsFailed, &[&err]));
+                return;
+            }
+        };
+
+        let icon_path = match drag_fallback_preview_icon_path() {
+            Some(path) => path,
+            None => {
+                self.push_warning(tr(Msg::DragIconMissing).to_string());
+                return;
+            }
+        };
+
+        if let Err(err) = drag::start_drag(
+            frame,
+            DragItem::Files(vec![path]),
+            Image::File(icon_path),
+            |_result, _position| {},
+            Options::default(),
+        ) {
+            self.push_error(tr_fmt(Msg::DragStartFailed, &[&err]));
+        }
+    }
+
+    pub(crate) fn mark_search_dirty(&mut self) {
+        self.search_dirty = true;
+    }
+
+    // 保存済みの設定からツールバーのプロファイル一覧を更新する。
+    pub(crate) fn sync_profiles(&mut self, settings: &SettingsData) {
+        self.profile_names = settings
+            .profiles
+            .iter()
+            .map(|profile| profile.name.clone())
+            .collect();
+        self.active_profile = settings.active_profile.clone();
+    }
+
+    pub(crate) fn sync_presets(&mut self, settings: &SettingsData) {
+        self.presets = settings.presets.clone();
+    }
+
+    pub(crate) fn sync_cookie_accounts(&mut self, settings: &SettingsData) {
+        self.cookie_account_names = settings
+            .cookie_accounts
+            .iter()
+            .map(|account| account.name.clone())
+            .collect();
+        self.active_cookie_account = settings.active_cookie_account.clone();
+    }
+
+    // 異常終了で残った作業フォルダを、設定・最近使ったフォルダ・プロファイルの保存先から探して削除する。
+    fn remove_stale_staging_dirs(&mut self, settings: &SettingsData) {
+        let mut dirs = vec![self.download_dir.clone()];
+        let candidates = settings.recent_download_dirs.iter().chain(
+            settings
+                .profiles
+                .iter()
+                .map(|profile| &profile.download_dir),
+        );
+        for dir in candidates {
+            let dir = PathBuf::from(dir.trim());
+            if !dir.as_os_str().is_empty() && !dirs.contains(&dir) {
+                dirs.push(dir);
+            }
+        }
+        // 再開できるよう、中断したダウンロードの作業フォルダは残す。
+        let keep = self
+            .interrupted_job
+            .as_ref()
+            .and_then(|job| job.staging_dir.clone());
+        let removed = remove_stale_staging_dirs(&dirs, STALE_STAGING_AGE, keep.as_deref());
+        if !removed.is_empty() {
+            self.push_status(tr_fmt(Msg::StaleStagingRemoved, &[&removed.len()]));
+        }
+    }
+
+    // 使った保存先を最近使ったフォルダの先頭へ移して保存する。
+    fn remember_download_dir(&mut self, dir: &Path) {
+        let mut data = SettingsData::load();
+        data.remember_download_dir(&dir.to_string_lossy());
+        if data.recent_download_dirs == self.recent_download_dirs {
+            return;
+        }
+        self.recent_download_dirs = data.recent_download_dirs.clone();
+        if let Err(err) = save_settings(&data) {
+            self.push_error(tr_fmt(Msg::RecentDirsSaveFailed, &[&err]));
+        }
+    }
+
+    // ツールバーで選んだプロファイルに切り替えて保存する。次のダウンロードから反映する。
+    pub(crate) fn switch_profile(&mut self, name: &str) {
+        let mut data = SettingsData::load();
+        let applied = data.apply_profile(name).map(|()| {
+            let dir = data.download_dir.clone();
+            data.remember_download_dir(&dir);
+        });
+        if let Err(err) = applied.and_then(|()| save_settings(&data)) {
+            self.push_error(tr_fmt(Msg::ProfileSwitchFailed, &[&err]));
+            return;
+        }
+        self.download_dir = PathBuf::from(&data.download_dir);
+        self.refresh_needed = true;
+        // 保存先の一覧は検索インデックスから読むため、新しい保存先をインデックスへ加える。
+        if self.search_engine.is_some()
+            && let Err(err) = self.sync_search_roots(&data)
+        {
+            self.push_error(tr_fmt(Msg::SearchSyncFailed, &[&err]));
+        }
+        self.recent_download_dirs = data.recent_download_dirs.clone();
+        self.sync_profiles(&data);
+        self.settings_ui.reload_form();
+        self.push_status(tr_fmt(Msg::ProfileSwitched, &[&name]));
+    }
+
+    // 次に追加するダウンロードで使うクッキーのアカウントを切り替えて保存する。空欄なら既定に戻す。
+    pub(crate) fn switch_cookie_account(&mut self, name: &str) {
+        let mut data = SettingsData::load();
+        data.active_cookie_account = name.to_string();
+        if let Err(err) = save_settings(&data) {
+            self.push_error(tr_fmt(Msg::AccountSwitchFailed, &[&err]));
+            return;
+        }
+        self.sync_cookie_accounts(&data);
+        self.settings_ui.reload_form();
+        if name.is_empty() {
+            self.push_status(tr(Msg::DefaultCookiesRestored));
+        } else {
+            self.push_status(tr_fmt(Msg::AccountCookiesUsed, &[&name]));
+        }
+    }
+
+    // 現在の検索クエリを履歴の先頭へ追加して保存する。
+    pub(crate) fn record_search_history(&mut self) {
+        let query = self.search_query.trim().to_string();
+        if query.is_empty() || self.search_history.first() == Some(&query) {
+            return;
+        }
+        self.search_history.retain(|entry| entry != &query);
+        self.search_history.insert(0, query);
+        self.search_history.truncate(SEARCH_HISTORY_LIMIT);
+
+        let mut data = SettingsData::load();
+        data.search_history = self.search_history.clone();
+        if let Err(err) = save_settings(&data) {
+            self.push_error(tr_fmt(Msg::SearchHistorySaveFailed, &[&err]));
+        }
+    }
+
+    // 履歴のクエリで再検索する。
+    pub(crate) fn rerun_search_history(&mut self, query: String) {
+        self.search_query = query;
+        self.mark_search_dirty();
+        self.record_search_history();
+    }
+
+    pub(crate) fn sync_search_roots(&mut self, settings: &SettingsData) -> Result<(), String> {
+        let Some(engine) = self.search_engine.as_ref() else {
+            return Err(tr(Msg::SearchNotReadyRestart).to_string());
+        };
+        let paths = index_roots(&settings.search_roots, &self.download_dir);
+        engine.sync_roots(&paths, &link_following_roots(settings))?;
+        self.search_roots_sync_error = None;
+        self.search_dirty = true;
+        self.refresh_needed = true;
+        Ok(())
+    }
+
+    // 検索インデックスを dir へバックアップし始める。manual は設定画面から始めた場合。
+    pub(crate) fn start_index_backup(&mut self, dir: PathBuf, manual: bool) {
+        if self.index_backup.is_some() {
+            return;
+        }
+        let Some(engine) = self.search_engine.clone() else {
+            if manual {
+                self.push_warning(tr(Msg::SearchNotReadyRestart).to_string());
+            }
+            return;
+        };
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = tx.send(engine.backup_into(&dir, INDEX_BACKUP_KEEP));
+        });
+        self.index_backup = Some((rx, manual));
+    }
+
+    // 設定の間隔を過ぎていれば、裏で検索インデックスをバックアップする。
+    fn maybe_backup_index(&mut self) {
+        let now = Instant::now();
+        if now < self.next_index_backup_check {
+            return;
+        }
+        self.next_index_backup_check = now + INDEX_BACKUP_CHECK_INTERVAL;
+        let (enabled, dir, hours) = load_index_backup();
+        let interval = Duration::from_secs(u64::from(hours) * 60 * 60);
+        if enabled && backup_due(&dir, interval, SystemTime::now()) {
+            self.start_index_backup(dir, false);
+        }
+    }
+
+    fn poll_index_backup(&mut self) {
+        let Some((rx, manual)) = self.index_backup.as_ref() else {
+            return;
+        };
+        let manual = *manual;
+        let result = match rx.try_recv() {
+            Ok(result) => result,
+            Err(mpsc::TryRecvError::Empty) => return,
+            Err(mpsc::TryRecvError::Disconnected) => Err(tr(Msg::IndexBackupStopped).to_string()),
+        };
+        self.index_backup = None;
+        match result {
+            Ok(path) => {
+                let message = tr_fmt(Msg::IndexBackedUp, &[&path.to_string_lossy()]);
+                self.push_status(message.clone());
+                if manual {
+                    self.push_toast(ToastKind::Success, message);
+                }
+            }
+            Err(err) => {
+                let message = tr_fmt(Msg::IndexBackupFailed, &[&err]);
+                self.push_error(message.clone());
+                self.push_toast(ToastKind::Error, message);
+            }
+        }
+    }
+
+    // バックアップから検索インデックスを戻す。戻した後は、その後のファイルの変化を再インデックスで取り込む。
+    pub(crate) fn restore_index(&mut self, path: &Path) -> Result<(), String> {
+        let Some(engine) = self.search_engine.clone() else {
+            return Err(tr(Msg::SearchNotReadyRestart).to_string());
+        };
+        engine.restore_from(path)?;
+        // 戻した DB の対象フォルダを今の設定にそろえる。
+        self.sync_search_roots(&SettingsData::load())?;
+        engine.reindex_all_async()?;
+        self.proxy_links = engine.proxy_links().unwrap_or_default();
+        self.push_toast(
+            ToastKind::Success,
+            tr_fmt(Msg::IndexRestored, &[&path.to_string_lossy()]),
+        );
+        Ok(())
+    }
+
+    pub(crate) fn request_reindex_all(&mut self) -> Result<(), String> {
+        let Some(engine) = self.search_engine.as_ref() else {
+            return Err(tr(Msg::SearchNotReady).to_string());
+        };
+        engine.reindex_all_async()?;
+        self.search_dirty = true;
+        Ok(())
+    }
+
+    pub(crate) fn indexed_paths(&self) -> Result<Vec<PathBuf>, String> {
+        let Some(engine) = self.search_engine.as_ref() else {
+            return Err(tr(Msg::SearchNotReady).to_string());
+        };
+        engine.indexed_paths()
+    }
+
+    fn poll_download_events(&mut self) {
+        let mut events = Vec::new();
+        if let Some(rx) = self.rx.as_ref() {
+            while let Ok(event) = rx.try_recv() {
+                events.push(event);
+            }
+        }
+
+        let mut done = None;
+        for event in events {
+            if let Some(server) = self.event_stream.as_ref() {
+                server.publish(StreamEvent::from(&event));
+            }
+            match event {
+                DownloadEvent::Log(level, line) => self.push_job_log(level, line),
+                DownloadEvent::ToolLog(source, line) => {
+                    self.status_logs
+                        .push_tool_line(source, self.current_job_id, line)
+                }
+                DownloadEvent::Stage(stage, elapsed) => {
+                    self.handle_stage(&stage, &elapsed);
+                    if stage.is_finished() {
+                        done = Some((stage, elapsed));
+                    }
+                }
+                DownloadEvent::Idle => self.clear_progress(),
+                DownloadEvent::CropPreview(_) | DownloadEvent::CollisionPrompt(_) => {
+                    self.show_or_defer_prompt(self.current_job_id, event);
+                }
+                DownloadEvent::AudioTrackPrompt(prompt) => {
+                    self.audio_track_prompt = Some(AudioTrackPromptState::new(prompt));
+                }
+                DownloadEvent::MetadataPreview(preview) => {
+                    self.preview_prompt = Some(PreviewPromptState::new(preview));
+                }
+                DownloadEvent::FormatPrompt(prompt) => {
+                    self.format_prompt = Some(FormatPromptState::new(prompt));
+                }
+                DownloadEvent::AlreadyDownloaded(path) => {
+                    let url = self
+                        .current_download
+                        .as_ref()
+                        .map(|job| job.url.clone())
+                        .unwrap_or_default();
+                    self.already_downloaded = Some(AlreadyDownloaded { url, path });
+                }
+                DownloadEvent::Downloaded => self.current_downloaded = true,
+                // 最初に見つかったエラーが原因であることが多い。
+                DownloadEvent::KnownError(kind) => {
+                    self.detected_error.get_or_insert(kind);
+                }
+            }
+        }
+
+        if let Some((stage, elapsed)) = done {
+            let finished = self.current_download.take();
+            let job_id = self.current_job_id.take();
+            let detected_error = self.detected_error.take();
+            self.finish_download(finished, job_id, detected_error, stage, &elapsed);
+            self.download_in_progress = false;
+            self.current_downloaded = false;
+            self.download_active_flag.store(false, Ordering::Relaxed);
+            self.audio_track_prompt = None;
+            self.format_prompt = None;
+            self.preview_prompt = None;
+            self.rx = None;
+            self.cancel_token = None;
+
+            if !self.queue_paused
+                && !self.offline
+                && let Some(next) = self.download_queue.pop_next()
+            {
+                self.publish_queue_length();
+                self.start_download(next);
+            }
+        }
+    }
+
+    // 裏で変換を続けている項目のイベントを処理し、終わった項目を一覧から外す。
+    fn poll_conversion_events(&mut self) {
+        let mut index = 0;
+        while index < self.conversions.len() {
+            let mut events = Vec::new();
+            while let Ok(event) = self.conversions[index].rx.try_recv() {
+                events.push(event);
+            }
+            let job_id = self.conversions[index].job_id;
+            let mut done = None;
+            for event in events {
+                // 途中の進捗は実行中のダウンロードの進捗と混ざるため配信しない。
+                let intermediate = matches!(
+                    &event,
+                    DownloadEvent::Stage(stage, _) if !stage.is_finished()
+                ) || matches!(event, DownloadEvent::Idle);
+                if !intermediate && let Some(server) = self.event_stream.as_ref() {
+                    server.publish(StreamEvent::from(&event));
+                }
+                match event {
+                    DownloadEvent::Log(level, line) => self.push_log_for(job_id, level, line),
+                    DownloadEvent::ToolLog(source, line) => {
+                        self.status_logs.push_tool_line(source, job_id, line)
+                    }
+                    DownloadEvent::Stage(stage, elapsed) => {
+                        if stage.is_finished() {
+                            done = Some((stage, elapsed));
+                        } else {
+                            let conversion = &mut self.conversions[index];
+                            conversion.message = stage.message(&elapsed);
+                            conversion.progress = stage.fraction();
+                        }
+                    }
+                    DownloadEvent::CropPreview(_) | DownloadEvent::CollisionPrompt(_) => {
+                        self.show_or_defer_prompt(job_id, event);
+                    }
+                    DownloadEvent::KnownError(kind) => {
+                        self.conversions[index].detected_error.get_or_insert(kind);
+                    }
+                    _ => {}
+                }
+            }
+
+            let Some((stage, elapsed)) = done else {
+                index += 1;
+                continue;
+            };
+            let conversion = self.conversions.remove(index);
+            conversion.active_flag.store(false, Ordering::Relaxed);
+            self.finish_download(
+                conversion.job,
+                conversion.job_id,
+                conversion.detected_error,
+                stage,
+                &elapsed,
+            );
+        }
+    }
+
+    // 取得を終えたダウンロードの変換を裏へ回し、待機中の次のダウンロードを始める。
+    fn detach_converting_download(&mut self) {
+        if !self.current_downloaded
+            || self.queue_paused
+            || self.offline
+            || self.download_queue.is_empty()
+        {
+            return;
+        }
+        let (Some(rx), Some(cancel)) = (self.rx.take(), self.cancel_token.take()) else {
+            return;
+        };
+        let job = self.current_download.take();
+        let url = job.as_ref().map(|job| job.url.clone()).unwrap_or_default();
+        // 変換を続ける処理は元のフラグを見続けるため、次のダウンロードには新しいフラグを渡す。
+        let active_flag = std::mem::replace(
+            &mut self.download_active_flag,
+            Arc::new(AtomicBool::new(false)),
+        );
+        self.conversions.push(BackgroundConversion {
+            job,
+            job_id: self.current_job_id.take(),
+            url: url.clone(),
+            rx,
+            active_flag,
+            cancel,
+            detected_error: self.detected_error.take(),
+            message: self.progress_message.clone(),
+            progress: self.progress_value,
+        });
+        self.current_downloaded = false;
+        self.download_in_progress = false;
+        self.clear_progress();
+        self.push_status(tr_fmt(Msg::ConversionHandedOff, &[&url]));
+
+        if let Some(next) = self.download_queue.pop_next() {
+            self.publish_queue_length();
+            self.start_download(next);
+        }
+    }
+
+    // 黒帯と同名ファイルの確認は一度に 1 つだけ表示し、表示中なら閉じられるまで待たせる。
+    fn show_or_defer_prompt(&mut self, job_id: Option<u64>, event: DownloadEvent) {
+        if self.crop_prompt.is_some() || self.collision_prompt.is_some() {
+            self.deferred_prompts.push_back((job_id, event));
+            return;
+        }
+        self.prompt_job_id = job_id;
+        match event {
+            DownloadEvent::CropPreview(preview) => {
+                self.crop_prompt = Some(CropPromptState::new(preview));
+            }
+            DownloadEvent::CollisionPrompt(prompt) => self.collision_prompt = Some(prompt),
+            _ => {}
+        }
+    }
+
+    fn show_deferred_prompt(&mut self) {
+        if self.crop_prompt.is_none()
+            && self.collision_prompt.is_none()
+            && let Some((job_id, event)) = self.deferred_prompts.pop_front()
+        {
+            self.show_or_defer_prompt(job_id, event);
+        }
+    }
+
+    // 終わったダウンロードの結果を反映する。実行中の項目と裏で変換していた項目で共通。
+    fn finish_download(
+        &mut self,
+        finished: Option<DownloadJob>,
+        job_id: Option<u64>,
+        detected_error: Option<KnownError>,
+        stage: DownloadStage,
+        elapsed: &str,
+    ) {
+        let result = match stage {
+            DownloadStage::Failed { kind } => Err(kind),
+            _ => Ok(()),
+        };
+        // 応答がなかった項目は、設定に応じて上限の回数までキューの先頭からやり直す。
+        let stall_retry = match (&result, &finished) {
+            (Err(FailureKind::Stalled(_)), Some(job))
+                if load_stall_timeout().1 && job.stall_retries < STALL_RETRY_LIMIT =>
+            {
+                let mut job = job.clone();
+                job.stall_retries += 1;
+                job.resume_staging_dir = None;
+                Some(job)
+            }
+            _ => None,
+        };
+        let retry_message = stall_retry.as_ref().map(|job| {
+            tr_fmt(
+                Msg::RetryingStalled,
+                &[&job.stall_retries, &STALL_RETRY_LIMIT, &job.url],
+            )
+        });
+        if let Some(job) = stall_retry {
+            self.download_queue.push_front(job);
+            self.publish_queue_length();
+        } else if let Err(FailureKind::Error(err) | FailureKind::Stalled(err)) = &result
+            && let Some(job) = finished.clone()
+        {
+            self.failed_jobs.push(job, err.clone());
+        }
+        // 一時停止で中断したダウンロードは、再開時にやり直せるようキューの先頭へ戻す。
+        if self.queue_paused
+            && result == Err(FailureKind::Cancelled)
+            && let Some(job) = finished
+        {
+            self.download_queue.push_front(job);
+            self.publish_queue_length();
+        } else if result == Err(FailureKind::Offline)
+            && let Some(job) = finished
+        {
+            // 接続が戻ったら同じURLから再開する。
+            self.download_queue.push_front(job);
+            self.publish_queue_length();
+        }
+        let (succeeded, summary) = match &result {
+            Ok(()) => (true, format!("completed {elapsed}")),
+            Err(kind) => (false, kind.message().to_string()),
+        };
+        self.send_osc_feedback(
+            "/vjd/done",
+            &[OscArg::Int(i64::from(succeeded)), OscArg::Str(summary)],
+        );
+        self.notify_download_result(&result);
+        // 一時停止による中断は利用者の操作なので知らせない。
+        match &result {
+            Ok(()) => self.push_toast(
+                ToastKind::Success,
+                tr_fmt(Msg::DownloadCompletedToast, &[&elapsed]),
+            ),
+            Err(FailureKind::Cancelled) => {
+                if !self.queue_paused {
+                    self.push_toast(ToastKind::Info, tr(Msg::DownloadCancelled));
+                }
+            }
+            Err(FailureKind::Stalled(_)) if retry_message.is_some() => {
+                self.push_toast(ToastKind::Info, retry_message.unwrap_or_default());
+            }
+            // 理由が分かる失敗は、トーストの代わりに対処の操作を案内する。
+            Err(_) if detected_error.is_some() => {
+                self.error_dialog = detected_error;
+            }
+            Err(kind) => self.push_toast(
+                ToastKind::Error,
+                tr_fmt(Msg::DownloadFailedToast, &[&kind.message()]),
+            ),
+        }
+        let job_status = match &result {
+            Ok(()) => JobStatus::Completed,
+            Err(FailureKind::Cancelled) => JobStatus::Cancelled,
+            Err(_) => JobStatus::Failed,
+        };
+        match result {
+            Ok(()) => self.push_log_for(
+                job_id,
+                LogLevel::Info,
+                format!("Download completed. Total time: {elapsed}"),
+            ),
+            Err(FailureKind::Cancelled) => {
+                self.push_log_for(job_id, LogLevel::Info, tr(Msg::DownloadCancelled))
+            }
+            Err(kind) => self.push_log_for(
+                job_id,
+                LogLevel::Error,
+                format!("Download failed: {}", kind.message()),
+            ),
+        }
+        if let Some(job_id) = job_id {
+            self.status_logs.finish_job(job_id, job_status);
+        }
+        // 終わった項目の確認は、表示中のものも待たせているものも閉じる。
+        if self.prompt_job_id == job_id {
+            self.crop_prompt = None;
+            self.collision_prompt = None;
+            self.prompt_job_id = None;
+        }
+        self.deferred_prompts.retain(|(owner, _)| *owner != job_id);
+        self.refresh_needed = true;
+    }
+
+    // 接続状態の変化を反映し、復帰時は待機キューを再開する。
+    fn poll_network_state(&mut self) {
+        let offline = !network::last_known_online();
+        if offline == self.offline {
+            return;
+        }
+        self.offline = offline;
+        if offline {
+            self.push_error(tr(Msg::OfflineError).to_string());
+            return;
+        }
+        self.push_status(tr(Msg::NetworkReconnected).to_string());
+        if !self.download_in_progress
+            && !self.queue_paused
+            && let Some(next) = self.download_queue.pop_next()
+        {
+            self.publish_queue_length();
+            self.start_download(next);
+        }
+    }
+
+    // 電源がバッテリーに切り替わったらインデックスを控えめにし、電源につないだら見送った全走査を行う。
+    fn poll_power_state(&mut self) {
+        let low_power = self.battery_saver && power::last_known_on_battery();
+        if low_power == self.low_power {
+            return;
+        }
+        self.low_power = low_power;
+        let resume_reindex = search_index::set_low_power(low_power);
+        if low_power {
+            self.push_status(tr(Msg::LowPowerIndexing).to_string());
+            return;
+        }
+        self.push_status(tr(Msg::LowPowerEnded).to_string());
+        if resume_reindex && let Some(engine) = self.search_engine.as_ref() {
+            if let Err(err) = engine.reindex_all_async() {
+                self.push_error(tr_fmt(Msg::SearchSyncFailed, &[&err]));
+            }
+            self.search_dirty = true;
+        }
+    }
+
+    pub(crate) fn set_battery_saver(&mut self, enabled: bool) {
+        self.battery_saver = enabled;
+        self.poll_power_state();
+    }
+
+    // メニューバーのステータス項目から届いた操作を処理する。
+    fn poll_status_item_actions(&mut self, ctx: &egui::Context) {
+        for action in mac_status_item::take_status_item_actions() {
+            match action {
+                StatusItemAction::DownloadClipboard => self.start_download_from_clipboard(),
+                StatusItemAction::TogglePause => self.toggle_pause_all(),
+                StatusItemAction::OpenWindow => {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+                }
+            }
+        }
+    }
+
+    // OSC で届いた操作を処理する。
+    fn poll_osc_commands(&mut self) {
+        let mut commands = Vec::new();
+        if let Some(bridge) = self.osc.as_ref() {
+            while let Some(command) = bridge.try_recv() {
+                commands.push(command);
+            }
+        }
+        for command in commands {
+            match command {
+                OscCommand::Download(url) => self.enqueue_download(url),
+                OscCommand::DownloadClipboard => self.start_download_from_clipboard(),
+                OscCommand::Search(query) => {
+                    self.search_query = query;
+                    self.mark_search_dirty();
+                }
+                OscCommand::Stop => {
+                    if self.download_in_progress {
+                        self.request_cancel_download();
+                    }
+                }
+            }
+        }
+    }
+
+    // 監視フォルダに置かれた動画をキューへ追加する。
+    fn poll_watch_folder(&mut self) {
+        let mut paths = Vec::new();
+        if let Some(watch) = self.watch_folder.as_ref() {
+            while let Some(path) = watch.try_recv() {
+                paths.push(path);
+            }
+        }
+        for path in paths {
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            self.push_toast(ToastKind::Info, tr_fmt(Msg::WatchFolderQueued, &[&name]));
+            let job = self.new_import_job(path);
+            self.enqueue_job(job);
+        }
+    }
+
+    // 購読元で見つけた新しい動画を待機キューへ追加する。追加しない設定の場合は知らせるだけにする。
+    fn poll_subscriptions(&mut self) {
+        let mut checks = Vec::new();
+        if let Some(poller) = self.subscriptions.as_ref() {
+            while let Some(check) = poller.try_recv() {
+                checks.push(check);
+            }
+        }
+        for check in checks {
+            let uploads = match check.result {
+                Ok(uploads) if uploads.is_empty() => continue,
+                Ok(uploads) => uploads,
+                Err(err) => {
+                    self.push_error(tr_fmt(Msg::SubscriptionCheckFailed, &[&check.source, &err]));
+                    continue;
+                }
+            };
+            let message = if self.subscription_auto_queue {
+                tr_fmt(Msg::SubscriptionQueued, &[&uploads.len(), &check.source])
+            } else {
+                tr_fmt(Msg::SubscriptionFound, &[&uploads.len(), &check.source])
+            };
+            for upload in &uploads {
+                self.push_status(tr_fmt(
+                    Msg::SubscriptionUpload,
+                    &[&upload.title, &upload.url],
+                ));
+            }
+            if self.notifications_enabled && self.last_focus_state == Some(false) {
+                let titles: Vec<&str> =
+                    uploads.iter().map(|upload| upload.title.as_str()).collect();
+                mac_notification::post_notification(&message, &titles.join("\n"), None);
+            }
+            self.push_toast(ToastKind::Info, message);
+            if self.subscription_auto_queue {
+                for upload in uploads {
+                    self.enqueue_download(upload.url);
+                }
+            }
+        }
+    }
+
+    // URL スキームや AppleScript から届いた要求を処理する。
+    fn poll_external_requests(&mut self) {
+        for request in mac_apple_event::take_pending_requests() {
+            match request {
+                Ok(AppUrlRequest::Download(url)) => self.enqueue_download(url),
+                Ok(AppUrlRequest::Search(query)) => {
+                    self.search_query = query;
+                    self.mark_search_dirty();
+                }
+                Err(err) => self.push_error(tr_fmt(Msg::ExternalRequestFailed, &[&err])),
+            }
+        }
+    }
+
+    // スクリプトの status コマンド向けに現在の状態を公開する。
+    fn publish_script_status(&mut self) {
+        let state = if self.download_in_progress {
+            "downloading"
+        } else {
+            "idle"
+        };
+        let latest = self
+            .downloaded_files
+            .first()
+            .map(|file| file.path.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let text = format!(
+            "state: {state}\nprogress: {:.2}\nmessage: {}\nqueued: {}\nlatest: {latest}",
+            reported_progress(self.progress_value),
+            self.progress_message,
+            self.download_queue.len()
+        );
+        if text != self.published_script_status {
+            mac_apple_event::publish_script_status(&text);
+            self.published_script_status = text;
+        }
+    }
+
+    // Dock アイコンの進捗バーとバッジを、表示が変わるときだけ更新する。
+    fn sync_dock_tile(&mut self) {
+        let progress = self
+            .download_in_progress
+            .then(|| reported_progress(self.progress_value));
+        let queued = self.download_queue.len();
+        // 1% 単位で比較して Dock の再描画を減らす。
+        let state = (
+            progress.map(|value| (value.clamp(0.0, 1.0) * 100.0).round() as i32),
+            queued,
+        );
+        if self.published_dock_state != Some(state) {
+            mac_dock::update_dock_tile(progress, queued);
+            self.published_dock_state = Some(state);
+        }
+    }
+
+    // メニューバーの表示を、進捗・待ち件数・一時停止状態が変わるときだけ更新する。
+    fn sync_status_item(&mut self) {
+        let progress = self
+            .download_in_progress
+            .then(|| reported_progress(self.progress_value));
+        let queued = self.download_queue.len();
+        let state = (
+            progress.map(|value| (value * 100.0).round() as i32),
+            queued,
+            self.queue_paused,
+        );
+        if self.published_status_item != Some(state) {
+            mac_status_item::update_status_item(progress, queued, self.queue_paused);
+            self.published_status_item = Some(state);
+        }
+    }
+
+    // インデックスから読んでいる間はインデックスが更新された時だけ、使えない間は 2 秒ごとに読み直す。
+    fn refresh_downloads_if_needed(&mut self) {
+        let stale = match self.downloads_index_seq {
+            Some(seq) => self
+                .search_engine
+                .as_ref()
+                .is_none_or(|engine| engine.change_seq() != seq),
+            None => self.last_scan.elapsed() >= Duration::from_secs(2),
+        };
+        if !self.refresh_needed && !stale {
+            return;
+        }
+        self.refresh_needed = false;
+        self.last_scan = Instant::now();
+        if let Some(engine) = self.search_engine.as_ref() {
+            // 読んでいる間に書き込まれた分は、次のフレームで読み直す。
+            let seq = engine.change_seq();
+            if let Ok(Some(hits)) = engine.files_in_dir(&self.download_dir) {
+                self.set_downloaded_files(hits.into_iter().map(ListedFile::from).collect());
+                self.downloads_index_seq = Some(seq);
+                return;
+            }
+        }
+        self.downloads_index_seq = None;
+        self.set_downloaded_files(load_mp4_files(&self.download_dir));
+    }
+
+    // 一覧を差し替える。新しく現れたファイルのうち、起動後に更新されたものをこの起動中の保存とみなす。
+    // インデックスの初回走査で現れる既存のファイルは、更新時刻が古いため含めない。
+    fn set_downloaded_files(&mut self, files: Vec<ListedFile>) {
+        let previous = self
+            .downloaded_files
+            .iter()
+            .map(|file| &file.path)
+            .collect::<HashSet<_>>();
+        for file in &files {
+            if previous.contains(&file.path) || self.session_downloads.contains(&file.path) {
+                continue;
+            }
+            let modified = fs::metadata(&file.path).and_then(|meta| meta.modified());
+            if modified.is_ok_and(|time| time >= self.session_started) {
+                self.session_downloads.insert(file.path.clone());
+            }
+        }
+        self.downloaded_files = files;
+        self.downloads_order_key = None;
+        self.publish_recent_downloads();
+    }
+
+    // メニューの「最近のダウンロード」を、新しい順に RECENT_MENU_LIMIT 件で更新する。
+    fn publish_recent_downloads(&mut self) {
+        let mut files = self.downloaded_files.iter().collect::<Vec<_>>();
+        files.sort_by(|a, b| b.modified_time.cmp(&a.modified_time));
+        let recent = files
+            .into_iter()
+            .take(RECENT_MENU_LIMIT)
+            .map(|file| file.path.clone())
+            .collect::<Vec<_>>();
+        if recent != self.published_recent_downloads {
+            mac_menu::set_recent_downloads(&recent);
+            self.published_recent_downloads = recent;
+        }
+    }
+
+    // 「最近のダウンロード」で選ばれたファイルを Finder で表示し、そのままドラッグできるよう一覧で選ぶ。
+    fn open_recent_download(&mut self, path: &Path) {
+        if let Err(err) = reveal_in_file_manager(path) {
+            self.push_error(tr_fmt(Msg::RevealInFinderFailed, &[&err]));
+        }
+        if let Some(file) = self.downloaded_files.iter().find(|file| file.path == path) {
+            let group = DateGroup::of(file.modified_time, local_now());
+            self.collapsed_date_groups.remove(&group);
+        }
+        self.select_file(FileListKind::Downloads, path);
+        self.scroll_to_selection = true;
+    }
+
+    // 失敗・キャンセルした場合は進捗表示を待機中に戻す。
+    fn handle_stage(&mut self, stage: &DownloadStage, elapsed: &str) {
+        if let DownloadStage::Failed { .. } = stage {
+            self.clear_progress();
+            return;
+        }
+        let message = stage.message(elapsed);
+        self.send_osc_feedback(
+            "/vjd/progress",
+            &[
+                OscArg::Float(f64::from(stage.reported_progress())),
+                OscArg::Str(message.clone()),
+            ],
+        );
+        self.progress_message = message;
+        self.progress_value = stage.fraction();
+        self.progress_visible = true;
+    }
+
+    fn clear_progress(&mut self) {
+        self.send_osc_feedback(
+            "/vjd/progress",
+            &[OscArg::Float(0.0), OscArg::Str(String::new())],
+        );
+        self.progress_message = tr(Msg::Waiting).to_string();
+        self.progress_value = Some(0.0);
+        self.progress_visible = false;
+    }
+
+    fn is_yt_dlp_ready(&self) -> bool {
+        let path = yt_dlp_path();
+        path.exists() && is_executable(&path)
+    }
+
+    fn is_tools_ready(&self) -> bool {
+        self.is_yt_dlp_ready()
+    }
+
+    fn poll_input_mode_change(&mut self) {
+        let Some(mode) = current_mode() else {
+            return;
+        };
+
+        if self.last_input_mode.is_none() {
+            self.last_input_mode = Some(mode);
+            return;
+        }
+
+        if self.last_input_mode.as_ref() == Some(&mode) {
+            return;
+        }
+
+        self.last_input_mode = Some(mode.clone());
+        if !self.input_source_notify {
+            return;
+        }
+        match mode {
+            InputMode::Japanese => self.push_status(tr(Msg::InputJapanese).to_string()),
+            InputMode::English => self.push_status(tr(Msg::InputEnglish).to_string()),
+            InputMode::Other(name) => self.push_status(tr_fmt(Msg::InputSourceChanged, &[&name])),
+        }
+    }
+
+    // 検索欄にフォーカスが移った時に、設定した入力モードへ切り替える。
+    pub(crate) fn switch_search_input_mode(&mut self) {
+        let Some(target) = self.search_input_mode.target() else {
+            return;
+        };
+        if let Some(previous) = select_mode(&target) {
+            self.restore_input_source.get_or_insert(previous);
+            // 自分で切り替えた分はステータスに出さない。
+            self.last_input_mode = Some(target);
+        }
+    }
+
+    // 検索欄からフォーカスが外れた時に、切り替える前の入力ソースへ戻す。
+    // フォーカス中に利用者が別のモードへ変えていた場合はそのままにする。
+    pub(crate) fn restore_search_input_mode(&mut self) {
+        let Some(previous) = self.restore_input_source.take() else {
+            return;
+        };
+        if current_mode() == self.search_input_mode.target() && select_source(&previous) {
+            self.last_input_mode = current_mode();
+        }
+    }
+
+    fn submit_search_if_needed(&mut self) {
+        if !self.search_dirty {
+            return;
+        }
+
+        if self.search_query.trim().is_empty() {
+            self.search_results.clear();
+            self.clear_file_selection(FileListKind::Search);
+            self.search_next_cursor = None;
+            self.search_loading_more = false;
+            let has_persistent_search_error =
+                self.search_engine.is_none() || self.search_roots_sync_error.is_some();
+            if !has_persistent_search_error {
+                self.search_error = None;
+            }
+            self.search_dirty = false;
+            return;
+        }
+
+        let request = self.build_search_request(None);
+        // インデックスに載らない拡張子は一致しようがないため、検索せずに理由を出す。
+        if let Some(ext) = request.unindexed_extension() {
+            self.search_error = Some(tr_fmt(
+                Msg::ExtensionNotIndexed,
+                &[&ext, &search_index::INDEXED_EXTENSION],
+            ));
+            self.search_results.clear();
+            self.clear_file_selection(FileListKind::Search);
+            self.search_next_cursor = None;
+            self.search_loading_more = false;
+            // 送信済みの検索の結果で上書きされないよう、番号を進めておく。
+            self.search_request_seq = self.search_request_seq.saturating_add(1);
+            self.applied_search_seq = self.search_request_seq;
+            self.search_dirty = false;
+            return;
+        }
+        if self.send_search_job(request, false) {
+            self.search_dirty = false;
+            self.search_loading_more = false;
+        }
+    }
+
+    // 現在の結果の続きを keyset カーソルで取得する。
+    pub(crate) fn request_more_search_results(&mut self) {
+        if self.search_loading_more || self.search_dirty {
+            return;
+        }
+        let Some(cursor) = self.search_next_cursor.clone() else {
+            return;
+        };
+        let request = self.build_search_request(Some(cursor));
+        if self.send_search_job(request, true) {
+            self.search_loading_more = true;
+        }
+    }
+
+    fn build_search_request(&self, after: Option<SearchCursor>) -> SearchRequest {
+        search_request_for_query(&self.search_query, SEARCH_PAGE_SIZE, after)
+    }
+
+    fn send_search_job(&mut self, request: SearchRequest, append: bool) -> bool {
+        let Some(tx) = self.search_job_tx.as_ref() else {
+            return false;
+        };
+
+        self.search_request_seq = self.search_request_seq.saturating_add(1);
+        let seq = self.search_request_seq;
+        if tx
+            .send(SearchJob {
+                seq,
+                request,
+                append,
+            })
+            .is_ok()
+        {
+            true
+        } else {
+            self.search_error = Some(tr(Msg::SearchRequestFailed).to_string());
+            false
+        }
+    }
+
+    fn poll_search_results(&mut self) {
+        let Some(rx) = self.search_result_rx.as_ref() else {
+            return;
+        };
+
+        let mut latest_result = None;
+        while let Ok(result) = rx.try_recv() {
             latest_result = Some(result);
         }
 
@@ -462,18 +2799,36 @@ impl DownloaderApp {
         if result.seq < self.applied_search_seq {
             return;
         }
+        // 追加読み込みは最新リクエストの結果のときだけ連結する。
+        if result.append && result.seq != self.search_request_seq {
+            return;
+        }
 
         self.applied_search_seq = result.seq;
+        if result.append {
+            self.search_loading_more = false;
+        }
         match result.result {
-            Ok(hits) => {
-                self.search_results = hits;
+            Ok(page) => {
+                if result.append {
+                    self.search_results.extend(page.hits);
+                } else {
+                    self.search_results = page.hits;
+                    // 件数が同じでも別の結果なので、並びは作り直す。
+                    self.search_order.clear();
+                    self.clear_file_selection(FileListKind::Search);
+                }
+                self.search_next_cursor = page.next_cursor;
                 self.search_error = None;
             }
             Err(err) => {
                 self.search_results.clear();
+                self.clear_file_selection(FileListKind::Search);
+                self.search_next_cursor = None;
                 self.search_error = Some(err);
             }
         }
+        self.refresh_search_order();
     }
 
     fn maintain_cursor_tracking(&mut self, ctx: &egui::Context) {
@@ -525,6 +2880,26 @@ impl eframe::App for DownloaderApp {
         if mac_menu::take_open_logs_request() {
             self.log_ui.open_logs();
         }
+        if mac_menu::take_open_log_folder_request() {
+            self.open_log_folder();
+        }
+        if mac_menu::take_export_playlist_request() {
+            self.export_playlist();
+        }
+        if let Some(path) = mac_menu::take_recent_download_request() {
+            self.open_recent_download(&path);
+        }
+        if let Some(path) = mac_notification::take_reveal_request()
+            && let Err(err) = reveal_in_file_manager(&path)
+        {
+            self.push_error(tr_fmt(Msg::RevealInFinderFailed, &[&err]));
+        }
+        if mac_hotkey::take_summon_request() {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+            ui::focus_search_input(self, ctx);
+        }
         self.current_window_size = ctx.input(|i| i.viewport().inner_rect.map(|rect| rect.size()));
         if let Some(size) = self.pending_window_resize.take() {
             ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(size));
@@ -543,8 +2918,39 @@ impl eframe::App for DownloaderApp {
         self.settings_ui.poll_tool_updates();
         self.settings_ui.auto_refresh_if_needed();
         self.poll_input_mode_change();
+        self.poll_network_state();
+        self.poll_power_state();
         self.poll_download_events();
+        self.poll_conversion_events();
+        self.poll_proxy_events();
+        self.poll_mute_results();
+        self.poll_cloud_fetch();
+        self.poll_index_backup();
+        self.maybe_backup_index();
+        // 裏の作業は画面の操作がなくても終わるため、結果を受け取れるよう描き直しを予約する。
+        if self.proxy_batch.is_some()
+            || self.mute_batch.is_some()
+            || self.cloud_fetch.is_some()
+            || self.index_backup.is_some()
+        {
+            ctx.request_repaint_after(Duration::from_millis(250));
+        } else {
+            ctx.request_repaint_after(
+                self.next_index_backup_check
+                    .saturating_duration_since(Instant::now()),
+            );
+        }
+        self.detach_converting_download();
+        self.show_deferred_prompt();
+        self.poll_external_requests();
+        self.poll_osc_commands();
+        self.poll_watch_folder();
+        self.poll_subscriptions();
+        self.poll_status_item_actions(ctx);
         self.refresh_downloads_if_needed();
+        self.publish_script_status();
+        self.sync_dock_tile();
+        self.sync_status_item();
         self.poll_search_results();
         self.submit_search_if_needed();
         ui::render(self, ctx, _frame);
@@ -558,10 +2964,58 @@ impl eframe::App for DownloaderApp {
         }
         data.download_panel_width = format_dimension(self.download_panel_width.max(1.0));
         data.search_panel_width = format_dimension(self.search_panel_width.max(1.0));
+        data.search_window_detached = self.search_detached;
+        data.search_window_width = format_dimension(self.search_window_size.x.max(240.0));
+        data.search_window_height = format_dimension(self.search_window_size.y.max(320.0));
         let _ = save_settings(&data);
     }
 }
 
+// 検索対象フォルダに保存先を加えた、インデックスするフォルダ。保存先の一覧もインデックスから読む。
+// 保存先がすでに検索対象フォルダの中にある場合は加えない。プロキシのフォルダは作ってあれば加える。
+fn index_roots(search_roots: &[String], download_dir: &Path) -> Vec<PathBuf> {
+    let mut roots = search_roots.iter().map(PathBuf::from).collect::<Vec<_>>();
+    if !roots.iter().any(|root| download_dir.starts_with(root)) && ensure_dir(download_dir).is_ok()
+    {
+        roots.push(download_dir.to_path_buf());
+    }
+    let proxies = proxy_dir();
+    if proxies.is_dir() && !roots.iter().any(|root| proxies.starts_with(root)) {
+        roots.push(proxies);
+    }
+    roots
+}
+
+// シンボリックリンクをたどる検索対象フォルダ。保存先やプロキシのフォルダはたどらない。
+fn link_following_roots(settings: &SettingsData) -> Vec<PathBuf> {
+    settings
+        .search_follow_links
+        .iter()
+        .map(PathBuf::from)
+        .collect()
+}
+
+// 検索欄の入力から検索条件を組み立てる。
+fn search_request_for_query(
+    query: &str,
+    limit: usize,
+    after: Option<SearchCursor>,
+) -> SearchRequest {
+    let request = SearchRequest::from_query_text(query);
+    // 演算子だけのクエリは更新日時順で返す。
+    let sort = if request.query.is_empty() {
+        SearchSort::ModifiedDesc
+    } else {
+        SearchSort::NameAsc
+    };
+    SearchRequest {
+        limit,
+        sort,
+        after,
+        ..request
+    }
+}
+
 fn search_worker_loop(
     engine: SearchEngine,
     rx: mpsc::Receiver<SearchJob>,
@@ -576,6 +3030,7 @@ fn search_worker_loop(
         if tx
             .send(SearchJobResult {
                 seq: job.seq,
+                append: job.append,
                 result,
             })
             .is_err()
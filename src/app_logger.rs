@@ -1,36 +1,214 @@
+mod log_file;
+
 use std::collections::VecDeque;
+use std::path::Path;
 use std::time::{Duration, Instant};
 
 use time::OffsetDateTime;
 use time::macros::format_description;
 
+use crate::i18n::{Msg, tr};
+use crate::redact::redact_line;
+use log_file::LogFile;
+
 const MAX_ENTRIES: usize = 1000;
+// ログ画面の見出しに使う、ダウンロードの状態を覚えておく数
+const MAX_JOBS: usize = 100;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 4] = [
+        LogLevel::Debug,
+        LogLevel::Info,
+        LogLevel::Warn,
+        LogLevel::Error,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+        }
+    }
+
+    // 外部ツールの行は、行頭の印でレベルを決める。
+    // yt-dlp の WARNING:/ERROR:/[debug] と、ffmpeg の進捗行（frame=/size=）とエラー行を振り分ける。
+    fn of_tool_line(line: &str) -> Self {
+        let lower = line.to_ascii_lowercase();
+        if lower.starts_with("[debug]") || lower.starts_with("frame=") || lower.starts_with("size=")
+        {
+            LogLevel::Debug
+        } else if lower.starts_with("warning") {
+            LogLevel::Warn
+        } else if lower.starts_with("error") {
+            LogLevel::Error
+        } else {
+            LogLevel::Info
+        }
+    }
+}
+
+// ログの出どころ
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogSource {
+    App,
+    YtDlp,
+    Ffmpeg,
+}
+
+impl LogSource {
+    pub const ALL: [LogSource; 3] = [LogSource::App, LogSource::YtDlp, LogSource::Ffmpeg];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LogSource::App => "app",
+            LogSource::YtDlp => "yt-dlp",
+            LogSource::Ffmpeg => "ffmpeg",
+        }
+    }
+}
+
+pub struct LogRecord {
+    at: Instant,
+    // YYYY-MM-DD（ファイルへ書く行にだけ付ける）
+    date: String,
+    // HH:mm:ss
+    pub timestamp: String,
+    pub level: LogLevel,
+    pub source: LogSource,
+    // ダウンロード中の出力は、何件目のダウンロードかを持つ。
+    pub job_id: Option<u64>,
+    pub message: String,
+}
+
+impl LogRecord {
+    // [12:34:56] [INFO] [yt-dlp] #3 メッセージ
+    pub fn line(&self) -> String {
+        self.format_line(&self.timestamp)
+    }
+
+    // 日をまたいでも追えるよう、ファイルには日付も書く。
+    fn file_line(&self) -> String {
+        self.format_line(&format!("{} {}", self.date, self.timestamp))
+    }
+
+    fn format_line(&self, time: &str) -> String {
+        let job = self
+            .job_id
+            .map(|job_id| format!(" #{job_id}"))
+            .unwrap_or_default();
+        format!(
+            "[{time}] [{}] [{}]{job} {}",
+            self.level.label(),
+            self.source.label(),
+            self.message
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JobStatus {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn label(self) -> &'static str {
+        match self {
+            JobStatus::Running => tr(Msg::JobRunning),
+            JobStatus::Completed => tr(Msg::JobCompleted),
+            JobStatus::Failed => tr(Msg::JobFailed),
+            JobStatus::Cancelled => tr(Msg::JobCancelled),
+        }
+    }
+}
+
+// ログ画面でダウンロードごとにまとめるときの見出し
+pub struct JobSummary {
+    pub id: u64,
+    pub url: String,
+    pub status: JobStatus,
+}
 
 pub struct AppLogger {
-    entries: VecDeque<LogEntry>,
+    entries: VecDeque<LogRecord>,
+    jobs: VecDeque<JobSummary>,
+    // ログフォルダへの書き出し先。開けなかった場合は None。
+    file: Option<LogFile>,
 }
 
 impl AppLogger {
     pub fn new() -> Self {
         Self {
             entries: VecDeque::with_capacity(MAX_ENTRIES),
+            jobs: VecDeque::new(),
+            file: None,
         }
     }
 
-    pub fn push(&mut self, message: impl Into<String>) {
-        let message = message.into();
+    // 以降のログを dir のファイルにも書き出す。
+    pub fn write_to_dir(&mut self, dir: &Path) {
+        match LogFile::open(dir) {
+            Ok(file) => self.file = Some(file),
+            Err(err) => self.push(LogLevel::Error, err),
+        }
+    }
+
+    pub fn push(&mut self, level: LogLevel, message: impl Into<String>) {
+        self.push_record(level, LogSource::App, None, message);
+    }
+
+    // 外部ツールが出力した行。レベルは行頭の印で決める。
+    pub fn push_tool_line(
+        &mut self,
+        source: LogSource,
+        job_id: Option<u64>,
+        line: impl Into<String>,
+    ) {
+        let line = line.into();
+        self.push_record(LogLevel::of_tool_line(&line), source, job_id, line);
+    }
+
+    pub fn push_record(
+        &mut self,
+        level: LogLevel,
+        source: LogSource,
+        job_id: Option<u64>,
+        message: impl Into<String>,
+    ) {
+        // 署名付き URL やクッキーは、画面にもファイルにも残さない。
+        let message = redact_line(&message.into());
         if message.is_empty() {
             return;
         }
 
-        let timestamp = current_time_text();
-        let line = format!("[{timestamp}] {message}");
-        println!("{line}");
-
-        self.entries.push_back(LogEntry {
+        let (date, timestamp) = current_date_time();
+        let record = LogRecord {
             at: Instant::now(),
-            line,
-        });
+            date,
+            timestamp,
+            level,
+            source,
+            job_id,
+            message,
+        };
+        println!("{}", record.line());
+        if let Some(file) = self.file.as_mut() {
+            file.write_line(&record.file_line());
+        }
+
+        self.entries.push_back(record);
 
         while self.entries.len() > MAX_ENTRIES {
             self.entries.pop_front();
@@ -39,14 +217,51 @@ impl AppLogger {
 
     pub fn clear(&mut self) {
         self.entries.clear();
+        self.jobs.retain(|job| job.status == JobStatus::Running);
+    }
+
+    pub fn begin_job(&mut self, id: u64, url: &str) {
+        self.jobs.push_back(JobSummary {
+            id,
+            url: url.to_string(),
+            status: JobStatus::Running,
+        });
+        while self.jobs.len() > MAX_JOBS {
+            self.jobs.pop_front();
+        }
+    }
+
+    pub fn finish_job(&mut self, id: u64, status: JobStatus) {
+        if let Some(job) = self.jobs.iter_mut().find(|job| job.id == id) {
+            job.status = status;
+        }
+    }
+
+    pub fn job(&self, id: u64) -> Option<&JobSummary> {
+        self.jobs.iter().find(|job| job.id == id)
+    }
+
+    // 行をダウンロードごと（ダウンロード外の行は None）にまとめ、最初の行が出た順に並べる。
+    pub fn grouped_records(&self) -> Vec<(Option<u64>, Vec<&LogRecord>)> {
+        let mut groups: Vec<(Option<u64>, Vec<&LogRecord>)> = Vec::new();
+        for record in &self.entries {
+            match groups
+                .iter_mut()
+                .find(|(job_id, _)| *job_id == record.job_id)
+            {
+                Some((_, records)) => records.push(record),
+                None => groups.push((record.job_id, vec![record])),
+            }
+        }
+        groups
     }
 
     pub fn is_empty(&self) -> bool {
         self.entries.is_empty()
     }
 
-    pub fn lines(&self) -> impl Iterator<Item = &str> {
-        self.entries.iter().map(|entry| entry.line.as_str())
+    pub fn records(&self) -> impl Iterator<Item = &LogRecord> {
+        self.entries.iter()
     }
 
     pub fn build_recent_snapshot(&self, duration: Duration) -> String {
@@ -65,7 +280,7 @@ impl AppLogger {
             if !out.is_empty() {
                 out.push('\n');
             }
-            out.push_str(&entry.line);
+            out.push_str(&entry.line());
         }
         out
     }
@@ -77,13 +292,110 @@ impl Default for AppLogger {
     }
 }
 
-struct LogEntry {
-    at: Instant,
-    line: String,
+fn current_date_time() -> (String, String) {
+    let now = OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc());
+    let date = now
+        .format(&format_description!("[year]-[month]-[day]"))
+        .unwrap_or_else(|_| "0000-00-00".to_string());
+    let time = now
+        .format(&format_description!("[hour]:[minute]:[second]"))
+        .unwrap_or_else(|_| "00:00:00".to_string());
+    (date, time)
 }
 
-fn current_time_text() -> String {
-    let now = OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc());
-    now.format(&format_description!("[hour]:[minute]:[second]"))
-        .unwrap_or_else(|_| "00:00:00".to_string())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_level_source_and_job() {
+        let mut logger = AppLogger::new();
+        logger.push_tool_line(
+            LogSource::YtDlp,
+            Some(3),
+            "ERROR: [youtube] abc: Video unavailable",
+        );
+        logger.push_tool_line(LogSource::YtDlp, Some(3), "WARNING: nsig extraction failed");
+        logger.push_tool_line(
+            LogSource::Ffmpeg,
+            Some(3),
+            "frame=  120 fps= 60 q=-1.0 size=  1024kB",
+        );
+        // 行の途中の語ではレベルを決めない（ファイル名に failed を含む場合など）
+        logger.push_tool_line(
+            LogSource::YtDlp,
+            Some(3),
+            "[download] Destination: failed_take_error.mp4",
+        );
+        logger.push(
+            LogLevel::Error,
+            "Could not create the output folder: denied",
+        );
+        logger.push(LogLevel::Warn, "保存先の空き容量を確認できませんでした。");
+        logger.push(LogLevel::Info, "Downloading to /tmp");
+        logger.push(LogLevel::Info, "");
+
+        let levels = logger
+            .records()
+            .map(|record| (record.source, record.level))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            levels,
+            [
+                (LogSource::YtDlp, LogLevel::Error),
+                (LogSource::YtDlp, LogLevel::Warn),
+                (LogSource::Ffmpeg, LogLevel::Debug),
+                (LogSource::YtDlp, LogLevel::Info),
+                (LogSource::App, LogLevel::Error),
+                (LogSource::App, LogLevel::Warn),
+                (LogSource::App, LogLevel::Info),
+            ]
+        );
+        let first = logger.records().next().unwrap();
+        assert!(
+            first
+                .line()
+                .ends_with("] [ERROR] [yt-dlp] #3 ERROR: [youtube] abc: Video unavailable")
+        );
+        assert!(
+            logger
+                .records()
+                .last()
+                .unwrap()
+                .line()
+                .ends_with("] [INFO] [app] Downloading to /tmp")
+        );
+    }
+
+    #[test]
+    fn groups_records_by_job() {
+        let mut logger = AppLogger::new();
+        logger.push(LogLevel::Info, "起動しました");
+        logger.begin_job(1, "https://example.com/a");
+        logger.push_record(
+            LogLevel::Info,
+            LogSource::App,
+            Some(1),
+            "Downloading to /tmp",
+        );
+        logger.begin_job(2, "https://example.com/b");
+        logger.push_tool_line(LogSource::YtDlp, Some(2), "[download] 10%");
+        logger.push_tool_line(LogSource::Ffmpeg, Some(1), "frame=1");
+        logger.push(LogLevel::Info, "パスをコピーしました");
+        logger.finish_job(1, JobStatus::Failed);
+
+        let groups = logger
+            .grouped_records()
+            .into_iter()
+            .map(|(job_id, records)| (job_id, records.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(groups, [(None, 2), (Some(1), 2), (Some(2), 1)]);
+        assert_eq!(logger.job(1).unwrap().status, JobStatus::Failed);
+        assert_eq!(logger.job(2).unwrap().status, JobStatus::Running);
+
+        // 表示をクリアしても、実行中のダウンロードの見出しは残す。
+        logger.clear();
+        assert!(logger.job(1).is_none());
+        assert_eq!(logger.job(2).unwrap().url, "https://example.com/b");
+    }
 }
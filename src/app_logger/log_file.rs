@@ -0,0 +1,100 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::i18n::{Msg, tr_fmt};
+
+// 1 ファイルの上限。超えたら古いファイルへ回す。
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+// vjdownloader.log に加えて残す古いファイルの数
+const KEEP_OLD_FILES: usize = 4;
+const FILE_STEM: &str = "vjdownloader";
+
+// ログフォルダへ 1 行ずつ追記し、大きくなったら vjdownloader.1.log〜 へ回す。
+pub(super) struct LogFile {
+    dir: PathBuf,
+    file: File,
+    size: u64,
+}
+
+impl LogFile {
+    pub(super) fn open(dir: &Path) -> Result<Self, String> {
+        fs::create_dir_all(dir).map_err(|err| tr_fmt(Msg::LogDirCreateFailed, &[&err]))?;
+        let file = open_current(dir)?;
+        let size = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            file,
+            size,
+        })
+    }
+
+    // 書き込めなかった場合も、画面のログは続けられるようにエラーは返さない。
+    pub(super) fn write_line(&mut self, line: &str) {
+        let bytes = line.len() as u64 + 1;
+        if self.size > 0 && self.size + bytes > MAX_FILE_BYTES {
+            self.rotate();
+        }
+        if writeln!(self.file, "{line}").is_ok() {
+            self.size += bytes;
+        }
+    }
+
+    fn rotate(&mut self) {
+        let _ = fs::remove_file(rotated_path(&self.dir, KEEP_OLD_FILES));
+        for index in (1..KEEP_OLD_FILES).rev() {
+            let _ = fs::rename(
+                rotated_path(&self.dir, index),
+                rotated_path(&self.dir, index + 1),
+            );
+        }
+        let _ = fs::rename(current_path(&self.dir), rotated_path(&self.dir, 1));
+        if let Ok(file) = open_current(&self.dir) {
+            self.file = file;
+            self.size = 0;
+        }
+    }
+}
+
+fn current_path(dir: &Path) -> PathBuf {
+    dir.join(format!("{FILE_STEM}.log"))
+}
+
+fn rotated_path(dir: &Path, index: usize) -> PathBuf {
+    dir.join(format!("{FILE_STEM}.{index}.log"))
+}
+
+fn open_current(dir: &Path) -> Result<File, String> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(current_path(dir))
+        .map_err(|err| tr_fmt(Msg::LogFileOpenFailed, &[&err]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotates_when_file_is_full() {
+        let dir = std::env::temp_dir().join(format!("vjd-log-rotation-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let mut log = LogFile::open(&dir).unwrap();
+        let line = "x".repeat(1023);
+        // 1 行 1024 バイトで、ちょうど 1 ファイル分を書いた後の 1 行で回る。
+        for _ in 0..(MAX_FILE_BYTES / 1024) * 6 + 1 {
+            log.write_line(&line);
+        }
+
+        assert_eq!(fs::metadata(current_path(&dir)).unwrap().len(), 1024);
+        for index in 1..=KEEP_OLD_FILES {
+            assert_eq!(
+                fs::metadata(rotated_path(&dir, index)).unwrap().len(),
+                MAX_FILE_BYTES
+            );
+        }
+        assert!(!rotated_path(&dir, KEEP_OLD_FILES + 1).exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
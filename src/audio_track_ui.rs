@@ -0,0 +1,91 @@
+use eframe::egui;
+
+use crate::app::DownloaderApp;
+use crate::cursor::pointing;
+use crate::download::AudioTrackPrompt;
+use crate::i18n::{Msg, tr};
+
+pub struct AudioTrackPromptState {
+    pub prompt: AudioTrackPrompt,
+    selected: usize,
+}
+
+impl AudioTrackPromptState {
+    pub fn new(prompt: AudioTrackPrompt) -> Self {
+        Self {
+            prompt,
+            selected: 0,
+        }
+    }
+}
+
+pub fn render_audio_track_prompt(
+    // 選択待ちの音声トラックを持つアプリ
+    app: &mut DownloaderApp,
+    // ウィンドウ表示に使うコンテキスト
+    ctx: &egui::Context,
+) {
+    let Some(state) = app.audio_track_prompt.as_mut() else {
+        return;
+    };
+
+    let mut open = true;
+    let mut answer = None;
+    egui::Window::new(tr(Msg::ChooseAudioTrack))
+        .collapsible(false)
+        .resizable(false)
+        .default_width(360.0)
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .open(&mut open)
+        .show(ctx, |ui| {
+            ui.label(
+                egui::RichText::new(tr(Msg::MultipleAudioTracks))
+                    .size(11.5)
+                    .color(egui::Color32::from_rgb(140, 150, 170)),
+            );
+            ui.add_space(8.0);
+
+            for (index, track) in state.prompt.tracks.iter().enumerate() {
+                let _ = pointing(ui.radio_value(&mut state.selected, index, &track.label));
+            }
+
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let apply_btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::UseThisTrack))
+                            .size(12.5)
+                            .color(egui::Color32::from_rgb(8, 14, 24)),
+                    )
+                    .fill(egui::Color32::from_rgb(16, 190, 255));
+                    if pointing(ui.add(apply_btn)).clicked() {
+                        answer = state
+                            .prompt
+                            .tracks
+                            .get(state.selected)
+                            .map(|track| Some(track.language.clone()));
+                    }
+
+                    let default_btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::DefaultAudio))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(180, 190, 210)),
+                    )
+                    .fill(egui::Color32::from_rgb(24, 30, 45));
+                    if pointing(ui.add(default_btn)).clicked() {
+                        answer = Some(None);
+                    }
+                });
+            });
+        });
+
+    // 閉じた場合は既定の音声でダウンロードする。
+    if !open {
+        answer = Some(None);
+    }
+    if let Some(language) = answer
+        && let Some(state) = app.audio_track_prompt.take()
+    {
+        state.prompt.respond(language);
+    }
+}
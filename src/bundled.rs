@@ -1,42 +1,49 @@
-use std::fs;
-use std::os::unix::fs::PermissionsExt;
-use std::path::Path;
-
-use crate::paths::{ffmpeg_path, ffprobe_path};
-
-const BUNDLED_FFMPEG: &[u8] =
-    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/bin/ffmpeg"));
-const BUNDLED_FFPROBE: &[u8] =
-    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/bin/ffprobe"));
+// 同梱の ffmpeg/ffprobe は macOS 向けのバイナリのため、macOS でだけ展開する。
+// 他の OS では設定画面から取得したものか、PATH 上のものを使う（`paths::ffmpeg_path`）。
+#[cfg(target_os = "macos")]
+mod imp {
+    use std::fs;
+    use std::path::Path;
+
+    use crate::paths::{ffmpeg_path, ffmpeg_update_marker_path, ffprobe_path};
+    use crate::platform::ensure_executable;
+
+    const BUNDLED_FFMPEG: &[u8] =
+        include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/bin/ffmpeg"));
+    const BUNDLED_FFPROBE: &[u8] =
+        include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/bin/ffprobe"));
+
+    pub fn ensure_bundled_tools() -> Result<(), String> {
+        // 設定画面で更新した ffmpeg は同梱版で上書きしない。
+        let keep_existing = ffmpeg_update_marker_path().exists();
+        ensure_bundled_bin(&ffmpeg_path(), BUNDLED_FFMPEG, keep_existing)?;
+        ensure_bundled_bin(&ffprobe_path(), BUNDLED_FFPROBE, keep_existing)?;
+        Ok(())
+    }
 
-pub fn ensure_bundled_tools() -> Result<(), String> {
-    ensure_bundled_bin(&ffmpeg_path(), BUNDLED_FFMPEG)?;
-    ensure_bundled_bin(&ffprobe_path(), BUNDLED_FFPROBE)?;
-    Ok(())
-}
+    fn ensure_bundled_bin(path: &Path, bytes: &[u8], keep_existing: bool) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+        }
 
-fn ensure_bundled_bin(path: &Path, bytes: &[u8]) -> Result<(), String> {
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent).map_err(|err| err.to_string())?;
-    }
+        let needs_write = match fs::metadata(path) {
+            Ok(_) if keep_existing => false,
+            Ok(meta) => meta.len() != bytes.len() as u64,
+            Err(_) => true,
+        };
 
-    let needs_write = match fs::metadata(path) {
-        Ok(meta) => meta.len() != bytes.len() as u64,
-        Err(_) => true,
-    };
+        if needs_write {
+            fs::write(path, bytes).map_err(|err| err.to_string())?;
+        }
 
-    if needs_write {
-        fs::write(path, bytes).map_err(|err| err.to_string())?;
+        ensure_executable(path)
     }
+}
 
-    let mut perms = fs::metadata(path)
-        .map_err(|err| err.to_string())?
-        .permissions();
-    let mode = perms.mode();
-    if mode & 0o111 != 0o111 {
-        perms.set_mode(mode | 0o111);
-        fs::set_permissions(path, perms).map_err(|err| err.to_string())?;
-    }
+#[cfg(target_os = "macos")]
+pub use imp::ensure_bundled_tools;
 
+#[cfg(not(target_os = "macos"))]
+pub fn ensure_bundled_tools() -> Result<(), String> {
     Ok(())
 }
@@ -0,0 +1,113 @@
+use eframe::egui;
+use url::Url;
+
+use crate::app::DownloaderApp;
+use crate::cursor::pointing;
+use crate::download::{parse_timestamp_list, read_clipboard_text};
+use crate::i18n::{Msg, tr};
+
+// タイムスタンプの一覧で分割してダウンロードする指定の入力
+pub struct ChapterListState {
+    url: String,
+    timestamps: String,
+    // 一覧を読めなかった理由。直すまでダウンロードを始めない。
+    error: Option<String>,
+}
+
+impl ChapterListState {
+    // タイムスタンプを貼り付けるとクリップボードが置き換わるため、開いた時点の URL を先に入れておく。
+    pub fn from_clipboard() -> Self {
+        let url = read_clipboard_text()
+            .filter(|text| {
+                Url::parse(text).is_ok_and(|url| matches!(url.scheme(), "http" | "https"))
+            })
+            .unwrap_or_default();
+        Self {
+            url,
+            timestamps: String::new(),
+            error: None,
+        }
+    }
+}
+
+pub fn render_chapter_list(
+    // 入力中のタイムスタンプの一覧を持つアプリ
+    app: &mut DownloaderApp,
+    // ウィンドウ表示に使うコンテキスト
+    ctx: &egui::Context,
+) {
+    let Some(state) = app.chapter_list.as_mut() else {
+        return;
+    };
+
+    let mut open = true;
+    let mut submit = None;
+    egui::Window::new(tr(Msg::SplitByTimestamps))
+        .collapsible(false)
+        .resizable(true)
+        .default_width(460.0)
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .open(&mut open)
+        .show(ctx, |ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut state.url)
+                    .hint_text(tr(Msg::SplitUrlHint))
+                    .desired_width(f32::INFINITY),
+            );
+            ui.add_space(6.0);
+            ui.label(
+                egui::RichText::new(tr(Msg::SplitByTimestampsNotice))
+                    .size(11.5)
+                    .color(egui::Color32::from_rgb(140, 150, 170)),
+            );
+            egui::ScrollArea::vertical()
+                .max_height(280.0)
+                .show(ui, |ui| {
+                    let edited = ui
+                        .add(
+                            egui::TextEdit::multiline(&mut state.timestamps)
+                                .hint_text("0:00 Intro\n3:15 Track 1\n7:42 Track 2")
+                                .font(egui::TextStyle::Monospace)
+                                .desired_width(f32::INFINITY)
+                                .desired_rows(8),
+                        )
+                        .changed();
+                    if edited {
+                        state.error = None;
+                    }
+                });
+            if let Some(error) = &state.error {
+                ui.label(
+                    egui::RichText::new(error)
+                        .size(11.5)
+                        .color(egui::Color32::from_rgb(248, 113, 113)),
+                );
+            }
+
+            ui.add_space(10.0);
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                let download_btn = egui::Button::new(
+                    egui::RichText::new(tr(Msg::SplitAndDownload))
+                        .size(12.5)
+                        .color(egui::Color32::from_rgb(8, 14, 24)),
+                )
+                .fill(egui::Color32::from_rgb(16, 190, 255));
+                let ready = !state.url.trim().is_empty();
+                if pointing(ui.add_enabled(ready, download_btn)).clicked() {
+                    match parse_timestamp_list(&state.timestamps) {
+                        Ok(_) => {
+                            submit = Some((state.url.trim().to_string(), state.timestamps.clone()));
+                        }
+                        Err(err) => state.error = Some(err),
+                    }
+                }
+            });
+        });
+
+    if let Some((url, timestamps)) = submit {
+        app.chapter_list = None;
+        app.start_download_split_by_timestamps(url, timestamps);
+    } else if !open {
+        app.chapter_list = None;
+    }
+}
@@ -0,0 +1,63 @@
+// iCloud Drive・Dropbox などの「オンライン専用」ファイル（中身がまだクラウドにだけあるファイル）の扱い。
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+use crate::platform::is_dataless;
+
+// 一度に読む大きさ
+const READ_CHUNK_SIZE: usize = 1024 * 1024;
+
+// 中身がまだクラウドにだけあるファイルか。読み込むと OS がダウンロードを始める。
+pub fn is_cloud_only(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|meta| is_dataless(&meta))
+}
+
+// 最後まで読み込み、OS（File Provider）にクラウドから取得させる。読んだ中身は捨てる。
+fn materialize(path: &Path) -> Result<(), String> {
+    let mut file = File::open(path).map_err(|err| err.to_string())?;
+    let mut buf = vec![0_u8; READ_CHUNK_SIZE];
+    loop {
+        match file.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(_) => {}
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err.to_string()),
+        }
+    }
+}
+
+// 選んだファイルのうちクラウドにだけあるものを順に取得し、1 件ごとに結果を送る。すべて終えると送信側を閉じる。
+// 取得済みのファイルは読まずに飛ばす。
+pub fn fetch_cloud_files(paths: Vec<PathBuf>, tx: mpsc::Sender<(PathBuf, Result<(), String>)>) {
+    for path in paths {
+        if !is_cloud_only(&path) {
+            continue;
+        }
+        let result = materialize(&path);
+        if tx.send((path, result)).is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn skips_files_already_on_disk() {
+        let dir = tempdir().unwrap();
+        let clip = dir.path().join("clip.mp4");
+        fs::write(&clip, vec![1_u8; READ_CHUNK_SIZE + 10]).unwrap();
+        assert!(!is_cloud_only(&clip));
+        materialize(&clip).unwrap();
+        assert!(materialize(&dir.path().join("missing.mp4")).is_err());
+
+        let (tx, rx) = mpsc::channel();
+        fetch_cloud_files(vec![clip], tx);
+        assert_eq!(rx.iter().count(), 0);
+    }
+}
@@ -0,0 +1,84 @@
+use eframe::egui;
+
+use crate::app::DownloaderApp;
+use crate::cursor::pointing;
+use crate::download::CollisionAction;
+use crate::i18n::{Msg, tr};
+
+pub fn render_collision_prompt(
+    // 同名ファイルの扱いの返答待ちを持つアプリ
+    app: &mut DownloaderApp,
+    // ウィンドウ表示に使うコンテキスト
+    ctx: &egui::Context,
+) {
+    let Some(prompt) = app.collision_prompt.as_ref() else {
+        return;
+    };
+
+    let mut open = true;
+    let mut answer = None;
+    egui::Window::new(tr(Msg::FileExists))
+        .collapsible(false)
+        .resizable(false)
+        .default_width(380.0)
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .open(&mut open)
+        .show(ctx, |ui| {
+            ui.label(
+                egui::RichText::new(tr(Msg::FileExistsMessage))
+                    .size(11.5)
+                    .color(egui::Color32::from_rgb(140, 150, 170)),
+            );
+            ui.add_space(6.0);
+            ui.label(
+                egui::RichText::new(&prompt.file_name)
+                    .size(12.0)
+                    .color(egui::Color32::from_rgb(220, 230, 245)),
+            );
+
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let rename_btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::SaveWithNumber))
+                            .size(12.5)
+                            .color(egui::Color32::from_rgb(8, 14, 24)),
+                    )
+                    .fill(egui::Color32::from_rgb(16, 190, 255));
+                    if pointing(ui.add(rename_btn)).clicked() {
+                        answer = Some(CollisionAction::Rename);
+                    }
+
+                    let overwrite_btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::Overwrite))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(180, 190, 210)),
+                    )
+                    .fill(egui::Color32::from_rgb(24, 30, 45));
+                    if pointing(ui.add(overwrite_btn)).clicked() {
+                        answer = Some(CollisionAction::Overwrite);
+                    }
+
+                    let skip_btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::DontSave))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(180, 190, 210)),
+                    )
+                    .fill(egui::Color32::from_rgb(24, 30, 45));
+                    if pointing(ui.add(skip_btn)).clicked() {
+                        answer = Some(CollisionAction::Skip);
+                    }
+                });
+            });
+        });
+
+    // 閉じた場合は番号を付けて保存する。
+    if !open {
+        answer = Some(CollisionAction::Rename);
+    }
+    if let Some(action) = answer
+        && let Some(prompt) = app.collision_prompt.take()
+    {
+        prompt.respond(action);
+    }
+}
@@ -0,0 +1,103 @@
+use eframe::egui;
+
+use crate::app::DownloaderApp;
+use crate::cursor::pointing;
+use crate::download::CropPreview;
+use crate::i18n::{Msg, tr, tr_fmt};
+
+pub struct CropPromptState {
+    pub preview: CropPreview,
+    texture: Option<egui::TextureHandle>,
+}
+
+impl CropPromptState {
+    pub fn new(preview: CropPreview) -> Self {
+        Self {
+            preview,
+            texture: None,
+        }
+    }
+
+    // プレビュー画像は最初の描画時にテクスチャへ読み込む。
+    fn texture(&mut self, ctx: &egui::Context) -> &egui::TextureHandle {
+        self.texture.get_or_insert_with(|| {
+            let image = egui::ColorImage::from_rgba_unmultiplied(
+                self.preview.image_size,
+                &self.preview.rgba,
+            );
+            ctx.load_texture("crop-preview", image, egui::TextureOptions::LINEAR)
+        })
+    }
+}
+
+pub fn render_crop_prompt(
+    // 確認待ちの切り取り範囲を持つアプリ
+    app: &mut DownloaderApp,
+    // ウィンドウ表示とテクスチャ読み込みに使うコンテキスト
+    ctx: &egui::Context,
+) {
+    let Some(state) = app.crop_prompt.as_mut() else {
+        return;
+    };
+
+    let mut open = true;
+    let mut answer = None;
+    egui::Window::new(tr(Msg::CropBlackBars))
+        .collapsible(false)
+        .resizable(false)
+        .default_width(500.0)
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .open(&mut open)
+        .show(ctx, |ui| {
+            let crop = state.preview.crop;
+            let [source_width, source_height] = state.preview.source_size;
+            ui.label(
+                egui::RichText::new(tr_fmt(
+                    Msg::CropDetected,
+                    &[&source_width, &source_height, &crop.width, &crop.height],
+                ))
+                .size(11.5)
+                .color(egui::Color32::from_rgb(140, 150, 170)),
+            );
+            ui.add_space(8.0);
+
+            let texture = state.texture(ctx);
+            let size = texture.size_vec2();
+            ui.image((texture.id(), size));
+
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let apply_btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::Crop))
+                            .size(12.5)
+                            .color(egui::Color32::from_rgb(8, 14, 24)),
+                    )
+                    .fill(egui::Color32::from_rgb(16, 190, 255));
+                    if pointing(ui.add(apply_btn)).clicked() {
+                        answer = Some(true);
+                    }
+
+                    let skip_btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::DontCrop))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(180, 190, 210)),
+                    )
+                    .fill(egui::Color32::from_rgb(24, 30, 45));
+                    if pointing(ui.add(skip_btn)).clicked() {
+                        answer = Some(false);
+                    }
+                });
+            });
+        });
+
+    // 閉じた場合は切り取らずに変換を続ける。
+    if !open {
+        answer = Some(false);
+    }
+    if let Some(apply) = answer
+        && let Some(state) = app.crop_prompt.take()
+    {
+        state.preview.respond(apply);
+    }
+}
@@ -0,0 +1,146 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+use crate::i18n::{Msg, tr_fmt};
+use crate::paths::{last_failed_command_path, log_dir};
+use crate::platform::create_zip;
+use crate::redact::redact_args;
+use crate::settings::redacted_settings_toml;
+use crate::settings_ui::{ToolKind, read_tool_version, tool_path};
+use crate::updater::CURRENT_VERSION;
+
+pub const DEFAULT_BUG_REPORT_NAME: &str = "VJDownloader-report.zip";
+// バグレポートの recent.log に含める画面のログの範囲
+pub const BUG_REPORT_LOG_WINDOW: Duration = Duration::from_secs(60 * 60);
+// ZIP の中のフォルダ名
+const BUNDLE_DIR_NAME: &str = "VJDownloader-report";
+// ターミナルへ貼り付けて実行できるよう、空白や記号を含む引数はシングルクォートで囲む。
+pub fn shell_join(args: &[String]) -> String {
+    args.iter()
+        .map(|arg| {
+            let plain = !arg.is_empty()
+                && arg
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphanumeric() || "-_./:=,+@%".contains(ch));
+            if plain {
+                arg.clone()
+            } else {
+                format!("'{}'", arg.replace('\'', r"'\''"))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// 失敗した yt-dlp の実行を、秘密の値を伏せて書き残す。次に失敗するまで上書きしない。
+pub fn record_failed_yt_dlp_command(yt_dlp: &Path, args: &[String]) {
+    let mut command = vec![yt_dlp.to_string_lossy().to_string()];
+    command.extend(redact_args(args));
+    let _ = fs::write(last_failed_command_path(), shell_join(&command) + "\n");
+}
+
+// バグレポートを dest の ZIP に書き出す。
+// 直近のログ、ログフォルダ、ツールのバージョン、伏せ字にした設定、OS の情報、最後に失敗した yt-dlp の実行を含める。
+pub fn export_bundle(dest: &Path, recent_log: &str) -> Result<(), String> {
+    let work_dir = std::env::temp_dir().join(format!("vjdownloader-report-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&work_dir);
+    let report_dir = work_dir.join(BUNDLE_DIR_NAME);
+    let result = write_bundle_files(&report_dir, recent_log).and_then(|()| {
+        // 既存の ZIP に追記されないよう、先に消しておく。
+        let _ = fs::remove_file(dest);
+        create_zip(&report_dir, dest)
+    });
+    let _ = fs::remove_dir_all(&work_dir);
+    result
+}
+
+fn write_bundle_files(report_dir: &Path, recent_log: &str) -> Result<(), String> {
+    let write = |name: &str, contents: &str| {
+        fs::write(report_dir.join(name), contents)
+            .map_err(|err| tr_fmt(Msg::BugReportWriteFailed, &[&name, &err]))
+    };
+    fs::create_dir_all(report_dir.join("logs"))
+        .map_err(|err| tr_fmt(Msg::BugReportWorkDirFailed, &[&err]))?;
+
+    write("system.txt", &system_info())?;
+    write("recent.log", recent_log)?;
+    write(
+        "settings.toml",
+        &redacted_settings_toml()
+            .unwrap_or_else(|err| format!("# {}\n", tr_fmt(Msg::BugReportSettingsFailed, &[&err]))),
+    )?;
+    if let Ok(command) = fs::read_to_string(last_failed_command_path()) {
+        write("last_failed_yt_dlp_command.txt", &command)?;
+    }
+    if let Ok(entries) = fs::read_dir(log_dir()) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "log") {
+                let _ = fs::copy(&path, report_dir.join("logs").join(entry.file_name()));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn system_info() -> String {
+    let mut lines = vec![
+        format!("VJDownloader {CURRENT_VERSION}"),
+        format!(
+            "OS: {} {} ({})",
+            std::env::consts::OS,
+            os_version(),
+            std::env::consts::ARCH
+        ),
+    ];
+    for (label, kind) in [
+        ("yt-dlp", ToolKind::YtDlp),
+        ("ffmpeg", ToolKind::Ffmpeg),
+        ("deno", ToolKind::Deno),
+    ] {
+        let version = read_tool_version(kind, &tool_path(kind))
+            .unwrap_or_else(|err| tr_fmt(Msg::BugReportToolVersionUnknown, &[&err]));
+        lines.push(format!("{label}: {version}"));
+    }
+    lines.join("\n") + "\n"
+}
+
+// macOS は sw_vers の製品バージョン。取得できない場合は空欄。
+fn os_version() -> String {
+    if !cfg!(target_os = "macos") {
+        return String::new();
+    }
+    Command::new("sw_vers")
+        .arg("-productVersion")
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_secret_options() {
+        let args = [
+            "--cookies-from-browser",
+            "chrome:Profile 1",
+            "--username",
+            "vj",
+            "--password=hunter2",
+            "-f",
+            "bv*+ba/b",
+            "https://example.com/watch?v=abc",
+        ]
+        .map(str::to_string);
+        let redacted = redact_args(&args);
+        assert_eq!(
+            shell_join(&redacted),
+            "--cookies-from-browser '<redacted>' --username '<redacted>' \
+             '--password=<redacted>' -f 'bv*+ba/b' 'https://example.com/watch?v=abc'"
+        );
+    }
+}
@@ -1,100 +1,160 @@
 mod animethemes;
+mod aria2;
+mod audio_track;
+mod cancel;
+mod chapter_split;
+mod conversion_slots;
+mod convert;
+mod crop;
+mod disk_space;
+mod dry_run;
+mod encoder;
+mod format_choice;
+mod fragments;
+mod history;
+mod hwaccel;
+mod import;
+mod known_error;
+mod mute;
+mod netrc;
+mod preview;
 mod process;
+mod proxy;
+mod queue;
+mod recovery;
+mod runner;
+mod scene_split;
+mod stage;
 mod staging;
+mod subscription;
 mod tools;
+mod trial;
+mod validate;
+mod watchdog;
+mod watermark;
 
 use arboard::Clipboard;
-use std::fs;
 use std::path::PathBuf;
-use std::process::{Child, Command};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, Mutex, mpsc};
+use std::sync::{Arc, mpsc};
 use std::thread;
 use std::time::{Duration, Instant};
 
+use crate::app_logger::{LogLevel, LogSource};
 use crate::bundled::ensure_bundled_tools;
-use crate::fs_utils::{ensure_dir, is_executable};
-use crate::paths::{ffmpeg_path, yt_dlp_path};
-
-pub use tools::{ensure_deno, ensure_yt_dlp, update_deno, update_yt_dlp};
+use crate::fs_utils::ensure_dir;
+use crate::i18n::{Msg, tr, tr_fmt};
+use crate::network;
+use crate::paths::{download_archive_path, ffmpeg_path, yt_dlp_path};
+use crate::platform::is_executable;
+use crate::settings::{
+    DownloadPreset, EncoderProfile, load_audio_track_prompt, load_collision_policy,
+    load_concurrent_fragments, load_download_max_height, load_encoder_profile, load_preview_prompt,
+    load_use_aria2c, load_yt_dlp_extra_args,
+};
+
+pub use aria2::detect_aria2c;
+pub use audio_track::AudioTrackPrompt;
+pub use cancel::CancellationToken;
+pub use chapter_split::parse_timestamp_list;
+pub use crop::CropPreview;
+pub use dry_run::{DryRunReport, build_dry_run};
+pub use encoder::{DEFAULT_TARGET_SIZE_MB, QualityMode, detect_encoder_profile, encoder_label};
+pub use format_choice::{FormatKind, FormatPrompt, megabytes_label};
+pub use known_error::KnownError;
+pub use mute::{AudioReplacement, MuteEvent, replace_audio_files};
+pub use netrc::{dry_run_login_args, netrc_login_args, remove_stale_login_files};
+pub use preview::{LONG_DURATION_SECONDS, MetadataPreview};
+pub use proxy::{ProxyEvent, generate_proxies};
+pub use queue::{FailedJobs, JobQueue};
+pub use recovery::{JobRecord, JobStage, clear_job_record, load_interrupted_job};
+pub use stage::{DownloadStage, FailureKind, reported_progress};
+pub use staging::{CollisionAction, CollisionPrompt, STALE_STAGING_AGE, remove_stale_staging_dirs};
+pub use subscription::{NewUpload, check_subscription};
+pub use tools::{ensure_deno, ensure_yt_dlp, update_deno, update_ffmpeg, update_yt_dlp};
+pub use trial::{TrialReport, run_trial_download};
+pub use watchdog::STALL_RETRY_LIMIT;
 
 pub enum DownloadEvent {
-    Log(String),
-    Progress(ProgressUpdate),
-    Done(Result<(), String>, String),
+    // アプリが出すログ行。レベルは送る側で決める。
+    Log(LogLevel, String),
+    // yt-dlp・ffmpeg などの外部ツールが出力した行
+    ToolLog(LogSource, String),
+    // 段階が変わった、または段階の中で進んだ。経過時間は表示用の文字列。
+    // 完了・失敗（DownloadStage::is_finished）は最後に 1 度だけ届く。
+    Stage(DownloadStage, String),
+    // 完了の表示を終えて、進捗パネルを待機中に戻す
+    Idle,
+    // 黒帯の切り取りを確認してもらう。返答があるまで変換は止まる。
+    CropPreview(CropPreview),
+    // 使う音声トラックを選んでもらう。返答があるまでダウンロードは始めない。
+    AudioTrackPrompt(AudioTrackPrompt),
+    // 動画の情報を見て、ダウンロードするか決めてもらう。返答があるまでダウンロードは始めない。
+    MetadataPreview(MetadataPreview),
+    // 取得するフォーマットを選んでもらう。返答があるまでダウンロードは始めない。
+    FormatPrompt(FormatPrompt),
+    // 保存先の同名ファイルの扱いを選んでもらう。返答があるまで保存先へ移さない。
+    CollisionPrompt(CollisionPrompt),
+    // 以前にダウンロード済みのためスキップした。保存したファイルが分からない場合は None。
+    AlreadyDownloaded(Option<PathBuf>),
+    // yt-dlp の出力に、設定で対処できるエラーが見つかった
+    KnownError(KnownError),
+    // 取得を終えて変換に移った。これ以降の変換中に、次のダウンロードを始めてよい。
+    Downloaded,
 }
 
-pub(crate) const CANCELLED_ERROR: &str = "__CANCELLED__";
-
-#[derive(Clone, Debug)]
-pub struct ProgressUpdate {
-    pub message: String,
-    pub progress: f32,
-    pub visible: bool,
+// ダウンロード処理が途中で終わった理由。取り消しとオフラインは失敗と区別して扱う。
+#[derive(Debug, PartialEq)]
+enum PipelineError {
+    Cancelled,
+    Offline,
+    Failed(String),
 }
 
-impl ProgressUpdate {
-    pub fn info_video_metadata(elapsed: &str) -> Self {
-        Self {
-            message: format!("動画情報確認中・・・{}", format_elapsed(elapsed)),
-            progress: -1.0,
-            visible: true,
-        }
-    }
-
-    pub fn info_loading(elapsed: &str) -> Self {
-        Self {
-            message: format!("動画読み込み中...{}", format_elapsed(elapsed)),
-            progress: -1.0,
-            visible: true,
-        }
-    }
-
-    pub fn downloading(percent: f32, elapsed: &str) -> Self {
-        let clamped = percent.clamp(0.0, 100.0);
-        Self {
-            message: format!(
-                "ダウンロード中... {:.1}%{}",
-                clamped,
-                format_elapsed(elapsed)
-            ),
-            progress: clamped / 100.0,
-            visible: true,
-        }
-    }
-
-    pub fn post_processing(elapsed: &str) -> Self {
-        Self {
-            message: format!("変換中...{}", format_elapsed(elapsed)),
-            progress: -1.0,
-            visible: true,
-        }
-    }
-
-    pub fn converting(percent: f32, elapsed: &str) -> Self {
-        let clamped = percent.clamp(0.0, 100.0);
-        Self {
-            message: format!("変換中... {:.1}%{}", clamped, format_elapsed(elapsed)),
-            progress: clamped / 100.0,
-            visible: true,
-        }
+impl From<String> for PipelineError {
+    fn from(err: String) -> Self {
+        PipelineError::Failed(err)
     }
+}
 
-    pub fn completed(elapsed: &str) -> Self {
-        Self {
-            message: format!("ダウンロード完了!{}", format_elapsed(elapsed)),
-            progress: 1.0,
-            visible: true,
+// 画面からの返答を待つ。キャンセルされた場合は中断し、画面が閉じられた場合は None を返す。
+pub(super) fn wait_for_reply<T>(
+    answer: &mpsc::Receiver<T>,
+    cancel: &CancellationToken,
+) -> Result<Option<T>, String> {
+    loop {
+        cancel.check()?;
+        match answer.recv_timeout(Duration::from_millis(200)) {
+            Ok(value) => return Ok(Some(value)),
+            Err(mpsc::RecvTimeoutError::Disconnected) => return Ok(None),
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
         }
     }
+}
 
-    pub fn hidden() -> Self {
-        Self {
-            message: String::new(),
-            progress: 0.0,
-            visible: false,
-        }
-    }
+// 待機キューに積む 1 件分のダウンロード指定
+#[derive(Clone, Debug, PartialEq)]
+pub struct DownloadJob {
+    pub url: String,
+    pub quality: QualityMode,
+    // この項目だけ保存先を変える場合のフォルダ。None は開始時点の設定の保存先。
+    pub output_dir: Option<PathBuf>,
+    // クッキーを取得するアカウント名。空欄は設定のクッキー設定。
+    pub cookie_account: String,
+    // ダウンロード前に形式を一覧して選ぶ
+    pub choose_format: bool,
+    // 中断したダウンロードを再開する場合の作業フォルダ
+    pub resume_staging_dir: Option<PathBuf>,
+    // 待機キューで他の項目より先に実行する
+    pub priority: bool,
+    // プリセットのボタンから追加した場合の、この項目だけに使うエンコード方式と高さの上限
+    pub preset: Option<DownloadPreset>,
+    // 監視フォルダから取り込む動画。URL の代わりにこのファイルを変換して保存先へ移す。
+    pub source_file: Option<PathBuf>,
+    // 貼り付けたタイムスタンプの一覧。保存後にこの一覧のチャプターごとに分割する。
+    pub split_timestamps: Option<String>,
+    // 応答がなかったため自動でやり直した回数
+    pub stall_retries: u8,
 }
 
 pub(super) struct ProgressContext {
@@ -102,6 +162,8 @@ pub(super) struct ProgressContext {
     active: Arc<AtomicBool>,
     progress_started: AtomicBool,
     post_processing: AtomicBool,
+    handed_off: AtomicBool,
+    watchdog: watchdog::Watchdog,
 }
 
 impl ProgressContext {
@@ -112,6 +174,8 @@ impl ProgressContext {
             active,
             progress_started: AtomicBool::new(false),
             post_processing: AtomicBool::new(false),
+            handed_off: AtomicBool::new(false),
+            watchdog: watchdog::Watchdog::default(),
         })
     }
 
@@ -143,208 +207,475 @@ impl ProgressContext {
         self.post_processing.load(Ordering::Relaxed)
     }
 
-    fn deactivate(&self) {
-        self.active.store(false, Ordering::Relaxed);
+    // 変換を次のダウンロードと並べて続けられるよう画面へ知らせる。
+    // 中断の記録は次のダウンロードが使うため、ここで消してこれ以降は書かない。
+    fn hand_off(&self, tx: &mpsc::Sender<DownloadEvent>) {
+        clear_job_record();
+        self.handed_off.store(true, Ordering::Relaxed);
+        let _ = tx.send(DownloadEvent::Downloaded);
     }
 
-    fn is_active(&self) -> bool {
-        self.active.load(Ordering::Relaxed)
+    fn handed_off(&self) -> bool {
+        self.handed_off.load(Ordering::Relaxed)
     }
-}
-
-#[derive(Clone, Default)]
-pub struct ProcessTracker {
-    pids: Arc<Mutex<Vec<u32>>>,
-}
 
-impl ProcessTracker {
-    pub fn new() -> Self {
-        Self::default()
-    }
-
-    pub fn register(&self, child: &Child) {
-        let pid = child.id();
-        if pid == 0 {
-            return;
-        }
-        let mut pids = self.pids.lock().unwrap();
-        if !pids.contains(&pid) {
-            pids.push(pid);
-        }
+    fn deactivate(&self) {
+        self.active.store(false, Ordering::Relaxed);
     }
 
-    pub fn terminate_all(&self) {
-        let pids = {
-            let pids = self.pids.lock().unwrap();
-            pids.clone()
-        };
-        for pid in &pids {
-            let _ = Command::new("kill")
-                .arg("-TERM")
-                .arg(pid.to_string())
-                .status();
-        }
-        for pid in &pids {
-            let _ = Command::new("kill")
-                .arg("-KILL")
-                .arg(pid.to_string())
-                .status();
-        }
+    fn is_active(&self) -> bool {
+        self.active.load(Ordering::Relaxed)
     }
 }
 
 // ダウンロード処理のエントリポイント。進捗初期化から完了通知までを統括する。
 pub fn run_download(
-    url: String,
+    job: DownloadJob,
     output_dir: PathBuf,
-    cookie_args: Vec<String>,
+    auth_args: Vec<String>,
     tx: mpsc::Sender<DownloadEvent>,
     active_flag: Arc<AtomicBool>,
-    cancel_flag: Arc<AtomicBool>,
-    tracker: ProcessTracker,
+    cancel: CancellationToken,
 ) {
     let progress = ProgressContext::new(active_flag);
-    let _ = tx.send(DownloadEvent::Progress(ProgressUpdate::info_loading(
-        &progress.elapsed(),
-    )));
+    let _ = tx.send(DownloadEvent::Stage(
+        DownloadStage::Resolving {
+            checking_info: false,
+        },
+        progress.elapsed(),
+    ));
     start_loading_elapsed_ticker(progress.clone(), tx.clone());
+    watchdog::start_watchdog(progress.clone(), cancel.clone());
 
+    // 取り消した後の失敗（止めた外部ツールの異常終了など）は、取り消しとして扱う。
     let result = run_download_inner(
-        url,
+        &runner::SystemRunner,
+        job,
         output_dir,
-        cookie_args,
+        auth_args,
         &tx,
         &progress,
-        &cancel_flag,
-        &tracker,
-    );
+        &cancel,
+    )
+    .map_err(|err| {
+        if cancel.is_cancelled() {
+            PipelineError::Cancelled
+        } else {
+            err
+        }
+    });
+
+    // 成功・失敗・キャンセルのいずれでも終わったため、中断の記録とログイン情報のファイルは残さない。
+    cancel.finish();
+    if !progress.handed_off() {
+        clear_job_record();
+    }
+
+    let stage = match result {
+        Ok(()) => DownloadStage::Done,
+        // 見張りが中断した場合は、キャンセルではなく応答がなかったことによる失敗とする。
+        Err(err) => DownloadStage::Failed {
+            kind: match progress.watchdog.stalled_minutes() {
+                Some(minutes) => FailureKind::Stalled(tr_fmt(Msg::DownloadStalled, &[&minutes])),
+                None => FailureKind::from_error(err),
+            },
+        },
+    };
+    finalize_progress(&progress, &tx, stage);
+}
 
-    let total_elapsed = progress.elapsed();
-    finalize_progress(&progress, &tx, result.is_ok());
-    let _ = tx.send(DownloadEvent::Done(result, total_elapsed));
+// プリセットで追加した場合は、設定の代わりにプリセットのエンコード方式と高さの上限を使う。
+fn job_encoder_settings(preset: Option<&DownloadPreset>) -> (EncoderProfile, Option<u32>) {
+    match preset {
+        Some(preset) => (
+            preset.encoder_profile,
+            (preset.max_height > 0).then_some(preset.max_height),
+        ),
+        None => (load_encoder_profile(), load_download_max_height()),
+    }
 }
 
 // URL 判定と実体処理の振り分け、作業フォルダ後始末を行うメインフロー。
+// yt-dlp・ffmpeg・ffprobe・curl の起動は runner を通し、テストでは偽の起動役に差し替えられるようにする。
 fn run_download_inner(
-    url: String,
+    runner: &dyn runner::CommandRunner,
+    job: DownloadJob,
     output_dir: PathBuf,
-    cookie_args: Vec<String>,
+    auth_args: Vec<String>,
     tx: &mpsc::Sender<DownloadEvent>,
     progress: &Arc<ProgressContext>,
-    cancel_flag: &Arc<AtomicBool>,
-    tracker: &ProcessTracker,
-) -> Result<(), String> {
-    if cancel_flag.load(Ordering::Relaxed) {
-        return Err(CANCELLED_ERROR.to_string());
+    cancel: &CancellationToken,
+) -> Result<(), PipelineError> {
+    cancel.check()?;
+    let DownloadJob {
+        url,
+        quality,
+        choose_format,
+        resume_staging_dir,
+        preset,
+        source_file,
+        split_timestamps,
+        ..
+    } = job;
+    if let Some(preset) = &preset {
+        let _ = tx.send(DownloadEvent::Log(
+            LogLevel::Info,
+            tr_fmt(Msg::UsingPreset, &[&preset.name]),
+        ));
     }
+    let (profile, max_height) = job_encoder_settings(preset.as_ref());
+    // 監視フォルダの動画はダウンロードせず、変換だけ行う。
+    if let Some(source) = source_file {
+        ensure_bundled_tools()?;
+        let ffmpeg = ffmpeg_path();
+        if !ffmpeg.exists() {
+            return Err(tr(Msg::FfmpegNotFound).to_string().into());
+        }
+        let encoding = encoder::select_video_encoding(&ffmpeg, quality, profile, tx)?;
+        import::import_video_file(
+            runner,
+            &source,
+            &output_dir,
+            &encoding,
+            tx,
+            progress,
+            cancel,
+        )?;
+        return Ok(());
+    }
+    // 以前に保存したファイルが残っていれば、ダウンロードせずに知らせる。
+    // ファイルが消えている場合は、yt-dlp にも飛ばされないようアーカイブを使わずに取り直す。
+    let use_archive = match history::find_downloaded(&url) {
+        Some(path) if path.is_file() => {
+            let _ = tx.send(DownloadEvent::Log(
+                LogLevel::Info,
+                tr_fmt(Msg::SkippedAlreadyDownloadedPath, &[&path.display()]),
+            ));
+            let _ = tx.send(DownloadEvent::AlreadyDownloaded(Some(path)));
+            return Ok(());
+        }
+        Some(_) => {
+            let _ = tx.send(DownloadEvent::Log(
+                LogLevel::Warn,
+                tr(Msg::RedownloadMissingFile).to_string(),
+            ));
+            false
+        }
+        None => true,
+    };
+    // 異常終了した場合に次回の起動で再開できるよう、進み具合を書き残す。
+    let mut record = JobRecord {
+        url: url.clone(),
+        quality,
+        output_dir: output_dir.clone(),
+        staging_dir: None,
+        stage: JobStage::Preparing,
+    };
+    record.save();
 
     // 必須ツールの存在確認を先に行う。
     ensure_bundled_tools()?;
     let ffmpeg = ffmpeg_path();
     if !ffmpeg.exists() {
-        return Err("ffmpegが見つかりません。".to_string());
+        return Err(tr(Msg::FfmpegNotFound).to_string().into());
     }
 
     let yt_dlp_path = yt_dlp_path();
     if !yt_dlp_path.exists() || !is_executable(&yt_dlp_path) {
-        return Err("yt-dlpが見つかりません。".to_string());
+        return Err(tr(Msg::YtDlpNotFound).to_string().into());
+    }
+
+    // yt-dlp がタイムアウトするまで待たず、接続できない時点で止める。
+    if !network::check_online() {
+        return Err(PipelineError::Offline);
     }
+    if load_use_aria2c() && detect_aria2c().is_none() {
+        let _ = tx.send(DownloadEvent::Log(
+            LogLevel::Warn,
+            tr(Msg::Aria2cNotFound).to_string(),
+        ));
+    }
+    if load_concurrent_fragments().1
+        && let Some(speed) = fragments::measured_speed()
+    {
+        let _ = tx.send(DownloadEvent::Log(
+            LogLevel::Info,
+            tr_fmt(
+                Msg::AutoFragments,
+                &[
+                    &fragments::concurrent_fragments(),
+                    &fragments::speed_label(speed),
+                ],
+            ),
+        ));
+    }
+
+    // 長い動画などを誤って落とさないよう、タイトルや長さを見てから始めてもらう。
+    if !is_animethemes_url(&url) && load_preview_prompt() {
+        preview::confirm_download(runner, &yt_dlp_path, &ffmpeg, &url, &auth_args, tx, cancel)?;
+    }
+
+    // 形式を選ぶ場合は、yt-dlp の並べ替えに任せず選んだフォーマット ID を取得する。
+    let chosen_format = if choose_format && !is_animethemes_url(&url) {
+        format_choice::choose_format(
+            runner,
+            &yt_dlp_path,
+            &url,
+            &auth_args,
+            &tools::js_runtime_args(),
+            tx,
+            cancel,
+        )?
+    } else {
+        None
+    };
+
+    // 音声トラックが複数ある場合は、ダウンロード前に使うトラックを選んでもらう。
+    // 形式を選ぶ場合は、音声もその一覧から選ぶ。
+    let audio_language = if !is_animethemes_url(&url) && !choose_format && load_audio_track_prompt()
+    {
+        audio_track::choose_audio_track(
+            runner,
+            &yt_dlp_path,
+            &url,
+            &auth_args,
+            &tools::js_runtime_args(),
+            tx,
+            cancel,
+        )?
+    } else {
+        None
+    };
 
     // 出力先と staging を作成する。
     if let Err(err) = ensure_dir(&output_dir) {
-        return Err(format!("保存先フォルダの作成に失敗しました: {err}"));
+        return Err(tr_fmt(Msg::CreateOutputDirFailed, &[&err]).into());
     }
-    let staging_dir = staging::create_download_staging_dir(&output_dir)?;
+
+    // 変換の途中で容量不足にならないよう、推定サイズと保存先の空き容量を先に比べる。
+    // AnimeThemes は直リンクの Content-Length が分かった時点で確認する。
+    // 取得した動画情報で、yt-dlp のダウンロードアーカイブに記録済みかも確かめる。
+    if !is_animethemes_url(&url) {
+        let extra_args = load_yt_dlp_extra_args();
+        if !extra_args.is_empty() {
+            let _ = tx.send(DownloadEvent::Log(
+                LogLevel::Info,
+                tr_fmt(Msg::ExtraArgsAppended, &[&extra_args.join(" ")]),
+            ));
+        }
+        let _ = tx.send(DownloadEvent::Log(
+            LogLevel::Info,
+            tr(Msg::CheckingDiskSpace).to_string(),
+        ));
+        let args = tools::base_yt_dlp_args(
+            &ffmpeg.to_string_lossy(),
+            &auth_args,
+            &tools::js_runtime_args(),
+            audio_language.as_deref(),
+            max_height,
+        );
+        let info = process::fetch_yt_dlp_info(runner, &yt_dlp_path, &args, &url, cancel).ok();
+        cancel.check()?;
+        if use_archive
+            && info
+                .as_deref()
+                .and_then(history::archive_id)
+                .is_some_and(|id| history::is_archived(&id))
+        {
+            let _ = tx.send(DownloadEvent::Log(
+                LogLevel::Info,
+                tr(Msg::SkippedArchived).to_string(),
+            ));
+            let _ = tx.send(DownloadEvent::AlreadyDownloaded(None));
+            return Ok(());
+        }
+        let estimated = match &chosen_format {
+            Some(chosen) => chosen.size,
+            None => info.as_deref().and_then(disk_space::estimate_download_size),
+        };
+        disk_space::ensure_free_space(&output_dir, estimated, tx)?;
+    }
+    let staging_dir = match resume_staging_dir.filter(|dir| dir.is_dir()) {
+        Some(dir) => {
+            let _ = tx.send(DownloadEvent::Log(
+                LogLevel::Info,
+                tr_fmt(Msg::ResumingStaging, &[&dir.display()]),
+            ));
+            dir
+        }
+        None => staging::create_download_staging_dir(&output_dir)?,
+    };
+    record.staging_dir = Some(staging_dir.clone());
+    let staging_guard = staging::StagingDir::new(staging_dir.clone());
+    record.advance(JobStage::Downloading);
 
     // URL 種別ごとに処理を分岐する。
     let download_result = if is_animethemes_url(&url) {
         progress.mark_progress_started();
-        let _ = tx.send(DownloadEvent::Progress(
-            ProgressUpdate::info_video_metadata(&progress.elapsed()),
+        let _ = tx.send(DownloadEvent::Stage(
+            DownloadStage::Resolving {
+                checking_info: true,
+            },
+            progress.elapsed(),
         ));
-        animethemes::run_animethemes_pipeline(
-            &url,
-            &staging_dir,
-            &yt_dlp_path,
-            &ffmpeg,
-            tx,
-            progress,
-            cancel_flag,
-            tracker,
-        )
+        // 受信しながら変換するため、2 パスは使えない。
+        encoder::select_video_encoding(&ffmpeg, quality, profile, tx).and_then(|encoding| {
+            animethemes::run_animethemes_pipeline(
+                runner,
+                &url,
+                &staging_dir,
+                &yt_dlp_path,
+                &ffmpeg,
+                &encoding.single_pass(),
+                tx,
+                progress,
+                cancel,
+            )
+        })
     } else {
         let output_template = staging_dir.join("%(title)s.%(ext)s");
         let ffmpeg_arg = ffmpeg.to_string_lossy().to_string();
-        let js_runtime = tools::js_runtime_arg();
+        let js_runtime = tools::js_runtime_args();
+        let archive_args = if use_archive {
+            vec![
+                "--download-archive".to_string(),
+                download_archive_path().to_string_lossy().to_string(),
+            ]
+        } else {
+            Vec::new()
+        };
 
         let mut args = Vec::new();
-        args.extend(tools::base_yt_dlp_args(
-            &ffmpeg_arg,
-            &cookie_args,
-            &js_runtime,
-        ));
+        match &chosen_format {
+            Some(chosen) => args.extend(tools::chosen_format_yt_dlp_args(
+                &ffmpeg_arg,
+                &auth_args,
+                &js_runtime,
+                &chosen.selector,
+            )),
+            None => args.extend(tools::base_yt_dlp_args(
+                &ffmpeg_arg,
+                &auth_args,
+                &js_runtime,
+                audio_language.as_deref(),
+                max_height,
+            )),
+        }
+        args.extend(archive_args.iter().cloned());
+        // 投稿者名などで検索できるよう、動画情報も書き出す（保存先へ移す時にアプリのフォルダへ移す）。
+        args.push("--write-info-json".to_string());
         args.push("-o".to_string());
         args.push(output_template.to_string_lossy().to_string());
+        // `-` で始まる URL をオプションとして読まないよう、`--` の後に置く。
+        args.push("--".to_string());
         args.push(url.clone());
 
-        let status = process::run_yt_dlp(&yt_dlp_path, &args, tx, progress.clone(), true, tracker);
-        match status {
-            Ok(code) if code.success() => Ok(()),
-            Ok(_) => {
-                let _ = tx.send(DownloadEvent::Log(
-                    "H.264優先モードに失敗。互換モードで再試行します。".to_string(),
-                ));
-                if cancel_flag.load(Ordering::Relaxed) {
-                    Err(CANCELLED_ERROR.to_string())
-                } else {
-                    let mut fallback_args = Vec::new();
-                    fallback_args.extend(tools::fallback_yt_dlp_args(
-                        &ffmpeg_arg,
-                        &cookie_args,
-                        &js_runtime,
-                    ));
-                    fallback_args.push("-o".to_string());
-                    fallback_args.push(output_template.to_string_lossy().to_string());
-                    fallback_args.push(url);
-
-                    let status = process::run_yt_dlp(
-                        &yt_dlp_path,
-                        &fallback_args,
-                        tx,
-                        progress.clone(),
-                        true,
-                        tracker,
-                    );
-                    if cancel_flag.load(Ordering::Relaxed) {
-                        Err(CANCELLED_ERROR.to_string())
-                    } else {
-                        match status {
-                            Ok(code) if code.success() => Ok(()),
-                            Ok(code) => Err(format!("yt-dlp exited with status: {code}")),
-                            Err(err) => Err(format!("yt-dlpの実行に失敗しました: {err}")),
-                        }
-                    }
-                }
+        // mp4 互換のストリームはコピーし、それ以外とサイズ指定を超える映像だけ再エンコードする。
+        let convert_staged = || {
+            progress.hand_off(tx);
+            encoder::select_video_encoding(&ffmpeg, quality, profile, tx).and_then(|encoding| {
+                convert::convert_staged_videos_to_mp4(
+                    runner,
+                    &staging_dir,
+                    &ffmpeg,
+                    &encoding,
+                    tx,
+                    progress,
+                    cancel,
+                )
+            })
+        };
+
+        // 形式を選んだ場合は、その形式のまま失敗とする。
+        let fallback_args = chosen_format.is_none().then(|| {
+            let mut fallback_args = Vec::new();
+            fallback_args.extend(tools::fallback_yt_dlp_args(
+                &ffmpeg_arg,
+                &auth_args,
+                &js_runtime,
+                audio_language.as_deref(),
+                max_height,
+            ));
+            fallback_args.extend(archive_args);
+            fallback_args.push("--write-info-json".to_string());
+            fallback_args.push("-o".to_string());
+            fallback_args.push(output_template.to_string_lossy().to_string());
+            fallback_args.push("--".to_string());
+            fallback_args.push(url.clone());
+            fallback_args
+        });
+        process::run_yt_dlp_with_fallback(
+            runner,
+            &yt_dlp_path,
+            &args,
+            fallback_args.as_deref(),
+            tx,
+            progress,
+            cancel,
+        )
+        .and_then(|fell_back| {
+            // 選んだ形式は mkv で結合するため、常に mp4 へそろえる。
+            // 標準以外の画質方式は、mp4 で受信した場合も変換で反映する。
+            if fell_back || chosen_format.is_some() || quality != QualityMode::Standard {
+                convert_staged()
+            } else {
+                Ok(())
             }
-            Err(err) => Err(format!("yt-dlpの実行に失敗しました: {err}")),
-        }
+        })
     };
 
-    // 成功時のみ staging 内 MP4 を昇格し、最後に staging を掃除する。
+    // 成功時のみ staging 内 MP4 を昇格して検証し、最後に staging を掃除する。
     let promote_result = match &download_result {
-        Ok(()) => staging::promote_downloaded_mp4_files(&staging_dir, &output_dir),
+        Ok(()) => {
+            if !progress.handed_off() {
+                record.advance(JobStage::Finishing);
+            }
+            let _ = tx.send(DownloadEvent::Stage(
+                DownloadStage::Finalizing,
+                progress.elapsed(),
+            ));
+            staging::promote_downloaded_mp4_files(
+                &staging_dir,
+                &output_dir,
+                load_collision_policy(),
+                tx,
+                cancel,
+            )
+            .and_then(|promoted| {
+                validate::validate_outputs(runner, &promoted, &output_dir, &ffmpeg, tx, cancel)?;
+                if let Some(path) = promoted.first() {
+                    history::record_download(&url, path);
+                }
+                // チャプターで分割しなかった動画だけをシーン分割に回す。
+                chapter_split::split_saved_outputs(
+                    runner,
+                    &promoted,
+                    &output_dir,
+                    &ffmpeg,
+                    split_timestamps.as_deref(),
+                    tx,
+                    cancel,
+                )
+                .and_then(|remaining| {
+                    scene_split::split_saved_outputs(
+                        runner,
+                        &remaining,
+                        &output_dir,
+                        &ffmpeg,
+                        tx,
+                        cancel,
+                    )
+                })
+            })
+        }
         Err(_) => Ok(()),
     };
-    let cleanup_error = fs::remove_dir_all(&staging_dir).err();
+    let cleanup_error = staging_guard.remove().err();
 
-    if let Err(err) = promote_result {
-        return Err(err);
-    }
-    if let Err(err) = download_result {
-        return Err(err);
-    }
+    promote_result?;
+    download_result?;
     if let Some(err) = cleanup_error {
-        return Err(format!("一時フォルダの削除に失敗しました: {err}"));
+        return Err(tr_fmt(Msg::RemoveTempDirFailed, &[&err]).into());
     }
     Ok(())
 }
@@ -365,39 +696,31 @@ fn is_animethemes_url(url: &str) -> bool {
     url.to_lowercase().contains("animethemes.moe")
 }
 
-// 経過時間表示のフォーマットを統一する。
-fn format_elapsed(elapsed: &str) -> String {
-    if elapsed.trim().is_empty() {
-        String::new()
-    } else {
-        format!(" (経過: {elapsed})")
-    }
-}
-
 // 進捗率がまだ取れない初期フェーズの表示を定期更新する。
 fn start_loading_elapsed_ticker(progress: Arc<ProgressContext>, tx: mpsc::Sender<DownloadEvent>) {
     thread::spawn(move || {
         while progress.is_active() && !progress.progress_started() {
-            let update = ProgressUpdate::info_loading(&progress.elapsed());
-            let _ = tx.send(DownloadEvent::Progress(update));
+            let stage = DownloadStage::Resolving {
+                checking_info: false,
+            };
+            let _ = tx.send(DownloadEvent::Stage(stage, progress.elapsed()));
             thread::sleep(Duration::from_secs(1));
         }
     });
 }
 
-// 完了/失敗に応じて最終進捗状態を通知し、必要なら自動非表示を予約する。
+// 完了/失敗の段階を通知し、完了時は表示の自動非表示を予約する。
 fn finalize_progress(
     progress: &Arc<ProgressContext>,
     tx: &mpsc::Sender<DownloadEvent>,
-    success: bool,
+    stage: DownloadStage,
 ) {
     let elapsed = progress.elapsed();
     progress.deactivate();
-    if success {
-        let _ = tx.send(DownloadEvent::Progress(ProgressUpdate::completed(&elapsed)));
+    let succeeded = stage == DownloadStage::Done;
+    let _ = tx.send(DownloadEvent::Stage(stage, elapsed));
+    if succeeded {
         schedule_progress_hide_if_idle(progress.active.clone(), tx.clone());
-    } else {
-        let _ = tx.send(DownloadEvent::Progress(ProgressUpdate::hidden()));
     }
 }
 
@@ -405,7 +728,7 @@ fn schedule_progress_hide_if_idle(active: Arc<AtomicBool>, tx: mpsc::Sender<Down
     thread::spawn(move || {
         thread::sleep(Duration::from_millis(1200));
         if !active.load(Ordering::Relaxed) {
-            let _ = tx.send(DownloadEvent::Progress(ProgressUpdate::hidden()));
+            let _ = tx.send(DownloadEvent::Idle);
         }
     });
 }
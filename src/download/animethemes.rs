@@ -1,88 +1,118 @@
 use serde_json::Value;
+use std::ffi::OsStr;
 use std::fs;
 use std::io::{BufReader, Read, Write};
 use std::path::{Path, PathBuf};
-use std::process::{Command, Stdio};
-use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, mpsc};
 use std::thread;
+use std::time::Instant;
 use url::Url;
 
+use crate::app_logger::{LogLevel, LogSource};
+use crate::i18n::{Msg, tr, tr_fmt};
+use crate::settings::{EncoderProfile, FilenameMode, load_filename_mode};
+
+use super::convert::{
+    MediaInfo, applied_watermark, filter_args, is_remux_only, is_video_copied, probe_media_info,
+    run_ffmpeg_conversion, stream_args, video_filters,
+};
+use super::disk_space::ensure_free_space;
+use super::encoder::{VideoEncoding, encoder_label};
+use super::hwaccel::{conversion_speed_log, hwaccel_args};
 use super::process::{run_pipe_to_ffmpeg_or_cancel, spawn_stream_thread, terminate_child_process};
-use super::{CANCELLED_ERROR, DownloadEvent, ProcessTracker, ProgressContext, ProgressUpdate};
+use super::runner::{CommandRunner, CommandSpec, output};
+use super::{
+    CancellationToken, DownloadEvent, DownloadStage, ProgressContext, conversion_slots, fragments,
+};
 
 const ANIMETHEMES_USER_AGENT: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
 const ANIMETHEMES_API_ENDPOINT: &str = "https://api.animethemes.moe";
 const ANIMETHEMES_HTML_RANGE: &str = "0-262143";
+// APFS のファイル名は 255 バイトまで。時刻と拡張子の分を残す。
+const MAX_FILENAME_BASE_BYTES: usize = 200;
 
 // AnimeThemes URL の場合に、直リンク優先で MP4 を生成する専用パイプラインを実行する。
 pub(super) fn run_animethemes_pipeline(
+    runner: &dyn CommandRunner,
     url: &str,
     output_dir: &Path,
     yt_dlp: &Path,
     ffmpeg: &Path,
+    encoding: &VideoEncoding,
     tx: &mpsc::Sender<DownloadEvent>,
     progress: &Arc<ProgressContext>,
-    cancel_flag: &Arc<AtomicBool>,
-    tracker: &ProcessTracker,
+    cancel: &CancellationToken,
 ) -> Result<(), String> {
-    if cancel_flag.load(Ordering::Relaxed) {
-        return Err(CANCELLED_ERROR.to_string());
-    }
-    ensure_apple_silicon_gpu_encoder(ffmpeg)?;
-    let output_path = build_animethemes_output_path(url, output_dir);
+    cancel.check()?;
+    let output_path = build_animethemes_output_path(url, output_dir, load_filename_mode());
 
-    let direct_url = fetch_animethemes_direct_webm(url, tx)?;
+    let direct_url = fetch_animethemes_direct_webm(runner, url, tx, cancel)?;
     match direct_url {
         Some(webm_url) => {
-            let _ = tx.send(DownloadEvent::Log(format!(
-                "AnimeThemes直リンクを取得しました: {webm_url}"
-            )));
-            let direct_result = stream_animethemes_webm_to_mp4_with_gpu(
+            let _ = tx.send(DownloadEvent::Log(
+                LogLevel::Info,
+                tr_fmt(Msg::AnimeThemesDirectLinkFound, &[&webm_url]),
+            ));
+            // 空き容量が足りない場合は、フォールバックせずにここで止める。
+            let total_bytes = fetch_content_length(runner, &webm_url, cancel);
+            cancel.check()?;
+            if total_bytes.is_some()
+                && let Some(dir) = output_path.parent()
+            {
+                ensure_free_space(dir, total_bytes, tx)?;
+            }
+            let direct_result = stream_animethemes_webm_to_mp4(
+                runner,
                 &webm_url,
+                total_bytes,
                 ffmpeg,
+                encoding,
                 &output_path,
                 tx,
                 progress,
-                tracker,
-                cancel_flag,
+                cancel,
             );
             match direct_result {
                 Ok(()) => {}
-                Err(err) if err == CANCELLED_ERROR => return Err(err),
+                Err(err) if cancel.is_cancelled() => return Err(err),
                 Err(err) => {
-                    let _ = tx.send(DownloadEvent::Log(format!(
-                        "AnimeThemes直リンク経路で失敗しました: {err}"
-                    )));
                     let _ = tx.send(DownloadEvent::Log(
-                        "yt-dlpフォールバックへ切り替えます。".to_string(),
+                        LogLevel::Warn,
+                        tr_fmt(Msg::AnimeThemesDirectLinkFailed, &[&err]),
+                    ));
+                    let _ = tx.send(DownloadEvent::Log(
+                        LogLevel::Info,
+                        tr(Msg::AnimeThemesSwitchToYtDlp).to_string(),
                     ));
                     run_animethemes_yt_dlp_fallback(
+                        runner,
                         url,
                         yt_dlp,
                         ffmpeg,
+                        encoding,
                         &output_path,
                         tx,
                         progress,
-                        tracker,
-                        cancel_flag,
+                        cancel,
                     )?;
                 }
             }
         }
         None => {
             let _ = tx.send(DownloadEvent::Log(
-                "AnimeThemes直リンク取得に失敗。yt-dlpでフォールバックします。".to_string(),
+                LogLevel::Warn,
+                tr(Msg::AnimeThemesNoDirectLink).to_string(),
             ));
             run_animethemes_yt_dlp_fallback(
+                runner,
                 url,
                 yt_dlp,
                 ffmpeg,
+                encoding,
                 &output_path,
                 tx,
                 progress,
-                tracker,
-                cancel_flag,
+                cancel,
             )?;
         }
     }
@@ -91,142 +121,202 @@ pub(super) fn run_animethemes_pipeline(
 }
 
 fn run_animethemes_yt_dlp_fallback(
+    runner: &dyn CommandRunner,
     url: &str,
     yt_dlp: &Path,
     ffmpeg: &Path,
+    encoding: &VideoEncoding,
     output_path: &Path,
     tx: &mpsc::Sender<DownloadEvent>,
     progress: &Arc<ProgressContext>,
-    tracker: &ProcessTracker,
-    cancel_flag: &Arc<AtomicBool>,
+    cancel: &CancellationToken,
 ) -> Result<(), String> {
-    let mut cmd = Command::new(yt_dlp);
-    cmd.arg("--no-playlist")
-        .arg("--concurrent-fragments")
-        .arg("4")
-        .arg("-f")
-        .arg("bv+ba/b")
-        .arg("--ffmpeg-location")
-        .arg(ffmpeg.to_string_lossy().to_string())
-        .arg("-o")
-        .arg("-")
-        .arg(url);
+    let cmd = CommandSpec::new(yt_dlp)
+        .args(["--no-playlist", "--concurrent-fragments"])
+        .arg(fragments::concurrent_fragments().to_string())
+        .args(["-f", "bv+ba/b", "--ffmpeg-location"])
+        .arg(ffmpeg)
+        .args(["-o", "-", "--", url]);
     run_pipe_to_ffmpeg_or_cancel(
-        cmd,
+        runner,
+        &cmd,
         ffmpeg,
         output_path,
         tx,
         progress,
         "webm",
-        tracker,
-        cancel_flag,
+        encoding,
+        cancel,
     )
 }
 
 // curl 受信ストリームを ffmpeg に流し込み、ダウンロードと変換を並列で進める。
-fn stream_animethemes_webm_to_mp4_with_gpu(
+fn stream_animethemes_webm_to_mp4(
+    runner: &dyn CommandRunner,
     webm_url: &str,
+    total_bytes: Option<u64>,
     ffmpeg: &Path,
+    encoding: &VideoEncoding,
     output_path: &Path,
     tx: &mpsc::Sender<DownloadEvent>,
     progress: &Arc<ProgressContext>,
-    tracker: &ProcessTracker,
-    cancel_flag: &Arc<AtomicBool>,
+    cancel: &CancellationToken,
 ) -> Result<(), String> {
     let _ = tx.send(DownloadEvent::Log(
-        "動画ダウンロードと変換を同時に開始します。".to_string(),
+        LogLevel::Info,
+        tr(Msg::StreamingStarted).to_string(),
     ));
-    let total_bytes = fetch_content_length(webm_url);
     if let Some(total) = total_bytes {
-        let _ = tx.send(DownloadEvent::Log(format!(
-            "動画サイズを確認しました: {:.1}MB",
-            total as f64 / (1024.0 * 1024.0)
-        )));
+        let _ = tx.send(DownloadEvent::Log(
+            LogLevel::Info,
+            tr_fmt(
+                Msg::StreamingSize,
+                &[&format!("{:.1}", total as f64 / (1024.0 * 1024.0))],
+            ),
+        ));
     } else {
         let _ = tx.send(DownloadEvent::Log(
-            "動画サイズを取得できなかったため、MBベースで進捗ログを表示します。".to_string(),
+            LogLevel::Info,
+            tr(Msg::StreamingSizeUnknown).to_string(),
         ));
     }
 
-    let mut curl_cmd = Command::new("curl");
-    curl_cmd
-        .arg("-sS")
-        .arg("-L")
-        .arg("-m")
-        .arg("120")
-        .arg("--fail")
-        .arg("-o")
-        .arg("-")
-        .arg("-A")
-        .arg(ANIMETHEMES_USER_AGENT)
-        .arg(webm_url)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped());
-
-    let mut curl_child = curl_cmd
-        .spawn()
-        .map_err(|err| format!("curl起動に失敗しました: {err}"))?;
-    tracker.register(&curl_child);
-    spawn_stream_thread(curl_child.stderr.take(), tx, progress);
-
-    let mut curl_stdout = match curl_child.stdout.take() {
+    let _slot = conversion_slots::acquire(encoding, tx, cancel)?;
+    let info = probe_direct_video(runner, webm_url, encoding, tx, cancel);
+    cancel.check()?;
+    // ミラーによってはすでに mp4 互換の動画のため、その場合は変換を省く。
+    if let Some(info) = info.as_ref() {
+        let args = stream_args(info, encoding, None);
+        if is_remux_only(&args) {
+            let _ = tx.send(DownloadEvent::Log(
+                LogLevel::Info,
+                tr(Msg::DirectRemux).to_string(),
+            ));
+            progress.mark_progress_started();
+            let stage = DownloadStage::Converting {
+                pct: None,
+                pass: None,
+            };
+            let _ = tx.send(DownloadEvent::Stage(stage, progress.elapsed()));
+            let cmd = direct_remux_command(ffmpeg, webm_url, &args, output_path);
+            let result = run_ffmpeg_conversion(
+                runner,
+                &cmd,
+                info.duration_seconds,
+                None,
+                tx,
+                progress,
+                cancel,
+            );
+            if result.is_err() {
+                let _ = fs::remove_file(output_path);
+                cancel.check()?;
+            }
+            return result;
+        }
+    }
+
+    let curl_cmd = CommandSpec::new(Path::new("curl"))
+        .args(["-sS", "-L", "-m", "120", "--fail", "-o", "-"])
+        .args(["-A", ANIMETHEMES_USER_AGENT, webm_url]);
+
+    let mut curl_child = runner
+        .spawn(&curl_cmd, cancel)
+        .map_err(|err| tr_fmt(Msg::ToolLaunchFailed, &[&"curl", &err]))?;
+    let _watch = progress.watchdog.watch();
+    // curl のエラー出力はアプリのログとして扱う。
+    spawn_stream_thread(curl_child.take_stderr(), LogSource::App, tx, progress);
+
+    let mut curl_stdout = match curl_child.take_stdout() {
         Some(stdout) => stdout,
         None => {
-            terminate_child_process(&mut curl_child);
-            return Err("curl出力の取得に失敗しました。".to_string());
+            terminate_child_process(curl_child.as_mut());
+            return Err(tr(Msg::CurlOutputFailed).to_string());
         }
     };
 
     let _ = tx.send(DownloadEvent::Log(
-        "ffmpeg(GPU: h264_videotoolbox)でストリーミング変換を開始します。".to_string(),
+        LogLevel::Info,
+        tr_fmt(
+            Msg::StreamingConvertStarted,
+            &[&encoder_label(encoding.profile)],
+        ),
     ));
+    // 調べられた場合は、mp4 に入るストリームをコピーする。
+    let (args, watermark) = match info.as_ref() {
+        Some(info) => (
+            stream_args(info, encoding, None),
+            applied_watermark(info, encoding, None),
+        ),
+        None => {
+            let watermark = encoding.stream_watermark();
+            let args = [
+                encoding
+                    .video_args()
+                    .iter()
+                    .map(|arg| arg.to_string())
+                    .collect(),
+                filter_args(&[], watermark),
+                ["-c:a", "aac", "-b:a", "192k"].map(str::to_string).to_vec(),
+            ]
+            .concat();
+            (args, watermark)
+        }
+    };
+    let reencode = !is_video_copied(&args);
+    let video_codec = info.as_ref().and_then(|info| info.video_codec.as_deref());
+    let hwaccel = hwaccel_args(video_codec, reencode);
+    if !hwaccel.is_empty() {
+        let _ = tx.send(DownloadEvent::Log(
+            LogLevel::Info,
+            tr(Msg::HardwareDecode).to_string(),
+        ));
+    }
+    if watermark.is_some() {
+        let _ = tx.send(DownloadEvent::Log(
+            LogLevel::Info,
+            tr(Msg::WatermarkOverlaid).to_string(),
+        ));
+    }
+
+    let mut ffmpeg_cmd = CommandSpec::new(ffmpeg)
+        .args(["-stats", "-analyzeduration", "100M", "-probesize", "100M"])
+        .args(hwaccel.iter().copied())
+        .args(["-f", "webm", "-i", "pipe:0"]);
+    if let Some(watermark) = watermark {
+        ffmpeg_cmd = ffmpeg_cmd.arg("-i").arg(&watermark.path);
+    }
+    let ffmpeg_cmd = ffmpeg_cmd
+        .args(args.iter().cloned())
+        .args([
+            "-ignore_unknown",
+            "-movflags",
+            "+faststart",
+            "-f",
+            "mp4",
+            "-y",
+        ])
+        .arg(output_path)
+        .stdin_piped();
+
+    let started = Instant::now();
+    let mut ffmpeg_child = match runner.spawn(&ffmpeg_cmd, cancel) {
+        Ok(child) => child,
+        Err(err) => {
+            terminate_child_process(curl_child.as_mut());
+            return Err(tr_fmt(Msg::ToolLaunchFailed, &[&"ffmpeg", &err]));
+        }
+    };
+    spawn_stream_thread(ffmpeg_child.take_stdout(), LogSource::Ffmpeg, tx, progress);
+    spawn_ffmpeg_conversion_thread(ffmpeg_child.take_stderr(), tx, progress, None, None);
 
-    let mut ffmpeg_cmd = Command::new(ffmpeg);
-    ffmpeg_cmd
-        .arg("-stats")
-        .arg("-analyzeduration")
-        .arg("100M")
-        .arg("-probesize")
-        .arg("100M")
-        .arg("-f")
-        .arg("webm")
-        .arg("-i")
-        .arg("pipe:0")
-        .arg("-c:v")
-        .arg("h264_videotoolbox")
-        .arg("-b:v")
-        .arg("5M")
-        .arg("-pix_fmt")
-        .arg("yuv420p")
-        .arg("-c:a")
-        .arg("aac")
-        .arg("-b:a")
-        .arg("192k")
-        .arg("-ignore_unknown")
-        .arg("-movflags")
-        .arg("+faststart")
-        .arg("-f")
-        .arg("mp4")
-        .arg("-y")
-        .arg(output_path.to_string_lossy().to_string())
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped());
-
-    let mut ffmpeg_child = ffmpeg_cmd
-        .spawn()
-        .map_err(|err| format!("ffmpeg起動に失敗しました: {err}"))?;
-    tracker.register(&ffmpeg_child);
-    spawn_stream_thread(ffmpeg_child.stdout.take(), tx, progress);
-    spawn_ffmpeg_conversion_thread(ffmpeg_child.stderr.take(), tx, progress, None);
-
-    let mut ffmpeg_stdin = match ffmpeg_child.stdin.take() {
+    let mut ffmpeg_stdin = match ffmpeg_child.take_stdin() {
         Some(stdin) => stdin,
         None => {
-            terminate_child_process(&mut curl_child);
-            terminate_child_process(&mut ffmpeg_child);
+            terminate_child_process(curl_child.as_mut());
+            terminate_child_process(ffmpeg_child.as_mut());
             let _ = fs::remove_file(output_path);
-            return Err("ffmpeg入力パイプの取得に失敗しました。".to_string());
+            return Err(tr(Msg::FfmpegPipeFailed).to_string());
         }
     };
 
@@ -235,30 +325,31 @@ fn stream_animethemes_webm_to_mp4_with_gpu(
     let mut last_bytes_log: u64 = 0;
     let mut buf = [0u8; 64 * 1024];
     loop {
-        if cancel_flag.load(Ordering::Relaxed) {
-            terminate_child_process(&mut curl_child);
-            terminate_child_process(&mut ffmpeg_child);
+        if cancel.is_cancelled() {
+            terminate_child_process(curl_child.as_mut());
+            terminate_child_process(ffmpeg_child.as_mut());
             let _ = fs::remove_file(output_path);
-            return Err(CANCELLED_ERROR.to_string());
+            return Err(tr(Msg::DownloadCancelled).to_string());
         }
 
         let read = match curl_stdout.read(&mut buf) {
             Ok(read) => read,
             Err(err) => {
-                terminate_child_process(&mut curl_child);
-                terminate_child_process(&mut ffmpeg_child);
+                terminate_child_process(curl_child.as_mut());
+                terminate_child_process(ffmpeg_child.as_mut());
                 let _ = fs::remove_file(output_path);
-                return Err(format!("動画ストリームの読み取りに失敗しました: {err}"));
+                return Err(tr_fmt(Msg::StreamReadFailed, &[&err]));
             }
         };
         if read == 0 {
             break;
         }
+        progress.watchdog.touch();
         if let Err(err) = ffmpeg_stdin.write_all(&buf[..read]) {
-            terminate_child_process(&mut curl_child);
-            terminate_child_process(&mut ffmpeg_child);
+            terminate_child_process(curl_child.as_mut());
+            terminate_child_process(ffmpeg_child.as_mut());
             let _ = fs::remove_file(output_path);
-            return Err(format!("ffmpeg入力への書き込みに失敗しました: {err}"));
+            return Err(tr_fmt(Msg::FfmpegWriteFailed, &[&err]));
         }
 
         downloaded += read as u64;
@@ -266,86 +357,117 @@ fn stream_animethemes_webm_to_mp4_with_gpu(
             if total > 0 {
                 progress.mark_progress_started();
                 let percent = (downloaded as f64 * 100.0 / total as f64).clamp(0.0, 100.0) as f32;
-                let _ = tx.send(DownloadEvent::Progress(ProgressUpdate::downloading(
-                    percent,
-                    &progress.elapsed(),
-                )));
+                let stage = DownloadStage::Fetching {
+                    bytes: downloaded,
+                    total: Some(total),
+                };
+                let _ = tx.send(DownloadEvent::Stage(stage, progress.elapsed()));
                 let bucket = (percent / 5.0).floor() as i64;
                 if bucket > last_log_bucket {
                     last_log_bucket = bucket;
-                    let _ = tx.send(DownloadEvent::Log(format!(
-                        "ダウンロード進捗: {:.1}%",
-                        percent
-                    )));
+                    let _ = tx.send(DownloadEvent::Log(
+                        LogLevel::Info,
+                        tr_fmt(Msg::DownloadProgressPercent, &[&format!("{percent:.1}")]),
+                    ));
                 }
             }
         } else if downloaded >= last_bytes_log.saturating_add(10 * 1024 * 1024) {
             last_bytes_log = downloaded;
-            let _ = tx.send(DownloadEvent::Log(format!(
-                "ダウンロード進捗: {:.1}MB",
-                downloaded as f64 / (1024.0 * 1024.0)
-            )));
+            progress.mark_progress_started();
+            let stage = DownloadStage::Fetching {
+                bytes: downloaded,
+                total: None,
+            };
+            let _ = tx.send(DownloadEvent::Stage(stage, progress.elapsed()));
+            let _ = tx.send(DownloadEvent::Log(
+                LogLevel::Info,
+                tr_fmt(
+                    Msg::DownloadProgressSize,
+                    &[&format!("{:.1}", downloaded as f64 / (1024.0 * 1024.0))],
+                ),
+            ));
         }
     }
     drop(ffmpeg_stdin);
 
     let curl_status = curl_child
         .wait()
-        .map_err(|err| format!("curlの終了待ちに失敗しました: {err}"))?;
+        .map_err(|err| tr_fmt(Msg::ToolWaitFailed, &[&"curl", &err]))?;
 
-    if cancel_flag.load(Ordering::Relaxed) {
-        terminate_child_process(&mut ffmpeg_child);
+    if cancel.is_cancelled() {
+        terminate_child_process(ffmpeg_child.as_mut());
         let _ = fs::remove_file(output_path);
-        return Err(CANCELLED_ERROR.to_string());
+        return Err(tr(Msg::DownloadCancelled).to_string());
     }
     if !curl_status.success() {
-        terminate_child_process(&mut ffmpeg_child);
+        terminate_child_process(ffmpeg_child.as_mut());
         let _ = fs::remove_file(output_path);
-        return Err(format!("curlが異常終了しました: {curl_status}"));
+        return Err(tr_fmt(Msg::ToolExitedWithError, &[&"curl", &curl_status]));
     }
 
     progress.mark_progress_started();
-    let _ = tx.send(DownloadEvent::Progress(ProgressUpdate::downloading(
-        100.0,
-        &progress.elapsed(),
-    )));
-    let _ = tx.send(DownloadEvent::Log("ダウンロード進捗: 100.0%".to_string()));
+    let stage = DownloadStage::Fetching {
+        bytes: downloaded,
+        total: Some(downloaded),
+    };
+    let _ = tx.send(DownloadEvent::Stage(stage, progress.elapsed()));
+    let _ = tx.send(DownloadEvent::Log(
+        LogLevel::Info,
+        tr_fmt(Msg::DownloadProgressPercent, &[&"100.0"]),
+    ));
     progress.set_post_processing();
-    let _ = tx.send(DownloadEvent::Progress(ProgressUpdate::post_processing(
-        &progress.elapsed(),
-    )));
+    let stage = DownloadStage::Converting {
+        pct: None,
+        pass: None,
+    };
+    let _ = tx.send(DownloadEvent::Stage(stage, progress.elapsed()));
 
     let ffmpeg_status = ffmpeg_child
         .wait()
-        .map_err(|err| format!("ffmpegの終了待ちに失敗しました: {err}"))?;
-    if cancel_flag.load(Ordering::Relaxed) {
+        .map_err(|err| tr_fmt(Msg::ToolWaitFailed, &[&"ffmpeg", &err]))?;
+    if cancel.is_cancelled() {
         let _ = fs::remove_file(output_path);
-        return Err(CANCELLED_ERROR.to_string());
+        return Err(tr(Msg::DownloadCancelled).to_string());
     }
     if !ffmpeg_status.success() {
         let _ = fs::remove_file(output_path);
-        return Err(format!("ffmpegが異常終了しました: {ffmpeg_status}"));
+        return Err(tr_fmt(
+            Msg::ToolExitedWithError,
+            &[&"ffmpeg", &ffmpeg_status],
+        ));
+    }
+    let stage = DownloadStage::Converting {
+        pct: Some(100.0),
+        pass: None,
+    };
+    let _ = tx.send(DownloadEvent::Stage(stage, progress.elapsed()));
+    let _ = tx.send(DownloadEvent::Log(
+        LogLevel::Info,
+        tr(Msg::FfmpegConversionDone).to_string(),
+    ));
+    // 受信しながら変換するため、回線が遅い場合は受信の時間も含まれる。
+    let duration = info.and_then(|info| info.duration_seconds);
+    if reencode
+        && let Some(log) = conversion_speed_log(duration, started.elapsed(), !hwaccel.is_empty())
+    {
+        let _ = tx.send(DownloadEvent::Log(LogLevel::Info, log));
     }
-    let _ = tx.send(DownloadEvent::Progress(ProgressUpdate::converting(
-        100.0,
-        &progress.elapsed(),
-    )));
-    let _ = tx.send(DownloadEvent::Log("ffmpeg変換が完了しました。".to_string()));
     Ok(())
 }
 
 // ffmpeg の stderr を解析して変換進捗を推定するスレッドを起動する。
-fn spawn_ffmpeg_conversion_thread<R: Read + Send + 'static>(
+pub(super) fn spawn_ffmpeg_conversion_thread<R: Read + Send + 'static>(
     reader: Option<R>,
     tx: &mpsc::Sender<DownloadEvent>,
     progress: &Arc<ProgressContext>,
     total_seconds: Option<f64>,
+    pass: Option<u8>,
 ) {
     if let Some(reader) = reader {
         let tx_clone = tx.clone();
         let progress_clone = progress.clone();
         thread::spawn(move || {
-            stream_ffmpeg_conversion_lines(reader, tx_clone, progress_clone, total_seconds)
+            stream_ffmpeg_conversion_lines(reader, tx_clone, progress_clone, total_seconds, pass)
         });
     }
 }
@@ -356,6 +478,7 @@ fn stream_ffmpeg_conversion_lines<R: Read + Send + 'static>(
     tx: mpsc::Sender<DownloadEvent>,
     progress: Arc<ProgressContext>,
     total_seconds: Option<f64>,
+    pass: Option<u8>,
 ) {
     let mut buffered = BufReader::new(reader);
     let mut buf = [0u8; 4096];
@@ -367,6 +490,7 @@ fn stream_ffmpeg_conversion_lines<R: Read + Send + 'static>(
             Ok(n) => n,
             Err(_) => break,
         };
+        progress.watchdog.touch();
         for &byte in &buf[..read] {
             if byte == b'\n' || byte == b'\r' {
                 if !line.is_empty() {
@@ -376,6 +500,7 @@ fn stream_ffmpeg_conversion_lines<R: Read + Send + 'static>(
                         &tx,
                         &progress,
                         total_seconds,
+                        pass,
                         &mut last_percent,
                     );
                     line.clear();
@@ -387,7 +512,7 @@ fn stream_ffmpeg_conversion_lines<R: Read + Send + 'static>(
     }
     if !line.is_empty() {
         let text = String::from_utf8_lossy(&line).to_string();
-        handle_ffmpeg_conversion_line(text, &tx, &progress, total_seconds, &mut last_percent);
+        handle_ffmpeg_conversion_line(text, &tx, &progress, total_seconds, pass, &mut last_percent);
     }
 }
 
@@ -397,6 +522,7 @@ fn handle_ffmpeg_conversion_line(
     tx: &mpsc::Sender<DownloadEvent>,
     progress: &Arc<ProgressContext>,
     total_seconds: Option<f64>,
+    pass: Option<u8>,
     last_percent: &mut f32,
 ) {
     let trimmed = line.trim();
@@ -410,16 +536,83 @@ fn handle_ffmpeg_conversion_line(
                 let percent = ((current / total) * 100.0).clamp(0.0, 100.0) as f32;
                 if percent >= *last_percent + 0.2 || percent >= 99.9 {
                     *last_percent = percent;
-                    let _ = tx.send(DownloadEvent::Progress(ProgressUpdate::converting(
-                        percent,
-                        &progress.elapsed(),
-                    )));
+                    let stage = DownloadStage::Converting {
+                        pct: Some(percent),
+                        pass,
+                    };
+                    let _ = tx.send(DownloadEvent::Stage(stage, progress.elapsed()));
+                }
+            }
+        }
+    }
+
+    let _ = tx.send(DownloadEvent::ToolLog(
+        LogSource::Ffmpeg,
+        trimmed.to_string(),
+    ));
+}
+
+// 直リンクを ffprobe で調べる。コーデックで変換の要否とデコード方式を決め、長さは変換速度の計算に使う。
+// 自動補正が有効なら、インターレース解除や SAR 補正を行う理由をログへ出す。
+fn probe_direct_video(
+    runner: &dyn CommandRunner,
+    webm_url: &str,
+    encoding: &VideoEncoding,
+    tx: &mpsc::Sender<DownloadEvent>,
+    cancel: &CancellationToken,
+) -> Option<MediaInfo> {
+    match probe_media_info(
+        runner,
+        OsStr::new(webm_url),
+        Some(ANIMETHEMES_USER_AGENT),
+        cancel,
+    ) {
+        Ok(mut info) => {
+            // AnimeThemes の webm は SDR のため、トーンマッピングは行わない。
+            info.hdr = false;
+            if encoding.profile != EncoderProfile::Copy {
+                for (_, reason) in video_filters(&info, encoding) {
+                    let _ = tx.send(DownloadEvent::Log(LogLevel::Info, reason.to_string()));
                 }
             }
+            Some(info)
+        }
+        Err(err) => {
+            let _ = tx.send(DownloadEvent::Log(
+                LogLevel::Warn,
+                tr_fmt(Msg::ConvertWithoutProbe, &[&err]),
+            ));
+            None
         }
     }
+}
 
-    let _ = tx.send(DownloadEvent::Log(trimmed.to_string()));
+// mp4 互換のコーデックの直リンクを、受信しながら再エンコードせずに mp4 へ詰め替えるコマンド。
+// mp4 はパイプでは読めない場合があるため、ffmpeg に直リンクを直接読ませる。
+fn direct_remux_command(
+    ffmpeg: &Path,
+    webm_url: &str,
+    args: &[String],
+    output_path: &Path,
+) -> CommandSpec {
+    CommandSpec::new(ffmpeg)
+        .args([
+            "-stats",
+            "-user_agent",
+            ANIMETHEMES_USER_AGENT,
+            "-i",
+            webm_url,
+        ])
+        .args(args.iter().cloned())
+        .args([
+            "-ignore_unknown",
+            "-movflags",
+            "+faststart",
+            "-f",
+            "mp4",
+            "-y",
+        ])
+        .arg(output_path)
 }
 
 fn parse_ffmpeg_time_seconds(line: &str) -> Option<f64> {
@@ -441,16 +634,20 @@ fn parse_hhmmss_to_seconds(value: &str) -> Option<f64> {
 }
 
 // HEAD/Range の順で Content-Length を取得し、進捗計算に使う。
-fn fetch_content_length(url: &str) -> Option<u64> {
-    let head_output = Command::new("curl")
-        .arg("-sIL")
-        .arg("-m")
-        .arg("8")
-        .arg("-A")
-        .arg(ANIMETHEMES_USER_AGENT)
-        .arg(url)
-        .output()
-        .ok()?;
+fn fetch_content_length(
+    runner: &dyn CommandRunner,
+    url: &str,
+    cancel: &CancellationToken,
+) -> Option<u64> {
+    let head = CommandSpec::new(Path::new("curl")).args([
+        "-sIL",
+        "-m",
+        "8",
+        "-A",
+        ANIMETHEMES_USER_AGENT,
+        url,
+    ]);
+    let head_output = output(runner, &head, cancel).ok()?;
     if head_output.status.success() {
         let headers = String::from_utf8_lossy(&head_output.stdout);
         if let Some(len) = parse_content_length_from_headers(&headers) {
@@ -458,21 +655,10 @@ fn fetch_content_length(url: &str) -> Option<u64> {
         }
     }
 
-    let range_output = Command::new("curl")
-        .arg("-sSL")
-        .arg("-m")
-        .arg("10")
-        .arg("-A")
-        .arg(ANIMETHEMES_USER_AGENT)
-        .arg("-r")
-        .arg("0-0")
-        .arg("-D")
-        .arg("-")
-        .arg("-o")
-        .arg("/dev/null")
-        .arg(url)
-        .output()
-        .ok()?;
+    let range = CommandSpec::new(Path::new("curl"))
+        .args(["-sSL", "-m", "10", "-A", ANIMETHEMES_USER_AGENT])
+        .args(["-r", "0-0", "-D", "-", "-o", "/dev/null", url]);
+    let range_output = output(runner, &range, cancel).ok()?;
     if !range_output.status.success() {
         return None;
     }
@@ -510,55 +696,29 @@ fn parse_content_range_total(headers: &str) -> Option<u64> {
     result
 }
 
-// Apple Silicon + h264_videotoolbox 前提を満たしているかを検証する。
-fn ensure_apple_silicon_gpu_encoder(ffmpeg: &Path) -> Result<(), String> {
-    if std::env::consts::ARCH != "aarch64" {
-        return Err(
-            "Apple Silicon環境のみ対応です。h264_videotoolbox(GPU)が必須です。".to_string(),
-        );
-    }
-    let output = Command::new(ffmpeg)
-        .arg("-hide_banner")
-        .arg("-encoders")
-        .output()
-        .map_err(|err| format!("ffmpegエンコーダ確認に失敗しました: {err}"))?;
-    if !output.status.success() {
-        return Err(format!(
-            "ffmpegエンコーダ確認に失敗しました: {}",
-            output.status
-        ));
-    }
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    let joined = format!("{stdout}\n{stderr}");
-    if !joined.contains("h264_videotoolbox") {
-        return Err(
-            "ffmpegにh264_videotoolboxがありません。Apple Silicon GPU変換を継続できません。"
-                .to_string(),
-        );
-    }
-    Ok(())
-}
-
 // API 取得を優先し、失敗時は HTML 解析で直リンクを探す。
 fn fetch_animethemes_direct_webm(
+    runner: &dyn CommandRunner,
     url: &str,
     tx: &mpsc::Sender<DownloadEvent>,
+    cancel: &CancellationToken,
 ) -> Result<Option<String>, String> {
-    if let Some(webm_url) = fetch_animethemes_webm_via_api(url, tx)? {
+    if let Some(webm_url) = fetch_animethemes_webm_via_api(runner, url, tx, cancel)? {
         return Ok(Some(webm_url));
     }
-    fetch_animethemes_webm_via_html(url, tx)
+    fetch_animethemes_webm_via_html(runner, url, tx, cancel)
 }
 
 fn fetch_animethemes_webm_via_api(
+    runner: &dyn CommandRunner,
     page_url: &str,
     tx: &mpsc::Sender<DownloadEvent>,
+    cancel: &CancellationToken,
 ) -> Result<Option<String>, String> {
     let Some((anime_slug, theme_slug)) = parse_animethemes_page_slugs(page_url) else {
         let _ = tx.send(DownloadEvent::Log(
-            "AnimeThemes URL解析に失敗。HTML解析へフォールバックします。".to_string(),
+            LogLevel::Warn,
+            tr(Msg::AnimeThemesSlugFailed).to_string(),
         ));
         return Ok(None);
     };
@@ -573,23 +733,21 @@ fn fetch_animethemes_webm_via_api(
     ];
 
     for api_url in api_urls {
-        let output = Command::new("curl")
-            .arg("-sL")
-            .arg("-m")
-            .arg("8")
-            .arg("-A")
-            .arg(ANIMETHEMES_USER_AGENT)
-            .arg("-H")
-            .arg("Accept: application/json")
-            .arg(&api_url)
-            .output()
-            .map_err(|err| format!("AnimeThemes API取得に失敗しました: {err}"))?;
+        let cmd = CommandSpec::new(Path::new("curl"))
+            .args(["-sL", "-m", "8", "-A", ANIMETHEMES_USER_AGENT])
+            .args(["-H", "Accept: application/json", api_url.as_str()]);
+        let output = output(runner, &cmd, cancel)
+            .map_err(|err| tr_fmt(Msg::AnimeThemesApiFailed, &[&err]))?;
+        cancel.check()?;
 
         if !output.status.success() {
-            let _ = tx.send(DownloadEvent::Log(format!(
-                "AnimeThemes API取得に失敗しました: {} ({api_url})",
-                output.status
-            )));
+            let _ = tx.send(DownloadEvent::Log(
+                LogLevel::Warn,
+                tr_fmt(
+                    Msg::AnimeThemesApiFailed,
+                    &[&format!("{} ({api_url})", output.status)],
+                ),
+            ));
             continue;
         }
 
@@ -598,42 +756,40 @@ fn fetch_animethemes_webm_via_api(
             Ok(Some(webm_url)) => return Ok(Some(webm_url)),
             Ok(None) => continue,
             Err(reason) => {
-                let _ = tx.send(DownloadEvent::Log(format!(
-                    "AnimeThemes APIレスポンス解析に失敗しました: {reason} ({api_url})"
-                )));
+                let _ = tx.send(DownloadEvent::Log(
+                    LogLevel::Warn,
+                    tr_fmt(Msg::AnimeThemesApiParseFailed, &[&reason, &api_url]),
+                ));
                 continue;
             }
         }
     }
 
     let _ = tx.send(DownloadEvent::Log(
-        "AnimeThemes APIに対象テーマの直リンクがありません。HTML解析へフォールバックします。"
-            .to_string(),
+        LogLevel::Warn,
+        tr(Msg::AnimeThemesApiNoLink).to_string(),
     ));
     Ok(None)
 }
 
 fn fetch_animethemes_webm_via_html(
+    runner: &dyn CommandRunner,
     url: &str,
     tx: &mpsc::Sender<DownloadEvent>,
+    cancel: &CancellationToken,
 ) -> Result<Option<String>, String> {
-    let range_output = Command::new("curl")
-        .arg("-sL")
-        .arg("-m")
-        .arg("8")
-        .arg("-A")
-        .arg(ANIMETHEMES_USER_AGENT)
-        .arg("--range")
-        .arg(ANIMETHEMES_HTML_RANGE)
-        .arg(url)
-        .output()
-        .map_err(|err| format!("curl起動に失敗しました: {err}"))?;
+    let range = CommandSpec::new(Path::new("curl"))
+        .args(["-sL", "-m", "8", "-A", ANIMETHEMES_USER_AGENT])
+        .args(["--range", ANIMETHEMES_HTML_RANGE, url]);
+    let range_output = output(runner, &range, cancel)
+        .map_err(|err| tr_fmt(Msg::ToolLaunchFailed, &[&"curl", &err]))?;
+    cancel.check()?;
 
     if !range_output.status.success() {
-        let _ = tx.send(DownloadEvent::Log(format!(
-            "AnimeThemesページ取得に失敗しました: {}",
-            range_output.status
-        )));
+        let _ = tx.send(DownloadEvent::Log(
+            LogLevel::Warn,
+            tr_fmt(Msg::AnimeThemesPageFailed, &[&range_output.status]),
+        ));
         return Ok(None);
     }
 
@@ -643,24 +799,26 @@ fn fetch_animethemes_webm_via_html(
     }
 
     let _ = tx.send(DownloadEvent::Log(
-        "AnimeThemes HTML部分取得では直リンクが見つかりません。全文取得で再試行します。"
-            .to_string(),
+        LogLevel::Warn,
+        tr(Msg::AnimeThemesPartialNoLink).to_string(),
     ));
-    let full_output = Command::new("curl")
-        .arg("-sL")
-        .arg("-m")
-        .arg("8")
-        .arg("-A")
-        .arg(ANIMETHEMES_USER_AGENT)
-        .arg(url)
-        .output()
-        .map_err(|err| format!("curl起動に失敗しました: {err}"))?;
+    let full = CommandSpec::new(Path::new("curl")).args([
+        "-sL",
+        "-m",
+        "8",
+        "-A",
+        ANIMETHEMES_USER_AGENT,
+        url,
+    ]);
+    let full_output = output(runner, &full, cancel)
+        .map_err(|err| tr_fmt(Msg::ToolLaunchFailed, &[&"curl", &err]))?;
+    cancel.check()?;
 
     if !full_output.status.success() {
-        let _ = tx.send(DownloadEvent::Log(format!(
-            "AnimeThemesページ全文取得に失敗しました: {}",
-            full_output.status
-        )));
+        let _ = tx.send(DownloadEvent::Log(
+            LogLevel::Warn,
+            tr_fmt(Msg::AnimeThemesFullPageFailed, &[&full_output.status]),
+        ));
         return Ok(None);
     }
 
@@ -685,7 +843,7 @@ fn extract_animethemes_webm_from_api_json(
     theme_slug: &str,
 ) -> Result<Option<String>, String> {
     let value: Value =
-        serde_json::from_str(json).map_err(|err| format!("JSON解析に失敗しました: {err}"))?;
+        serde_json::from_str(json).map_err(|err| tr_fmt(Msg::JsonParseFailed, &[&err]))?;
     if let Some(link) = extract_animethemes_webm_from_json_api(&value, theme_slug) {
         return Ok(Some(link));
     }
@@ -954,7 +1112,7 @@ fn extract_animethemes_webm(html: &str) -> Option<String> {
 }
 
 // AnimeThemes URL の末尾を使って保存ファイル名を生成する。
-fn build_animethemes_output_path(url: &str, output_dir: &Path) -> PathBuf {
+fn build_animethemes_output_path(url: &str, output_dir: &Path, mode: FilenameMode) -> PathBuf {
     let timestamp = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .map(|d| d.as_millis())
@@ -1000,20 +1158,36 @@ fn build_animethemes_output_path(url: &str, output_dir: &Path) -> PathBuf {
     }
 
     let base = picked.join("-");
-    let mut safe_base = sanitize_filename_component(&base);
+    let mut safe_base = sanitize_filename_component(&base, mode);
     if safe_base.trim().is_empty() {
         safe_base = "animethemes".to_string();
     }
     output_dir.join(format!("{safe_base}-{timestamp}.mp4"))
 }
 
-fn sanitize_filename_component(raw: &str) -> String {
+// raw は URL のパス（パーセントエンコードされたまま）を受け取る。
+fn sanitize_filename_component(raw: &str, mode: FilenameMode) -> String {
     let mut out = String::with_capacity(raw.len());
-    for ch in raw.chars() {
-        if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' || ch == '.' {
-            out.push(ch);
-        } else {
-            out.push('_');
+    match mode {
+        FilenameMode::Ascii => {
+            for ch in raw.chars() {
+                if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' || ch == '.' {
+                    out.push(ch);
+                } else {
+                    out.push('_');
+                }
+            }
+        }
+        FilenameMode::Unicode => {
+            for ch in percent_decode(raw).chars() {
+                if ch == '/' || ch == ':' || ch.is_control() {
+                    out.push('_');
+                } else if out.len() + ch.len_utf8() <= MAX_FILENAME_BASE_BYTES {
+                    out.push(ch);
+                } else {
+                    break;
+                }
+            }
         }
     }
     if out.is_empty() {
@@ -1023,12 +1197,61 @@ fn sanitize_filename_component(raw: &str) -> String {
     }
 }
 
+// %XX を戻す。UTF-8 として読めないバイトは置換文字にする。
+fn percent_decode(raw: &str) -> String {
+    let bytes = raw.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let hex = bytes
+            .get(idx + 1..idx + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match hex {
+            Some(byte) if bytes[idx] == b'%' => {
+                decoded.push(byte);
+                idx += 3;
+            }
+            _ => {
+                decoded.push(bytes[idx]);
+                idx += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
-        extract_animethemes_webm_from_api_json, parse_content_length_from_headers,
-        parse_content_range_total,
+        ANIMETHEMES_USER_AGENT, FilenameMode, extract_animethemes_webm_from_api_json,
+        fetch_content_length, parse_content_length_from_headers, parse_content_range_total,
+        sanitize_filename_component,
     };
+    use crate::download::CancellationToken;
+    use crate::download::runner::fake::{FakeRun, FakeRunner};
+
+    #[test]
+    fn sanitizes_filename_by_mode() {
+        let raw = "%E9%80%B2%E6%92%83%E3%81%AE%E5%B7%A8%E4%BA%BA-OP1:v2";
+        assert_eq!(
+            sanitize_filename_component(raw, FilenameMode::Unicode),
+            "進撃の巨人-OP1_v2"
+        );
+        assert_eq!(
+            sanitize_filename_component("a%2Fb%ZZ", FilenameMode::Unicode),
+            "a_b%ZZ"
+        );
+        assert_eq!(
+            sanitize_filename_component("Shingeki-OP1:v2", FilenameMode::Ascii),
+            "Shingeki-OP1_v2"
+        );
+        let long = "あ".repeat(100);
+        assert_eq!(
+            sanitize_filename_component(&long, FilenameMode::Unicode).len(),
+            198
+        );
+    }
 
     #[test]
     fn extracts_webm_from_json_api_included_response() {
@@ -1184,4 +1407,36 @@ mod tests {
         let headers = "HTTP/2 200\r\nContent-Length: 75350559\r\n";
         assert_eq!(parse_content_length_from_headers(headers), Some(75_350_559));
     }
+
+    // HEAD に失敗した場合は、先頭 1 バイトの取得で合計サイズを調べる。
+    #[test]
+    fn falls_back_to_range_request_for_content_length() {
+        let url = "https://v.animethemes.moe/Clip-OP1.webm";
+        let runner = FakeRunner::new(vec![
+            FakeRun::exit(22),
+            FakeRun::Exit {
+                code: 0,
+                stdout: "HTTP/2 206\r\nContent-Range: bytes 0-0/48937934\r\n",
+                stderr: "",
+            },
+        ]);
+        assert_eq!(
+            fetch_content_length(&runner, url, &CancellationToken::new()),
+            Some(48_937_934)
+        );
+        let calls = runner.calls();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].program.to_str(), Some("curl"));
+        assert_eq!(
+            calls[0].args,
+            ["-sIL", "-m", "8", "-A", ANIMETHEMES_USER_AGENT, url]
+        );
+        assert!(calls[1].args.iter().any(|arg| arg == "0-0"));
+
+        let runner = FakeRunner::new(vec![FakeRun::exit(6), FakeRun::exit(6)]);
+        assert_eq!(
+            fetch_content_length(&runner, url, &CancellationToken::new()),
+            None
+        );
+    }
 }
@@ -0,0 +1,92 @@
+use std::path::PathBuf;
+
+use crate::paths::aria2c_path;
+use crate::platform::{executable_name, is_executable};
+use crate::settings::load_use_aria2c;
+
+use super::stage::parse_size;
+
+// 大きなファイルや断片の多い 4K を速く取得するため、1 ファイルを 16 本の接続に分けて取得する。
+// 進捗は 1 秒ごとの概要行から読む。
+const ARIA2C_ARGS: &str =
+    "aria2c:-x 16 -s 16 -k 1M --file-allocation=none --summary-interval=1 --console-log-level=warn";
+
+// アプリの bin フォルダ、Homebrew、PATH の順に aria2c を探す。aria2c は自動で取得しない。
+pub fn detect_aria2c() -> Option<PathBuf> {
+    let mut candidates = vec![
+        aria2c_path(),
+        PathBuf::from("/opt/homebrew/bin/aria2c"),
+        PathBuf::from("/usr/local/bin/aria2c"),
+    ];
+    if let Some(path_env) = std::env::var_os("PATH") {
+        for dir in std::env::split_paths(&path_env) {
+            candidates.push(dir.join(executable_name("aria2c")));
+        }
+    }
+
+    candidates
+        .into_iter()
+        .find(|candidate| candidate.exists() && is_executable(candidate))
+}
+
+// 設定で aria2c を使う場合に、yt-dlp へ渡す外部ダウンローダーの引数。見つからない場合は空。
+pub(super) fn downloader_args() -> Vec<String> {
+    if !load_use_aria2c() {
+        return Vec::new();
+    }
+    let Some(aria2c) = detect_aria2c() else {
+        return Vec::new();
+    };
+    vec![
+        "--downloader".to_string(),
+        aria2c.to_string_lossy().to_string(),
+        "--downloader-args".to_string(),
+        ARIA2C_ARGS.to_string(),
+    ]
+}
+
+// aria2c の概要行 `[#2089b0 400MiB/1.2GiB(33%) CN:16 DL:115MiB ETA:7s]` から取得済みと合計のバイト数を読む。
+pub(super) fn parse_progress(line: &str) -> Option<(u64, u64)> {
+    let sizes = line.strip_prefix("[#")?.split_whitespace().nth(1)?;
+    let (bytes, rest) = sizes.split_once('/')?;
+    let total = rest.split('(').next()?;
+    Some((parse_size(bytes)?, parse_size(total)?))
+}
+
+// 同じ概要行の `DL:115MiB` から速度（バイト/秒）を読む。
+pub(super) fn parse_speed(line: &str) -> Option<u64> {
+    if !line.starts_with("[#") {
+        return None;
+    }
+    let value = line
+        .trim_end_matches(']')
+        .split_whitespace()
+        .find_map(|part| part.strip_prefix("DL:"))?;
+    parse_size(value).filter(|bytes| *bytes > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_aria2c_summary_line() {
+        assert_eq!(
+            parse_speed("[#2089b0 400MiB/1.2GiB(33%) CN:16 DL:115MiB ETA:7s]"),
+            Some(115 * 1024 * 1024)
+        );
+        assert_eq!(
+            parse_speed("[#2089b0 1.0MiB/10MiB(10%) CN:4 DL:512KiB]"),
+            Some(512 * 1024)
+        );
+        assert_eq!(parse_speed("[#2089b0 0B/0B CN:1 DL:0B]"), None);
+        assert_eq!(
+            parse_progress("[#2089b0 400MiB/1.2GiB(33%) CN:16 DL:115MiB ETA:7s]"),
+            Some((400 * 1024 * 1024, (1.2 * 1024.0 * 1024.0 * 1024.0) as u64))
+        );
+        assert_eq!(
+            parse_speed("[download]  12.3% of 80.00MiB at 3.21MiB/s"),
+            None
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use std::path::Path;
+use std::sync::mpsc;
+
+use serde_json::Value;
+
+use crate::app_logger::LogLevel;
+use crate::i18n::{Msg, tr, tr_fmt};
+
+use super::process::fetch_yt_dlp_info;
+use super::runner::CommandRunner;
+use super::{CancellationToken, DownloadEvent, wait_for_reply};
+
+// 選べる音声トラック（yt-dlp の language ごと）
+#[derive(Clone, Debug, PartialEq)]
+pub struct AudioTrack {
+    pub language: String,
+    pub label: String,
+}
+
+// 複数の音声トラックがある場合に、どれを使うか選んでもらう。
+pub struct AudioTrackPrompt {
+    pub tracks: Vec<AudioTrack>,
+    reply: mpsc::Sender<Option<String>>,
+}
+
+impl AudioTrackPrompt {
+    // 選んだトラックの language を返す。None は既定のトラックを使う。
+    pub fn respond(&self, language: Option<String>) {
+        let _ = self.reply.send(language);
+    }
+}
+
+// `yt-dlp -J` の formats から、音声のみのフォーマットを language ごとにまとめる。
+pub(super) fn parse_audio_tracks(json: &str) -> Result<Vec<AudioTrack>, String> {
+    let value: Value =
+        serde_json::from_str(json).map_err(|err| tr_fmt(Msg::VideoInfoParseFailed, &[&err]))?;
+    let formats = value
+        .get("formats")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+
+    // language ごとに、ビットレートが最も高いフォーマットの説明を使う。
+    let mut best: Vec<(String, f64, String)> = Vec::new();
+    for format in &formats {
+        let text = |key: &str| format.get(key).and_then(Value::as_str).unwrap_or_default();
+        let audio_only = text("vcodec") == "none" && !matches!(text("acodec"), "" | "none");
+        let language = text("language").trim();
+        if !audio_only || language.is_empty() {
+            continue;
+        }
+        let bitrate = format.get("abr").and_then(Value::as_f64).unwrap_or(0.0);
+        // format_note は「Japanese original (default), medium」のような形式のため、品質の部分を除く。
+        let note = text("format_note")
+            .split(',')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+        match best.iter_mut().find(|(lang, _, _)| lang == language) {
+            Some(entry) if entry.1 < bitrate => {
+                entry.1 = bitrate;
+                entry.2 = note;
+            }
+            Some(_) => {}
+            None => best.push((language.to_string(), bitrate, note)),
+        }
+    }
+
+    let mut tracks = best
+        .into_iter()
+        .map(|(language, _, note)| AudioTrack {
+            label: if note.is_empty() {
+                language.clone()
+            } else {
+                format!("{language}: {note}")
+            },
+            language,
+        })
+        .collect::<Vec<_>>();
+    tracks.sort_by(|a, b| a.language.cmp(&b.language));
+    Ok(tracks)
+}
+
+fn fetch_audio_tracks(
+    runner: &dyn CommandRunner,
+    yt_dlp: &Path,
+    url: &str,
+    auth_args: &[String],
+    js_runtime: &[String],
+    cancel: &CancellationToken,
+) -> Result<Vec<AudioTrack>, String> {
+    let mut args = vec!["--no-playlist".to_string()];
+    args.extend(auth_args.iter().cloned());
+    args.extend([
+        "--extractor-args".to_string(),
+        "youtube:player_client=web".to_string(),
+    ]);
+    args.extend(js_runtime.iter().cloned());
+    parse_audio_tracks(&fetch_yt_dlp_info(runner, yt_dlp, &args, url, cancel)?)
+}
+
+// 音声トラックが複数ある場合に選んでもらい、使うトラックの language を返す。
+// 1 つしか無い場合や確認に失敗した場合は None（既定のトラック）を返す。
+pub(super) fn choose_audio_track(
+    runner: &dyn CommandRunner,
+    yt_dlp: &Path,
+    url: &str,
+    auth_args: &[String],
+    js_runtime: &[String],
+    tx: &mpsc::Sender<DownloadEvent>,
+    cancel: &CancellationToken,
+) -> Result<Option<String>, String> {
+    let _ = tx.send(DownloadEvent::Log(
+        LogLevel::Info,
+        tr(Msg::CheckingAudioTracks).to_string(),
+    ));
+    let tracks = match fetch_audio_tracks(runner, yt_dlp, url, auth_args, js_runtime, cancel) {
+        Ok(tracks) => tracks,
+        Err(err) => {
+            cancel.check()?;
+            let _ = tx.send(DownloadEvent::Log(
+                LogLevel::Warn,
+                tr_fmt(Msg::AudioTrackFallback, &[&err]),
+            ));
+            return Ok(None);
+        }
+    };
+    if tracks.len() < 2 {
+        return Ok(None);
+    }
+
+    let _ = tx.send(DownloadEvent::Log(
+        LogLevel::Info,
+        tr_fmt(Msg::AudioTracksFound, &[&tracks.len()]),
+    ));
+    let (reply, answer) = mpsc::channel();
+    let _ = tx.send(DownloadEvent::AudioTrackPrompt(AudioTrackPrompt {
+        tracks,
+        reply,
+    }));
+    // 選択画面が閉じられた場合は既定のトラックを使う。
+    let language = wait_for_reply(&answer, cancel)?.flatten();
+    let _ = tx.send(DownloadEvent::Log(
+        LogLevel::Info,
+        match &language {
+            Some(language) => tr_fmt(Msg::DownloadWithAudioTrack, &[language]),
+            None => tr(Msg::DownloadWithDefaultAudio).to_string(),
+        },
+    ));
+    Ok(language)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_audio_formats_by_language() {
+        let json = r#"{
+            "formats": [
+                {"format_id": "139-0", "vcodec": "none", "acodec": "mp4a.40.5", "language": "ja", "format_note": "Japanese original (default), low", "abr": 48.0},
+                {"format_id": "140-0", "vcodec": "none", "acodec": "mp4a.40.2", "language": "ja", "format_note": "Japanese original (default), medium", "abr": 129.5},
+                {"format_id": "140-1", "vcodec": "none", "acodec": "mp4a.40.2", "language": "en-US", "format_note": "English (United States), medium", "abr": 129.5},
+                {"format_id": "137", "vcodec": "avc1.640028", "acodec": "none", "language": null},
+                {"format_id": "18", "vcodec": "avc1.42001E", "acodec": "mp4a.40.2", "language": "ja"}
+            ]
+        }"#;
+        let tracks = parse_audio_tracks(json).unwrap();
+        assert_eq!(
+            tracks,
+            [
+                AudioTrack {
+                    language: "en-US".to_string(),
+                    label: "en-US: English (United States)".to_string(),
+                },
+                AudioTrack {
+                    language: "ja".to_string(),
+                    label: "ja: Japanese original (default)".to_string(),
+                },
+            ]
+        );
+        assert!(parse_audio_tracks(r#"{"formats": []}"#).unwrap().is_empty());
+    }
+}
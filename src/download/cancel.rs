@@ -0,0 +1,272 @@
+use std::fs;
+use std::io;
+use std::ops::{Deref, DerefMut};
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitStatus};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::i18n::{Msg, tr};
+use crate::platform;
+
+// 終了を促してから、残ったプロセスを強制終了するまでの猶予。
+// yt-dlp や ffmpeg が書きかけのファイルを片付ける時間を残す。
+const KILL_GRACE: Duration = Duration::from_millis(500);
+
+// 1 件のダウンロードの取り消し。起動した外部プロセス（yt-dlp・ffmpeg・直リンクを受信する curl）を
+// プロセスグループごとに覚えておき、取り消した時点で孫プロセスも含めてまとめて止める。
+// 複製しても同じ取り消しを共有する。
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    // 終了を待ち終えていない子プロセスのプロセスグループ番号。
+    // 子プロセスはグループの先頭として起動するため、起動時のプロセス番号と同じ値になる。
+    groups: Arc<Mutex<Vec<u32>>>,
+    temp_files: Arc<TempFiles>,
+}
+
+// ダウンロードを終えたら消す一時ファイル（yt-dlp に渡す netrc など）。
+// 後始末を呼ばずに最後の複製が破棄された場合も消す。
+#[derive(Default)]
+struct TempFiles(Mutex<Vec<PathBuf>>);
+
+impl TempFiles {
+    fn remove_all(&self) {
+        for path in self.0.lock().unwrap().drain(..) {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+impl Drop for TempFiles {
+    fn drop(&mut self) {
+        self.remove_all();
+    }
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 取り消しを記録し、起動中の外部プロセスを止める。
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        let groups = self.groups.lock().unwrap().clone();
+        Self::stop(groups);
+    }
+
+    // グループごとに終了を促し、猶予の後に同じグループを強制終了する。
+    // 先頭のプロセスが先に終わって回収されても、残った孫プロセスを止めるためにグループへ送る。
+    // 画面から呼ばれるため、猶予は別スレッドで待つ。
+    fn stop(groups: Vec<u32>) {
+        if groups.is_empty() {
+            return;
+        }
+        platform::terminate_process_groups(&groups);
+        thread::spawn(move || {
+            thread::sleep(KILL_GRACE);
+            platform::kill_process_groups(&groups);
+        });
+    }
+
+    // ダウンロードを終えた時に消すファイルとして覚える。
+    pub(super) fn remove_when_finished(&self, path: PathBuf) {
+        self.temp_files.0.lock().unwrap().push(path);
+    }
+
+    // 成功・失敗・取り消しのいずれかでダウンロード処理を終えた時に呼び、一時ファイルを消す。
+    pub(super) fn finish(&self) {
+        self.temp_files.remove_all();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    // 取り消されていれば、ダウンロード処理を中断するエラーを返す。
+    // 取り消しかどうかはエラーの文言ではなく、このトークンで判定する。
+    pub(super) fn check(&self) -> Result<(), String> {
+        if self.is_cancelled() {
+            Err(tr(Msg::DownloadCancelled).to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    // 外部プロセスを新しいプロセスグループで起動して覚える。
+    pub(super) fn spawn(&self, command: &mut Command) -> io::Result<TrackedChild> {
+        platform::use_own_process_group(command);
+        let child = command.spawn()?;
+        let pgid = child.id();
+        self.register(pgid);
+        Ok(TrackedChild {
+            child,
+            pgid,
+            groups: Arc::clone(&self.groups),
+        })
+    }
+
+    // 起動した外部プロセスのグループを覚える。取り消しと同時に起動したプロセスも取りこぼさないよう、
+    // 覚えた後に取り消し済みならすぐに止める。
+    fn register(&self, pgid: u32) {
+        if pgid == 0 {
+            return;
+        }
+        {
+            let mut groups = self.groups.lock().unwrap();
+            if !groups.contains(&pgid) {
+                groups.push(pgid);
+            }
+        }
+        if self.is_cancelled() {
+            Self::stop(vec![pgid]);
+        }
+    }
+}
+
+// CancellationToken::spawn で起動した子プロセス。終了を待ち終えた時点で、以後の取り消しの対象から外す。
+// 回収済みのプロセス番号は別のプロセスに使い回されるため、後から取り消した時には止めない。
+pub(super) struct TrackedChild {
+    child: Child,
+    pgid: u32,
+    groups: Arc<Mutex<Vec<u32>>>,
+}
+
+impl TrackedChild {
+    pub(super) fn wait(&mut self) -> io::Result<ExitStatus> {
+        let status = self.child.wait();
+        self.forget();
+        status
+    }
+
+    fn forget(&self) {
+        self.groups
+            .lock()
+            .unwrap()
+            .retain(|registered| *registered != self.pgid);
+    }
+}
+
+impl Deref for TrackedChild {
+    type Target = Child;
+
+    fn deref(&self) -> &Child {
+        &self.child
+    }
+}
+
+impl DerefMut for TrackedChild {
+    fn deref_mut(&mut self) -> &mut Child {
+        &mut self.child
+    }
+}
+
+// 待たずに手放した場合も、一覧に残さない。
+impl Drop for TrackedChild {
+    fn drop(&mut self) {
+        self.forget();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(unix)]
+    use std::path::Path;
+
+    use super::*;
+    #[cfg(unix)]
+    use crate::download::runner::{CommandSpec, SystemRunner, output};
+
+    #[test]
+    fn shares_cancellation_between_clones() {
+        let token = CancellationToken::new();
+        let worker = token.clone();
+        assert_eq!(worker.check(), Ok(()));
+
+        token.cancel();
+        assert!(worker.is_cancelled());
+        assert_eq!(worker.check(), Err(tr(Msg::DownloadCancelled).to_string()));
+
+        // 取り消した後に起動したコマンドは、終わるのを待たずに止める。
+        #[cfg(unix)]
+        {
+            let started = std::time::Instant::now();
+            let sleep = CommandSpec::new(Path::new("sleep")).arg("5");
+            let output = output(&SystemRunner, &sleep, &worker).unwrap();
+            assert!(!output.status.success());
+            assert!(started.elapsed() < std::time::Duration::from_secs(5));
+        }
+    }
+
+    // 子プロセスが起動した孫プロセスも止まり、出力の終わりを待ち続けない。
+    #[cfg(unix)]
+    #[test]
+    fn stops_grandchild_processes() {
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(300));
+            canceller.cancel();
+        });
+
+        let started = std::time::Instant::now();
+        let sh = CommandSpec::new(Path::new("sh")).args(["-c", "sleep 5 & sleep 5; wait"]);
+        let output = output(&SystemRunner, &sh, &token).unwrap();
+        handle.join().unwrap();
+        assert!(!output.status.success());
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+        assert!(token.groups.lock().unwrap().is_empty());
+    }
+
+    // 終了を待ち終えたプロセスの番号は、使い回されても止めないよう一覧から外す。
+    #[cfg(unix)]
+    #[test]
+    fn forgets_waited_processes() {
+        let token = CancellationToken::new();
+        let mut child = token.spawn(&mut Command::new("true")).unwrap();
+        assert_eq!(*token.groups.lock().unwrap(), vec![child.id()]);
+
+        assert!(child.wait().unwrap().success());
+        assert!(token.groups.lock().unwrap().is_empty());
+    }
+
+    // 先頭のプロセスが終了を促されてすぐ終わり、回収された後も、
+    // 終了の合図を無視する孫プロセスは猶予の後に強制終了する。
+    #[cfg(unix)]
+    #[test]
+    fn kills_group_after_leader_is_reaped() {
+        use std::io::{BufRead, BufReader};
+        use std::process::Stdio;
+
+        let token = CancellationToken::new();
+        let mut command = Command::new("sh");
+        command
+            .args([
+                "-c",
+                "trap '' TERM; sleep 5 >/dev/null & trap - TERM; echo $!; exec sleep 5",
+            ])
+            .stdout(Stdio::piped());
+        let mut child = token.spawn(&mut command).unwrap();
+        let mut line = String::new();
+        BufReader::new(child.stdout.take().unwrap())
+            .read_line(&mut line)
+            .unwrap();
+        let grandchild = line.trim().to_string();
+
+        token.cancel();
+        child.wait().unwrap();
+        assert!(token.groups.lock().unwrap().is_empty());
+
+        std::thread::sleep(KILL_GRACE + std::time::Duration::from_millis(500));
+        // 終了済み（回収待ちを含む）なら ps に出ないか、状態が Z になる。
+        let ps = Command::new("ps")
+            .args(["-o", "stat=", "-p", &grandchild])
+            .output()
+            .unwrap();
+        let stat = String::from_utf8_lossy(&ps.stdout);
+        assert!(stat.trim().is_empty() || stat.trim_start().starts_with('Z'));
+    }
+}
@@ -0,0 +1,364 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+use serde_json::Value;
+
+use crate::app_logger::LogLevel;
+use crate::i18n::{Msg, tr, tr_fmt};
+use crate::settings::load_chapter_split;
+use crate::video_info::{info_json_path, store_info_json};
+
+use super::convert::probe_media_info;
+use super::runner::CommandRunner;
+use super::scene_split::{segment_pattern, write_segments};
+use super::staging::{StagingDir, create_download_staging_dir, next_available_destination};
+use super::{CancellationToken, DownloadEvent};
+
+// これより短い残りしかないチャプターは、前のクリップに含める（秒）
+const MIN_CHAPTER_SECONDS: f64 = 1.0;
+// クリップ名に使うチャプター名の長さの上限（文字数）
+const MAX_TITLE_CHARS: usize = 120;
+
+// 切り分ける 1 区間。終わりは次のチャプターの始まり（最後は動画の終わり）とする。
+#[derive(Clone, Debug, PartialEq)]
+pub struct Chapter {
+    pub start_seconds: f64,
+    pub title: String,
+}
+
+// `1:23:45`・`12:34`・`0:05` の形の時刻を秒にする。
+fn parse_timestamp(text: &str) -> Option<f64> {
+    let parts = text.split(':').collect::<Vec<_>>();
+    if !(2..=3).contains(&parts.len()) {
+        return None;
+    }
+    let mut seconds = 0_u64;
+    for (index, part) in parts.iter().enumerate() {
+        if part.is_empty() || !part.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let value: u64 = part.parse().ok()?;
+        // 先頭以外の分・秒は 60 未満に限る。
+        if index > 0 && (value >= 60 || part.len() != 2) {
+            return None;
+        }
+        seconds = seconds * 60 + value;
+    }
+    Some(seconds as f64)
+}
+
+// チャプター名の前後に付く区切り（`0:00 - Intro`、`[0:00] | Intro` など）を外す。
+// 名前の末尾の括弧（`Track (Remix)`）は残す。
+fn trim_separators(text: &str) -> &str {
+    text.trim_start_matches(|c: char| c.is_whitespace() || "-–—|:・.)]".contains(c))
+        .trim_end_matches(|c: char| c.is_whitespace() || "-–—|:・".contains(c))
+}
+
+// 1 行から時刻とチャプター名を読む。行の中で最初に見つかった時刻を使い、名前はその後ろ（無ければ前）とする。
+fn parse_timestamp_line(line: &str) -> Option<Chapter> {
+    let mut offset = 0;
+    for token in line.split_whitespace() {
+        let position = offset + line[offset..].find(token)?;
+        offset = position + token.len();
+        let bare = token.trim_matches(|c: char| "[]()".contains(c));
+        let bare = bare.trim_end_matches(|c: char| "-–—|,".contains(c));
+        let Some(start_seconds) = parse_timestamp(bare) else {
+            continue;
+        };
+        let after = trim_separators(&line[offset..]);
+        let title = if after.is_empty() {
+            // 「01. Intro 0:00」のような行では、先頭の番号を除いた前半を名前にする。
+            trim_separators(line[..position].trim_start_matches(|c: char| c.is_ascii_digit()))
+        } else {
+            after
+        };
+        return Some(Chapter {
+            start_seconds,
+            title: title.to_string(),
+        });
+    }
+    None
+}
+
+// 貼り付けたタイムスタンプの一覧を読む。時刻の無い行は飛ばし、時刻は増えていく順でなければならない。
+pub fn parse_timestamp_list(text: &str) -> Result<Vec<Chapter>, String> {
+    let mut chapters: Vec<Chapter> = Vec::new();
+    for line in text.lines() {
+        let Some(chapter) = parse_timestamp_line(line) else {
+            continue;
+        };
+        if chapters
+            .last()
+            .is_some_and(|last| chapter.start_seconds <= last.start_seconds)
+        {
+            return Err(tr_fmt(Msg::ChapterListOutOfOrder, &[&line.trim()]));
+        }
+        chapters.push(chapter);
+    }
+    if chapters.len() < 2 {
+        return Err(tr(Msg::ChapterListTooShort).to_string());
+    }
+    Ok(chapters)
+}
+
+// yt-dlp の info JSON の `chapters` を読む。
+fn chapters_from_info_json(json: &str) -> Vec<Chapter> {
+    let Ok(value) = serde_json::from_str::<Value>(json) else {
+        return Vec::new();
+    };
+    value
+        .get("chapters")
+        .and_then(Value::as_array)
+        .map(|chapters| {
+            chapters
+                .iter()
+                .filter_map(|chapter| {
+                    Some(Chapter {
+                        start_seconds: chapter.get("start_time")?.as_f64()?,
+                        title: chapter
+                            .get("title")
+                            .and_then(Value::as_str)
+                            .unwrap_or_default()
+                            .trim()
+                            .to_string(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// 分割する時刻。最初のチャプターより前の部分は最初のクリップに含め、動画の終わりを越えるチャプターは使わない。
+fn chapter_points(chapters: &[Chapter], duration_seconds: f64) -> Vec<f64> {
+    chapters
+        .iter()
+        .skip(1)
+        .map(|chapter| chapter.start_seconds)
+        .take_while(|&start| duration_seconds - start >= MIN_CHAPTER_SECONDS)
+        .collect()
+}
+
+// クリップのファイル名。`<番号> <チャプター名>.mp4` とし、名前が無い場合は元の名前に番号を付ける。
+fn chapter_file_name(index: usize, title: &str, stem: &str) -> String {
+    let title = title
+        .chars()
+        .map(|c| {
+            if c.is_control() || "/\\:*?\"<>|".contains(c) {
+                '_'
+            } else {
+                c
+            }
+        })
+        .take(MAX_TITLE_CHARS)
+        .collect::<String>();
+    // 先頭の「.」は隠しファイルになるため外す。
+    let title = title.trim().trim_start_matches('.').trim();
+    if title.is_empty() {
+        format!("{stem} {:02}.mp4", index + 1)
+    } else {
+        format!("{:02} {title}.mp4", index + 1)
+    }
+}
+
+// 保存した動画をチャプターごとに分割し、チャプター名を付けたクリップを元の動画の隣に置く。元の動画は残す。
+// 動画が短く 2 つ以上に分けられない場合は分割せず、空の一覧を返す。
+fn split_into_chapters(
+    runner: &dyn CommandRunner,
+    path: &Path,
+    output_dir: &Path,
+    ffmpeg: &Path,
+    chapters: &[Chapter],
+    tx: &mpsc::Sender<DownloadEvent>,
+    cancel: &CancellationToken,
+) -> Result<Vec<PathBuf>, String> {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let _ = tx.send(DownloadEvent::Log(
+        LogLevel::Info,
+        tr_fmt(Msg::ChapterSplitStarted, &[&name, &chapters.len()]),
+    ));
+
+    let duration = probe_media_info(runner, path.as_os_str(), None, cancel)?
+        .duration_seconds
+        .unwrap_or(0.0);
+    let points = chapter_points(chapters, duration);
+    if points.is_empty() {
+        let _ = tx.send(DownloadEvent::Log(
+            LogLevel::Info,
+            tr_fmt(Msg::ChapterSplitTooShort, &[&name]),
+        ));
+        return Ok(Vec::new());
+    }
+
+    // 書き出し途中のクリップが一覧に出ないよう、一時フォルダへ書いてから移す。
+    // segment の連番の名前で書き出し、移す時にチャプター名を付ける。
+    let staging = StagingDir::new(create_download_staging_dir(output_dir)?);
+    write_segments(
+        runner,
+        ffmpeg,
+        path,
+        &points,
+        &segment_pattern(staging.path(), "chapter"),
+        cancel,
+    )?;
+    let mut clips = fs::read_dir(staging.path())
+        .map_err(|err| tr_fmt(Msg::ClipCheckFailed, &[&err]))?
+        .flatten()
+        .map(|entry| entry.path())
+        .collect::<Vec<_>>();
+    clips.sort();
+
+    // 元の動画の情報（投稿者名など）を各クリップにも付け、クリップも同じ語で検索できるようにする。
+    let info_json = info_json_path(path);
+    let mut moved = Vec::new();
+    for (index, (clip, chapter)) in clips.iter().zip(chapters).enumerate() {
+        let mut destination = output_dir.join(chapter_file_name(index, &chapter.title, &stem));
+        if destination.exists() {
+            destination = next_available_destination(&destination)?;
+        }
+        if info_json.is_file() {
+            let _ = store_info_json(&info_json, &destination);
+        }
+        fs::rename(clip, &destination).map_err(|err| tr_fmt(Msg::ClipMoveFailed, &[&err]))?;
+        moved.push(destination);
+    }
+    let _ = staging.remove();
+    let _ = tx.send(DownloadEvent::Log(
+        LogLevel::Info,
+        tr_fmt(Msg::ChapterSplitDone, &[&name, &moved.len()]),
+    ));
+    Ok(moved)
+}
+
+// 貼り付けたタイムスタンプの一覧か、設定で有効な場合は動画のチャプターで、保存した動画をそれぞれ分割する。
+// 分割しなかった動画を返す（シーン分割はこれらだけに行う）。失敗しても保存した動画は残すため、
+// 取り消し以外はログに出して続ける。
+pub(super) fn split_saved_outputs(
+    runner: &dyn CommandRunner,
+    outputs: &[PathBuf],
+    output_dir: &Path,
+    ffmpeg: &Path,
+    timestamps: Option<&str>,
+    tx: &mpsc::Sender<DownloadEvent>,
+    cancel: &CancellationToken,
+) -> Result<Vec<PathBuf>, String> {
+    let pasted = match timestamps.map(parse_timestamp_list) {
+        Some(Ok(chapters)) => Some(chapters),
+        Some(Err(err)) => {
+            let _ = tx.send(DownloadEvent::Log(
+                LogLevel::Error,
+                tr_fmt(Msg::ChapterSplitFailed, &[&err]),
+            ));
+            None
+        }
+        None => None,
+    };
+    let use_video_chapters = load_chapter_split();
+    let mut remaining = Vec::new();
+    for path in outputs {
+        let chapters = match &pasted {
+            Some(chapters) => chapters.clone(),
+            None if use_video_chapters => fs::read_to_string(info_json_path(path))
+                .map(|json| chapters_from_info_json(&json))
+                .unwrap_or_default(),
+            None => Vec::new(),
+        };
+        if chapters.len() < 2 {
+            remaining.push(path.clone());
+            continue;
+        }
+        match split_into_chapters(runner, path, output_dir, ffmpeg, &chapters, tx, cancel) {
+            Ok(clips) if !clips.is_empty() => {}
+            Ok(_) => remaining.push(path.clone()),
+            Err(err) => {
+                cancel.check()?;
+                let _ = tx.send(DownloadEvent::Log(
+                    LogLevel::Error,
+                    tr_fmt(Msg::ChapterSplitFailed, &[&err]),
+                ));
+                remaining.push(path.clone());
+            }
+        }
+    }
+    Ok(remaining)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chapter(start_seconds: f64, title: &str) -> Chapter {
+        Chapter {
+            start_seconds,
+            title: title.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_pasted_timestamp_lists() {
+        let text = "\
+Tracklist:
+0:00 Intro
+[03:15] - Artist A – Track 1 (Edit)
+01. Artist B | Track 2 7:42
+1:02:03 Outro
+";
+        assert_eq!(
+            parse_timestamp_list(text),
+            Ok(vec![
+                chapter(0.0, "Intro"),
+                chapter(195.0, "Artist A – Track 1 (Edit)"),
+                chapter(462.0, "Artist B | Track 2"),
+                chapter(3723.0, "Outro"),
+            ])
+        );
+        assert!(parse_timestamp_list("0:00 Intro\n5:00 A\n4:00 B").is_err());
+        assert!(parse_timestamp_list("0:00 Intro").is_err());
+        // 秒が 60 以上のものや桁の足りないものは時刻とみなさない。
+        assert_eq!(parse_timestamp("1:75"), None);
+        assert_eq!(parse_timestamp("1:5"), None);
+        assert_eq!(parse_timestamp("2024"), None);
+    }
+
+    #[test]
+    fn reads_chapters_from_info_json() {
+        let json = r#"{"id": "abc", "chapters": [
+            {"start_time": 0.0, "end_time": 61.5, "title": " Intro "},
+            {"start_time": 61.5, "end_time": 300.0, "title": "Drop"}
+        ]}"#;
+        assert_eq!(
+            chapters_from_info_json(json),
+            vec![chapter(0.0, "Intro"), chapter(61.5, "Drop")]
+        );
+        assert!(chapters_from_info_json(r#"{"id": "abc", "chapters": null}"#).is_empty());
+    }
+
+    #[test]
+    fn cuts_at_chapter_starts_within_the_video() {
+        let chapters = [
+            chapter(12.0, "A"),
+            chapter(60.0, "B"),
+            chapter(119.5, "C"),
+            chapter(200.0, "D"),
+        ];
+        assert_eq!(chapter_points(&chapters, 120.0), vec![60.0]);
+        assert!(chapter_points(&chapters[..1], 120.0).is_empty());
+    }
+
+    #[test]
+    fn names_clips_after_chapters() {
+        assert_eq!(
+            chapter_file_name(0, "Artist / Track: 1?", "mix"),
+            "01 Artist _ Track_ 1_.mp4"
+        );
+        assert_eq!(chapter_file_name(11, " .hidden", "mix"), "12 hidden.mp4");
+        assert_eq!(chapter_file_name(2, "", "mix"), "mix 03.mp4");
+    }
+}
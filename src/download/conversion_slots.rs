@@ -0,0 +1,146 @@
+use std::sync::mpsc;
+use std::sync::{Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::app_logger::LogLevel;
+use crate::i18n::{Msg, tr};
+use crate::power;
+use crate::settings::{load_defer_gpu_conversions, load_parallel_conversions};
+
+use super::encoder::VideoEncoding;
+use super::{CancellationToken, DownloadEvent};
+
+// キャンセルを確かめる間隔
+const WAIT_INTERVAL: Duration = Duration::from_millis(200);
+
+// アプリ全体で同時に行う変換の数を、設定の数までに抑える。
+static CONVERSIONS: SlotPool = SlotPool::new();
+
+struct SlotPool {
+    running: Mutex<usize>,
+    freed: Condvar,
+}
+
+// 変換の枠。破棄すると枠を空けて、待っている変換を進める。
+pub(super) struct ConversionSlot<'a>(&'a SlotPool);
+
+impl Drop for ConversionSlot<'_> {
+    fn drop(&mut self) {
+        let mut running = self.0.running.lock().unwrap();
+        *running = running.saturating_sub(1);
+        self.0.freed.notify_all();
+    }
+}
+
+impl SlotPool {
+    const fn new() -> Self {
+        Self {
+            running: Mutex::new(0),
+            freed: Condvar::new(),
+        }
+    }
+
+    // 枠が空くまで待つ。待っている間にキャンセルされた場合は中断する。
+    fn acquire(
+        &self,
+        limit: usize,
+        cancel: &CancellationToken,
+        mut on_wait: impl FnMut(),
+    ) -> Result<ConversionSlot<'_>, String> {
+        let mut running = self.running.lock().unwrap();
+        let mut waited = false;
+        while *running >= limit {
+            if !waited {
+                waited = true;
+                on_wait();
+            }
+            running = self.freed.wait_timeout(running, WAIT_INTERVAL).unwrap().0;
+            cancel.check()?;
+        }
+        *running += 1;
+        Ok(ConversionSlot(self))
+    }
+}
+
+// バッテリー駆動中は、電源につなぐまで待つ。待っている間にキャンセルされた場合は中断する。
+fn wait_for_ac_power(
+    cancel: &CancellationToken,
+    on_battery: impl Fn() -> bool,
+    on_wait: impl FnOnce(),
+) -> Result<(), String> {
+    if !on_battery() {
+        return Ok(());
+    }
+    on_wait();
+    while on_battery() {
+        thread::sleep(WAIT_INTERVAL);
+        cancel.check()?;
+    }
+    Ok(())
+}
+
+// ffmpeg で変換する前に、変換の枠を確保する。
+// 設定で GPU の変換を待たせる場合、バッテリー駆動中は枠を取る前に電源につなぐまで待つ。
+pub(super) fn acquire(
+    encoding: &VideoEncoding,
+    tx: &mpsc::Sender<DownloadEvent>,
+    cancel: &CancellationToken,
+) -> Result<ConversionSlot<'static>, String> {
+    cancel.check()?;
+    if encoding.uses_gpu() && load_defer_gpu_conversions() {
+        wait_for_ac_power(cancel, power::last_known_on_battery, || {
+            let _ = tx.send(DownloadEvent::Log(
+                LogLevel::Info,
+                tr(Msg::WaitingForAcPower).to_string(),
+            ));
+        })?;
+    }
+    let limit = usize::from(load_parallel_conversions());
+    CONVERSIONS.acquire(limit, cancel, || {
+        let _ = tx.send(DownloadEvent::Log(
+            LogLevel::Info,
+            tr(Msg::WaitingForConversionSlot).to_string(),
+        ));
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_concurrent_conversions() {
+        let pool = SlotPool::new();
+        let cancel = CancellationToken::new();
+        let mut waits = 0;
+        let first = pool.acquire(2, &cancel, || waits += 1).unwrap();
+        let second = pool.acquire(2, &cancel, || waits += 1).unwrap();
+        assert_eq!(waits, 0);
+
+        // 枠が埋まっている間に取り消すと、待つのをやめる。
+        let cancelled = CancellationToken::new();
+        cancelled.cancel();
+        assert!(pool.acquire(2, &cancelled, || waits += 1).is_err());
+        assert_eq!(waits, 1);
+
+        drop(first);
+        let third = pool.acquire(2, &cancel, || waits += 1).unwrap();
+        assert_eq!(waits, 1);
+        drop((second, third));
+        assert_eq!(*pool.running.lock().unwrap(), 0);
+    }
+
+    #[test]
+    fn waits_for_ac_power_until_cancelled() {
+        let cancel = CancellationToken::new();
+        let mut waits = 0;
+        wait_for_ac_power(&cancel, || false, || waits += 1).unwrap();
+        assert_eq!(waits, 0);
+
+        let cancelled = CancellationToken::new();
+        cancelled.cancel();
+        assert!(wait_for_ac_power(&cancelled, || true, || waits += 1).is_err());
+        assert_eq!(waits, 1);
+    }
+}
@@ -0,0 +1,862 @@
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, mpsc};
+use std::thread;
+use std::time::Instant;
+
+use serde_json::Value;
+
+use crate::app_logger::LogLevel;
+use crate::i18n::{Msg, tr, tr_fmt};
+use crate::paths::ffprobe_path;
+
+use super::animethemes::spawn_ffmpeg_conversion_thread;
+use super::crop::{CropArea, confirm_crop};
+use crate::settings::EncoderProfile;
+
+use super::encoder::{QualityMode, VideoEncoding, target_video_bitrate_kbps};
+use super::hwaccel::{conversion_speed_log, hwaccel_args};
+use super::runner::{CommandRunner, CommandSpec, output};
+use super::watermark::Watermark;
+use super::{CancellationToken, DownloadEvent, DownloadStage, ProgressContext, conversion_slots};
+
+// mp4 にそのまま格納できるコーデック（ffprobe の codec_name）
+const MP4_VIDEO_CODECS: [&str; 2] = ["h264", "hevc"];
+const MP4_AUDIO_CODECS: [&str; 2] = ["aac", "mp3"];
+// HDR（PQ / HLG）の映像を示す color_transfer
+const HDR_TRANSFERS: [&str; 2] = ["smpte2084", "arib-std-b67"];
+// HDR を BT.709 の SDR へ変換するフィルタ（ffmpeg の zscale が必要）
+const HDR_TONEMAP_FILTER: &str = "zscale=t=linear:npl=100,format=gbrpf32le,zscale=p=bt709,tonemap=tonemap=hable:desat=0,zscale=t=bt709:m=bt709:r=tv,format=yuv420p";
+// インターレースを示す field_order
+const INTERLACED_FIELD_ORDERS: [&str; 4] = ["tt", "bb", "tb", "bt"];
+const DEINTERLACE_FILTER: &str = "yadif";
+// 非正方ピクセルを横方向に伸縮して正方ピクセルへそろえる。
+const SAR_CORRECTION_FILTER: &str = "scale=trunc(iw*sar/2)*2:ih,setsar=1";
+// ffprobe で取得する項目
+const PROBE_ENTRIES: &str = "stream=codec_type,codec_name,width,height,color_transfer,field_order,sample_aspect_ratio:format=duration,size";
+// yt-dlp の途中ファイルなど、変換対象にしない拡張子
+const SKIPPED_EXTENSIONS: [&str; 5] = ["part", "ytdl", "json", "jpg", "webp"];
+
+// ffprobe で調べた入力ファイルの情報
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(super) struct MediaInfo {
+    pub video_codec: Option<String>,
+    pub audio_codec: Option<String>,
+    pub duration_seconds: Option<f64>,
+    pub size_bytes: Option<u64>,
+    // 映像が HDR（PQ / HLG）かどうか
+    pub hdr: bool,
+    pub interlaced: bool,
+    // 画素比（SAR）が 1:1 でない（アナモルフィック）かどうか
+    pub anamorphic: bool,
+    pub width: u32,
+    pub height: u32,
+    // 確認済みの黒帯の切り取り範囲（ffprobe ではなく cropdetect の結果）
+    pub crop: Option<CropArea>,
+}
+
+// `ffprobe -show_entries <PROBE_ENTRIES> -of json` の出力を読む。
+pub(super) fn parse_media_info(json: &str) -> Result<MediaInfo, String> {
+    let value: Value =
+        serde_json::from_str(json).map_err(|err| tr_fmt(Msg::ProbeOutputParseFailed, &[&err]))?;
+    let streams = value
+        .get("streams")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    let stream_of = |kind: &str| {
+        streams
+            .iter()
+            .find(|stream| stream.get("codec_type").and_then(Value::as_str) == Some(kind))
+    };
+    let codec_of = |kind: &str| {
+        stream_of(kind)
+            .and_then(|stream| stream.get("codec_name").and_then(Value::as_str))
+            .map(|name| name.to_ascii_lowercase())
+    };
+    let video_value = |key: &str| {
+        stream_of("video")
+            .and_then(|stream| stream.get(key))
+            .and_then(Value::as_str)
+    };
+    let hdr =
+        video_value("color_transfer").is_some_and(|transfer| HDR_TRANSFERS.contains(&transfer));
+    let interlaced =
+        video_value("field_order").is_some_and(|order| INTERLACED_FIELD_ORDERS.contains(&order));
+    let anamorphic = video_value("sample_aspect_ratio")
+        .and_then(|sar| sar.split_once(':'))
+        .and_then(|(num, den)| Some((num.parse::<u32>().ok()?, den.parse::<u32>().ok()?)))
+        .is_some_and(|(num, den)| num != 0 && den != 0 && num != den);
+    let dimension = |key: &str| {
+        stream_of("video")
+            .and_then(|stream| stream.get(key))
+            .and_then(Value::as_u64)
+            .and_then(|value| u32::try_from(value).ok())
+            .unwrap_or(0)
+    };
+    let format_value = |key: &str| {
+        value
+            .get("format")
+            .and_then(|format| format.get(key))
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+    Ok(MediaInfo {
+        video_codec: codec_of("video"),
+        audio_codec: codec_of("audio"),
+        duration_seconds: format_value("duration").and_then(|raw| raw.parse().ok()),
+        size_bytes: format_value("size").and_then(|raw| raw.parse().ok()),
+        hdr,
+        interlaced,
+        anamorphic,
+        width: dimension("width"),
+        height: dimension("height"),
+        crop: None,
+    })
+}
+
+// ffprobe でファイルまたは URL を調べる。URL の場合は user_agent を指定できる。
+pub(super) fn probe_media_info(
+    runner: &dyn CommandRunner,
+    input: &OsStr,
+    user_agent: Option<&str>,
+    cancel: &CancellationToken,
+) -> Result<MediaInfo, String> {
+    let mut cmd = CommandSpec::new(&ffprobe_path()).args(["-v", "error"]);
+    if let Some(user_agent) = user_agent {
+        cmd = cmd.arg("-user_agent").arg(user_agent);
+    }
+    let cmd = cmd
+        .args(["-show_entries", PROBE_ENTRIES, "-of", "json"])
+        .arg(input);
+    let output = output(runner, &cmd, cancel)
+        .map_err(|err| tr_fmt(Msg::ToolLaunchFailed, &[&"ffprobe", &err]))?;
+    cancel.check()?;
+    if !output.status.success() {
+        return Err(tr_fmt(
+            Msg::ProbeFailed,
+            &[&String::from_utf8_lossy(&output.stderr).trim()],
+        ));
+    }
+    parse_media_info(&String::from_utf8_lossy(&output.stdout))
+}
+
+// サイズ指定で、目標を超える場合だけ映像のビットレート（kbps）を返す。
+pub(super) fn target_bitrate(info: &MediaInfo, encoding: &VideoEncoding) -> Option<u32> {
+    let QualityMode::TargetSize(megabytes) = encoding.quality else {
+        return None;
+    };
+    let limit = u64::from(megabytes) * 1024 * 1024;
+    if info.size_bytes.is_some_and(|size| size <= limit) {
+        return None;
+    }
+    target_video_bitrate_kbps(megabytes, info.duration_seconds)
+}
+
+// ffmpeg が zscale フィルタ（HDR のトーンマッピングに使う）を持っているか。
+fn supports_tonemap(runner: &dyn CommandRunner, ffmpeg: &Path, cancel: &CancellationToken) -> bool {
+    let cmd = CommandSpec::new(ffmpeg).args(["-hide_banner", "-filters"]);
+    output(runner, &cmd, cancel)
+        .map(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .any(|line| line.split_whitespace().nth(1) == Some("zscale"))
+        })
+        .unwrap_or(false)
+}
+
+// 映像に掛けるフィルタと、掛ける理由（ログ用）。
+// 自動補正が有効ならインターレース解除と SAR 補正、確認済みなら黒帯の切り取り、
+// HDR なら SDR へのトーンマッピングを行う。切り取りは元のピクセル単位のため SAR 補正より前に掛ける。
+pub(super) fn video_filters(
+    info: &MediaInfo,
+    encoding: &VideoEncoding,
+) -> Vec<(String, &'static str)> {
+    let mut filters = Vec::new();
+    if encoding.auto_correct && info.interlaced {
+        filters.push((DEINTERLACE_FILTER.to_string(), tr(Msg::DeinterlaceNote)));
+    }
+    if let Some(crop) = info.crop {
+        filters.push((crop.filter(), tr(Msg::CropNote)));
+    }
+    if encoding.auto_correct && info.anamorphic {
+        filters.push((SAR_CORRECTION_FILTER.to_string(), tr(Msg::SquarePixelNote)));
+    }
+    if info.hdr {
+        filters.push((HDR_TONEMAP_FILTER.to_string(), tr(Msg::TonemapNote)));
+    }
+    filters
+}
+
+// 映像を再エンコードできるか。コピー設定ではサイズ指定の場合だけ再エンコードする。
+fn can_reencode_video(
+    info: &MediaInfo,
+    encoding: &VideoEncoding,
+    target_kbps: Option<u32>,
+) -> bool {
+    info.video_codec.is_some()
+        && (encoding.profile != EncoderProfile::Copy || target_kbps.is_some())
+}
+
+// フィルタ・透かしを掛けるために映像を再エンコードするか。コピー設定では掛けない。
+fn needs_filters(info: &MediaInfo, encoding: &VideoEncoding, target_kbps: Option<u32>) -> bool {
+    can_reencode_video(info, encoding, target_kbps)
+        && (!video_filters(info, encoding).is_empty() || encoding.watermark.is_some())
+}
+
+// 映像に重ねる透かし。映像を再エンコードしない場合は重ねない。
+pub(super) fn applied_watermark<'a>(
+    info: &MediaInfo,
+    encoding: &'a VideoEncoding,
+    target_kbps: Option<u32>,
+) -> Option<&'a Watermark> {
+    encoding
+        .watermark
+        .as_ref()
+        .filter(|_| needs_filters(info, encoding, target_kbps))
+}
+
+// ffmpeg の `-vf` 指定。透かしがある場合は 2 つ目の入力を重ねる `-filter_complex` にする。
+// フィルタも透かしも無い場合は空。
+pub(super) fn filter_args(
+    filters: &[(String, &str)],
+    watermark: Option<&Watermark>,
+) -> Vec<String> {
+    let chain = (!filters.is_empty()).then(|| {
+        filters
+            .iter()
+            .map(|(filter, _)| filter.as_str())
+            .collect::<Vec<_>>()
+            .join(",")
+    });
+    match (chain, watermark) {
+        (chain, Some(watermark)) => vec![
+            "-filter_complex".to_string(),
+            watermark.filter_complex(chain.as_deref()),
+            "-map".to_string(),
+            "[v]".to_string(),
+            "-map".to_string(),
+            "0:a?".to_string(),
+        ],
+        (Some(chain), None) => vec!["-vf".to_string(), chain],
+        (None, None) => Vec::new(),
+    }
+}
+
+// 入力のコーデックに合わせた ffmpeg の変換指定を返す。mp4 に入るストリームは再エンコードせずコピーする。
+// target_kbps がある場合はコーデックに関わらず映像をそのビットレートで再エンコードする。
+// フィルタ（補正・HDR 変換）を掛ける映像と、画質一定・2 パスを選んだ映像はコピーせずに再エンコードする。
+pub(super) fn stream_args(
+    info: &MediaInfo,
+    encoding: &VideoEncoding,
+    target_kbps: Option<u32>,
+) -> Vec<String> {
+    let filtered = needs_filters(info, encoding, target_kbps);
+    let mut video: Vec<String> = match (info.video_codec.as_deref(), target_kbps) {
+        (Some(_), Some(kbps)) => encoding.bitrate_video_args(kbps),
+        (Some(_), None) if filtered || encoding.forces_reencode() => owned(encoding.video_args()),
+        // QuickTime で再生できるよう HEVC には hvc1 タグを付ける。
+        (Some("hevc"), None) => owned(&["-c:v", "copy", "-tag:v", "hvc1"]),
+        (Some(codec), None) if MP4_VIDEO_CODECS.contains(&codec) => owned(&["-c:v", "copy"]),
+        (Some(_), None) => owned(encoding.video_args()),
+        (None, _) => Vec::new(),
+    };
+    if filtered {
+        video.extend(filter_args(
+            &video_filters(info, encoding),
+            encoding.watermark.as_ref(),
+        ));
+    }
+    let audio = match info.audio_codec.as_deref() {
+        Some(codec) if MP4_AUDIO_CODECS.contains(&codec) => owned(&["-c:a", "copy"]),
+        Some(_) => owned(&["-c:a", "aac", "-b:a", "192k"]),
+        None => Vec::new(),
+    };
+    [video, audio].concat()
+}
+
+fn owned(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+// 映像をコピーする（再エンコードしない）かどうか。映像が無い場合もコピー扱い。
+pub(super) fn is_video_copied(args: &[String]) -> bool {
+    args.chunks(2)
+        .find(|pair| pair[0] == "-c:v")
+        .is_none_or(|pair| pair.get(1).is_some_and(|value| value == "copy"))
+}
+
+// 変換指定のうち映像に関する部分（`-c:a` より前）
+fn video_part(args: &[String]) -> &[String] {
+    let end = args
+        .iter()
+        .position(|arg| arg == "-c:a")
+        .unwrap_or(args.len());
+    &args[..end]
+}
+
+// 映像・音声ともにコピーで済む（再エンコードしない）かどうか。
+pub(super) fn is_remux_only(args: &[String]) -> bool {
+    args.chunks(2)
+        .filter(|pair| pair[0] == "-c:v" || pair[0] == "-c:a")
+        .all(|pair| pair.get(1).is_some_and(|value| value == "copy"))
+}
+
+// staging 内の動画を mp4 にそろえる。mp4 互換のコーデックは詰め替えだけで済ませる。
+pub(super) fn convert_staged_videos_to_mp4(
+    runner: &dyn CommandRunner,
+    staging_dir: &Path,
+    ffmpeg: &Path,
+    encoding: &VideoEncoding,
+    tx: &mpsc::Sender<DownloadEvent>,
+    progress: &Arc<ProgressContext>,
+    cancel: &CancellationToken,
+) -> Result<(), String> {
+    let _slot = conversion_slots::acquire(encoding, tx, cancel)?;
+    for input in staged_video_files(staging_dir)? {
+        cancel.check()?;
+        let mut info = probe_media_info(runner, input.as_os_str(), None, cancel)?;
+        if info.hdr {
+            // zscale が無い ffmpeg ではトーンマッピングせずに通常どおり変換する。
+            if !supports_tonemap(runner, ffmpeg, cancel) {
+                let _ = tx.send(DownloadEvent::Log(
+                    LogLevel::Warn,
+                    tr(Msg::NoZscale).to_string(),
+                ));
+                info.hdr = false;
+            }
+        }
+        let target_kbps = target_bitrate(&info, encoding);
+        // 黒帯の切り取りは、プレビューで確認してから行う。
+        if encoding.crop_detect && can_reencode_video(&info, encoding, target_kbps) {
+            info.crop = confirm_crop(
+                runner,
+                ffmpeg,
+                &input,
+                [info.width, info.height],
+                info.duration_seconds,
+                tx,
+                cancel,
+            )?;
+        }
+        if let QualityMode::TargetSize(megabytes) = encoding.quality {
+            let _ = tx.send(DownloadEvent::Log(
+                LogLevel::Info,
+                match target_kbps {
+                    Some(kbps) => tr_fmt(Msg::TargetSizeBitrate, &[&megabytes, &kbps]),
+                    None if info.duration_seconds.is_none() => {
+                        tr(Msg::TargetSizeNoDuration).to_string()
+                    }
+                    None => tr_fmt(Msg::TargetSizeAlreadySmall, &[&megabytes]),
+                },
+            ));
+        }
+        if encoding.profile == EncoderProfile::Copy
+            && matches!(
+                encoding.quality,
+                QualityMode::ConstantQuality | QualityMode::TwoPass
+            )
+        {
+            let _ = tx.send(DownloadEvent::Log(
+                LogLevel::Info,
+                tr(Msg::QualityIgnoredForCopy).to_string(),
+            ));
+        }
+        if needs_filters(&info, encoding, target_kbps) {
+            for (_, reason) in video_filters(&info, encoding) {
+                let _ = tx.send(DownloadEvent::Log(LogLevel::Info, reason.to_string()));
+            }
+        }
+        let watermark = applied_watermark(&info, encoding, target_kbps);
+        if watermark.is_some() {
+            let _ = tx.send(DownloadEvent::Log(
+                LogLevel::Info,
+                tr(Msg::WatermarkOverlaid).to_string(),
+            ));
+        }
+        let args = stream_args(&info, encoding, target_kbps);
+        let is_mp4 = has_extension(&input, "mp4");
+        if is_mp4 && is_remux_only(&args) {
+            // すでに mp4 互換の mp4 なので何もしない。
+            continue;
+        }
+
+        let remux = is_remux_only(&args);
+        let _ = tx.send(DownloadEvent::Log(
+            LogLevel::Info,
+            if remux {
+                tr(Msg::RemuxToMp4).to_string()
+            } else {
+                tr(Msg::ConvertToMp4).to_string()
+            },
+        ));
+        progress.mark_progress_started();
+        progress.set_post_processing();
+        let stage = DownloadStage::Converting {
+            pct: None,
+            pass: None,
+        };
+        let _ = tx.send(DownloadEvent::Stage(stage, progress.elapsed()));
+
+        let output = converted_output_path(&input);
+        let reencode = !is_video_copied(&args);
+        let hwaccel = hwaccel_args(info.video_codec.as_deref(), reencode);
+        if !hwaccel.is_empty() {
+            let _ = tx.send(DownloadEvent::Log(
+                LogLevel::Info,
+                tr(Msg::HardwareDecode).to_string(),
+            ));
+        }
+        let started = Instant::now();
+        // 映像を再エンコードする場合だけ、先に 1 パス目で映像を解析する。
+        let pass_log = staging_dir.join("ffmpeg2pass");
+        let two_pass = encoding.two_pass() && reencode;
+        let mut pass = None;
+        if two_pass {
+            let mut cmd = CommandSpec::new(ffmpeg)
+                .args(["-hide_banner", "-stats"])
+                .args(hwaccel.iter().copied())
+                .arg("-i")
+                .arg(&input);
+            if let Some(watermark) = watermark {
+                cmd = cmd.arg("-i").arg(&watermark.path);
+            }
+            let cmd = cmd
+                .args(video_part(&args).iter().cloned())
+                .args(["-pass", "1", "-passlogfile"])
+                .arg(&pass_log)
+                .args(["-an", "-f", "null", "-y", "-"]);
+            let result = run_ffmpeg_conversion(
+                runner,
+                &cmd,
+                info.duration_seconds,
+                Some(1),
+                tx,
+                progress,
+                cancel,
+            );
+            if let Err(err) = result {
+                cancel.check()?;
+                return Err(err);
+            }
+            pass = Some(2);
+        }
+
+        let mut cmd = CommandSpec::new(ffmpeg)
+            .args(["-hide_banner", "-stats"])
+            .args(hwaccel.iter().copied())
+            .arg("-i")
+            .arg(&input);
+        if let Some(watermark) = watermark {
+            cmd = cmd.arg("-i").arg(&watermark.path);
+        }
+        cmd = cmd.args(args.iter().cloned());
+        if two_pass {
+            cmd = cmd.args(["-pass", "2", "-passlogfile"]).arg(&pass_log);
+        }
+        let cmd = cmd
+            .args(["-movflags", "+faststart", "-f", "mp4", "-y"])
+            .arg(&output);
+        let result = run_ffmpeg_conversion(
+            runner,
+            &cmd,
+            info.duration_seconds,
+            pass,
+            tx,
+            progress,
+            cancel,
+        );
+        if let Err(err) = result {
+            let _ = fs::remove_file(&output);
+            cancel.check()?;
+            return Err(err);
+        }
+        if reencode
+            && let Some(log) = conversion_speed_log(
+                info.duration_seconds,
+                started.elapsed(),
+                !hwaccel.is_empty(),
+            )
+        {
+            let _ = tx.send(DownloadEvent::Log(LogLevel::Info, log));
+        }
+        let _ = fs::remove_file(&input);
+        // mp4 を再エンコードした場合は一時名から元の名前へ戻す。
+        if is_mp4 {
+            fs::rename(&output, &input).map_err(|err| err.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+// ffmpeg を起動し、stderr の time= から変換進捗を表示しながら終了を待つ。
+pub(super) fn run_ffmpeg_conversion(
+    runner: &dyn CommandRunner,
+    cmd: &CommandSpec,
+    duration_seconds: Option<f64>,
+    pass: Option<u8>,
+    tx: &mpsc::Sender<DownloadEvent>,
+    progress: &Arc<ProgressContext>,
+    cancel: &CancellationToken,
+) -> Result<(), String> {
+    let mut child = runner
+        .spawn(cmd, cancel)
+        .map_err(|err| tr_fmt(Msg::ToolLaunchFailed, &[&"ffmpeg", &err]))?;
+    let _watch = progress.watchdog.watch();
+    // 標準出力は使わないが、パイプが詰まって止まらないよう読み捨てる。
+    if let Some(mut stdout) = child.take_stdout() {
+        thread::spawn(move || io::copy(&mut stdout, &mut io::sink()));
+    }
+    spawn_ffmpeg_conversion_thread(child.take_stderr(), tx, progress, duration_seconds, pass);
+
+    let status = child
+        .wait()
+        .map_err(|err| tr_fmt(Msg::ToolWaitFailed, &[&"ffmpeg", &err]))?;
+    if !status.success() {
+        return Err(tr_fmt(Msg::ToolExitedWithError, &[&"ffmpeg", &status]));
+    }
+    Ok(())
+}
+
+fn staged_video_files(staging_dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let entries =
+        fs::read_dir(staging_dir).map_err(|err| tr_fmt(Msg::StagingReadFailed, &[&err]))?;
+    let mut files = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            !SKIPPED_EXTENSIONS
+                .iter()
+                .any(|ext| has_extension(path, ext))
+        })
+        .collect::<Vec<_>>();
+    files.sort();
+    Ok(files)
+}
+
+fn converted_output_path(input: &Path) -> PathBuf {
+    if has_extension(input, "mp4") {
+        input.with_extension("converting.mp4")
+    } else {
+        input.with_extension("mp4")
+    }
+}
+
+fn has_extension(path: &Path, ext: &str) -> bool {
+    path.extension()
+        .and_then(|value| value.to_str())
+        .is_some_and(|value| value.eq_ignore_ascii_case(ext))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app_logger::LogSource;
+    use crate::download::runner::fake::{FakeRun, FakeRunner};
+    use crate::settings::{EncoderProfile, WatermarkPosition};
+    use std::sync::atomic::AtomicBool;
+
+    fn standard(profile: EncoderProfile) -> VideoEncoding {
+        VideoEncoding {
+            profile,
+            quality: QualityMode::Standard,
+            auto_correct: true,
+            crop_detect: false,
+            watermark: None,
+        }
+    }
+
+    #[test]
+    fn copies_mp4_compatible_streams() {
+        let json = r#"{
+            "streams": [
+                {"codec_type": "video", "codec_name": "h264"},
+                {"codec_type": "audio", "codec_name": "opus"}
+            ],
+            "format": {"duration": "212.480000", "size": "31457280"}
+        }"#;
+        let info = parse_media_info(json).unwrap();
+        assert_eq!(info.video_codec.as_deref(), Some("h264"));
+        assert_eq!(info.duration_seconds, Some(212.48));
+        assert_eq!(info.size_bytes, Some(30 * 1024 * 1024));
+
+        let args = stream_args(&info, &standard(EncoderProfile::VideoToolboxH264), None);
+        assert_eq!(args, ["-c:v", "copy", "-c:a", "aac", "-b:a", "192k"]);
+        assert!(!is_remux_only(&args));
+        assert!(is_video_copied(&args));
+
+        let h264_aac = MediaInfo {
+            audio_codec: Some("aac".to_string()),
+            ..info
+        };
+        assert!(is_remux_only(&stream_args(
+            &h264_aac,
+            &standard(EncoderProfile::VideoToolboxH264),
+            None
+        )));
+
+        let vp9 = MediaInfo {
+            video_codec: Some("vp9".to_string()),
+            audio_codec: None,
+            duration_seconds: None,
+            size_bytes: None,
+            hdr: false,
+            interlaced: false,
+            anamorphic: false,
+            width: 1920,
+            height: 1080,
+            crop: None,
+        };
+        assert_eq!(
+            stream_args(&vp9, &standard(EncoderProfile::X264), None),
+            owned(standard(EncoderProfile::X264).video_args())
+        );
+        assert!(!is_video_copied(&stream_args(
+            &vp9,
+            &standard(EncoderProfile::X264),
+            None
+        )));
+    }
+
+    #[test]
+    fn reencodes_compatible_video_for_quality_modes() {
+        let info = MediaInfo {
+            video_codec: Some("h264".to_string()),
+            audio_codec: Some("aac".to_string()),
+            duration_seconds: Some(60.0),
+            size_bytes: Some(40 * 1024 * 1024),
+            hdr: false,
+            interlaced: false,
+            anamorphic: false,
+            width: 1920,
+            height: 1080,
+            crop: None,
+        };
+        let crf = VideoEncoding {
+            quality: QualityMode::ConstantQuality,
+            ..standard(EncoderProfile::X264)
+        };
+        let args = stream_args(&info, &crf, None);
+        assert!(!is_video_copied(&args));
+        assert!(args.windows(2).any(|pair| pair == ["-crf", "18"]));
+        assert_eq!(&args[args.len() - 2..], ["-c:a", "copy"]);
+
+        let two_pass = VideoEncoding {
+            quality: QualityMode::TwoPass,
+            ..standard(EncoderProfile::VideoToolboxH264)
+        };
+        assert!(!is_video_copied(&stream_args(&info, &two_pass, None)));
+
+        // コピー設定では画質方式に関わらずコピーする。
+        let copy = VideoEncoding {
+            quality: QualityMode::ConstantQuality,
+            ..standard(EncoderProfile::Copy)
+        };
+        assert!(is_remux_only(&stream_args(&info, &copy, None)));
+    }
+
+    #[test]
+    fn reencodes_to_fit_target_size() {
+        let info = MediaInfo {
+            video_codec: Some("h264".to_string()),
+            audio_codec: Some("aac".to_string()),
+            duration_seconds: Some(60.0),
+            size_bytes: Some(40 * 1024 * 1024),
+            hdr: false,
+            interlaced: false,
+            anamorphic: false,
+            width: 1920,
+            height: 1080,
+            crop: None,
+        };
+        let sized = VideoEncoding {
+            profile: EncoderProfile::X264,
+            quality: QualityMode::TargetSize(10),
+            auto_correct: true,
+            crop_detect: false,
+            watermark: None,
+        };
+        let kbps = target_bitrate(&info, &sized);
+        assert_eq!(kbps, Some(1136));
+        let args = stream_args(&info, &sized, kbps);
+        assert!(!is_video_copied(&args));
+        assert_eq!(
+            video_part(&args).last().map(String::as_str),
+            Some("yuv420p")
+        );
+        assert_eq!(&args[args.len() - 2..], ["-c:a", "copy"]);
+
+        // すでに目標以下なら詰め替えだけで済ませる。
+        let small = MediaInfo {
+            size_bytes: Some(5 * 1024 * 1024),
+            ..info
+        };
+        assert_eq!(target_bitrate(&small, &sized), None);
+        assert!(is_remux_only(&stream_args(&small, &sized, None)));
+    }
+
+    #[test]
+    fn tonemaps_hdr_video() {
+        let json = r#"{
+            "streams": [
+                {"codec_type": "video", "codec_name": "vp9", "color_transfer": "smpte2084"},
+                {"codec_type": "audio", "codec_name": "opus"}
+            ],
+            "format": {"duration": "180.0"}
+        }"#;
+        let info = parse_media_info(json).unwrap();
+        assert!(info.hdr);
+        let args = stream_args(&info, &standard(EncoderProfile::VideoToolboxH264), None);
+        assert!(args.contains(&HDR_TONEMAP_FILTER.to_string()));
+
+        // HDR の HEVC もコピーせずに変換する。
+        let hevc = MediaInfo {
+            video_codec: Some("hevc".to_string()),
+            ..info.clone()
+        };
+        assert!(!is_video_copied(&stream_args(
+            &hevc,
+            &standard(EncoderProfile::VideoToolboxH264),
+            None
+        )));
+        // コピー設定ではトーンマッピングしない。
+        assert!(is_video_copied(&stream_args(
+            &hevc,
+            &standard(EncoderProfile::Copy),
+            None
+        )));
+    }
+
+    #[test]
+    fn corrects_interlaced_and_anamorphic_video() {
+        let json = r#"{
+            "streams": [
+                {"codec_type": "video", "codec_name": "h264", "field_order": "tt", "sample_aspect_ratio": "32:27"},
+                {"codec_type": "audio", "codec_name": "aac"}
+            ],
+            "format": {"duration": "90.0"}
+        }"#;
+        let info = parse_media_info(json).unwrap();
+        assert!(info.interlaced && info.anamorphic);
+        let args = stream_args(&info, &standard(EncoderProfile::X264), None);
+        assert!(!is_video_copied(&args));
+        let chain = format!("{DEINTERLACE_FILTER},{SAR_CORRECTION_FILTER}");
+        assert!(args.contains(&chain));
+
+        // 自動補正を切った場合はそのままコピーする。
+        let off = VideoEncoding {
+            auto_correct: false,
+            ..standard(EncoderProfile::X264)
+        };
+        assert!(is_remux_only(&stream_args(&info, &off, None)));
+
+        let square = parse_media_info(
+            r#"{"streams": [{"codec_type": "video", "codec_name": "h264", "field_order": "progressive", "sample_aspect_ratio": "1:1"}]}"#,
+        )
+        .unwrap();
+        assert!(!square.interlaced && !square.anamorphic);
+    }
+
+    #[test]
+    fn overlays_watermark() {
+        let info = parse_media_info(
+            r#"{"streams": [{"codec_type": "video", "codec_name": "h264"}, {"codec_type": "audio", "codec_name": "aac"}]}"#,
+        )
+        .unwrap();
+        let watermark = Watermark {
+            path: PathBuf::from("/tmp/tag.png"),
+            position: WatermarkPosition::TopLeft,
+            opacity: 50,
+        };
+        let encoding = VideoEncoding {
+            watermark: Some(watermark.clone()),
+            ..standard(EncoderProfile::X264)
+        };
+        // 透かしを重ねる場合は mp4 互換の映像でも再エンコードする。
+        let args = stream_args(&info, &encoding, None);
+        assert!(!is_video_copied(&args));
+        assert_eq!(applied_watermark(&info, &encoding, None), Some(&watermark));
+        assert!(args.contains(&watermark.filter_complex(None)));
+        assert_eq!(
+            &args[args.len() - 6..args.len() - 2],
+            ["-map", "[v]", "-map", "0:a?"]
+        );
+
+        // コピー設定では重ねない。
+        let copy = VideoEncoding {
+            watermark: Some(watermark),
+            ..standard(EncoderProfile::Copy)
+        };
+        assert!(is_remux_only(&stream_args(&info, &copy, None)));
+        assert_eq!(applied_watermark(&info, &copy, None), None);
+    }
+
+    #[test]
+    fn probes_media_info_through_runner() {
+        let runner = FakeRunner::new(vec![
+            FakeRun::Exit {
+                code: 0,
+                stdout: r#"{"streams": [{"codec_type": "video", "codec_name": "vp9"}], "format": {"duration": "90.0"}}"#,
+                stderr: "",
+            },
+            FakeRun::Exit {
+                code: 1,
+                stdout: "",
+                stderr: "clip.webm: Invalid data found when processing input\n",
+            },
+        ]);
+        let cancel = CancellationToken::new();
+        let info = probe_media_info(&runner, OsStr::new("clip.webm"), Some("UA"), &cancel).unwrap();
+        assert_eq!(info.video_codec.as_deref(), Some("vp9"));
+        assert_eq!(info.duration_seconds, Some(90.0));
+        assert_eq!(
+            runner.calls()[0].args[..4],
+            ["-v", "error", "-user_agent", "UA"]
+        );
+
+        assert_eq!(
+            probe_media_info(&runner, OsStr::new("clip.webm"), None, &cancel),
+            Err(tr_fmt(
+                Msg::ProbeFailed,
+                &[&"clip.webm: Invalid data found when processing input"]
+            ))
+        );
+    }
+
+    // 変換に失敗した場合は終了状態をエラーにし、取り消した場合は終わるのを待たずに戻る。
+    #[test]
+    fn reports_failed_and_cancelled_conversions() {
+        let (tx, rx) = mpsc::channel();
+        let progress = ProgressContext::new(Arc::new(AtomicBool::new(false)));
+        let cmd = CommandSpec::new(Path::new("ffmpeg")).args(["-i", "in.webm", "out.mp4"]);
+        let runner = FakeRunner::new(vec![FakeRun::Exit {
+            code: 1,
+            stdout: "",
+            stderr: "in.webm: Invalid data found when processing input\n",
+        }]);
+        let cancel = CancellationToken::new();
+        let result = run_ffmpeg_conversion(&runner, &cmd, None, None, &tx, &progress, &cancel);
+        assert!(result.is_err_and(|err| err.contains("ffmpeg")));
+        assert_eq!(runner.calls(), vec![cmd.clone()]);
+
+        let runner = FakeRunner::new(vec![FakeRun::UntilCancelled]);
+        let canceller = cancel.clone();
+        let handle = thread::spawn(move || {
+            thread::sleep(std::time::Duration::from_millis(50));
+            canceller.cancel();
+        });
+        let result = run_ffmpeg_conversion(&runner, &cmd, None, None, &tx, &progress, &cancel);
+        handle.join().unwrap();
+        assert!(result.is_err());
+        assert_eq!(cancel.check(), Err(tr(Msg::DownloadCancelled).to_string()));
+        drop(tx);
+        // ffmpeg のエラー出力はログに流す。
+        assert!(rx.iter().any(|event| matches!(
+            event,
+            DownloadEvent::ToolLog(LogSource::Ffmpeg, line) if line.contains("Invalid data")
+        )));
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::mpsc;
+
+use super::runner::{CommandRunner, CommandSpec, output};
+use super::{CancellationToken, DownloadEvent, wait_for_reply};
+use crate::app_logger::LogLevel;
+use crate::i18n::{Msg, tr, tr_fmt};
+
+// 確認用のプレビュー画像の幅
+const PREVIEW_WIDTH: usize = 480;
+// cropdetect で調べる区間の長さ（秒）
+const DETECT_SECONDS: f64 = 30.0;
+// 元の面積に対してこれ以上残る場合は、黒帯が無いものとして切り取らない。
+const MIN_CROP_RATIO: f64 = 0.98;
+
+// cropdetect で見つけた切り取り範囲（元映像のピクセル単位）
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CropArea {
+    pub width: u32,
+    pub height: u32,
+    pub x: u32,
+    pub y: u32,
+}
+
+impl CropArea {
+    pub(super) fn filter(&self) -> String {
+        format!("crop={}:{}:{}:{}", self.width, self.height, self.x, self.y)
+    }
+
+    pub fn label(&self) -> String {
+        tr_fmt(
+            Msg::CropAreaLabel,
+            &[&self.width, &self.height, &self.x, &self.y],
+        )
+    }
+}
+
+// 切り取る前に確認してもらうためのプレビュー。枠線で切り取り範囲を示す。
+pub struct CropPreview {
+    pub crop: CropArea,
+    pub source_size: [u32; 2],
+    pub image_size: [usize; 2],
+    pub rgba: Vec<u8>,
+    reply: mpsc::Sender<bool>,
+}
+
+impl CropPreview {
+    // 切り取るかどうかをダウンロード処理へ返す。
+    pub fn respond(&self, apply: bool) {
+        let _ = self.reply.send(apply);
+    }
+}
+
+// cropdetect のログから、最も多く検出された切り取り範囲を返す。
+pub(super) fn parse_cropdetect(log: &str) -> Option<CropArea> {
+    let mut counts: HashMap<CropArea, usize> = HashMap::new();
+    for line in log.lines() {
+        let Some(idx) = line.rfind("crop=") else {
+            continue;
+        };
+        let values = line[idx + "crop=".len()..]
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .split(':')
+            .map(|value| value.parse::<u32>().ok())
+            .collect::<Option<Vec<_>>>();
+        if let Some([width, height, x, y]) = values.as_deref()
+            && *width > 0
+            && *height > 0
+        {
+            let crop = CropArea {
+                width: *width,
+                height: *height,
+                x: *x,
+                y: *y,
+            };
+            *counts.entry(crop).or_default() += 1;
+        }
+    }
+    counts
+        .into_iter()
+        .max_by_key(|(crop, count)| (*count, crop.width * crop.height))
+        .map(|(crop, _)| crop)
+}
+
+// 黒帯を切り取る意味がある（元の大きさより十分小さい）か。
+pub(super) fn is_meaningful_crop(crop: CropArea, width: u32, height: u32) -> bool {
+    let source = f64::from(width) * f64::from(height);
+    let cropped = f64::from(crop.width) * f64::from(crop.height);
+    source > 0.0 && cropped < source * MIN_CROP_RATIO
+}
+
+fn detect_crop(
+    runner: &dyn CommandRunner,
+    ffmpeg: &Path,
+    input: &Path,
+    duration_seconds: Option<f64>,
+    cancel: &CancellationToken,
+) -> Result<Option<CropArea>, String> {
+    // 冒頭のロゴや暗転を避けるため、全体の 1/4 あたりから調べる。
+    let start = duration_seconds.map(|value| value / 4.0).unwrap_or(0.0);
+    let cmd = CommandSpec::new(ffmpeg)
+        .args(["-hide_banner", "-ss"])
+        .arg(format!("{start:.2}"))
+        .arg("-i")
+        .arg(input)
+        .arg("-t")
+        .arg(format!("{DETECT_SECONDS}"))
+        .args(["-vf", "cropdetect=limit=24:round=2:reset=0"])
+        .args(["-an", "-f", "null", "-"]);
+    let output = output(runner, &cmd, cancel)
+        .map_err(|err| tr_fmt(Msg::ToolLaunchFailed, &[&"ffmpeg", &err]))?;
+    if !output.status.success() {
+        return Err(tr_fmt(Msg::CropDetectFailed, &[&output.status]));
+    }
+    Ok(parse_cropdetect(&String::from_utf8_lossy(&output.stderr)))
+}
+
+// 切り取り範囲に枠線を描いた 1 フレームを RGBA で取り出す。
+fn render_preview(
+    runner: &dyn CommandRunner,
+    ffmpeg: &Path,
+    input: &Path,
+    crop: CropArea,
+    duration_seconds: Option<f64>,
+    cancel: &CancellationToken,
+) -> Option<([usize; 2], Vec<u8>)> {
+    let at = duration_seconds.map(|value| value / 2.0).unwrap_or(0.0);
+    let cmd = CommandSpec::new(ffmpeg)
+        .args(["-hide_banner", "-v", "error", "-ss"])
+        .arg(format!("{at:.2}"))
+        .arg("-i")
+        .arg(input)
+        .args(["-frames:v", "1", "-vf"])
+        .arg(format!(
+            "drawbox=x={}:y={}:w={}:h={}:color=red@0.9:t=4,scale={PREVIEW_WIDTH}:-2",
+            crop.x, crop.y, crop.width, crop.height
+        ))
+        .args(["-f", "rawvideo", "-pix_fmt", "rgba", "-"]);
+    let output = output(runner, &cmd, cancel).ok()?;
+    let row = PREVIEW_WIDTH * 4;
+    if !output.status.success() || output.stdout.is_empty() || output.stdout.len() % row != 0 {
+        return None;
+    }
+    let height = output.stdout.len() / row;
+    Some(([PREVIEW_WIDTH, height], output.stdout))
+}
+
+// 黒帯を検出し、プレビューで確認できた場合だけ切り取り範囲を返す。
+pub(super) fn confirm_crop(
+    runner: &dyn CommandRunner,
+    ffmpeg: &Path,
+    input: &Path,
+    source_size: [u32; 2],
+    duration_seconds: Option<f64>,
+    tx: &mpsc::Sender<DownloadEvent>,
+    cancel: &CancellationToken,
+) -> Result<Option<CropArea>, String> {
+    let _ = tx.send(DownloadEvent::Log(
+        LogLevel::Info,
+        tr(Msg::DetectingCrop).to_string(),
+    ));
+    let crop = match detect_crop(runner, ffmpeg, input, duration_seconds, cancel) {
+        Ok(Some(crop)) if is_meaningful_crop(crop, source_size[0], source_size[1]) => crop,
+        Ok(_) => {
+            let _ = tx.send(DownloadEvent::Log(
+                LogLevel::Info,
+                tr(Msg::NoCropFound).to_string(),
+            ));
+            return Ok(None);
+        }
+        Err(err) => {
+            cancel.check()?;
+            let _ = tx.send(DownloadEvent::Log(
+                LogLevel::Warn,
+                tr_fmt(Msg::CropFallback, &[&err]),
+            ));
+            return Ok(None);
+        }
+    };
+    let Some((image_size, rgba)) =
+        render_preview(runner, ffmpeg, input, crop, duration_seconds, cancel)
+    else {
+        let _ = tx.send(DownloadEvent::Log(
+            LogLevel::Warn,
+            tr(Msg::CropPreviewUnavailable).to_string(),
+        ));
+        return Ok(None);
+    };
+
+    let _ = tx.send(DownloadEvent::Log(
+        LogLevel::Info,
+        tr_fmt(Msg::CropFoundConfirm, &[&crop.label()]),
+    ));
+    let (reply, answer) = mpsc::channel();
+    let _ = tx.send(DownloadEvent::CropPreview(CropPreview {
+        crop,
+        source_size,
+        image_size,
+        rgba,
+        reply,
+    }));
+    // 確認画面が閉じられた（送信側が破棄された）場合は切り取らない。
+    let apply = wait_for_reply(&answer, cancel)?.unwrap_or(false);
+    Ok(apply.then_some(crop))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_most_frequent_crop() {
+        let log = "\
+[Parsed_cropdetect_0 @ 0x1] x1:0 x2:1919 y1:138 y2:941 w:1920 h:800 x:0 y:140 pts:1 t:0.04 crop=1920:800:0:140
+[Parsed_cropdetect_0 @ 0x1] x1:0 x2:1919 y1:0 y2:1079 w:1920 h:1080 x:0 y:0 pts:2 t:0.08 crop=1920:1080:0:0
+[Parsed_cropdetect_0 @ 0x1] x1:0 x2:1919 y1:138 y2:941 w:1920 h:800 x:0 y:140 pts:3 t:0.12 crop=1920:800:0:140
+frame=  720 fps=0.0 q=-0.0 Lsize=N/A time=00:00:30.00";
+        let crop = parse_cropdetect(log).unwrap();
+        assert_eq!(
+            crop,
+            CropArea {
+                width: 1920,
+                height: 800,
+                x: 0,
+                y: 140,
+            }
+        );
+        assert_eq!(crop.filter(), "crop=1920:800:0:140");
+        assert!(is_meaningful_crop(crop, 1920, 1080));
+        assert!(!is_meaningful_crop(
+            CropArea {
+                width: 1920,
+                height: 1072,
+                x: 0,
+                y: 4,
+            },
+            1920,
+            1080
+        ));
+        assert_eq!(parse_cropdetect("no crop here"), None);
+    }
+}
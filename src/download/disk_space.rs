@@ -0,0 +1,128 @@
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+
+use serde_json::Value;
+
+use crate::app_logger::LogLevel;
+use crate::i18n::{Msg, tr, tr_fmt};
+
+use super::DownloadEvent;
+
+// 推定サイズに対して作業中に必要になる空き容量の倍率。
+// 結合前の映像・音声と結合後のファイル（変換する場合は変換後のファイル）が一時的に同時に置かれる。
+const WORKING_SPACE_FACTOR: u64 = 2;
+
+const MIB: f64 = 1024.0 * 1024.0;
+
+// `yt-dlp -J` の結果から、選ばれるフォーマットの合計サイズを推定する。
+// 結合する場合は requested_formats の合計を使い、サイズが無いものがあれば推定しない。
+pub(super) fn estimate_download_size(json: &str) -> Option<u64> {
+    let value: Value = serde_json::from_str(json).ok()?;
+    let size_of = |format: &Value| {
+        ["filesize", "filesize_approx"]
+            .iter()
+            .find_map(|key| format.get(key).and_then(Value::as_f64))
+            .filter(|size| *size > 0.0)
+            .map(|size| size as u64)
+    };
+    match value.get("requested_formats").and_then(Value::as_array) {
+        Some(formats) if !formats.is_empty() => formats.iter().map(size_of).sum(),
+        _ => size_of(&value),
+    }
+}
+
+// `df -Pk` の出力から空き容量（バイト）を読み取る。
+fn parse_df_available(output: &str) -> Option<u64> {
+    let line = output.lines().nth(1)?;
+    let kilobytes = line.split_whitespace().nth(3)?.parse::<u64>().ok()?;
+    Some(kilobytes * 1024)
+}
+
+// 保存先のボリュームの空き容量（バイト）を返す。確認できない場合は None。
+fn available_space(dir: &Path) -> Option<u64> {
+    let output = Command::new("df")
+        .arg("-Pk")
+        .arg(dir)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_df_available(&String::from_utf8_lossy(&output.stdout))
+}
+
+// 推定サイズと空き容量を比べる。推定サイズより空きが少ない場合は開始しない。
+// 変換の作業領域まで足りない場合は警告だけ出して続ける。
+pub(super) fn ensure_free_space(
+    dir: &Path,
+    estimated: Option<u64>,
+    tx: &mpsc::Sender<DownloadEvent>,
+) -> Result<(), String> {
+    let Some(estimated) = estimated else {
+        let _ = tx.send(DownloadEvent::Log(
+            LogLevel::Warn,
+            tr(Msg::DiskSpaceUnknownSize).to_string(),
+        ));
+        return Ok(());
+    };
+    let Some(available) = available_space(dir) else {
+        let _ = tx.send(DownloadEvent::Log(
+            LogLevel::Warn,
+            tr(Msg::DiskSpaceUnavailable).to_string(),
+        ));
+        return Ok(());
+    };
+    let estimated_mb = estimated as f64 / MIB;
+    let available_mb = available as f64 / MIB;
+    if available < estimated {
+        return Err(tr_fmt(
+            Msg::DiskSpaceInsufficient,
+            &[&format!("{estimated_mb:.1}"), &format!("{available_mb:.1}")],
+        ));
+    }
+    if available < estimated.saturating_mul(WORKING_SPACE_FACTOR) {
+        let _ = tx.send(DownloadEvent::Log(
+            LogLevel::Warn,
+            tr_fmt(
+                Msg::DiskSpaceLow,
+                &[&format!("{estimated_mb:.1}"), &format!("{available_mb:.1}")],
+            ),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimates_size_from_requested_formats() {
+        let merged = r#"{
+            "filesize_approx": 1,
+            "requested_formats": [
+                {"format_id": "137", "filesize": 50000000},
+                {"format_id": "140", "filesize": null, "filesize_approx": 3000000.5}
+            ]
+        }"#;
+        assert_eq!(estimate_download_size(merged), Some(53_000_000));
+        assert_eq!(
+            estimate_download_size(r#"{"filesize_approx": 1200}"#),
+            Some(1200)
+        );
+        assert_eq!(
+            estimate_download_size(r#"{"requested_formats": [{"filesize": 10}, {}]}"#),
+            None
+        );
+    }
+
+    #[test]
+    fn reads_available_space_from_df() {
+        let output = "Filesystem 1024-blocks Used Available Capacity Mounted on\n/dev/disk3s5 971350180 512000000 459350180 53% /System/Volumes/Data\n";
+        assert_eq!(parse_df_available(output), Some(459_350_180 * 1024));
+        assert_eq!(parse_df_available(""), None);
+    }
+}
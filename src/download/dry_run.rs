@@ -0,0 +1,256 @@
+use std::path::Path;
+use std::sync::mpsc;
+
+use crate::diagnostics::shell_join;
+use crate::i18n::{Msg, tr};
+use crate::paths::{download_archive_path, ffmpeg_path, yt_dlp_path};
+use crate::redact::redact_args;
+use crate::settings::{
+    EncoderProfile, load_audio_track_prompt, load_auto_correct_video, load_crop_detect,
+};
+
+use super::convert::{MediaInfo, stream_args};
+use super::encoder::VideoEncoding;
+use super::watermark::load_watermark;
+use super::{DownloadJob, QualityMode, history, is_animethemes_url, job_encoder_settings, tools};
+
+// 形式を一覧から選ぶ場合に、選んだフォーマット ID の代わりに置く文字列
+const FORMAT_PLACEHOLDER: &str = "<FORMAT>";
+// ダウンロードした動画の代わりに置く文字列
+const INPUT_PLACEHOLDER: &str = "<downloaded file>";
+
+// 実行せずに組み立てたコマンド 1 つ
+#[derive(Clone, Debug, PartialEq)]
+pub struct DryRunCommand {
+    // 何のためのコマンドか（ダウンロード・やり直し・変換）
+    pub label: &'static str,
+    // 端末に貼り付けて実行できる 1 行。ユーザー名・パスワード・クッキーは伏せる。
+    pub line: String,
+}
+
+// ドライランの結果。実行した場合と同じ設定で組み立てたコマンドと、実行しないと決まらない部分の補足。
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DryRunReport {
+    pub url: String,
+    pub commands: Vec<DryRunCommand>,
+    pub notes: Vec<&'static str>,
+}
+
+impl DryRunReport {
+    // すべてのコマンドを 1 行ずつ並べた文字列（まとめてコピーする用）
+    pub fn all_lines(&self) -> String {
+        self.commands
+            .iter()
+            .map(|command| command.line.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn command_line(program: &Path, args: &[String]) -> String {
+    let mut words = vec![program.to_string_lossy().to_string()];
+    words.extend(redact_args(args));
+    shell_join(&words)
+}
+
+// ダウンロードを始めずに、同じ設定で実行されるはずの yt-dlp・ffmpeg のコマンドを組み立てる。
+// 外部ツールは起動しない。形式や音声トラックの選択、変換の要否など実行しないと決まらない部分は補足に書く。
+pub fn build_dry_run(job: &DownloadJob, output_dir: &Path, auth_args: &[String]) -> DryRunReport {
+    let mut report = DryRunReport {
+        url: job.url.clone(),
+        ..Default::default()
+    };
+    let (profile, max_height) = job_encoder_settings(job.preset.as_ref());
+    let ffmpeg = ffmpeg_path();
+    if !ffmpeg.exists() {
+        report.notes.push(tr(Msg::DryRunToolsMissing));
+    }
+
+    // 監視フォルダの動画は変換だけ行う。
+    if let Some(source) = &job.source_file {
+        report.commands.push(DryRunCommand {
+            label: tr(Msg::DryRunConvert),
+            line: conversion_line(&ffmpeg, source, output_dir, job.quality, profile),
+        });
+        return report;
+    }
+    if is_animethemes_url(&job.url) {
+        report.notes.push(tr(Msg::DryRunAnimeThemes));
+        return report;
+    }
+
+    let yt_dlp = yt_dlp_path();
+    if !yt_dlp.exists() && !report.notes.contains(&tr(Msg::DryRunToolsMissing)) {
+        report.notes.push(tr(Msg::DryRunToolsMissing));
+    }
+    let ffmpeg_arg = ffmpeg.to_string_lossy().to_string();
+    let js_runtime = tools::js_runtime_args();
+    // 保存済みのファイルが残っている場合、実行時はダウンロードせずに知らせる。
+    // 消えている場合は、アーカイブを使わずに取り直す。
+    let downloaded = history::find_downloaded(&job.url);
+    if downloaded.as_ref().is_some_and(|path| path.is_file()) {
+        report.notes.push(tr(Msg::DryRunAlreadyDownloaded));
+    }
+    let use_archive = downloaded.is_none();
+    let mut tail = Vec::new();
+    if use_archive {
+        tail.push("--download-archive".to_string());
+        tail.push(download_archive_path().to_string_lossy().to_string());
+    }
+    tail.push("--write-info-json".to_string());
+    tail.push("-o".to_string());
+    tail.push(
+        output_dir
+            .join("%(title)s.%(ext)s")
+            .to_string_lossy()
+            .to_string(),
+    );
+    tail.push("--".to_string());
+    tail.push(job.url.clone());
+
+    let mut args = if job.choose_format {
+        report.notes.push(tr(Msg::DryRunFormatChosenLater));
+        tools::chosen_format_yt_dlp_args(&ffmpeg_arg, auth_args, &js_runtime, FORMAT_PLACEHOLDER)
+    } else {
+        if load_audio_track_prompt() {
+            report.notes.push(tr(Msg::DryRunAudioTrackLater));
+        }
+        tools::base_yt_dlp_args(&ffmpeg_arg, auth_args, &js_runtime, None, max_height)
+    };
+    args.extend(tail.iter().cloned());
+    report.commands.push(DryRunCommand {
+        label: tr(Msg::DryRunDownload),
+        line: command_line(&yt_dlp, &args),
+    });
+
+    if !job.choose_format {
+        let mut fallback =
+            tools::fallback_yt_dlp_args(&ffmpeg_arg, auth_args, &js_runtime, None, max_height);
+        fallback.extend(tail);
+        report.commands.push(DryRunCommand {
+            label: tr(Msg::DryRunFallback),
+            line: command_line(&yt_dlp, &fallback),
+        });
+    }
+
+    report.commands.push(DryRunCommand {
+        label: tr(Msg::DryRunConvert),
+        line: conversion_line(
+            &ffmpeg,
+            Path::new(INPUT_PLACEHOLDER),
+            output_dir,
+            job.quality,
+            profile,
+        ),
+    });
+    report.notes.push(tr(Msg::DryRunConvertNote));
+    if job.split_timestamps.is_some() {
+        report.notes.push(tr(Msg::DryRunSplitByTimestamps));
+    }
+    if auth_args != redact_args(auth_args).as_slice() {
+        report.notes.push(tr(Msg::DryRunCredentialsHidden));
+    }
+    report.notes.push(tr(Msg::DryRunStagingNote));
+    report
+}
+
+// mp4 互換でない動画（VP9 / Opus）を変換する場合の ffmpeg のコマンド。
+// 実際の指定は ffprobe の結果や 2 パスの有無で変わるため、代表的な 1 行として示す。
+fn conversion_line(
+    ffmpeg: &Path,
+    input: &Path,
+    output_dir: &Path,
+    quality: QualityMode,
+    encoder_profile: EncoderProfile,
+) -> String {
+    let profile = if matches!(quality, QualityMode::TwoPass | QualityMode::TargetSize(_)) {
+        EncoderProfile::X264
+    } else {
+        encoder_profile
+    };
+    // 透かしの設定が使えない場合のログは、実行する時に出す。
+    let (tx, _rx) = mpsc::channel();
+    let encoding = VideoEncoding {
+        profile,
+        quality,
+        auto_correct: load_auto_correct_video(),
+        crop_detect: load_crop_detect(),
+        watermark: load_watermark(&tx),
+    };
+    let info = MediaInfo {
+        video_codec: Some("vp9".to_string()),
+        audio_codec: Some("opus".to_string()),
+        ..Default::default()
+    };
+    let output = output_dir.join(
+        input
+            .file_stem()
+            .map(|stem| format!("{}.mp4", stem.to_string_lossy()))
+            .unwrap_or_else(|| "output.mp4".to_string()),
+    );
+
+    let mut args = vec![
+        "-hide_banner".to_string(),
+        "-i".to_string(),
+        input.to_string_lossy().to_string(),
+    ];
+    if let Some(watermark) = &encoding.watermark {
+        args.push("-i".to_string());
+        args.push(watermark.path.to_string_lossy().to_string());
+    }
+    args.extend(stream_args(&info, &encoding, None));
+    args.extend(
+        ["-movflags", "+faststart", "-f", "mp4", "-y"]
+            .iter()
+            .map(|arg| arg.to_string()),
+    );
+    args.push(output.to_string_lossy().to_string());
+    command_line(ffmpeg, &args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn joins_lines_for_copy() {
+        let report = DryRunReport {
+            url: "https://example.com/v".to_string(),
+            commands: vec![
+                DryRunCommand {
+                    label: "a",
+                    line: "yt-dlp https://example.com/v".to_string(),
+                },
+                DryRunCommand {
+                    label: "b",
+                    line: "ffmpeg -i in.mkv out.mp4".to_string(),
+                },
+            ],
+            notes: Vec::new(),
+        };
+        assert_eq!(
+            report.all_lines(),
+            "yt-dlp https://example.com/v\nffmpeg -i in.mkv out.mp4"
+        );
+    }
+
+    #[test]
+    fn hides_credentials_in_command_line() {
+        let line = command_line(
+            Path::new("/opt/bin/yt-dlp"),
+            &[
+                "--username".to_string(),
+                "vj".to_string(),
+                "--password".to_string(),
+                "secret".to_string(),
+                "https://example.com/v?a=1&b=2".to_string(),
+            ],
+        );
+        assert!(
+            line.starts_with("/opt/bin/yt-dlp --username '<redacted>' --password '<redacted>' ")
+        );
+        assert!(!line.contains("vj"));
+        assert!(!line.contains("secret"));
+        assert!(line.ends_with("'https://example.com/v?a=1&b=2'"));
+    }
+}
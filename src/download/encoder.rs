@@ -0,0 +1,414 @@
+use std::path::Path;
+use std::process::Command;
+use std::sync::mpsc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::app_logger::LogLevel;
+use crate::i18n::{Msg, tr, tr_fmt};
+use crate::settings::{EncoderProfile, load_auto_correct_video, load_crop_detect};
+
+use super::DownloadEvent;
+use super::watermark::{Watermark, load_watermark};
+
+// ffmpeg の `-c:v` 以降に渡す映像の変換指定。copy は映像ストリームをそのまま使う。
+pub(super) fn video_codec_args(profile: EncoderProfile) -> &'static [&'static str] {
+    match profile {
+        EncoderProfile::VideoToolboxH264 => &[
+            "-c:v",
+            "h264_videotoolbox",
+            "-b:v",
+            "5M",
+            "-pix_fmt",
+            "yuv420p",
+        ],
+        // QuickTime で再生できるよう hvc1 タグを付ける。
+        EncoderProfile::VideoToolboxHevc => &[
+            "-c:v",
+            "hevc_videotoolbox",
+            "-b:v",
+            "5M",
+            "-tag:v",
+            "hvc1",
+            "-pix_fmt",
+            "yuv420p",
+        ],
+        EncoderProfile::X264 => &[
+            "-c:v", "libx264", "-preset", "veryfast", "-crf", "20", "-pix_fmt", "yuv420p",
+        ],
+        EncoderProfile::Copy => &["-c:v", "copy"],
+    }
+}
+
+// サイズ指定の初期値（Discord の添付上限に合わせる）
+pub const DEFAULT_TARGET_SIZE_MB: u32 = 10;
+// 目標サイズから映像に割り当てる際に差し引く音声のビットレート
+const TARGET_SIZE_AUDIO_KBPS: f64 = 192.0;
+// コンテナのオーバーヘッドや誤差で上限を超えないよう残す余裕
+const TARGET_SIZE_MARGIN: f64 = 0.95;
+const MIN_TARGET_VIDEO_KBPS: u32 = 100;
+
+// ダウンロードごとに選ぶ画質の方式。再エンコードする場合だけ効く。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QualityMode {
+    #[default]
+    Standard,
+    // libx264 は -crf、VideoToolbox は -q:v で画質を一定に保つ。
+    ConstantQuality,
+    // libx264 で解析と本変換の 2 回に分けてビットレートを配分する。
+    TwoPass,
+    // 動画の長さから映像のビットレートを決め、指定 MB 以下に収める。
+    TargetSize(u32),
+}
+
+impl QualityMode {
+    pub const ALL: [QualityMode; 4] = [
+        QualityMode::Standard,
+        QualityMode::ConstantQuality,
+        QualityMode::TwoPass,
+        QualityMode::TargetSize(DEFAULT_TARGET_SIZE_MB),
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            QualityMode::Standard => tr(Msg::QualityStandard),
+            QualityMode::ConstantQuality => tr(Msg::QualityConstant),
+            QualityMode::TwoPass => tr(Msg::QualityTwoPass),
+            QualityMode::TargetSize(_) => tr(Msg::QualityTargetSize),
+        }
+    }
+}
+
+// 実際に使うエンコード方式と画質方式の組み合わせ
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) struct VideoEncoding {
+    pub profile: EncoderProfile,
+    pub quality: QualityMode,
+    // インターレース解除と SAR 補正を行うか（設定の「映像の自動補正」）
+    pub auto_correct: bool,
+    // cropdetect で黒帯を検出し、確認のうえ切り取るか（設定の「黒帯の切り取り」）
+    pub crop_detect: bool,
+    // 映像に重ねる透かし（設定の「透かし」）
+    pub watermark: Option<Watermark>,
+}
+
+impl VideoEncoding {
+    // GPU（VideoToolbox）で変換するか
+    pub(super) fn uses_gpu(&self) -> bool {
+        matches!(
+            self.profile,
+            EncoderProfile::VideoToolboxH264 | EncoderProfile::VideoToolboxHevc
+        )
+    }
+
+    // 画質方式を反映した映像の変換指定。2 パスの場合は各パス共通の指定を返す。
+    pub(super) fn video_args(&self) -> &'static [&'static str] {
+        match (self.quality, self.profile) {
+            (QualityMode::Standard | QualityMode::TargetSize(_), profile)
+            | (_, profile @ EncoderProfile::Copy) => video_codec_args(profile),
+            (_, EncoderProfile::VideoToolboxH264) => &[
+                "-c:v",
+                "h264_videotoolbox",
+                "-q:v",
+                "65",
+                "-pix_fmt",
+                "yuv420p",
+            ],
+            (_, EncoderProfile::VideoToolboxHevc) => &[
+                "-c:v",
+                "hevc_videotoolbox",
+                "-q:v",
+                "65",
+                "-tag:v",
+                "hvc1",
+                "-pix_fmt",
+                "yuv420p",
+            ],
+            (QualityMode::TwoPass, EncoderProfile::X264) => &[
+                "-c:v", "libx264", "-preset", "slow", "-b:v", "8M", "-pix_fmt", "yuv420p",
+            ],
+            (_, EncoderProfile::X264) => &[
+                "-c:v", "libx264", "-preset", "slow", "-crf", "18", "-pix_fmt", "yuv420p",
+            ],
+        }
+    }
+
+    // サイズ指定で使う、ビットレートを固定した映像の変換指定。
+    pub(super) fn bitrate_video_args(&self, kbps: u32) -> Vec<String> {
+        let bitrate = format!("{kbps}k");
+        let args: Vec<&str> = match self.profile {
+            EncoderProfile::VideoToolboxH264 => {
+                vec!["-c:v", "h264_videotoolbox", "-b:v", &bitrate]
+            }
+            EncoderProfile::VideoToolboxHevc => vec![
+                "-c:v",
+                "hevc_videotoolbox",
+                "-b:v",
+                &bitrate,
+                "-tag:v",
+                "hvc1",
+            ],
+            EncoderProfile::X264 | EncoderProfile::Copy => {
+                vec!["-c:v", "libx264", "-preset", "slow", "-b:v", &bitrate]
+            }
+        };
+        args.into_iter()
+            .chain(["-pix_fmt", "yuv420p"])
+            .map(str::to_string)
+            .collect()
+    }
+
+    // 画質方式の指定を効かせるため、mp4 互換の映像でもコピーせずに再エンコードするか。
+    // サイズ指定は目標を超える場合だけ再エンコードするため含めない。コピー設定では再エンコードしない。
+    pub(super) fn forces_reencode(&self) -> bool {
+        matches!(
+            self.quality,
+            QualityMode::ConstantQuality | QualityMode::TwoPass
+        ) && self.profile != EncoderProfile::Copy
+    }
+
+    // 2 パス変換を行うか（libx264 のみ対応）
+    pub(super) fn two_pass(&self) -> bool {
+        matches!(
+            self.quality,
+            QualityMode::TwoPass | QualityMode::TargetSize(_)
+        ) && self.profile == EncoderProfile::X264
+    }
+
+    // 受信しながら変換する場合の透かし。コピー設定では映像を再エンコードしないため重ねない。
+    pub(super) fn stream_watermark(&self) -> Option<&Watermark> {
+        self.watermark
+            .as_ref()
+            .filter(|_| self.profile != EncoderProfile::Copy)
+    }
+
+    // パイプ入力など 1 回しか読めない変換では、2 パスの代わりに品質指定を使う。
+    // 動画の長さが分からないため、サイズ指定は標準の変換に戻す。
+    pub(super) fn single_pass(&self) -> Self {
+        let quality = match self.quality {
+            QualityMode::TwoPass => QualityMode::ConstantQuality,
+            QualityMode::TargetSize(_) => QualityMode::Standard,
+            quality => quality,
+        };
+        Self {
+            quality,
+            ..self.clone()
+        }
+    }
+}
+
+// 目標サイズと動画の長さから、映像に割り当てるビットレート（kbps）を求める。
+pub(super) fn target_video_bitrate_kbps(
+    megabytes: u32,
+    duration_seconds: Option<f64>,
+) -> Option<u32> {
+    let duration = duration_seconds.filter(|value| *value > 0.0)?;
+    let total_kbits = f64::from(megabytes) * 1024.0 * 1024.0 * 8.0 / 1000.0;
+    let video_kbps = total_kbits * TARGET_SIZE_MARGIN / duration - TARGET_SIZE_AUDIO_KBPS;
+    Some((video_kbps.floor() as u32).max(MIN_TARGET_VIDEO_KBPS))
+}
+
+pub fn encoder_label(profile: EncoderProfile) -> &'static str {
+    match profile {
+        EncoderProfile::VideoToolboxH264 => "GPU: h264_videotoolbox",
+        EncoderProfile::VideoToolboxHevc => "GPU: hevc_videotoolbox",
+        EncoderProfile::X264 => "CPU: libx264",
+        EncoderProfile::Copy => tr(Msg::NoReencode),
+    }
+}
+
+// プロファイルが必要とする ffmpeg のエンコーダ名（copy は不要）
+fn required_encoder(profile: EncoderProfile) -> Option<&'static str> {
+    match profile {
+        EncoderProfile::VideoToolboxH264 => Some("h264_videotoolbox"),
+        EncoderProfile::VideoToolboxHevc => Some("hevc_videotoolbox"),
+        EncoderProfile::X264 => Some("libx264"),
+        EncoderProfile::Copy => None,
+    }
+}
+
+// `ffmpeg -encoders` の出力から実際に使うプロファイルを決める。
+// 設定のエンコーダが無い場合は VideoToolbox H.264、libx264 の順に切り替える。
+pub(super) fn choose_encoder_profile(
+    preferred: EncoderProfile,
+    encoders: &str,
+) -> Option<EncoderProfile> {
+    let has = |name: &str| {
+        encoders
+            .lines()
+            .any(|line| line.split_whitespace().nth(1) == Some(name))
+    };
+    match required_encoder(preferred) {
+        None => return Some(preferred),
+        Some(name) if has(name) => return Some(preferred),
+        Some(_) => {}
+    }
+    if has("h264_videotoolbox") {
+        Some(EncoderProfile::VideoToolboxH264)
+    } else if has("libx264") {
+        Some(EncoderProfile::X264)
+    } else {
+        None
+    }
+}
+
+// ffmpeg が対応するエンコーダを調べ、preferred を使えるか（使えなければ代わり）を返す。
+pub fn detect_encoder_profile(
+    ffmpeg: &Path,
+    preferred: EncoderProfile,
+) -> Result<EncoderProfile, String> {
+    let output = Command::new(ffmpeg)
+        .arg("-hide_banner")
+        .arg("-encoders")
+        .output()
+        .map_err(|err| tr_fmt(Msg::EncoderCheckFailed, &[&err]))?;
+    if !output.status.success() {
+        return Err(tr_fmt(Msg::EncoderCheckFailed, &[&output.status]));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    choose_encoder_profile(preferred, &format!("{stdout}\n{stderr}"))
+        .ok_or_else(|| tr(Msg::NoH264Encoder).to_string())
+}
+
+// 指定のプロファイル（設定かプリセット）で変換できるかを確認し、切り替える場合は理由をログへ出す。
+// 2 パスは libx264 でしか行えないため、指定に関わらず libx264 を優先する。
+pub(super) fn select_video_encoding(
+    ffmpeg: &Path,
+    quality: QualityMode,
+    encoder_profile: EncoderProfile,
+    tx: &mpsc::Sender<DownloadEvent>,
+) -> Result<VideoEncoding, String> {
+    let preferred = if matches!(quality, QualityMode::TwoPass | QualityMode::TargetSize(_)) {
+        EncoderProfile::X264
+    } else {
+        encoder_profile
+    };
+    let profile = detect_encoder_profile(ffmpeg, preferred)?;
+    if profile != preferred {
+        let mut message = tr_fmt(
+            Msg::EncoderUnavailable,
+            &[&encoder_label(preferred), &encoder_label(profile)],
+        );
+        if profile == EncoderProfile::X264 {
+            message.push_str(tr(Msg::EncoderSlowNote));
+        }
+        let _ = tx.send(DownloadEvent::Log(LogLevel::Warn, message));
+    }
+    let encoding = VideoEncoding {
+        profile,
+        quality,
+        auto_correct: load_auto_correct_video(),
+        crop_detect: load_crop_detect(),
+        watermark: load_watermark(tx),
+    };
+    if quality == QualityMode::TwoPass && !encoding.two_pass() {
+        let _ = tx.send(DownloadEvent::Log(
+            LogLevel::Warn,
+            tr(Msg::TwoPassNeedsX264).to_string(),
+        ));
+    }
+    if matches!(quality, QualityMode::TargetSize(_)) && !encoding.two_pass() {
+        let _ = tx.send(DownloadEvent::Log(
+            LogLevel::Info,
+            tr(Msg::TargetSizeOnePass).to_string(),
+        ));
+    }
+    Ok(encoding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefers_configured_profile_and_falls_back() {
+        let both = " V....D libx264              libx264 H.264 / AVC\n V....D h264_videotoolbox    VideoToolbox H.264 Encoder\n";
+        assert_eq!(
+            choose_encoder_profile(EncoderProfile::VideoToolboxH264, both),
+            Some(EncoderProfile::VideoToolboxH264)
+        );
+        assert_eq!(
+            choose_encoder_profile(EncoderProfile::X264, both),
+            Some(EncoderProfile::X264)
+        );
+        // HEVC が無い場合は H.264 の VideoToolbox へ切り替える。
+        assert_eq!(
+            choose_encoder_profile(EncoderProfile::VideoToolboxHevc, both),
+            Some(EncoderProfile::VideoToolboxH264)
+        );
+        let intel = " V....D libx264              libx264 H.264 / AVC\n V....D libx264rgb           libx264 H.264 RGB\n";
+        assert_eq!(
+            choose_encoder_profile(EncoderProfile::VideoToolboxH264, intel),
+            Some(EncoderProfile::X264)
+        );
+        assert_eq!(
+            choose_encoder_profile(EncoderProfile::VideoToolboxH264, " V....D mpeg4  MPEG-4\n"),
+            None
+        );
+        assert_eq!(
+            choose_encoder_profile(EncoderProfile::Copy, ""),
+            Some(EncoderProfile::Copy)
+        );
+    }
+
+    #[test]
+    fn two_pass_only_with_libx264() {
+        let x264 = VideoEncoding {
+            profile: EncoderProfile::X264,
+            quality: QualityMode::TwoPass,
+            auto_correct: false,
+            crop_detect: false,
+            watermark: None,
+        };
+        assert!(x264.two_pass());
+        assert!(x264.video_args().contains(&"8M"));
+        assert!(!x264.single_pass().two_pass());
+        assert!(x264.single_pass().video_args().contains(&"-crf"));
+
+        let gpu = VideoEncoding {
+            profile: EncoderProfile::VideoToolboxH264,
+            quality: QualityMode::TwoPass,
+            auto_correct: false,
+            crop_detect: false,
+            watermark: None,
+        };
+        assert!(!gpu.two_pass());
+        assert!(gpu.video_args().contains(&"-q:v"));
+
+        let standard = VideoEncoding {
+            quality: QualityMode::Standard,
+            ..gpu
+        };
+        assert_eq!(
+            standard.video_args(),
+            video_codec_args(EncoderProfile::VideoToolboxH264)
+        );
+    }
+
+    #[test]
+    fn computes_bitrate_for_target_size() {
+        // 10MB・60 秒: 83886kbit * 0.95 / 60 - 192 = 1136kbps
+        assert_eq!(target_video_bitrate_kbps(10, Some(60.0)), Some(1136));
+        // 長すぎる動画でも下限を下回らない。
+        assert_eq!(target_video_bitrate_kbps(1, Some(3600.0)), Some(100));
+        assert_eq!(target_video_bitrate_kbps(10, None), None);
+
+        let sized = VideoEncoding {
+            profile: EncoderProfile::X264,
+            quality: QualityMode::TargetSize(10),
+            auto_correct: false,
+            crop_detect: false,
+            watermark: None,
+        };
+        assert!(sized.two_pass());
+        assert_eq!(
+            sized.bitrate_video_args(1136),
+            [
+                "-c:v", "libx264", "-preset", "slow", "-b:v", "1136k", "-pix_fmt", "yuv420p"
+            ]
+        );
+        assert_eq!(sized.single_pass().quality, QualityMode::Standard);
+    }
+}
@@ -0,0 +1,277 @@
+use std::path::Path;
+use std::sync::mpsc;
+
+use serde_json::Value;
+
+use super::process::fetch_yt_dlp_info;
+use super::runner::CommandRunner;
+use super::{CancellationToken, DownloadEvent, wait_for_reply};
+use crate::app_logger::LogLevel;
+use crate::i18n::{Msg, tr, tr_fmt};
+
+const MIB: f64 = 1024.0 * 1024.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FormatKind {
+    // 映像のみ（音声と結合する）
+    Video,
+    Audio,
+    // 映像と音声の両方を含む
+    Combined,
+}
+
+// 選べるフォーマット（yt-dlp の format_id ごと）
+#[derive(Clone, Debug, PartialEq)]
+pub struct FormatOption {
+    pub format_id: String,
+    pub kind: FormatKind,
+    pub label: String,
+    // filesize（無ければ filesize_approx）。分からない場合は None。
+    pub size: Option<u64>,
+}
+
+// ダウンロード前に、取得するフォーマットを選んでもらう。
+pub struct FormatPrompt {
+    pub title: String,
+    pub formats: Vec<FormatOption>,
+    reply: mpsc::Sender<Option<Vec<String>>>,
+}
+
+impl FormatPrompt {
+    // 選んだ format_id（映像、音声の順）を返す。None は自動で選ぶ。
+    pub fn respond(&self, format_ids: Option<Vec<String>>) {
+        let _ = self.reply.send(format_ids);
+    }
+}
+
+// 選んだフォーマットの yt-dlp の -f 指定と、合計サイズ
+pub(super) struct ChosenFormat {
+    pub selector: String,
+    pub size: Option<u64>,
+}
+
+pub fn megabytes_label(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / MIB)
+}
+
+// `yt-dlp -J` の結果から、タイトルと選べるフォーマットを取り出す。
+// 映像は高さ、音声はビットレートの高い順に並べ、ストーリーボードなど映像も音声も無いものは除く。
+pub(super) fn parse_formats(json: &str) -> Result<(String, Vec<FormatOption>), String> {
+    let value: Value =
+        serde_json::from_str(json).map_err(|err| tr_fmt(Msg::VideoInfoParseFailed, &[&err]))?;
+    let title = value
+        .get("title")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    let entries = value
+        .get("formats")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+
+    let mut formats = Vec::new();
+    for format in &entries {
+        let text = |key: &str| format.get(key).and_then(Value::as_str).unwrap_or_default();
+        let number = |key: &str| format.get(key).and_then(Value::as_f64).unwrap_or(0.0);
+        let has_video = !matches!(text("vcodec"), "" | "none");
+        let has_audio = !matches!(text("acodec"), "" | "none");
+        let kind = match (has_video, has_audio) {
+            (true, true) => FormatKind::Combined,
+            (true, false) => FormatKind::Video,
+            (false, true) => FormatKind::Audio,
+            (false, false) => continue,
+        };
+        let format_id = text("format_id");
+        if format_id.is_empty() {
+            continue;
+        }
+        // コーデックは avc1.640028 のような詳細を除いて表示する。
+        let codec = |key: &str| text(key).split('.').next().unwrap_or_default().to_string();
+        let mut parts = vec![format_id.to_string()];
+        match kind {
+            FormatKind::Audio => {
+                parts.push(codec("acodec"));
+                if number("abr") > 0.0 {
+                    parts.push(format!("{:.0}kbps", number("abr")));
+                }
+                let language = text("language");
+                if !language.is_empty() {
+                    parts.push(language.to_string());
+                }
+            }
+            FormatKind::Video | FormatKind::Combined => {
+                parts.push(text("resolution").to_string());
+                if number("fps") > 0.0 {
+                    parts.push(format!("{:.0}fps", number("fps")));
+                }
+                parts.push(codec("vcodec"));
+                if kind == FormatKind::Combined {
+                    parts.push(format!("+ {}", codec("acodec")));
+                }
+            }
+        }
+        parts.push(text("ext").to_string());
+        let size = ["filesize", "filesize_approx"]
+            .iter()
+            .map(|key| number(key))
+            .find(|size| *size > 0.0)
+            .map(|size| size as u64);
+        if let Some(size) = size {
+            parts.push(tr_fmt(Msg::AboutSize, &[&megabytes_label(size)]));
+        }
+        parts.retain(|part| !part.is_empty());
+        formats.push((
+            number("height"),
+            number("tbr").max(number("abr")),
+            FormatOption {
+                format_id: format_id.to_string(),
+                kind,
+                label: parts.join(" "),
+                size,
+            },
+        ));
+    }
+    formats.sort_by(|a, b| b.0.total_cmp(&a.0).then(b.1.total_cmp(&a.1)));
+    Ok((
+        title,
+        formats.into_iter().map(|(_, _, format)| format).collect(),
+    ))
+}
+
+// 選んだ format_id から -f 指定と合計サイズを組み立てる。サイズが分からないものがあれば None。
+fn chosen_format(formats: &[FormatOption], format_ids: &[String]) -> Option<ChosenFormat> {
+    let chosen = format_ids
+        .iter()
+        .map(|id| formats.iter().find(|format| &format.format_id == id))
+        .collect::<Option<Vec<_>>>()?;
+    if chosen.is_empty() {
+        return None;
+    }
+    Some(ChosenFormat {
+        selector: format_ids.join("+"),
+        size: chosen.iter().map(|format| format.size).sum(),
+    })
+}
+
+fn fetch_formats(
+    runner: &dyn CommandRunner,
+    yt_dlp: &Path,
+    url: &str,
+    auth_args: &[String],
+    js_runtime: &[String],
+    cancel: &CancellationToken,
+) -> Result<(String, Vec<FormatOption>), String> {
+    let mut args = vec!["--no-playlist".to_string()];
+    args.extend(auth_args.iter().cloned());
+    args.extend([
+        "--extractor-args".to_string(),
+        "youtube:player_client=web".to_string(),
+    ]);
+    args.extend(js_runtime.iter().cloned());
+    parse_formats(&fetch_yt_dlp_info(runner, yt_dlp, &args, url, cancel)?)
+}
+
+// 取得できるフォーマットを一覧して選んでもらう。
+// 選ばずに閉じた場合や一覧を取得できなかった場合は None（これまでどおり自動で選ぶ）を返す。
+pub(super) fn choose_format(
+    runner: &dyn CommandRunner,
+    yt_dlp: &Path,
+    url: &str,
+    auth_args: &[String],
+    js_runtime: &[String],
+    tx: &mpsc::Sender<DownloadEvent>,
+    cancel: &CancellationToken,
+) -> Result<Option<ChosenFormat>, String> {
+    let _ = tx.send(DownloadEvent::Log(
+        LogLevel::Info,
+        tr(Msg::CheckingFormats).to_string(),
+    ));
+    let (title, formats) = match fetch_formats(runner, yt_dlp, url, auth_args, js_runtime, cancel) {
+        Ok(found) => found,
+        Err(err) => {
+            cancel.check()?;
+            let _ = tx.send(DownloadEvent::Log(
+                LogLevel::Warn,
+                tr_fmt(Msg::FormatFallback, &[&err]),
+            ));
+            return Ok(None);
+        }
+    };
+    if !formats
+        .iter()
+        .any(|format| format.kind != FormatKind::Audio)
+    {
+        let _ = tx.send(DownloadEvent::Log(
+            LogLevel::Warn,
+            tr(Msg::NoVideoFormats).to_string(),
+        ));
+        return Ok(None);
+    }
+
+    let _ = tx.send(DownloadEvent::Log(
+        LogLevel::Info,
+        tr_fmt(Msg::FormatsFound, &[&formats.len()]),
+    ));
+    let (reply, answer) = mpsc::channel();
+    let _ = tx.send(DownloadEvent::FormatPrompt(FormatPrompt {
+        title,
+        formats: formats.clone(),
+        reply,
+    }));
+    let chosen = wait_for_reply(&answer, cancel)?
+        .flatten()
+        .and_then(|format_ids| chosen_format(&formats, &format_ids));
+    let _ = tx.send(DownloadEvent::Log(
+        LogLevel::Info,
+        match &chosen {
+            Some(chosen) => tr_fmt(Msg::DownloadWithFormat, &[&chosen.selector]),
+            None => tr(Msg::DownloadWithAutoFormat).to_string(),
+        },
+    ));
+    Ok(chosen)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_formats_and_builds_selector() {
+        let json = r#"{
+            "title": "Clip",
+            "formats": [
+                {"format_id": "sb0", "vcodec": "none", "acodec": "none", "ext": "mhtml"},
+                {"format_id": "140", "vcodec": "none", "acodec": "mp4a.40.2", "abr": 129.5, "ext": "m4a", "filesize": 2097152},
+                {"format_id": "18", "vcodec": "avc1.42001E", "acodec": "mp4a.40.2", "resolution": "640x360", "height": 360, "fps": 30, "ext": "mp4"},
+                {"format_id": "137", "vcodec": "avc1.640028", "acodec": "none", "resolution": "1920x1080", "height": 1080, "fps": 60, "ext": "mp4", "filesize_approx": 10485760}
+            ]
+        }"#;
+        let (title, formats) = parse_formats(json).unwrap();
+        assert_eq!(title, "Clip");
+        let ids = formats
+            .iter()
+            .map(|format| (format.format_id.as_str(), format.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ids,
+            [
+                ("137", FormatKind::Video),
+                ("18", FormatKind::Combined),
+                ("140", FormatKind::Audio)
+            ]
+        );
+        assert_eq!(formats[0].label, "137 1920x1080 60fps avc1 mp4 約10.0 MB");
+        assert_eq!(formats[1].label, "18 640x360 30fps avc1 + mp4a mp4");
+
+        let chosen = chosen_format(&formats, &["137".to_string(), "140".to_string()]).unwrap();
+        assert_eq!(chosen.selector, "137+140");
+        assert_eq!(chosen.size, Some(12582912));
+        assert_eq!(
+            chosen_format(&formats, &["18".to_string()]).unwrap().size,
+            None
+        );
+        assert!(chosen_format(&formats, &["999".to_string()]).is_none());
+        assert!(chosen_format(&formats, &[]).is_none());
+    }
+}
@@ -0,0 +1,90 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::settings::load_concurrent_fragments;
+
+use super::stage::parse_size;
+
+const MIB: u64 = 1024 * 1024;
+// 回線速度（バイト/秒）ごとの断片の数。遅い回線では並列にすると途切れやすいため減らす。
+const SPEED_STEPS: [(u64, u8); 4] = [(MIB, 1), (4 * MIB, 2), (12 * MIB, 4), (40 * MIB, 8)];
+const FASTEST_FRAGMENTS: u8 = 16;
+
+// yt-dlp の進捗から測った回線速度（バイト/秒）。0 はまだ測っていない。
+static MEASURED_SPEED: AtomicU64 = AtomicU64::new(0);
+
+// yt-dlp に渡す同時に取得する断片の数。自動調整では、測った回線速度から設定の数を上限に決める。
+pub(super) fn concurrent_fragments() -> u8 {
+    let (count, auto) = load_concurrent_fragments();
+    if !auto {
+        return count;
+    }
+    fragments_for_speed(measured_speed()).map_or(count, |tuned| tuned.min(count))
+}
+
+pub(super) fn measured_speed() -> Option<u64> {
+    Some(MEASURED_SPEED.load(Ordering::Relaxed)).filter(|speed| *speed > 0)
+}
+
+// 一時的な速度の揺れに引きずられないよう、前回までの値となだらかにつなぐ。
+pub(super) fn record_speed(bytes_per_second: u64) {
+    let previous = MEASURED_SPEED.load(Ordering::Relaxed);
+    let smoothed = if previous == 0 {
+        bytes_per_second
+    } else {
+        (previous * 3 + bytes_per_second) / 4
+    };
+    MEASURED_SPEED.store(smoothed, Ordering::Relaxed);
+}
+
+fn fragments_for_speed(speed: Option<u64>) -> Option<u8> {
+    let speed = speed?;
+    Some(
+        SPEED_STEPS
+            .iter()
+            .find(|(limit, _)| speed < *limit)
+            .map_or(FASTEST_FRAGMENTS, |(_, fragments)| *fragments),
+    )
+}
+
+// yt-dlp の `[download]  12.3% of 80.00MiB at 3.21MiB/s ETA 00:20` から速度を読む。
+pub(super) fn parse_speed(line: &str) -> Option<u64> {
+    if !line.starts_with("[download]") {
+        return None;
+    }
+    let (_, rest) = line.split_once(" at ")?;
+    let value = rest.split_whitespace().next()?.strip_suffix("/s")?;
+    parse_size(value).filter(|bytes| *bytes > 0)
+}
+
+// ログ表示用の回線速度（MB/s）
+pub(super) fn speed_label(bytes_per_second: u64) -> String {
+    format!("{:.1}MB/s", bytes_per_second as f64 / MIB as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tunes_fragments_from_download_speed() {
+        assert_eq!(
+            parse_speed("[download]  12.3% of ~ 80.00MiB at    3.50MiB/s ETA 00:20 (frag 4/40)"),
+            Some(3 * MIB + MIB / 2)
+        );
+        assert_eq!(
+            parse_speed("[download]   1.0% of 10.00MiB at  512.00KiB/s ETA 00:19"),
+            Some(512 * 1024)
+        );
+        assert_eq!(
+            parse_speed("[download]   0.0% of 10.00MiB at Unknown B/s ETA Unknown"),
+            None
+        );
+        assert_eq!(parse_speed("[ffmpeg] Merging formats at 3.0MiB/s"), None);
+
+        assert_eq!(fragments_for_speed(None), None);
+        assert_eq!(fragments_for_speed(Some(500 * 1024)), Some(1));
+        assert_eq!(fragments_for_speed(Some(6 * MIB)), Some(4));
+        assert_eq!(fragments_for_speed(Some(100 * MIB)), Some(16));
+        assert_eq!(speed_label(3 * MIB + MIB / 2), "3.5MB/s");
+    }
+}
@@ -0,0 +1,225 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::{Connection, OptionalExtension, params};
+use url::Url;
+
+use crate::paths::{download_archive_path, download_history_db_path};
+
+// 比べる前に URL の表記ゆれ（ホストの大文字・フラグメント）をそろえる。
+fn normalize_url(url: &str) -> String {
+    let trimmed = url.trim();
+    match Url::parse(trimmed) {
+        Ok(mut parsed) => {
+            parsed.set_fragment(None);
+            parsed.to_string()
+        }
+        Err(_) => trimmed.to_string(),
+    }
+}
+
+// 正規化した URL の FNV-1a（64 ビット）を 16 進で返す。
+fn url_hash(url: &str) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in normalize_url(url).bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{hash:016x}")
+}
+
+// `yt-dlp -J` の結果から、ダウンロードアーカイブの 1 行（`<extractor> <id>`）を作る。
+pub(super) fn archive_id(json: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(json).ok()?;
+    let extractor = value
+        .get("extractor_key")
+        .or_else(|| value.get("ie_key"))
+        .and_then(|v| v.as_str())?;
+    let id = value.get("id").and_then(|v| v.as_str())?;
+    Some(format!("{} {id}", extractor.to_lowercase()))
+}
+
+fn archive_contains(contents: &str, archive_id: &str) -> bool {
+    contents.lines().any(|line| line.trim() == archive_id)
+}
+
+pub(super) fn is_archived(archive_id: &str) -> bool {
+    fs::read_to_string(download_archive_path())
+        .is_ok_and(|contents| archive_contains(&contents, archive_id))
+}
+
+fn open_history(path: &Path) -> Result<Connection, String> {
+    let conn = Connection::open(path).map_err(|err| err.to_string())?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS downloads (
+            url_hash TEXT PRIMARY KEY,
+            url TEXT NOT NULL,
+            output_path TEXT NOT NULL,
+            downloaded_at INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS subscription_entries (
+            source_hash TEXT NOT NULL,
+            url_hash TEXT NOT NULL,
+            seen_at INTEGER NOT NULL,
+            PRIMARY KEY (source_hash, url_hash)
+        );",
+    )
+    .map_err(|err| err.to_string())?;
+    Ok(conn)
+}
+
+fn find_in(conn: &Connection, url: &str) -> Result<Option<PathBuf>, String> {
+    conn.query_row(
+        "SELECT output_path FROM downloads WHERE url_hash = ?1",
+        params![url_hash(url)],
+        |row| row.get::<_, String>(0),
+    )
+    .optional()
+    .map(|path| path.map(PathBuf::from))
+    .map_err(|err| err.to_string())
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn record_in(conn: &Connection, url: &str, output_path: &Path) -> Result<(), String> {
+    let now = now_secs();
+    conn.execute(
+        "INSERT INTO downloads (url_hash, url, output_path, downloaded_at)
+         VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(url_hash) DO UPDATE SET
+            url = excluded.url,
+            output_path = excluded.output_path,
+            downloaded_at = excluded.downloaded_at",
+        params![
+            url_hash(url),
+            normalize_url(url),
+            output_path.to_string_lossy(),
+            now
+        ],
+    )
+    .map(|_| ())
+    .map_err(|err| err.to_string())
+}
+
+// 購読元の一覧のうち、まだ見ていない動画の URL を返し、見たものとして記録する。
+// 初めて確認する購読元は今ある動画を記録するだけにし、何も返さない。ダウンロード済みの URL は返さない。
+fn take_unseen_in(conn: &Connection, source: &str, urls: &[String]) -> Result<Vec<String>, String> {
+    let source_hash = url_hash(source);
+    let first_check = conn
+        .query_row(
+            "SELECT 1 FROM subscription_entries WHERE source_hash = ?1 LIMIT 1",
+            params![source_hash],
+            |_| Ok(()),
+        )
+        .optional()
+        .map_err(|err| err.to_string())?
+        .is_none();
+    let now = now_secs();
+    let mut unseen = Vec::new();
+    for url in urls {
+        let inserted = conn
+            .execute(
+                "INSERT OR IGNORE INTO subscription_entries (source_hash, url_hash, seen_at)
+                 VALUES (?1, ?2, ?3)",
+                params![source_hash, url_hash(url), now],
+            )
+            .map_err(|err| err.to_string())?;
+        if inserted > 0 && !first_check && find_in(conn, url)?.is_none() {
+            unseen.push(url.clone());
+        }
+    }
+    Ok(unseen)
+}
+
+pub(super) fn take_unseen_urls(source: &str, urls: &[String]) -> Result<Vec<String>, String> {
+    let conn = open_history(&download_history_db_path())?;
+    take_unseen_in(&conn, source, urls)
+}
+
+// 以前に同じ URL をダウンロードした場合は、保存したファイルのパスを返す（消えている場合もある）。
+pub(super) fn find_downloaded(url: &str) -> Option<PathBuf> {
+    let conn = open_history(&download_history_db_path()).ok()?;
+    find_in(&conn, url).ok().flatten()
+}
+
+// 記録できなくてもダウンロード自体は成功として扱う。
+pub(super) fn record_download(url: &str, output_path: &Path) {
+    let result = open_history(&download_history_db_path())
+        .and_then(|conn| record_in(&conn, url, output_path));
+    if let Err(err) = result {
+        eprintln!("[download] failed to record history: {err}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_download_archive_lines() {
+        let json = r#"{"id": "dQw4w9WgXcQ", "extractor_key": "Youtube", "title": "clip"}"#;
+        let id = archive_id(json).unwrap();
+        assert_eq!(id, "youtube dQw4w9WgXcQ");
+        assert!(archive_contains("vimeo 123\nyoutube dQw4w9WgXcQ\n", &id));
+        assert!(!archive_contains("youtube other\n", &id));
+        assert_eq!(archive_id(r#"{"title": "clip"}"#), None);
+    }
+
+    #[test]
+    fn finds_recorded_urls_by_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_history(&dir.path().join("history.sqlite3")).unwrap();
+        let url = "https://www.youtube.com/watch?v=abc";
+        assert_eq!(find_in(&conn, url).unwrap(), None);
+
+        record_in(&conn, url, Path::new("/VJ/clip.mp4")).unwrap();
+        assert_eq!(
+            find_in(&conn, "https://WWW.YOUTUBE.COM/watch?v=abc#t=30").unwrap(),
+            Some(PathBuf::from("/VJ/clip.mp4"))
+        );
+        assert_eq!(
+            find_in(&conn, "https://www.youtube.com/watch?v=xyz").unwrap(),
+            None
+        );
+
+        record_in(&conn, url, Path::new("/VJ/clip (1).mp4")).unwrap();
+        assert_eq!(
+            find_in(&conn, url).unwrap(),
+            Some(PathBuf::from("/VJ/clip (1).mp4"))
+        );
+    }
+
+    #[test]
+    fn returns_only_uploads_added_after_first_check() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_history(&dir.path().join("history.sqlite3")).unwrap();
+        let source = "https://www.youtube.com/@vj/videos";
+        let old = "https://www.youtube.com/watch?v=old".to_string();
+        let fresh = "https://www.youtube.com/watch?v=fresh".to_string();
+        let fetched = "https://www.youtube.com/watch?v=fetched".to_string();
+
+        // 初めての確認では、今ある動画を記録するだけ。
+        assert!(
+            take_unseen_in(&conn, source, &[old.clone()])
+                .unwrap()
+                .is_empty()
+        );
+        record_in(&conn, &fetched, Path::new("/VJ/fetched.mp4")).unwrap();
+        assert_eq!(
+            take_unseen_in(&conn, source, &[fresh.clone(), fetched, old.clone()]).unwrap(),
+            vec![fresh.clone()]
+        );
+        // 一度返した動画は次から返さない。
+        assert!(
+            take_unseen_in(&conn, source, &[fresh, old])
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+use crate::i18n::{Msg, tr_fmt};
+
+// VideoToolbox でデコードできるコーデック（ffprobe の codec_name）。
+// VP9・AV1 は対応するチップの Mac に限られ、非対応の場合は ffmpeg がソフトウェアデコードに戻す。
+const VIDEOTOOLBOX_DECODE_CODECS: [&str; 7] = [
+    "h264",
+    "hevc",
+    "vp9",
+    "av1",
+    "prores",
+    "mpeg2video",
+    "mpeg4",
+];
+const HWACCEL_ARGS: [&str; 2] = ["-hwaccel", "videotoolbox"];
+
+// 入力のコーデックをハードウェアでデコードできるか。パイプ入力などで分からない場合も試す。
+fn supports_hardware_decode(codec: Option<&str>) -> bool {
+    codec.is_none_or(|codec| VIDEOTOOLBOX_DECODE_CODECS.contains(&codec))
+}
+
+// 映像を再エンコードする場合に、`-i` より前へ付けるデコードの指定。
+pub(super) fn hwaccel_args(codec: Option<&str>, reencode: bool) -> &'static [&'static str] {
+    if cfg!(target_os = "macos") && reencode && supports_hardware_decode(codec) {
+        &HWACCEL_ARGS
+    } else {
+        &[]
+    }
+}
+
+// 変換にかかった時間を、動画の長さに対する速さとしてログに残す。デコード方式ごとに比べられるようにする。
+pub(super) fn conversion_speed_log(
+    duration_seconds: Option<f64>,
+    elapsed: Duration,
+    hardware: bool,
+) -> Option<String> {
+    let duration = duration_seconds.filter(|seconds| *seconds > 0.0)?;
+    let elapsed = elapsed.as_secs_f64().max(0.001);
+    let msg = if hardware {
+        Msg::HardwareDecodeSpeed
+    } else {
+        Msg::SoftwareDecodeSpeed
+    };
+    Some(tr_fmt(
+        msg,
+        &[
+            &format!("{duration:.0}"),
+            &format!("{elapsed:.1}"),
+            &format!("{:.1}", duration / elapsed),
+        ],
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_supported_codecs_on_hardware() {
+        assert!(supports_hardware_decode(Some("vp9")));
+        assert!(supports_hardware_decode(Some("h264")));
+        assert!(supports_hardware_decode(None));
+        assert!(!supports_hardware_decode(Some("vp8")));
+        // 映像をコピーする場合はデコードしない。
+        assert!(hwaccel_args(Some("vp9"), false).is_empty());
+        assert!(hwaccel_args(Some("vp8"), true).is_empty());
+
+        assert_eq!(
+            conversion_speed_log(None, Duration::from_secs(10), true),
+            None
+        );
+        let log = conversion_speed_log(Some(90.0), Duration::from_secs(30), true).unwrap();
+        assert!(log.contains("3.0"));
+    }
+}
@@ -0,0 +1,76 @@
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, mpsc};
+
+use crate::app_logger::LogLevel;
+use crate::fs_utils::ensure_dir;
+use crate::i18n::{Msg, tr_fmt};
+use crate::paths::ffmpeg_path;
+use crate::settings::load_collision_policy;
+
+use super::encoder::VideoEncoding;
+use super::runner::CommandRunner;
+use super::{CancellationToken, DownloadEvent, ProgressContext};
+use super::{convert, disk_space, staging, validate};
+
+// 監視フォルダに置かれた動画を、ダウンロードと同じ変換・保存の手順で保存先へ移す。
+// 元のファイルは staging へコピーして変換し、保存先へ移せた後にだけ削除する。
+pub(super) fn import_video_file(
+    runner: &dyn CommandRunner,
+    source: &Path,
+    output_dir: &Path,
+    encoding: &VideoEncoding,
+    tx: &mpsc::Sender<DownloadEvent>,
+    progress: &Arc<ProgressContext>,
+    cancel: &CancellationToken,
+) -> Result<(), String> {
+    let Some(file_name) = source.file_name().filter(|_| source.is_file()) else {
+        return Err(tr_fmt(Msg::WatchedFileMissing, &[&source.display()]));
+    };
+    let _ = tx.send(DownloadEvent::Log(
+        LogLevel::Info,
+        tr_fmt(Msg::ImportingWatchedFile, &[&source.display()]),
+    ));
+    if let Err(err) = ensure_dir(output_dir) {
+        return Err(tr_fmt(Msg::CreateOutputDirFailed, &[&err]));
+    }
+    let size = fs::metadata(source).ok().map(|meta| meta.len());
+    disk_space::ensure_free_space(output_dir, size, tx)?;
+
+    let ffmpeg = ffmpeg_path();
+    let staging = staging::StagingDir::new(staging::create_download_staging_dir(output_dir)?);
+    let staging_dir = staging.path();
+    let result = fs::copy(source, staging_dir.join(file_name))
+        .map_err(|err| tr_fmt(Msg::CopyWatchedFileFailed, &[&err]))
+        .and_then(|_| {
+            convert::convert_staged_videos_to_mp4(
+                runner,
+                staging_dir,
+                &ffmpeg,
+                encoding,
+                tx,
+                progress,
+                cancel,
+            )
+        })
+        .and_then(|()| {
+            staging::promote_downloaded_mp4_files(
+                staging_dir,
+                output_dir,
+                load_collision_policy(),
+                tx,
+                cancel,
+            )
+        })
+        .and_then(|promoted| {
+            validate::validate_outputs(runner, &promoted, output_dir, &ffmpeg, tx, cancel)
+        });
+    let cleanup_error = staging.remove().err();
+    result?;
+
+    fs::remove_file(source).map_err(|err| tr_fmt(Msg::RemoveWatchedFileFailed, &[&err]))?;
+    if let Some(err) = cleanup_error {
+        return Err(tr_fmt(Msg::RemoveTempDirFailed, &[&err]));
+    }
+    Ok(())
+}
@@ -0,0 +1,124 @@
+use crate::i18n::{Msg, tr};
+
+// yt-dlp のエラー出力から分かる、設定を変えれば対処できる失敗
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KnownError {
+    BotCheck,
+    AgeRestricted,
+    MembersOnly,
+    GeoBlocked,
+}
+
+impl KnownError {
+    // yt-dlp の ERROR 行から判定する。WARNING などの行は、その後に成功する場合があるため対象にしない。
+    pub fn detect(line: &str) -> Option<Self> {
+        let lower = line.trim_start().to_ascii_lowercase();
+        if !lower.starts_with("error:") {
+            return None;
+        }
+        let has = |patterns: &[&str]| patterns.iter().any(|pattern| lower.contains(pattern));
+        if has(&["not a bot"]) {
+            Some(KnownError::BotCheck)
+        } else if has(&[
+            "confirm your age",
+            "age-restricted",
+            "inappropriate for some users",
+        ]) {
+            Some(KnownError::AgeRestricted)
+        } else if has(&[
+            "members-only",
+            "members only",
+            "join this channel to get access",
+        ]) {
+            Some(KnownError::MembersOnly)
+        } else if has(&[
+            "available in your country",
+            "geo restricted",
+            "geo-restricted",
+            "not available from your location",
+        ]) {
+            Some(KnownError::GeoBlocked)
+        } else {
+            None
+        }
+    }
+
+    // WebSocket 配信などで使う識別子
+    pub fn as_key(self) -> &'static str {
+        match self {
+            KnownError::BotCheck => "bot_check",
+            KnownError::AgeRestricted => "age_restricted",
+            KnownError::MembersOnly => "members_only",
+            KnownError::GeoBlocked => "geo_blocked",
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            KnownError::BotCheck => tr(Msg::BotCheckTitle),
+            KnownError::AgeRestricted => tr(Msg::AgeRestrictedTitle),
+            KnownError::MembersOnly => tr(Msg::MembersOnlyTitle),
+            KnownError::GeoBlocked => tr(Msg::GeoBlockedTitle),
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            KnownError::BotCheck => tr(Msg::BotCheckDescription),
+            KnownError::AgeRestricted => tr(Msg::AgeRestrictedDescription),
+            KnownError::MembersOnly => tr(Msg::MembersOnlyDescription),
+            KnownError::GeoBlocked => tr(Msg::GeoBlockedDescription),
+        }
+    }
+
+    // 対処に使う設定のボタン。地域の制限はクッキーでは解決しないため、詳細オプションを案内する。
+    pub fn action_label(self) -> &'static str {
+        match self {
+            KnownError::GeoBlocked => tr(Msg::OpenExtraOptions),
+            KnownError::BotCheck | KnownError::AgeRestricted | KnownError::MembersOnly => {
+                tr(Msg::OpenCookieSettings)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_actionable_errors() {
+        assert_eq!(
+            KnownError::detect(
+                "ERROR: [youtube] abc: Sign in to confirm you’re not a bot. Use --cookies-from-browser or --cookies for the authentication."
+            ),
+            Some(KnownError::BotCheck)
+        );
+        assert_eq!(
+            KnownError::detect(
+                "ERROR: [youtube] abc: Join this channel to get access to members-only content like this video, and other exclusive perks."
+            ),
+            Some(KnownError::MembersOnly)
+        );
+        assert_eq!(
+            KnownError::detect(
+                "ERROR: [youtube] abc: The uploader has not made this video available in your country"
+            ),
+            Some(KnownError::GeoBlocked)
+        );
+        assert_eq!(
+            KnownError::detect(
+                "ERROR: [youtube] abc: Sign in to confirm your age. This video may be inappropriate for some users."
+            ),
+            Some(KnownError::AgeRestricted)
+        );
+        assert_eq!(
+            KnownError::detect("WARNING: [youtube] Sign in to confirm you're not a bot"),
+            None
+        );
+        assert_eq!(
+            KnownError::detect("ERROR: unable to download video data: HTTP Error 403"),
+            None
+        );
+    }
+}
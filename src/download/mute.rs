@@ -0,0 +1,273 @@
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+use crate::bundled::ensure_bundled_tools;
+use crate::i18n::{Msg, tr, tr_fmt};
+use crate::paths::ffmpeg_path;
+
+use super::CancellationToken;
+use super::runner::{CommandRunner, CommandSpec, SystemRunner, output};
+use super::staging::{StagingDir, create_download_staging_dir, next_available_destination};
+
+// 選んだ動画の音声をどうするか
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AudioReplacement {
+    // 音声を外す
+    Remove,
+    // 無音の音声に差し替える。音声トラックが無いと読み込めないソフト向け。
+    Silence,
+    // 音声ファイル（クリック音など）に差し替える。動画より短い場合は繰り返し、長い場合は動画の長さで切る。
+    Track(PathBuf),
+}
+
+impl AudioReplacement {
+    // 作ったコピーの名前に付ける印
+    fn suffix(&self) -> String {
+        match self {
+            AudioReplacement::Remove => "muted".to_string(),
+            AudioReplacement::Silence => "silent".to_string(),
+            AudioReplacement::Track(track) => track
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().trim().to_string())
+                .filter(|stem| !stem.is_empty())
+                .unwrap_or_else(|| "audio".to_string()),
+        }
+    }
+}
+
+// 音声の差し替えの進み具合。画面側でステータスに表示する。
+pub enum MuteEvent {
+    // index 番目（0 始まり）の書き出しを始めた
+    Started { index: usize, total: usize },
+    Created { original: PathBuf, output: PathBuf },
+    Failed { original: PathBuf, error: String },
+    // すべて終えた（取り消した場合も届く）
+    Finished { created: usize, cancelled: bool },
+}
+
+// 音声を差し替えたコピーのファイル名。拡張子は元の動画のまま、名前の後ろに印を付ける（`clip muted.mp4`）。
+pub(super) fn muted_file_name(original: &Path, replacement: &AudioReplacement) -> String {
+    let stem = original
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .filter(|stem| !stem.trim().is_empty())
+        .unwrap_or_else(|| "video".to_string());
+    let ext = original
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_string())
+        .unwrap_or_else(|| "mp4".to_string());
+    format!("{stem} {}.{ext}", replacement.suffix())
+}
+
+// ffmpeg の引数。映像は再エンコードせずにコピーし、音声だけを外すか差し替える。
+fn replacement_args(
+    original: &Path,
+    replacement: &AudioReplacement,
+    output: &Path,
+) -> Vec<OsString> {
+    let mut args: Vec<OsString> = vec!["-hide_banner".into(), "-y".into(), "-i".into()];
+    args.push(original.into());
+    match replacement {
+        AudioReplacement::Remove => {
+            args.extend(["-map", "0:v:0", "-c:v", "copy", "-an"].map(OsString::from));
+        }
+        AudioReplacement::Silence => {
+            args.extend(
+                [
+                    "-f",
+                    "lavfi",
+                    "-i",
+                    "anullsrc=channel_layout=stereo:sample_rate=48000",
+                ]
+                .map(OsString::from),
+            );
+            args.extend(
+                [
+                    "-map",
+                    "0:v:0",
+                    "-map",
+                    "1:a:0",
+                    "-c:v",
+                    "copy",
+                    "-c:a",
+                    "aac",
+                    "-b:a",
+                    "128k",
+                    "-shortest",
+                ]
+                .map(OsString::from),
+            );
+        }
+        AudioReplacement::Track(track) => {
+            args.extend(["-stream_loop", "-1", "-i"].map(OsString::from));
+            args.push(track.into());
+            args.extend(
+                [
+                    "-map",
+                    "0:v:0",
+                    "-map",
+                    "1:a:0",
+                    "-c:v",
+                    "copy",
+                    "-c:a",
+                    "aac",
+                    "-b:a",
+                    "192k",
+                    "-shortest",
+                ]
+                .map(OsString::from),
+            );
+        }
+    }
+    args.extend(["-movflags", "+faststart"].map(OsString::from));
+    args.push(output.into());
+    args
+}
+
+// 音声を差し替えたコピーを元の動画の隣に作る。
+fn replace_audio(
+    runner: &dyn CommandRunner,
+    original: &Path,
+    replacement: &AudioReplacement,
+    ffmpeg: &Path,
+    cancel: &CancellationToken,
+) -> Result<PathBuf, String> {
+    let dir = original
+        .parent()
+        .filter(|_| original.is_file())
+        .ok_or_else(|| tr_fmt(Msg::OriginalVideoMissing, &[&original.to_string_lossy()]))?;
+    let file_name = muted_file_name(original, replacement);
+    // 書き出し途中のファイルが一覧に出ないよう、一時フォルダへ書いてから移す。
+    let staging = StagingDir::new(create_download_staging_dir(dir)?);
+    let staged = staging.path().join(&file_name);
+    let cmd = CommandSpec::new(ffmpeg).args(replacement_args(original, replacement, &staged));
+    let output = output(runner, &cmd, cancel)
+        .map_err(|err| tr_fmt(Msg::ToolLaunchFailed, &[&"ffmpeg", &err]))?;
+    cancel.check()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let last = stderr.lines().last().unwrap_or_default().trim();
+        return Err(tr_fmt(Msg::AudioReplaceFailed, &[&last]));
+    }
+
+    let mut destination = dir.join(&file_name);
+    if destination.exists() {
+        destination = next_available_destination(&destination)?;
+    }
+    fs::rename(&staged, &destination).map_err(|err| tr_fmt(Msg::MoveFileFailed, &[&err]))?;
+    let _ = staging.remove();
+    Ok(destination)
+}
+
+// 選んだ動画から順に音声を差し替えたコピーを作る。1 件失敗しても残りは続け、取り消した場合だけ止める。
+pub fn replace_audio_files(
+    originals: Vec<PathBuf>,
+    replacement: AudioReplacement,
+    tx: mpsc::Sender<MuteEvent>,
+    cancel: CancellationToken,
+) {
+    let total = originals.len();
+    let mut created = 0;
+    let prepared = ensure_bundled_tools()
+        .and_then(|()| {
+            let ffmpeg = ffmpeg_path();
+            if ffmpeg.exists() {
+                Ok(ffmpeg)
+            } else {
+                Err(tr(Msg::FfmpegNotFound).to_string())
+            }
+        })
+        .and_then(|ffmpeg| match &replacement {
+            AudioReplacement::Track(track) if !track.is_file() => {
+                Err(tr_fmt(Msg::AudioFileMissing, &[&track.to_string_lossy()]))
+            }
+            _ => Ok(ffmpeg),
+        });
+    let ffmpeg = match prepared {
+        Ok(ffmpeg) => ffmpeg,
+        Err(error) => {
+            for original in originals {
+                let _ = tx.send(MuteEvent::Failed {
+                    original,
+                    error: error.clone(),
+                });
+            }
+            let _ = tx.send(MuteEvent::Finished {
+                created,
+                cancelled: false,
+            });
+            return;
+        }
+    };
+
+    for (index, original) in originals.into_iter().enumerate() {
+        if cancel.is_cancelled() {
+            break;
+        }
+        let _ = tx.send(MuteEvent::Started { index, total });
+        match replace_audio(&SystemRunner, &original, &replacement, &ffmpeg, &cancel) {
+            Ok(output) => {
+                created += 1;
+                let _ = tx.send(MuteEvent::Created { original, output });
+            }
+            Err(_) if cancel.is_cancelled() => break,
+            Err(error) => {
+                let _ = tx.send(MuteEvent::Failed { original, error });
+            }
+        }
+    }
+    let _ = tx.send(MuteEvent::Finished {
+        created,
+        cancelled: cancel.is_cancelled(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_extension_of_original() {
+        assert_eq!(
+            muted_file_name(
+                Path::new("/clips/strobe loop.mov"),
+                &AudioReplacement::Remove
+            ),
+            "strobe loop muted.mov"
+        );
+        assert_eq!(
+            muted_file_name(Path::new("/clips/intro"), &AudioReplacement::Silence),
+            "intro silent.mp4"
+        );
+        assert_eq!(
+            muted_file_name(
+                Path::new("/clips/intro.mp4"),
+                &AudioReplacement::Track(PathBuf::from("/audio/click 120.wav"))
+            ),
+            "intro click 120.mp4"
+        );
+    }
+
+    #[test]
+    fn copies_video_and_swaps_audio() {
+        let args = |replacement: &AudioReplacement| {
+            replacement_args(Path::new("in.mp4"), replacement, Path::new("out.mp4"))
+                .into_iter()
+                .map(|arg| arg.to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        assert_eq!(
+            args(&AudioReplacement::Remove),
+            "-hide_banner -y -i in.mp4 -map 0:v:0 -c:v copy -an -movflags +faststart out.mp4"
+        );
+        assert!(args(&AudioReplacement::Silence).contains(
+            "-f lavfi -i anullsrc=channel_layout=stereo:sample_rate=48000 -map 0:v:0 -map 1:a:0"
+        ));
+        let track = args(&AudioReplacement::Track(PathBuf::from("click.wav")));
+        assert!(track.contains("-stream_loop -1 -i click.wav -map 0:v:0 -map 1:a:0 -c:v copy"));
+        assert!(track.contains("-shortest"));
+    }
+}
@@ -0,0 +1,142 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crate::i18n::{Msg, tr_fmt};
+use crate::paths::login_temp_dir;
+use crate::settings::SiteLogin;
+
+use super::CancellationToken;
+
+// 試すだけの表示で、netrc ファイルの場所の代わりに出す印
+const DRY_RUN_NETRC: &str = "<netrc>";
+
+static NEXT_FILE: AtomicU32 = AtomicU32::new(0);
+
+// サイトのログインを yt-dlp へ渡す引数。パスワードをコマンドラインに載せないよう、
+// 所有者だけが読める netrc ファイルへ書いて場所だけを渡す。ファイルはダウンロードを終えた時に消す。
+pub fn netrc_login_args(
+    login: &SiteLogin,
+    cancel: &CancellationToken,
+) -> Result<Vec<String>, String> {
+    let path = write_netrc(&login_temp_dir(), login, cancel)
+        .map_err(|err| tr_fmt(Msg::NetrcWriteFailed, &[&err]))?;
+    Ok(netrc_args(&path))
+}
+
+// 試すだけの場合はファイルを作らず、場所の代わりに印を出す。
+pub fn dry_run_login_args() -> Vec<String> {
+    netrc_args(Path::new(DRY_RUN_NETRC))
+}
+
+// 異常終了で残ったログイン情報のファイルを消す。起動時、ダウンロードを始める前に呼ぶ。
+pub fn remove_stale_login_files() {
+    if let Ok(entries) = fs::read_dir(login_temp_dir()) {
+        for entry in entries.flatten() {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+fn netrc_args(path: &Path) -> Vec<String> {
+    vec![
+        "--netrc".to_string(),
+        "--netrc-location".to_string(),
+        path.to_string_lossy().to_string(),
+    ]
+}
+
+fn write_netrc(dir: &Path, login: &SiteLogin, cancel: &CancellationToken) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!(
+        "login-{}-{}.netrc",
+        std::process::id(),
+        NEXT_FILE.fetch_add(1, Ordering::Relaxed)
+    ));
+    // 同じ名前のファイルが残っていれば消し、他のユーザーが読めるファイルへは書かない。
+    let _ = fs::remove_file(&path);
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&path)?;
+    cancel.remove_when_finished(path.clone());
+    file.write_all(netrc_contents(login).as_bytes())?;
+    Ok(path)
+}
+
+// yt-dlp は抽出器ごとの名前（youtube など）で探すため、どの名前にも当たる default に書く。
+fn netrc_contents(login: &SiteLogin) -> String {
+    format!(
+        "default login {} password {}\n",
+        netrc_token(&login.username),
+        netrc_token(&login.password)
+    )
+}
+
+// 空白・引用符・# を含む値は、二重引用符で囲んでエスケープする。
+fn netrc_token(value: &str) -> String {
+    let plain = !value.is_empty()
+        && !value
+            .chars()
+            .any(|ch| ch.is_whitespace() || matches!(ch, '"' | '\\' | '#'));
+    if plain {
+        value.to_string()
+    } else {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn login(username: &str, password: &str) -> SiteLogin {
+        SiteLogin {
+            site: "example.com".to_string(),
+            username: username.to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    #[test]
+    fn quotes_netrc_values() {
+        assert_eq!(
+            netrc_contents(&login("vj", "hunter2")),
+            "default login vj password hunter2\n"
+        );
+        assert_eq!(
+            netrc_contents(&login("vj user", r#"pa"ss\ #1"#)),
+            "default login \"vj user\" password \"pa\\\"ss\\\\ #1\"\n"
+        );
+    }
+
+    // パスワードはファイルにだけ書き、ダウンロードを終えたら消す。
+    #[test]
+    fn writes_private_netrc_and_removes_it_when_finished() {
+        let dir = std::env::temp_dir().join(format!("vjd-netrc-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let cancel = CancellationToken::new();
+        let path = write_netrc(&dir, &login("vj", "hunter2"), &cancel).unwrap();
+
+        assert!(!netrc_args(&path).iter().any(|arg| arg.contains("hunter2")));
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "default login vj password hunter2\n"
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        cancel.finish();
+        assert!(!path.exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
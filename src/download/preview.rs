@@ -0,0 +1,200 @@
+use std::path::Path;
+use std::sync::mpsc;
+
+use serde_json::Value;
+
+use crate::app_logger::LogLevel;
+use crate::i18n::{Msg, tr, tr_fmt};
+
+use super::process::fetch_yt_dlp_info;
+use super::runner::{CommandRunner, CommandSpec, output};
+use super::tools::js_runtime_args;
+use super::{CancellationToken, DownloadEvent, PipelineError, wait_for_reply};
+
+// サムネイルを表示する幅
+const THUMBNAIL_WIDTH: usize = 320;
+// これより長い動画は、確認画面で長さを目立たせる（秒）
+pub const LONG_DURATION_SECONDS: f64 = 60.0 * 60.0;
+
+// ダウンロードを始める前に、動画の情報を確認してもらう。
+pub struct MetadataPreview {
+    pub title: String,
+    pub uploader: String,
+    pub duration_seconds: Option<f64>,
+    // サムネイルの大きさと RGBA。取得できなかった場合は None。
+    pub thumbnail: Option<([usize; 2], Vec<u8>)>,
+    reply: mpsc::Sender<bool>,
+}
+
+impl MetadataPreview {
+    // ダウンロードするかどうかをダウンロード処理へ返す。
+    pub fn respond(&self, download: bool) {
+        let _ = self.reply.send(download);
+    }
+
+    // 1:02:03 / 4:05 の形式。長さが分からない場合は None。
+    pub fn duration_label(&self) -> Option<String> {
+        let total = self.duration_seconds?.round() as u64;
+        let (hours, minutes, seconds) = (total / 3600, total / 60 % 60, total % 60);
+        Some(if hours > 0 {
+            format!("{hours}:{minutes:02}:{seconds:02}")
+        } else {
+            format!("{minutes}:{seconds:02}")
+        })
+    }
+}
+
+// `yt-dlp -J` の結果から、タイトル・投稿者・長さ・サムネイルの URL を取り出す。
+fn parse_metadata(json: &str) -> Result<(String, String, Option<f64>, Option<String>), String> {
+    let value: Value =
+        serde_json::from_str(json).map_err(|err| tr_fmt(Msg::VideoInfoParseFailed, &[&err]))?;
+    let text = |key: &str| {
+        value
+            .get(key)
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .map(str::to_string)
+    };
+    let title = text("title").unwrap_or_default();
+    let uploader = text("uploader")
+        .or_else(|| text("channel"))
+        .unwrap_or_default();
+    let duration = value.get("duration").and_then(Value::as_f64);
+    Ok((title, uploader, duration, text("thumbnail")))
+}
+
+// サムネイルを ffmpeg で読み込み、表示用の大きさの RGBA にする。
+fn load_thumbnail(
+    runner: &dyn CommandRunner,
+    ffmpeg: &Path,
+    url: &str,
+    cancel: &CancellationToken,
+) -> Option<([usize; 2], Vec<u8>)> {
+    let cmd = CommandSpec::new(ffmpeg)
+        .args([
+            "-hide_banner",
+            "-v",
+            "error",
+            "-i",
+            url,
+            "-frames:v",
+            "1",
+            "-vf",
+        ])
+        .arg(format!("scale={THUMBNAIL_WIDTH}:-2"))
+        .args(["-f", "rawvideo", "-pix_fmt", "rgba", "-"]);
+    let output = output(runner, &cmd, cancel).ok()?;
+    let row = THUMBNAIL_WIDTH * 4;
+    if !output.status.success() || output.stdout.is_empty() || output.stdout.len() % row != 0 {
+        return None;
+    }
+    let height = output.stdout.len() / row;
+    Some(([THUMBNAIL_WIDTH, height], output.stdout))
+}
+
+fn fetch_metadata(
+    runner: &dyn CommandRunner,
+    yt_dlp: &Path,
+    url: &str,
+    auth_args: &[String],
+    js_runtime: &[String],
+    cancel: &CancellationToken,
+) -> Result<(String, String, Option<f64>, Option<String>), String> {
+    let mut args = vec!["--no-playlist".to_string()];
+    args.extend(auth_args.iter().cloned());
+    args.extend([
+        "--extractor-args".to_string(),
+        "youtube:player_client=web".to_string(),
+    ]);
+    args.extend(js_runtime.iter().cloned());
+    parse_metadata(&fetch_yt_dlp_info(runner, yt_dlp, &args, url, cancel)?)
+}
+
+// 動画の情報を表示して、ダウンロードするか確認する。
+// やめた場合や確認画面を閉じた場合はキャンセル扱いにし、情報を取得できなかった場合は確認せずに続ける。
+pub(super) fn confirm_download(
+    runner: &dyn CommandRunner,
+    yt_dlp: &Path,
+    ffmpeg: &Path,
+    url: &str,
+    auth_args: &[String],
+    tx: &mpsc::Sender<DownloadEvent>,
+    cancel: &CancellationToken,
+) -> Result<(), PipelineError> {
+    let _ = tx.send(DownloadEvent::Log(
+        LogLevel::Info,
+        tr(Msg::FetchingPreview).to_string(),
+    ));
+    let (title, uploader, duration_seconds, thumbnail_url) =
+        match fetch_metadata(runner, yt_dlp, url, auth_args, &js_runtime_args(), cancel) {
+            Ok(metadata) => metadata,
+            Err(err) => {
+                cancel.check()?;
+                let _ = tx.send(DownloadEvent::Log(
+                    LogLevel::Warn,
+                    tr_fmt(Msg::PreviewFallback, &[&err]),
+                ));
+                return Ok(());
+            }
+        };
+    let thumbnail = thumbnail_url
+        .as_deref()
+        .and_then(|thumbnail_url| load_thumbnail(runner, ffmpeg, thumbnail_url, cancel));
+    cancel.check()?;
+
+    let (reply, answer) = mpsc::channel();
+    let _ = tx.send(DownloadEvent::MetadataPreview(MetadataPreview {
+        title,
+        uploader,
+        duration_seconds,
+        thumbnail,
+        reply,
+    }));
+    if wait_for_reply(&answer, cancel)? == Some(true) {
+        Ok(())
+    } else {
+        let _ = tx.send(DownloadEvent::Log(
+            LogLevel::Info,
+            tr(Msg::PreviewDeclined).to_string(),
+        ));
+        Err(PipelineError::Cancelled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_preview_metadata() {
+        let json = r#"{
+            "title": " Live set ",
+            "uploader": null,
+            "channel": "VJ Channel",
+            "duration": 10983.0,
+            "thumbnail": "https://i.ytimg.com/vi/abc/maxresdefault.webp"
+        }"#;
+        let (title, uploader, duration_seconds, thumbnail) = parse_metadata(json).unwrap();
+        assert_eq!(title, "Live set");
+        assert_eq!(uploader, "VJ Channel");
+        assert_eq!(
+            thumbnail.as_deref(),
+            Some("https://i.ytimg.com/vi/abc/maxresdefault.webp")
+        );
+
+        let (reply, _answer) = mpsc::channel();
+        let mut preview = MetadataPreview {
+            title,
+            uploader,
+            duration_seconds,
+            thumbnail: None,
+            reply,
+        };
+        assert_eq!(preview.duration_label().as_deref(), Some("3:03:03"));
+        preview.duration_seconds = Some(29.6);
+        assert_eq!(preview.duration_label().as_deref(), Some("0:30"));
+        preview.duration_seconds = None;
+        assert_eq!(preview.duration_label(), None);
+    }
+}
@@ -1,96 +1,125 @@
-use std::io::{BufReader, Read};
+use std::io::{self, BufReader, Read};
 use std::path::Path;
-use std::process::{Child, Command, Stdio};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::process::ExitStatus;
 use std::sync::{Arc, mpsc};
 use std::thread;
 
+use crate::app_logger::{LogLevel, LogSource};
+use crate::diagnostics::record_failed_yt_dlp_command;
+use crate::i18n::{Msg, tr, tr_fmt};
 use crate::paths::bin_dir;
+use crate::settings::EncoderProfile;
 
-use super::{CANCELLED_ERROR, DownloadEvent, ProcessTracker, ProgressContext, ProgressUpdate};
+use super::convert::filter_args;
+use super::encoder::VideoEncoding;
+use super::hwaccel::hwaccel_args;
+use super::runner::{CommandRunner, CommandSpec, RunningCommand, read_stdout};
+use super::{
+    CancellationToken, DownloadEvent, DownloadStage, KnownError, ProgressContext, aria2,
+    conversion_slots, fragments, stage,
+};
 
 // 子プロセスを強制終了して wait まで行い、プロセスを確実に回収する。
-pub(super) fn terminate_child_process(child: &mut Child) {
+pub(super) fn terminate_child_process(child: &mut dyn RunningCommand) {
     let _ = child.kill();
     let _ = child.wait();
 }
 
 // producer -> ffmpeg のパイプラインを組み、MP4 へ変換する。
+// producer の標準出力は、別スレッドで ffmpeg の標準入力へ書き写す。
 fn run_pipe_to_ffmpeg(
-    mut producer: Command,
+    runner: &dyn CommandRunner,
+    producer: &CommandSpec,
     ffmpeg: &Path,
     output_path: &Path,
     tx: &mpsc::Sender<DownloadEvent>,
     progress: &Arc<ProgressContext>,
     input_format: &str,
-    tracker: &ProcessTracker,
+    encoding: &VideoEncoding,
+    cancel: &CancellationToken,
 ) -> Result<(), String> {
-    producer.stdout(Stdio::piped()).stderr(Stdio::piped());
-    let mut producer_child = producer
-        .spawn()
-        .map_err(|err| format!("パイプライン起動に失敗しました: {err}"))?;
-    tracker.register(&producer_child);
-
-    spawn_stream_thread(producer_child.stderr.take(), tx, progress);
-
-    let mut ffmpeg_cmd = Command::new(ffmpeg);
-    ffmpeg_cmd
-        .arg("-loglevel")
-        .arg("error")
-        .arg("-analyzeduration")
-        .arg("100M")
-        .arg("-probesize")
-        .arg("100M")
-        .arg("-f")
-        .arg(input_format)
-        .arg("-i")
-        .arg("pipe:0")
-        .arg("-c:v")
-        .arg("h264_videotoolbox")
-        .arg("-b:v")
-        .arg("5M")
-        .arg("-pix_fmt")
-        .arg("yuv420p")
-        .arg("-c:a")
-        .arg("aac")
-        .arg("-b:a")
-        .arg("192k")
-        .arg("-ignore_unknown")
-        .arg("-movflags")
-        .arg("+faststart")
-        .arg("-f")
-        .arg("mp4")
-        .arg("-y")
-        .arg(output_path.to_string_lossy().to_string())
-        .stdin(
-            producer_child
-                .stdout
-                .take()
-                .ok_or_else(|| "パイプ入力の取得に失敗しました。".to_string())?,
-        )
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped());
+    let _slot = conversion_slots::acquire(encoding, tx, cancel)?;
+    let mut producer_child = runner
+        .spawn(producer, cancel)
+        .map_err(|err| tr_fmt(Msg::PipelineLaunchFailed, &[&err]))?;
+    let _watch = progress.watchdog.watch();
+
+    spawn_stream_thread(producer_child.take_stderr(), LogSource::YtDlp, tx, progress);
+    let Some(mut producer_stdout) = producer_child.take_stdout() else {
+        terminate_child_process(producer_child.as_mut());
+        return Err(tr(Msg::PipeInputFailed).to_string());
+    };
+
+    // パイプ入力ではコーデックが分からないため、デコードできなければ ffmpeg がソフトウェアに戻す。
+    let hwaccel = hwaccel_args(None, encoding.profile != EncoderProfile::Copy);
+    if !hwaccel.is_empty() {
+        let _ = tx.send(DownloadEvent::Log(
+            LogLevel::Info,
+            tr(Msg::HardwareDecode).to_string(),
+        ));
+    }
+    let mut ffmpeg_cmd = CommandSpec::new(ffmpeg)
+        .args([
+            "-loglevel",
+            "error",
+            "-analyzeduration",
+            "100M",
+            "-probesize",
+            "100M",
+        ])
+        .args(hwaccel.iter().copied())
+        .args(["-f", input_format, "-i", "pipe:0"]);
+    let watermark = encoding.stream_watermark();
+    if let Some(watermark) = watermark {
+        let _ = tx.send(DownloadEvent::Log(
+            LogLevel::Info,
+            tr(Msg::WatermarkOverlaid).to_string(),
+        ));
+        ffmpeg_cmd = ffmpeg_cmd.arg("-i").arg(&watermark.path);
+    }
+    let ffmpeg_cmd = ffmpeg_cmd
+        .args(encoding.video_args().iter().copied())
+        .args(filter_args(&[], watermark))
+        .args(["-c:a", "aac", "-b:a", "192k", "-ignore_unknown"])
+        .args(["-movflags", "+faststart", "-f", "mp4", "-y"])
+        .arg(output_path)
+        .stdin_piped();
 
-    let mut ffmpeg_child = ffmpeg_cmd
-        .spawn()
-        .map_err(|err| format!("ffmpeg起動に失敗しました: {err}"))?;
-    tracker.register(&ffmpeg_child);
+    let mut ffmpeg_child = match runner.spawn(&ffmpeg_cmd, cancel) {
+        Ok(child) => child,
+        Err(err) => {
+            terminate_child_process(producer_child.as_mut());
+            return Err(tr_fmt(Msg::ToolLaunchFailed, &[&"ffmpeg", &err]));
+        }
+    };
 
-    spawn_stream_thread(ffmpeg_child.stdout.take(), tx, progress);
-    spawn_stream_thread(ffmpeg_child.stderr.take(), tx, progress);
+    spawn_stream_thread(ffmpeg_child.take_stdout(), LogSource::Ffmpeg, tx, progress);
+    spawn_stream_thread(ffmpeg_child.take_stderr(), LogSource::Ffmpeg, tx, progress);
+    // ffmpeg が先に終わった場合は書き込みに失敗して抜け、producer の出力を閉じて止める。
+    let pipe = ffmpeg_child.take_stdin().map(|mut ffmpeg_stdin| {
+        thread::spawn(move || {
+            let _ = io::copy(&mut producer_stdout, &mut ffmpeg_stdin);
+        })
+    });
 
     let ffmpeg_status = ffmpeg_child
         .wait()
-        .map_err(|err| format!("ffmpegの終了待ちに失敗しました: {err}"))?;
+        .map_err(|err| tr_fmt(Msg::ToolWaitFailed, &[&"ffmpeg", &err]))?;
     let producer_status = producer_child
         .wait()
-        .map_err(|err| format!("パイプライン終了待ちに失敗しました: {err}"))?;
+        .map_err(|err| tr_fmt(Msg::PipelineWaitFailed, &[&err]))?;
+    if let Some(pipe) = pipe {
+        let _ = pipe.join();
+    }
 
     if !ffmpeg_status.success() {
-        return Err(format!("ffmpegが異常終了しました: {ffmpeg_status}"));
+        return Err(tr_fmt(
+            Msg::ToolExitedWithError,
+            &[&"ffmpeg", &ffmpeg_status],
+        ));
     }
     if !producer_status.success() {
-        return Err(format!("パイプラインが異常終了しました: {producer_status}"));
+        return Err(tr_fmt(Msg::PipelineExitedWithError, &[&producer_status]));
     }
 
     Ok(())
@@ -98,78 +127,159 @@ fn run_pipe_to_ffmpeg(
 
 // パイプライン失敗時に、ユーザーキャンセルによる失敗かどうかを判定する。
 pub(super) fn run_pipe_to_ffmpeg_or_cancel(
-    producer: Command,
+    runner: &dyn CommandRunner,
+    producer: &CommandSpec,
     ffmpeg: &Path,
     output_path: &Path,
     tx: &mpsc::Sender<DownloadEvent>,
     progress: &Arc<ProgressContext>,
     input_format: &str,
-    tracker: &ProcessTracker,
-    cancel_flag: &Arc<AtomicBool>,
+    encoding: &VideoEncoding,
+    cancel: &CancellationToken,
 ) -> Result<(), String> {
     match run_pipe_to_ffmpeg(
+        runner,
         producer,
         ffmpeg,
         output_path,
         tx,
         progress,
         input_format,
-        tracker,
+        encoding,
+        cancel,
     ) {
         Ok(()) => Ok(()),
         Err(err) => {
-            if cancel_flag.load(Ordering::Relaxed) {
-                Err(CANCELLED_ERROR.to_string())
-            } else {
-                Err(err)
-            }
+            cancel.check()?;
+            Err(err)
         }
     }
 }
 
+// 同梱の deno などを yt-dlp から使えるよう、bin フォルダを PATH の先頭に加える。
+pub(super) fn with_bin_path(spec: CommandSpec) -> CommandSpec {
+    let mut paths = Vec::new();
+    let bin = bin_dir();
+    if bin.exists() {
+        paths.push(bin.as_os_str().to_owned());
+    }
+    if let Some(current) = std::env::var_os("PATH") {
+        paths.push(current);
+    }
+    match std::env::join_paths(paths) {
+        Ok(joined) => spec.env("PATH", joined),
+        Err(_) => spec,
+    }
+}
+
+// `yt-dlp <args> -J -- <url>` で動画情報の JSON を取得する。ダウンロードは行わない。
+pub(super) fn fetch_yt_dlp_info(
+    runner: &dyn CommandRunner,
+    yt_dlp_path: &Path,
+    args: &[String],
+    url: &str,
+    cancel: &CancellationToken,
+) -> Result<String, String> {
+    let spec = with_bin_path(
+        CommandSpec::new(yt_dlp_path)
+            .args(args.iter().cloned())
+            .arg("-J")
+            .arg("--")
+            .arg(url),
+    );
+    let (status, stdout) = read_stdout(runner, &spec, cancel)
+        .map_err(|err| tr_fmt(Msg::ToolLaunchFailed, &[&"yt-dlp", &err]))?;
+    if !status.success() {
+        return Err(tr_fmt(Msg::VideoInfoFetchFailed, &[&status]));
+    }
+    Ok(String::from_utf8_lossy(&stdout).to_string())
+}
+
 // yt-dlp を起動し、標準出力・標準エラーを並列で読み取って UI に流す。
 pub(super) fn run_yt_dlp(
+    runner: &dyn CommandRunner,
     yt_dlp_path: &Path,
     args: &[String],
     tx: &mpsc::Sender<DownloadEvent>,
     progress: Arc<ProgressContext>,
     add_bin_to_path: bool,
-    tracker: &ProcessTracker,
-) -> Result<std::process::ExitStatus, String> {
-    let mut command = Command::new(yt_dlp_path);
-    command
-        .args(args)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped());
-
+    cancel: &CancellationToken,
+) -> Result<ExitStatus, String> {
+    let mut spec = CommandSpec::new(yt_dlp_path).args(args.iter().cloned());
     if add_bin_to_path {
-        let mut paths = Vec::new();
-        let bin = bin_dir();
-        if bin.exists() {
-            paths.push(bin.as_os_str().to_owned());
-        }
-        if let Some(current) = std::env::var_os("PATH") {
-            paths.push(current);
-        }
-        if let Ok(joined) = std::env::join_paths(paths) {
-            command.env("PATH", joined);
-        }
+        spec = with_bin_path(spec);
     }
 
-    let mut child = command
-        .spawn()
-        .map_err(|err| format!("yt-dlpの起動に失敗しました: {err}"))?;
-    tracker.register(&child);
+    let mut running = runner
+        .spawn(&spec, cancel)
+        .map_err(|err| tr_fmt(Msg::ToolLaunchFailed, &[&"yt-dlp", &err]))?;
+    let _watch = progress.watchdog.watch();
+
+    spawn_stream_thread(running.take_stdout(), LogSource::YtDlp, tx, &progress);
+    spawn_stream_thread(running.take_stderr(), LogSource::YtDlp, tx, &progress);
+
+    let status = running.wait().map_err(|err| err.to_string())?;
+    // キャンセルで止めた（シグナルで終わった）場合は失敗として残さない。
+    if !status.success() && status.code().is_some() {
+        record_failed_yt_dlp_command(yt_dlp_path, args);
+    }
+    Ok(status)
+}
 
-    spawn_stream_thread(child.stdout.take(), tx, &progress);
-    spawn_stream_thread(child.stderr.take(), tx, &progress);
+// yt-dlp でダウンロードし、失敗した場合は H.264 の形式を選び直してもう 1 度だけ試す。
+// フォーマットを選んだ場合は選び直さない。やり直して取得できた場合は Ok(true) を返す。
+pub(super) fn run_yt_dlp_with_fallback(
+    runner: &dyn CommandRunner,
+    yt_dlp_path: &Path,
+    args: &[String],
+    fallback_args: Option<&[String]>,
+    tx: &mpsc::Sender<DownloadEvent>,
+    progress: &Arc<ProgressContext>,
+    cancel: &CancellationToken,
+) -> Result<bool, String> {
+    let status = run_yt_dlp(
+        runner,
+        yt_dlp_path,
+        args,
+        tx,
+        progress.clone(),
+        true,
+        cancel,
+    )
+    .map_err(|err| tr_fmt(Msg::YtDlpRunFailed, &[&err]))?;
+    if status.success() {
+        return Ok(false);
+    }
+    let Some(fallback_args) = fallback_args else {
+        return Err(format!("yt-dlp exited with status: {status}"));
+    };
 
-    child.wait().map_err(|err| err.to_string())
+    let _ = tx.send(DownloadEvent::Log(
+        LogLevel::Warn,
+        tr(Msg::H264FallbackRetry).to_string(),
+    ));
+    cancel.check()?;
+    let status = run_yt_dlp(
+        runner,
+        yt_dlp_path,
+        fallback_args,
+        tx,
+        progress.clone(),
+        true,
+        cancel,
+    );
+    cancel.check()?;
+    match status {
+        Ok(code) if code.success() => Ok(true),
+        Ok(code) => Err(format!("yt-dlp exited with status: {code}")),
+        Err(err) => Err(tr_fmt(Msg::YtDlpRunFailed, &[&err])),
+    }
 }
 
 // 子プロセスのストリームを 1 行ずつ分解してログ・進捗イベントに変換する。
 fn stream_lines<R: Read + Send + 'static>(
     reader: R,
+    source: LogSource,
     tx: mpsc::Sender<DownloadEvent>,
     progress: Arc<ProgressContext>,
 ) {
@@ -182,14 +292,15 @@ fn stream_lines<R: Read + Send + 'static>(
             Ok(n) => n,
             Err(_) => break,
         };
+        progress.watchdog.touch();
         for &byte in &buf[..read] {
             if byte == b'\n' || byte == b'\r' {
                 if !line.is_empty() {
                     if let Ok(text) = String::from_utf8(line.clone()) {
-                        handle_stream_line(text, &tx, &progress);
+                        handle_stream_line(text, source, &tx, &progress);
                     } else {
                         let text = String::from_utf8_lossy(&line).to_string();
-                        handle_stream_line(text, &tx, &progress);
+                        handle_stream_line(text, source, &tx, &progress);
                     }
                     line.clear();
                 }
@@ -200,26 +311,28 @@ fn stream_lines<R: Read + Send + 'static>(
     }
     if !line.is_empty() {
         let text = String::from_utf8_lossy(&line).to_string();
-        handle_stream_line(text, &tx, &progress);
+        handle_stream_line(text, source, &tx, &progress);
     }
 }
 
 // Optional Reader を安全に監視スレッドへ渡すためのヘルパー。
 pub(super) fn spawn_stream_thread<R: Read + Send + 'static>(
     reader: Option<R>,
+    source: LogSource,
     tx: &mpsc::Sender<DownloadEvent>,
     progress: &Arc<ProgressContext>,
 ) {
     if let Some(reader) = reader {
         let tx_clone = tx.clone();
         let progress_clone = progress.clone();
-        thread::spawn(move || stream_lines(reader, tx_clone, progress_clone));
+        thread::spawn(move || stream_lines(reader, source, tx_clone, progress_clone));
     }
 }
 
 // 1 行ログを進捗解析し、その後 UI ログへ送る。
 fn handle_stream_line(
     line: String,
+    source: LogSource,
     tx: &mpsc::Sender<DownloadEvent>,
     progress: &Arc<ProgressContext>,
 ) {
@@ -229,8 +342,19 @@ fn handle_stream_line(
     }
 
     handle_progress_line(trimmed, progress, tx);
+    if source == LogSource::YtDlp
+        && let Some(speed) = fragments::parse_speed(trimmed).or_else(|| aria2::parse_speed(trimmed))
+    {
+        fragments::record_speed(speed);
+    }
 
-    let _ = tx.send(DownloadEvent::Log(trimmed.to_string()));
+    if source == LogSource::YtDlp
+        && let Some(kind) = KnownError::detect(trimmed)
+    {
+        let _ = tx.send(DownloadEvent::KnownError(kind));
+    }
+
+    let _ = tx.send(DownloadEvent::ToolLog(source, trimmed.to_string()));
 }
 
 // yt-dlp/ffmpeg ログから進捗パーセンテージや変換フェーズ遷移を検出する。
@@ -246,43 +370,37 @@ fn handle_progress_line(
     if is_post_processing_line(line) {
         progress.mark_progress_started();
         progress.set_post_processing();
-        let update = ProgressUpdate::post_processing(&progress.elapsed());
-        let _ = tx.send(DownloadEvent::Progress(update));
+        let stage = DownloadStage::Converting {
+            pct: None,
+            pass: None,
+        };
+        let _ = tx.send(DownloadEvent::Stage(stage, progress.elapsed()));
         return;
     }
 
-    if let Some(percent) = extract_percent(line) {
+    if let Some((bytes, total)) = parse_fetch_progress(line).or_else(|| aria2::parse_progress(line))
+    {
         progress.mark_progress_started();
-        let update = ProgressUpdate::downloading(percent, &progress.elapsed());
-        let _ = tx.send(DownloadEvent::Progress(update));
+        let stage = DownloadStage::Fetching {
+            bytes,
+            total: Some(total),
+        };
+        let _ = tx.send(DownloadEvent::Stage(stage, progress.elapsed()));
     }
 }
 
-// 1 行文字列内の "xx.x%" 形式を抽出する。
-fn extract_percent(line: &str) -> Option<f32> {
-    let chars = line.chars().collect::<Vec<_>>();
-    let mut idx = 0usize;
-    while idx < chars.len() {
-        if chars[idx] == '%' {
-            let mut start = idx;
-            while start > 0 {
-                let c = chars[start - 1];
-                if c.is_ascii_digit() || c == '.' {
-                    start -= 1;
-                } else {
-                    break;
-                }
-            }
-            if start < idx {
-                let candidate: String = chars[start..idx].iter().collect();
-                if let Ok(value) = candidate.parse::<f32>() {
-                    return Some(value);
-                }
-            }
-        }
-        idx += 1;
+// yt-dlp の `[download]  12.3% of ~ 80.00MiB at ...` から取得済みと合計のバイト数を推定する。
+fn parse_fetch_progress(line: &str) -> Option<(u64, u64)> {
+    let mut parts = line.strip_prefix("[download]")?.split_whitespace();
+    let percent = parts.next()?.strip_suffix('%')?.parse::<f64>().ok()?;
+    if parts.next()? != "of" {
+        return None;
     }
-    None
+    let total = parts
+        .find(|part| *part != "~")
+        .and_then(stage::parse_size)?;
+    let bytes = (total as f64 * percent.clamp(0.0, 100.0) / 100.0) as u64;
+    Some((bytes, total))
 }
 
 // ダウンロード完了後の後処理フェーズを示す行かどうかを判定する。
@@ -300,3 +418,194 @@ fn is_post_processing_line(line: &str) -> bool {
         || lower.contains("merging formats into")
         || lower.contains("post-process")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::download::runner::fake::{FakeRun, FakeRunner};
+    use std::sync::atomic::AtomicBool;
+    use std::time::Duration;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    fn progress() -> Arc<ProgressContext> {
+        ProgressContext::new(Arc::new(AtomicBool::new(false)))
+    }
+
+    #[test]
+    fn retries_with_fallback_args_after_failure() {
+        let runner = FakeRunner::new(vec![FakeRun::exit(1), FakeRun::exit(0)]);
+        let (tx, rx) = mpsc::channel();
+        let result = run_yt_dlp_with_fallback(
+            &runner,
+            Path::new("yt-dlp"),
+            &args(&["-f", "bv*+ba", "https://example.com/v"]),
+            Some(&args(&["-f", "avc1", "https://example.com/v"])),
+            &tx,
+            &progress(),
+            &CancellationToken::new(),
+        );
+
+        assert_eq!(result, Ok(true));
+        let calls = runner.calls();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[1].args, ["-f", "avc1", "https://example.com/v"]);
+        assert!(rx.try_iter().any(|event| matches!(
+            event,
+            DownloadEvent::Log(_, text) if text == tr(Msg::H264FallbackRetry)
+        )));
+    }
+
+    #[test]
+    fn does_not_retry_chosen_format() {
+        let runner = FakeRunner::new(vec![FakeRun::exit(1)]);
+        let (tx, _rx) = mpsc::channel();
+        let result = run_yt_dlp_with_fallback(
+            &runner,
+            Path::new("yt-dlp"),
+            &args(&["-f", "137+140", "https://example.com/v"]),
+            None,
+            &tx,
+            &progress(),
+            &CancellationToken::new(),
+        );
+
+        assert!(result.unwrap_err().starts_with("yt-dlp exited with status"));
+        assert_eq!(runner.calls().len(), 1);
+    }
+
+    #[test]
+    fn stops_without_retry_when_cancelled() {
+        let runner = FakeRunner::new(vec![FakeRun::UntilCancelled]);
+        let (tx, _rx) = mpsc::channel();
+        let cancel = CancellationToken::new();
+        let canceller = cancel.clone();
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            canceller.cancel();
+        });
+        let result = run_yt_dlp_with_fallback(
+            &runner,
+            Path::new("yt-dlp"),
+            &args(&["https://example.com/v"]),
+            Some(&args(&["-f", "avc1", "https://example.com/v"])),
+            &tx,
+            &progress(),
+            &cancel,
+        );
+        handle.join().unwrap();
+
+        assert_eq!(result, Err(tr(Msg::DownloadCancelled).to_string()));
+        assert_eq!(runner.calls().len(), 1);
+    }
+
+    #[test]
+    fn reports_missing_yt_dlp() {
+        let runner = FakeRunner::new(vec![FakeRun::SpawnError(std::io::ErrorKind::NotFound)]);
+        let (tx, _rx) = mpsc::channel();
+        let result = run_yt_dlp_with_fallback(
+            &runner,
+            Path::new("/missing/yt-dlp"),
+            &args(&["https://example.com/v"]),
+            Some(&args(&["https://example.com/v"])),
+            &tx,
+            &progress(),
+            &CancellationToken::new(),
+        );
+
+        let err = result.unwrap_err();
+        assert_ne!(err, tr(Msg::DownloadCancelled));
+        assert!(err.contains("yt-dlpの起動に失敗しました"));
+        assert_eq!(runner.calls().len(), 1);
+    }
+
+    #[test]
+    fn streams_known_errors_from_stderr() {
+        let runner = FakeRunner::new(vec![FakeRun::Exit {
+            code: 1,
+            stdout: "[youtube] abc: Downloading webpage\n",
+            stderr: "ERROR: [youtube] abc: Sign in to confirm you\u{2019}re not a bot.\n",
+        }]);
+        let (tx, rx) = mpsc::channel();
+        let status = run_yt_dlp(
+            &runner,
+            Path::new("yt-dlp"),
+            &args(&["https://example.com/v"]),
+            &tx,
+            progress(),
+            false,
+            &CancellationToken::new(),
+        )
+        .expect("run fake yt-dlp");
+        drop(tx);
+
+        assert!(!status.success());
+        let events = rx.iter().collect::<Vec<_>>();
+        assert!(
+            events
+                .iter()
+                .any(|event| matches!(event, DownloadEvent::KnownError(KnownError::BotCheck)))
+        );
+        assert!(events.iter().any(|event| matches!(
+            event,
+            DownloadEvent::ToolLog(LogSource::YtDlp, line) if line.contains("Downloading webpage")
+        )));
+    }
+
+    #[test]
+    fn fetches_info_json_from_stdout() {
+        let runner = FakeRunner::new(vec![
+            FakeRun::Exit {
+                code: 0,
+                stdout: "{\"id\": \"abc\"}",
+                stderr: "",
+            },
+            FakeRun::exit(1),
+        ]);
+        let cancel = CancellationToken::new();
+        let info = fetch_yt_dlp_info(
+            &runner,
+            Path::new("yt-dlp"),
+            &args(&["--no-playlist"]),
+            "https://example.com/v",
+            &cancel,
+        );
+        assert_eq!(info, Ok("{\"id\": \"abc\"}".to_string()));
+        assert_eq!(
+            runner.calls()[0].args,
+            ["--no-playlist", "-J", "--", "https://example.com/v"]
+        );
+
+        assert!(
+            fetch_yt_dlp_info(
+                &runner,
+                Path::new("yt-dlp"),
+                &[],
+                "https://example.com/v",
+                &cancel
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn parses_yt_dlp_fetch_progress() {
+        assert_eq!(
+            parse_fetch_progress(
+                "[download]  25.0% of ~  80.00MiB at 3.21MiB/s ETA 00:20 (frag 4/40)"
+            ),
+            Some((20 * 1024 * 1024, 80 * 1024 * 1024))
+        );
+        assert_eq!(
+            parse_fetch_progress("[download] 100% of 512.00KiB in 00:00:01 at 400.00KiB/s"),
+            Some((512 * 1024, 512 * 1024))
+        );
+        assert_eq!(
+            parse_fetch_progress("[download] Destination: /tmp/clip.f137.mp4"),
+            None
+        );
+        assert_eq!(parse_fetch_progress("[Merger] 50% done"), None);
+    }
+}
@@ -0,0 +1,176 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+use crate::bundled::ensure_bundled_tools;
+use crate::fs_utils::ensure_dir;
+use crate::i18n::{Msg, tr, tr_fmt};
+use crate::paths::ffmpeg_path;
+
+use super::CancellationToken;
+use super::runner::{CommandRunner, CommandSpec, SystemRunner};
+use super::staging::{StagingDir, create_download_staging_dir, next_available_destination};
+
+// プロキシの高さ（px）。元の動画がこれより低い場合は拡大しない。
+const PROXY_HEIGHT: u32 = 480;
+
+// プロキシ作成の進み具合。画面側でインデックスへの紐付けと表示を行う。
+pub enum ProxyEvent {
+    // index 番目（0 始まり）の作成を始めた
+    Started { index: usize, total: usize },
+    Created { original: PathBuf, proxy: PathBuf },
+    Failed { original: PathBuf, error: String },
+    // すべて終えた（取り消した場合も届く）
+    Finished { created: usize, cancelled: bool },
+}
+
+// プロキシのファイル名。元のファイル名の後ろに高さを付ける（`clip 480p.mp4`）。
+pub(super) fn proxy_file_name(original: &Path) -> String {
+    let stem = original
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .filter(|stem| !stem.trim().is_empty())
+        .unwrap_or_else(|| "video".to_string());
+    format!("{stem} {PROXY_HEIGHT}p.mp4")
+}
+
+// 高さを PROXY_HEIGHT までに縮め、幅は縦横比を保った偶数にする。
+pub(super) fn proxy_scale_filter() -> String {
+    format!("scale=-2:'min({PROXY_HEIGHT},ih)'")
+}
+
+fn write_proxy(
+    runner: &dyn CommandRunner,
+    ffmpeg: &Path,
+    input: &Path,
+    output: &Path,
+    cancel: &CancellationToken,
+) -> Result<(), String> {
+    let cmd = CommandSpec::new(ffmpeg)
+        .args(["-hide_banner", "-y", "-i"])
+        .arg(input)
+        .args(["-map", "0:v:0", "-map", "0:a:0?", "-vf"])
+        .arg(proxy_scale_filter())
+        .args(["-c:v", "libx264", "-preset", "veryfast", "-crf", "28"])
+        .args(["-pix_fmt", "yuv420p"])
+        .args(["-c:a", "aac", "-b:a", "128k"])
+        .args(["-movflags", "+faststart"])
+        .arg(output);
+    let output_status = super::runner::output(runner, &cmd, cancel)
+        .map_err(|err| tr_fmt(Msg::ToolLaunchFailed, &[&"ffmpeg", &err]))?;
+    cancel.check()?;
+    if !output_status.status.success() {
+        let stderr = String::from_utf8_lossy(&output_status.stderr);
+        let last = stderr.lines().last().unwrap_or_default().trim();
+        return Err(tr_fmt(Msg::ProxyWriteFailed, &[&last]));
+    }
+    Ok(())
+}
+
+// 1 件分のプロキシを一時フォルダへ書き出してから、プロキシのフォルダへ移す。
+fn create_proxy(
+    runner: &dyn CommandRunner,
+    original: &Path,
+    proxy_dir: &Path,
+    ffmpeg: &Path,
+    cancel: &CancellationToken,
+) -> Result<PathBuf, String> {
+    if !original.is_file() {
+        return Err(tr_fmt(
+            Msg::OriginalVideoMissing,
+            &[&original.to_string_lossy()],
+        ));
+    }
+    let file_name = proxy_file_name(original);
+    // 書き出し途中のファイルが検索結果に出ないよう、一時フォルダへ書いてから移す。
+    let staging = StagingDir::new(create_download_staging_dir(proxy_dir)?);
+    let staged = staging.path().join(&file_name);
+    write_proxy(runner, ffmpeg, original, &staged, cancel)?;
+
+    let mut destination = proxy_dir.join(&file_name);
+    if destination.exists() {
+        destination = next_available_destination(&destination)?;
+    }
+    fs::rename(&staged, &destination).map_err(|err| tr_fmt(Msg::ProxyMoveFailed, &[&err]))?;
+    let _ = staging.remove();
+    Ok(destination)
+}
+
+// 選んだ動画から順に低解像度のプロキシを作る。1 件失敗しても残りは続け、取り消した場合だけ止める。
+pub fn generate_proxies(
+    originals: Vec<PathBuf>,
+    proxy_dir: PathBuf,
+    tx: mpsc::Sender<ProxyEvent>,
+    cancel: CancellationToken,
+) {
+    let total = originals.len();
+    let mut created = 0;
+    let prepared = ensure_bundled_tools()
+        .and_then(|()| {
+            ensure_dir(&proxy_dir).map_err(|err| tr_fmt(Msg::CreateFolderFailed, &[&err]))
+        })
+        .and_then(|()| {
+            let ffmpeg = ffmpeg_path();
+            if ffmpeg.exists() {
+                Ok(ffmpeg)
+            } else {
+                Err(tr(Msg::FfmpegNotFound).to_string())
+            }
+        });
+    let ffmpeg = match prepared {
+        Ok(ffmpeg) => ffmpeg,
+        Err(error) => {
+            for original in originals {
+                let _ = tx.send(ProxyEvent::Failed {
+                    original,
+                    error: error.clone(),
+                });
+            }
+            let _ = tx.send(ProxyEvent::Finished {
+                created,
+                cancelled: false,
+            });
+            return;
+        }
+    };
+
+    for (index, original) in originals.into_iter().enumerate() {
+        if cancel.is_cancelled() {
+            break;
+        }
+        let _ = tx.send(ProxyEvent::Started { index, total });
+        match create_proxy(&SystemRunner, &original, &proxy_dir, &ffmpeg, &cancel) {
+            Ok(proxy) => {
+                created += 1;
+                let _ = tx.send(ProxyEvent::Created { original, proxy });
+            }
+            Err(_) if cancel.is_cancelled() => break,
+            Err(error) => {
+                let _ = tx.send(ProxyEvent::Failed { original, error });
+            }
+        }
+    }
+    let _ = tx.send(ProxyEvent::Finished {
+        created,
+        cancelled: cancel.is_cancelled(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_proxy_after_original() {
+        assert_eq!(
+            proxy_file_name(Path::new("/Volumes/SSD/loops/neon tunnel.mov")),
+            "neon tunnel 480p.mp4"
+        );
+        assert_eq!(proxy_file_name(Path::new("/")), "video 480p.mp4");
+    }
+
+    #[test]
+    fn never_upscales_small_sources() {
+        assert_eq!(proxy_scale_filter(), "scale=-2:'min(480,ih)'");
+    }
+}
@@ -0,0 +1,184 @@
+use super::DownloadJob;
+
+// 待機中のダウンロード。優先の項目を先に、同じ優先度の中では並べた順に実行する。
+#[derive(Debug, Default)]
+pub struct JobQueue {
+    jobs: Vec<DownloadJob>,
+}
+
+impl JobQueue {
+    pub fn len(&self) -> usize {
+        self.jobs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&DownloadJob> {
+        self.jobs.get(index)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &DownloadJob> {
+        self.jobs.iter()
+    }
+
+    // 同じ優先度の最後に追加する。
+    pub fn push_back(&mut self, job: DownloadJob) {
+        self.jobs.push(job);
+        self.sort_by_priority();
+    }
+
+    // 中断した項目を戻す。同じ優先度の先頭に入れる。
+    pub fn push_front(&mut self, job: DownloadJob) {
+        self.jobs.insert(0, job);
+        self.sort_by_priority();
+    }
+
+    pub fn pop_next(&mut self) -> Option<DownloadJob> {
+        if self.jobs.is_empty() {
+            None
+        } else {
+            Some(self.jobs.remove(0))
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.jobs.clear();
+    }
+
+    // ドラッグで並べ替える。優先度をまたぐ移動は、同じ優先度の端に収める。
+    pub fn move_job(&mut self, from: usize, to: usize) {
+        if from >= self.jobs.len() || from == to {
+            return;
+        }
+        let job = self.jobs.remove(from);
+        let to = to.min(self.jobs.len());
+        self.jobs.insert(to, job);
+        self.sort_by_priority();
+    }
+
+    // 優先にした項目は、すでに優先の項目の後ろに並ぶ。
+    pub fn set_priority(&mut self, index: usize, priority: bool) {
+        let Some(job) = self.jobs.get_mut(index) else {
+            return;
+        };
+        job.priority = priority;
+        self.sort_by_priority();
+    }
+
+    // 安定ソートなので、同じ優先度の中の順番は変わらない。
+    fn sort_by_priority(&mut self) {
+        self.jobs.sort_by_key(|job| !job.priority);
+    }
+}
+
+// 失敗一覧に残しておく上限。超えた分は古いものから消す。
+const MAX_FAILED_JOBS: usize = 20;
+
+// 失敗したダウンロードと、そのエラー
+#[derive(Clone, Debug)]
+pub struct FailedJob {
+    pub job: DownloadJob,
+    pub error: String,
+}
+
+// 失敗したダウンロードの一覧。新しいものを先頭に並べ、同じ指定でやり直せるようにする。
+#[derive(Debug, Default)]
+pub struct FailedJobs {
+    jobs: Vec<FailedJob>,
+}
+
+impl FailedJobs {
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &FailedJob> {
+        self.jobs.iter()
+    }
+
+    // 同じ URL の古い失敗は、新しい失敗で置き換える。
+    pub fn push(&mut self, job: DownloadJob, error: String) {
+        self.jobs.retain(|failed| failed.job.url != job.url);
+        self.jobs.insert(0, FailedJob { job, error });
+        self.jobs.truncate(MAX_FAILED_JOBS);
+    }
+
+    pub fn take(&mut self, index: usize) -> Option<FailedJob> {
+        (index < self.jobs.len()).then(|| self.jobs.remove(index))
+    }
+
+    pub fn clear(&mut self) {
+        self.jobs.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::download::QualityMode;
+
+    fn job(url: &str) -> DownloadJob {
+        DownloadJob {
+            url: url.to_string(),
+            quality: QualityMode::Standard,
+            output_dir: None,
+            cookie_account: String::new(),
+            choose_format: false,
+            resume_staging_dir: None,
+            priority: false,
+            preset: None,
+            source_file: None,
+            split_timestamps: None,
+            stall_retries: 0,
+        }
+    }
+
+    fn urls(queue: &JobQueue) -> Vec<&str> {
+        queue.iter().map(|job| job.url.as_str()).collect()
+    }
+
+    #[test]
+    fn honors_priority_then_order() {
+        let mut queue = JobQueue::default();
+        for url in ["a", "b", "c", "d"] {
+            queue.push_back(job(url));
+        }
+        queue.set_priority(2, true);
+        assert_eq!(urls(&queue), ["c", "a", "b", "d"]);
+        queue.set_priority(3, true);
+        assert_eq!(urls(&queue), ["c", "d", "a", "b"]);
+
+        // 優先でない項目は、優先の項目より前へは移せない。
+        queue.move_job(3, 0);
+        assert_eq!(urls(&queue), ["c", "d", "b", "a"]);
+        queue.move_job(1, 0);
+        assert_eq!(urls(&queue), ["d", "c", "b", "a"]);
+
+        queue.push_back(job("e"));
+        queue.push_front(job("f"));
+        assert_eq!(urls(&queue), ["d", "c", "f", "b", "a", "e"]);
+        assert_eq!(queue.pop_next().map(|job| job.url), Some("d".to_string()));
+        assert_eq!(queue.len(), 5);
+    }
+
+    #[test]
+    fn keeps_latest_failure_per_url() {
+        let mut failed = FailedJobs::default();
+        failed.push(job("a"), "first".to_string());
+        failed.push(job("b"), "second".to_string());
+        failed.push(job("a"), "third".to_string());
+        let errors: Vec<&str> = failed.iter().map(|f| f.error.as_str()).collect();
+        assert_eq!(errors, ["third", "second"]);
+
+        for index in 0..MAX_FAILED_JOBS + 5 {
+            failed.push(job(&index.to_string()), String::new());
+        }
+        assert_eq!(failed.iter().count(), MAX_FAILED_JOBS);
+
+        let retried = failed.take(0).unwrap();
+        assert_eq!(retried.job.url, (MAX_FAILED_JOBS + 4).to_string());
+        assert!(failed.take(MAX_FAILED_JOBS).is_none());
+    }
+}
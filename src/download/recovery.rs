@@ -0,0 +1,106 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::{Msg, tr};
+use crate::paths::active_job_file_path;
+
+use super::QualityMode;
+use super::staging::is_staging_dir;
+
+// 実行中のダウンロードがどこまで進んだか
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStage {
+    Preparing,
+    Downloading,
+    Converting,
+    Finishing,
+}
+
+impl JobStage {
+    pub fn label(self) -> &'static str {
+        match self {
+            JobStage::Preparing => tr(Msg::StagePreparing),
+            JobStage::Downloading => tr(Msg::StageDownloading),
+            JobStage::Converting => tr(Msg::StageConverting),
+            JobStage::Finishing => tr(Msg::StageFinishing),
+        }
+    }
+}
+
+// 実行中のダウンロードの記録。異常終了した場合は次回の起動時に残っている。
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct JobRecord {
+    pub url: String,
+    pub quality: QualityMode,
+    pub output_dir: PathBuf,
+    // 作業フォルダを作る前は None
+    pub staging_dir: Option<PathBuf>,
+    pub stage: JobStage,
+}
+
+impl JobRecord {
+    // 途中の状態を書き込む。書き込めなくてもダウンロードは続ける。
+    pub(super) fn save(&self) {
+        let path = active_job_file_path();
+        let result = serde_json::to_string_pretty(self)
+            .map_err(|err| err.to_string())
+            .and_then(|json| fs::write(&path, json).map_err(|err| err.to_string()));
+        if let Err(err) = result {
+            eprintln!("[download] failed to save {}: {err}", path.display());
+        }
+    }
+
+    pub(super) fn advance(&mut self, stage: JobStage) {
+        self.stage = stage;
+        self.save();
+    }
+
+    // 作業フォルダが残っていれば、yt-dlp の途中ファイルから続きを取得できる。
+    pub fn can_resume(&self) -> bool {
+        self.staging_dir.as_ref().is_some_and(|dir| dir.is_dir())
+    }
+
+    // 再開しない場合は作業フォルダを削除する。記録が壊れていても作業フォルダ以外は消さない。
+    pub fn discard_staging_dir(&self) {
+        if let Some(dir) = self.staging_dir.as_ref()
+            && is_staging_dir(dir)
+        {
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+}
+
+// 前回の起動で終わらなかったダウンロードの記録を読み込む。
+pub fn load_interrupted_job() -> Option<JobRecord> {
+    let contents = fs::read_to_string(active_job_file_path()).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+pub fn clear_job_record() {
+    let _ = fs::remove_file(active_job_file_path());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_job_record() {
+        let record = JobRecord {
+            url: "https://www.youtube.com/watch?v=abc".to_string(),
+            quality: QualityMode::TargetSize(50),
+            output_dir: PathBuf::from("/Volumes/SSD/VJDL"),
+            staging_dir: Some(PathBuf::from(
+                "/Volumes/SSD/VJDL/.vjdownloader-staging/job-1-2-0",
+            )),
+            stage: JobStage::Converting,
+        };
+        let json = serde_json::to_string(&record).unwrap();
+        assert!(json.contains(r#""stage":"converting""#));
+        assert_eq!(serde_json::from_str::<JobRecord>(&json).unwrap(), record);
+        assert!(!record.can_resume());
+    }
+}
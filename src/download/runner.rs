@@ -0,0 +1,399 @@
+use std::ffi::OsString;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Output, Stdio};
+use std::thread;
+
+use super::CancellationToken;
+use super::cancel::TrackedChild;
+
+// 起動する外部コマンド。Command と違って中身を比べられるため、テストで何を起動したかを確かめられる。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) struct CommandSpec {
+    pub program: PathBuf,
+    pub args: Vec<OsString>,
+    // 上書きする環境変数（PATH など）
+    pub envs: Vec<(String, OsString)>,
+    // 標準入力をパイプにするか。false の場合は閉じて起動する。
+    pub pipe_stdin: bool,
+}
+
+impl CommandSpec {
+    pub(super) fn new(program: &Path) -> Self {
+        Self {
+            program: program.to_path_buf(),
+            args: Vec::new(),
+            envs: Vec::new(),
+            pipe_stdin: false,
+        }
+    }
+
+    pub(super) fn arg(mut self, arg: impl Into<OsString>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    pub(super) fn args<S: Into<OsString>>(mut self, args: impl IntoIterator<Item = S>) -> Self {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    // ffmpeg へ別のコマンドの出力を流し込む場合など、標準入力へ書き込めるようにする。
+    pub(super) fn stdin_piped(mut self) -> Self {
+        self.pipe_stdin = true;
+        self
+    }
+
+    pub(super) fn env(mut self, key: &str, value: OsString) -> Self {
+        self.envs.push((key.to_string(), value));
+        self
+    }
+
+    fn to_command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        command.args(&self.args);
+        for (key, value) in &self.envs {
+            command.env(key, value);
+        }
+        command
+    }
+}
+
+// 起動済みの外部コマンド。標準入力・標準出力・標準エラーはそれぞれ 1 度だけ取り出せる。
+pub(super) trait RunningCommand: Send {
+    // 標準入力をパイプにして起動した場合だけ返す。手放すと入力の終わりを伝える。
+    fn take_stdin(&mut self) -> Option<Box<dyn Write + Send>>;
+    fn take_stdout(&mut self) -> Option<Box<dyn Read + Send>>;
+    fn take_stderr(&mut self) -> Option<Box<dyn Read + Send>>;
+    // 強制終了する。終了の回収は wait で行う。
+    fn kill(&mut self) -> io::Result<()>;
+    fn wait(&mut self) -> io::Result<ExitStatus>;
+}
+
+// 外部コマンドの起動役。本番では SystemRunner で実際に起動し、テストでは決まった出力を返す偽物に差し替える。
+// yt-dlp・ffmpeg・ffprobe・直リンクを受信する curl は、すべてこれを通して起動する。
+pub(super) trait CommandRunner: Send + Sync {
+    // 標準出力・標準エラーをパイプにして起動する。標準入力は spec.pipe_stdin の場合だけパイプにし、それ以外は閉じる。
+    // 取り消した時点で止まるよう cancel に登録する。
+    fn spawn(
+        &self,
+        spec: &CommandSpec,
+        cancel: &CancellationToken,
+    ) -> io::Result<Box<dyn RunningCommand>>;
+}
+
+// 実際に子プロセスを起動する。
+pub(super) struct SystemRunner;
+
+impl CommandRunner for SystemRunner {
+    fn spawn(
+        &self,
+        spec: &CommandSpec,
+        cancel: &CancellationToken,
+    ) -> io::Result<Box<dyn RunningCommand>> {
+        let mut command = spec.to_command();
+        command
+            .stdin(if spec.pipe_stdin {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let child = cancel.spawn(&mut command)?;
+        Ok(Box::new(ChildProcess(child)))
+    }
+}
+
+struct ChildProcess(TrackedChild);
+
+impl RunningCommand for ChildProcess {
+    fn take_stdin(&mut self) -> Option<Box<dyn Write + Send>> {
+        self.0
+            .stdin
+            .take()
+            .map(|stdin| Box::new(stdin) as Box<dyn Write + Send>)
+    }
+
+    fn take_stdout(&mut self) -> Option<Box<dyn Read + Send>> {
+        self.0
+            .stdout
+            .take()
+            .map(|out| Box::new(out) as Box<dyn Read + Send>)
+    }
+
+    fn take_stderr(&mut self) -> Option<Box<dyn Read + Send>> {
+        self.0
+            .stderr
+            .take()
+            .map(|err| Box::new(err) as Box<dyn Read + Send>)
+    }
+
+    fn kill(&mut self) -> io::Result<()> {
+        self.0.kill()
+    }
+
+    fn wait(&mut self) -> io::Result<ExitStatus> {
+        self.0.wait()
+    }
+}
+
+// 起動して標準出力をすべて読み、終了を待つ。標準エラーは読み捨てる（パイプが詰まって止まらないよう別スレッドで読む）。
+pub(super) fn read_stdout(
+    runner: &dyn CommandRunner,
+    spec: &CommandSpec,
+    cancel: &CancellationToken,
+) -> io::Result<(ExitStatus, Vec<u8>)> {
+    read_output(runner, spec, cancel, false).map(|(status, stdout, _)| (status, stdout))
+}
+
+// `Command::output` の代わりに使う。標準出力・標準エラーをすべて読み、終了を待つ。
+// 取り消して止めた場合は失敗の終了状態が返るため、呼び出し側は cancel.check で中断を確かめる。
+pub(super) fn output(
+    runner: &dyn CommandRunner,
+    spec: &CommandSpec,
+    cancel: &CancellationToken,
+) -> io::Result<Output> {
+    read_output(runner, spec, cancel, true).map(|(status, stdout, stderr)| Output {
+        status,
+        stdout,
+        stderr,
+    })
+}
+
+// 起動して標準出力・標準エラーをすべて読み、終了を待つ。keep_stderr が false の場合、標準エラーは空で返す。
+pub(super) fn read_output(
+    runner: &dyn CommandRunner,
+    spec: &CommandSpec,
+    cancel: &CancellationToken,
+    keep_stderr: bool,
+) -> io::Result<(ExitStatus, Vec<u8>, Vec<u8>)> {
+    let mut running = runner.spawn(spec, cancel)?;
+    let drain = running.take_stderr().map(|mut stderr| {
+        thread::spawn(move || {
+            let mut kept = Vec::new();
+            if keep_stderr {
+                let _ = stderr.read_to_end(&mut kept);
+            } else {
+                let _ = io::copy(&mut stderr, &mut io::sink());
+            }
+            kept
+        })
+    });
+    let mut stdout = Vec::new();
+    if let Some(mut out) = running.take_stdout() {
+        out.read_to_end(&mut stdout)?;
+    }
+    let status = running.wait()?;
+    let stderr = drain
+        .and_then(|drain| drain.join().ok())
+        .unwrap_or_default();
+    Ok((status, stdout, stderr))
+}
+
+// テスト用の偽の起動役。起動されるたびに、用意した結果を先頭から 1 つずつ返す。
+#[cfg(test)]
+pub(super) mod fake {
+    use std::collections::VecDeque;
+    use std::io::{self, Cursor, Read, Write};
+    use std::process::ExitStatus;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use super::{CommandRunner, CommandSpec, RunningCommand};
+    use crate::download::CancellationToken;
+
+    // 1 回の起動で起きること
+    pub(in crate::download) enum FakeRun {
+        // 出力を返して code で終わる
+        Exit {
+            code: i32,
+            stdout: &'static str,
+            stderr: &'static str,
+        },
+        // 起動に失敗する（コマンドが見つからない場合など）
+        SpawnError(io::ErrorKind),
+        // 取り消されるか強制終了されるまで終わらず、その後はシグナルで止まった扱いにする
+        UntilCancelled,
+    }
+
+    impl FakeRun {
+        pub(in crate::download) fn exit(code: i32) -> Self {
+            FakeRun::Exit {
+                code,
+                stdout: "",
+                stderr: "",
+            }
+        }
+    }
+
+    #[derive(Default)]
+    pub(in crate::download) struct FakeRunner {
+        script: Mutex<VecDeque<FakeRun>>,
+        calls: Mutex<Vec<CommandSpec>>,
+    }
+
+    impl FakeRunner {
+        pub(in crate::download) fn new(script: Vec<FakeRun>) -> Self {
+            Self {
+                script: Mutex::new(script.into()),
+                calls: Mutex::new(Vec::new()),
+            }
+        }
+
+        // 起動されたコマンド（古い順）
+        pub(in crate::download) fn calls(&self) -> Vec<CommandSpec> {
+            self.calls.lock().unwrap().clone()
+        }
+    }
+
+    impl CommandRunner for FakeRunner {
+        fn spawn(
+            &self,
+            spec: &CommandSpec,
+            cancel: &CancellationToken,
+        ) -> io::Result<Box<dyn RunningCommand>> {
+            self.calls.lock().unwrap().push(spec.clone());
+            let run = self
+                .script
+                .lock()
+                .unwrap()
+                .pop_front()
+                .unwrap_or_else(|| panic!("予定していないコマンドが起動されました: {spec:?}"));
+            match run {
+                FakeRun::Exit {
+                    code,
+                    stdout,
+                    stderr,
+                } => Ok(Box::new(FakeProcess {
+                    stdin: spec.pipe_stdin,
+                    stdout: Some(stdout),
+                    stderr: Some(stderr),
+                    finish: Finish::Exit(code),
+                    killed: false,
+                    cancel: cancel.clone(),
+                })),
+                FakeRun::SpawnError(kind) => Err(io::Error::from(kind)),
+                FakeRun::UntilCancelled => Ok(Box::new(FakeProcess {
+                    stdin: spec.pipe_stdin,
+                    stdout: Some(""),
+                    stderr: Some(""),
+                    finish: Finish::UntilCancelled,
+                    killed: false,
+                    cancel: cancel.clone(),
+                })),
+            }
+        }
+    }
+
+    enum Finish {
+        Exit(i32),
+        UntilCancelled,
+    }
+
+    struct FakeProcess {
+        // 標準入力をまだ取り出していないか。書き込んだ内容は捨てる。
+        stdin: bool,
+        stdout: Option<&'static str>,
+        stderr: Option<&'static str>,
+        finish: Finish,
+        killed: bool,
+        cancel: CancellationToken,
+    }
+
+    impl RunningCommand for FakeProcess {
+        fn take_stdin(&mut self) -> Option<Box<dyn Write + Send>> {
+            std::mem::take(&mut self.stdin).then(|| Box::new(io::sink()) as Box<dyn Write + Send>)
+        }
+
+        fn take_stdout(&mut self) -> Option<Box<dyn Read + Send>> {
+            self.stdout
+                .take()
+                .map(|text| Box::new(Cursor::new(text.as_bytes())) as Box<dyn Read + Send>)
+        }
+
+        fn take_stderr(&mut self) -> Option<Box<dyn Read + Send>> {
+            self.stderr
+                .take()
+                .map(|text| Box::new(Cursor::new(text.as_bytes())) as Box<dyn Read + Send>)
+        }
+
+        fn kill(&mut self) -> io::Result<()> {
+            self.killed = true;
+            Ok(())
+        }
+
+        fn wait(&mut self) -> io::Result<ExitStatus> {
+            if self.killed {
+                return Ok(killed_status());
+            }
+            match self.finish {
+                Finish::Exit(code) => Ok(exit_status(code)),
+                Finish::UntilCancelled => {
+                    while !self.cancel.is_cancelled() {
+                        std::thread::sleep(Duration::from_millis(10));
+                    }
+                    Ok(killed_status())
+                }
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    fn exit_status(code: i32) -> ExitStatus {
+        use std::os::unix::process::ExitStatusExt;
+        ExitStatus::from_raw(code << 8)
+    }
+
+    #[cfg(windows)]
+    fn exit_status(code: i32) -> ExitStatus {
+        use std::os::windows::process::ExitStatusExt;
+        ExitStatus::from_raw(code as u32)
+    }
+
+    // SIGKILL で止まった終了状態。終了コードは持たない。
+    #[cfg(unix)]
+    fn killed_status() -> ExitStatus {
+        use std::os::unix::process::ExitStatusExt;
+        ExitStatus::from_raw(9)
+    }
+
+    #[cfg(windows)]
+    fn killed_status() -> ExitStatus {
+        exit_status(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fake::{FakeRun, FakeRunner};
+    use super::*;
+
+    #[test]
+    fn reads_stdout_of_fake_command() {
+        let runner = FakeRunner::new(vec![FakeRun::Exit {
+            code: 0,
+            stdout: "{\"id\": \"abc\"}",
+            stderr: "WARNING: ignored",
+        }]);
+        let spec = CommandSpec::new(Path::new("yt-dlp")).args(["-J", "https://example.com"]);
+        let (status, stdout) =
+            read_stdout(&runner, &spec, &CancellationToken::new()).expect("run fake");
+        assert!(status.success());
+        assert_eq!(stdout, b"{\"id\": \"abc\"}");
+        assert_eq!(runner.calls(), vec![spec]);
+    }
+
+    // 実際のプロセスでも、標準エラーが多くても詰まらずに標準出力を読み切る。
+    #[cfg(unix)]
+    #[test]
+    fn system_runner_drains_stderr() {
+        let spec = CommandSpec::new(Path::new("sh"))
+            .arg("-c")
+            .arg("head -c 200000 /dev/zero >&2; echo done");
+        let (status, stdout) =
+            read_stdout(&SystemRunner, &spec, &CancellationToken::new()).expect("run sh");
+        assert!(status.success());
+        assert_eq!(stdout, b"done\n");
+    }
+}
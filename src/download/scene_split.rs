@@ -0,0 +1,256 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+use crate::app_logger::LogLevel;
+use crate::i18n::{Msg, tr_fmt};
+use crate::settings::{EncoderProfile, load_encoder_profile, load_scene_split};
+
+use super::convert::probe_media_info;
+use super::encoder::{detect_encoder_profile, video_codec_args};
+use super::runner::{CommandRunner, CommandSpec, output};
+use super::staging::{StagingDir, create_download_staging_dir, next_available_destination};
+use super::{CancellationToken, DownloadEvent};
+
+// これより短いクリップはできないよう、近すぎるカットをまとめる（秒）
+const MIN_CLIP_SECONDS: f64 = 1.0;
+
+// showinfo のログから、select で選ばれたフレームの時刻（秒）を順に取り出す。
+pub(super) fn parse_scene_times(log: &str) -> Vec<f64> {
+    log.lines()
+        .filter(|line| line.contains("Parsed_showinfo"))
+        .filter_map(|line| {
+            let idx = line.find("pts_time:")?;
+            line[idx + "pts_time:".len()..]
+                .split_whitespace()
+                .next()?
+                .parse::<f64>()
+                .ok()
+        })
+        .collect()
+}
+
+// 分割する時刻を決める。前のカット・先頭・末尾から MIN_CLIP_SECONDS 未満のカットは使わない。
+pub(super) fn split_points(cuts: &[f64], duration_seconds: f64) -> Vec<f64> {
+    let mut points: Vec<f64> = Vec::new();
+    let mut previous = 0.0;
+    for &cut in cuts {
+        if cut - previous < MIN_CLIP_SECONDS || duration_seconds - cut < MIN_CLIP_SECONDS {
+            continue;
+        }
+        points.push(cut);
+        previous = cut;
+    }
+    points
+}
+
+// segment の出力名は % を書式として扱うため、ファイル名の % を逃がす。
+pub(super) fn segment_pattern(dir: &Path, stem: &str) -> PathBuf {
+    dir.join(format!("{} %03d.mp4", stem.replace('%', "%%")))
+}
+
+fn detect_scene_cuts(
+    runner: &dyn CommandRunner,
+    ffmpeg: &Path,
+    input: &Path,
+    threshold: u8,
+    cancel: &CancellationToken,
+) -> Result<Vec<f64>, String> {
+    let filter = format!(
+        "select='gt(scene,{:.2})',showinfo",
+        f64::from(threshold) / 100.0
+    );
+    let cmd = CommandSpec::new(ffmpeg)
+        .args(["-hide_banner", "-i"])
+        .arg(input)
+        .args(["-map", "0:v:0", "-vf"])
+        .arg(filter)
+        .args(["-f", "null", "-"]);
+    let output = output(runner, &cmd, cancel)
+        .map_err(|err| tr_fmt(Msg::ToolLaunchFailed, &[&"ffmpeg", &err]))?;
+    cancel.check()?;
+    if !output.status.success() {
+        return Err(tr_fmt(Msg::SceneDetectFailed, &[&output.status]));
+    }
+    Ok(parse_scene_times(&String::from_utf8_lossy(&output.stderr)))
+}
+
+// 指定した時刻にキーフレームを置いて再エンコードし、segment で切り分ける。
+pub(super) fn write_segments(
+    runner: &dyn CommandRunner,
+    ffmpeg: &Path,
+    input: &Path,
+    points: &[f64],
+    pattern: &Path,
+    cancel: &CancellationToken,
+) -> Result<(), String> {
+    // 映像をコピーするとキーフレームでしか切れないため、コピーの設定でも再エンコードする。
+    let preferred = match load_encoder_profile() {
+        EncoderProfile::Copy => EncoderProfile::X264,
+        profile => profile,
+    };
+    let profile = detect_encoder_profile(ffmpeg, preferred)?;
+    let times = points
+        .iter()
+        .map(|point| format!("{point:.3}"))
+        .collect::<Vec<_>>()
+        .join(",");
+    let cmd = CommandSpec::new(ffmpeg)
+        .args(["-hide_banner", "-y", "-i"])
+        .arg(input)
+        .args(["-map", "0:v:0", "-map", "0:a:0?"])
+        .args(video_codec_args(profile).iter().copied())
+        .arg("-force_key_frames")
+        .arg(&times)
+        .args([
+            "-c:a",
+            "aac",
+            "-b:a",
+            "192k",
+            "-f",
+            "segment",
+            "-segment_times",
+        ])
+        .arg(&times)
+        .args(["-reset_timestamps", "1", "-segment_format", "mp4"])
+        .args(["-segment_format_options", "movflags=+faststart"])
+        .arg(pattern);
+    let output = output(runner, &cmd, cancel)
+        .map_err(|err| tr_fmt(Msg::ToolLaunchFailed, &[&"ffmpeg", &err]))?;
+    cancel.check()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let last = stderr.lines().last().unwrap_or_default().trim();
+        return Err(tr_fmt(Msg::ClipWriteFailed, &[&last]));
+    }
+    Ok(())
+}
+
+// 保存した動画をシーンの切り替わりで分割し、連番のクリップを元の動画の隣に置く。元の動画は残す。
+// カットが見つからない場合は分割せず、空の一覧を返す。
+pub(super) fn split_into_scenes(
+    runner: &dyn CommandRunner,
+    path: &Path,
+    output_dir: &Path,
+    ffmpeg: &Path,
+    threshold: u8,
+    tx: &mpsc::Sender<DownloadEvent>,
+    cancel: &CancellationToken,
+) -> Result<Vec<PathBuf>, String> {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let _ = tx.send(DownloadEvent::Log(
+        LogLevel::Info,
+        tr_fmt(Msg::SceneSplitStarted, &[&name]),
+    ));
+
+    let duration = probe_media_info(runner, path.as_os_str(), None, cancel)?
+        .duration_seconds
+        .unwrap_or(0.0);
+    let cuts = detect_scene_cuts(runner, ffmpeg, path, threshold, cancel)?;
+    let points = split_points(&cuts, duration);
+    if points.is_empty() {
+        let _ = tx.send(DownloadEvent::Log(
+            LogLevel::Info,
+            tr_fmt(Msg::SceneSplitNoCuts, &[&name]),
+        ));
+        return Ok(Vec::new());
+    }
+
+    // 書き出し途中のクリップが一覧に出ないよう、一時フォルダへ書いてから移す。
+    let staging = StagingDir::new(create_download_staging_dir(output_dir)?);
+    write_segments(
+        runner,
+        ffmpeg,
+        path,
+        &points,
+        &segment_pattern(staging.path(), &stem),
+        cancel,
+    )?;
+    let mut clips = fs::read_dir(staging.path())
+        .map_err(|err| tr_fmt(Msg::ClipCheckFailed, &[&err]))?
+        .flatten()
+        .map(|entry| entry.path())
+        .collect::<Vec<_>>();
+    clips.sort();
+
+    let mut moved = Vec::new();
+    for clip in clips {
+        let Some(file_name) = clip.file_name() else {
+            continue;
+        };
+        let mut destination = output_dir.join(file_name);
+        if destination.exists() {
+            destination = next_available_destination(&destination)?;
+        }
+        fs::rename(&clip, &destination).map_err(|err| tr_fmt(Msg::ClipMoveFailed, &[&err]))?;
+        moved.push(destination);
+    }
+    let _ = staging.remove();
+    let _ = tx.send(DownloadEvent::Log(
+        LogLevel::Info,
+        tr_fmt(Msg::SceneSplitDone, &[&name, &moved.len()]),
+    ));
+    Ok(moved)
+}
+
+// 設定で有効な場合に、保存した動画をそれぞれ分割する。分割に失敗しても保存した動画は残すため、
+// 取り消し以外はログに出して続ける。
+pub(super) fn split_saved_outputs(
+    runner: &dyn CommandRunner,
+    outputs: &[PathBuf],
+    output_dir: &Path,
+    ffmpeg: &Path,
+    tx: &mpsc::Sender<DownloadEvent>,
+    cancel: &CancellationToken,
+) -> Result<(), String> {
+    let (enabled, threshold) = load_scene_split();
+    if !enabled {
+        return Ok(());
+    }
+    for path in outputs {
+        if let Err(err) = split_into_scenes(runner, path, output_dir, ffmpeg, threshold, tx, cancel)
+        {
+            cancel.check()?;
+            let _ = tx.send(DownloadEvent::Log(
+                LogLevel::Error,
+                tr_fmt(Msg::SceneSplitFailed, &[&err]),
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_showinfo_times() {
+        let log = "\
+[Parsed_showinfo_1 @ 0x1] config in time_base: 1/15360, frame_rate: 30/1
+[Parsed_showinfo_1 @ 0x1] n:   0 pts: 192000 pts_time:12.5    duration: 512
+[Parsed_showinfo_1 @ 0x1] n:   1 pts: 460800 pts_time:30.0334 duration: 512
+frame= 1800 fps=900 q=-0.0 Lsize=N/A time=00:01:00.00";
+        assert_eq!(parse_scene_times(log), vec![12.5, 30.0334]);
+    }
+
+    #[test]
+    fn drops_cuts_that_would_make_tiny_clips() {
+        let cuts = [0.4, 5.0, 5.6, 12.0, 59.5];
+        assert_eq!(split_points(&cuts, 60.0), vec![5.0, 12.0]);
+        assert!(split_points(&[], 60.0).is_empty());
+    }
+
+    #[test]
+    fn escapes_percent_in_segment_pattern() {
+        let pattern = segment_pattern(Path::new("/tmp/out"), "100% mix");
+        assert_eq!(pattern, Path::new("/tmp/out/100%% mix %03d.mp4"));
+    }
+}
@@ -0,0 +1,214 @@
+use crate::i18n::{Msg, tr, tr_fmt};
+
+use super::PipelineError;
+
+const MIB: f64 = 1024.0 * 1024.0;
+
+// ダウンロードの段階。画面・OSC・WebSocket 配信はこの値から表示を組み立てる。
+#[derive(Clone, Debug, PartialEq)]
+pub enum DownloadStage {
+    // 動画を読み込み始めた。`checking_info` は API やページから動画情報を確認している間。
+    Resolving { checking_info: bool },
+    // 取得中。合計が分からない場合は total が None。
+    Fetching { bytes: u64, total: Option<u64> },
+    // 変換・結合中。割合が分からない間は pct が None、2 パス変換では pass に何パス目かを持つ。
+    Converting { pct: Option<f32>, pass: Option<u8> },
+    // 保存先へ移して確認している
+    Finalizing,
+    Done,
+    Failed { kind: FailureKind },
+}
+
+// 失敗の種類。キャンセルとオフラインによる中断は、失敗の一覧や通知の対象にしない。
+#[derive(Clone, Debug, PartialEq)]
+pub enum FailureKind {
+    Cancelled,
+    Offline,
+    // 外部ツールの出力が止まったため、見張りが中断した。
+    Stalled(String),
+    Error(String),
+}
+
+impl FailureKind {
+    // ダウンロード処理が返したエラーから判定する。
+    pub(super) fn from_error(err: PipelineError) -> Self {
+        match err {
+            PipelineError::Cancelled => FailureKind::Cancelled,
+            PipelineError::Offline => FailureKind::Offline,
+            PipelineError::Failed(err) => FailureKind::Error(err),
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            FailureKind::Cancelled => tr(Msg::DownloadCancelled),
+            FailureKind::Offline => tr(Msg::OfflineError),
+            FailureKind::Stalled(err) | FailureKind::Error(err) => err,
+        }
+    }
+}
+
+impl DownloadStage {
+    // 完了・失敗・キャンセルのいずれかで、これ以降の段階は届かない。
+    pub fn is_finished(&self) -> bool {
+        matches!(self, DownloadStage::Done | DownloadStage::Failed { .. })
+    }
+
+    // 進捗バーの割合（0.0〜1.0）。分からない段階では None。
+    pub fn fraction(&self) -> Option<f32> {
+        match self {
+            DownloadStage::Fetching {
+                bytes,
+                total: Some(total),
+            } if *total > 0 => Some((*bytes as f64 / *total as f64).clamp(0.0, 1.0) as f32),
+            DownloadStage::Converting { pct: Some(pct), .. } => Some(pct.clamp(0.0, 100.0) / 100.0),
+            DownloadStage::Done => Some(1.0),
+            _ => None,
+        }
+    }
+
+    pub fn reported_progress(&self) -> f32 {
+        reported_progress(self.fraction())
+    }
+
+    // WebSocket 配信などで使う識別子
+    pub fn as_key(&self) -> &'static str {
+        match self {
+            DownloadStage::Resolving { .. } => "resolving",
+            DownloadStage::Fetching { .. } => "fetching",
+            DownloadStage::Converting { .. } => "converting",
+            DownloadStage::Finalizing => "finalizing",
+            DownloadStage::Done => "done",
+            DownloadStage::Failed { .. } => "failed",
+        }
+    }
+
+    // 進捗パネルに表示する文言。elapsed は開始からの経過時間。
+    pub fn message(&self, elapsed: &str) -> String {
+        let elapsed = format_elapsed(elapsed);
+        match self {
+            DownloadStage::Resolving {
+                checking_info: true,
+            } => tr_fmt(Msg::FetchingInfo, &[&elapsed]),
+            DownloadStage::Resolving { .. } => tr_fmt(Msg::LoadingVideo, &[&elapsed]),
+            DownloadStage::Fetching { bytes, total: None } => tr_fmt(
+                Msg::DownloadingSize,
+                &[&format!("{:.1}MB", *bytes as f64 / MIB), &elapsed],
+            ),
+            DownloadStage::Fetching { .. } => tr_fmt(
+                Msg::DownloadingPercent,
+                &[&percent_label(self.fraction()), &elapsed],
+            ),
+            DownloadStage::Converting { pct: None, .. } => tr_fmt(Msg::Converting, &[&elapsed]),
+            DownloadStage::Converting { pass: None, .. } => tr_fmt(
+                Msg::ConvertingPercent,
+                &[&percent_label(self.fraction()), &elapsed],
+            ),
+            DownloadStage::Converting {
+                pass: Some(pass), ..
+            } => {
+                let step = if *pass == 1 {
+                    tr(Msg::PassAnalyzing)
+                } else {
+                    tr(Msg::PassConverting)
+                };
+                tr_fmt(
+                    Msg::PassProgress,
+                    &[&step, pass, &percent_label(self.fraction()), &elapsed],
+                )
+            }
+            DownloadStage::Finalizing => tr_fmt(Msg::Finalizing, &[&elapsed]),
+            DownloadStage::Done => tr_fmt(Msg::DownloadComplete, &[&elapsed]),
+            DownloadStage::Failed { kind } => kind.message().to_string(),
+        }
+    }
+}
+
+// OSC・WebSocket・スクリプト・メニューバーへ渡す進捗率。外部向けには割合が分からない場合を負値で表す。
+pub fn reported_progress(fraction: Option<f32>) -> f32 {
+    fraction.unwrap_or(-1.0)
+}
+
+fn percent_label(fraction: Option<f32>) -> String {
+    format!("{:.1}", fraction.unwrap_or(0.0) * 100.0)
+}
+
+// 経過時間表示のフォーマットを統一する。
+fn format_elapsed(elapsed: &str) -> String {
+    if elapsed.trim().is_empty() {
+        String::new()
+    } else {
+        tr_fmt(Msg::Elapsed, &[&elapsed])
+    }
+}
+
+// yt-dlp・aria2c の表記（`80.00MiB`、`1.2GiB`、`512KiB`、`0B`）をバイト数にする。
+pub(super) fn parse_size(value: &str) -> Option<u64> {
+    let unit_start = value
+        .find(|ch: char| !ch.is_ascii_digit() && ch != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(unit_start);
+    let multiplier = match unit {
+        "" | "B" => 1.0,
+        "KiB" => 1024.0,
+        "MiB" => MIB,
+        "GiB" => MIB * 1024.0,
+        _ => return None,
+    };
+    Some((number.parse::<f64>().ok()? * multiplier) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derives_progress_from_stage() {
+        let fetching = DownloadStage::Fetching {
+            bytes: 25,
+            total: Some(100),
+        };
+        assert_eq!(fetching.fraction(), Some(0.25));
+        assert_eq!(fetching.as_key(), "fetching");
+        let unknown = DownloadStage::Fetching {
+            bytes: 25,
+            total: None,
+        };
+        assert_eq!(unknown.fraction(), None);
+        assert_eq!(unknown.reported_progress(), -1.0);
+        assert_eq!(
+            DownloadStage::Converting {
+                pct: Some(150.0),
+                pass: None
+            }
+            .fraction(),
+            Some(1.0)
+        );
+        assert_eq!(DownloadStage::Done.fraction(), Some(1.0));
+        assert!(
+            DownloadStage::Failed {
+                kind: FailureKind::Cancelled
+            }
+            .is_finished()
+        );
+        assert!(!DownloadStage::Finalizing.is_finished());
+
+        assert_eq!(
+            FailureKind::from_error(PipelineError::Cancelled),
+            FailureKind::Cancelled
+        );
+        assert_eq!(
+            FailureKind::from_error(PipelineError::Offline),
+            FailureKind::Offline
+        );
+        assert_eq!(
+            FailureKind::from_error("HTTP Error 403".to_string().into()),
+            FailureKind::Error("HTTP Error 403".to_string())
+        );
+
+        assert_eq!(parse_size("80.00MiB"), Some(80 * 1024 * 1024));
+        assert_eq!(parse_size("512KiB"), Some(512 * 1024));
+        assert_eq!(parse_size("0B"), Some(0));
+        assert_eq!(parse_size("Unknown"), None);
+    }
+}
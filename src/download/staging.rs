@@ -1,14 +1,79 @@
 use std::fs;
-use std::io::ErrorKind;
+use std::io::{self, ErrorKind};
 use std::path::{Path, PathBuf};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::mpsc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use crate::app_logger::LogLevel;
 use crate::fs_utils::ensure_dir;
+use crate::i18n::{Msg, tr, tr_fmt};
+use crate::settings::CollisionPolicy;
+use crate::video_info::{staged_info_json, store_info_json};
+
+use super::{CancellationToken, DownloadEvent, wait_for_reply};
+
+const STAGING_DIR_NAME: &str = ".vjdownloader-staging";
+
+// この時間より前に作られた作業フォルダは、異常終了で残ったものとみなす。
+pub const STALE_STAGING_AGE: Duration = Duration::from_secs(6 * 60 * 60);
+
+// 同名のファイルがある場合に、実際に行う扱い
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CollisionAction {
+    Overwrite,
+    Rename,
+    Skip,
+}
+
+// 保存先に同名のファイルがある場合に、どうするか選んでもらう。
+pub struct CollisionPrompt {
+    pub file_name: String,
+    reply: mpsc::Sender<CollisionAction>,
+}
+
+impl CollisionPrompt {
+    pub fn respond(&self, action: CollisionAction) {
+        let _ = self.reply.send(action);
+    }
+}
+
+// ダウンロード 1 件分の一時作業フォルダ。途中で失敗・取り消し・パニックしても、破棄時に必ず消す。
+pub(super) struct StagingDir {
+    path: PathBuf,
+    removed: bool,
+}
+
+impl StagingDir {
+    pub(super) fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            removed: false,
+        }
+    }
+
+    pub(super) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // 消せなかった場合を呼び出し側で知らせるために、結果を返して消す。
+    pub(super) fn remove(mut self) -> io::Result<()> {
+        self.removed = true;
+        fs::remove_dir_all(&self.path)
+    }
+}
+
+impl Drop for StagingDir {
+    fn drop(&mut self) {
+        if !self.removed {
+            let _ = fs::remove_dir_all(&self.path);
+        }
+    }
+}
 
 // ダウンロードごとに衝突しない一時作業フォルダを作成する。
 pub(super) fn create_download_staging_dir(output_dir: &Path) -> Result<PathBuf, String> {
-    let staging_root = output_dir.join(".vjdownloader-staging");
-    ensure_dir(&staging_root).map_err(|err| format!("一時フォルダの準備に失敗しました: {err}"))?;
+    let staging_root = output_dir.join(STAGING_DIR_NAME);
+    ensure_dir(&staging_root).map_err(|err| tr_fmt(Msg::StagingPrepareFailed, &[&err]))?;
 
     let pid = std::process::id();
     let timestamp = SystemTime::now()
@@ -20,23 +85,28 @@ pub(super) fn create_download_staging_dir(output_dir: &Path) -> Result<PathBuf,
         match fs::create_dir(&candidate) {
             Ok(()) => return Ok(candidate),
             Err(err) if err.kind() == ErrorKind::AlreadyExists => continue,
-            Err(err) => return Err(format!("一時フォルダの作成に失敗しました: {err}")),
+            Err(err) => return Err(tr_fmt(Msg::StagingCreateFailed, &[&err])),
         }
     }
-    Err("一時フォルダ名の確保に失敗しました。".to_string())
+    Err(tr(Msg::StagingNameFailed).to_string())
 }
 
-// 一時フォルダ内の MP4 のみを最終保存先へ移動する。
+// 一時フォルダ内の MP4 のみを最終保存先へ移動し、移動先のパスを返す。
+// 同じ名前の info JSON があれば、移動先のファイル用にアプリのフォルダへ保存する。
+// 同名のファイルがある場合は policy に従い、保存しなかったファイルは返さない。
 pub(super) fn promote_downloaded_mp4_files(
     staging_dir: &Path,
     output_dir: &Path,
-) -> Result<(), String> {
-    let entries = fs::read_dir(staging_dir)
-        .map_err(|err| format!("一時フォルダの読み取りに失敗しました: {err}"))?;
+    policy: CollisionPolicy,
+    tx: &mpsc::Sender<DownloadEvent>,
+    cancel: &CancellationToken,
+) -> Result<Vec<PathBuf>, String> {
+    let entries =
+        fs::read_dir(staging_dir).map_err(|err| tr_fmt(Msg::StagingReadFailed, &[&err]))?;
     let mut mp4_files = Vec::new();
 
     for entry in entries {
-        let entry = entry.map_err(|err| format!("一時フォルダの読み取りに失敗しました: {err}"))?;
+        let entry = entry.map_err(|err| tr_fmt(Msg::StagingReadFailed, &[&err]))?;
         let path = entry.path();
         if !path.is_file() {
             continue;
@@ -52,32 +122,91 @@ pub(super) fn promote_downloaded_mp4_files(
     }
 
     if mp4_files.is_empty() {
-        return Err("ダウンロード完了後のMP4ファイルが見つかりませんでした。".to_string());
+        return Err(tr(Msg::NoMp4AfterDownload).to_string());
     }
 
     mp4_files.sort();
+    let mut promoted = Vec::new();
     for src in mp4_files {
-        move_file_to_output_dir(&src, output_dir)?;
+        let file_name = src
+            .file_name()
+            .ok_or_else(|| tr(Msg::InvalidFileName).to_string())?;
+        let name = file_name.to_string_lossy().to_string();
+        let mut destination = output_dir.join(file_name);
+        if destination.exists() {
+            match collision_action(policy, &name, tx, cancel)? {
+                CollisionAction::Overwrite => {
+                    let _ = tx.send(DownloadEvent::Log(
+                        LogLevel::Info,
+                        tr_fmt(Msg::OverwroteExisting, &[&name]),
+                    ));
+                }
+                CollisionAction::Rename => {
+                    destination = next_available_destination(&destination)?;
+                    let renamed = destination
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy();
+                    let _ = tx.send(DownloadEvent::Log(
+                        LogLevel::Info,
+                        tr_fmt(Msg::SavedWithNumber, &[&renamed]),
+                    ));
+                }
+                CollisionAction::Skip => {
+                    let _ = tx.send(DownloadEvent::Log(
+                        LogLevel::Info,
+                        tr_fmt(Msg::SkippedExisting, &[&name]),
+                    ));
+                    continue;
+                }
+            }
+        }
+        // 保存先が決まったところで動画情報を移し、検索インデックスが保存直後から投稿者名などを読めるようにする。
+        let info_json = staged_info_json(&src);
+        if info_json.is_file()
+            && let Err(err) = store_info_json(&info_json, &destination)
+        {
+            let _ = tx.send(DownloadEvent::Log(
+                LogLevel::Error,
+                tr_fmt(Msg::VideoInfoSaveFailed, &[&err]),
+            ));
+        }
+        move_file_to_output_dir(&src, &destination)?;
+        promoted.push(destination);
     }
 
-    Ok(())
+    Ok(promoted)
 }
 
-// 同名衝突を避けながら、最終保存先へファイルを移動する。
-fn move_file_to_output_dir(src: &Path, output_dir: &Path) -> Result<(), String> {
-    let file_name = src
-        .file_name()
-        .ok_or_else(|| "保存対象のファイル名が不正です。".to_string())?;
-    let mut destination = output_dir.join(file_name);
-    if destination.exists() {
-        destination = next_available_destination(&destination)?;
+// 設定の扱いを決める。毎回確認する設定では画面で選んでもらう。
+fn collision_action(
+    policy: CollisionPolicy,
+    file_name: &str,
+    tx: &mpsc::Sender<DownloadEvent>,
+    cancel: &CancellationToken,
+) -> Result<CollisionAction, String> {
+    match policy {
+        CollisionPolicy::Overwrite => Ok(CollisionAction::Overwrite),
+        CollisionPolicy::Rename => Ok(CollisionAction::Rename),
+        CollisionPolicy::Skip => Ok(CollisionAction::Skip),
+        CollisionPolicy::Ask => {
+            let (reply, answer) = mpsc::channel();
+            let _ = tx.send(DownloadEvent::CollisionPrompt(CollisionPrompt {
+                file_name: file_name.to_string(),
+                reply,
+            }));
+            // 確認画面が閉じられた場合は番号を付けて保存する。
+            Ok(wait_for_reply(&answer, cancel)?.unwrap_or(CollisionAction::Rename))
+        }
     }
+}
 
-    fs::rename(src, &destination).map_err(|err| {
-        format!(
-            "動画ファイルの配置に失敗しました: {} -> {} ({err})",
-            src.to_string_lossy(),
-            destination.to_string_lossy()
+// 最終保存先へファイルを移動する。同名のファイルがあれば置き換える。
+fn move_file_to_output_dir(src: &Path, destination: &Path) -> Result<(), String> {
+    fs::rename(src, destination).map_err(|err| {
+        tr_fmt(
+            Msg::PlaceFileFailed,
+            &[&src.to_string_lossy(), &destination.to_string_lossy(), &err],
         )
     })?;
 
@@ -85,10 +214,10 @@ fn move_file_to_output_dir(src: &Path, output_dir: &Path) -> Result<(), String>
 }
 
 // 既存ファイルがある場合、"(n)" サフィックス付きの保存先を探す。
-fn next_available_destination(base_path: &Path) -> Result<PathBuf, String> {
+pub(super) fn next_available_destination(base_path: &Path) -> Result<PathBuf, String> {
     let parent = base_path
         .parent()
-        .ok_or_else(|| "保存先フォルダの解決に失敗しました。".to_string())?;
+        .ok_or_else(|| tr(Msg::ResolveOutputDirFailed).to_string())?;
     let stem = base_path
         .file_stem()
         .map(|s| s.to_string_lossy().to_string())
@@ -110,5 +239,123 @@ fn next_available_destination(base_path: &Path) -> Result<PathBuf, String> {
             return Ok(candidate);
         }
     }
-    Err("同名ファイルが多すぎるため保存先を確保できませんでした。".to_string())
+    Err(tr(Msg::TooManyDuplicates).to_string())
+}
+
+// .vjdownloader-staging 直下の job-* フォルダかを判定する。
+pub(super) fn is_staging_dir(path: &Path) -> bool {
+    let in_staging_root = path
+        .parent()
+        .and_then(Path::file_name)
+        .is_some_and(|name| name == STAGING_DIR_NAME);
+    let is_job = path
+        .file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with("job-"));
+    in_staging_root && is_job
+}
+
+// 作業フォルダ名（job-<作成時刻ms>-<pid>-<連番>）から作成時刻を読み取る。
+fn staging_created_at(name: &str) -> Option<SystemTime> {
+    let millis = name
+        .strip_prefix("job-")?
+        .split('-')
+        .next()?
+        .parse::<u64>()
+        .ok()?;
+    Some(UNIX_EPOCH + Duration::from_millis(millis))
+}
+
+// 保存先ごとの作業フォルダから、max_age より古い job-* を削除して削除したフォルダを返す。
+// keep（再開できる中断したダウンロードの作業フォルダ）は残す。空になった .vjdownloader-staging も削除する。
+pub fn remove_stale_staging_dirs(
+    output_dirs: &[PathBuf],
+    max_age: Duration,
+    keep: Option<&Path>,
+) -> Vec<PathBuf> {
+    let now = SystemTime::now();
+    let mut removed = Vec::new();
+    for output_dir in output_dirs {
+        let staging_root = output_dir.join(STAGING_DIR_NAME);
+        let Ok(entries) = fs::read_dir(&staging_root) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let stale = path.is_dir()
+                && keep != Some(path.as_path())
+                && staging_created_at(&entry.file_name().to_string_lossy())
+                    .and_then(|created| now.duration_since(created).ok())
+                    .is_some_and(|age| age >= max_age);
+            if stale && fs::remove_dir_all(&path).is_ok() {
+                removed.push(path);
+            }
+        }
+        // 他のダウンロードが使用中の場合は空でないため残る。
+        let _ = fs::remove_dir(&staging_root);
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removes_only_stale_staging_dirs() {
+        let output = tempfile::tempdir().unwrap();
+        let staging_root = output.path().join(STAGING_DIR_NAME);
+        let now_millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis();
+        let old = staging_root.join("job-1000-42-0");
+        let fresh = staging_root.join(format!("job-{now_millis}-42-0"));
+        let other = staging_root.join("notes");
+        for dir in [&old, &fresh, &other] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        fs::write(old.join("clip.mp4.part"), b"partial").unwrap();
+
+        let dirs = vec![output.path().to_path_buf(), output.path().join("missing")];
+        let kept = staging_root.join("job-2000-42-0");
+        fs::create_dir_all(&kept).unwrap();
+        let removed = remove_stale_staging_dirs(&dirs, STALE_STAGING_AGE, Some(&kept));
+        assert_eq!(removed, [old]);
+        assert!(kept.is_dir());
+        assert!(fresh.is_dir());
+        assert!(other.is_dir());
+
+        fs::remove_dir(&fresh).unwrap();
+        fs::remove_dir(&other).unwrap();
+        remove_stale_staging_dirs(&dirs, STALE_STAGING_AGE, None);
+        assert!(!staging_root.exists());
+    }
+
+    #[test]
+    fn applies_collision_policy() {
+        let output = tempfile::tempdir().unwrap();
+        let (tx, _rx) = mpsc::channel();
+        let cancel = CancellationToken::new();
+        let promote = |policy| {
+            let staging = create_download_staging_dir(output.path()).unwrap();
+            fs::write(staging.join("clip.mp4"), b"new").unwrap();
+            let promoted =
+                promote_downloaded_mp4_files(&staging, output.path(), policy, &tx, &cancel)
+                    .unwrap();
+            fs::remove_dir_all(&staging).unwrap();
+            promoted
+        };
+        let existing = output.path().join("clip.mp4");
+        fs::write(&existing, b"old").unwrap();
+
+        assert!(promote(CollisionPolicy::Skip).is_empty());
+        assert_eq!(fs::read(&existing).unwrap(), b"old");
+
+        let renamed = output.path().join("clip (1).mp4");
+        assert_eq!(promote(CollisionPolicy::Rename), [renamed.as_path()]);
+        assert_eq!(fs::read(&renamed).unwrap(), b"new");
+
+        assert_eq!(promote(CollisionPolicy::Overwrite), [existing.as_path()]);
+        assert_eq!(fs::read(&existing).unwrap(), b"new");
+    }
 }
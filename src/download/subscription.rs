@@ -0,0 +1,200 @@
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::i18n::{Msg, tr, tr_fmt};
+use crate::network;
+use crate::paths::yt_dlp_path;
+use crate::redact::redact_line;
+use crate::settings::load_cookie_args;
+
+use super::process::with_bin_path;
+use super::runner::{CommandRunner, CommandSpec, SystemRunner, read_output};
+use super::{CancellationToken, history};
+
+// 1 回の確認で調べる動画の数（チャンネル・プレイリストの先頭から）
+const CHECK_LATEST: u32 = 30;
+
+// 購読したチャンネル・プレイリストで見つけた新しい動画
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NewUpload {
+    pub url: String,
+    pub title: String,
+}
+
+// 一覧の 1 件
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct PlaylistEntry {
+    url: String,
+    title: String,
+    // ダウンロードアーカイブの 1 行（`<extractor> <id>`）
+    archive_id: Option<String>,
+}
+
+// 購読元の一覧を yt-dlp の flat-playlist で取得し、履歴とアーカイブにない新しい動画を返す。
+// 初めて確認する購読元は、今ある動画を見たものとして記録するだけにする。
+pub fn check_subscription(
+    source: &str,
+    cancel: &CancellationToken,
+) -> Result<Vec<NewUpload>, String> {
+    let yt_dlp = yt_dlp_path();
+    if !yt_dlp.exists() {
+        return Err(tr(Msg::YtDlpNotFound).to_string());
+    }
+    network::ensure_online()?;
+    let entries = list_entries(
+        &SystemRunner,
+        &yt_dlp,
+        source,
+        &load_cookie_args(""),
+        cancel,
+    )?;
+    let entries: Vec<PlaylistEntry> = entries
+        .into_iter()
+        .filter(|entry| {
+            entry
+                .archive_id
+                .as_deref()
+                .is_none_or(|id| !history::is_archived(id))
+        })
+        .collect();
+    let urls: Vec<String> = entries.iter().map(|entry| entry.url.clone()).collect();
+    let unseen = history::take_unseen_urls(source, &urls)?;
+    Ok(entries
+        .into_iter()
+        .filter(|entry| unseen.contains(&entry.url))
+        .map(|entry| NewUpload {
+            url: entry.url,
+            title: entry.title,
+        })
+        .collect())
+}
+
+// 動画ごとの情報は取得せず、一覧だけを 1 行 1 件の JSON で受け取る。
+fn list_entries(
+    runner: &dyn CommandRunner,
+    yt_dlp: &Path,
+    source: &str,
+    auth_args: &[String],
+    cancel: &CancellationToken,
+) -> Result<Vec<PlaylistEntry>, String> {
+    let spec = with_bin_path(
+        CommandSpec::new(yt_dlp)
+            .args(["--flat-playlist", "--dump-json", "--ignore-errors"])
+            .arg("--playlist-end")
+            .arg(CHECK_LATEST.to_string())
+            .args(auth_args.iter().cloned())
+            .arg("--")
+            .arg(source),
+    );
+    let (status, stdout, stderr) = read_output(runner, &spec, cancel, true)
+        .map_err(|err| tr_fmt(Msg::YtDlpRunFailed, &[&err]))?;
+    cancel.check()?;
+    let entries: Vec<PlaylistEntry> = String::from_utf8_lossy(&stdout)
+        .lines()
+        .filter_map(parse_entry)
+        .collect();
+    // 一部の動画が読めなくても、取得できた分は使う。
+    if !status.success() && entries.is_empty() {
+        let stderr = String::from_utf8_lossy(&stderr);
+        let reason = stderr
+            .lines()
+            .rev()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .map(redact_line)
+            .unwrap_or_else(|| status.to_string());
+        return Err(tr_fmt(Msg::SubscriptionListFailed, &[&reason]));
+    }
+    Ok(entries)
+}
+
+fn parse_entry(line: &str) -> Option<PlaylistEntry> {
+    let value: Value = serde_json::from_str(line).ok()?;
+    let url = ["webpage_url", "url"]
+        .iter()
+        .filter_map(|key| value.get(key).and_then(Value::as_str))
+        .find(|url| url.starts_with("http"))?
+        .to_string();
+    let title = value
+        .get("title")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|title| !title.is_empty())
+        .unwrap_or(&url)
+        .to_string();
+    Some(PlaylistEntry {
+        url,
+        title,
+        archive_id: history::archive_id(line),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::download::runner::fake::{FakeRun, FakeRunner};
+
+    #[test]
+    fn lists_flat_playlist_entries() {
+        let runner = FakeRunner::new(vec![FakeRun::Exit {
+            code: 1,
+            stdout: concat!(
+                r#"{"_type": "url", "ie_key": "Youtube", "id": "abc", "url": "https://www.youtube.com/watch?v=abc", "title": " Loop 01 "}"#,
+                "\n",
+                r#"{"_type": "url", "ie_key": "Youtube", "id": "def", "url": "https://www.youtube.com/watch?v=def", "title": null}"#,
+                "\n",
+                "not json\n",
+            ),
+            stderr: "ERROR: [youtube] xyz: Private video\n",
+        }]);
+
+        let entries = list_entries(
+            &runner,
+            Path::new("yt-dlp"),
+            "https://www.youtube.com/@vj/videos",
+            &[],
+            &CancellationToken::new(),
+        )
+        .unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                PlaylistEntry {
+                    url: "https://www.youtube.com/watch?v=abc".to_string(),
+                    title: "Loop 01".to_string(),
+                    archive_id: Some("youtube abc".to_string()),
+                },
+                PlaylistEntry {
+                    url: "https://www.youtube.com/watch?v=def".to_string(),
+                    title: "https://www.youtube.com/watch?v=def".to_string(),
+                    archive_id: Some("youtube def".to_string()),
+                },
+            ]
+        );
+        let args = &runner.calls()[0].args;
+        assert!(args.iter().any(|arg| arg == "--flat-playlist"));
+        assert_eq!(
+            args.last().and_then(|arg| arg.to_str()),
+            Some("https://www.youtube.com/@vj/videos")
+        );
+    }
+
+    #[test]
+    fn reports_last_error_line_when_nothing_listed() {
+        let runner = FakeRunner::new(vec![FakeRun::Exit {
+            code: 1,
+            stdout: "",
+            stderr: "WARNING: slow\nERROR: [youtube:tab] @missing: This channel does not exist.\n",
+        }]);
+        let err = list_entries(
+            &runner,
+            Path::new("yt-dlp"),
+            "https://www.youtube.com/@missing",
+            &[],
+            &CancellationToken::new(),
+        )
+        .unwrap_err();
+        assert!(err.contains("does not exist"));
+    }
+}
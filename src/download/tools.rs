@@ -1,13 +1,30 @@
 use std::fs;
-use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::mpsc;
 
-use crate::fs_utils::{ensure_dir, is_executable};
-use crate::paths::{bin_dir, deno_path, yt_dlp_path};
-
-use super::DownloadEvent;
+use crate::app_logger::LogLevel;
+use crate::fs_utils::ensure_dir;
+use crate::i18n::{Msg, tr, tr_fmt};
+use crate::network::ensure_online;
+use crate::paths::{
+    app_ffmpeg_path, app_ffprobe_path, bin_dir, deno_path, ffmpeg_update_marker_path, yt_dlp_path,
+};
+use crate::platform::{
+    deno_target, ensure_executable, executable_name, extract_zip, ffmpeg_release_platform,
+    is_executable, yt_dlp_asset_name,
+};
+
+use crate::settings::{
+    EncoderProfile, JsRuntime, YtDlpChannel, load_js_runtime, load_yt_dlp_extra_args,
+    load_yt_dlp_source,
+};
+
+use super::encoder::detect_encoder_profile;
+use super::{DownloadEvent, aria2, fragments};
+
+// macOS/Linux 向けの静的ビルド（macOS 版は h264_videotoolbox 有効）を配布している取得元
+const FFMPEG_RELEASE_BASE: &str = "https://ffmpeg.martin-riedl.de/redirect/latest";
 
 // yt-dlp が存在しない場合は取得し、実行権限を保証して返す。
 pub fn ensure_yt_dlp(tx: Option<&mpsc::Sender<DownloadEvent>>) -> Result<PathBuf, String> {
@@ -17,26 +34,45 @@ pub fn ensure_yt_dlp(tx: Option<&mpsc::Sender<DownloadEvent>>) -> Result<PathBuf
         return Ok(yt_dlp);
     }
 
+    // オフライン時は curl のタイムアウトを待たずに諦める。
+    ensure_online()?;
     let bin = bin_dir();
     ensure_dir(&bin)?;
     if let Some(tx) = tx {
         let _ = tx.send(DownloadEvent::Log(
-            "yt-dlpが見つかりません。ダウンロードします。".to_string(),
+            LogLevel::Warn,
+            tr_fmt(Msg::ToolMissingDownloading, &[&"yt-dlp"]),
         ));
     }
 
-    let url = "https://github.com/yt-dlp/yt-dlp/releases/latest/download/yt-dlp_macos";
-    curl_download(url, &yt_dlp, "yt-dlp")?;
+    let (channel, pinned) = load_yt_dlp_source();
+    curl_download(&yt_dlp_download_url(channel, &pinned), &yt_dlp, "yt-dlp")?;
 
     ensure_executable(&yt_dlp)?;
     if let Some(tx) = tx {
         let _ = tx.send(DownloadEvent::Log(
-            "yt-dlpをダウンロードしました。".to_string(),
+            LogLevel::Info,
+            tr_fmt(Msg::ToolDownloaded, &[&"yt-dlp"]),
         ));
     }
     Ok(yt_dlp)
 }
 
+// 取得元の設定から yt-dlp のダウンロード URL を組み立てる。固定バージョンが空なら最新を使う。
+pub(crate) fn yt_dlp_download_url(channel: YtDlpChannel, pinned: &str) -> String {
+    let repo = match channel {
+        YtDlpChannel::Stable => "yt-dlp/yt-dlp",
+        YtDlpChannel::Nightly => "yt-dlp/yt-dlp-nightly-builds",
+    };
+    let pinned = pinned.trim();
+    let asset = yt_dlp_asset_name();
+    if pinned.is_empty() {
+        format!("https://github.com/{repo}/releases/latest/download/{asset}")
+    } else {
+        format!("https://github.com/{repo}/releases/download/{pinned}/{asset}")
+    }
+}
+
 // deno が存在しない場合は ZIP 取得と展開を行い、実行権限を保証して返す。
 pub fn ensure_deno(tx: Option<&mpsc::Sender<DownloadEvent>>) -> Result<PathBuf, String> {
     let deno = deno_path();
@@ -45,41 +81,36 @@ pub fn ensure_deno(tx: Option<&mpsc::Sender<DownloadEvent>>) -> Result<PathBuf,
         return Ok(deno);
     }
 
+    ensure_online()?;
     let bin = bin_dir();
     ensure_dir(&bin)?;
     if let Some(tx) = tx {
         let _ = tx.send(DownloadEvent::Log(
-            "denoが見つかりません。ダウンロードします。".to_string(),
+            LogLevel::Warn,
+            tr_fmt(Msg::ToolMissingDownloading, &[&"deno"]),
         ));
     }
 
     let zip_path = bin.join("deno.zip");
-    let url =
-        "https://github.com/denoland/deno/releases/latest/download/deno-aarch64-apple-darwin.zip";
-    curl_download(url, &zip_path, "deno")?;
-
-    let status = Command::new("unzip")
-        .arg("-o")
-        .arg(zip_path.to_string_lossy().to_string())
-        .arg("-d")
-        .arg(bin.to_string_lossy().to_string())
-        .status()
-        .map_err(|err| format!("unzip起動に失敗しました: {err}"))?;
+    let url = format!(
+        "https://github.com/denoland/deno/releases/latest/download/deno-{}.zip",
+        deno_target()
+    );
+    curl_download(&url, &zip_path, "deno")?;
 
+    let extracted = extract_zip(&zip_path, &bin);
     let _ = fs::remove_file(&zip_path);
-
-    if !status.success() {
-        return Err(format!("denoの展開に失敗しました: {status}"));
-    }
+    extracted.map_err(|err| tr_fmt(Msg::ToolExtractFailed, &[&"deno", &err]))?;
 
     if !deno.exists() {
-        return Err("denoが見つかりません。".to_string());
+        return Err(tr_fmt(Msg::ToolNotFound, &[&"deno"]));
     }
 
     ensure_executable(&deno)?;
     if let Some(tx) = tx {
         let _ = tx.send(DownloadEvent::Log(
-            "denoをダウンロードしました。".to_string(),
+            LogLevel::Info,
+            tr_fmt(Msg::ToolDownloaded, &[&"deno"]),
         ));
     }
     Ok(deno)
@@ -87,51 +118,162 @@ pub fn ensure_deno(tx: Option<&mpsc::Sender<DownloadEvent>>) -> Result<PathBuf,
 
 // 既存バイナリをバックアップしてから更新し、失敗時はロールバックする。
 pub fn update_yt_dlp(tx: Option<&mpsc::Sender<DownloadEvent>>) -> Result<PathBuf, String> {
+    // オフライン時は既存バイナリを退避する前に中止する。
+    ensure_online()?;
     let yt_dlp = yt_dlp_path();
     update_tool_with_rollback(&yt_dlp, "yt-dlp", tx, ensure_yt_dlp)
 }
 
 // 既存バイナリをバックアップしてから更新し、失敗時はロールバックする。
 pub fn update_deno(tx: Option<&mpsc::Sender<DownloadEvent>>) -> Result<PathBuf, String> {
+    ensure_online()?;
     let deno = deno_path();
     update_tool_with_rollback(&deno, "deno", tx, ensure_deno)
 }
 
-// 実行可能な deno を探索し、yt-dlp に渡す runtime 指定文字列を返す。
-pub(super) fn js_runtime_arg() -> String {
-    match detect_deno_binary() {
-        Some(path) => format!("deno:{}", path.to_string_lossy()),
-        None => "deno".to_string(),
+// ffmpeg/ffprobe を最新版へ更新する。H.264 エンコーダを確認できない場合は元に戻す。
+pub fn update_ffmpeg(tx: Option<&mpsc::Sender<DownloadEvent>>) -> Result<PathBuf, String> {
+    ensure_online()?;
+    let ffmpeg = update_tool_with_rollback(&app_ffmpeg_path(), "ffmpeg", tx, |tx| {
+        let path = install_zipped_tool(
+            &ffmpeg_release_url("ffmpeg")?,
+            &app_ffmpeg_path(),
+            "ffmpeg",
+            tx,
+        )?;
+        detect_encoder_profile(&path, EncoderProfile::VideoToolboxH264)?;
+        Ok(path)
+    })?;
+    update_tool_with_rollback(&app_ffprobe_path(), "ffprobe", tx, |tx| {
+        let path = install_zipped_tool(
+            &ffmpeg_release_url("ffprobe")?,
+            &app_ffprobe_path(),
+            "ffprobe",
+            tx,
+        )?;
+        let status = Command::new(&path)
+            .arg("-version")
+            .status()
+            .map_err(|err| tr_fmt(Msg::FfprobeCheckFailed, &[&err]))?;
+        if !status.success() {
+            return Err(tr_fmt(Msg::FfprobeCheckFailed, &[&status]));
+        }
+        Ok(path)
+    })?;
+    // 以降は同梱版で上書きしないよう印を残す。
+    fs::write(ffmpeg_update_marker_path(), b"").map_err(|err| err.to_string())?;
+    Ok(ffmpeg)
+}
+
+// 実行中の OS/CPU に合わせた ffmpeg/ffprobe の取得 URL を返す。
+fn ffmpeg_release_url(name: &str) -> Result<String, String> {
+    let (os, arch) =
+        ffmpeg_release_platform().ok_or_else(|| tr(Msg::FfmpegUpdateUnsupported).to_string())?;
+    Ok(format!(
+        "{FFMPEG_RELEASE_BASE}/{os}/{arch}/release/{name}.zip"
+    ))
+}
+
+// ZIP に単体バイナリが入っている配布物を取得し、target へ展開する。
+fn install_zipped_tool(
+    url: &str,
+    target: &Path,
+    label: &str,
+    tx: Option<&mpsc::Sender<DownloadEvent>>,
+) -> Result<PathBuf, String> {
+    let bin = bin_dir();
+    ensure_dir(&bin)?;
+    if let Some(tx) = tx {
+        let _ = tx.send(DownloadEvent::Log(
+            LogLevel::Info,
+            tr_fmt(Msg::ToolDownloading, &[&label]),
+        ));
+    }
+
+    let zip_path = bin.join(format!("{label}.zip"));
+    curl_download(url, &zip_path, label)?;
+    let extracted = extract_zip(&zip_path, &bin);
+    let _ = fs::remove_file(&zip_path);
+    extracted.map_err(|err| tr_fmt(Msg::ToolExtractFailed, &[&label, &err]))?;
+    if !target.exists() {
+        return Err(tr_fmt(Msg::ToolNotFound, &[&label]));
+    }
+
+    ensure_executable(target)?;
+    if let Some(tx) = tx {
+        let _ = tx.send(DownloadEvent::Log(
+            LogLevel::Info,
+            tr_fmt(Msg::ToolDownloaded, &[&label]),
+        ));
+    }
+    Ok(target.to_path_buf())
+}
+
+// 設定の JavaScript ランタイムを探索し、yt-dlp に渡す引数を返す。
+// 使わない場合は、yt-dlp が既定で探すランタイムも無効にする。
+pub(super) fn js_runtime_args() -> Vec<String> {
+    let (name, path) = match load_js_runtime() {
+        JsRuntime::Deno => ("deno", detect_deno_binary()),
+        JsRuntime::Node => ("node", detect_node_binary()),
+        JsRuntime::Disabled => return vec!["--no-js-runtimes".to_string()],
+    };
+    let runtime = match path {
+        Some(path) => format!("{name}:{}", path.to_string_lossy()),
+        None => name.to_string(),
+    };
+    vec!["--js-runtimes".to_string(), runtime]
+}
+
+// `-f` に渡すフォーマット指定。max_height で映像の高さを制限し、
+// audio_language を指定した場合はその言語の音声を結合する（無ければ既定の音声）。
+fn format_selector(max_height: Option<u32>, audio_language: Option<&str>) -> Option<String> {
+    let height = max_height
+        .map(|height| format!("[height<={height}]"))
+        .unwrap_or_default();
+    match audio_language {
+        Some(language) => Some(format!(
+            "bv*{height}+ba[language={language}]/bv*{height}+ba/b{height}"
+        )),
+        None if max_height.is_some() => Some(format!("bv*{height}+ba/b{height}")),
+        None => None,
     }
 }
 
 // yt-dlp の通常ダウンロード用引数セットを組み立てる。
+// 画質の上限（設定かプリセット）を指定している場合は、その高さまでの映像を取得する。
 pub(super) fn base_yt_dlp_args(
     ffmpeg_path: &str,
-    cookie_args: &[String],
-    js_runtime: &str,
+    auth_args: &[String],
+    js_runtime: &[String],
+    audio_language: Option<&str>,
+    max_height: Option<u32>,
 ) -> Vec<String> {
     let mut args = vec!["--no-playlist".to_string()];
-    args.extend(cookie_args.iter().cloned());
+    args.extend(auth_args.iter().cloned());
     args.extend(vec![
         "--extractor-args".to_string(),
         "youtube:player_client=web".to_string(),
         "--extractor-args".to_string(),
         "youtube:skip=translated_subs".to_string(),
         "--concurrent-fragments".to_string(),
-        "4".to_string(),
+        fragments::concurrent_fragments().to_string(),
         "-S".to_string(),
         "vcodec:h264,res,acodec:m4a".to_string(),
         "--match-filter".to_string(),
         "vcodec~='(?i)^(avc|h264)'".to_string(),
     ]);
+    if let Some(selector) = format_selector(max_height, audio_language) {
+        args.push("-f".to_string());
+        args.push(selector);
+    }
 
     args.push("--merge-output-format".to_string());
     args.push("mp4".to_string());
     args.push("--ffmpeg-location".to_string());
     args.push(ffmpeg_path.to_string());
-    args.push("--js-runtimes".to_string());
-    args.push(js_runtime.to_string());
+    args.extend(js_runtime.iter().cloned());
+    args.extend(aria2::downloader_args());
+    args.extend(load_yt_dlp_extra_args());
 
     args
 }
@@ -139,34 +281,85 @@ pub(super) fn base_yt_dlp_args(
 // H.264 優先モードが失敗した場合のフォールバック引数セットを組み立てる。
 pub(super) fn fallback_yt_dlp_args(
     ffmpeg_path: &str,
-    cookie_args: &[String],
-    js_runtime: &str,
+    auth_args: &[String],
+    js_runtime: &[String],
+    audio_language: Option<&str>,
+    max_height: Option<u32>,
 ) -> Vec<String> {
     let mut args = vec!["--no-playlist".to_string()];
-    args.extend(cookie_args.iter().cloned());
+    args.extend(auth_args.iter().cloned());
     args.extend(vec![
         "--extractor-args".to_string(),
         "youtube:player_client=web".to_string(),
         "--extractor-args".to_string(),
         "youtube:skip=translated_subs".to_string(),
         "--concurrent-fragments".to_string(),
-        "4".to_string(),
+        fragments::concurrent_fragments().to_string(),
     ]);
 
+    // 互換モードは 720p までに抑える。上限がそれより低い場合は上限に従う。
+    let max_height = max_height.map_or(720, |height| height.min(720));
     args.push("-f".to_string());
-    args.push("bv*[height<=720]+ba/b[height<=720]".to_string());
-    args.push("--recode-video".to_string());
-    args.push("mp4".to_string());
-    args.push("--postprocessor-args".to_string());
-    args.push("VideoConvertor:-c:v h264_videotoolbox -b:v 5M -pix_fmt yuv420p".to_string());
+    args.extend(format_selector(Some(max_height), audio_language));
+    // どのコーデックの組み合わせでも結合できる mkv にし、mp4 への変換はダウンロード後に行う。
+    args.push("--merge-output-format".to_string());
+    args.push("mkv".to_string());
     args.push("--ffmpeg-location".to_string());
     args.push(ffmpeg_path.to_string());
-    args.push("--js-runtimes".to_string());
-    args.push(js_runtime.to_string());
+    args.extend(js_runtime.iter().cloned());
+    args.extend(aria2::downloader_args());
+    args.extend(load_yt_dlp_extra_args());
 
     args
 }
 
+// 形式を選んだ場合の引数セット。並べ替えやコーデックでの絞り込みはせず、選んだフォーマット ID を取得する。
+pub(super) fn chosen_format_yt_dlp_args(
+    ffmpeg_path: &str,
+    auth_args: &[String],
+    js_runtime: &[String],
+    selector: &str,
+) -> Vec<String> {
+    let mut args = vec!["--no-playlist".to_string()];
+    args.extend(auth_args.iter().cloned());
+    args.extend([
+        "--extractor-args".to_string(),
+        "youtube:player_client=web".to_string(),
+        "--extractor-args".to_string(),
+        "youtube:skip=translated_subs".to_string(),
+        "--concurrent-fragments".to_string(),
+        fragments::concurrent_fragments().to_string(),
+        "-f".to_string(),
+        selector.to_string(),
+        // 選んだ組み合わせのまま結合できる mkv にし、mp4 への変換はダウンロード後に行う。
+        "--merge-output-format".to_string(),
+        "mkv".to_string(),
+        "--ffmpeg-location".to_string(),
+        ffmpeg_path.to_string(),
+    ]);
+    args.extend(js_runtime.iter().cloned());
+    args.extend(aria2::downloader_args());
+    args.extend(load_yt_dlp_extra_args());
+    args
+}
+
+// Node.js は自動で取得しないため、Homebrew と PATH 上のものだけを探す。
+fn detect_node_binary() -> Option<PathBuf> {
+    let mut candidates = vec![
+        PathBuf::from("/opt/homebrew/bin/node"),
+        PathBuf::from("/usr/local/bin/node"),
+    ];
+    if let Some(path_env) = std::env::var_os("PATH") {
+        for dir in std::env::split_paths(&path_env) {
+            candidates.push(dir.join(executable_name("node")));
+        }
+    }
+
+    candidates
+        .into_iter()
+        .find(|candidate| candidate.exists() && is_executable(candidate))
+}
+
 fn detect_deno_binary() -> Option<PathBuf> {
     let mut candidates = Vec::new();
     if let Some(path) = std::env::var_os("DENO_PATH") {
@@ -178,14 +371,14 @@ fn detect_deno_binary() -> Option<PathBuf> {
     candidates.push(deno_path());
 
     if let Some(home) = dirs::home_dir() {
-        candidates.push(home.join(".deno").join("bin").join("deno"));
+        candidates.push(home.join(".deno").join("bin").join(executable_name("deno")));
     }
     candidates.push(PathBuf::from("/opt/homebrew/bin/deno"));
     candidates.push(PathBuf::from("/usr/local/bin/deno"));
 
     if let Some(path_env) = std::env::var_os("PATH") {
         for dir in std::env::split_paths(&path_env) {
-            candidates.push(dir.join("deno"));
+            candidates.push(dir.join(executable_name("deno")));
         }
     }
 
@@ -209,7 +402,7 @@ where
 
     let backup_path = next_backup_path(path);
     fs::rename(path, &backup_path)
-        .map_err(|err| format!("{label}の更新準備に失敗しました: {err}"))?;
+        .map_err(|err| tr_fmt(Msg::ToolUpdatePrepareFailed, &[&label, &err]))?;
 
     match installer(tx) {
         Ok(updated_path) => {
@@ -222,8 +415,9 @@ where
             }
             match fs::rename(&backup_path, path) {
                 Ok(()) => Err(err),
-                Err(restore_err) => Err(format!(
-                    "{label}の更新に失敗し、旧バージョンの復元にも失敗しました: {restore_err} (更新エラー: {err})"
+                Err(restore_err) => Err(tr_fmt(
+                    Msg::ToolRestoreFailed,
+                    &[&label, &restore_err, &err],
                 )),
             }
         }
@@ -253,29 +447,59 @@ fn next_backup_path(path: &Path) -> PathBuf {
     parent.join(format!("{file_name}.update-backup.fallback"))
 }
 
-fn ensure_executable(path: &Path) -> Result<(), String> {
-    let metadata = fs::metadata(path).map_err(|err| err.to_string())?;
-    let mut perms = metadata.permissions();
-    let mode = perms.mode();
-    if mode & 0o111 != 0o111 {
-        perms.set_mode(mode | 0o111);
-        fs::set_permissions(path, perms).map_err(|err| err.to_string())?;
-    }
-    Ok(())
-}
-
 fn curl_download(url: &str, output_path: &Path, label: &str) -> Result<(), String> {
+    // 存在しないリリースを指定した場合に 404 のページを保存しないよう -f を付ける。
     let status = Command::new("curl")
-        .arg("-L")
+        .arg("-fL")
         .arg("-o")
         .arg(output_path.to_string_lossy().to_string())
         .arg(url)
         .status()
-        .map_err(|err| format!("curl起動に失敗しました: {err}"))?;
+        .map_err(|err| tr_fmt(Msg::ToolLaunchFailed, &[&"curl", &err]))?;
 
     if status.success() {
         Ok(())
     } else {
-        Err(format!("{label}のダウンロードに失敗しました: {status}"))
+        Err(tr_fmt(Msg::ToolDownloadFailed, &[&label, &status]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_format_by_height_and_language() {
+        assert_eq!(format_selector(None, None), None);
+        assert_eq!(
+            format_selector(Some(1080), None).as_deref(),
+            Some("bv*[height<=1080]+ba/b[height<=1080]")
+        );
+        assert_eq!(
+            format_selector(None, Some("ja")).as_deref(),
+            Some("bv*+ba[language=ja]/bv*+ba/b")
+        );
+        assert_eq!(
+            format_selector(Some(720), Some("en")).as_deref(),
+            Some("bv*[height<=720]+ba[language=en]/bv*[height<=720]+ba/b[height<=720]")
+        );
+    }
+
+    #[test]
+    fn builds_yt_dlp_url_for_channel_and_pin() {
+        assert_eq!(
+            yt_dlp_download_url(YtDlpChannel::Stable, ""),
+            format!(
+                "https://github.com/yt-dlp/yt-dlp/releases/latest/download/{}",
+                yt_dlp_asset_name()
+            )
+        );
+        assert_eq!(
+            yt_dlp_download_url(YtDlpChannel::Nightly, " 2025.01.26.034637 "),
+            format!(
+                "https://github.com/yt-dlp/yt-dlp-nightly-builds/releases/download/2025.01.26.034637/{}",
+                yt_dlp_asset_name()
+            )
+        );
     }
 }
@@ -0,0 +1,185 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::fs_utils::ensure_dir;
+use crate::i18n::{Msg, tr, tr_fmt};
+use crate::network;
+use crate::paths::{ffmpeg_path, yt_dlp_path};
+use crate::redact::redact_line;
+use crate::settings::load_cookie_args;
+
+use super::convert::probe_media_info;
+use super::process::with_bin_path;
+use super::runner::{CommandRunner, CommandSpec, SystemRunner, read_output};
+use super::staging::{StagingDir, create_download_staging_dir};
+use super::{CancellationToken, tools};
+
+// 試しに取得する動画（YouTube に最初に投稿された約 19 秒の動画）
+const TRIAL_URL: &str = "https://www.youtube.com/watch?v=jNQXAC9IVRw";
+// 試しのダウンロードは小さい画質で済ませる。
+const TRIAL_MAX_HEIGHT: u32 = 360;
+const TRIAL_FILE_STEM: &str = "trial";
+
+// 試しのダウンロードで保存できた動画
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrialReport {
+    pub video_codec: String,
+    pub width: u32,
+    pub height: u32,
+    pub size_bytes: u64,
+}
+
+// 初回セットアップの最後に、短い動画を保存先の作業フォルダへダウンロードし、ffprobe で読めるかまで確かめる。
+// ダウンロードアーカイブと履歴には残さず、確かめた後は作業フォルダごと消す。
+pub fn run_trial_download(
+    output_dir: &Path,
+    cancel: &CancellationToken,
+) -> Result<TrialReport, String> {
+    let yt_dlp = yt_dlp_path();
+    if !yt_dlp.exists() {
+        return Err(tr(Msg::YtDlpNotFound).to_string());
+    }
+    let ffmpeg = ffmpeg_path();
+    if !ffmpeg.exists() {
+        return Err(tr(Msg::FfmpegNotFound).to_string());
+    }
+    network::ensure_online()?;
+    ensure_dir(output_dir).map_err(|err| tr_fmt(Msg::CreateOutputDirFailed, &[&err]))?;
+    let staging = StagingDir::new(create_download_staging_dir(output_dir)?);
+
+    let video = download_trial(&SystemRunner, &yt_dlp, &ffmpeg, staging.path(), cancel)?;
+    let info = probe_media_info(&SystemRunner, video.as_os_str(), None, cancel)?;
+    let Some(video_codec) = info.video_codec else {
+        return Err(tr(Msg::TrialNoVideoStream).to_string());
+    };
+    Ok(TrialReport {
+        video_codec,
+        width: info.width,
+        height: info.height,
+        size_bytes: fs::metadata(&video).map(|meta| meta.len()).unwrap_or(0),
+    })
+}
+
+// 通常のダウンロードと同じ引数（クッキー・JavaScript ランタイム・追加オプションを含む）で取得し、保存した mp4 を返す。
+fn download_trial(
+    runner: &dyn CommandRunner,
+    yt_dlp: &Path,
+    ffmpeg: &Path,
+    dir: &Path,
+    cancel: &CancellationToken,
+) -> Result<PathBuf, String> {
+    let mut args = tools::base_yt_dlp_args(
+        &ffmpeg.to_string_lossy(),
+        &load_cookie_args(""),
+        &tools::js_runtime_args(),
+        None,
+        Some(TRIAL_MAX_HEIGHT),
+    );
+    args.push("-o".to_string());
+    args.push(
+        dir.join(format!("{TRIAL_FILE_STEM}.%(ext)s"))
+            .to_string_lossy()
+            .to_string(),
+    );
+    args.push("--".to_string());
+    args.push(TRIAL_URL.to_string());
+
+    let spec = with_bin_path(CommandSpec::new(yt_dlp).args(args));
+    let (status, _, stderr) = read_output(runner, &spec, cancel, true)
+        .map_err(|err| tr_fmt(Msg::YtDlpRunFailed, &[&err]))?;
+    cancel.check()?;
+    if !status.success() {
+        // 最後のエラー行に理由が出る（ボット確認・ネットワークなど）。
+        let stderr = String::from_utf8_lossy(&stderr);
+        let reason = stderr
+            .lines()
+            .rev()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .map(redact_line)
+            .unwrap_or_else(|| status.to_string());
+        return Err(tr_fmt(Msg::TrialDownloadFailed, &[&reason]));
+    }
+
+    fs::read_dir(dir)
+        .ok()
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .find(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("mp4"))
+        })
+        .ok_or_else(|| tr(Msg::TrialNoFile).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::download::runner::fake::{FakeRun, FakeRunner};
+    use tempfile::tempdir;
+
+    #[test]
+    fn returns_downloaded_mp4_without_archive() {
+        let dir = tempdir().unwrap();
+        // yt-dlp の代わりに、保存される mp4 を先に置いておく。
+        let saved = dir.path().join("trial.mp4");
+        fs::write(&saved, b"").unwrap();
+        let runner = FakeRunner::new(vec![FakeRun::exit(0)]);
+
+        let video = download_trial(
+            &runner,
+            Path::new("yt-dlp"),
+            Path::new("ffmpeg"),
+            dir.path(),
+            &CancellationToken::new(),
+        )
+        .unwrap();
+        assert_eq!(video, saved);
+        let calls = runner.calls();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(
+            calls[0].args.last().and_then(|arg| arg.to_str()),
+            Some(TRIAL_URL)
+        );
+        assert!(!calls[0].args.iter().any(|arg| arg == "--download-archive"));
+    }
+
+    #[test]
+    fn reports_last_error_line() {
+        let dir = tempdir().unwrap();
+        let runner = FakeRunner::new(vec![FakeRun::Exit {
+            code: 1,
+            stdout: "",
+            stderr: "WARNING: slow\nERROR: [youtube] jNQXAC9IVRw: Sign in to confirm you're not a bot\n\n",
+        }]);
+
+        let err = download_trial(
+            &runner,
+            Path::new("yt-dlp"),
+            Path::new("ffmpeg"),
+            dir.path(),
+            &CancellationToken::new(),
+        )
+        .unwrap_err();
+        assert!(err.contains("Sign in to confirm"));
+        assert!(!err.contains("WARNING"));
+    }
+
+    #[test]
+    fn fails_when_nothing_was_saved() {
+        let dir = tempdir().unwrap();
+        let runner = FakeRunner::new(vec![FakeRun::exit(0)]);
+
+        let err = download_trial(
+            &runner,
+            Path::new("yt-dlp"),
+            Path::new("ffmpeg"),
+            dir.path(),
+            &CancellationToken::new(),
+        )
+        .unwrap_err();
+        assert_eq!(err, tr(Msg::TrialNoFile));
+    }
+}
@@ -0,0 +1,132 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+use crate::app_logger::LogLevel;
+use crate::faststart::remux_faststart;
+use crate::fs_utils::ensure_dir;
+use crate::i18n::{Msg, tr, tr_fmt};
+use crate::search_index::{Mp4Layout, read_mp4_layout};
+
+use super::convert::probe_media_info;
+use super::runner::{CommandRunner, CommandSpec, output};
+use super::staging::next_available_destination;
+use super::{CancellationToken, DownloadEvent};
+
+// 壊れた出力を移すフォルダ（保存先の直下）
+const QUARANTINE_DIR_NAME: &str = ".vjdownloader-quarantine";
+// デコードできるかを確かめる長さ（秒）
+const DECODE_CHECK_SECONDS: &str = "1";
+
+// moov が mdat より前にあり、断片化していないか。moov が無い場合は Err。
+fn check_faststart(path: &Path) -> Result<bool, String> {
+    match read_mp4_layout(path) {
+        Some(layout) => Ok(layout == Mp4Layout::Faststart),
+        None => Err(tr(Msg::NoMoovBox).to_string()),
+    }
+}
+
+// ffprobe で映像と長さを確認し、先頭をデコードできるか試す。
+fn check_playable(
+    runner: &dyn CommandRunner,
+    path: &Path,
+    ffmpeg: &Path,
+    cancel: &CancellationToken,
+) -> Result<(), String> {
+    let info = probe_media_info(runner, path.as_os_str(), None, cancel)?;
+    if info.video_codec.is_none() {
+        return Err(tr(Msg::NoVideoStream).to_string());
+    }
+    if !info.duration_seconds.is_some_and(|duration| duration > 0.0) {
+        return Err(tr(Msg::ZeroDuration).to_string());
+    }
+    let cmd = CommandSpec::new(ffmpeg)
+        .args(["-hide_banner", "-v", "error", "-xerror", "-i"])
+        .arg(path)
+        .args([
+            "-t",
+            DECODE_CHECK_SECONDS,
+            "-map",
+            "0:v:0",
+            "-f",
+            "null",
+            "-",
+        ]);
+    let output = output(runner, &cmd, cancel)
+        .map_err(|err| tr_fmt(Msg::ToolLaunchFailed, &[&"ffmpeg", &err]))?;
+    if !output.status.success() {
+        return Err(tr_fmt(
+            Msg::DecodeFailed,
+            &[&String::from_utf8_lossy(&output.stderr).trim()],
+        ));
+    }
+    Ok(())
+}
+
+// 壊れた出力を保存先の隔離フォルダへ移し、移動先を返す。
+fn quarantine(path: &Path, output_dir: &Path) -> Result<PathBuf, String> {
+    let quarantine_dir = output_dir.join(QUARANTINE_DIR_NAME);
+    ensure_dir(&quarantine_dir).map_err(|err| tr_fmt(Msg::QuarantineDirFailed, &[&err]))?;
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| tr(Msg::QuarantineNameInvalid).to_string())?;
+    let mut destination = quarantine_dir.join(file_name);
+    if destination.exists() {
+        destination = next_available_destination(&destination)?;
+    }
+    fs::rename(path, &destination).map_err(|err| tr_fmt(Msg::QuarantineFailed, &[&err]))?;
+    Ok(destination)
+}
+
+// 保存先へ移した MP4 を検証する。faststart でなければ作り直し、壊れていれば隔離してエラーにする。
+pub(super) fn validate_outputs(
+    runner: &dyn CommandRunner,
+    outputs: &[PathBuf],
+    output_dir: &Path,
+    ffmpeg: &Path,
+    tx: &mpsc::Sender<DownloadEvent>,
+    cancel: &CancellationToken,
+) -> Result<(), String> {
+    let mut broken = Vec::new();
+    for path in outputs {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let checked =
+            check_playable(runner, path, ffmpeg, cancel).and_then(|()| check_faststart(path));
+        // 確認中に取り消した場合は、途中で止めたことによる失敗を壊れたファイルとみなさない。
+        cancel.check()?;
+        match checked {
+            Ok(true) => {}
+            Ok(false) => {
+                let _ = tx.send(DownloadEvent::Log(
+                    LogLevel::Info,
+                    tr_fmt(Msg::FaststartRemux, &[&name]),
+                ));
+                if let Err(err) = remux_faststart(path, ffmpeg) {
+                    let _ = tx.send(DownloadEvent::Log(
+                        LogLevel::Error,
+                        tr_fmt(Msg::FaststartFailed, &[&name, &err]),
+                    ));
+                }
+            }
+            Err(reason) => {
+                let moved = quarantine(path, output_dir)?;
+                let _ = tx.send(DownloadEvent::Log(
+                    LogLevel::Error,
+                    tr_fmt(Msg::QuarantinedFile, &[&name, &reason, &moved.display()]),
+                ));
+                broken.push(name);
+            }
+        }
+    }
+    if broken.is_empty() {
+        Ok(())
+    } else {
+        Err(tr_fmt(
+            Msg::QuarantinedFiles,
+            &[&QUARANTINE_DIR_NAME, &broken.join(", ")],
+        ))
+    }
+}
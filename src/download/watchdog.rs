@@ -0,0 +1,130 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::settings::load_stall_timeout;
+
+use super::{CancellationToken, ProgressContext};
+
+// 応答がなかったダウンロードを自動でやり直す回数の上限
+pub const STALL_RETRY_LIMIT: u8 = 2;
+// 変換中は yt-dlp の結合など出力の少ない処理があるため、取得中の何倍まで待つか。
+const CONVERTING_FACTOR: u32 = 3;
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+// 外部プロセス（yt-dlp・curl・ffmpeg）の出力が止まったまま動かなくなったダウンロードを見張る。
+// 確認ダイアログの応答待ちなど、出力を待っていない間は見張らない。
+#[derive(Default)]
+pub(super) struct Watchdog {
+    state: Mutex<WatchState>,
+    // 止まっていると判断して中断した時の待ち時間（分）。0 は中断していない。
+    stalled_minutes: AtomicU32,
+}
+
+#[derive(Default)]
+struct WatchState {
+    // 出力を待っている外部プロセスの数
+    watching: usize,
+    last_activity: Option<Instant>,
+}
+
+// 破棄すると、その外部プロセスの出力を待つのをやめる。
+pub(super) struct WatchGuard<'a>(&'a Watchdog);
+
+impl Drop for WatchGuard<'_> {
+    fn drop(&mut self) {
+        let mut state = self.0.state.lock().unwrap();
+        state.watching = state.watching.saturating_sub(1);
+        state.last_activity = Some(Instant::now());
+    }
+}
+
+impl Watchdog {
+    // 外部プロセスの出力を待ち始める。
+    pub(super) fn watch(&self) -> WatchGuard<'_> {
+        let mut state = self.state.lock().unwrap();
+        state.watching += 1;
+        state.last_activity = Some(Instant::now());
+        WatchGuard(self)
+    }
+
+    // ログの行や受信したデータが届いた。
+    pub(super) fn touch(&self) {
+        self.state.lock().unwrap().last_activity = Some(Instant::now());
+    }
+
+    pub(super) fn stalled_minutes(&self) -> Option<u32> {
+        Some(self.stalled_minutes.load(Ordering::Relaxed)).filter(|minutes| *minutes > 0)
+    }
+
+    // 出力を待っている間に、最後の出力から経った時間
+    fn idle_for(&self, now: Instant) -> Option<Duration> {
+        let state = self.state.lock().unwrap();
+        if state.watching == 0 {
+            return None;
+        }
+        Some(now.saturating_duration_since(state.last_activity?))
+    }
+}
+
+// 段階ごとの待ち時間（分）。0 は見張らない。
+fn stall_limit_minutes(minutes: u32, converting: bool) -> u32 {
+    if converting {
+        minutes.saturating_mul(CONVERTING_FACTOR)
+    } else {
+        minutes
+    }
+}
+
+// 設定の時間だけ出力が止まったら、中断してダウンロードを終わらせる。
+pub(super) fn start_watchdog(progress: Arc<ProgressContext>, cancel: CancellationToken) {
+    let (minutes, _) = load_stall_timeout();
+    if minutes == 0 {
+        return;
+    }
+    thread::spawn(move || {
+        while progress.is_active() && !cancel.is_cancelled() {
+            thread::sleep(CHECK_INTERVAL);
+            let limit = stall_limit_minutes(minutes, progress.post_processing());
+            let stalled = progress
+                .watchdog
+                .idle_for(Instant::now())
+                .is_some_and(|idle| idle >= Duration::from_secs(u64::from(limit) * 60));
+            if stalled && progress.is_active() {
+                progress
+                    .watchdog
+                    .stalled_minutes
+                    .store(limit, Ordering::Relaxed);
+                cancel.cancel();
+                return;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measures_idle_time_only_while_watching() {
+        let watchdog = Watchdog::default();
+        let now = Instant::now();
+        assert_eq!(watchdog.idle_for(now + Duration::from_secs(600)), None);
+
+        {
+            let _guard = watchdog.watch();
+            let later = Instant::now() + Duration::from_secs(90);
+            assert!(watchdog.idle_for(later).unwrap() >= Duration::from_secs(90));
+            watchdog.touch();
+            assert!(watchdog.idle_for(Instant::now()).unwrap() < Duration::from_secs(90));
+        }
+        // 応答待ちの間などは、どれだけ経っても止まったとみなさない。
+        assert_eq!(watchdog.idle_for(now + Duration::from_secs(600)), None);
+        assert_eq!(watchdog.stalled_minutes(), None);
+
+        assert_eq!(stall_limit_minutes(5, false), 5);
+        assert_eq!(stall_limit_minutes(5, true), 15);
+    }
+}
@@ -0,0 +1,103 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+use crate::app_logger::LogLevel;
+use crate::i18n::{Msg, tr_fmt};
+use crate::paths::make_absolute_path;
+use crate::settings::{WatermarkPosition, load_watermark_settings};
+
+use super::DownloadEvent;
+
+// 透かしを映像の端から離す距離（px）
+const WATERMARK_MARGIN: u32 = 20;
+
+// 変換時に映像へ重ねる PNG の透かし
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) struct Watermark {
+    pub path: PathBuf,
+    pub position: WatermarkPosition,
+    // 不透明度（0〜100%）
+    pub opacity: u8,
+}
+
+impl Watermark {
+    // overlay に渡す位置。W/H は映像、w/h は透かしの大きさ。
+    fn overlay_position(&self) -> String {
+        let m = WATERMARK_MARGIN;
+        match self.position {
+            WatermarkPosition::TopLeft => format!("{m}:{m}"),
+            WatermarkPosition::TopRight => format!("W-w-{m}:{m}"),
+            WatermarkPosition::BottomLeft => format!("{m}:H-h-{m}"),
+            WatermarkPosition::BottomRight => format!("W-w-{m}:H-h-{m}"),
+            WatermarkPosition::Center => "(W-w)/2:(H-h)/2".to_string(),
+        }
+    }
+
+    // 2 つ目の入力（PNG）を映像に重ねる `-filter_complex` の指定。chain は先に掛けるフィルタ。
+    // 結果の映像は [v] に出力する。
+    pub(super) fn filter_complex(&self, chain: Option<&str>) -> String {
+        let alpha = format!(
+            "[1:v]format=rgba,colorchannelmixer=aa={:.2}[wm]",
+            f64::from(self.opacity) / 100.0
+        );
+        let overlay = format!("overlay={}:format=auto[v]", self.overlay_position());
+        match chain {
+            Some(chain) => format!("[0:v]{chain}[base];{alpha};[base][wm]{overlay}"),
+            None => format!("{alpha};[0:v][wm]{overlay}"),
+        }
+    }
+}
+
+fn is_png(path: &Path) -> bool {
+    path.extension()
+        .and_then(|value| value.to_str())
+        .is_some_and(|value| value.eq_ignore_ascii_case("png"))
+}
+
+// 設定の透かしを読み込む。PNG が見つからない場合はログを出して重ねない。
+pub(super) fn load_watermark(tx: &mpsc::Sender<DownloadEvent>) -> Option<Watermark> {
+    let (path, position, opacity) = load_watermark_settings();
+    if path.is_empty() {
+        return None;
+    }
+    let path = make_absolute_path(&path);
+    if !path.is_file() || !is_png(&path) {
+        let _ = tx.send(DownloadEvent::Log(
+            LogLevel::Warn,
+            tr_fmt(Msg::WatermarkMissing, &[&path.display()]),
+        ));
+        return None;
+    }
+    Some(Watermark {
+        path,
+        position,
+        opacity,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_overlay_graph() {
+        let watermark = Watermark {
+            path: PathBuf::from("/tmp/tag.png"),
+            position: WatermarkPosition::BottomRight,
+            opacity: 80,
+        };
+        assert_eq!(
+            watermark.filter_complex(None),
+            "[1:v]format=rgba,colorchannelmixer=aa=0.80[wm];[0:v][wm]overlay=W-w-20:H-h-20:format=auto[v]"
+        );
+        let centered = Watermark {
+            position: WatermarkPosition::Center,
+            opacity: 100,
+            ..watermark
+        };
+        assert_eq!(
+            centered.filter_complex(Some("yadif")),
+            "[0:v]yadif[base];[1:v]format=rgba,colorchannelmixer=aa=1.00[wm];[base][wm]overlay=(W-w)/2:(H-h)/2:format=auto[v]"
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use eframe::egui;
+
+use crate::app::DownloaderApp;
+use crate::cursor::pointing;
+use crate::i18n::{Msg, tr};
+use crate::toast_ui::ToastKind;
+
+pub fn render_dry_run(
+    // 表示中のドライランの結果を持つアプリ
+    app: &mut DownloaderApp,
+    // ウィンドウ表示とクリップボードに使うコンテキスト
+    ctx: &egui::Context,
+) {
+    let Some(report) = app.dry_run_report.as_ref() else {
+        return;
+    };
+
+    let mut open = true;
+    let mut close = false;
+    let mut copied = None;
+    egui::Window::new(tr(Msg::DryRunTitle))
+        .collapsible(false)
+        .resizable(true)
+        .default_width(560.0)
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .open(&mut open)
+        .show(ctx, |ui| {
+            ui.add(
+                egui::Label::new(
+                    egui::RichText::new(&report.url)
+                        .size(12.0)
+                        .color(egui::Color32::from_rgb(220, 230, 245)),
+                )
+                .truncate(),
+            );
+
+            egui::ScrollArea::vertical()
+                .max_height(360.0)
+                .auto_shrink([false, true])
+                .show(ui, |ui| {
+                    for command in &report.commands {
+                        ui.add_space(8.0);
+                        ui.horizontal(|ui| {
+                            ui.label(
+                                egui::RichText::new(command.label)
+                                    .size(12.0)
+                                    .color(egui::Color32::from_rgb(150, 160, 180)),
+                            );
+                            ui.with_layout(
+                                egui::Layout::right_to_left(egui::Align::Center),
+                                |ui| {
+                                    if pointing(ui.small_button(tr(Msg::CopyCommand))).clicked() {
+                                        copied = Some(command.line.clone());
+                                    }
+                                },
+                            );
+                        });
+                        // 選択してコピーできるよう、編集できない入力欄に出す。
+                        let mut line = command.line.as_str();
+                        ui.add(
+                            egui::TextEdit::multiline(&mut line)
+                                .font(egui::TextStyle::Monospace)
+                                .desired_width(f32::INFINITY)
+                                .desired_rows(2),
+                        );
+                    }
+
+                    if !report.notes.is_empty() {
+                        ui.add_space(8.0);
+                    }
+                    for note in &report.notes {
+                        ui.add(
+                            egui::Label::new(
+                                egui::RichText::new(format!("• {note}"))
+                                    .size(11.5)
+                                    .color(egui::Color32::from_rgb(150, 160, 180)),
+                            )
+                            .wrap(),
+                        );
+                    }
+                });
+
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let copy_btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::CopyAllCommands))
+                            .size(12.5)
+                            .color(egui::Color32::from_rgb(8, 14, 24)),
+                    )
+                    .fill(egui::Color32::from_rgb(16, 190, 255));
+                    if pointing(ui.add_enabled(!report.commands.is_empty(), copy_btn)).clicked() {
+                        copied = Some(report.all_lines());
+                    }
+
+                    let close_btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::Close))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(180, 190, 210)),
+                    )
+                    .fill(egui::Color32::from_rgb(24, 30, 45));
+                    if pointing(ui.add(close_btn)).clicked() {
+                        close = true;
+                    }
+                });
+            });
+        });
+
+    if let Some(text) = copied {
+        ctx.copy_text(text);
+        app.push_toast(ToastKind::Success, tr(Msg::CommandsCopied));
+    }
+    if close || !open {
+        app.dry_run_report = None;
+    }
+}
@@ -0,0 +1,91 @@
+use eframe::egui;
+
+use crate::app::DownloaderApp;
+use crate::cursor::pointing;
+use crate::i18n::{Msg, tr};
+
+// 失敗の理由を見た後の操作
+enum ErrorAnswer {
+    OpenSettings,
+    ViewLogs,
+    Close,
+}
+
+pub fn render_error_dialog(
+    // 対処できる失敗を持つアプリ
+    app: &mut DownloaderApp,
+    // ウィンドウ表示に使うコンテキスト
+    ctx: &egui::Context,
+) {
+    let Some(kind) = app.error_dialog else {
+        return;
+    };
+
+    let mut open = true;
+    let mut answer = None;
+    egui::Window::new(kind.title())
+        .collapsible(false)
+        .resizable(false)
+        .default_width(380.0)
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .open(&mut open)
+        .show(ctx, |ui| {
+            ui.add(
+                egui::Label::new(
+                    egui::RichText::new(kind.description())
+                        .size(12.0)
+                        .color(egui::Color32::from_rgb(220, 230, 245)),
+                )
+                .wrap(),
+            );
+
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let settings_btn = egui::Button::new(
+                        egui::RichText::new(kind.action_label())
+                            .size(12.5)
+                            .color(egui::Color32::from_rgb(8, 14, 24)),
+                    )
+                    .fill(egui::Color32::from_rgb(16, 190, 255));
+                    if pointing(ui.add(settings_btn)).clicked() {
+                        answer = Some(ErrorAnswer::OpenSettings);
+                    }
+
+                    let logs_btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::ViewLogs))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(180, 190, 210)),
+                    )
+                    .fill(egui::Color32::from_rgb(24, 30, 45));
+                    if pointing(ui.add(logs_btn)).clicked() {
+                        answer = Some(ErrorAnswer::ViewLogs);
+                    }
+
+                    let close_btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::Close))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(180, 190, 210)),
+                    )
+                    .fill(egui::Color32::from_rgb(24, 30, 45));
+                    if pointing(ui.add(close_btn)).clicked() {
+                        answer = Some(ErrorAnswer::Close);
+                    }
+                });
+            });
+        });
+
+    if !open {
+        answer = Some(ErrorAnswer::Close);
+    }
+    let Some(answer) = answer else {
+        return;
+    };
+    app.error_dialog = None;
+    match answer {
+        // Cookie・詳細オプションはどちらも設定画面にある。
+        ErrorAnswer::OpenSettings => app.settings_ui.open_settings(),
+        ErrorAnswer::ViewLogs => app.log_ui.open_logs(),
+        ErrorAnswer::Close => {}
+    }
+}
@@ -0,0 +1,355 @@
+use serde::Serialize;
+use std::io::ErrorKind;
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use tungstenite::handshake::server::{Callback, ErrorResponse, Request, Response};
+use tungstenite::http::{StatusCode, header};
+use tungstenite::{Message, WebSocket};
+
+use crate::download::{DownloadEvent, DownloadStage, FailureKind};
+use crate::i18n::{Msg, tr, tr_fmt};
+use crate::redact::redact_line;
+
+pub const DEFAULT_EVENT_STREAM_PORT: u16 = 9002;
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+const CLIENT_IO_TIMEOUT: Duration = Duration::from_secs(1);
+
+// WebSocket へ配信するイベント。type フィールドで種類を区別する。
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamEvent {
+    Log {
+        message: String,
+    },
+    Progress {
+        message: String,
+        progress: f32,
+        visible: bool,
+        // DownloadStage::as_key。待機中に戻った場合は idle。
+        stage: &'static str,
+    },
+    Done {
+        ok: bool,
+        cancelled: bool,
+        error: Option<String>,
+        elapsed: String,
+    },
+    Queue {
+        queued: usize,
+    },
+}
+
+impl From<&DownloadEvent> for StreamEvent {
+    fn from(event: &DownloadEvent) -> Self {
+        match event {
+            DownloadEvent::Log(_, line) | DownloadEvent::ToolLog(_, line) => StreamEvent::Log {
+                message: redact_line(line),
+            },
+            DownloadEvent::Stage(DownloadStage::Done, elapsed) => StreamEvent::Done {
+                ok: true,
+                cancelled: false,
+                error: None,
+                elapsed: elapsed.clone(),
+            },
+            DownloadEvent::Stage(DownloadStage::Failed { kind }, elapsed) => StreamEvent::Done {
+                ok: false,
+                cancelled: *kind == FailureKind::Cancelled,
+                error: (*kind != FailureKind::Cancelled).then(|| redact_line(kind.message())),
+                elapsed: elapsed.clone(),
+            },
+            DownloadEvent::Stage(stage, elapsed) => StreamEvent::Progress {
+                message: stage.message(elapsed),
+                progress: stage.reported_progress(),
+                visible: true,
+                stage: stage.as_key(),
+            },
+            DownloadEvent::Idle => StreamEvent::Progress {
+                message: String::new(),
+                progress: 0.0,
+                visible: false,
+                stage: "idle",
+            },
+            DownloadEvent::CropPreview(preview) => StreamEvent::Log {
+                message: tr_fmt(Msg::StreamCropPending, &[&preview.crop.label()]),
+            },
+            DownloadEvent::AudioTrackPrompt(prompt) => StreamEvent::Log {
+                message: tr_fmt(
+                    Msg::StreamAudioTrackPending,
+                    &[&prompt
+                        .tracks
+                        .iter()
+                        .map(|track| track.language.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")],
+                ),
+            },
+            DownloadEvent::MetadataPreview(preview) => StreamEvent::Log {
+                message: tr_fmt(Msg::StreamPreviewPending, &[&preview.title]),
+            },
+            DownloadEvent::FormatPrompt(prompt) => StreamEvent::Log {
+                message: tr_fmt(Msg::StreamFormatPending, &[&prompt.formats.len()]),
+            },
+            DownloadEvent::CollisionPrompt(prompt) => StreamEvent::Log {
+                message: tr_fmt(Msg::StreamCollisionPending, &[&prompt.file_name]),
+            },
+            DownloadEvent::AlreadyDownloaded(path) => StreamEvent::Log {
+                message: match path {
+                    Some(path) => tr_fmt(Msg::StreamAlreadyDownloaded, &[&path.display()]),
+                    None => tr(Msg::StreamAlreadyInArchive).to_string(),
+                },
+            },
+            DownloadEvent::Downloaded => StreamEvent::Log {
+                message: tr(Msg::StreamDownloaded).to_string(),
+            },
+            DownloadEvent::KnownError(kind) => StreamEvent::Log {
+                message: tr_fmt(Msg::StreamKnownError, &[&kind.as_key()]),
+            },
+        }
+    }
+}
+
+enum StreamCommand {
+    Publish(StreamEvent),
+    Stop,
+}
+
+// ローカルの WebSocket 接続へダウンロードイベントを配信する。破棄時に配信スレッドを止める。
+pub struct EventStreamServer {
+    tx: mpsc::Sender<StreamCommand>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl EventStreamServer {
+    // allowed_origins は接続を許すブラウザの Origin（parse_allowed_origins で整えたもの）。
+    pub fn start(port: u16, allowed_origins: Vec<String>) -> Result<Self, String> {
+        let listener = TcpListener::bind(("127.0.0.1", port))
+            .map_err(|err| tr_fmt(Msg::EventStreamPortOpenFailed, &[&port, &err]))?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|err| err.to_string())?;
+
+        let (tx, rx) = mpsc::channel();
+        let worker = thread::spawn(move || serve_loop(listener, rx, allowed_origins));
+        Ok(Self {
+            tx,
+            worker: Some(worker),
+        })
+    }
+
+    pub fn publish(&self, event: StreamEvent) {
+        let _ = self.tx.send(StreamCommand::Publish(event));
+    }
+}
+
+impl Drop for EventStreamServer {
+    fn drop(&mut self) {
+        let _ = self.tx.send(StreamCommand::Stop);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn serve_loop(
+    listener: TcpListener,
+    rx: mpsc::Receiver<StreamCommand>,
+    allowed_origins: Vec<String>,
+) {
+    let mut clients: Vec<WebSocket<TcpStream>> = Vec::new();
+    // 新しく接続したクライアントへ現在の状態を伝えるため、直近の進捗と待ち件数を保持する。
+    let mut last_progress: Option<String> = None;
+    let mut last_queue: Option<String> = None;
+
+    loop {
+        loop {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    let Some(mut client) = accept_client(stream, &allowed_origins) else {
+                        continue;
+                    };
+                    if last_progress
+                        .iter()
+                        .chain(last_queue.iter())
+                        .all(|payload| client.send(Message::text(payload.clone())).is_ok())
+                    {
+                        clients.push(client);
+                    }
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(_) => break,
+            }
+        }
+
+        let event = match rx.recv_timeout(POLL_INTERVAL) {
+            Ok(StreamCommand::Publish(event)) => event,
+            Ok(StreamCommand::Stop) | Err(RecvTimeoutError::Disconnected) => break,
+            Err(RecvTimeoutError::Timeout) => continue,
+        };
+        let Ok(payload) = serde_json::to_string(&event) else {
+            continue;
+        };
+        match event {
+            StreamEvent::Progress { .. } => last_progress = Some(payload.clone()),
+            StreamEvent::Queue { .. } => last_queue = Some(payload.clone()),
+            StreamEvent::Done { .. } => last_progress = None,
+            StreamEvent::Log { .. } => {}
+        }
+        clients.retain_mut(|client| client.send(Message::text(payload.clone())).is_ok());
+    }
+
+    for mut client in clients {
+        let _ = client.close(None);
+        let _ = client.flush();
+    }
+}
+
+fn accept_client(stream: TcpStream, allowed_origins: &[String]) -> Option<WebSocket<TcpStream>> {
+    stream.set_nonblocking(false).ok()?;
+    stream.set_read_timeout(Some(CLIENT_IO_TIMEOUT)).ok()?;
+    stream.set_write_timeout(Some(CLIENT_IO_TIMEOUT)).ok()?;
+    tungstenite::accept_hdr(stream, OriginCheck(allowed_origins)).ok()
+}
+
+// ブラウザは Origin ヘッダーを付けて接続するため、開いているページから配信を読まれないよう
+// 許可した Origin 以外は 403 で断る。Origin を付けない外部ツールからの接続は受け付ける。
+struct OriginCheck<'a>(&'a [String]);
+
+impl Callback for OriginCheck<'_> {
+    fn on_request(self, request: &Request, response: Response) -> Result<Response, ErrorResponse> {
+        let allowed = match request.headers().get(header::ORIGIN) {
+            None => true,
+            Some(origin) => origin
+                .to_str()
+                .is_ok_and(|origin| self.0.contains(&normalize_origin(origin))),
+        };
+        if allowed {
+            return Ok(response);
+        }
+        let mut rejection = ErrorResponse::new(None);
+        *rejection.status_mut() = StatusCode::FORBIDDEN;
+        Err(rejection)
+    }
+}
+
+fn normalize_origin(origin: &str) -> String {
+    origin.trim().trim_end_matches('/').to_ascii_lowercase()
+}
+
+// カンマ区切りの許可する Origin を分け、空欄と重複を除く。
+pub fn parse_allowed_origins(raw: &str) -> Vec<String> {
+    let mut origins: Vec<String> = Vec::new();
+    for origin in raw.split(',').map(normalize_origin) {
+        if !origin.is_empty() && !origins.contains(&origin) {
+            origins.push(origin);
+        }
+    }
+    origins
+}
+
+// ポートの入力値を検証する。空欄は既定ポート。
+pub fn parse_event_stream_port(raw: &str) -> Result<u16, String> {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return Ok(DEFAULT_EVENT_STREAM_PORT);
+    }
+    match trimmed.parse::<u16>() {
+        Ok(port) if port > 0 => Ok(port),
+        _ => Err(tr(Msg::EventStreamPortInvalid).to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_download_events_with_type_tag() {
+        let done = StreamEvent::from(&DownloadEvent::Stage(
+            DownloadStage::Failed {
+                kind: FailureKind::Cancelled,
+            },
+            "3s".to_string(),
+        ));
+        assert_eq!(
+            serde_json::to_value(&done).unwrap(),
+            serde_json::json!({
+                "type": "done",
+                "ok": false,
+                "cancelled": true,
+                "error": null,
+                "elapsed": "3s",
+            })
+        );
+        let progress = StreamEvent::from(&DownloadEvent::Stage(
+            DownloadStage::Fetching {
+                bytes: 1,
+                total: Some(4),
+            },
+            String::new(),
+        ));
+        let progress = serde_json::to_value(&progress).unwrap();
+        assert_eq!(progress["stage"], "fetching");
+        assert_eq!(progress["progress"], 0.25);
+        let queue = serde_json::to_value(StreamEvent::Queue { queued: 2 }).unwrap();
+        assert_eq!(queue, serde_json::json!({ "type": "queue", "queued": 2 }));
+    }
+
+    #[test]
+    fn streams_events_to_connected_clients() {
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let server = EventStreamServer::start(port, Vec::new()).unwrap();
+        let (mut client, _) = tungstenite::connect(format!("ws://127.0.0.1:{port}")).unwrap();
+        // 接続がサーバー側で受理されるまで待ってから配信する。
+        thread::sleep(POLL_INTERVAL * 3);
+
+        server.publish(StreamEvent::Log {
+            message: "hello".to_string(),
+        });
+        let message = client.read().unwrap();
+        assert_eq!(
+            message.to_text().unwrap(),
+            r#"{"type":"log","message":"hello"}"#
+        );
+    }
+
+    #[test]
+    fn rejects_browser_origins_unless_allowed() {
+        use tungstenite::client::IntoClientRequest;
+
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let _server = EventStreamServer::start(
+            port,
+            parse_allowed_origins(" https://Dashboard.example/ , ,https://dashboard.example"),
+        )
+        .unwrap();
+        let connect = |origin: &str| {
+            let mut request = format!("ws://127.0.0.1:{port}")
+                .into_client_request()
+                .unwrap();
+            request
+                .headers_mut()
+                .insert(header::ORIGIN, origin.parse().unwrap());
+            tungstenite::connect(request)
+        };
+
+        match connect("https://evil.example") {
+            Err(tungstenite::Error::Http(response)) => {
+                assert_eq!(response.status(), StatusCode::FORBIDDEN);
+            }
+            other => panic!("unexpected handshake result: {:?}", other.map(|_| ())),
+        }
+        assert!(connect("https://dashboard.example").is_ok());
+        // Origin を付けない外部ツールはこれまでどおり接続できる
+        assert!(tungstenite::connect(format!("ws://127.0.0.1:{port}")).is_ok());
+    }
+}
@@ -0,0 +1,123 @@
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+
+use crate::i18n::{Msg, tr_fmt};
+use crate::paths::ffmpeg_path;
+use crate::search_index::{Mp4Layout, read_mp4_layout};
+
+// 作り直す前の元ファイルを残すときの拡張子（mp4 ではないため検索インデックスに入らない）
+const BACKUP_EXTENSION: &str = "bak";
+
+// 検索インデックス全体の faststart 確認の状態
+#[derive(Clone, Debug, Default)]
+pub enum FaststartAuditState {
+    #[default]
+    Idle,
+    Running {
+        checked: usize,
+        total: usize,
+    },
+    Done(FaststartAuditReport),
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FaststartAuditReport {
+    pub checked: usize,
+    // moov を先頭へ移したファイル
+    pub fixed: Vec<PathBuf>,
+    // moov が無いなど、構造を読めなかったファイル
+    pub unreadable: Vec<PathBuf>,
+    // 作り直しに失敗したファイルと理由
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+// ストリームをコピーしたまま moov を先頭へ移す。
+// Finder タグなどの拡張属性を残すため、元のファイルへ中身を書き戻す。
+pub fn remux_faststart(path: &Path, ffmpeg: &Path) -> Result<(), String> {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let temp = path.with_file_name(format!(".{file_name}.faststart"));
+    let output = Command::new(ffmpeg)
+        .arg("-hide_banner")
+        .arg("-v")
+        .arg("error")
+        .arg("-i")
+        .arg(path)
+        .arg("-map")
+        .arg("0")
+        .arg("-c")
+        .arg("copy")
+        .arg("-movflags")
+        .arg("+faststart")
+        .arg("-f")
+        .arg("mp4")
+        .arg("-y")
+        .arg(&temp)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|err| tr_fmt(Msg::FaststartFfmpegFailed, &[&err]))?;
+    let result = if output.status.success() {
+        overwrite_contents(&temp, path)
+            .map_err(|err| tr_fmt(Msg::FaststartWriteBackFailed, &[&err]))
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    };
+    let _ = fs::remove_file(&temp);
+    result
+}
+
+fn overwrite_contents(src: &Path, dest: &Path) -> io::Result<()> {
+    let mut reader = File::open(src)?;
+    let mut writer = OpenOptions::new().write(true).truncate(true).open(dest)?;
+    io::copy(&mut reader, &mut writer)?;
+    writer.sync_all()
+}
+
+// 元ファイルを `<名前>.bak` として残してから作り直す。
+fn fix_with_backup(path: &Path, ffmpeg: &Path) -> Result<(), String> {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(format!(".{BACKUP_EXTENSION}"));
+    let backup = PathBuf::from(backup);
+    if backup.exists() {
+        return Err(tr_fmt(Msg::FaststartBackupExists, &[&backup.display()]));
+    }
+    fs::copy(path, &backup).map_err(|err| tr_fmt(Msg::FaststartBackupFailed, &[&err]))?;
+    remux_faststart(path, ffmpeg).inspect_err(|_| {
+        let _ = fs::remove_file(&backup);
+    })
+}
+
+// 別スレッドで各ファイルを確認し、faststart でないものを作り直す。進捗は tx へ送る。
+pub fn start_faststart_audit(paths: Vec<PathBuf>, tx: mpsc::Sender<FaststartAuditState>) {
+    thread::spawn(move || {
+        let ffmpeg = ffmpeg_path();
+        let total = paths.len();
+        let mut report = FaststartAuditReport::default();
+        for path in paths {
+            match read_mp4_layout(&path) {
+                Some(Mp4Layout::Faststart) => {}
+                Some(Mp4Layout::MoovAtEnd | Mp4Layout::Fragmented) => {
+                    match fix_with_backup(&path, &ffmpeg) {
+                        Ok(()) => report.fixed.push(path),
+                        Err(err) => report.failed.push((path, err)),
+                    }
+                }
+                // 確認中に消えたファイルは数えない。
+                None if !path.is_file() => {}
+                None => report.unreadable.push(path),
+            }
+            report.checked += 1;
+            let _ = tx.send(FaststartAuditState::Running {
+                checked: report.checked,
+                total,
+            });
+        }
+        let _ = tx.send(FaststartAuditState::Done(report));
+    });
+}
@@ -0,0 +1,406 @@
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+
+use time::{OffsetDateTime, UtcOffset};
+
+use crate::search_index::SearchHit;
+
+// 一覧に表示するファイルと、列に表示するサイズ・長さ。長さはインデックスで読めた場合だけ分かる。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ListedFile {
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    pub duration_ms: Option<i64>,
+    // 更新日時（UNIX 秒）
+    pub modified_time: i64,
+    // 中身がクラウドにだけあるか
+    pub is_cloud: bool,
+}
+
+impl From<SearchHit> for ListedFile {
+    fn from(hit: SearchHit) -> Self {
+        Self {
+            path: PathBuf::from(hit.path),
+            size_bytes: u64::try_from(hit.size_bytes).unwrap_or(0),
+            duration_ms: hit.duration_ms,
+            modified_time: hit.modified_time,
+            is_cloud: hit.is_cloud,
+        }
+    }
+}
+
+// ダウンロード一覧を更新日で分ける区切り
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DateGroup {
+    Today,
+    Yesterday,
+    ThisWeek,
+    Older,
+}
+
+impl DateGroup {
+    pub const ALL: [DateGroup; 4] = [
+        DateGroup::Today,
+        DateGroup::Yesterday,
+        DateGroup::ThisWeek,
+        DateGroup::Older,
+    ];
+
+    // 更新日時（UNIX 秒）を、now の日付から数えた日数で分ける。今週は一昨日から 6 日前まで。
+    // 未来の日時は今日として扱う。
+    pub fn of(modified_time: i64, now: OffsetDateTime) -> Self {
+        let Ok(modified) = OffsetDateTime::from_unix_timestamp(modified_time) else {
+            return DateGroup::Older;
+        };
+        let days = (now.date() - modified.to_offset(now.offset()).date()).whole_days();
+        match days {
+            ..=0 => DateGroup::Today,
+            1 => DateGroup::Yesterday,
+            2..=6 => DateGroup::ThisWeek,
+            _ => DateGroup::Older,
+        }
+    }
+}
+
+// 日付の区切りに使う現在時刻。地域の時差が取れない場合は UTC で数える。
+pub fn local_now() -> OffsetDateTime {
+    let offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
+    OffsetDateTime::now_utc().to_offset(offset)
+}
+
+// 列見出しで並べ替えられる列
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FileSortColumn {
+    Name,
+    Size,
+    Duration,
+}
+
+// 列見出しで選んだ並び順
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FileSort {
+    pub column: FileSortColumn,
+    pub descending: bool,
+}
+
+impl FileSort {
+    // 見出しのクリックで並び順を切り替え、同じ列の 3 回目で元の並びに戻す。
+    // 名前は昇順、サイズと長さは大きい順から始める。
+    pub fn toggle(current: Option<FileSort>, column: FileSortColumn) -> Option<FileSort> {
+        let first_descending = column != FileSortColumn::Name;
+        match current {
+            Some(sort) if sort.column == column && sort.descending == first_descending => {
+                Some(FileSort {
+                    column,
+                    descending: !first_descending,
+                })
+            }
+            Some(sort) if sort.column == column => None,
+            _ => Some(FileSort {
+                column,
+                descending: first_descending,
+            }),
+        }
+    }
+}
+
+// 一覧の行から並べ替えと列表示に使う値を取り出す。
+pub trait FileColumns {
+    fn file_name(&self) -> String;
+    fn size_bytes(&self) -> u64;
+    fn duration_ms(&self) -> Option<i64>;
+}
+
+impl<T: FileColumns> FileColumns for &T {
+    fn file_name(&self) -> String {
+        (*self).file_name()
+    }
+
+    fn size_bytes(&self) -> u64 {
+        (*self).size_bytes()
+    }
+
+    fn duration_ms(&self) -> Option<i64> {
+        (*self).duration_ms()
+    }
+}
+
+impl FileColumns for ListedFile {
+    fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+
+    fn size_bytes(&self) -> u64 {
+        self.size_bytes
+    }
+
+    fn duration_ms(&self) -> Option<i64> {
+        self.duration_ms
+    }
+}
+
+impl FileColumns for SearchHit {
+    fn file_name(&self) -> String {
+        self.file_name.clone()
+    }
+
+    fn size_bytes(&self) -> u64 {
+        u64::try_from(self.size_bytes).unwrap_or(0)
+    }
+
+    fn duration_ms(&self) -> Option<i64> {
+        self.duration_ms
+    }
+}
+
+// 並び順に従った行の位置を返す。並び順が None の場合と値が同じ行は、元の並びを保つ。
+// 名前は大文字・小文字を区別せず、長さが分からない行は並び順に関わらず末尾に置く。
+pub fn sorted_order<T: FileColumns>(items: &[T], sort: Option<FileSort>) -> Vec<usize> {
+    let mut order = (0..items.len()).collect::<Vec<_>>();
+    let Some(sort) = sort else {
+        return order;
+    };
+    let directed = |ordering: Ordering| {
+        if sort.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    };
+    match sort.column {
+        FileSortColumn::Name => {
+            let names = items
+                .iter()
+                .map(|item| item.file_name().to_lowercase())
+                .collect::<Vec<_>>();
+            order.sort_by(|a, b| directed(names[*a].cmp(&names[*b])));
+        }
+        FileSortColumn::Size => {
+            order.sort_by(|a, b| directed(items[*a].size_bytes().cmp(&items[*b].size_bytes())));
+        }
+        FileSortColumn::Duration => {
+            order.sort_by(
+                |a, b| match (items[*a].duration_ms(), items[*b].duration_ms()) {
+                    (Some(a), Some(b)) => directed(a.cmp(&b)),
+                    (Some(_), None) => Ordering::Less,
+                    (None, Some(_)) => Ordering::Greater,
+                    (None, None) => Ordering::Equal,
+                },
+            );
+        }
+    }
+    order
+}
+
+// 検索結果のパスを、表示中の並び順で返す。
+pub fn ordered_hit_paths(hits: &[SearchHit], order: &[usize]) -> Vec<PathBuf> {
+    order
+        .iter()
+        .filter_map(|index| hits.get(*index))
+        .map(|hit| PathBuf::from(&hit.path))
+        .collect()
+}
+
+// 起点の行から対象の行までを、一覧の並び順で返す。どちらかが一覧に無い場合は None。
+pub fn selection_range(paths: &[PathBuf], anchor: &Path, target: &Path) -> Option<Vec<PathBuf>> {
+    let anchor = paths.iter().position(|path| path == anchor)?;
+    let target = paths.iter().position(|path| path == target)?;
+    Some(paths[anchor.min(target)..=anchor.max(target)].to_vec())
+}
+
+// 一覧の並び順のうち、選択中の行だけを返す。
+pub fn selected_in_order(paths: Vec<PathBuf>, selected: &[PathBuf]) -> Vec<PathBuf> {
+    paths
+        .into_iter()
+        .filter(|path| selected.contains(path))
+        .collect()
+}
+
+// サイズ列の表記。1GB 以上は GB、それ未満は MB で表す。
+pub fn size_label(bytes: u64) -> String {
+    const MIB: f64 = 1024.0 * 1024.0;
+    let megabytes = bytes as f64 / MIB;
+    if megabytes >= 1024.0 {
+        format!("{:.1} GB", megabytes / 1024.0)
+    } else {
+        format!("{megabytes:.1} MB")
+    }
+}
+
+// 長さ列の表記（`1:02:03`・`4:05`）
+pub fn duration_label(duration_ms: i64) -> String {
+    let total = (duration_ms.max(0) + 500) / 1000;
+    let (hours, minutes, seconds) = (total / 3600, total / 60 % 60, total % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    fn file(name: &str, size_bytes: u64, duration_ms: Option<i64>) -> ListedFile {
+        ListedFile {
+            path: PathBuf::from("/videos").join(name),
+            size_bytes,
+            duration_ms,
+            modified_time: 0,
+            is_cloud: false,
+        }
+    }
+
+    #[test]
+    fn keeps_original_order_without_sort() {
+        let files = vec![file("b.mp4", 1, None), file("a.mp4", 2, None)];
+        assert_eq!(sorted_order(&files, None), vec![0, 1]);
+    }
+
+    #[test]
+    fn sorts_by_name_ignoring_case() {
+        let files = vec![
+            file("b.mp4", 1, None),
+            file("C.mp4", 1, None),
+            file("a.mp4", 1, None),
+        ];
+        let sort = FileSort {
+            column: FileSortColumn::Name,
+            descending: false,
+        };
+        assert_eq!(sorted_order(&files, Some(sort)), vec![2, 0, 1]);
+    }
+
+    #[test]
+    fn puts_unknown_durations_last_in_both_directions() {
+        let files = vec![
+            file("a.mp4", 1, None),
+            file("b.mp4", 1, Some(5_000)),
+            file("c.mp4", 1, Some(90_000)),
+        ];
+        let ascending = FileSort {
+            column: FileSortColumn::Duration,
+            descending: false,
+        };
+        let descending = FileSort {
+            descending: true,
+            ..ascending
+        };
+        assert_eq!(sorted_order(&files, Some(ascending)), vec![1, 2, 0]);
+        assert_eq!(sorted_order(&files, Some(descending)), vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn toggles_through_directions_and_back_to_original_order() {
+        let first = FileSort::toggle(None, FileSortColumn::Size);
+        assert_eq!(
+            first,
+            Some(FileSort {
+                column: FileSortColumn::Size,
+                descending: true,
+            })
+        );
+        let second = FileSort::toggle(first, FileSortColumn::Size);
+        assert_eq!(second.map(|sort| sort.descending), Some(false));
+        assert_eq!(FileSort::toggle(second, FileSortColumn::Size), None);
+        assert_eq!(
+            FileSort::toggle(second, FileSortColumn::Name),
+            Some(FileSort {
+                column: FileSortColumn::Name,
+                descending: false,
+            })
+        );
+    }
+
+    #[test]
+    fn groups_by_calendar_day_in_local_offset() {
+        let now = datetime!(2026-10-17 00:30 +09:00);
+        let at = |value: OffsetDateTime| value.unix_timestamp();
+        assert_eq!(
+            DateGroup::of(at(datetime!(2026-10-17 00:10 +09:00)), now),
+            DateGroup::Today
+        );
+        // UTC では同じ日だが、地域の日付では前日
+        assert_eq!(
+            DateGroup::of(at(datetime!(2026-10-16 23:50 +09:00)), now),
+            DateGroup::Yesterday
+        );
+        assert_eq!(
+            DateGroup::of(at(datetime!(2026-10-11 12:00 +09:00)), now),
+            DateGroup::ThisWeek
+        );
+        assert_eq!(
+            DateGroup::of(at(datetime!(2026-10-10 12:00 +09:00)), now),
+            DateGroup::Older
+        );
+        assert_eq!(
+            DateGroup::of(at(datetime!(2026-10-18 12:00 +09:00)), now),
+            DateGroup::Today
+        );
+    }
+
+    fn hit(name: &str) -> SearchHit {
+        SearchHit {
+            path: format!("/videos/{name}"),
+            file_name: name.to_string(),
+            size_bytes: 0,
+            modified_time: 0,
+            root_id: 1,
+            parent_dir: "/videos".to_string(),
+            duration_ms: None,
+            is_cloud: false,
+        }
+    }
+
+    #[test]
+    fn range_selects_and_exports_search_rows_in_display_order() {
+        let hits = vec![hit("d.mp4"), hit("b.mp4"), hit("a.mp4"), hit("c.mp4")];
+        let order = sorted_order(
+            &hits,
+            Some(FileSort {
+                column: FileSortColumn::Name,
+                descending: false,
+            }),
+        );
+        let paths = ordered_hit_paths(&hits, &order);
+
+        // Shift+クリックは表示中の並びで、後ろから前へ選んでも同じ範囲になる
+        let range = selection_range(
+            &paths,
+            Path::new("/videos/c.mp4"),
+            Path::new("/videos/a.mp4"),
+        )
+        .unwrap();
+        assert_eq!(
+            range,
+            ["/videos/a.mp4", "/videos/b.mp4", "/videos/c.mp4"].map(PathBuf::from)
+        );
+
+        // 書き出しは選択した行だけを一覧の並び順で返す
+        let selected = [
+            PathBuf::from("/videos/c.mp4"),
+            PathBuf::from("/videos/a.mp4"),
+        ];
+        assert_eq!(
+            selected_in_order(paths.clone(), &selected),
+            ["/videos/a.mp4", "/videos/c.mp4"].map(PathBuf::from)
+        );
+        assert_eq!(
+            selection_range(&paths, Path::new("/videos/a.mp4"), Path::new("/other.mp4")),
+            None
+        );
+    }
+
+    #[test]
+    fn formats_size_and_duration() {
+        assert_eq!(size_label(5 * 1024 * 1024), "5.0 MB");
+        assert_eq!(size_label(4 * 1024 * 1024 * 1024), "4.0 GB");
+        assert_eq!(duration_label(65_400), "1:05");
+        assert_eq!(duration_label(3_723_000), "1:02:03");
+    }
+}
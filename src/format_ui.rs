@@ -0,0 +1,183 @@
+use eframe::egui;
+
+use crate::app::DownloaderApp;
+use crate::cursor::pointing;
+use crate::download::{FormatKind, FormatPrompt, megabytes_label};
+use crate::i18n::{Msg, tr, tr_fmt};
+
+pub struct FormatPromptState {
+    pub prompt: FormatPrompt,
+    // 選択中の映像（音声付きを含む）と音声の、formats 内の位置
+    video: Option<usize>,
+    audio: Option<usize>,
+}
+
+impl FormatPromptState {
+    // 一覧の先頭（最も高画質な映像と、最もビットレートの高い音声）を選んだ状態で始める。
+    pub fn new(prompt: FormatPrompt) -> Self {
+        let video = prompt
+            .formats
+            .iter()
+            .position(|format| format.kind != FormatKind::Audio);
+        let audio = prompt
+            .formats
+            .iter()
+            .position(|format| format.kind == FormatKind::Audio);
+        Self {
+            prompt,
+            video,
+            audio,
+        }
+    }
+
+    // 音声付きの映像を選んでいる場合は音声を結合しない。
+    fn selected_ids(&self) -> Vec<String> {
+        let video = self.video.and_then(|index| self.prompt.formats.get(index));
+        let audio = self
+            .audio
+            .filter(|_| video.is_some_and(|format| format.kind == FormatKind::Video))
+            .and_then(|index| self.prompt.formats.get(index));
+        video
+            .into_iter()
+            .chain(audio)
+            .map(|format| format.format_id.clone())
+            .collect()
+    }
+
+    fn total_size_label(&self) -> String {
+        let ids = self.selected_ids();
+        let size: Option<u64> = self
+            .prompt
+            .formats
+            .iter()
+            .filter(|format| ids.contains(&format.format_id))
+            .map(|format| format.size)
+            .sum();
+        match size {
+            Some(size) => tr_fmt(Msg::TotalSizeAbout, &[&megabytes_label(size)]),
+            None => tr(Msg::TotalSizeUnknown).to_string(),
+        }
+    }
+}
+
+pub fn render_format_prompt(
+    // 形式の選択待ちを持つアプリ
+    app: &mut DownloaderApp,
+    // ウィンドウ表示に使うコンテキスト
+    ctx: &egui::Context,
+) {
+    let Some(state) = app.format_prompt.as_mut() else {
+        return;
+    };
+
+    let mut open = true;
+    let mut answer = None;
+    egui::Window::new(tr(Msg::ChooseFormat))
+        .collapsible(false)
+        .resizable(false)
+        .default_width(460.0)
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .open(&mut open)
+        .show(ctx, |ui| {
+            ui.label(
+                egui::RichText::new(&state.prompt.title)
+                    .size(12.0)
+                    .color(egui::Color32::from_rgb(220, 230, 245)),
+            );
+            ui.label(
+                egui::RichText::new(tr(Msg::FormatNotice))
+                    .size(11.5)
+                    .color(egui::Color32::from_rgb(140, 150, 170)),
+            );
+
+            ui.add_space(8.0);
+            ui.label(
+                egui::RichText::new(tr(Msg::Video))
+                    .size(12.0)
+                    .color(egui::Color32::from_rgb(150, 160, 180)),
+            );
+            egui::ScrollArea::vertical()
+                .id_salt("format-video-list")
+                .max_height(200.0)
+                .show(ui, |ui| {
+                    for (index, format) in state.prompt.formats.iter().enumerate() {
+                        if format.kind == FormatKind::Audio {
+                            continue;
+                        }
+                        let _ =
+                            pointing(ui.radio_value(&mut state.video, Some(index), &format.label));
+                    }
+                });
+
+            let needs_audio = state
+                .video
+                .and_then(|index| state.prompt.formats.get(index))
+                .is_some_and(|format| format.kind == FormatKind::Video);
+            ui.add_space(8.0);
+            ui.label(
+                egui::RichText::new(tr(Msg::Audio))
+                    .size(12.0)
+                    .color(egui::Color32::from_rgb(150, 160, 180)),
+            );
+            ui.add_enabled_ui(needs_audio, |ui| {
+                egui::ScrollArea::vertical()
+                    .id_salt("format-audio-list")
+                    .max_height(140.0)
+                    .show(ui, |ui| {
+                        let _ = pointing(ui.radio_value(&mut state.audio, None, tr(Msg::NoAudio)));
+                        for (index, format) in state.prompt.formats.iter().enumerate() {
+                            if format.kind != FormatKind::Audio {
+                                continue;
+                            }
+                            let _ = pointing(ui.radio_value(
+                                &mut state.audio,
+                                Some(index),
+                                &format.label,
+                            ));
+                        }
+                    });
+            });
+
+            ui.add_space(8.0);
+            ui.label(
+                egui::RichText::new(state.total_size_label())
+                    .size(11.5)
+                    .color(egui::Color32::from_rgb(140, 150, 170)),
+            );
+
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let apply_btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::DownloadThisFormat))
+                            .size(12.5)
+                            .color(egui::Color32::from_rgb(8, 14, 24)),
+                    )
+                    .fill(egui::Color32::from_rgb(16, 190, 255));
+                    if pointing(ui.add_enabled(state.video.is_some(), apply_btn)).clicked() {
+                        answer = Some(Some(state.selected_ids()));
+                    }
+
+                    let auto_btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::ChooseAutomatically))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(180, 190, 210)),
+                    )
+                    .fill(egui::Color32::from_rgb(24, 30, 45));
+                    if pointing(ui.add(auto_btn)).clicked() {
+                        answer = Some(None);
+                    }
+                });
+            });
+        });
+
+    // 閉じた場合は自動で選ぶ。
+    if !open {
+        answer = Some(None);
+    }
+    if let Some(format_ids) = answer
+        && let Some(state) = app.format_prompt.take()
+    {
+        state.prompt.respond(format_ids);
+    }
+}
@@ -1,14 +1,18 @@
 use std::fs;
-use std::os::unix::fs::PermissionsExt;
-use std::path::{Path, PathBuf};
+use std::path::Path;
+
+use crate::file_list::ListedFile;
+use crate::i18n::{Msg, tr};
+use crate::platform::is_dataless;
 
 pub fn ensure_dir(path: &Path) -> Result<(), String> {
     fs::create_dir_all(path).map_err(|err| err.to_string())
 }
 
-pub fn load_mp4_files(dir: &Path) -> Vec<PathBuf> {
+// フォルダ直下の MP4 を新しい順に返す。長さは読まないため None になる。
+pub fn load_mp4_files(dir: &Path) -> Vec<ListedFile> {
     let _ = ensure_dir(dir);
-    let mut items: Vec<(PathBuf, std::time::SystemTime)> = Vec::new();
+    let mut items: Vec<(ListedFile, std::time::SystemTime)> = Vec::new();
 
     let entries = match fs::read_dir(dir) {
         Ok(entries) => entries,
@@ -28,26 +32,32 @@ pub fn load_mp4_files(dir: &Path) -> Vec<PathBuf> {
         if ext != "mp4" {
             continue;
         }
-        let modified = entry
-            .metadata()
-            .and_then(|meta| meta.modified())
+        let metadata = entry.metadata().ok();
+        let modified = metadata
+            .as_ref()
+            .and_then(|meta| meta.modified().ok())
             .unwrap_or(std::time::SystemTime::UNIX_EPOCH);
-        items.push((path, modified));
+        let is_cloud = metadata.as_ref().is_some_and(is_dataless);
+        let file = ListedFile {
+            path,
+            size_bytes: metadata.map(|meta| meta.len()).unwrap_or(0),
+            duration_ms: None,
+            modified_time: modified
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs() as i64)
+                .unwrap_or(0),
+            is_cloud,
+        };
+        items.push((file, modified));
     }
 
     items.sort_by(|a, b| b.1.cmp(&a.1));
-    items.into_iter().map(|(path, _)| path).collect()
+    items.into_iter().map(|(file, _)| file).collect()
 }
 
 pub fn delete_download_file(path: &Path) -> Result<(), String> {
     if !path.exists() {
-        return Err("ファイルが見つかりません。".to_string());
+        return Err(tr(Msg::FileNotFound).to_string());
     }
     fs::remove_file(path).map_err(|err| err.to_string())
 }
-
-pub fn is_executable(path: &Path) -> bool {
-    fs::metadata(path)
-        .map(|meta| meta.permissions().mode() & 0o111 != 0)
-        .unwrap_or(false)
-}
@@ -0,0 +1,1364 @@
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use crate::settings::Language;
+
+// 画面の言語。起動時と設定の保存時に切り替える。
+static CURRENT_LANGUAGE: AtomicU8 = AtomicU8::new(Language::Japanese as u8);
+
+pub fn set_language(language: Language) {
+    CURRENT_LANGUAGE.store(language as u8, Ordering::Relaxed);
+}
+
+pub fn current_language() -> Language {
+    if CURRENT_LANGUAGE.load(Ordering::Relaxed) == Language::English as u8 {
+        Language::English
+    } else {
+        Language::Japanese
+    }
+}
+
+// メッセージ ID の文言を現在の言語で返す。
+pub fn tr(msg: Msg) -> &'static str {
+    msg.text(current_language())
+}
+
+// {0}・{1}… を args で置き換える。言語によって語順が変わるため、番号で指定する。
+pub fn tr_fmt(msg: Msg, args: &[&dyn Display]) -> String {
+    fill(tr(msg), args)
+}
+
+fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut text = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        text.push_str(&rest[..start]);
+        let placeholder = rest[start + 1..]
+            .split_once('}')
+            .and_then(|(index, _)| index.parse::<usize>().ok().map(|i| (index.len(), i)));
+        match placeholder {
+            Some((len, index)) if index < args.len() => {
+                text.push_str(&args[index].to_string());
+                rest = &rest[start + len + 2..];
+            }
+            _ => {
+                text.push('{');
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+    text.push_str(rest);
+    text
+}
+
+// メッセージ ID と、日本語・英語の文言の表
+macro_rules! messages {
+    ($($id:ident => $ja:literal, $en:literal;)*) => {
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        pub enum Msg {
+            $($id,)*
+        }
+
+        impl Msg {
+            #[cfg(test)]
+            const ALL: &[Msg] = &[$(Msg::$id,)*];
+
+            fn text(self, language: Language) -> &'static str {
+                match (self, language) {
+                    $(
+                        (Msg::$id, Language::Japanese) => $ja,
+                        (Msg::$id, Language::English) => $en,
+                    )*
+                }
+            }
+        }
+    };
+}
+
+messages! {
+    // メイン画面
+    PathCopied => "パスをコピーしました: {0}", "Copied path: {0}";
+    PastedUrlAdded => "貼り付けたURLを追加しました: {0}", "Added the pasted URL: {0}";
+    PasteNotUrl => "貼り付けた内容はURLではありません。", "The pasted text is not a URL.";
+    ChooseFormatAndDownload => "形式を選んでダウンロード…", "Choose Format and Download…";
+    SplitByTimestampsAndDownload => "タイムスタンプで分割してダウンロード…", "Download and Split by Timestamps…";
+    Profile => "プロファイル", "Profile";
+    NotSelected => "未選択", "None";
+    Account => "アカウント", "Account";
+    DefaultAccount => "既定", "Default";
+    Quality => "画質", "Quality";
+    DryRunOption => "実行せずにコマンドを表示（ドライラン）", "Show commands without running (dry run)";
+    DryRunHint =>
+        "オンの間は、追加したダウンロードを実行せず、yt-dlp・ffmpeg に渡すコマンドを表示します。",
+        "While on, added downloads are not run; the yt-dlp and ffmpeg commands are shown instead.";
+    DryRunLogged => "ドライラン: 実行せずにコマンドを表示しました: {0}", "Dry run: showed the commands without running: {0}";
+    DryRunTitle => "ドライラン", "Dry Run";
+    DryRunDownload => "ダウンロード", "Download";
+    DryRunFallback => "H.264 で取得できなかった場合", "If H.264 is not available";
+    DryRunConvert => "mp4 への変換", "Convert to mp4";
+    DryRunConvertNote =>
+        "変換は必要な場合だけ行います。実際の指定は ffprobe で調べたコーデックや画質方式で変わります（ここでは VP9 / Opus の場合）。",
+        "Conversion only runs when needed. The actual options depend on the codecs found by ffprobe and the quality mode (shown here for VP9 / Opus).";
+    DryRunFormatChosenLater =>
+        "形式は実行時に一覧から選びます。<FORMAT> の部分が選んだフォーマット ID になります。",
+        "The format is chosen from a list when run. <FORMAT> becomes the chosen format ID.";
+    DryRunAudioTrackLater =>
+        "音声トラックが複数ある場合は、実行時に選んだ言語が -f に加わります。",
+        "If there are several audio tracks, the language chosen when run is added to -f.";
+    DryRunAnimeThemes =>
+        "AnimeThemes の URL は yt-dlp を使わずに取得するため、表示できるコマンドはありません。",
+        "AnimeThemes URLs are fetched without yt-dlp, so there are no commands to show.";
+    DryRunSplitByTimestamps =>
+        "保存後、貼り付けたタイムスタンプごとに ffmpeg の segment で分割します。",
+        "After saving, the video is split at the pasted timestamps with ffmpeg's segment muxer.";
+    DryRunCredentialsHidden =>
+        "ユーザー名・パスワード・クッキーは <redacted> に伏せています。",
+        "User names, passwords and cookies are hidden as <redacted>.";
+    DryRunAlreadyDownloaded =>
+        "この URL は保存済みのため、実行時はダウンロードせずに知らせます。",
+        "This URL has already been saved, so running it only reports the existing file.";
+    DryRunStagingNote =>
+        "実行時は保存先の中の作業フォルダへ保存してから、保存先へ移します。",
+        "When run, files are saved to a working folder inside the output folder first, then moved.";
+    DryRunToolsMissing =>
+        "yt-dlp または ffmpeg がまだ準備されていません。実行するにはセットアップが必要です。",
+        "yt-dlp or ffmpeg is not set up yet. Setup is required before running.";
+    CopyCommand => "コピー", "Copy";
+    CopyAllCommands => "すべてコピー", "Copy All";
+    CommandsCopied => "コマンドをコピーしました", "Copied the commands";
+    DragListToVdmx => "リストをドラッグしてVDMXへドロップ", "Drag the list and drop it onto VDMX";
+    SaveTo => "保存先", "Save to";
+    SettingsSaveDir => "設定の保存先（{0}）", "Default folder ({0})";
+    OtherFolder => "その他のフォルダ...", "Other Folder...";
+    SearchRootsMissing =>
+        "設定で検索対象フォルダ（外付けSSD等）を指定してください。",
+        "Set the folders to search (e.g. an external SSD) in Settings.";
+    SearchHint => "ファイル名またはメタ情報で検索...", "Search by file name or metadata...";
+    RecentSearches => "最近の検索", "Recent searches";
+    NoMatchingFiles => "該当するファイルはありませんでした", "No matching files";
+    Loading => "読み込み中...", "Loading...";
+    LoadMore => "さらに読み込む", "Load more";
+    NoDownloadsYet => "まだダウンロードがありません。", "No downloads yet.";
+    DownloadsFilterHint => "ファイル名で絞り込む...", "Filter by file name...";
+    SessionOnly => "この起動中のみ", "This session only";
+    ColumnName => "名前", "Name";
+    ColumnSize => "サイズ", "Size";
+    ColumnDuration => "長さ", "Duration";
+    DateGroupToday => "今日", "Today";
+    DateGroupYesterday => "昨日", "Yesterday";
+    DateGroupThisWeek => "今週", "This week";
+    DateGroupOlder => "それ以前", "Older";
+    RecentDownloadsMenu => "最近のダウンロード", "Recent Downloads";
+    NoRecentDownloads => "なし", "None";
+    ExportPlaylistMenu => "プレイリストを書き出す...", "Export Playlist...";
+    EditTagsMenu => "タグと評価を編集...", "Edit Tags and Rating...";
+    StripAudioMenu => "音声を削除したコピーを作成", "Make Copy Without Audio";
+    SilenceAudioMenu => "無音に差し替えたコピーを作成", "Make Copy With Silent Audio";
+    ReplaceAudioMenu => "音声ファイルに差し替えたコピーを作成…", "Make Copy With Audio File…";
+    CancelReplaceAudioMenu => "音声の差し替えをキャンセル", "Cancel Audio Replacement";
+    GenerateProxiesMenu => "プロキシ（480p）を作成", "Generate Proxies (480p)";
+    CancelProxiesMenu => "プロキシの作成をキャンセル", "Cancel Proxy Generation";
+    FetchCloudMenu => "クラウドから取得", "Download from Cloud";
+    SkippedAlreadyDownloaded => "ダウンロード済みのためスキップしました", "Skipped because it was already downloaded";
+    Close => "閉じる", "Close";
+    RecordedInArchive =>
+        "yt-dlpのダウンロードアーカイブに記録済みです: {0}",
+        "Already recorded in the yt-dlp download archive: {0}";
+    OpenFile => "ファイルを開く", "Open File";
+    OpenFileFailed => "ファイルを開けませんでした: {0}", "Could not open the file: {0}";
+    RevealInFinder => "Finderで表示", "Show in Finder";
+    RevealInFinderFailed => "Finderで表示できませんでした: {0}", "Could not show in Finder: {0}";
+    Offline => "オフラインです", "Offline";
+    Waiting => "待機中...", "Waiting...";
+    OfflineQueueNotice =>
+        "ネットワークに接続できません。接続が戻るとダウンロードを開始します。",
+        "No network connection. Downloads start when the connection is back.";
+    QueuedUrls => "待機中のURL: {0}件", "Queued URLs: {0}";
+    QueuePausedSuffix => "（一時停止中）", " (paused)";
+    QueueWaitingNetworkSuffix => "（接続の回復待ち）", " (waiting for network)";
+    QueueNextUp => "次に実行", "Next up";
+    QueueNextUpHint =>
+        "待機中の他の項目より先に実行します。もう一度押すと元に戻します。",
+        "Run this before the other queued items. Click again to undo.";
+    QueueDragHint => "ドラッグして順番を入れ替えます。", "Drag to reorder.";
+    QueueMarkedNextUp => "次に実行します: {0}", "Up next: {0}";
+    CancelAll => "すべてキャンセル", "Cancel All";
+    CancelAllTitle => "すべてキャンセルしますか？", "Cancel all downloads?";
+    CancelAllMessage =>
+        "実行中と待機中の{0}件のダウンロードをキャンセルします。待機中の項目はキューから消えます。",
+        "This cancels {0} running and queued downloads. Queued items are removed from the queue.";
+    CancelledAll => "{0}件のダウンロードをキャンセルしました。", "Cancelled {0} downloads.";
+    FailedDownloads => "失敗したダウンロード", "Failed Downloads";
+    Retry => "やり直す", "Retry";
+    Dismiss => "一覧から消す", "Dismiss";
+    ClearAll => "すべて消す", "Clear All";
+    PresetHint =>
+        "クリップボードのURLを、この設定でダウンロードします: {0}・{1}・{2}",
+        "Download the clipboard URL with these settings: {0}, {1}, {2}";
+    UsingPreset => "プリセット「{0}」の設定でダウンロードします。", "Downloading with the \"{0}\" preset.";
+    Presets => "プリセット", "Presets";
+    PresetsNotice =>
+        "メイン画面のボタンから、その1件だけこの設定でダウンロードします。名前が空欄のボタンは表示しません。",
+        "Buttons on the main window download one item with these settings. Presets with an empty name are hidden.";
+    PresetName => "ボタンの名前", "Button name";
+    AutoFragments =>
+        "直近の回線速度（{1}）に合わせて、断片を{0}個ずつ同時に取得します。",
+        "Fetching {0} fragments at a time to match the recent connection speed ({1}).";
+    ConcurrentFragments => "同時に取得する断片", "Parallel fragments";
+    Aria2cNotFound =>
+        "aria2cが見つからないため、yt-dlpの標準のダウンローダーで取得します。",
+        "aria2c was not found, so yt-dlp's built-in downloader is used.";
+    ExternalDownloader => "外部ダウンローダー", "External downloader";
+    UseAria2c => "aria2cを使う", "Use aria2c";
+    Aria2cFound => "見つかりました: {0}", "Found: {0}";
+    Aria2cMissing =>
+        "見つかりません。Homebrew（brew install aria2）で入れるか、~/.vjdownloader/bin に置いてください。",
+        "Not found. Install it with Homebrew (brew install aria2) or place it in ~/.vjdownloader/bin.";
+    StallTimeout => "応答がない場合に中断", "Stop when unresponsive";
+    StallMinutes => " 分", " min";
+    StallRetry => "自動でやり直す", "Retry automatically";
+    StallTimeoutHint =>
+        "yt-dlp・curl・ffmpegの出力がこの時間止まったら中断します。変換中はこの3倍まで待ちます。0で中断しません。",
+        "Stops the download when yt-dlp, curl or ffmpeg prints nothing for this long. Conversion waits three times as long. 0 never stops.";
+    AutoTuneFragments => "回線速度で自動調整", "Auto-tune by connection speed";
+    AutoTuneFragmentsHint =>
+        "直近のダウンロード速度から、この数を上限に決めます。遅い回線では減らします。",
+        "Picks a count up to this number from recent download speeds, fewer on slow connections.";
+    WatchFolder => "監視フォルダ", "Watch Folder";
+    WatchFolderNotice =>
+        "このフォルダに置いた動画を自動でmp4へ変換し、設定の出力先フォルダへ移します。変換できた元のファイルは削除します。",
+        "Videos placed in this folder are converted to mp4 automatically and moved to the output folder. The original file is deleted once converted.";
+    WatchFolderOption => "監視フォルダを使う", "Use a watch folder";
+    WatchFolderHint => "監視するフォルダ", "Folder to watch";
+    WatchFolderRequired =>
+        "監視フォルダには、存在するフォルダを指定してください。",
+        "Choose an existing folder to watch.";
+    WatchFolderSameAsOutput =>
+        "監視フォルダには、出力先フォルダと別のフォルダを指定してください。",
+        "The watch folder must be different from the output folder.";
+    WatchFolderMissing =>
+        "監視フォルダが見つかりません: {0}",
+        "Watch folder not found: {0}";
+    WatchFolderStartFailed =>
+        "監視フォルダを監視できませんでした: {0}",
+        "Could not watch the folder: {0}";
+    WatchFolderQueued => "監視フォルダの動画を追加しました: {0}", "Added from the watch folder: {0}";
+    ImportingWatchedFile => "監視フォルダの動画を変換します: {0}", "Converting from the watch folder: {0}";
+    WatchedFileMissing =>
+        "監視フォルダの動画が見つかりません: {0}",
+        "The video in the watch folder is gone: {0}";
+    CopyWatchedFileFailed =>
+        "監視フォルダの動画を一時フォルダへコピーできませんでした: {0}",
+        "Could not copy the video to the temporary folder: {0}";
+    RemoveWatchedFileFailed =>
+        "変換後に監視フォルダの元のファイルを削除できませんでした: {0}",
+        "Converted, but could not delete the original file in the watch folder: {0}";
+
+    // ステータス表示と通知
+    BundledToolsFailed => "同梱ツールの配置に失敗しました: {0}", "Could not install the bundled tools: {0}";
+    SearchInitFailed => "検索エンジンの初期化に失敗しました。", "Could not start the search engine.";
+    SearchSyncFailed => "検索対象フォルダの同期に失敗しました: {0}", "Could not sync the search folders: {0}";
+    HotkeyRegisterFailed =>
+        "呼び出しショートカットを登録できませんでした: {0}",
+        "Could not register the summon shortcut: {0}";
+    OscStartFailed => "OSCを開始できませんでした: {0}", "Could not start OSC: {0}";
+    EventStreamStartFailed =>
+        "WebSocket配信を開始できませんでした: {0}",
+        "Could not start the WebSocket stream: {0}";
+    OpenLogFolderFailed => "ログフォルダを開けませんでした: {0}", "Could not open the log folder: {0}";
+    BugReportExported => "バグレポートを書き出しました: {0}", "Exported the bug report: {0}";
+    BugReportExportFailed => "バグレポートを書き出せませんでした: {0}", "Could not export the bug report: {0}";
+    InterruptedJobDiscarded => "中断したダウンロードを破棄しました: {0}", "Discarded the interrupted download: {0}";
+    SetupRequired =>
+        "初回セットアップが必要です。設定から自動セットアップを行ってください。",
+        "Setup is required. Run the automatic setup from Settings.";
+    Queued => "ダウンロード待ちに追加しました（{0}件待ち）: {1}", "Queued the download ({0} waiting): {1}";
+    QueuedOffline =>
+        "オフラインのためダウンロード待ちに追加しました（{0}件待ち）: {1}",
+        "Offline. Queued the download ({0} waiting): {1}";
+    LoginSkipped => "ログインせずにダウンロードします: {0}", "Downloading without logging in: {0}";
+    Cancelling => "キャンセル中...", "Cancelling...";
+    QueueResumed => "ダウンロードを再開しました。", "Resumed downloads.";
+    QueuePaused => "ダウンロードを一時停止しました。", "Paused downloads.";
+    NothingToExport => "書き出すファイルがありません。", "There are no files to export.";
+    PlaylistExported => "プレイリストを書き出しました（{0}件）: {1}", "Exported the playlist ({0} files): {1}";
+    PlaylistExportFailed => "プレイリストを書き出せませんでした: {0}", "Could not export the playlist: {0}";
+    SelectFilesToTag => "タグを編集するファイルを選択してください。", "Select the files to tag.";
+    FinderTagsReadFailed => "Finderタグを読み取れませんでした: {0}", "Could not read the Finder tags: {0}";
+    FinderTagsWriteFailed => "Finderタグを書き込めませんでした: {0}", "Could not write the Finder tags: {0}";
+    TagsApplied => "{0}件のファイルにタグを設定しました。", "Tagged {0} files.";
+    SelectFilesToMute => "音声を削除するファイルを選択してください。", "Select the files to remove audio from.";
+    ReplaceAudioStarted => "{0}件の音声を差し替えます", "Replacing audio in {0} files";
+    ReplaceAudioProgress => "音声を差し替え中 ({0}/{1})", "Replacing audio ({0}/{1})";
+    MutedCopyCreated => "音声を差し替えたコピーを作成しました: {0}", "Created copy with replaced audio: {0}";
+    MutedCopyFailed => "音声を差し替えられませんでした: {0}: {1}", "Failed to replace audio: {0}: {1}";
+    MutedCopiesDone => "{0}件の音声を差し替えたコピーを作成しました", "Created {0} copies with replaced audio";
+    ReplaceAudioCancelled => "音声の差し替えをキャンセルしました（{0}件作成済み）", "Audio replacement cancelled ({0} created)";
+    SearchWindowTitle => "検索", "Search";
+    DetachSearch => "別ウィンドウ", "Pop Out";
+    DetachSearchHint => "検索を別のウィンドウに切り離します", "Move search into its own window";
+    AttachSearch => "戻す", "Dock";
+    AttachSearchHint => "検索をメイン画面に戻します", "Move search back into the main window";
+    IndexBackup => "検索インデックスのバックアップ", "Search Index Backup";
+    IndexBackupNotice => "タグや評価を含む検索インデックスを定期的に保存し、壊れた場合に戻せるようにします。", "Periodically saves the search index, including tags and ratings, so it can be restored if it gets corrupted.";
+    IndexBackupOption => "定期的にバックアップする", "Back up periodically";
+    IndexBackupHours => " 時間ごと", " h interval";
+    BackupIndexNow => "今すぐバックアップ", "Back Up Now";
+    RestoreIndexFrom => "バックアップから復元...", "Restore from Backup...";
+    RestoreIndex => "復元する", "Restore";
+    ConfirmIndexRestore => "現在の検索インデックスを {0} の内容で置き換えます。", "The current search index will be replaced with {0}.";
+    IndexBackedUp => "検索インデックスをバックアップしました: {0}", "Backed up search index: {0}";
+    IndexBackupFailed => "検索インデックスのバックアップに失敗しました: {0}", "Failed to back up search index: {0}";
+    IndexBackupStopped => "バックアップが途中で止まりました。", "The backup stopped unexpectedly.";
+    IndexRestored => "検索インデックスを復元しました: {0}", "Restored search index: {0}";
+    IndexRestoreFailed => "検索インデックスの復元に失敗しました: {0}", "Failed to restore search index: {0}";
+    SelectFilesForProxies => "プロキシを作るファイルを選択してください。", "Select the files to make proxies for.";
+    ProxiesStarted => "{0}件のプロキシを作成します", "Generating {0} proxies";
+    ProxyProgress => "プロキシを作成中 ({0}/{1})", "Generating proxy ({0}/{1})";
+    ProxyFailed => "プロキシを作成できませんでした: {0}: {1}", "Failed to generate proxy: {0}: {1}";
+    ProxyLinkFailed => "プロキシをインデックスに記録できませんでした: {0}", "Failed to record proxy in index: {0}";
+    ProxiesDone => "{0}件のプロキシを作成しました", "Generated {0} proxies";
+    ProxiesCancelled => "プロキシの作成をキャンセルしました（{0}件作成済み）", "Proxy generation cancelled ({0} generated)";
+    UsingProxy => "元の動画が見つからないため、プロキシを使います: {0}", "Original not found; using proxy: {0}";
+    UsingProxyForCloud => "元の動画がクラウドにだけあるため、プロキシを使います: {0}", "Original is only in the cloud; using proxy: {0}";
+    CloudOnlyDragBlocked =>
+        "クラウドにだけあるためドラッグできません。右クリックの「クラウドから取得」で取得してください: {0}",
+        "Can't drag a file that is only in the cloud. Use \"Download from Cloud\" from the right-click menu: {0}";
+    CloudFetchOnDrag => "クラウドから取得しています。終わったらもう一度ドラッグしてください: {0}", "Downloading from the cloud. Drag again when it finishes: {0}";
+    SelectCloudFiles => "クラウドから取得するファイルを選択してください。", "Select the files to download from the cloud.";
+    CloudFetchStarted => "クラウドから取得しています ({0}件)", "Downloading {0} files from the cloud";
+    CloudFetchFailed => "クラウドから取得できませんでした: {0}: {1}", "Failed to download from the cloud: {0}: {1}";
+    CloudFetchDone => "{0}件のファイルをクラウドから取得しました", "Downloaded {0} files from the cloud";
+    DownloadFinishedBody => "ダウンロードが完了しました。", "The download has finished.";
+    DownloadFinishedTitle => "ダウンロード完了", "Download Complete";
+    DownloadFailedTitle => "ダウンロード失敗", "Download Failed";
+    ReindexFailed => "再インデックスを開始できませんでした: {0}", "Could not start reindexing: {0}";
+    DeleteFailed => "削除に失敗しました: {0}", "Could not delete: {0}";
+    FileDeleted => "{0}を削除しました。", "Deleted {0}.";
+    ClipboardEmpty => "クリップボードにURLがありません。", "There is no URL on the clipboard.";
+    DownloadCompletedToast => "ダウンロードが完了しました（{0}）", "Download finished ({0})";
+    DownloadFailedToast => "ダウンロードに失敗しました: {0}", "Download failed: {0}";
+    ClickToDismiss => "クリックで閉じる", "Click to dismiss";
+    DragItemsFailed => "ドラッグ対象の取得に失敗しました: {0}", "Could not get the files to drag: {0}";
+    DragIconMissing => "ドラッグ用フォールバックアイコンが見つかりません。", "The fallback drag icon is missing.";
+    DragStartFailed => "ドラッグ開始に失敗しました: {0}", "Could not start dragging: {0}";
+    StaleStagingRemoved =>
+        "前回の終了時に残った一時フォルダを{0}件削除しました。",
+        "Removed {0} temporary folders left from the last session.";
+    RecentDirsSaveFailed => "最近使ったフォルダの保存に失敗しました: {0}", "Could not save the recent folders: {0}";
+    ProfileSwitchFailed => "プロファイルの切り替えに失敗しました: {0}", "Could not switch the profile: {0}";
+    ProfileSwitched => "プロファイル「{0}」に切り替えました。", "Switched to the profile \"{0}\".";
+    AccountSwitchFailed => "アカウントの切り替えに失敗しました: {0}", "Could not switch the account: {0}";
+    DefaultCookiesRestored => "既定のクッキー設定に戻しました。", "Using the default cookie settings.";
+    AccountCookiesUsed => "アカウント「{0}」のクッキーを使います。", "Using cookies of the account \"{0}\".";
+    SearchHistorySaveFailed => "検索履歴の保存に失敗しました: {0}", "Could not save the search history: {0}";
+    SearchNotReadyRestart =>
+        "検索エンジンが初期化されていません。アプリを再起動してください。",
+        "The search engine is not ready. Restart the app.";
+    SearchNotReady => "検索エンジンが初期化されていません。", "The search engine is not ready.";
+    DownloadCancelled => "ダウンロードをキャンセルしました。", "Cancelled the download.";
+    DownloadStalled =>
+        "{0}分間応答がなかったため、ダウンロードを中断しました。",
+        "Stopped the download because it was unresponsive for {0} min.";
+    RetryingStalled =>
+        "応答がなかったダウンロードをやり直します（{0}/{1}回目）: {2}",
+        "Retrying the unresponsive download (attempt {0}/{1}): {2}";
+    NetworkReconnected => "ネットワークに再接続しました。", "Reconnected to the network.";
+    ExternalRequestFailed => "外部からの要求を処理できません: {0}", "Could not handle the external request: {0}";
+    InputJapanese => "日本語になりました", "Input switched to Japanese";
+    InputEnglish => "英字になりました", "Input switched to English";
+    InputSourceChanged => "入力ソースが変更されました: {0}", "Input source changed: {0}";
+    ExtensionNotIndexed =>
+        "ext:{0} では検索できません。検索の対象は .{1} のファイルだけです。",
+        "ext:{0} cannot match anything. Only .{1} files are indexed.";
+    SearchRequestFailed =>
+        "検索ワーカーにリクエストを送信できませんでした。",
+        "Could not send the request to the search worker.";
+
+    // ログ画面
+    Log => "ログ", "Log";
+    NoLogsYet => "ログはまだありません。", "No logs yet.";
+    LogFolderNotice =>
+        "ログは ~/.vjdownloader/logs にも保存されます。",
+        "Logs are also saved to ~/.vjdownloader/logs.";
+    ClearLog => "表示をクリア", "Clear";
+    CopyRecentLog => "直近10分をコピー", "Copy Last 10 Minutes";
+    ExportBugReport => "バグレポートを書き出す…", "Export Bug Report…";
+    CopyLogFailed => "ログのコピーに失敗しました: {0}", "Could not copy the log: {0}";
+    Unknown => "不明", "Unknown";
+    JobGroupHeader => "#{0} {1}  {2}（{3}件）", "#{0} {1}  {2} ({3})";
+    OtherLogGroup => "ダウンロード以外（{0}件）", "Other ({0})";
+    LogLevel => "レベル", "Level";
+    LogSourceFilter => "出力元", "Source";
+    GroupByDownload => "ダウンロードごとにまとめる", "Group by download";
+
+    // ダウンロード前の確認
+    ConfirmDownload => "ダウンロードの確認", "Confirm Download";
+    UnknownTitle => "（タイトル不明）", "(Unknown title)";
+    DurationLong => "長さ {0}（長い動画です）", "Length {0} (long video)";
+    Duration => "長さ {0}", "Length {0}";
+    DurationUnknown => "長さは不明です", "Unknown length";
+    Download => "ダウンロード", "Download";
+    DontDownload => "やめる", "Don't Download";
+
+    // 黒帯の切り取り
+    CropBlackBars => "黒帯の切り取り", "Crop Black Bars";
+    CropDetected =>
+        "黒帯を検出しました。赤枠の内側を残します（{0}x{1} → {2}x{3}）。",
+        "Black bars detected. The area inside the red frame is kept ({0}x{1} → {2}x{3}).";
+    Crop => "切り取る", "Crop";
+    DontCrop => "切り取らない", "Don't Crop";
+
+    // 音声トラックの選択
+    ChooseAudioTrack => "音声トラックの選択", "Choose Audio Track";
+    MultipleAudioTracks =>
+        "複数の音声トラックがあります。mp4に入れるトラックを選んでください。",
+        "There are several audio tracks. Choose the one to put in the mp4.";
+    UseThisTrack => "このトラックを使う", "Use This Track";
+    DefaultAudio => "既定の音声", "Default Audio";
+
+    // 同名のファイル
+    FileExists => "同名のファイル", "File Exists";
+    FileExistsMessage =>
+        "保存先に同じ名前のファイルがあります。",
+        "A file with the same name already exists in the folder.";
+    SaveWithNumber => "番号を付けて保存", "Save with a number";
+    Overwrite => "上書き", "Overwrite";
+    DontSave => "保存しない", "Don't save";
+
+    // 形式の選択
+    TotalSizeAbout => "合計 約{0}", "Total about {0}";
+    TotalSizeUnknown => "合計サイズは不明です", "Total size unknown";
+    ChooseFormat => "形式の選択", "Choose Format";
+    SplitByTimestamps => "タイムスタンプで分割", "Split by Timestamps";
+    SplitUrlHint => "動画の URL", "Video URL";
+    SplitByTimestampsNotice =>
+        "概要欄などのトラックリストを貼り付けてください。保存後、1 行ごとに「番号 名前」のクリップに分割します。",
+        "Paste a track list, e.g. from the description. After saving, the video is split into one \"number title\" clip per line.";
+    SplitAndDownload => "ダウンロード", "Download";
+    FormatNotice =>
+        "映像のみの形式を選んだ場合は音声と結合し、ダウンロード後にmp4へ変換します。",
+        "A video-only format is merged with audio and converted to mp4 after downloading.";
+    Video => "映像", "Video";
+    Audio => "音声", "Audio";
+    NoAudio => "音声なし", "No audio";
+    DownloadThisFormat => "この形式でダウンロード", "Download This Format";
+    ChooseAutomatically => "自動で選ぶ", "Choose Automatically";
+
+    // 中断したダウンロード
+    InterruptedDownload => "中断したダウンロード", "Interrupted Download";
+    InterruptedMessage =>
+        "前回の終了時に{0}だったダウンロードがあります。",
+        "A download was interrupted while {0} when the app last quit.";
+    SaveToPath => "保存先: {0}", "Save to: {0}";
+    Resume => "再開", "Resume";
+    RestartFromScratch => "最初からやり直す", "Start Over";
+    Discard => "破棄", "Discard";
+
+    // タグと評価
+    TagsAndRating => "タグと評価", "Tags and Rating";
+    ReplaceTagsMessage => "{0}件のファイルのFinderタグを置き換えます。", "Replaces the Finder tags of {0} files.";
+    Tags => "タグ", "Tags";
+    TagsHint => "カンマ区切り（例: live, intro）", "Comma separated (e.g. live, intro)";
+    Rating => "評価", "Rating";
+    Apply => "適用", "Apply";
+    Cancel => "キャンセル", "Cancel";
+
+    // メニュー
+    SettingsMenu => "設定...", "Settings...";
+    LogsMenu => "ログ...", "Logs...";
+    OpenLogFolder => "ログフォルダを開く", "Open Log Folder";
+    ResumeAll => "すべて再開", "Resume All";
+    PauseAll => "すべて一時停止", "Pause All";
+    DownloadClipboardUrl => "クリップボードのURLをダウンロード", "Download URL from Clipboard";
+    OpenWindow => "ウィンドウを開く", "Open Window";
+
+    // 設定の選択肢と検証
+    ChannelStable => "安定版", "Stable";
+    ChannelNightly => "ナイトリー", "Nightly";
+    JsRuntimeDisabled => "使わない", "None";
+    JsRuntime => "JavaScriptランタイム", "JavaScript runtime";
+    JsRuntimeDenoNotice =>
+        "見つからない場合はDenoを自動で取得します。",
+        "Deno is downloaded automatically if it is not found.";
+    JsRuntimeNodeNotice =>
+        "インストール済みのNode.js（Homebrew・PATH）を使います。Denoは取得しません。",
+        "Uses the installed Node.js (Homebrew or PATH). Deno is not downloaded.";
+    JsRuntimeDisabledNotice =>
+        "ランタイムを使いません。YouTubeでは取得できる形式が減ることがあります。",
+        "No runtime is used. Some YouTube formats may be unavailable.";
+    EncoderVideoToolboxH264 => "VideoToolbox H.264（GPU）", "VideoToolbox H.264 (GPU)";
+    EncoderVideoToolboxHevc => "VideoToolbox HEVC（GPU）", "VideoToolbox HEVC (GPU)";
+    EncoderX264 => "libx264（CPU）", "libx264 (CPU)";
+    EncoderCopy => "再エンコードしない（コピー）", "Don't re-encode (copy)";
+    TopLeft => "左上", "Top left";
+    TopRight => "右上", "Top right";
+    BottomLeft => "左下", "Bottom left";
+    BottomRight => "右下", "Bottom right";
+    Center => "中央", "Center";
+    CollisionOverwrite => "上書きする", "Overwrite";
+    CollisionAsk => "毎回確認する", "Ask every time";
+    FilenameAscii => "英数字のみ", "Letters and digits only";
+    FilenameUnicode => "日本語などを残す", "Keep non-ASCII characters";
+    NoLimit => "制限なし", "No limit";
+    ProfileNotFound => "プロファイル「{0}」が見つかりません。", "The profile \"{0}\" was not found.";
+    ProfileNameRequired => "プロファイル名を入力してください。", "Enter a profile name.";
+    NetrcWriteFailed => "ログイン情報の一時ファイルを作成できませんでした: {0}", "Could not create the temporary login file: {0}";
+    KeychainPasswordMissing =>
+        "{0} のパスワードがキーチェーンにありません。設定画面で入力し直してください。",
+        "The password for {0} is not in the keychain. Enter it again in Settings.";
+    PinnedVersionFormat =>
+        "yt-dlpの固定バージョンはリリースタグ（例: 2025.01.26）で入力してください。",
+        "Enter the pinned yt-dlp version as a release tag (e.g. 2025.01.26).";
+    UnclosedSingleQuote => "シングルクォートが閉じていません。", "A single quote is not closed.";
+    UnclosedDoubleQuote => "ダブルクォートが閉じていません。", "A double quote is not closed.";
+    TrailingBackslash => "末尾のバックスラッシュの後に文字がありません。", "Nothing follows the trailing backslash.";
+    ShortcutInvalid => "{0}のショートカット: {1}", "Shortcut for {0}: {1}";
+    ShortcutConflict =>
+        "「{0}」と「{1}」に同じショートカットが割り当てられています。",
+        "\"{0}\" and \"{1}\" use the same shortcut.";
+    ShortcutUnknownKey => "キーを認識できません: {0}", "Unknown key: {0}";
+    ShortcutSingleKey => "指定できるキーは1つだけです。", "Only one key can be assigned.";
+    ShortcutKeyRequired => "キーを指定してください（例: cmd+d）。", "Specify a key (e.g. cmd+d).";
+    ShortcutModifierRequired =>
+        "ファンクションキー以外はcmdかctrlを含めてください。",
+        "Include cmd or ctrl unless it is a function key.";
+    ShortcutReserved =>
+        "{0}はシステムで使われているため割り当てられません。",
+        "{0} is used by the system and can't be assigned.";
+
+    // 設定画面
+    UpdatePrepareFailed => "更新の準備に失敗しました: {0}", "Could not prepare the update: {0}";
+    CheckingYtDlp => "yt-dlpの状態を確認中...", "Checking yt-dlp...";
+    CheckingDeno => "Denoの状態を確認中...", "Checking Deno...";
+    CheckingFfmpeg => "ffmpegの状態を確認中...", "Checking ffmpeg...";
+    SetupFailed => "セットアップに失敗しました: {0}", "Setup failed: {0}";
+    SettingUpTool => "{0}をセットアップ中...", "Setting up {0}...";
+    UpdatingTool => "{0}を更新中...", "Updating {0}...";
+    AutoSetup => "自動セットアップ", "Set Up Automatically";
+    GetLatest => "最新を取得", "Get Latest";
+    Checking => "確認中...", "Checking...";
+    CheckingVersion => "バージョンを確認中...", "Checking the version...";
+    NotInstalled => "未インストール", "Not installed";
+    NoPermission => "権限不足", "No permission";
+    NotExecutable => "実行権限がありません。", "The file is not executable.";
+    VersionCheckFailed => "バージョン取得に失敗しました。", "Could not read the version.";
+    Ready => "準備完了", "Ready";
+    ToolStatusLatest => "{0}（取得元: {1}・最新）", "{0} (source: {1}, latest)";
+    PinnedVersionMismatch =>
+        "固定バージョン {0}（{1}）と異なります。「最新を取得」で切り替えます。",
+        "Differs from the pinned version {0} ({1}). Use \"Get Latest\" to switch.";
+    ToolStatusPinned => "{0}（取得元: {1}・{2}に固定）", "{0} (source: {1}, pinned to {2})";
+    ToolStatusEncoder => "{0}（{1}）", "{0} ({1})";
+    ToolStatusEncoderFallback =>
+        "{0}（{1}が使えないため{2}で変換します）",
+        "{0} ({1} is unavailable, converting with {2})";
+    InitialSetup => "初回セットアップ", "Initial Setup";
+    Settings => "設定", "Settings";
+    SetupStepOf => "ステップ {0} / {1}", "Step {0} of {1}";
+    SetupToolsTitle => "ツールのセットアップ", "Set Up Tools";
+    SetupToolsNotice =>
+        "初回起動ではyt-dlpとffmpegのダウンロードと実行権限の付与が必要です。\nボタン一つで最新を取得できます。",
+        "On first launch, yt-dlp and ffmpeg have to be downloaded and made executable.\nOne click gets the latest versions.";
+    SetupToolsRequired => "必要なツールを準備すると次へ進めます。", "Set up the required tools to continue.";
+    SetupFolderTitle => "保存先を選ぶ", "Choose the Download Folder";
+    SetupFolderNotice =>
+        "ダウンロードした動画を保存するフォルダです。空欄の場合は既定のフォルダに保存します。",
+        "Downloaded videos are saved to this folder. Leave it empty to use the default folder.";
+    SetupSearchTitle => "検索対象を追加", "Add Folders to Search";
+    SetupSearchNotice =>
+        "外付けSSDなど、mp4を探すフォルダを追加します。後から設定で変えられます。",
+        "Add folders to search for mp4 files, such as an external SSD. You can change them later in Settings.";
+    SetupCookiesTitle => "Cookieの設定（任意）", "Cookies (Optional)";
+    SetupCookiesNotice =>
+        "ログインが必要な動画を取得する場合だけ設定します。不要なら「次へ」で進んでください。",
+        "Only needed for videos that require signing in. Otherwise, click \"Next\" to continue.";
+    SetupTrialTitle => "テストダウンロード", "Test Download";
+    SetupTrialNotice =>
+        "短い動画を保存先へ試しにダウンロードし、変換・読み込みまで動くかを確かめます。保存した動画は確認後に削除します。",
+        "Downloads a short video to the download folder to check the whole process up to reading the file. The video is deleted afterwards.";
+    RunTrialDownload => "テストを実行", "Run Test";
+    TrialRunning => "テスト動画をダウンロードしています...", "Downloading the test video...";
+    TrialPassed =>
+        "{0}（{1}x{2}・{3}）を保存して読み込めました。準備完了です。",
+        "Saved and read {0} ({1}x{2}, {3}). You're all set.";
+    TrialFailedHint =>
+        "「戻る」で設定を見直すか、設定の「診断」で原因を確かめてください。",
+        "Go back to review the settings, or find the cause with Diagnostics in Settings.";
+    SetupBack => "戻る", "Back";
+    SetupNext => "次へ", "Next";
+    SetupFinish => "完了", "Finish";
+    OpenSettings => "設定を開く", "Open Settings";
+    AppSettings => "アプリ設定", "App Settings";
+    AppSettingsNotice =>
+        "ウィンドウサイズ、保存先、検索対象、依存ツールの状態をまとめて管理します。",
+        "Manage the window size, folders, search targets and tool status in one place.";
+    Language => "言語", "Language";
+    WindowWidth => "画面幅", "Window width";
+    WidthHint => "例: 320", "e.g. 320";
+    WindowHeight => "画面高さ", "Window height";
+    HeightHint => "例: 1000", "e.g. 1000";
+    OutputFolder => "出力先フォルダ", "Output folder";
+    ChooseFolder => "フォルダを選択", "Choose Folder";
+    RecentFolders => "最近使ったフォルダ", "Recent folders";
+    SummonShortcut => "呼び出しショートカット", "Summon shortcut";
+    SummonShortcutHint => "例: cmd+shift+space（空欄で無効）", "e.g. cmd+shift+space (empty to disable)";
+    Shortcuts => "ショートカット", "Shortcuts";
+    ShortcutStartDownload => "ダウンロード開始", "Start download";
+    ShortcutCancel => "ダウンロードの中止", "Cancel download";
+    ShortcutFocusSearch => "検索欄へ移動", "Focus search";
+    ShortcutOpenSettings => "設定を開く", "Open settings";
+    ShortcutOpenLogs => "ログを開く", "Open logs";
+    ShortcutNone => "なし", "None";
+    ShortcutChange => "変更", "Change";
+    ShortcutRecording =>
+        "キーを押してください（Escで取り消し、Deleteで無効）",
+        "Press a key (Esc to cancel, Delete to disable)";
+    RestoreDefaults => "既定に戻す", "Restore Defaults";
+    YtDlpSource => "yt-dlpの取得元", "yt-dlp source";
+    PinnedVersionHint => "固定バージョン（空欄で最新）", "Pinned version (empty for latest)";
+    YtDlpExtraArgs => "yt-dlpの詳細オプション", "yt-dlp extra options";
+    YtDlpExtraArgsHint =>
+        "例: --sleep-requests 1 --limit-rate 5M",
+        "e.g. --sleep-requests 1 --limit-rate 5M";
+    YtDlpExtraArgsNotice =>
+        "上級者向け: すべてのyt-dlpの実行の末尾にそのまま追加します。指定によってはダウンロードや変換が失敗します。",
+        "Advanced: appended as-is to every yt-dlp run. Some options can make downloads or conversions fail.";
+    ConnectionCheck => "接続の確認", "Connection check";
+    ConnectionCheckOption =>
+        "ダウンロードの前にネットワーク接続を確認する",
+        "Check the network connection before downloading";
+    ConnectionCheckHint =>
+        "yt-dlpの--proxyや環境変数HTTPS_PROXYのプロキシを通して確認します。確認が当てにならない環境では無効にすると、オフライン扱いにせずyt-dlpに任せます。",
+        "Checks through the proxy from yt-dlp's --proxy or the HTTPS_PROXY environment variable. Turn this off where the check is unreliable to let yt-dlp try without treating the app as offline.";
+    MaxQuality => "画質の上限", "Maximum quality";
+    FilenameCharacters => "ファイル名の文字", "File name characters";
+    EncoderMethod => "エンコード方式", "Encoder";
+    AutoCorrectVideo => "映像の自動補正", "Automatic video correction";
+    AutoCorrectVideoOption =>
+        "インターレース解除と画素比（SAR）の補正を行う",
+        "Deinterlace and correct the pixel aspect ratio (SAR)";
+    CropDetectOption =>
+        "変換時に黒帯を検出し、プレビューで確認してから切り取る",
+        "Detect black bars when converting and crop after checking a preview";
+    SceneSplit => "シーン分割", "Scene splitting";
+    SceneSplitOption =>
+        "保存後、カットの切り替わりで別々のクリップに分割する",
+        "After saving, split the video into separate clips at hard cuts";
+    SceneThresholdHint =>
+        "カットとみなす映像の変化量です。小さいほど細かく分割します。",
+        "How much the picture must change to count as a cut. Lower values split more often.";
+    SceneSplitStarted => "{0} をシーンごとに分割しています...", "Splitting {0} into scenes...";
+    SceneSplitNoCuts => "{0} にカットが見つからないため、分割しませんでした", "No cuts found in {0}, so it was not split";
+    SceneSplitDone => "{0} を {1} 個のクリップに分割しました", "Split {0} into {1} clips";
+    SceneSplitFailed => "シーン分割に失敗しました: {0}", "Scene splitting failed: {0}";
+    ChapterSplit => "チャプター分割", "Chapter splitting";
+    ChapterSplitOption =>
+        "保存後、チャプターのある動画をチャプターごとのクリップに分割する",
+        "After saving, split videos with chapters into one clip per chapter";
+    ChapterSplitHint =>
+        "クリップには「番号 チャプター名」の名前を付けます。チャプターで分割した動画はシーン分割しません。",
+        "Clips are named \"number chapter title\". Videos split by chapter are not split into scenes.";
+    ChapterSplitStarted => "{0} を {1} 個のチャプターで分割しています...", "Splitting {0} into {1} chapters...";
+    ChapterSplitTooShort => "{0} はチャプターの途中で終わるため、分割しませんでした", "{0} ends before its second chapter, so it was not split";
+    ChapterSplitDone => "{0} を {1} 個のチャプターに分割しました", "Split {0} into {1} chapters";
+    ChapterSplitFailed => "チャプター分割に失敗しました: {0}", "Chapter splitting failed: {0}";
+    ChapterListTooShort =>
+        "時刻の付いた行が 2 行以上必要です（例: 0:00 Intro）",
+        "At least two lines with a timestamp are needed (e.g. 0:00 Intro)";
+    ChapterListOutOfOrder => "時刻が前の行より後になっていません: {0}", "This timestamp is not later than the previous line: {0}";
+    ParallelConversions => "同時に行う変換", "Parallel conversions";
+    ParallelConversionsHint =>
+        "ダウンロードを終えたクリップの変換中も、次のダウンロードを始めます。VideoToolboxは同時に多く変換すると互いに遅くなるため、1〜2を推奨します。",
+        "The next download starts while finished clips are converting. VideoToolbox slows down when many conversions run at once, so 1-2 is recommended.";
+    Subscriptions => "購読", "Subscriptions";
+    SubscriptionsNotice =>
+        "YouTubeのチャンネルやプレイリストを間隔ごとに確認し、新しい動画を見つけます。追加した時点の動画は対象にしません。",
+        "Checks YouTube channels and playlists on an interval for new videos. Videos already there when you add a source are skipped.";
+    SubscriptionsOption => "新しい動画を確認する", "Check for new videos";
+    SubscriptionInterval => "確認する間隔", "Check every";
+    SubscriptionAutoQueueOption =>
+        "見つけた動画を待機キューへ追加する（無効の場合は知らせるだけ）",
+        "Add found videos to the queue (otherwise only notify)";
+    SubscriptionUrlHint => "チャンネルまたはプレイリストのURL", "Channel or playlist URL";
+    AddSubscription => "追加", "Add";
+    NoSubscriptions => "購読しているチャンネル・プレイリストはありません。", "No channels or playlists subscribed.";
+    SubscriptionListFailed => "一覧を取得できませんでした: {0}", "Could not list the videos: {0}";
+    SubscriptionCheckFailed => "購読元を確認できませんでした: {0}: {1}", "Could not check the subscription: {0}: {1}";
+    SubscriptionQueued => "新しい動画を{0}件、待機キューへ追加しました: {1}", "Queued {0} new videos from {1}";
+    SubscriptionFound => "新しい動画が{0}件あります: {1}", "{0} new videos from {1}";
+    SubscriptionUpload => "新しい動画: {0} ({1})", "New video: {0} ({1})";
+    OnBattery => "バッテリー駆動中", "On battery";
+    BatterySaverOption => "インデックスを控えめにする", "Index lighter";
+    DeferGpuConversionsOption =>
+        "GPU（VideoToolbox）の変換を電源につなぐまで待つ",
+        "Hold GPU (VideoToolbox) conversions until on AC power";
+    LowPowerIndexing =>
+        "バッテリー駆動中のため、インデックスを控えめにします（自動の再インデックスは電源につなぐまで待ちます）。",
+        "On battery power; indexing runs lighter and automatic reindexing waits for AC power.";
+    LowPowerEnded => "電源につながりました。インデックスを通常どおり行います。", "On AC power; indexing runs normally again.";
+    WaitingForAcPower =>
+        "バッテリー駆動中のため、電源につなぐまで変換を待っています。",
+        "On battery power; waiting for AC power before converting.";
+    WaitingForConversionSlot =>
+        "ほかのクリップの変換が終わるのを待っています。",
+        "Waiting for other clips to finish converting.";
+    ConversionHandedOff =>
+        "ダウンロードを終えました。変換を続けながら次のダウンロードを始めます: {0}",
+        "Download finished. Converting while the next download starts: {0}";
+    BackgroundConversions => "変換中", "Converting";
+    DirectRemux =>
+        "直リンクの動画はmp4互換のコーデックのため、変換せずにmp4へ詰め替えます。",
+        "The direct link already uses MP4-compatible codecs; remuxing to MP4 without converting.";
+    HardwareDecode =>
+        "映像のデコードにVideoToolboxを使います（非対応のコーデックはソフトウェアでデコードします）。",
+        "Decoding video with VideoToolbox (unsupported codecs fall back to software decoding).";
+    HardwareDecodeSpeed =>
+        "ハードウェアデコードで変換しました: {0}秒の動画を{1}秒（{2}倍速）",
+        "Converted with hardware decoding: {0}s of video in {1}s ({2}x)";
+    SoftwareDecodeSpeed =>
+        "ソフトウェアデコードで変換しました: {0}秒の動画を{1}秒（{2}倍速）",
+        "Converted with software decoding: {0}s of video in {1}s ({2}x)";
+    Watermark => "透かし", "Watermark";
+    WatermarkHint => "変換時に重ねるPNG（空欄で重ねない）", "PNG to overlay when converting (empty for none)";
+    ChoosePng => "PNGを選択", "Choose PNG";
+    WatermarkPosition => "透かしの位置", "Watermark position";
+    Opacity => "不透明度", "Opacity";
+    AudioTrack => "音声トラック", "Audio track";
+    AudioTrackPromptOption =>
+        "複数の音声がある場合は、ダウンロード前に使うトラックを選ぶ",
+        "Choose the audio track before downloading when there are several";
+    PreviewPrompt => "ダウンロード前の確認", "Confirm before downloading";
+    PreviewPromptOption =>
+        "タイトル・長さ・投稿者を確認してからダウンロードする",
+        "Check the title, length and uploader before downloading";
+    Notifications => "完了通知", "Notifications";
+    NotificationsOption =>
+        "非アクティブ時に完了・失敗を通知する",
+        "Notify about finished and failed downloads while inactive";
+    MenuBar => "メニューバー", "Menu bar";
+    MenuBarOption => "進捗と操作メニューをメニューバーに表示する", "Show progress and controls in the menu bar";
+    InputSource => "入力ソース", "Input source";
+    InputSourceNotifyOption =>
+        "入力ソースを切り替えたときにステータスへ表示する",
+        "Show a status message when the input source changes";
+    LaunchAtLogin => "ログイン時に起動", "Launch at login";
+    LaunchAtLoginOption => "ログイン時にVJDownloaderを起動する", "Launch VJDownloader at login";
+    LoginItemApproval =>
+        "システム設定の「ログイン項目」で許可すると起動します。",
+        "Allow it under Login Items in System Settings to launch.";
+    LoginItemUnsupported =>
+        "macOS 13以降で.appとして起動した場合のみ設定できます。",
+        "Only available on macOS 13 or later when running as an .app.";
+    ProfileNotice =>
+        "出力先フォルダ・エンコード方式・画質の上限をまとめて保存し、ダウンロード画面から切り替えます。",
+        "Save the output folder, encoder and maximum quality together and switch between them on the download screen.";
+    ProfileName => "プロファイル名", "Profile name";
+    SaveCurrentSettings => "現在の設定で保存", "Save Current Settings";
+    Delete => "削除", "Delete";
+    YouTubeAuth => "YouTube認証", "YouTube authentication";
+    YouTubeAuthNotice =>
+        "bot確認が出る場合のみ有効化してください。ブラウザ名とプロファイルはyt-dlpの--cookies-from-browserに渡されます。",
+        "Enable only when YouTube asks you to confirm you are not a bot. The browser and profile are passed to yt-dlp's --cookies-from-browser.";
+    UseBrowserCookies => "ブラウザのクッキーを使う（bot確認対策）", "Use browser cookies (for bot checks)";
+    BrowserName => "ブラウザ名", "Browser";
+    BrowserHint => "例: chrome / firefox / safari", "e.g. chrome / firefox / safari";
+    BrowserProfileHint => "例: Default / Profile 1", "e.g. Default / Profile 1";
+    AccountNotice =>
+        "ブラウザとプロファイルの組に名前を付けると、ダウンロード画面でダウンロードごとに切り替えられます。",
+        "Name a browser and profile pair to switch it per download on the download screen.";
+    Name => "名前", "Name";
+    AddAccount => "アカウントを追加", "Add Account";
+    SiteLogins => "サイトのログイン", "Site logins";
+    SiteLoginsNotice =>
+        "ログインが必要なサイトのURLでは、yt-dlpに--username/--passwordを渡します。パスワードはキーチェーンに保存します。",
+        "For URLs of sites that need a login, --username/--password are passed to yt-dlp. Passwords are stored in the keychain.";
+    SiteHint => "例: nicovideo.jp", "e.g. nicovideo.jp";
+    Username => "ユーザー名", "Username";
+    PasswordHint => "変更時のみ入力", "Only to change";
+    AddLogin => "ログインを追加", "Add Login";
+    Osc => "OSC連携", "OSC";
+    OscNotice =>
+        "/vjd/download <url>・/vjd/search <query>・/vjd/stop を受信し、進捗を送信先へ返します。",
+        "Receives /vjd/download <url>, /vjd/search <query> and /vjd/stop, and sends progress back to the feedback address.";
+    OscOption => "OSCで操作を受け付ける", "Accept OSC commands";
+    ListenPort => "受信ポート", "Listen port";
+    OscPortHint => "例: 9000", "e.g. 9000";
+    FeedbackAddress => "フィードバック送信先", "Feedback address";
+    FeedbackHint => "例: 127.0.0.1:9001（空欄で送信しない）", "e.g. 127.0.0.1:9001 (empty to disable)";
+    OscAllowLan =>
+        "同じネットワークの他の機器からも受け付ける",
+        "Accept commands from other devices on the network";
+    EventStream => "WebSocket配信", "WebSocket stream";
+    EventStreamNotice =>
+        "ws://127.0.0.1:<ポート> に接続した外部ダッシュボードへ、ダウンロードの進捗・ログ・完了をJSONで配信します。",
+        "Streams download progress, logs and completions as JSON to dashboards connected to ws://127.0.0.1:<port>.";
+    EventStreamOption => "進捗イベントを配信する", "Stream progress events";
+    Port => "ポート", "Port";
+    EventStreamPortHint => "例: 9002", "e.g. 9002";
+    AllowedOrigins => "許可するOrigin", "Allowed origins";
+    AllowedOriginsHint =>
+        "例: https://dashboard.example（カンマ区切り。ブラウザのページは許可したOriginからだけ接続できます）",
+        "e.g. https://dashboard.example (comma-separated; browser pages can only connect from allowed origins)";
+    SearchRoots => "検索対象フォルダ", "Search folders";
+    ReindexAll => "全体を再インデックス", "Reindex All";
+    SearchRootsNotice =>
+        "mp4検索対象のルートフォルダを複数指定できます。",
+        "You can add several root folders to search for mp4 files.";
+    ReadFinderTagsOption =>
+        "インデックス時にFinderタグを読み込む（tag:で検索）",
+        "Read Finder tags when indexing (search with tag:)";
+    FollowLinksOption => "リンクをたどる", "Follow links";
+    FollowLinksHint =>
+        "このフォルダ内のシンボリックリンク先のフォルダ・動画もインデックスします。",
+        "Also index folders and videos that symbolic links in this folder point to.";
+    CloudFetchOnDragOption =>
+        "クラウドにだけあるファイルをドラッグしようとした時に取得を始める",
+        "Start downloading cloud-only files when dragged";
+    IndexSkipLabel => "インデックスしない項目", "Don't index";
+    IndexSkipHidden => "隠しファイル", "Hidden items";
+    IndexSkipBundles => "パッケージ（.appなど）", "Bundles (.app etc.)";
+    IndexSkipSystem => "システムフォルダ", "System folders";
+    IndexSkipNamesHint =>
+        "除外するフォルダ名・ファイル名（カンマ区切り）",
+        "Folder or file names to skip (comma-separated)";
+    SearchInputModeLabel => "検索欄の入力モード", "Search field input";
+    SearchInputKeep => "切り替えない", "Don't switch";
+    SearchInputEnglish => "英字", "English";
+    SearchInputJapanese => "日本語", "Japanese";
+    AddFolder => "フォルダを追加", "Add Folder";
+    NoSearchRoots => "検索対象フォルダが未設定です。", "No search folders yet.";
+    FaststartAudit => "faststartの確認", "faststart check";
+    CheckAndFix => "確認して修正", "Check and Fix";
+    FaststartNotice =>
+        "検索対象のmp4のうち、moovが末尾にあるものや断片化しているものを先頭へ移します。元のファイルは「.bak」を付けて残します。",
+        "Moves the moov atom to the front of searched mp4 files where it is at the end or fragmented. The original is kept with a \".bak\" suffix.";
+    FaststartChecking => "確認中... {0} / {1}", "Checking... {0} / {1}";
+    FaststartReport =>
+        "{0}件を確認し、{1}件を修正しました。読み取れない: {2}件 / 失敗: {3}件",
+        "Checked {0} files and fixed {1}. Unreadable: {2} / Failed: {3}";
+    SelfCheck => "診断", "Diagnostics";
+    RunSelfCheck => "診断する", "Run Diagnostics";
+    SelfCheckNotice =>
+        "ツール・エンコーダ・保存先・検索インデックス・ネットワークに問題がないかを確かめます。",
+        "Checks the tools, encoder, output folder, search index and network for problems.";
+    SelfCheckRunning => "診断中...", "Checking...";
+    SelfCheckAllPassed => "{0}項目すべて問題ありません。", "All {0} checks passed.";
+    SelfCheckSomeFailed => "{0}項目のうち{1}項目に問題があります。", "{1} of {0} checks found a problem.";
+    CheckOutputDir => "保存先への書き込み", "Writing to the output folder";
+    CheckSearchIndex => "検索インデックス", "Search index";
+    CheckNetwork => "ネットワーク", "Network";
+    CheckNetworkOnline => "github.com / youtube.com に接続できます", "Can reach github.com / youtube.com";
+    CheckToolMissing => "見つかりません: {0}", "Not found: {0}";
+    CheckToolNotExecutable => "実行できません: {0}", "Not executable: {0}";
+    CheckVideoToolboxMissing =>
+        "h264_videotoolbox が使えないため、{0}で変換します（時間がかかります）",
+        "h264_videotoolbox is not available, so {0} is used (slower)";
+    FixToolSetup =>
+        "下のツール欄の「最新を取得」で入れ直してください。",
+        "Reinstall it with \"Get Latest\" in the tools section below.";
+    FixVideoToolbox =>
+        "ffmpegを最新版に更新してください。VideoToolbox対応のffmpegが必要です。",
+        "Update ffmpeg to the latest version. An ffmpeg built with VideoToolbox is required.";
+    FixOutputDir =>
+        "保存先を書き込めるフォルダに変えるか、Finderの「情報を見る」でアクセス権を確認してください。",
+        "Choose a writable output folder, or check the permissions in Finder's Get Info.";
+    FixSearchIndexCorrupt =>
+        "検索インデックスのバックアップから戻すか、「全体を再インデックス」で作り直してください。",
+        "Restore the search index from a backup, or rebuild it with \"Reindex All\".";
+    FixSearchIndexNotReady =>
+        "アプリを再起動してください。直らない場合はバックアップから戻してください。",
+        "Restart the app. If that does not help, restore the index from a backup.";
+    FixNetwork =>
+        "Wi-Fiなどの接続と、プロキシ・ファイアウォールの設定を確認してください。",
+        "Check your Wi-Fi or other connection and your proxy / firewall settings.";
+    Unreadable => "読み取れない: {0}", "Unreadable: {0}";
+    FailedItem => "失敗: {0} ({1})", "Failed: {0} ({1})";
+    VersionLabel => "バージョン: {0}", "Version: {0}";
+    Required => "必須", "Required";
+    OpenInstaller => "インストーラーを開く", "Open Installer";
+    CheckForUpdates => "更新を確認", "Check for Updates";
+    AppVersion => "バージョン: {0}", "Version: {0}";
+    UpdateIdle => "GitHubのリリースから新しいバージョンを確認します。", "Checks GitHub releases for a new version.";
+    CheckingForUpdates => "更新を確認中...", "Checking for updates...";
+    UpToDate => "最新のバージョンです。", "You have the latest version.";
+    UpdateAvailable => "新しいバージョン {0} があります。", "Version {0} is available.";
+    UpdateStaging => "{0} をダウンロードして検証中...", "Downloading and verifying {0}...";
+    UpdateStaged =>
+        "{0} の準備ができました。アプリを終了してから置き換えてください。",
+        "{0} is ready. Quit the app before replacing it.";
+    OpenInstallerFailed => "インストーラーを開けませんでした: {0}", "Could not open the installer: {0}";
+    WindowSizeNotNumber => "画面の幅/高さは数値で入力してください。", "Enter numbers for the window width and height.";
+    BrowserNameRequired => "ブラウザ名を入力してください。", "Enter a browser name.";
+    AccountFieldsRequired =>
+        "アカウントの名前とブラウザ名を入力してください。",
+        "Enter a name and a browser for each account.";
+    DuplicateAccountName =>
+        "アカウント名「{0}」が重複しています。",
+        "The account name \"{0}\" is used more than once.";
+    ExtraArgsSplitFailed =>
+        "yt-dlpの詳細オプションを分割できません: {0}",
+        "Could not split the yt-dlp extra options: {0}";
+    WatermarkNotPng => "透かしにはPNGファイルを指定してください。", "Choose a PNG file for the watermark.";
+    CreateFolderFailed => "フォルダを作成できませんでした: {0}", "Could not create the folder: {0}";
+    LoginFieldsRequired =>
+        "ログインのサイトとユーザー名を入力してください。",
+        "Enter a site and a username for each login.";
+    PasswordRequired => "{0} のパスワードを入力してください。", "Enter the password for {0}.";
+    SearchRootNotDirectory =>
+        "検索対象フォルダがディレクトリではありません: {0}",
+        "The search folder is not a directory: {0}";
+
+    // ダウンロードの状態
+    JobRunning => "実行中", "Running";
+    JobCompleted => "完了", "Completed";
+    JobFailed => "失敗", "Failed";
+
+    // タグと評価
+    JobCancelled => "キャンセル", "Cancelled";
+
+    // ダウンロードの進捗とエラー
+    FetchingInfo => "動画情報確認中・・・{0}", "Checking video info...{0}";
+    LoadingVideo => "動画読み込み中...{0}", "Loading video...{0}";
+    DownloadingPercent => "ダウンロード中... {0}%{1}", "Downloading... {0}%{1}";
+    DownloadingSize => "ダウンロード中... {0}{1}", "Downloading... {0}{1}";
+    Converting => "変換中...{0}", "Converting...{0}";
+    ConvertingPercent => "変換中... {0}%{1}", "Converting... {0}%{1}";
+    PassAnalyzing => "解析中", "Analyzing";
+    PassConverting => "変換中", "Converting";
+    PassProgress => "{0}（{1}/2パス）... {2}%{3}", "{0} (pass {1}/2)... {2}%{3}";
+    Finalizing => "保存先へ移動中...{0}", "Moving to the output folder...{0}";
+    DownloadComplete => "ダウンロード完了!{0}", "Download complete!{0}";
+    SkippedAlreadyDownloadedPath =>
+        "ダウンロード済みのためスキップしました: {0}",
+        "Skipped because it was already downloaded: {0}";
+    RedownloadMissingFile =>
+        "以前に保存したファイルが見つからないため、もう一度ダウンロードします。",
+        "The previously saved file is missing, so it will be downloaded again.";
+    FfmpegNotFound => "ffmpegが見つかりません。", "ffmpeg was not found.";
+    YtDlpNotFound => "yt-dlpが見つかりません。", "yt-dlp was not found.";
+    CreateOutputDirFailed => "保存先フォルダの作成に失敗しました: {0}", "Could not create the output folder: {0}";
+    ExtraArgsAppended =>
+        "設定の詳細オプションをyt-dlpに追加します（失敗する場合は設定を見直してください）: {0}",
+        "Adding the extra options from Settings to yt-dlp (check them if the download fails): {0}";
+    CheckingDiskSpace => "保存先の空き容量を確認しています。", "Checking free space in the output folder.";
+    SkippedArchived =>
+        "yt-dlpのダウンロードアーカイブに記録済みのため、スキップしました。",
+        "Skipped because it is already in the yt-dlp download archive.";
+    ResumingStaging => "前回の作業フォルダから再開します: {0}", "Resuming from the previous working folder: {0}";
+    H264FallbackRetry =>
+        "H.264優先モードに失敗。互換モードで再試行します。",
+        "H.264 preferred mode failed. Retrying in compatible mode.";
+    TrialDownloadFailed => "試しのダウンロードに失敗しました: {0}", "The test download failed: {0}";
+    TrialNoFile => "yt-dlpは終了しましたが、mp4が保存されませんでした。", "yt-dlp finished, but no mp4 was saved.";
+    TrialNoVideoStream => "保存したmp4に映像がありません。", "The saved mp4 has no video stream.";
+    YtDlpRunFailed => "yt-dlpの実行に失敗しました: {0}", "Could not run yt-dlp: {0}";
+    VideoInfoSaveFailed => "動画情報の保存に失敗しました（検索は続けて使えます）: {0}", "Could not save the video info (search still works): {0}";
+    RemoveTempDirFailed => "一時フォルダの削除に失敗しました: {0}", "Could not remove the temporary folder: {0}";
+    Elapsed => " (経過: {0})", " (elapsed: {0})";
+
+    // 設定で対処できるエラー
+    BotCheckTitle => "ボットの確認でブロックされました", "Blocked by a bot check";
+    BotCheckDescription =>
+        "YouTubeがログインを求めています。Cookie設定でログイン済みのブラウザを選ぶと、ダウンロードできるようになります。",
+        "YouTube is asking you to sign in. Choose a browser you are signed in with in the Cookie settings to download this video.";
+    AgeRestrictedTitle => "年齢制限のある動画です", "Age-restricted video";
+    AgeRestrictedDescription =>
+        "年齢確認のためにログインが必要です。Cookie設定で、年齢確認済みのアカウントでログインしているブラウザを選んでください。",
+        "This video requires signing in to confirm your age. In the Cookie settings, choose a browser signed in with an age-verified account.";
+    MembersOnlyTitle => "メンバー限定の動画です", "Members-only video";
+    MembersOnlyDescription =>
+        "チャンネルのメンバーだけが見られる動画です。Cookie設定で、メンバーのアカウントでログインしているブラウザを選んでください。",
+        "Only channel members can watch this video. In the Cookie settings, choose a browser signed in with a member account.";
+    GeoBlockedTitle => "この地域では見られない動画です", "Not available in your region";
+    GeoBlockedDescription =>
+        "投稿者がこの国での公開を制限しています。詳細オプションでプロキシ（--proxy）などを指定すると取得できる場合があります。",
+        "The uploader has restricted this video in your country. Setting a proxy (--proxy) in the extra options may allow the download.";
+    OpenCookieSettings => "Cookie設定を開く", "Open Cookie Settings";
+    OpenExtraOptions => "詳細オプションを開く", "Open Extra Options";
+    ViewLogs => "ログを見る", "View Logs";
+
+    // ダウンロードの段階
+    StagePreparing => "準備中", "preparing";
+    StageDownloading => "ダウンロード中", "downloading";
+
+    // ダウンロードの進捗とエラー
+    StageConverting => "変換中", "converting";
+
+    // ダウンロードの段階
+    StageFinishing => "保存先へ移動中", "moving to the output folder";
+
+    // 変換の方式
+    QualityStandard => "標準", "Standard";
+    QualityConstant => "画質優先（品質指定）", "Quality (constant quality)";
+    QualityTwoPass => "画質優先（2パス）", "Quality (2-pass)";
+    QualityTargetSize => "サイズ指定", "Target size";
+    QualityIgnoredForCopy =>
+        "エンコード方式がコピーのため、画質の指定は使わずに映像をそのまま保存します。",
+        "The encoder is set to copy, so the video is saved as is without applying the quality mode.";
+    NoReencode => "再エンコードなし", "No re-encoding";
+    CropAreaLabel => "{0}x{1}（左 {2}px・上 {3}px から）", "{0}x{1} (from left {2}px, top {3}px)";
+    AboutSize => "約{0}", "~{0}";
+
+    // 外部ツールの実行
+    ToolLaunchFailed => "{0}の起動に失敗しました: {1}", "Could not start {0}: {1}";
+    ToolWaitFailed => "{0}の終了待ちに失敗しました: {1}", "Failed while waiting for {0}: {1}";
+    ToolExitedWithError => "{0}が異常終了しました: {1}", "{0} failed: {1}";
+    PipelineLaunchFailed => "パイプライン起動に失敗しました: {0}", "Could not start the pipeline: {0}";
+    PipelineWaitFailed => "パイプライン終了待ちに失敗しました: {0}", "Failed while waiting for the pipeline: {0}";
+    PipelineExitedWithError => "パイプラインが異常終了しました: {0}", "The pipeline failed: {0}";
+    PipeInputFailed => "パイプ入力の取得に失敗しました。", "Could not open the pipe input.";
+    VideoInfoParseFailed => "動画情報を解析できませんでした: {0}", "Could not parse the video info: {0}";
+    VideoInfoFetchFailed => "動画情報の取得に失敗しました: {0}", "Could not get the video info: {0}";
+    JsonParseFailed => "JSON解析に失敗しました: {0}", "Could not parse the JSON: {0}";
+
+    // AnimeThemes の直リンク
+    AnimeThemesDirectLinkFound => "AnimeThemes直リンクを取得しました: {0}", "Got the AnimeThemes direct link: {0}";
+    AnimeThemesDirectLinkFailed => "AnimeThemes直リンク経路で失敗しました: {0}", "The AnimeThemes direct link failed: {0}";
+    AnimeThemesSwitchToYtDlp => "yt-dlpフォールバックへ切り替えます。", "Switching to the yt-dlp fallback.";
+    AnimeThemesNoDirectLink =>
+        "AnimeThemes直リンク取得に失敗。yt-dlpでフォールバックします。",
+        "Could not get the AnimeThemes direct link. Falling back to yt-dlp.";
+    AnimeThemesSlugFailed =>
+        "AnimeThemes URL解析に失敗。HTML解析へフォールバックします。",
+        "Could not parse the AnimeThemes URL. Falling back to the page HTML.";
+    AnimeThemesApiFailed => "AnimeThemes API取得に失敗しました: {0}", "Could not fetch the AnimeThemes API: {0}";
+    AnimeThemesApiParseFailed =>
+        "AnimeThemes APIレスポンス解析に失敗しました: {0} ({1})",
+        "Could not parse the AnimeThemes API response: {0} ({1})";
+    AnimeThemesApiNoLink =>
+        "AnimeThemes APIに対象テーマの直リンクがありません。HTML解析へフォールバックします。",
+        "The AnimeThemes API has no direct link for this theme. Falling back to the page HTML.";
+    AnimeThemesPageFailed => "AnimeThemesページ取得に失敗しました: {0}", "Could not fetch the AnimeThemes page: {0}";
+    AnimeThemesPartialNoLink =>
+        "AnimeThemes HTML部分取得では直リンクが見つかりません。全文取得で再試行します。",
+        "No direct link in the first part of the AnimeThemes page. Retrying with the whole page.";
+    AnimeThemesFullPageFailed =>
+        "AnimeThemesページ全文取得に失敗しました: {0}",
+        "Could not fetch the whole AnimeThemes page: {0}";
+    StreamingStarted => "動画ダウンロードと変換を同時に開始します。", "Downloading and converting at the same time.";
+    StreamingSize => "動画サイズを確認しました: {0}MB", "Video size: {0}MB";
+    StreamingSizeUnknown =>
+        "動画サイズを取得できなかったため、MBベースで進捗ログを表示します。",
+        "The video size is unknown, so progress is logged in MB.";
+    StreamingConvertStarted =>
+        "ffmpeg({0})でストリーミング変換を開始します。",
+        "Starting the streaming conversion with ffmpeg ({0}).";
+    CurlOutputFailed => "curl出力の取得に失敗しました。", "Could not read the curl output.";
+    FfmpegPipeFailed => "ffmpeg入力パイプの取得に失敗しました。", "Could not open the ffmpeg input pipe.";
+    StreamReadFailed => "動画ストリームの読み取りに失敗しました: {0}", "Could not read the video stream: {0}";
+    FfmpegWriteFailed => "ffmpeg入力への書き込みに失敗しました: {0}", "Could not write to the ffmpeg input: {0}";
+    DownloadProgressPercent => "ダウンロード進捗: {0}%", "Download progress: {0}%";
+    DownloadProgressSize => "ダウンロード進捗: {0}MB", "Download progress: {0}MB";
+    FfmpegConversionDone => "ffmpeg変換が完了しました。", "The ffmpeg conversion finished.";
+    ConvertWithoutProbe =>
+        "映像情報を確認できなかったため、コーデックや自動補正を確かめずに変換します: {0}",
+        "Converting without checking the codecs or auto-correction because the video info is unavailable: {0}";
+
+    // ダウンロード前の確認と選択
+    FetchingPreview => "動画の情報を取得しています。", "Getting the video info.";
+    PreviewFallback => "{0}。確認せずにダウンロードします。", "{0}. Downloading without confirmation.";
+    PreviewDeclined => "確認画面でダウンロードを取りやめました。", "The download was cancelled in the confirmation dialog.";
+    CheckingAudioTracks => "音声トラックを確認しています。", "Checking the audio tracks.";
+    AudioTrackFallback => "{0}。既定の音声でダウンロードします。", "{0}. Downloading with the default audio.";
+    AudioTracksFound =>
+        "音声トラックが{0}個あります。使うトラックを選んでください。",
+        "There are {0} audio tracks. Choose the one to use.";
+    DownloadWithAudioTrack => "音声トラック「{0}」でダウンロードします。", "Downloading with the audio track \"{0}\".";
+    DownloadWithDefaultAudio => "既定の音声トラックでダウンロードします。", "Downloading with the default audio track.";
+    CheckingFormats => "取得できる形式を確認しています。", "Checking the available formats.";
+    FormatFallback => "{0}。形式を自動で選んでダウンロードします。", "{0}. Choosing the format automatically.";
+    NoVideoFormats =>
+        "映像の形式が見つからないため、自動で選んでダウンロードします。",
+        "No video formats were found, so the format is chosen automatically.";
+    FormatsFound =>
+        "{0}個の形式があります。ダウンロードする形式を選んでください。",
+        "There are {0} formats. Choose the one to download.";
+    DownloadWithFormat => "形式 {0} でダウンロードします。", "Downloading with format {0}.";
+    DownloadWithAutoFormat => "形式を自動で選んでダウンロードします。", "Choosing the format automatically.";
+    DetectingCrop => "黒帯を検出しています。", "Detecting black bars.";
+    NoCropFound => "黒帯は見つかりませんでした。", "No black bars were found.";
+    CropFallback => "{0}。切り取らずに変換します。", "{0}. Converting without cropping.";
+    CropPreviewUnavailable =>
+        "プレビューを作成できなかったため、切り取らずに変換します。",
+        "The preview is unavailable, so the video is converted without cropping.";
+    CropFoundConfirm =>
+        "黒帯を検出しました: {0}。切り取るか確認してください。",
+        "Black bars detected: {0}. Choose whether to crop them.";
+    CropDetectFailed => "黒帯の検出に失敗しました: {0}", "Could not detect the black bars: {0}";
+
+    // 変換
+    ProbeOutputParseFailed => "ffprobeの出力を解析できませんでした: {0}", "Could not parse the ffprobe output: {0}";
+    ProbeFailed => "ffprobeでの解析に失敗しました: {0}", "ffprobe failed: {0}";
+    DeinterlaceNote =>
+        "インターレース映像のため、インターレースを解除して変換します。",
+        "The video is interlaced, so it is deinterlaced while converting.";
+    CropNote => "黒帯を切り取って変換します。", "Cropping the black bars while converting.";
+    SquarePixelNote =>
+        "画素比が1:1でないため、正方ピクセルへ補正して変換します。",
+        "The pixel aspect ratio is not 1:1, so it is corrected to square pixels while converting.";
+    TonemapNote =>
+        "HDR映像のため、SDRへトーンマッピングして変換します。",
+        "The video is HDR, so it is tone-mapped to SDR while converting.";
+    NoZscale =>
+        "ffmpegにzscaleが無いため、HDR映像をトーンマッピングせずに変換します。",
+        "ffmpeg has no zscale, so the HDR video is converted without tone mapping.";
+    TargetSizeBitrate =>
+        "{0}MB以下に収めるため、映像を{1}kbpsで変換します。",
+        "Converting the video at {1}kbps to fit within {0}MB.";
+    TargetSizeNoDuration =>
+        "動画の長さを取得できないため、サイズを指定せずに変換します。",
+        "The video length is unknown, so it is converted without a target size.";
+    TargetSizeAlreadySmall => "{0}MB以下のため、サイズの調整は行いません。", "Already within {0}MB, so the size is not adjusted.";
+    WatermarkOverlaid => "透かしを重ねて変換します。", "Converting with the watermark overlaid.";
+    WatermarkMissing =>
+        "透かしのPNGが見つからないため、透かしを重ねずに変換します: {0}",
+        "The watermark PNG is missing, so the video is converted without it: {0}";
+    RemuxToMp4 =>
+        "mp4互換のコーデックのため、再エンコードせずにmp4へ詰め替えます。",
+        "The codecs are mp4-compatible, so the video is remuxed to mp4 without re-encoding.";
+    ConvertToMp4 => "mp4へ変換します。", "Converting to mp4.";
+    EncoderCheckFailed => "ffmpegエンコーダ確認に失敗しました: {0}", "Could not check the ffmpeg encoders: {0}";
+    NoH264Encoder =>
+        "ffmpegにh264_videotoolbox・libx264のどちらもありません。mp4へ変換できません。",
+        "ffmpeg has neither h264_videotoolbox nor libx264. Could not convert to mp4.";
+    EncoderUnavailable => "{0}が使えないため、{1}で変換します。", "{0} is unavailable, so {1} is used.";
+    EncoderSlowNote => "変換に時間がかかります。", " Conversion will take longer.";
+    TwoPassNeedsX264 =>
+        "2パス変換はlibx264のみ対応のため、品質指定で変換します。",
+        "Two-pass conversion needs libx264, so constant quality is used instead.";
+    TargetSizeOnePass =>
+        "libx264が使えないため1パスでサイズを合わせます。仕上がりのサイズは前後します。",
+        "libx264 is unavailable, so the size is matched in one pass. The final size may vary.";
+
+    // 空き容量
+    DiskSpaceUnknownSize =>
+        "動画サイズを推定できないため、空き容量を確認せずに続けます。",
+        "The video size is unknown, so free space is not checked.";
+    DiskSpaceUnavailable => "保存先の空き容量を確認できませんでした。", "Free space in the output folder is unknown.";
+    DiskSpaceInsufficient =>
+        "保存先の空き容量が足りません（推定 {0}MB / 空き {1}MB）。",
+        "Not enough free space in the output folder (estimated {0}MB / free {1}MB).";
+    DiskSpaceLow =>
+        "保存先の空き容量が少ないため、結合や変換の途中で失敗する可能性があります（推定 {0}MB / 空き {1}MB）。",
+        "Free space in the output folder is low, so merging or converting may fail (estimated {0}MB / free {1}MB).";
+
+    // 保存先への移動
+    StagingPrepareFailed => "一時フォルダの準備に失敗しました: {0}", "Could not prepare the temporary folder: {0}";
+    StagingCreateFailed => "一時フォルダの作成に失敗しました: {0}", "Could not create the temporary folder: {0}";
+    StagingNameFailed => "一時フォルダ名の確保に失敗しました。", "Could not find a free temporary folder name.";
+    StagingReadFailed => "一時フォルダの読み取りに失敗しました: {0}", "Could not read the temporary folder: {0}";
+    NoMp4AfterDownload =>
+        "ダウンロード完了後のMP4ファイルが見つかりませんでした。",
+        "Could not find the MP4 file after the download finished.";
+    InvalidFileName => "保存対象のファイル名が不正です。", "The file name to save is invalid.";
+    OverwroteExisting => "同名のファイルを上書きしました: {0}", "Overwrote the file with the same name: {0}";
+    SavedWithNumber => "同名のファイルがあるため、{0}として保存しました。", "A file with the same name exists, so it was saved as {0}.";
+    SkippedExisting => "同名のファイルがあるため保存しませんでした: {0}", "A file with the same name exists, so it was not saved: {0}";
+    PlaceFileFailed => "動画ファイルの配置に失敗しました: {0} -> {1} ({2})", "Could not move the video file: {0} -> {1} ({2})";
+    ResolveOutputDirFailed => "保存先フォルダの解決に失敗しました。", "Could not resolve the output folder.";
+    TooManyDuplicates =>
+        "同名ファイルが多すぎるため保存先を確保できませんでした。",
+        "Could not find a free file name because there are too many files with the same name.";
+    OriginalVideoMissing => "元の動画が見つかりません: {0}", "Could not find the original video: {0}";
+    MoveFileFailed => "ファイルの移動に失敗しました: {0}", "Could not move the file: {0}";
+    ClipCheckFailed => "クリップの確認に失敗しました: {0}", "Could not list the clips: {0}";
+    ClipMoveFailed => "クリップの移動に失敗しました: {0}", "Could not move the clips: {0}";
+    ClipWriteFailed => "クリップの書き出しに失敗しました: {0}", "Could not write the clips: {0}";
+    SceneDetectFailed => "カットの検出に失敗しました: {0}", "Could not detect the cuts: {0}";
+    AudioReplaceFailed => "音声の差し替えに失敗しました: {0}", "Could not replace the audio: {0}";
+    AudioFileMissing => "音声ファイルが見つかりません: {0}", "Could not find the audio file: {0}";
+    ProxyWriteFailed => "プロキシの書き出しに失敗しました: {0}", "Could not write the proxy: {0}";
+    ProxyMoveFailed => "プロキシの移動に失敗しました: {0}", "Could not move the proxy: {0}";
+
+    // 保存したファイルの確認
+    NoMoovBox => "moovボックスがありません", "no moov box";
+    NoVideoStream => "映像ストリームがありません", "no video stream";
+    ZeroDuration => "再生時間が0秒です", "the duration is 0 seconds";
+    DecodeFailed => "映像をデコードできません: {0}", "could not decode the video: {0}";
+    QuarantineDirFailed => "隔離フォルダの準備に失敗しました: {0}", "Could not prepare the quarantine folder: {0}";
+    QuarantineNameInvalid => "隔離するファイル名が不正です。", "The file name to quarantine is invalid.";
+    QuarantineFailed => "壊れたファイルの隔離に失敗しました: {0}", "Could not quarantine the broken file: {0}";
+    FaststartRemux =>
+        "{0} はfaststartになっていないため、moovを先頭へ移します。",
+        "{0} is not faststart, so the moov box is moved to the front.";
+    FaststartFailed =>
+        "{0} のfaststart化に失敗しました（再生開始が遅くなる可能性があります）: {1}",
+        "Could not make {0} faststart (playback may start slowly): {1}";
+    QuarantinedFile => "{0} が壊れているため隔離しました（{1}）: {2}", "{0} is broken and was quarantined ({1}): {2}";
+    QuarantinedFiles =>
+        "保存したファイルが壊れているため、{0}へ隔離しました: {1}",
+        "The saved files are broken and were moved to {0}: {1}";
+
+    // 外部ツールの取得と更新
+    ToolMissingDownloading => "{0}が見つかりません。ダウンロードします。", "{0} was not found. Downloading it.";
+    ToolDownloading => "{0}をダウンロードします。", "Downloading {0}.";
+    ToolDownloaded => "{0}をダウンロードしました。", "Downloaded {0}.";
+    ToolNotFound => "{0}が見つかりません。", "{0} was not found.";
+    ToolExtractFailed => "{0}の展開に失敗しました: {1}", "Could not extract {0}: {1}";
+    ToolDownloadFailed => "{0}のダウンロードに失敗しました: {1}", "Could not download {0}: {1}";
+    ToolUpdatePrepareFailed => "{0}の更新準備に失敗しました: {1}", "Could not prepare to update {0}: {1}";
+    ToolRestoreFailed =>
+        "{0}の更新に失敗し、旧バージョンの復元にも失敗しました: {1} (更新エラー: {2})",
+        "Updating {0} failed, and restoring the old version also failed: {1} (update error: {2})";
+    FfprobeCheckFailed => "ffprobeの起動確認に失敗しました: {0}", "Could not verify that ffprobe runs: {0}";
+    FfmpegUpdateUnsupported =>
+        "この OS では ffmpeg の自動更新に対応していません。",
+        "Automatic ffmpeg updates are not supported on this OS.";
+
+    // アプリの更新
+    ReleaseParseFailed => "リリース情報を解析できませんでした: {0}", "Could not parse the release info: {0}";
+    ReleaseNoInstaller => "リリースにインストーラー（.dmg/.zip）がありません。", "The release has no installer (.dmg/.zip).";
+    ReleaseFetchFailed => "リリース情報の取得に失敗しました: {0}", "Could not get the release info: {0}";
+    ChecksumUnpublished =>
+        "チェックサムが公開されていないため、更新を検証できません。",
+        "Could not verify the update because no checksum is published.";
+    ChecksumNotFound => "{0}のチェックサムが見つかりませんでした。", "Could not find the checksum for {0}.";
+    ChecksumMismatch =>
+        "チェックサムが一致しません（期待値: {0}, 実際: {1}）。",
+        "The checksum does not match (expected: {0}, actual: {1}).";
+    ChecksumFailed => "チェックサムの計算に失敗しました。", "Could not compute the checksum.";
+    UpdateDownloadFailed => "ダウンロードに失敗しました: {0}", "The download failed: {0}";
+
+    // ネットワークとファイル操作
+    OfflineError => "オフラインです。ネットワーク接続を確認してください。", "You are offline. Check your network connection.";
+    ExtractCommandFailed => "展開コマンドの起動に失敗しました: {0}", "Could not start the extract command: {0}";
+    ZipCommandFailed => "圧縮コマンドの起動に失敗しました: {0}", "Could not start the zip command: {0}";
+    OpenerFailed => "{0} が失敗しました: {1}", "{0} failed: {1}";
+    FileNotFound => "ファイルが見つかりません。", "The file was not found.";
+
+    // URL スキームと AppleScript
+    UrlParseFailed => "URLを解析できません: {0}", "Could not parse the URL: {0}";
+    UnsupportedUrlScheme => "未対応のURLスキームです: {0}", "Unsupported URL scheme: {0}";
+    DownloadUrlMissing => "ダウンロードするURL（url=...）が指定されていません。", "No URL to download (url=...) was given.";
+    UnsupportedAction => "未対応の操作です: {0}", "Unsupported action: {0}";
+    InvalidDownloadUrl => "ダウンロードURLが不正です: {0}", "Invalid download URL: {0}";
+    DownloadUrlNotWeb =>
+        "ダウンロードURLは http/https のみ対応しています: {0}",
+        "Only http/https download URLs are supported: {0}";
+    UnsupportedCommand => "未対応のコマンドです。", "Unsupported command.";
+    EnterDownloadUrl => "ダウンロードするURLを指定してください。", "Specify a URL to download.";
+    SearchUnavailable => "検索を実行できません。", "Could not run the search.";
+
+    // OSC
+    OscPortOpenFailed => "OSCポート {0} を開けませんでした: {1}", "Could not open OSC port {0}: {1}";
+    OscPortInvalid =>
+        "OSCの受信ポートは1〜65535の数値で入力してください。",
+        "Enter a number from 1 to 65535 for the OSC receive port.";
+    OscTargetInvalid =>
+        "OSCの送信先を解決できません（例: 127.0.0.1:9001）: {0}",
+        "Could not resolve the OSC target (e.g. 127.0.0.1:9001): {0}";
+    OscBundleTooDeep => "OSCバンドルの入れ子が深すぎます。", "The OSC bundle is nested too deeply.";
+    OscBundleInvalid => "OSCバンドルが不正です。", "Invalid OSC bundle.";
+    OscAddressInvalid => "OSCアドレスが不正です。", "Invalid OSC address.";
+    OscTypeTagInvalid => "OSC型タグが不正です。", "Invalid OSC type tag.";
+    OscTypeTagUnsupported => "未対応のOSC型タグです: {0}", "Unsupported OSC type tag: {0}";
+    OscBlobInvalid => "OSCブロブが不正です。", "Invalid OSC blob.";
+    OscStringUnterminated => "OSC文字列が終端されていません。", "The OSC string is not terminated.";
+    OscPacketTruncated => "OSCパケットが途中で終わっています。", "The OSC packet is truncated.";
+
+    // WebSocket 配信のログ
+    StreamCropPending => "黒帯の切り取りを確認中: {0}", "Checking the black bar crop: {0}";
+    StreamAudioTrackPending => "音声トラックの選択待ち: {0}", "Waiting for an audio track choice: {0}";
+    StreamPreviewPending => "ダウンロード前の確認待ち: {0}", "Waiting for confirmation before downloading: {0}";
+    StreamFormatPending => "形式の選択待ち: {0}件", "Waiting for a format choice: {0} formats";
+    StreamCollisionPending => "同名ファイルの扱いを確認中: {0}", "Asking how to handle the existing file: {0}";
+    StreamAlreadyDownloaded => "ダウンロード済み: {0}", "Already downloaded: {0}";
+    StreamAlreadyInArchive =>
+        "ダウンロード済み（ダウンロードアーカイブに記録済み）",
+        "Already downloaded (recorded in the download archive)";
+    StreamDownloaded => "ダウンロード完了、変換中", "Downloaded, converting";
+    StreamKnownError => "既知のエラー: {0}", "Known error: {0}";
+    EventStreamPortOpenFailed => "WebSocketポート {0} を開けませんでした: {1}", "Could not open WebSocket port {0}: {1}";
+    EventStreamPortInvalid =>
+        "WebSocketのポートは1〜65535の数値で入力してください。",
+        "Enter a number from 1 to 65535 for the WebSocket port.";
+
+    // キーチェーンとログイン項目
+    KeychainReadFailed =>
+        "キーチェーンの読み込みに失敗しました（OSStatus {0}）。",
+        "Could not read from the keychain (OSStatus {0}).";
+    KeychainSaveFailed =>
+        "キーチェーンの保存に失敗しました（OSStatus {0}）。",
+        "Could not save to the keychain (OSStatus {0}).";
+    KeychainDeleteFailed =>
+        "キーチェーンの削除に失敗しました（OSStatus {0}）。",
+        "Could not delete from the keychain (OSStatus {0}).";
+    KeychainPasswordInvalid => "キーチェーンのパスワードが不正です。", "The password in the keychain is invalid.";
+    KeychainUnsupported => "キーチェーンは macOS でのみ利用できます。", "The keychain is only available on macOS.";
+    LoginItemNeedsApp =>
+        "ログイン時の起動は macOS 13 以降で .app として起動した場合のみ設定できます。",
+        "Launch at login can only be set on macOS 13 or later when running as an .app.";
+    LoginItemChangeFailed => "ログイン項目を変更できませんでした: {0}", "Could not change the login item: {0}";
+    LoginItemMacOnly => "ログイン時の起動は macOS でのみ利用できます。", "Launch at login is only available on macOS.";
+    UnknownError => "不明なエラー", "Unknown error";
+
+    // 呼び出しショートカット
+    HotkeyUnknownKey => "ショートカットのキーを認識できません: {0}", "Unknown shortcut key: {0}";
+    HotkeySingleKey => "ショートカットに指定できるキーは1つだけです。", "A shortcut can only have one key.";
+    HotkeyKeyRequired =>
+        "ショートカットにキーを指定してください（例: cmd+shift+space）。",
+        "Specify a key for the shortcut (e.g. cmd+shift+space).";
+    HotkeyModifierRequired =>
+        "ショートカットには修飾キー（cmd/shift/option/ctrl）を含めてください。",
+        "Include a modifier key (cmd/shift/option/ctrl) in the shortcut.";
+    HotkeyMainThread =>
+        "ショートカットの登録はメインスレッドで行う必要があります。",
+        "The shortcut must be registered on the main thread.";
+    HotkeyHandlerFailed =>
+        "ショートカットのイベントハンドラを登録できませんでした (OSStatus {0})",
+        "Could not install the shortcut event handler (OSStatus {0})";
+    HotkeyInUse => "このショートカットは他のアプリで使用されています。", "This shortcut is used by another app.";
+    HotkeyRegisterStatusFailed =>
+        "ショートカットを登録できませんでした (OSStatus {0})",
+        "Could not register the shortcut (OSStatus {0})";
+
+    // faststart の修正とバグレポート
+    FaststartFfmpegFailed => "ffmpeg起動に失敗しました: {0}", "Could not start ffmpeg: {0}";
+    FaststartWriteBackFailed => "書き戻しに失敗しました: {0}", "Could not write the file back: {0}";
+    FaststartBackupExists => "バックアップが既にあります: {0}", "A backup already exists: {0}";
+    FaststartBackupFailed => "バックアップに失敗しました: {0}", "Could not back up the file: {0}";
+    BugReportWriteFailed => "{0} を書き出せませんでした: {1}", "Could not write {0}: {1}";
+    BugReportWorkDirFailed => "作業フォルダを作成できませんでした: {0}", "Could not create the work folder: {0}";
+    BugReportSettingsFailed => "設定を読み込めませんでした: {0}", "Could not read the settings: {0}";
+    BugReportToolVersionUnknown => "不明（{0}）", "Unknown ({0})";
+
+    // ログファイルと検索対象フォルダ
+    LogDirCreateFailed => "ログフォルダを作成できません: {0}", "Could not create the log folder: {0}";
+    LogFileOpenFailed => "ログファイルを開けません: {0}", "Could not open the log file: {0}";
+    SearchRootInvalid =>
+        "検索対象フォルダが存在しないか、ディレクトリではありません: {0}",
+        "The search folder does not exist or is not a directory: {0}";
+
+    // Finder タグ
+    FinderTagsParseFailed => "Finderタグの形式を解析できませんでした。", "Could not parse the Finder tags.";
+    FinderTagsMacOnly => "Finderタグは macOS でのみ利用できます。", "Finder tags are only available on macOS.";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeholders(text: &str) -> Vec<&str> {
+        let mut found = text
+            .match_indices('{')
+            .filter_map(|(start, _)| text[start..].split_once('}').map(|(head, _)| head))
+            .collect::<Vec<_>>();
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+
+    #[test]
+    fn translations_use_the_same_placeholders() {
+        for msg in Msg::ALL {
+            let (ja, en) = (msg.text(Language::Japanese), msg.text(Language::English));
+            assert!(!en.trim().is_empty(), "{msg:?}");
+            assert_eq!(placeholders(ja), placeholders(en), "{msg:?}");
+        }
+        assert_eq!(
+            fill("{1} を {0} へ（{2}）", &[&"dir", &3]),
+            "3 を dir へ（{2}）"
+        );
+    }
+}
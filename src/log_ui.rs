@@ -4,20 +4,37 @@ use arboard::Clipboard;
 use eframe::egui;
 
 use crate::app::DownloaderApp;
+use crate::app_logger::{JobStatus, LogLevel, LogRecord, LogSource};
 use crate::cursor::pointing;
+use crate::i18n::{Msg, tr, tr_fmt};
 
 pub struct LogUiState {
     pub show_logs: bool,
+    // このレベル以上のログだけを表示する。
+    min_level: LogLevel,
+    // 表示しない出どころ
+    hidden_sources: Vec<LogSource>,
+    // ダウンロードごとに折りたためる見出しでまとめる。オフの場合は時刻順に並べる。
+    group_by_job: bool,
 }
 
 impl LogUiState {
     pub fn new() -> Self {
-        Self { show_logs: false }
+        Self {
+            show_logs: false,
+            min_level: LogLevel::Info,
+            hidden_sources: Vec::new(),
+            group_by_job: true,
+        }
     }
 
     pub fn open_logs(&mut self) {
         self.show_logs = true;
     }
+
+    fn shows(&self, record: &LogRecord) -> bool {
+        record.level >= self.min_level && !self.hidden_sources.contains(&record.source)
+    }
 }
 
 impl Default for LogUiState {
@@ -39,7 +56,7 @@ pub fn render_log_viewport(
     let mut close_requested = false;
     let viewport_id = log_viewport_id();
     let builder = egui::ViewportBuilder::default()
-        .with_title("ログ")
+        .with_title(tr(Msg::Log))
         .with_inner_size(egui::vec2(760.0, 460.0))
         .with_min_inner_size(egui::vec2(520.0, 280.0))
         .with_always_on_top();
@@ -52,7 +69,7 @@ pub fn render_log_viewport(
         match class {
             egui::ViewportClass::Embedded => {
                 let mut open = true;
-                egui::Window::new("ログ")
+                egui::Window::new(tr(Msg::Log))
                     .collapsible(false)
                     .resizable(true)
                     .default_width(740.0)
@@ -85,6 +102,7 @@ fn render_log_contents(
 ) {
     let mut copy_clicked = false;
     let mut clear_clicked = false;
+    let mut export_clicked = false;
     egui::Frame::NONE
         .inner_margin(egui::Margin {
             left: 12,
@@ -94,12 +112,14 @@ fn render_log_contents(
         })
         .show(ui, |ui| {
             ui.label(
-                egui::RichText::new("ログ")
+                egui::RichText::new(tr(Msg::Log))
                     .size(14.0)
                     .strong()
                     .color(egui::Color32::from_rgb(226, 232, 240)),
             );
             ui.add_space(8.0);
+            render_log_filters(ui, &mut app.log_ui);
+            ui.add_space(8.0);
 
             let list_height = (ui.available_height() - 42.0).max(130.0);
             egui::Frame::NONE
@@ -119,30 +139,21 @@ fn render_log_contents(
                             if app.status_logs.is_empty() {
                                 ui.add_space(4.0);
                                 ui.label(
-                                    egui::RichText::new("ログはまだありません。")
+                                    egui::RichText::new(tr(Msg::NoLogsYet))
                                         .size(12.0)
                                         .color(egui::Color32::from_rgb(148, 163, 184)),
                                 );
                                 return;
                             }
 
-                            for (index, line) in app.status_logs.lines().enumerate() {
-                                let fill = if index % 2 == 1 {
-                                    egui::Color32::from_rgba_unmultiplied(255, 255, 255, 6)
-                                } else {
-                                    egui::Color32::TRANSPARENT
-                                };
-                                egui::Frame::NONE
-                                    .fill(fill)
-                                    .inner_margin(egui::Margin::symmetric(10, 8))
-                                    .show(ui, |ui| {
-                                        ui.label(
-                                            egui::RichText::new(line)
-                                                .monospace()
-                                                .size(12.0)
-                                                .color(egui::Color32::from_rgb(229, 231, 235)),
-                                        );
-                                    });
+                            if app.log_ui.group_by_job {
+                                render_job_groups(ui, app);
+                            } else {
+                                let records = app
+                                    .status_logs
+                                    .records()
+                                    .filter(|record| app.log_ui.shows(record));
+                                render_log_rows(ui, records);
                             }
                         });
                 });
@@ -150,13 +161,13 @@ fn render_log_contents(
             ui.add_space(8.0);
             ui.horizontal(|ui| {
                 ui.label(
-                    egui::RichText::new("アプリを終了するとログはクリアされます。")
+                    egui::RichText::new(tr(Msg::LogFolderNotice))
                         .size(12.0)
                         .color(egui::Color32::from_rgb(148, 163, 184)),
                 );
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     let clear_btn = egui::Button::new(
-                        egui::RichText::new("表示をクリア")
+                        egui::RichText::new(tr(Msg::ClearLog))
                             .size(11.5)
                             .color(egui::Color32::from_rgb(226, 232, 240)),
                     )
@@ -170,7 +181,7 @@ fn render_log_contents(
                     }
 
                     let copy_btn = egui::Button::new(
-                        egui::RichText::new("直近10分をコピー")
+                        egui::RichText::new(tr(Msg::CopyRecentLog))
                             .size(11.5)
                             .color(egui::Color32::from_rgb(226, 232, 240)),
                     )
@@ -182,6 +193,20 @@ fn render_log_contents(
                     if pointing(ui.add(copy_btn)).clicked() {
                         copy_clicked = true;
                     }
+
+                    let export_btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::ExportBugReport))
+                            .size(11.5)
+                            .color(egui::Color32::from_rgb(226, 232, 240)),
+                    )
+                    .fill(egui::Color32::from_rgba_unmultiplied(226, 232, 240, 20))
+                    .stroke(egui::Stroke::new(
+                        1.0,
+                        egui::Color32::from_rgba_unmultiplied(255, 255, 255, 30),
+                    ));
+                    if pointing(ui.add(export_btn)).clicked() {
+                        export_clicked = true;
+                    }
                 });
             });
         });
@@ -190,11 +215,140 @@ fn render_log_contents(
         app.clear_logs();
     }
 
+    if export_clicked {
+        app.export_diagnostic_bundle();
+    }
+
     if copy_clicked {
         let snapshot = app.build_recent_log_snapshot(Duration::from_secs(10 * 60));
         if let Err(err) = copy_to_clipboard(&snapshot) {
-            app.push_status(format!("ログのコピーに失敗しました: {err}"));
+            app.push_error(tr_fmt(Msg::CopyLogFailed, &[&err]));
+        }
+    }
+}
+
+fn render_job_groups(
+    // ダウンロードごとの見出しの描画先
+    ui: &mut egui::Ui,
+    // ログ一覧とダウンロードの状態、表示の絞り込みを保持するアプリ
+    app: &DownloaderApp,
+) {
+    for (job_id, records) in app.status_logs.grouped_records() {
+        let records = records
+            .into_iter()
+            .filter(|record| app.log_ui.shows(record))
+            .collect::<Vec<_>>();
+        if records.is_empty() {
+            continue;
+        }
+        let count = records.len();
+        let (id_salt, title, color, open) = match job_id {
+            Some(id) => {
+                let job = app.status_logs.job(id);
+                let status = job.map(|job| job.status);
+                let url = job.map(|job| job.url.as_str()).unwrap_or_default();
+                let label = status.map(JobStatus::label).unwrap_or(tr(Msg::Unknown));
+                (
+                    id,
+                    tr_fmt(Msg::JobGroupHeader, &[&id, &label, &url, &count]),
+                    status_color(status),
+                    // 終わったダウンロードは、失敗したものだけ開いておく。
+                    !matches!(status, Some(JobStatus::Completed | JobStatus::Cancelled)),
+                )
+            }
+            None => (
+                0,
+                tr_fmt(Msg::OtherLogGroup, &[&count]),
+                egui::Color32::from_rgb(148, 163, 184),
+                true,
+            ),
+        };
+        egui::CollapsingHeader::new(egui::RichText::new(title).size(12.5).color(color))
+            .id_salt(("log-job", id_salt))
+            .default_open(open)
+            .show(ui, |ui| {
+                render_log_rows(ui, records.into_iter());
+            });
+    }
+}
+
+fn render_log_rows<'a>(
+    // 行の描画先
+    ui: &mut egui::Ui,
+    // 表示する行（絞り込み済み）
+    records: impl Iterator<Item = &'a LogRecord>,
+) {
+    for (index, record) in records.enumerate() {
+        let fill = if index % 2 == 1 {
+            egui::Color32::from_rgba_unmultiplied(255, 255, 255, 6)
+        } else {
+            egui::Color32::TRANSPARENT
+        };
+        egui::Frame::NONE
+            .fill(fill)
+            .inner_margin(egui::Margin::symmetric(10, 8))
+            .show(ui, |ui| {
+                ui.label(
+                    egui::RichText::new(record.line())
+                        .monospace()
+                        .size(12.0)
+                        .color(level_color(record.level)),
+                );
+            });
+    }
+}
+
+fn render_log_filters(
+    // フィルタ行の描画先
+    ui: &mut egui::Ui,
+    // 表示するレベルと出どころを保持するログ画面の状態
+    state: &mut LogUiState,
+) {
+    ui.horizontal(|ui| {
+        ui.label(
+            egui::RichText::new(tr(Msg::LogLevel))
+                .size(12.0)
+                .color(egui::Color32::from_rgb(148, 163, 184)),
+        );
+        for level in LogLevel::ALL {
+            let _ = pointing(ui.selectable_value(&mut state.min_level, level, level.label()));
+        }
+        ui.add_space(12.0);
+        ui.label(
+            egui::RichText::new(tr(Msg::LogSourceFilter))
+                .size(12.0)
+                .color(egui::Color32::from_rgb(148, 163, 184)),
+        );
+        for source in LogSource::ALL {
+            let mut shown = !state.hidden_sources.contains(&source);
+            if pointing(ui.checkbox(&mut shown, source.label())).changed() {
+                if shown {
+                    state.hidden_sources.retain(|hidden| *hidden != source);
+                } else {
+                    state.hidden_sources.push(source);
+                }
+            }
         }
+        ui.add_space(12.0);
+        let _ = pointing(ui.checkbox(&mut state.group_by_job, tr(Msg::GroupByDownload)));
+    });
+}
+
+fn status_color(status: Option<JobStatus>) -> egui::Color32 {
+    match status {
+        Some(JobStatus::Running) => egui::Color32::from_rgb(16, 190, 255),
+        Some(JobStatus::Completed) => egui::Color32::from_rgb(134, 239, 172),
+        Some(JobStatus::Failed) => egui::Color32::from_rgb(248, 113, 113),
+        Some(JobStatus::Cancelled) | None => egui::Color32::from_rgb(148, 163, 184),
+    }
+}
+
+fn level_color(level: LogLevel) -> egui::Color32 {
+    match level {
+        LogLevel::Debug => egui::Color32::from_rgb(148, 163, 184),
+        LogLevel::Info => egui::Color32::from_rgb(229, 231, 235),
+        LogLevel::Warn => egui::Color32::from_rgb(251, 191, 36),
+        LogLevel::Error => egui::Color32::from_rgb(248, 113, 113),
     }
 }
 
@@ -0,0 +1,384 @@
+use url::Url;
+
+use crate::i18n::{Msg, tr, tr_fmt};
+
+pub const URL_SCHEME: &str = "vjdownloader";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AppUrlRequest {
+    Download(String),
+    Search(String),
+}
+
+// スクリプトの search コマンドへ結果を返すための検索関数。
+pub type ScriptSearchFn = Box<dyn Fn(&str, usize) -> Result<Vec<String>, String> + Send>;
+
+// vjdownloader://download?url=... / vjdownloader://search?q=... を解析し、アプリへの要求に変換する。
+pub fn parse_app_url(raw: &str) -> Result<AppUrlRequest, String> {
+    let parsed = Url::parse(raw.trim()).map_err(|err| tr_fmt(Msg::UrlParseFailed, &[&err]))?;
+    if !parsed.scheme().eq_ignore_ascii_case(URL_SCHEME) {
+        return Err(tr_fmt(Msg::UnsupportedUrlScheme, &[&parsed.scheme()]));
+    }
+
+    // vjdownloader:download?... のようにホストが無い形式も受け付ける。
+    let action = parsed
+        .host_str()
+        .filter(|host| !host.is_empty())
+        .unwrap_or_else(|| parsed.path().trim_matches('/'))
+        .to_ascii_lowercase();
+    let param = |names: &[&str]| {
+        parsed
+            .query_pairs()
+            .find(|(key, _)| names.contains(&key.as_ref()))
+            .map(|(_, value)| value.trim().to_string())
+    };
+    match action.as_str() {
+        "download" => {
+            let target = param(&["url"])
+                .filter(|value| !value.is_empty())
+                .ok_or_else(|| tr(Msg::DownloadUrlMissing).to_string())?;
+            validate_download_url(&target)?;
+            Ok(AppUrlRequest::Download(target))
+        }
+        "search" => Ok(AppUrlRequest::Search(
+            param(&["q", "query"]).unwrap_or_default(),
+        )),
+        _ => Err(tr_fmt(Msg::UnsupportedAction, &[&action])),
+    }
+}
+
+// 外部から渡されたダウンロード URL が http/https であることを確認する。
+pub fn validate_download_url(target: &str) -> Result<(), String> {
+    let target_url = Url::parse(target).map_err(|err| tr_fmt(Msg::InvalidDownloadUrl, &[&err]))?;
+    if !matches!(target_url.scheme(), "http" | "https") {
+        return Err(tr_fmt(Msg::DownloadUrlNotWeb, &[&target]));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+mod imp {
+    use super::{AppUrlRequest, ScriptSearchFn, parse_app_url, validate_download_url};
+    use crate::i18n::{Msg, tr};
+    use eframe::egui;
+    use std::sync::{Mutex, OnceLock};
+
+    use objc2::rc::Retained;
+    use objc2::runtime::{AnyClass, AnyObject, ClassBuilder, Sel};
+    use objc2::{ClassType, msg_send, sel};
+    use objc2_foundation::{MainThreadMarker, NSObject, NSString};
+
+    // kInternetEventClass / kAEGetURL ('GURL') と keyDirectObject ('----')
+    const K_INTERNET_EVENT_CLASS: u32 = u32::from_be_bytes(*b"GURL");
+    const K_AE_GET_URL: u32 = u32::from_be_bytes(*b"GURL");
+    const KEY_DIRECT_OBJECT: u32 = u32::from_be_bytes(*b"----");
+    const KEY_ERROR_NUMBER: u32 = u32::from_be_bytes(*b"errn");
+    const KEY_ERROR_STRING: u32 = u32::from_be_bytes(*b"errs");
+
+    // スクリプト用コマンド（assets/scripting/VJDownloader.sdef と対応）
+    const SCRIPT_EVENT_CLASS: u32 = u32::from_be_bytes(*b"VJDL");
+    const SCRIPT_DOWNLOAD: u32 = u32::from_be_bytes(*b"dnld");
+    const SCRIPT_STATUS: u32 = u32::from_be_bytes(*b"stat");
+    const SCRIPT_SEARCH: u32 = u32::from_be_bytes(*b"srch");
+    const KEY_SEARCH_LIMIT: u32 = u32::from_be_bytes(*b"limt");
+    const DEFAULT_SCRIPT_SEARCH_LIMIT: usize = 50;
+    // errAEEventFailed
+    const SCRIPT_ERROR_NUMBER: i32 = -10000;
+
+    static PENDING_REQUESTS: Mutex<Vec<Result<AppUrlRequest, String>>> = Mutex::new(Vec::new());
+    static SCRIPT_STATUS_TEXT: Mutex<String> = Mutex::new(String::new());
+    static SCRIPT_SEARCH_FN: Mutex<Option<ScriptSearchFn>> = Mutex::new(None);
+    static HANDLER_TARGET: OnceLock<usize> = OnceLock::new();
+    static REPAINT_CONTEXT: Mutex<Option<egui::Context>> = Mutex::new(None);
+
+    // 起動直後の URL イベントを取りこぼさないよう、イベントループ開始前に呼び出す。
+    pub fn install_event_handlers() {
+        if MainThreadMarker::new().is_none() {
+            return;
+        }
+        HANDLER_TARGET.get_or_init(|| {
+            let target = create_handler_target();
+            register_handler(
+                &target,
+                sel!(handleGetURLEvent:withReplyEvent:),
+                K_INTERNET_EVENT_CLASS,
+                K_AE_GET_URL,
+            );
+            for event_id in [SCRIPT_DOWNLOAD, SCRIPT_STATUS, SCRIPT_SEARCH] {
+                register_handler(
+                    &target,
+                    sel!(handleScriptEvent:withReplyEvent:),
+                    SCRIPT_EVENT_CLASS,
+                    event_id,
+                );
+            }
+            Retained::into_raw(target) as usize
+        });
+    }
+
+    pub fn attach_repaint_context(ctx: &egui::Context) {
+        if let Ok(mut slot) = REPAINT_CONTEXT.lock() {
+            *slot = Some(ctx.clone());
+        }
+    }
+
+    pub fn take_pending_requests() -> Vec<Result<AppUrlRequest, String>> {
+        PENDING_REQUESTS
+            .lock()
+            .map(|mut requests| std::mem::take(&mut *requests))
+            .unwrap_or_default()
+    }
+
+    pub fn publish_script_status(text: &str) {
+        if let Ok(mut slot) = SCRIPT_STATUS_TEXT.lock() {
+            text.clone_into(&mut slot);
+        }
+    }
+
+    pub fn set_script_search(search: ScriptSearchFn) {
+        if let Ok(mut slot) = SCRIPT_SEARCH_FN.lock() {
+            *slot = Some(search);
+        }
+    }
+
+    fn push_request(request: Result<AppUrlRequest, String>) {
+        if let Ok(mut requests) = PENDING_REQUESTS.lock() {
+            requests.push(request);
+        }
+        if let Ok(slot) = REPAINT_CONTEXT.lock()
+            && let Some(ctx) = slot.as_ref()
+        {
+            ctx.request_repaint();
+        }
+    }
+
+    fn register_handler(target: &AnyObject, selector: Sel, event_class: u32, event_id: u32) {
+        let Some(manager_class) = AnyClass::get(c"NSAppleEventManager") else {
+            return;
+        };
+        let manager: Option<Retained<AnyObject>> =
+            unsafe { msg_send![manager_class, sharedAppleEventManager] };
+        let Some(manager) = manager else {
+            return;
+        };
+        unsafe {
+            let _: () = msg_send![
+                &*manager,
+                setEventHandler: target,
+                andSelector: selector,
+                forEventClass: event_class,
+                andEventID: event_id
+            ];
+        }
+    }
+
+    fn create_handler_target() -> Retained<AnyObject> {
+        let cls = handler_target_class();
+        unsafe { msg_send![cls, new] }
+    }
+
+    fn handler_target_class() -> &'static AnyClass {
+        static CLASS: OnceLock<&AnyClass> = OnceLock::new();
+        CLASS.get_or_init(|| {
+            let superclass = NSObject::class();
+            let mut builder =
+                ClassBuilder::new(c"VJDownloaderAppleEventTarget", superclass).expect("class");
+            unsafe {
+                builder.add_method(
+                    sel!(handleGetURLEvent:withReplyEvent:),
+                    handle_get_url_event as extern "C" fn(_, _, _, _),
+                );
+                builder.add_method(
+                    sel!(handleScriptEvent:withReplyEvent:),
+                    handle_script_event as extern "C" fn(_, _, _, _),
+                );
+            }
+            builder.register()
+        })
+    }
+
+    extern "C" fn handle_get_url_event(
+        _this: &AnyObject,
+        _sel: Sel,
+        event: *mut AnyObject,
+        _reply: *mut AnyObject,
+    ) {
+        if event.is_null() {
+            return;
+        }
+        let event = unsafe { &*event };
+        let Some(value) = param_string(event, KEY_DIRECT_OBJECT) else {
+            return;
+        };
+        push_request(parse_app_url(&value));
+    }
+
+    extern "C" fn handle_script_event(
+        _this: &AnyObject,
+        _sel: Sel,
+        event: *mut AnyObject,
+        reply: *mut AnyObject,
+    ) {
+        if event.is_null() {
+            return;
+        }
+        let event = unsafe { &*event };
+        let reply = unsafe { reply.as_ref() };
+        let event_id: u32 = unsafe { msg_send![event, eventID] };
+
+        let result = match event_id {
+            SCRIPT_DOWNLOAD => run_download_command(event),
+            SCRIPT_STATUS => Ok(string_descriptor(
+                &SCRIPT_STATUS_TEXT
+                    .lock()
+                    .map(|text| text.clone())
+                    .unwrap_or_default(),
+            )),
+            SCRIPT_SEARCH => run_search_command(event),
+            _ => Err(tr(Msg::UnsupportedCommand).to_string()),
+        };
+
+        let Some(reply) = reply else {
+            return;
+        };
+        match result {
+            Ok(Some(descriptor)) => set_reply_param(reply, &descriptor, KEY_DIRECT_OBJECT),
+            Ok(None) => {}
+            Err(message) => {
+                if let Some(number) = int_descriptor(SCRIPT_ERROR_NUMBER) {
+                    set_reply_param(reply, &number, KEY_ERROR_NUMBER);
+                }
+                if let Some(text) = string_descriptor(&message) {
+                    set_reply_param(reply, &text, KEY_ERROR_STRING);
+                }
+            }
+        }
+    }
+
+    fn run_download_command(event: &AnyObject) -> Result<Option<Retained<AnyObject>>, String> {
+        let url = param_string(event, KEY_DIRECT_OBJECT)
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| tr(Msg::EnterDownloadUrl).to_string())?;
+        validate_download_url(&url)?;
+        push_request(Ok(AppUrlRequest::Download(url.clone())));
+        Ok(string_descriptor(&url))
+    }
+
+    fn run_search_command(event: &AnyObject) -> Result<Option<Retained<AnyObject>>, String> {
+        let query = param_string(event, KEY_DIRECT_OBJECT).unwrap_or_default();
+        let limit = param_descriptor(event, KEY_SEARCH_LIMIT)
+            .map(|descriptor| unsafe { msg_send![&*descriptor, int32Value] })
+            .and_then(|value: i32| usize::try_from(value).ok())
+            .filter(|value| *value > 0)
+            .unwrap_or(DEFAULT_SCRIPT_SEARCH_LIMIT);
+
+        let paths = {
+            let slot = SCRIPT_SEARCH_FN
+                .lock()
+                .map_err(|_| tr(Msg::SearchUnavailable).to_string())?;
+            let search = slot
+                .as_ref()
+                .ok_or_else(|| tr(Msg::SearchNotReady).to_string())?;
+            search(&query, limit)?
+        };
+
+        let Some(descriptor_class) = AnyClass::get(c"NSAppleEventDescriptor") else {
+            return Ok(None);
+        };
+        let list: Option<Retained<AnyObject>> =
+            unsafe { msg_send![descriptor_class, listDescriptor] };
+        let Some(list) = list else {
+            return Ok(None);
+        };
+        for path in paths {
+            if let Some(item) = string_descriptor(&path) {
+                // index 0 は末尾への追加。
+                unsafe {
+                    let _: () = msg_send![&*list, insertDescriptor: &*item, atIndex: 0_isize];
+                }
+            }
+        }
+        Ok(Some(list))
+    }
+
+    fn param_descriptor(event: &AnyObject, keyword: u32) -> Option<Retained<AnyObject>> {
+        unsafe { msg_send![event, paramDescriptorForKeyword: keyword] }
+    }
+
+    fn param_string(event: &AnyObject, keyword: u32) -> Option<String> {
+        let descriptor = param_descriptor(event, keyword)?;
+        let value: Option<Retained<NSString>> = unsafe { msg_send![&*descriptor, stringValue] };
+        value.map(|value| value.to_string())
+    }
+
+    fn string_descriptor(value: &str) -> Option<Retained<AnyObject>> {
+        let descriptor_class = AnyClass::get(c"NSAppleEventDescriptor")?;
+        let value = NSString::from_str(value);
+        unsafe { msg_send![descriptor_class, descriptorWithString: &*value] }
+    }
+
+    fn int_descriptor(value: i32) -> Option<Retained<AnyObject>> {
+        let descriptor_class = AnyClass::get(c"NSAppleEventDescriptor")?;
+        unsafe { msg_send![descriptor_class, descriptorWithInt32: value] }
+    }
+
+    fn set_reply_param(reply: &AnyObject, descriptor: &AnyObject, keyword: u32) {
+        unsafe {
+            let _: () = msg_send![reply, setParamDescriptor: descriptor, forKeyword: keyword];
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub use imp::{
+    attach_repaint_context, install_event_handlers, publish_script_status, set_script_search,
+    take_pending_requests,
+};
+
+#[cfg(not(target_os = "macos"))]
+pub fn install_event_handlers() {}
+
+#[cfg(not(target_os = "macos"))]
+pub fn attach_repaint_context(_ctx: &eframe::egui::Context) {}
+
+#[cfg(not(target_os = "macos"))]
+pub fn take_pending_requests() -> Vec<Result<AppUrlRequest, String>> {
+    Vec::new()
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn publish_script_status(_text: &str) {}
+
+#[cfg(not(target_os = "macos"))]
+pub fn set_script_search(_search: ScriptSearchFn) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_download_request() {
+        let request = parse_app_url(
+            "vjdownloader://download?url=https%3A%2F%2Fwww.youtube.com%2Fwatch%3Fv%3Dabc",
+        )
+        .unwrap();
+        assert_eq!(
+            request,
+            AppUrlRequest::Download("https://www.youtube.com/watch?v=abc".to_string())
+        );
+        assert!(parse_app_url("VJDownloader:download?url=https://example.com/a").is_ok());
+        assert_eq!(
+            parse_app_url("vjdownloader://search?q=neon%20loop").unwrap(),
+            AppUrlRequest::Search("neon loop".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_requests() {
+        assert!(parse_app_url("https://example.com").is_err());
+        assert!(parse_app_url("vjdownloader://download").is_err());
+        assert!(parse_app_url("vjdownloader://download?url=file:///etc/passwd").is_err());
+        assert!(parse_app_url("vjdownloader://delete?url=https://example.com").is_err());
+    }
+}
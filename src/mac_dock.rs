@@ -0,0 +1,88 @@
+#[cfg(target_os = "macos")]
+mod imp {
+    use std::cell::RefCell;
+
+    use objc2::MainThreadOnly;
+    use objc2::rc::Retained;
+    use objc2_app_kit::{
+        NSApplication, NSImageView, NSProgressIndicator, NSProgressIndicatorStyle, NSView,
+    };
+    use objc2_foundation::{MainThreadMarker, NSPoint, NSRect, NSSize, NSString};
+
+    // Dock アイコン下部に重ねる進捗バーの高さ（タイルサイズに対する割合）
+    const BAR_HEIGHT_RATIO: f64 = 0.12;
+    const BAR_INSET_RATIO: f64 = 0.08;
+
+    struct DockOverlay {
+        content: Retained<NSView>,
+        bar: Retained<NSProgressIndicator>,
+    }
+
+    thread_local! {
+        static OVERLAY: RefCell<Option<DockOverlay>> = const { RefCell::new(None) };
+    }
+
+    // Dock アイコンへ進捗バーと待ち件数のバッジを表示する。progress が None ならアイコンを元に戻す。
+    pub fn update_dock_tile(progress: Option<f32>, queued: usize) {
+        let Some(mtm) = MainThreadMarker::new() else {
+            return;
+        };
+        let app = NSApplication::sharedApplication(mtm);
+        let tile = app.dockTile();
+
+        let badge = (queued > 0).then(|| NSString::from_str(&queued.to_string()));
+        tile.setBadgeLabel(badge.as_deref());
+
+        match progress {
+            Some(progress) => OVERLAY.with(|slot| {
+                let mut slot = slot.borrow_mut();
+                if slot.is_none() {
+                    *slot = create_overlay(&app, tile.size(), mtm);
+                }
+                if let Some(overlay) = slot.as_ref() {
+                    // 準備中（負の値）は空のバーで表示する。
+                    overlay
+                        .bar
+                        .setDoubleValue(f64::from(progress.clamp(0.0, 1.0)) * 100.0);
+                    tile.setContentView(Some(&overlay.content));
+                }
+            }),
+            None => tile.setContentView(None),
+        }
+        tile.display();
+    }
+
+    fn create_overlay(
+        app: &NSApplication,
+        size: NSSize,
+        mtm: MainThreadMarker,
+    ) -> Option<DockOverlay> {
+        let icon = app.applicationIconImage()?;
+        let frame = NSRect::new(NSPoint::new(0.0, 0.0), size);
+        let content = NSView::initWithFrame(NSView::alloc(mtm), frame);
+
+        let image_view = NSImageView::imageViewWithImage(&icon, mtm);
+        image_view.setFrame(frame);
+        content.addSubview(&image_view);
+
+        let inset = size.width * BAR_INSET_RATIO;
+        let bar_frame = NSRect::new(
+            NSPoint::new(inset, inset),
+            NSSize::new(size.width - inset * 2.0, size.height * BAR_HEIGHT_RATIO),
+        );
+        let bar = NSProgressIndicator::initWithFrame(NSProgressIndicator::alloc(mtm), bar_frame);
+        bar.setStyle(NSProgressIndicatorStyle::Bar);
+        bar.setIndeterminate(false);
+        bar.setMinValue(0.0);
+        bar.setMaxValue(100.0);
+        content.addSubview(&bar);
+
+        Some(DockOverlay { content, bar })
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub use imp::update_dock_tile;
+
+#[cfg(not(target_os = "macos"))]
+pub fn update_dock_tile(_progress: Option<f32>, _queued: usize) {}
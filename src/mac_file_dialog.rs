@@ -2,7 +2,7 @@
 mod imp {
     use std::path::{Path, PathBuf};
 
-    use objc2_app_kit::{NSModalResponseOK, NSOpenPanel};
+    use objc2_app_kit::{NSModalResponseOK, NSOpenPanel, NSSavePanel};
     use objc2_foundation::{MainThreadMarker, NSString, NSURL};
 
     pub fn choose_directory(current: Option<&Path>) -> Option<PathBuf> {
@@ -34,12 +34,68 @@ mod imp {
         let path_ns = url.path()?;
         Some(PathBuf::from(path_ns.to_string()))
     }
+
+    pub fn choose_file(current: Option<&Path>) -> Option<PathBuf> {
+        let mtm = MainThreadMarker::new()?;
+        let panel = NSOpenPanel::openPanel(mtm);
+        panel.setCanChooseDirectories(false);
+        panel.setCanChooseFiles(true);
+        panel.setAllowsMultipleSelection(false);
+
+        if let Some(path_str) = current.and_then(|path| path.to_str()) {
+            let ns_path = NSString::from_str(path_str);
+            let url = NSURL::fileURLWithPath_isDirectory(&ns_path, true);
+            panel.setDirectoryURL(Some(&url));
+        }
+
+        if panel.runModal() != NSModalResponseOK {
+            return None;
+        }
+        let urls = panel.URLs();
+        if urls.count() == 0 {
+            return None;
+        }
+        let path_ns = urls.objectAtIndex(0).path()?;
+        Some(PathBuf::from(path_ns.to_string()))
+    }
+
+    pub fn choose_save_path(default_name: &str, current: Option<&Path>) -> Option<PathBuf> {
+        let mtm = MainThreadMarker::new()?;
+        let panel = NSSavePanel::savePanel(mtm);
+        panel.setCanCreateDirectories(true);
+        panel.setNameFieldStringValue(&NSString::from_str(default_name));
+
+        if let Some(path_str) = current.and_then(|path| path.to_str()) {
+            let ns_path = NSString::from_str(path_str);
+            let url = NSURL::fileURLWithPath_isDirectory(&ns_path, true);
+            panel.setDirectoryURL(Some(&url));
+        }
+
+        if panel.runModal() != NSModalResponseOK {
+            return None;
+        }
+        let path_ns = panel.URL()?.path()?;
+        Some(PathBuf::from(path_ns.to_string()))
+    }
 }
 
 #[cfg(target_os = "macos")]
-pub use imp::choose_directory;
+pub use imp::{choose_directory, choose_file, choose_save_path};
 
 #[cfg(not(target_os = "macos"))]
 pub fn choose_directory(_current: Option<&std::path::Path>) -> Option<std::path::PathBuf> {
     None
 }
+
+#[cfg(not(target_os = "macos"))]
+pub fn choose_file(_current: Option<&std::path::Path>) -> Option<std::path::PathBuf> {
+    None
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn choose_save_path(
+    _default_name: &str,
+    _current: Option<&std::path::Path>,
+) -> Option<std::path::PathBuf> {
+    None
+}
@@ -0,0 +1,368 @@
+// Finder タグは拡張属性に「"名前\n色番号"」文字列の配列を binary plist で保存している。
+pub const FINDER_TAGS_XATTR: &str = "com.apple.metadata:_kMDItemUserTags";
+
+const BPLIST_MAGIC: &[u8] = b"bplist00";
+const BPLIST_TRAILER_LEN: usize = 32;
+
+// 評価は "★" を並べたタグとして Finder に書き出す。
+pub const MAX_RATING: u8 = 5;
+const RATING_CHAR: char = '★';
+
+// タグ配列を binary plist（文字列の配列）へ変換する。
+pub fn encode_tag_plist(entries: &[String]) -> Vec<u8> {
+    let object_count = entries.len() + 1;
+    let ref_size = if object_count < 0x100 { 1 } else { 2 };
+
+    let mut out = BPLIST_MAGIC.to_vec();
+    let mut offsets = Vec::with_capacity(object_count);
+
+    offsets.push(out.len());
+    push_marker(&mut out, 0xA0, entries.len());
+    for index in 1..object_count {
+        push_sized_int(&mut out, index as u64, ref_size);
+    }
+
+    for entry in entries {
+        offsets.push(out.len());
+        if entry.is_ascii() {
+            push_marker(&mut out, 0x50, entry.len());
+            out.extend_from_slice(entry.as_bytes());
+        } else {
+            let units = entry.encode_utf16().collect::<Vec<_>>();
+            push_marker(&mut out, 0x60, units.len());
+            for unit in units {
+                out.extend_from_slice(&unit.to_be_bytes());
+            }
+        }
+    }
+
+    let table_offset = out.len();
+    let offset_size = match table_offset {
+        0..=0xFF => 1,
+        0x100..=0xFFFF => 2,
+        _ => 4,
+    };
+    for offset in offsets {
+        push_sized_int(&mut out, offset as u64, offset_size);
+    }
+
+    out.extend_from_slice(&[0; 6]);
+    out.push(offset_size as u8);
+    out.push(ref_size as u8);
+    out.extend_from_slice(&(object_count as u64).to_be_bytes());
+    out.extend_from_slice(&0_u64.to_be_bytes());
+    out.extend_from_slice(&(table_offset as u64).to_be_bytes());
+    out
+}
+
+// binary plist から文字列配列を読み出す。形式が異なる場合は None。
+pub fn decode_tag_plist(data: &[u8]) -> Option<Vec<String>> {
+    if data.len() < BPLIST_MAGIC.len() + BPLIST_TRAILER_LEN || !data.starts_with(BPLIST_MAGIC) {
+        return None;
+    }
+    let trailer = &data[data.len() - BPLIST_TRAILER_LEN..];
+    let offset_size = trailer[6] as usize;
+    let ref_size = trailer[7] as usize;
+    let object_count = read_sized_int(trailer, 8, 8)? as usize;
+    let top_object = read_sized_int(trailer, 16, 8)? as usize;
+    let table_offset = read_sized_int(trailer, 24, 8)? as usize;
+
+    let object_offset = |index: usize| -> Option<usize> {
+        if index >= object_count {
+            return None;
+        }
+        read_sized_int(data, table_offset + index * offset_size, offset_size).map(|v| v as usize)
+    };
+
+    let top = object_offset(top_object)?;
+    let (kind, count, mut cursor) = read_marker(data, top)?;
+    if kind != 0xA0 {
+        return None;
+    }
+
+    let mut entries = Vec::with_capacity(count);
+    for _ in 0..count {
+        let index = read_sized_int(data, cursor, ref_size)? as usize;
+        cursor += ref_size;
+        entries.push(read_string(data, object_offset(index)?)?);
+    }
+    Some(entries)
+}
+
+fn read_string(data: &[u8], offset: usize) -> Option<String> {
+    let (kind, len, start) = read_marker(data, offset)?;
+    match kind {
+        0x50 => {
+            let bytes = data.get(start..start.checked_add(len)?)?;
+            String::from_utf8(bytes.to_vec()).ok()
+        }
+        0x60 => {
+            let bytes = data.get(start..start.checked_add(len.checked_mul(2)?)?)?;
+            let units = bytes
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                .collect::<Vec<_>>();
+            String::from_utf16(&units).ok()
+        }
+        _ => None,
+    }
+}
+
+// オブジェクトの種類（上位4bit）と長さ、本体の開始位置を返す。長さ 15 以上は後続の整数で表す。
+fn read_marker(data: &[u8], offset: usize) -> Option<(u8, usize, usize)> {
+    let marker = *data.get(offset)?;
+    let kind = marker & 0xF0;
+    let len = (marker & 0x0F) as usize;
+    if len != 0x0F {
+        return Some((kind, len, offset + 1));
+    }
+    let int_marker = *data.get(offset + 1)?;
+    if int_marker & 0xF0 != 0x10 {
+        return None;
+    }
+    let size = 1_usize << (int_marker & 0x0F);
+    let len = read_sized_int(data, offset + 2, size)? as usize;
+    Some((kind, len, offset + 2 + size))
+}
+
+fn push_marker(out: &mut Vec<u8>, kind: u8, len: usize) {
+    if len < 0x0F {
+        out.push(kind | len as u8);
+        return;
+    }
+    out.push(kind | 0x0F);
+    let (exponent, size) = match len {
+        0..=0xFF => (0, 1),
+        0x100..=0xFFFF => (1, 2),
+        _ => (2, 4),
+    };
+    out.push(0x10 | exponent);
+    push_sized_int(out, len as u64, size);
+}
+
+fn push_sized_int(out: &mut Vec<u8>, value: u64, size: usize) {
+    out.extend_from_slice(&value.to_be_bytes()[8 - size..]);
+}
+
+fn read_sized_int(data: &[u8], offset: usize, size: usize) -> Option<u64> {
+    if size == 0 || size > 8 {
+        return None;
+    }
+    let bytes = data.get(offset..offset.checked_add(size)?)?;
+    Some(
+        bytes
+            .iter()
+            .fold(0, |acc, byte| (acc << 8) | u64::from(*byte)),
+    )
+}
+
+// "名前\n色番号" から名前部分を取り出す。
+pub fn tag_name(entry: &str) -> &str {
+    entry.split('\n').next().unwrap_or(entry)
+}
+
+// 新しいタグ名の一覧へ、既存エントリの色番号を引き継いで保存用の配列を作る。
+pub fn merge_tag_entries(existing: &[String], names: &[String]) -> Vec<String> {
+    names
+        .iter()
+        .map(|name| {
+            existing
+                .iter()
+                .find(|entry| tag_name(entry) == name)
+                .cloned()
+                .unwrap_or_else(|| name.clone())
+        })
+        .collect()
+}
+
+// 評価タグ（"★"〜"★★★★★"）を返す。0 は評価なし。
+pub fn rating_tag(rating: u8) -> Option<String> {
+    (rating > 0).then(|| {
+        RATING_CHAR
+            .to_string()
+            .repeat(rating.min(MAX_RATING) as usize)
+    })
+}
+
+// タグ一覧を通常タグと評価に分ける。
+pub fn split_rating(tags: &[String]) -> (Vec<String>, u8) {
+    let mut rating = 0;
+    let mut others = Vec::new();
+    for tag in tags {
+        let count = tag.chars().count();
+        if (1..=MAX_RATING as usize).contains(&count) && tag.chars().all(|ch| ch == RATING_CHAR) {
+            rating = count as u8;
+        } else {
+            others.push(tag.clone());
+        }
+    }
+    (others, rating)
+}
+
+#[cfg(target_os = "macos")]
+mod imp {
+    use std::ffi::{CString, c_char, c_int, c_void};
+    use std::io;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    use super::{
+        FINDER_TAGS_XATTR, decode_tag_plist, encode_tag_plist, merge_tag_entries, tag_name,
+    };
+    use crate::i18n::{Msg, tr};
+
+    // 属性が存在しない場合の errno (ENOATTR)
+    const ENOATTR: i32 = 93;
+
+    unsafe extern "C" {
+        fn getxattr(
+            path: *const c_char,
+            name: *const c_char,
+            value: *mut c_void,
+            size: usize,
+            position: u32,
+            options: c_int,
+        ) -> isize;
+        fn setxattr(
+            path: *const c_char,
+            name: *const c_char,
+            value: *const c_void,
+            size: usize,
+            position: u32,
+            options: c_int,
+        ) -> c_int;
+        fn removexattr(path: *const c_char, name: *const c_char, options: c_int) -> c_int;
+    }
+
+    // Finder タグを "名前\n色番号" 形式のまま読み取る。タグが無ければ空。
+    fn read_tag_entries(path: &Path) -> Result<Vec<String>, String> {
+        let c_path = c_path(path)?;
+        let c_name = CString::new(FINDER_TAGS_XATTR).map_err(|err| err.to_string())?;
+        let size = unsafe {
+            getxattr(
+                c_path.as_ptr(),
+                c_name.as_ptr(),
+                std::ptr::null_mut(),
+                0,
+                0,
+                0,
+            )
+        };
+        if size < 0 {
+            return missing_or_error(Vec::new());
+        }
+        let mut buf = vec![0_u8; size as usize];
+        let read = unsafe {
+            getxattr(
+                c_path.as_ptr(),
+                c_name.as_ptr(),
+                buf.as_mut_ptr().cast(),
+                buf.len(),
+                0,
+                0,
+            )
+        };
+        if read < 0 {
+            return missing_or_error(Vec::new());
+        }
+        buf.truncate(read as usize);
+        decode_tag_plist(&buf).ok_or_else(|| tr(Msg::FinderTagsParseFailed).to_string())
+    }
+
+    pub fn read_finder_tags(path: &Path) -> Result<Vec<String>, String> {
+        Ok(read_tag_entries(path)?
+            .iter()
+            .map(|entry| tag_name(entry).to_string())
+            .collect())
+    }
+
+    // タグ名の一覧で Finder タグを置き換える。既存タグの色は引き継ぐ。
+    pub fn write_finder_tags(path: &Path, names: &[String]) -> Result<(), String> {
+        let c_path = c_path(path)?;
+        let c_name = CString::new(FINDER_TAGS_XATTR).map_err(|err| err.to_string())?;
+        if names.is_empty() {
+            let status = unsafe { removexattr(c_path.as_ptr(), c_name.as_ptr(), 0) };
+            if status != 0 {
+                return missing_or_error(());
+            }
+            return Ok(());
+        }
+
+        let existing = read_tag_entries(path).unwrap_or_default();
+        let data = encode_tag_plist(&merge_tag_entries(&existing, names));
+        let status = unsafe {
+            setxattr(
+                c_path.as_ptr(),
+                c_name.as_ptr(),
+                data.as_ptr().cast(),
+                data.len(),
+                0,
+                0,
+            )
+        };
+        if status != 0 {
+            return Err(io::Error::last_os_error().to_string());
+        }
+        Ok(())
+    }
+
+    fn c_path(path: &Path) -> Result<CString, String> {
+        CString::new(path.as_os_str().as_bytes()).map_err(|err| err.to_string())
+    }
+
+    fn missing_or_error<T>(missing: T) -> Result<T, String> {
+        let err = io::Error::last_os_error();
+        if err.raw_os_error() == Some(ENOATTR) {
+            Ok(missing)
+        } else {
+            Err(err.to_string())
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub use imp::{read_finder_tags, write_finder_tags};
+
+#[cfg(not(target_os = "macos"))]
+pub fn read_finder_tags(_path: &std::path::Path) -> Result<Vec<String>, String> {
+    Ok(Vec::new())
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn write_finder_tags(_path: &std::path::Path, _names: &[String]) -> Result<(), String> {
+    use crate::i18n::{Msg, tr};
+
+    Err(tr(Msg::FinderTagsMacOnly).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_tag_plist() {
+        let entries = vec![
+            "Red\n6".to_string(),
+            "ライブ用".to_string(),
+            "a-very-long-ascii-tag-name".to_string(),
+        ];
+        assert_eq!(decode_tag_plist(&encode_tag_plist(&entries)), Some(entries));
+        assert_eq!(decode_tag_plist(&encode_tag_plist(&[])), Some(Vec::new()));
+        assert_eq!(decode_tag_plist(b"not a plist"), None);
+    }
+
+    #[test]
+    fn keeps_colors_and_splits_rating() {
+        let existing = vec!["Red\n6".to_string(), "old".to_string()];
+        let names = vec!["new".to_string(), "Red".to_string()];
+        assert_eq!(
+            merge_tag_entries(&existing, &names),
+            vec!["new".to_string(), "Red\n6".to_string()]
+        );
+
+        let tags = vec!["loop".to_string(), rating_tag(3).unwrap()];
+        assert_eq!(split_rating(&tags), (vec!["loop".to_string()], 3));
+        assert_eq!(rating_tag(0), None);
+        assert_eq!(rating_tag(9).as_deref(), Some("★★★★★"));
+    }
+}
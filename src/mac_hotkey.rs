@@ -0,0 +1,295 @@
+use crate::i18n::{Msg, tr, tr_fmt};
+
+// Carbon の修飾キーマスク (cmdKey / shiftKey / optionKey / controlKey)
+const MODIFIER_COMMAND: u32 = 1 << 8;
+const MODIFIER_SHIFT: u32 = 1 << 9;
+const MODIFIER_OPTION: u32 = 1 << 11;
+const MODIFIER_CONTROL: u32 = 1 << 12;
+
+// キー名と仮想キーコード (kVK_*) の対応表
+const KEY_CODES: &[(&str, u32)] = &[
+    ("a", 0),
+    ("s", 1),
+    ("d", 2),
+    ("f", 3),
+    ("h", 4),
+    ("g", 5),
+    ("z", 6),
+    ("x", 7),
+    ("c", 8),
+    ("v", 9),
+    ("b", 11),
+    ("q", 12),
+    ("w", 13),
+    ("e", 14),
+    ("r", 15),
+    ("y", 16),
+    ("t", 17),
+    ("1", 18),
+    ("2", 19),
+    ("3", 20),
+    ("4", 21),
+    ("6", 22),
+    ("5", 23),
+    ("9", 25),
+    ("7", 26),
+    ("8", 28),
+    ("0", 29),
+    ("o", 31),
+    ("u", 32),
+    ("i", 34),
+    ("p", 35),
+    ("l", 37),
+    ("j", 38),
+    ("k", 40),
+    ("n", 45),
+    ("m", 46),
+    ("space", 49),
+    ("f5", 96),
+    ("f6", 97),
+    ("f7", 98),
+    ("f3", 99),
+    ("f8", 100),
+    ("f9", 101),
+    ("f11", 103),
+    ("f10", 109),
+    ("f12", 111),
+    ("f4", 118),
+    ("f2", 120),
+    ("f1", 122),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Hotkey {
+    pub key_code: u32,
+    pub modifiers: u32,
+}
+
+// "cmd+shift+space" 形式の文字列を解析する。空欄は無効（None）として扱う。
+pub fn parse_hotkey(raw: &str) -> Result<Option<Hotkey>, String> {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return Ok(None);
+    }
+
+    let mut modifiers = 0;
+    let mut key_code = None;
+    for part in trimmed.split('+') {
+        let name = part.trim().to_ascii_lowercase();
+        let modifier = match name.as_str() {
+            "cmd" | "command" | "⌘" => Some(MODIFIER_COMMAND),
+            "shift" | "⇧" => Some(MODIFIER_SHIFT),
+            "opt" | "option" | "alt" | "⌥" => Some(MODIFIER_OPTION),
+            "ctrl" | "control" | "⌃" => Some(MODIFIER_CONTROL),
+            _ => None,
+        };
+        if let Some(modifier) = modifier {
+            modifiers |= modifier;
+            continue;
+        }
+        let Some((_, code)) = KEY_CODES.iter().find(|(key, _)| *key == name) else {
+            return Err(tr_fmt(Msg::HotkeyUnknownKey, &[&part.trim()]));
+        };
+        if key_code.replace(*code).is_some() {
+            return Err(tr(Msg::HotkeySingleKey).to_string());
+        }
+    }
+
+    let Some(key_code) = key_code else {
+        return Err(tr(Msg::HotkeyKeyRequired).to_string());
+    };
+    if modifiers == 0 {
+        return Err(tr(Msg::HotkeyModifierRequired).to_string());
+    }
+    Ok(Some(Hotkey {
+        key_code,
+        modifiers,
+    }))
+}
+
+#[cfg(target_os = "macos")]
+mod imp {
+    use super::{Hotkey, parse_hotkey};
+    use crate::i18n::{Msg, tr, tr_fmt};
+    use eframe::egui;
+    use std::ffi::c_void;
+    use std::ptr;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::{Mutex, OnceLock};
+
+    use objc2_app_kit::NSApplication;
+    use objc2_foundation::MainThreadMarker;
+
+    const K_EVENT_CLASS_KEYBOARD: u32 = u32::from_be_bytes(*b"keyb");
+    const K_EVENT_HOT_KEY_PRESSED: u32 = 5;
+    const EVENT_HOT_KEY_EXISTS_ERR: i32 = -9878;
+    const HOTKEY_SIGNATURE: u32 = u32::from_be_bytes(*b"VJDL");
+
+    #[repr(C)]
+    struct EventTypeSpec {
+        event_class: u32,
+        event_kind: u32,
+    }
+
+    #[repr(C)]
+    struct EventHotKeyId {
+        signature: u32,
+        id: u32,
+    }
+
+    type EventHandlerProc = extern "C" fn(*mut c_void, *mut c_void, *mut c_void) -> i32;
+
+    #[link(name = "Carbon", kind = "framework")]
+    unsafe extern "C" {
+        fn GetApplicationEventTarget() -> *mut c_void;
+        fn InstallEventHandler(
+            target: *mut c_void,
+            handler: EventHandlerProc,
+            num_types: u32,
+            list: *const EventTypeSpec,
+            user_data: *mut c_void,
+            out_ref: *mut *mut c_void,
+        ) -> i32;
+        fn RegisterEventHotKey(
+            key_code: u32,
+            modifiers: u32,
+            hot_key_id: EventHotKeyId,
+            target: *mut c_void,
+            options: u32,
+            out_ref: *mut *mut c_void,
+        ) -> i32;
+        fn UnregisterEventHotKey(hot_key: *mut c_void) -> i32;
+    }
+
+    static SUMMON_REQUEST: AtomicBool = AtomicBool::new(false);
+    static HANDLER_INSTALLED: OnceLock<Result<(), String>> = OnceLock::new();
+    static REGISTERED_HOTKEY: AtomicUsize = AtomicUsize::new(0);
+    static REPAINT_CONTEXT: Mutex<Option<egui::Context>> = Mutex::new(None);
+
+    pub fn register_summon_hotkey(raw: &str, ctx: &egui::Context) -> Result<(), String> {
+        let hotkey = parse_hotkey(raw)?;
+        if MainThreadMarker::new().is_none() {
+            return Err(tr(Msg::HotkeyMainThread).to_string());
+        }
+        if let Ok(mut slot) = REPAINT_CONTEXT.lock() {
+            *slot = Some(ctx.clone());
+        }
+
+        unregister_current();
+        let Some(hotkey) = hotkey else {
+            return Ok(());
+        };
+        HANDLER_INSTALLED.get_or_init(install_handler).clone()?;
+        register(hotkey)
+    }
+
+    pub fn take_summon_request() -> bool {
+        SUMMON_REQUEST.swap(false, Ordering::Relaxed)
+    }
+
+    fn install_handler() -> Result<(), String> {
+        let spec = EventTypeSpec {
+            event_class: K_EVENT_CLASS_KEYBOARD,
+            event_kind: K_EVENT_HOT_KEY_PRESSED,
+        };
+        let status = unsafe {
+            InstallEventHandler(
+                GetApplicationEventTarget(),
+                handle_hotkey,
+                1,
+                &spec,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+        };
+        if status != 0 {
+            return Err(tr_fmt(Msg::HotkeyHandlerFailed, &[&status]));
+        }
+        Ok(())
+    }
+
+    fn register(hotkey: Hotkey) -> Result<(), String> {
+        let mut hotkey_ref = ptr::null_mut();
+        let status = unsafe {
+            RegisterEventHotKey(
+                hotkey.key_code,
+                hotkey.modifiers,
+                EventHotKeyId {
+                    signature: HOTKEY_SIGNATURE,
+                    id: 1,
+                },
+                GetApplicationEventTarget(),
+                0,
+                &mut hotkey_ref,
+            )
+        };
+        match status {
+            0 => {
+                REGISTERED_HOTKEY.store(hotkey_ref as usize, Ordering::Relaxed);
+                Ok(())
+            }
+            EVENT_HOT_KEY_EXISTS_ERR => Err(tr(Msg::HotkeyInUse).to_string()),
+            _ => Err(tr_fmt(Msg::HotkeyRegisterStatusFailed, &[&status])),
+        }
+    }
+
+    fn unregister_current() {
+        let current = REGISTERED_HOTKEY.swap(0, Ordering::Relaxed);
+        if current != 0 {
+            unsafe { UnregisterEventHotKey(current as *mut c_void) };
+        }
+    }
+
+    extern "C" fn handle_hotkey(
+        _next_handler: *mut c_void,
+        _event: *mut c_void,
+        _user_data: *mut c_void,
+    ) -> i32 {
+        SUMMON_REQUEST.store(true, Ordering::Relaxed);
+        if let Some(mtm) = MainThreadMarker::new() {
+            // 他アプリが前面にあっても確実に切り替えるため、非推奨だが強制アクティブ化を使う。
+            #[allow(deprecated)]
+            NSApplication::sharedApplication(mtm).activateIgnoringOtherApps(true);
+        }
+        if let Ok(slot) = REPAINT_CONTEXT.lock()
+            && let Some(ctx) = slot.as_ref()
+        {
+            ctx.request_repaint();
+        }
+        0
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub use imp::{register_summon_hotkey, take_summon_request};
+
+#[cfg(not(target_os = "macos"))]
+pub fn register_summon_hotkey(raw: &str, _ctx: &eframe::egui::Context) -> Result<(), String> {
+    parse_hotkey(raw).map(|_| ())
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn take_summon_request() -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_modifiers_and_key() {
+        let hotkey = parse_hotkey(" Cmd + Shift + Space ").unwrap().unwrap();
+        assert_eq!(hotkey.key_code, 49);
+        assert_eq!(hotkey.modifiers, MODIFIER_COMMAND | MODIFIER_SHIFT);
+        assert_eq!(parse_hotkey("").unwrap(), None);
+    }
+
+    #[test]
+    fn rejects_invalid_hotkeys() {
+        assert!(parse_hotkey("space").is_err());
+        assert!(parse_hotkey("cmd+shift").is_err());
+        assert!(parse_hotkey("cmd+a+b").is_err());
+        assert!(parse_hotkey("cmd+enter").is_err());
+    }
+}
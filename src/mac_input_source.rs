@@ -5,9 +5,54 @@ pub enum InputMode {
     Other(String),
 }
 
+// 入力ソースの ID と表示名から、日本語・英字・その他のどれかに分ける。
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn classify(input_source_id: &str, localized_name: &str, ascii_capable: bool) -> InputMode {
+    let source_id_lower = input_source_id.to_lowercase();
+    let name_lower = localized_name.to_lowercase();
+
+    if source_id_lower.contains("kotoeri")
+        || source_id_lower.contains("japanese")
+        || localized_name.contains("日本語")
+        || name_lower.contains("hiragana")
+        || name_lower.contains("katakana")
+    {
+        return InputMode::Japanese;
+    }
+
+    if source_id_lower.contains(".abc")
+        || localized_name == "ABC"
+        || (ascii_capable && source_id_lower.contains(".us"))
+        || (ascii_capable && name_lower == "us")
+    {
+        return InputMode::English;
+    }
+
+    let display = if !localized_name.is_empty() {
+        localized_name.to_string()
+    } else if !input_source_id.is_empty() {
+        input_source_id.to_string()
+    } else {
+        "unknown".to_string()
+    };
+
+    InputMode::Other(display)
+}
+
+// 同じモードの入力ソースが複数ある場合の優先度。日本語はひらがな入力、英字はキー配列そのものを選ぶ。
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn preference(mode: &InputMode, input_source_id: &str) -> u8 {
+    let source_id_lower = input_source_id.to_lowercase();
+    match mode {
+        InputMode::Japanese if source_id_lower.ends_with(".japanese") => 2,
+        InputMode::English if source_id_lower.starts_with("com.apple.keylayout.") => 2,
+        _ => 1,
+    }
+}
+
 #[cfg(target_os = "macos")]
 mod imp {
-    use super::InputMode;
+    use super::{InputMode, classify, preference};
     use std::ffi::{CStr, c_char, c_void};
 
     const K_CF_STRING_ENCODING_UTF8: u32 = 0x0800_0100;
@@ -15,6 +60,11 @@ mod imp {
     #[link(name = "Carbon", kind = "framework")]
     unsafe extern "C" {
         fn TISCopyCurrentKeyboardInputSource() -> *const c_void;
+        fn TISCreateInputSourceList(
+            properties: *const c_void,
+            include_all_installed: u8,
+        ) -> *const c_void;
+        fn TISSelectInputSource(input_source: *const c_void) -> i32;
         fn TISGetInputSourceProperty(
             input_source: *const c_void,
             key: *const c_void,
@@ -23,12 +73,19 @@ mod imp {
         static kTISPropertyInputSourceID: *const c_void;
         static kTISPropertyLocalizedName: *const c_void;
         static kTISPropertyInputSourceIsASCIICapable: *const c_void;
+        static kTISPropertyInputSourceIsSelectCapable: *const c_void;
+        static kTISPropertyInputSourceCategory: *const c_void;
+        static kTISCategoryKeyboardInputSource: *const c_void;
     }
 
     #[link(name = "CoreFoundation", kind = "framework")]
     unsafe extern "C" {
         fn CFRelease(cf: *const c_void);
         fn CFGetTypeID(cf: *const c_void) -> usize;
+        fn CFEqual(cf1: *const c_void, cf2: *const c_void) -> u8;
+
+        fn CFArrayGetCount(array: *const c_void) -> isize;
+        fn CFArrayGetValueAtIndex(array: *const c_void, index: isize) -> *const c_void;
 
         fn CFStringGetTypeID() -> usize;
         fn CFStringGetCStringPtr(string: *const c_void, encoding: u32) -> *const c_char;
@@ -43,13 +100,21 @@ mod imp {
         fn CFBooleanGetValue(boolean: *const c_void) -> u8;
     }
 
-    pub fn current_mode() -> Option<InputMode> {
-        let source = unsafe { TISCopyCurrentKeyboardInputSource() };
-        if source.is_null() {
-            return None;
+    // 入力ソースの ID・表示名・英字入力ができるか
+    struct SourceInfo {
+        id: String,
+        localized_name: String,
+        ascii_capable: bool,
+    }
+
+    impl SourceInfo {
+        fn mode(&self) -> InputMode {
+            classify(&self.id, &self.localized_name, self.ascii_capable)
         }
+    }
 
-        let input_source_id = unsafe {
+    unsafe fn source_info(source: *const c_void) -> SourceInfo {
+        let id = unsafe {
             cf_string_to_rust(TISGetInputSourceProperty(source, kTISPropertyInputSourceID))
         }
         .unwrap_or_default();
@@ -67,37 +132,88 @@ mod imp {
         }
         .unwrap_or(false);
 
+        SourceInfo {
+            id,
+            localized_name,
+            ascii_capable,
+        }
+    }
+
+    fn current_source() -> Option<SourceInfo> {
+        let source = unsafe { TISCopyCurrentKeyboardInputSource() };
+        if source.is_null() {
+            return None;
+        }
+        let info = unsafe { source_info(source) };
         unsafe { CFRelease(source) };
+        Some(info)
+    }
 
-        let source_id_lower = input_source_id.to_lowercase();
-        let name_lower = localized_name.to_lowercase();
+    pub fn current_mode() -> Option<InputMode> {
+        current_source().map(|info| info.mode())
+    }
 
-        if source_id_lower.contains("kotoeri")
-            || source_id_lower.contains("japanese")
-            || localized_name.contains("日本語")
-            || name_lower.contains("hiragana")
-            || name_lower.contains("katakana")
-        {
-            return Some(InputMode::Japanese);
+    // 有効な入力ソースのうち、キーボードから選べるものを順に渡す。f が true を返したら選んで終える。
+    fn select_first(mut f: impl FnMut(&SourceInfo) -> bool) -> bool {
+        let list = unsafe { TISCreateInputSourceList(std::ptr::null(), 0) };
+        if list.is_null() {
+            return false;
+        }
+        let mut selected = false;
+        let count = unsafe { CFArrayGetCount(list) };
+        for index in 0..count {
+            let source = unsafe { CFArrayGetValueAtIndex(list, index) };
+            if source.is_null() || !unsafe { is_selectable_keyboard(source) } {
+                continue;
+            }
+            if f(&unsafe { source_info(source) }) {
+                selected = unsafe { TISSelectInputSource(source) } == 0;
+                break;
+            }
+        }
+        unsafe { CFRelease(list) };
+        selected
+    }
+
+    unsafe fn is_selectable_keyboard(source: *const c_void) -> bool {
+        let select_capable = unsafe {
+            cf_bool_to_rust(TISGetInputSourceProperty(
+                source,
+                kTISPropertyInputSourceIsSelectCapable,
+            ))
         }
+        .unwrap_or(false);
+        let category =
+            unsafe { TISGetInputSourceProperty(source, kTISPropertyInputSourceCategory) };
+        select_capable
+            && !category.is_null()
+            && unsafe { CFEqual(category, kTISCategoryKeyboardInputSource) } != 0
+    }
 
-        if source_id_lower.contains(".abc")
-            || localized_name == "ABC"
-            || (ascii_capable && source_id_lower.contains(".us"))
-            || (ascii_capable && name_lower == "us")
-        {
-            return Some(InputMode::English);
+    // target のモードへ切り替える。切り替えた場合は、元に戻すための直前の入力ソースの ID を返す。
+    pub fn select_mode(target: &InputMode) -> Option<String> {
+        let current = current_source()?;
+        if current.mode() == *target {
+            return None;
         }
 
-        let display = if !localized_name.is_empty() {
-            localized_name
-        } else if !input_source_id.is_empty() {
-            input_source_id
-        } else {
-            "unknown".to_string()
-        };
+        // 優先度の高いものを探してから、その ID で選び直す。
+        let mut best: Option<(u8, String)> = None;
+        select_first(|info| {
+            if info.mode() == *target {
+                let rank = preference(target, &info.id);
+                if best.as_ref().is_none_or(|(best_rank, _)| rank > *best_rank) {
+                    best = Some((rank, info.id.clone()));
+                }
+            }
+            false
+        });
+        let (_, id) = best?;
+        select_source(&id).then_some(current.id)
+    }
 
-        Some(InputMode::Other(display))
+    pub fn select_source(id: &str) -> bool {
+        !id.is_empty() && select_first(|info| info.id == id)
     }
 
     unsafe fn cf_string_to_rust(cf: *const c_void) -> Option<String> {
@@ -147,9 +263,65 @@ mod imp {
 }
 
 #[cfg(target_os = "macos")]
-pub use imp::current_mode;
+pub use imp::{current_mode, select_mode, select_source};
 
 #[cfg(not(target_os = "macos"))]
 pub fn current_mode() -> Option<InputMode> {
     None
 }
+
+#[cfg(not(target_os = "macos"))]
+pub fn select_mode(_target: &InputMode) -> Option<String> {
+    None
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn select_source(_id: &str) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_common_sources() {
+        assert_eq!(
+            classify(
+                "com.apple.inputmethod.Kotoeri.RomajiTyping.Japanese",
+                "ひらがな",
+                false
+            ),
+            InputMode::Japanese
+        );
+        assert_eq!(
+            classify("com.apple.keylayout.ABC", "ABC", true),
+            InputMode::English
+        );
+        assert_eq!(
+            classify("com.apple.keylayout.US", "U.S.", true),
+            InputMode::English
+        );
+        assert_eq!(
+            classify("com.apple.keylayout.German", "German", true),
+            InputMode::Other("German".to_string())
+        );
+    }
+
+    #[test]
+    fn prefers_hiragana_and_plain_layouts() {
+        assert!(
+            preference(
+                &InputMode::Japanese,
+                "com.apple.inputmethod.Kotoeri.RomajiTyping.Japanese"
+            ) > preference(
+                &InputMode::Japanese,
+                "com.apple.inputmethod.Kotoeri.RomajiTyping.Japanese.Katakana"
+            )
+        );
+        assert!(
+            preference(&InputMode::English, "com.apple.keylayout.ABC")
+                > preference(&InputMode::English, "com.example.inputmethod.abc")
+        );
+    }
+}
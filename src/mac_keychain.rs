@@ -0,0 +1,190 @@
+// サイトのログイン用パスワードはキーチェーンの「汎用パスワード」項目に保存する。
+// サービス名は `VJDownloader: <サイト>`、アカウント名はユーザー名。
+fn service_name(site: &str) -> String {
+    format!("VJDownloader: {site}")
+}
+
+#[cfg(target_os = "macos")]
+mod imp {
+    use std::ffi::{c_char, c_void};
+    use std::ptr;
+
+    use crate::i18n::{Msg, tr, tr_fmt};
+
+    type OsStatus = i32;
+    type SecKeychainItemRef = *mut c_void;
+
+    const ERR_SEC_SUCCESS: OsStatus = 0;
+    const ERR_SEC_ITEM_NOT_FOUND: OsStatus = -25300;
+
+    #[link(name = "Security", kind = "framework")]
+    unsafe extern "C" {
+        fn SecKeychainFindGenericPassword(
+            keychain_or_array: *const c_void,
+            service_name_length: u32,
+            service_name: *const c_char,
+            account_name_length: u32,
+            account_name: *const c_char,
+            password_length: *mut u32,
+            password_data: *mut *mut c_void,
+            item_ref: *mut SecKeychainItemRef,
+        ) -> OsStatus;
+        fn SecKeychainAddGenericPassword(
+            keychain: *const c_void,
+            service_name_length: u32,
+            service_name: *const c_char,
+            account_name_length: u32,
+            account_name: *const c_char,
+            password_length: u32,
+            password_data: *const c_void,
+            item_ref: *mut SecKeychainItemRef,
+        ) -> OsStatus;
+        fn SecKeychainItemModifyAttributesAndData(
+            item_ref: SecKeychainItemRef,
+            attr_list: *const c_void,
+            length: u32,
+            data: *const c_void,
+        ) -> OsStatus;
+        fn SecKeychainItemDelete(item_ref: SecKeychainItemRef) -> OsStatus;
+        fn SecKeychainItemFreeContent(attr_list: *const c_void, data: *mut c_void) -> OsStatus;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    unsafe extern "C" {
+        fn CFRelease(cf: *const c_void);
+    }
+
+    // 見つかった項目。破棄時に参照を解放する。
+    struct KeychainItem(SecKeychainItemRef);
+
+    impl Drop for KeychainItem {
+        fn drop(&mut self) {
+            unsafe { CFRelease(self.0) };
+        }
+    }
+
+    fn status_error(msg: Msg, status: OsStatus) -> String {
+        tr_fmt(msg, &[&status])
+    }
+
+    // 項目を探し、want_password のときはパスワードも読み出す。
+    fn find_item(
+        service: &str,
+        account: &str,
+        want_password: bool,
+    ) -> Result<Option<(KeychainItem, Option<String>)>, String> {
+        let mut length = 0u32;
+        let mut data: *mut c_void = ptr::null_mut();
+        let mut item: SecKeychainItemRef = ptr::null_mut();
+        let (length_out, data_out): (*mut u32, *mut *mut c_void) = if want_password {
+            (&mut length, &mut data)
+        } else {
+            (ptr::null_mut(), ptr::null_mut())
+        };
+        let status = unsafe {
+            SecKeychainFindGenericPassword(
+                ptr::null(),
+                service.len() as u32,
+                service.as_ptr().cast(),
+                account.len() as u32,
+                account.as_ptr().cast(),
+                length_out,
+                data_out,
+                &mut item,
+            )
+        };
+        match status {
+            ERR_SEC_SUCCESS => {}
+            ERR_SEC_ITEM_NOT_FOUND => return Ok(None),
+            _ => return Err(status_error(Msg::KeychainReadFailed, status)),
+        }
+        let item = KeychainItem(item);
+        let password = if data.is_null() {
+            None
+        } else {
+            let bytes =
+                unsafe { std::slice::from_raw_parts(data.cast::<u8>(), length as usize) }.to_vec();
+            unsafe { SecKeychainItemFreeContent(ptr::null(), data) };
+            Some(String::from_utf8(bytes).map_err(|_| tr(Msg::KeychainPasswordInvalid))?)
+        };
+        Ok(Some((item, password)))
+    }
+
+    pub fn read_password(service: &str, account: &str) -> Result<Option<String>, String> {
+        Ok(find_item(service, account, true)?.and_then(|(_, password)| password))
+    }
+
+    // 同じ項目があればパスワードだけ書き換える。
+    pub fn save_password(service: &str, account: &str, password: &str) -> Result<(), String> {
+        let status = match find_item(service, account, false)? {
+            Some((item, _)) => unsafe {
+                SecKeychainItemModifyAttributesAndData(
+                    item.0,
+                    ptr::null(),
+                    password.len() as u32,
+                    password.as_ptr().cast(),
+                )
+            },
+            None => unsafe {
+                SecKeychainAddGenericPassword(
+                    ptr::null(),
+                    service.len() as u32,
+                    service.as_ptr().cast(),
+                    account.len() as u32,
+                    account.as_ptr().cast(),
+                    password.len() as u32,
+                    password.as_ptr().cast(),
+                    ptr::null_mut(),
+                )
+            },
+        };
+        if status == ERR_SEC_SUCCESS {
+            Ok(())
+        } else {
+            Err(status_error(Msg::KeychainSaveFailed, status))
+        }
+    }
+
+    // 項目が無い場合は何もしない。
+    pub fn delete_password(service: &str, account: &str) -> Result<(), String> {
+        let Some((item, _)) = find_item(service, account, false)? else {
+            return Ok(());
+        };
+        let status = unsafe { SecKeychainItemDelete(item.0) };
+        if status == ERR_SEC_SUCCESS {
+            Ok(())
+        } else {
+            Err(status_error(Msg::KeychainDeleteFailed, status))
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod imp {
+    use crate::i18n::{Msg, tr};
+
+    pub fn read_password(_service: &str, _account: &str) -> Result<Option<String>, String> {
+        Err(tr(Msg::KeychainUnsupported).to_string())
+    }
+
+    pub fn save_password(_service: &str, _account: &str, _password: &str) -> Result<(), String> {
+        Err(tr(Msg::KeychainUnsupported).to_string())
+    }
+
+    pub fn delete_password(_service: &str, _account: &str) -> Result<(), String> {
+        Err(tr(Msg::KeychainUnsupported).to_string())
+    }
+}
+
+// サイトのログインのパスワードを読み出す。保存されていない場合は None。
+pub fn read_login_password(site: &str, username: &str) -> Result<Option<String>, String> {
+    imp::read_password(&service_name(site), username)
+}
+
+pub fn save_login_password(site: &str, username: &str, password: &str) -> Result<(), String> {
+    imp::save_password(&service_name(site), username, password)
+}
+
+pub fn delete_login_password(site: &str, username: &str) -> Result<(), String> {
+    imp::delete_password(&service_name(site), username)
+}
@@ -0,0 +1,93 @@
+// ログイン項目（SMAppService.mainAppService）の登録状態
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoginItemStatus {
+    Disabled,
+    Enabled,
+    // 登録済みだが、システム設定の「ログイン項目」で許可されるまで起動しない。
+    RequiresApproval,
+    // macOS 13 未満、または .app として起動していないため登録できない。
+    Unavailable,
+}
+
+impl LoginItemStatus {
+    pub fn is_registered(self) -> bool {
+        matches!(
+            self,
+            LoginItemStatus::Enabled | LoginItemStatus::RequiresApproval
+        )
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod imp {
+    use objc2::msg_send;
+    use objc2::rc::Retained;
+    use objc2::runtime::{AnyClass, AnyObject, Bool};
+    use objc2_foundation::{NSBundle, NSError};
+
+    use super::LoginItemStatus;
+    use crate::i18n::{Msg, tr, tr_fmt};
+
+    // SMAppServiceStatus の値
+    const STATUS_ENABLED: isize = 1;
+    const STATUS_REQUIRES_APPROVAL: isize = 2;
+    const STATUS_NOT_FOUND: isize = 3;
+
+    #[link(name = "ServiceManagement", kind = "framework")]
+    unsafe extern "C" {}
+
+    pub fn login_item_status() -> LoginItemStatus {
+        let Some(service) = main_app_service() else {
+            return LoginItemStatus::Unavailable;
+        };
+        let status: isize = unsafe { msg_send![&service, status] };
+        match status {
+            STATUS_ENABLED => LoginItemStatus::Enabled,
+            STATUS_REQUIRES_APPROVAL => LoginItemStatus::RequiresApproval,
+            STATUS_NOT_FOUND => LoginItemStatus::Unavailable,
+            _ => LoginItemStatus::Disabled,
+        }
+    }
+
+    // アプリ本体をログイン項目へ登録・解除する。
+    pub fn set_launch_at_login(enabled: bool) -> Result<(), String> {
+        let service = main_app_service().ok_or_else(|| tr(Msg::LoginItemNeedsApp).to_string())?;
+        let mut error: *mut NSError = std::ptr::null_mut();
+        let ok: Bool = unsafe {
+            if enabled {
+                msg_send![&service, registerAndReturnError: &mut error]
+            } else {
+                msg_send![&service, unregisterAndReturnError: &mut error]
+            }
+        };
+        if ok.as_bool() {
+            return Ok(());
+        }
+        let detail = unsafe { error.as_ref() }
+            .map(|err| err.localizedDescription().to_string())
+            .unwrap_or_else(|| tr(Msg::UnknownError).to_string());
+        Err(tr_fmt(Msg::LoginItemChangeFailed, &[&detail]))
+    }
+
+    fn main_app_service() -> Option<Retained<AnyObject>> {
+        // バンドル ID の無いプロセス（cargo run など）は登録対象にならない。
+        NSBundle::mainBundle().bundleIdentifier()?;
+        let cls = AnyClass::get(c"SMAppService")?;
+        unsafe { msg_send![cls, mainAppService] }
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub use imp::{login_item_status, set_launch_at_login};
+
+#[cfg(not(target_os = "macos"))]
+pub fn login_item_status() -> LoginItemStatus {
+    LoginItemStatus::Unavailable
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn set_launch_at_login(_enabled: bool) -> Result<(), String> {
+    use crate::i18n::{Msg, tr};
+
+    Err(tr(Msg::LoginItemMacOnly).to_string())
+}
@@ -1,18 +1,31 @@
 #[cfg(target_os = "macos")]
 mod imp {
-    use std::sync::OnceLock;
+    use std::path::{Path, PathBuf};
     use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Mutex, OnceLock};
 
+    use eframe::egui;
     use objc2::rc::Retained;
     use objc2::runtime::{AnyClass, AnyObject, ClassBuilder, Sel};
     use objc2::{ClassType, msg_send, sel};
-    use objc2_app_kit::{NSApplication, NSEventModifierFlags, NSMenu, NSMenuItem};
+    use objc2_app_kit::{
+        NSApplication, NSEventModifierFlags, NSF1FunctionKey, NSMenu, NSMenuItem,
+        NSUpArrowFunctionKey,
+    };
     use objc2_foundation::{MainThreadMarker, NSObject, NSString};
 
+    use crate::i18n::{Msg, tr};
+
     static OPEN_SETTINGS_REQUEST: AtomicBool = AtomicBool::new(false);
     static OPEN_LOGS_REQUEST: AtomicBool = AtomicBool::new(false);
+    static OPEN_LOG_FOLDER_REQUEST: AtomicBool = AtomicBool::new(false);
+    static EXPORT_PLAYLIST_REQUEST: AtomicBool = AtomicBool::new(false);
     static MENU_INSTALLED: OnceLock<()> = OnceLock::new();
     static MENU_TARGET: OnceLock<usize> = OnceLock::new();
+    // 「最近のダウンロード」のサブメニューと、各項目のタグ（位置）に対応するファイル
+    static RECENT_MENU: OnceLock<usize> = OnceLock::new();
+    static RECENT_PATHS: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+    static RECENT_REQUEST: Mutex<Option<PathBuf>> = Mutex::new(None);
 
     pub fn install_settings_menu() {
         MENU_INSTALLED.get_or_init(|| {
@@ -28,6 +41,64 @@ mod imp {
         OPEN_LOGS_REQUEST.swap(false, Ordering::Relaxed)
     }
 
+    pub fn take_open_log_folder_request() -> bool {
+        OPEN_LOG_FOLDER_REQUEST.swap(false, Ordering::Relaxed)
+    }
+
+    pub fn take_export_playlist_request() -> bool {
+        EXPORT_PLAYLIST_REQUEST.swap(false, Ordering::Relaxed)
+    }
+
+    // 「最近のダウンロード」から選ばれたファイル
+    pub fn take_recent_download_request() -> Option<PathBuf> {
+        RECENT_REQUEST.lock().unwrap().take()
+    }
+
+    // 「最近のダウンロード」の項目を新しい順のファイルで作り直す。
+    pub fn set_recent_downloads(paths: &[PathBuf]) {
+        let Some(mtm) = MainThreadMarker::new() else {
+            return;
+        };
+        let (Some(target_ptr), Some(menu_ptr)) = (MENU_TARGET.get(), RECENT_MENU.get()) else {
+            return;
+        };
+        let target = unsafe { &*(*target_ptr as *mut AnyObject) };
+        let menu = unsafe { &*(*menu_ptr as *const NSMenu) };
+        *RECENT_PATHS.lock().unwrap() = paths.to_vec();
+
+        menu.removeAllItems();
+        if paths.is_empty() {
+            let item = menu_item(mtm, tr(Msg::NoRecentDownloads), None);
+            item.setEnabled(false);
+            menu.addItem(&item);
+            return;
+        }
+        for (index, path) in paths.iter().enumerate() {
+            let item = menu_item(mtm, &file_name(path), Some(sel!(openRecentDownload:)));
+            unsafe {
+                item.setTarget(Some(target));
+            }
+            item.setTag(index as isize);
+            item.setToolTip(Some(&NSString::from_str(&path.to_string_lossy())));
+            menu.addItem(&item);
+        }
+    }
+
+    fn file_name(path: &Path) -> String {
+        path.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string_lossy().to_string())
+    }
+
+    fn menu_item(mtm: MainThreadMarker, title: &str, action: Option<Sel>) -> Retained<NSMenuItem> {
+        let title = NSString::from_str(title);
+        let key_equivalent = NSString::from_str("");
+        let item = mtm.alloc::<NSMenuItem>();
+        unsafe {
+            NSMenuItem::initWithTitle_action_keyEquivalent(item, &title, action, &key_equivalent)
+        }
+    }
+
     fn install_settings_menu_inner() {
         let Some(mtm) = MainThreadMarker::new() else {
             return;
@@ -53,7 +124,7 @@ mod imp {
                 existing_item.setAction(Some(sel!(openSettings:)));
             }
         } else {
-            let title = NSString::from_str("設定...");
+            let title = NSString::from_str(tr(Msg::SettingsMenu));
             let key_equivalent = NSString::from_str(",");
             let item = mtm.alloc::<NSMenuItem>();
             let item = unsafe {
@@ -80,7 +151,7 @@ mod imp {
                 existing_logs.setAction(Some(sel!(openLogs:)));
             }
         } else {
-            let title = NSString::from_str("ログ...");
+            let title = NSString::from_str(tr(Msg::LogsMenu));
             let key_equivalent = NSString::from_str("l");
             let item = mtm.alloc::<NSMenuItem>();
             let item = unsafe {
@@ -100,6 +171,167 @@ mod imp {
             let insert_index = if count > 2 { 2 } else { count };
             app_menu.insertItem_atIndex(&item, insert_index);
         }
+
+        if let Some(existing_log_folder) = find_existing_log_folder(&app_menu) {
+            unsafe {
+                existing_log_folder.setTarget(Some(target));
+                existing_log_folder.setAction(Some(sel!(openLogFolder:)));
+            }
+        } else {
+            let title = NSString::from_str(tr(Msg::OpenLogFolder));
+            let key_equivalent = NSString::from_str("");
+            let item = mtm.alloc::<NSMenuItem>();
+            let item = unsafe {
+                NSMenuItem::initWithTitle_action_keyEquivalent(
+                    item,
+                    &title,
+                    Some(sel!(openLogFolder:)),
+                    &key_equivalent,
+                )
+            };
+            unsafe {
+                item.setTarget(Some(target));
+            }
+
+            let count = app_menu.numberOfItems();
+            let insert_index = if count > 3 { 3 } else { count };
+            app_menu.insertItem_atIndex(&item, insert_index);
+        }
+
+        if let Some(existing_export) = find_existing_export_playlist(&app_menu) {
+            unsafe {
+                existing_export.setTarget(Some(target));
+                existing_export.setAction(Some(sel!(exportPlaylist:)));
+            }
+        } else {
+            let title = NSString::from_str(tr(Msg::ExportPlaylistMenu));
+            let key_equivalent = NSString::from_str("e");
+            let item = mtm.alloc::<NSMenuItem>();
+            let item = unsafe {
+                NSMenuItem::initWithTitle_action_keyEquivalent(
+                    item,
+                    &title,
+                    Some(sel!(exportPlaylist:)),
+                    &key_equivalent,
+                )
+            };
+            unsafe {
+                item.setTarget(Some(target));
+            }
+            item.setKeyEquivalentModifierMask(NSEventModifierFlags::Command);
+
+            let count = app_menu.numberOfItems();
+            let insert_index = if count > 4 { 4 } else { count };
+            app_menu.insertItem_atIndex(&item, insert_index);
+        }
+
+        if find_existing_recent_downloads(&app_menu).is_none() {
+            let title = NSString::from_str(tr(Msg::RecentDownloadsMenu));
+            let submenu = NSMenu::initWithTitle(mtm.alloc::<NSMenu>(), &title);
+            // 項目がない場合の案内を選べない表示にする。
+            submenu.setAutoenablesItems(false);
+            let item = menu_item(mtm, tr(Msg::RecentDownloadsMenu), None);
+            item.setSubmenu(Some(&submenu));
+            RECENT_MENU.get_or_init(|| Retained::into_raw(submenu) as usize);
+
+            let count = app_menu.numberOfItems();
+            let insert_index = if count > 5 { 5 } else { count };
+            app_menu.insertItem_atIndex(&item, insert_index);
+            set_recent_downloads(&[]);
+        }
+    }
+
+    // 設定とログの項目のキー表示を、アプリ内のショートカットに合わせる。None の場合はキーを外す。
+    pub fn set_menu_shortcuts(
+        open_settings: Option<egui::KeyboardShortcut>,
+        open_logs: Option<egui::KeyboardShortcut>,
+    ) {
+        let Some(mtm) = MainThreadMarker::new() else {
+            return;
+        };
+        let Some(target_ptr) = MENU_TARGET.get() else {
+            return;
+        };
+        let target = unsafe { &*(*target_ptr as *mut AnyObject) };
+        let app = NSApplication::sharedApplication(mtm);
+        let Some(app_menu) = app
+            .mainMenu()
+            .and_then(|menu| menu.itemAtIndex(0))
+            .and_then(|item| item.submenu())
+        else {
+            return;
+        };
+
+        for (action, shortcut) in [
+            (sel!(openSettings:), open_settings),
+            (sel!(openLogs:), open_logs),
+        ] {
+            let index =
+                unsafe { app_menu.indexOfItemWithTarget_andAction(Some(target), Some(action)) };
+            if index < 0 {
+                continue;
+            }
+            let Some(item) = app_menu.itemAtIndex(index) else {
+                continue;
+            };
+            let (key, modifiers) = shortcut
+                .and_then(|shortcut| {
+                    key_equivalent(shortcut.logical_key)
+                        .map(|key| (key, modifier_mask(shortcut.modifiers)))
+                })
+                .unwrap_or((String::new(), NSEventModifierFlags::empty()));
+            item.setKeyEquivalent(&NSString::from_str(&key));
+            item.setKeyEquivalentModifierMask(modifiers);
+        }
+    }
+
+    // メニューのキー表示に使う文字。メニューで表せないキーは None。
+    fn key_equivalent(key: egui::Key) -> Option<String> {
+        let function_key = |offset: u32| char::from_u32(offset).map(String::from);
+        match key {
+            egui::Key::Enter => Some("\r".to_string()),
+            egui::Key::Tab => Some("\t".to_string()),
+            egui::Key::Space => Some(" ".to_string()),
+            egui::Key::Escape => Some("\u{1b}".to_string()),
+            egui::Key::Minus => Some("-".to_string()),
+            egui::Key::Quote => Some("'".to_string()),
+            egui::Key::ArrowUp => function_key(NSUpArrowFunctionKey),
+            egui::Key::ArrowDown => function_key(NSUpArrowFunctionKey + 1),
+            egui::Key::ArrowLeft => function_key(NSUpArrowFunctionKey + 2),
+            egui::Key::ArrowRight => function_key(NSUpArrowFunctionKey + 3),
+            _ => {
+                let name = key.symbol_or_name();
+                // F1〜F35 の文字コードは連続している。
+                if let Some(number) = name
+                    .strip_prefix('F')
+                    .and_then(|number| number.parse::<u32>().ok())
+                {
+                    return function_key(NSF1FunctionKey + number - 1);
+                }
+                let mut chars = name.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(ch), None) => Some(ch.to_ascii_lowercase().to_string()),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    fn modifier_mask(modifiers: egui::Modifiers) -> NSEventModifierFlags {
+        let mut mask = NSEventModifierFlags::empty();
+        if modifiers.command {
+            mask |= NSEventModifierFlags::Command;
+        }
+        if modifiers.ctrl {
+            mask |= NSEventModifierFlags::Control;
+        }
+        if modifiers.alt {
+            mask |= NSEventModifierFlags::Option;
+        }
+        if modifiers.shift {
+            mask |= NSEventModifierFlags::Shift;
+        }
+        mask
     }
 
     fn find_existing_preferences(menu: &NSMenu) -> Option<Retained<NSMenuItem>> {
@@ -112,6 +344,21 @@ mod imp {
         find_existing_item_by_titles(menu, &titles)
     }
 
+    fn find_existing_log_folder(menu: &NSMenu) -> Option<Retained<NSMenuItem>> {
+        let titles = ["ログフォルダを開く", "Open Log Folder"];
+        find_existing_item_by_titles(menu, &titles)
+    }
+
+    fn find_existing_export_playlist(menu: &NSMenu) -> Option<Retained<NSMenuItem>> {
+        let titles = ["プレイリストを書き出す...", "Export Playlist..."];
+        find_existing_item_by_titles(menu, &titles)
+    }
+
+    fn find_existing_recent_downloads(menu: &NSMenu) -> Option<Retained<NSMenuItem>> {
+        let titles = ["最近のダウンロード", "Recent Downloads"];
+        find_existing_item_by_titles(menu, &titles)
+    }
+
     fn find_existing_item_by_titles(
         menu: &NSMenu,
         titles: &[&str],
@@ -140,6 +387,18 @@ mod imp {
             unsafe {
                 builder.add_method(sel!(openSettings:), open_settings as extern "C" fn(_, _, _));
                 builder.add_method(sel!(openLogs:), open_logs as extern "C" fn(_, _, _));
+                builder.add_method(
+                    sel!(openLogFolder:),
+                    open_log_folder as extern "C" fn(_, _, _),
+                );
+                builder.add_method(
+                    sel!(exportPlaylist:),
+                    export_playlist as extern "C" fn(_, _, _),
+                );
+                builder.add_method(
+                    sel!(openRecentDownload:),
+                    open_recent_download as extern "C" fn(_, _, _),
+                );
             }
             builder.register()
         })
@@ -152,14 +411,45 @@ mod imp {
     extern "C" fn open_logs(_this: &AnyObject, _sel: Sel, _sender: *mut AnyObject) {
         OPEN_LOGS_REQUEST.store(true, Ordering::Relaxed);
     }
+
+    extern "C" fn open_log_folder(_this: &AnyObject, _sel: Sel, _sender: *mut AnyObject) {
+        OPEN_LOG_FOLDER_REQUEST.store(true, Ordering::Relaxed);
+    }
+
+    extern "C" fn export_playlist(_this: &AnyObject, _sel: Sel, _sender: *mut AnyObject) {
+        EXPORT_PLAYLIST_REQUEST.store(true, Ordering::Relaxed);
+    }
+
+    // 選ばれた項目のタグから、対応するファイルを取り出す。
+    extern "C" fn open_recent_download(_this: &AnyObject, _sel: Sel, sender: *mut AnyObject) {
+        if sender.is_null() {
+            return;
+        }
+        let tag: isize = unsafe { msg_send![&*sender, tag] };
+        let paths = RECENT_PATHS.lock().unwrap();
+        if let Some(path) = usize::try_from(tag).ok().and_then(|index| paths.get(index)) {
+            *RECENT_REQUEST.lock().unwrap() = Some(path.clone());
+        }
+    }
 }
 
 #[cfg(target_os = "macos")]
-pub use imp::{install_settings_menu, take_open_logs_request, take_open_settings_request};
+pub use imp::{
+    install_settings_menu, set_menu_shortcuts, set_recent_downloads, take_export_playlist_request,
+    take_open_log_folder_request, take_open_logs_request, take_open_settings_request,
+    take_recent_download_request,
+};
 
 #[cfg(not(target_os = "macos"))]
 pub fn install_settings_menu() {}
 
+#[cfg(not(target_os = "macos"))]
+pub fn set_menu_shortcuts(
+    _open_settings: Option<eframe::egui::KeyboardShortcut>,
+    _open_logs: Option<eframe::egui::KeyboardShortcut>,
+) {
+}
+
 #[cfg(not(target_os = "macos"))]
 pub fn take_open_settings_request() -> bool {
     false
@@ -169,3 +459,21 @@ pub fn take_open_settings_request() -> bool {
 pub fn take_open_logs_request() -> bool {
     false
 }
+
+#[cfg(not(target_os = "macos"))]
+pub fn take_open_log_folder_request() -> bool {
+    false
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn take_export_playlist_request() -> bool {
+    false
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn set_recent_downloads(_paths: &[std::path::PathBuf]) {}
+
+#[cfg(not(target_os = "macos"))]
+pub fn take_recent_download_request() -> Option<std::path::PathBuf> {
+    None
+}
@@ -0,0 +1,161 @@
+#[cfg(target_os = "macos")]
+mod imp {
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Mutex, OnceLock};
+
+    use block2::{Block, RcBlock};
+    use eframe::egui;
+    use objc2::rc::Retained;
+    use objc2::runtime::{AnyClass, AnyObject, Bool, ClassBuilder, Sel};
+    use objc2::{ClassType, msg_send, sel};
+    use objc2_foundation::{NSBundle, NSError, NSObject, NSString};
+    use objc2_user_notifications::{
+        UNAuthorizationOptions, UNMutableNotificationContent, UNNotificationPresentationOptions,
+        UNNotificationRequest, UNNotificationResponse, UNUserNotificationCenter,
+    };
+
+    // クリックで Finder 表示するファイルを通知 ID に埋め込むための接頭辞
+    const REVEAL_ID_PREFIX: &str = "reveal:";
+
+    static CENTER_READY: OnceLock<bool> = OnceLock::new();
+    static NOTIFICATION_SEQ: AtomicU64 = AtomicU64::new(0);
+    static REVEAL_REQUEST: Mutex<Option<PathBuf>> = Mutex::new(None);
+    static REPAINT_CONTEXT: Mutex<Option<egui::Context>> = Mutex::new(None);
+
+    // 通知センターへデリゲートを設定し、通知の許可を求める。.app として起動していない場合は何もしない。
+    pub fn install_notifications(ctx: &egui::Context) {
+        if let Ok(mut slot) = REPAINT_CONTEXT.lock() {
+            *slot = Some(ctx.clone());
+        }
+        CENTER_READY.get_or_init(install_inner);
+    }
+
+    pub fn take_reveal_request() -> Option<PathBuf> {
+        REVEAL_REQUEST.lock().ok()?.take()
+    }
+
+    // 通知を表示する。reveal を指定するとクリック時にそのファイルを Finder で表示する。
+    pub fn post_notification(title: &str, body: &str, reveal: Option<&Path>) {
+        if !CENTER_READY.get().copied().unwrap_or(false) {
+            return;
+        }
+        let content = UNMutableNotificationContent::new();
+        content.setTitle(&NSString::from_str(title));
+        content.setBody(&NSString::from_str(body));
+
+        let identifier = match reveal {
+            Some(path) => format!("{REVEAL_ID_PREFIX}{}", path.to_string_lossy()),
+            None => format!(
+                "vjdownloader-{}",
+                NOTIFICATION_SEQ.fetch_add(1, Ordering::Relaxed)
+            ),
+        };
+        let request = UNNotificationRequest::requestWithIdentifier_content_trigger(
+            &NSString::from_str(&identifier),
+            &content,
+            None,
+        );
+        UNUserNotificationCenter::currentNotificationCenter()
+            .addNotificationRequest_withCompletionHandler(&request, None);
+    }
+
+    fn install_inner() -> bool {
+        // バンドル ID の無いプロセスで通知センターに触れると例外になる。
+        if NSBundle::mainBundle().bundleIdentifier().is_none() {
+            return false;
+        }
+        let center = UNUserNotificationCenter::currentNotificationCenter();
+        let delegate = create_delegate();
+        unsafe {
+            let _: () = msg_send![&center, setDelegate: &*delegate];
+        }
+        // デリゲートは弱参照で保持されるため、アプリ終了まで解放しない。
+        std::mem::forget(delegate);
+
+        let completion = RcBlock::new(|_granted: Bool, _error: *mut NSError| {});
+        center.requestAuthorizationWithOptions_completionHandler(
+            UNAuthorizationOptions::Alert | UNAuthorizationOptions::Sound,
+            &completion,
+        );
+        true
+    }
+
+    fn create_delegate() -> Retained<AnyObject> {
+        let cls = delegate_class();
+        unsafe { msg_send![cls, new] }
+    }
+
+    fn delegate_class() -> &'static AnyClass {
+        static CLASS: OnceLock<&AnyClass> = OnceLock::new();
+        CLASS.get_or_init(|| {
+            let superclass = NSObject::class();
+            let mut builder = ClassBuilder::new(c"VJDownloaderNotificationDelegate", superclass)
+                .expect("class");
+            unsafe {
+                builder.add_method(
+                    sel!(userNotificationCenter:didReceiveNotificationResponse:withCompletionHandler:),
+                    did_receive_response as extern "C" fn(_, _, _, _, _),
+                );
+                builder.add_method(
+                    sel!(userNotificationCenter:willPresentNotification:withCompletionHandler:),
+                    will_present as extern "C" fn(_, _, _, _, _),
+                );
+            }
+            builder.register()
+        })
+    }
+
+    extern "C" fn did_receive_response(
+        _this: &AnyObject,
+        _sel: Sel,
+        _center: *mut AnyObject,
+        response: *mut UNNotificationResponse,
+        completion: *mut Block<dyn Fn()>,
+    ) {
+        if let Some(response) = unsafe { response.as_ref() } {
+            let identifier = response.notification().request().identifier().to_string();
+            if let Some(path) = identifier.strip_prefix(REVEAL_ID_PREFIX)
+                && let Ok(mut slot) = REVEAL_REQUEST.lock()
+            {
+                *slot = Some(PathBuf::from(path));
+            }
+        }
+        if let Ok(slot) = REPAINT_CONTEXT.lock()
+            && let Some(ctx) = slot.as_ref()
+        {
+            ctx.request_repaint();
+        }
+        if let Some(completion) = unsafe { completion.as_ref() } {
+            completion.call(());
+        }
+    }
+
+    // アプリが前面にある（別ウィンドウにフォーカスがある）場合もバナーを表示する。
+    extern "C" fn will_present(
+        _this: &AnyObject,
+        _sel: Sel,
+        _center: *mut AnyObject,
+        _notification: *mut AnyObject,
+        completion: *mut Block<dyn Fn(UNNotificationPresentationOptions)>,
+    ) {
+        if let Some(completion) = unsafe { completion.as_ref() } {
+            completion.call((UNNotificationPresentationOptions::Banner
+                | UNNotificationPresentationOptions::Sound,));
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub use imp::{install_notifications, post_notification, take_reveal_request};
+
+#[cfg(not(target_os = "macos"))]
+pub fn install_notifications(_ctx: &eframe::egui::Context) {}
+
+#[cfg(not(target_os = "macos"))]
+pub fn post_notification(_title: &str, _body: &str, _reveal: Option<&std::path::Path>) {}
+
+#[cfg(not(target_os = "macos"))]
+pub fn take_reveal_request() -> Option<std::path::PathBuf> {
+    None
+}
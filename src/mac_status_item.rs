@@ -0,0 +1,229 @@
+// メニューバーのステータス項目から届く操作
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatusItemAction {
+    DownloadClipboard,
+    TogglePause,
+    OpenWindow,
+}
+
+// メニューバーに表示する文字列。ダウンロード中は進捗率、待ちがあれば件数を添える。
+pub fn status_title(progress: Option<f32>, queued: usize, paused: bool) -> String {
+    let mut title = match progress {
+        Some(value) if value >= 0.0 => format!("VJ {:.0}%", value.clamp(0.0, 1.0) * 100.0),
+        Some(_) => "VJ …".to_string(),
+        None => "VJ".to_string(),
+    };
+    if queued > 0 {
+        title.push_str(&format!(" +{queued}"));
+    }
+    if paused {
+        title.push_str(" ⏸");
+    }
+    title
+}
+
+#[cfg(target_os = "macos")]
+mod imp {
+    use std::cell::RefCell;
+    use std::sync::{Mutex, OnceLock};
+
+    use eframe::egui;
+    use objc2::rc::Retained;
+    use objc2::runtime::{AnyClass, AnyObject, ClassBuilder, Sel};
+    use objc2::{ClassType, msg_send, sel};
+    use objc2_app_kit::{
+        NSMenu, NSMenuItem, NSStatusBar, NSStatusItem, NSVariableStatusItemLength,
+    };
+    use objc2_foundation::{MainThreadMarker, NSObject, NSString};
+
+    use crate::i18n::{Msg, tr};
+
+    use super::{StatusItemAction, status_title};
+
+    static PENDING_ACTIONS: Mutex<Vec<StatusItemAction>> = Mutex::new(Vec::new());
+    static REPAINT_CONTEXT: Mutex<Option<egui::Context>> = Mutex::new(None);
+    static MENU_TARGET: OnceLock<usize> = OnceLock::new();
+
+    struct StatusItem {
+        item: Retained<NSStatusItem>,
+        pause_item: Retained<NSMenuItem>,
+    }
+
+    thread_local! {
+        static STATUS_ITEM: RefCell<Option<StatusItem>> = const { RefCell::new(None) };
+    }
+
+    // ステータス項目を表示・非表示にする。
+    pub fn set_status_item_enabled(ctx: &egui::Context, enabled: bool) {
+        let Some(mtm) = MainThreadMarker::new() else {
+            return;
+        };
+        if let Ok(mut slot) = REPAINT_CONTEXT.lock() {
+            *slot = Some(ctx.clone());
+        }
+        STATUS_ITEM.with(|slot| {
+            let mut slot = slot.borrow_mut();
+            match (enabled, slot.as_ref()) {
+                (true, None) => *slot = Some(create_status_item(mtm)),
+                (false, Some(existing)) => {
+                    NSStatusBar::systemStatusBar().removeStatusItem(&existing.item);
+                    *slot = None;
+                }
+                _ => {}
+            }
+        });
+    }
+
+    // 表示中のステータス項目へ進捗と一時停止状態を反映する。
+    pub fn update_status_item(progress: Option<f32>, queued: usize, paused: bool) {
+        let Some(mtm) = MainThreadMarker::new() else {
+            return;
+        };
+        STATUS_ITEM.with(|slot| {
+            let slot = slot.borrow();
+            let Some(status) = slot.as_ref() else {
+                return;
+            };
+            if let Some(button) = status.item.button(mtm) {
+                button.setTitle(&NSString::from_str(&status_title(progress, queued, paused)));
+            }
+            let pause_title = if paused {
+                tr(Msg::ResumeAll)
+            } else {
+                tr(Msg::PauseAll)
+            };
+            status.pause_item.setTitle(&NSString::from_str(pause_title));
+        });
+    }
+
+    pub fn take_status_item_actions() -> Vec<StatusItemAction> {
+        PENDING_ACTIONS
+            .lock()
+            .map(|mut actions| std::mem::take(&mut *actions))
+            .unwrap_or_default()
+    }
+
+    fn create_status_item(mtm: MainThreadMarker) -> StatusItem {
+        let item = NSStatusBar::systemStatusBar().statusItemWithLength(NSVariableStatusItemLength);
+        if let Some(button) = item.button(mtm) {
+            button.setTitle(&NSString::from_str(&status_title(None, 0, false)));
+        }
+
+        let target_ptr =
+            MENU_TARGET.get_or_init(|| Retained::into_raw(create_menu_target()) as usize);
+        let target = unsafe { &*(*target_ptr as *mut AnyObject) };
+
+        let menu = NSMenu::new(mtm);
+        menu.addItem(&menu_item(
+            mtm,
+            target,
+            tr(Msg::DownloadClipboardUrl),
+            sel!(downloadClipboard:),
+        ));
+        let pause_item = menu_item(mtm, target, tr(Msg::PauseAll), sel!(togglePause:));
+        menu.addItem(&pause_item);
+        menu.addItem(&NSMenuItem::separatorItem(mtm));
+        menu.addItem(&menu_item(
+            mtm,
+            target,
+            tr(Msg::OpenWindow),
+            sel!(openWindow:),
+        ));
+        item.setMenu(Some(&menu));
+
+        StatusItem { item, pause_item }
+    }
+
+    fn menu_item(
+        mtm: MainThreadMarker,
+        target: &AnyObject,
+        title: &str,
+        action: Sel,
+    ) -> Retained<NSMenuItem> {
+        let item = unsafe {
+            NSMenuItem::initWithTitle_action_keyEquivalent(
+                mtm.alloc::<NSMenuItem>(),
+                &NSString::from_str(title),
+                Some(action),
+                &NSString::from_str(""),
+            )
+        };
+        unsafe {
+            item.setTarget(Some(target));
+        }
+        item
+    }
+
+    fn create_menu_target() -> Retained<AnyObject> {
+        let cls = menu_target_class();
+        unsafe { msg_send![cls, new] }
+    }
+
+    fn menu_target_class() -> &'static AnyClass {
+        static CLASS: OnceLock<&AnyClass> = OnceLock::new();
+        CLASS.get_or_init(|| {
+            let superclass = NSObject::class();
+            let mut builder =
+                ClassBuilder::new(c"VJDownloaderStatusItemTarget", superclass).expect("class");
+            unsafe {
+                builder.add_method(
+                    sel!(downloadClipboard:),
+                    download_clipboard as extern "C" fn(_, _, _),
+                );
+                builder.add_method(sel!(togglePause:), toggle_pause as extern "C" fn(_, _, _));
+                builder.add_method(sel!(openWindow:), open_window as extern "C" fn(_, _, _));
+            }
+            builder.register()
+        })
+    }
+
+    fn push_action(action: StatusItemAction) {
+        if let Ok(mut actions) = PENDING_ACTIONS.lock() {
+            actions.push(action);
+        }
+        // ウィンドウが隠れていても操作を処理できるよう再描画を要求する。
+        if let Ok(slot) = REPAINT_CONTEXT.lock()
+            && let Some(ctx) = slot.as_ref()
+        {
+            ctx.request_repaint();
+        }
+    }
+
+    extern "C" fn download_clipboard(_this: &AnyObject, _sel: Sel, _sender: *mut AnyObject) {
+        push_action(StatusItemAction::DownloadClipboard);
+    }
+
+    extern "C" fn toggle_pause(_this: &AnyObject, _sel: Sel, _sender: *mut AnyObject) {
+        push_action(StatusItemAction::TogglePause);
+    }
+
+    extern "C" fn open_window(_this: &AnyObject, _sel: Sel, _sender: *mut AnyObject) {
+        push_action(StatusItemAction::OpenWindow);
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub use imp::{set_status_item_enabled, take_status_item_actions, update_status_item};
+
+#[cfg(not(target_os = "macos"))]
+pub fn set_status_item_enabled(_ctx: &eframe::egui::Context, _enabled: bool) {}
+
+#[cfg(not(target_os = "macos"))]
+pub fn update_status_item(_progress: Option<f32>, _queued: usize, _paused: bool) {}
+
+#[cfg(not(target_os = "macos"))]
+pub fn take_status_item_actions() -> Vec<StatusItemAction> {
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_status_title() {
+        assert_eq!(status_title(None, 0, false), "VJ");
+        assert_eq!(status_title(Some(0.424), 2, false), "VJ 42% +2");
+        assert_eq!(status_title(Some(-1.0), 0, true), "VJ … ⏸");
+    }
+}
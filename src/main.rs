@@ -1,20 +1,57 @@
 mod app;
 mod app_logger;
+mod audio_track_ui;
 mod bundled;
+mod chapter_ui;
+mod cloud_files;
+mod collision_ui;
+mod crop_ui;
 mod cursor;
+mod diagnostics;
 mod download;
+mod dry_run_ui;
+mod error_ui;
+mod event_stream;
+mod faststart;
+mod file_list;
+mod format_ui;
 mod fs_utils;
+mod i18n;
 mod log_ui;
+mod mac_apple_event;
+mod mac_dock;
 mod mac_file_dialog;
+mod mac_finder_tags;
+mod mac_hotkey;
 mod mac_input_source;
+mod mac_keychain;
+mod mac_login_item;
 mod mac_menu;
+mod mac_notification;
+mod mac_status_item;
 mod mac_window;
+mod network;
+mod osc;
 mod paths;
+mod platform;
+mod playlist;
+mod power;
+mod preview_ui;
+mod recovery_ui;
+mod redact;
 mod search_index;
+mod self_check;
 mod settings;
 mod settings_ui;
+mod shortcuts;
+mod subscriptions;
+mod tag_ui;
 mod theme;
+mod toast_ui;
 mod ui;
+mod updater;
+mod video_info;
+mod watch_folder;
 
 fn main() -> eframe::Result<()> {
     app::run()
@@ -0,0 +1,384 @@
+use eframe::egui;
+use std::net::{Ipv4Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, mpsc};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::i18n::{Msg, tr, tr_fmt};
+use crate::mac_apple_event::validate_download_url;
+
+pub const DEFAULT_OSC_PORT: u16 = 9000;
+const RECV_TIMEOUT: Duration = Duration::from_millis(200);
+const MAX_PACKET_SIZE: usize = 8192;
+const MAX_BUNDLE_DEPTH: usize = 8;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum OscArg {
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Blob(Vec<u8>),
+    Bool(bool),
+    Nil,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct OscMessage {
+    pub address: String,
+    pub args: Vec<OscArg>,
+}
+
+// OSC から受け付ける操作。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OscCommand {
+    Download(String),
+    DownloadClipboard,
+    Search(String),
+    Stop,
+}
+
+// UDP で OSC を受信し、進捗フィードバックを送り返す。破棄時に受信スレッドを止める。
+pub struct OscBridge {
+    socket: UdpSocket,
+    feedback_target: Option<SocketAddr>,
+    rx: mpsc::Receiver<OscCommand>,
+    stop_flag: Arc<AtomicBool>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl OscBridge {
+    // allow_lan が無効な場合はループバックだけで待ち受け、他の機器からの送信を受けない。
+    pub fn start(
+        port: u16,
+        allow_lan: bool,
+        feedback_target: Option<SocketAddr>,
+        ctx: egui::Context,
+    ) -> Result<Self, String> {
+        let host = if allow_lan {
+            Ipv4Addr::UNSPECIFIED
+        } else {
+            Ipv4Addr::LOCALHOST
+        };
+        let socket = UdpSocket::bind((host, port))
+            .map_err(|err| tr_fmt(Msg::OscPortOpenFailed, &[&port, &err]))?;
+        let recv_socket = socket.try_clone().map_err(|err| err.to_string())?;
+        recv_socket
+            .set_read_timeout(Some(RECV_TIMEOUT))
+            .map_err(|err| err.to_string())?;
+
+        let (tx, rx) = mpsc::channel();
+        let stop_flag = Arc::new(AtomicBool::new(false));
+        let worker_stop = stop_flag.clone();
+        let worker = thread::spawn(move || receive_loop(recv_socket, tx, worker_stop, ctx));
+
+        Ok(Self {
+            socket,
+            feedback_target,
+            rx,
+            stop_flag,
+            worker: Some(worker),
+        })
+    }
+
+    pub fn try_recv(&self) -> Option<OscCommand> {
+        self.rx.try_recv().ok()
+    }
+
+    // フィードバック先が設定されている場合のみ送信する。送信失敗は無視する。
+    pub fn send_feedback(&self, address: &str, args: &[OscArg]) {
+        let Some(target) = self.feedback_target else {
+            return;
+        };
+        let _ = self.socket.send_to(&encode_message(address, args), target);
+    }
+}
+
+impl Drop for OscBridge {
+    fn drop(&mut self) {
+        self.stop_flag.store(true, Ordering::Relaxed);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn receive_loop(
+    socket: UdpSocket,
+    tx: mpsc::Sender<OscCommand>,
+    stop_flag: Arc<AtomicBool>,
+    ctx: egui::Context,
+) {
+    let mut buf = [0_u8; MAX_PACKET_SIZE];
+    while !stop_flag.load(Ordering::Relaxed) {
+        let Ok((len, _)) = socket.recv_from(&mut buf) else {
+            continue;
+        };
+        let mut messages = Vec::new();
+        if decode_packet(&buf[..len], 0, &mut messages).is_err() {
+            continue;
+        }
+        let mut received = false;
+        for message in messages {
+            if let Some(command) = command_from_message(&message) {
+                if tx.send(command).is_err() {
+                    return;
+                }
+                received = true;
+            }
+        }
+        if received {
+            ctx.request_repaint();
+        }
+    }
+}
+
+// /vjd/... のアドレスを操作へ変換する。未対応のアドレスは無視する。
+// ダウンロードは http/https の URL に限る。`-` で始まる値などを yt-dlp のオプションとして渡さないため。
+pub fn command_from_message(message: &OscMessage) -> Option<OscCommand> {
+    let first_str = || {
+        message.args.iter().find_map(|arg| match arg {
+            OscArg::Str(value) if !value.trim().is_empty() => Some(value.trim().to_string()),
+            _ => None,
+        })
+    };
+    match message.address.as_str() {
+        "/vjd/download" => first_str()
+            .filter(|url| validate_download_url(url).is_ok())
+            .map(OscCommand::Download),
+        "/vjd/download/clipboard" => Some(OscCommand::DownloadClipboard),
+        "/vjd/search" => Some(OscCommand::Search(first_str().unwrap_or_default())),
+        "/vjd/stop" => Some(OscCommand::Stop),
+        _ => None,
+    }
+}
+
+// 受信ポートの入力値を検証する。空欄は既定ポート。
+pub fn parse_listen_port(raw: &str) -> Result<u16, String> {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return Ok(DEFAULT_OSC_PORT);
+    }
+    match trimmed.parse::<u16>() {
+        Ok(port) if port > 0 => Ok(port),
+        _ => Err(tr(Msg::OscPortInvalid).to_string()),
+    }
+}
+
+// "host:port" 形式のフィードバック送信先を解決する。空欄は送信しない。
+pub fn parse_feedback_target(raw: &str) -> Result<Option<SocketAddr>, String> {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return Ok(None);
+    }
+    trimmed
+        .to_socket_addrs()
+        .ok()
+        .and_then(|mut addrs| addrs.next())
+        .map(Some)
+        .ok_or_else(|| tr_fmt(Msg::OscTargetInvalid, &[&trimmed]))
+}
+
+pub fn encode_message(address: &str, args: &[OscArg]) -> Vec<u8> {
+    let mut tags = String::from(",");
+    let mut payload = Vec::new();
+    for arg in args {
+        match arg {
+            OscArg::Int(value) => match i32::try_from(*value) {
+                Ok(value) => {
+                    tags.push('i');
+                    payload.extend_from_slice(&value.to_be_bytes());
+                }
+                Err(_) => {
+                    tags.push('h');
+                    payload.extend_from_slice(&value.to_be_bytes());
+                }
+            },
+            OscArg::Float(value) => {
+                tags.push('f');
+                payload.extend_from_slice(&(*value as f32).to_be_bytes());
+            }
+            OscArg::Str(value) => {
+                tags.push('s');
+                push_padded_str(&mut payload, value);
+            }
+            OscArg::Blob(value) => {
+                tags.push('b');
+                payload.extend_from_slice(&(value.len() as i32).to_be_bytes());
+                payload.extend_from_slice(value);
+                pad_to_four(&mut payload);
+            }
+            OscArg::Bool(true) => tags.push('T'),
+            OscArg::Bool(false) => tags.push('F'),
+            OscArg::Nil => tags.push('N'),
+        }
+    }
+
+    let mut out = Vec::new();
+    push_padded_str(&mut out, address);
+    push_padded_str(&mut out, &tags);
+    out.extend_from_slice(&payload);
+    out
+}
+
+// パケット（メッセージまたはバンドル）を分解し、含まれるメッセージを集める。
+pub fn decode_packet(data: &[u8], depth: usize, out: &mut Vec<OscMessage>) -> Result<(), String> {
+    if depth > MAX_BUNDLE_DEPTH {
+        return Err(tr(Msg::OscBundleTooDeep).to_string());
+    }
+    if data.starts_with(b"#bundle\0") {
+        // "#bundle" + タイムタグ(8) の後に (サイズ, 要素) が続く。タイムタグは無視して即時実行する。
+        let mut offset = 16;
+        while offset < data.len() {
+            let size = read_i32(data, &mut offset)?;
+            let size = usize::try_from(size).map_err(|_| tr(Msg::OscBundleInvalid).to_string())?;
+            let end = offset
+                .checked_add(size)
+                .filter(|end| *end <= data.len())
+                .ok_or_else(|| tr(Msg::OscBundleInvalid).to_string())?;
+            decode_packet(&data[offset..end], depth + 1, out)?;
+            offset = end;
+        }
+        return Ok(());
+    }
+    out.push(decode_message(data)?);
+    Ok(())
+}
+
+fn decode_message(data: &[u8]) -> Result<OscMessage, String> {
+    let mut offset = 0;
+    let address = read_padded_str(data, &mut offset)?;
+    if !address.starts_with('/') {
+        return Err(tr(Msg::OscAddressInvalid).to_string());
+    }
+    // 型タグが無い古い形式は引数なしとして扱う。
+    if offset >= data.len() {
+        return Ok(OscMessage {
+            address,
+            args: Vec::new(),
+        });
+    }
+    let tags = read_padded_str(data, &mut offset)?;
+    let Some(tags) = tags.strip_prefix(',') else {
+        return Err(tr(Msg::OscTypeTagInvalid).to_string());
+    };
+
+    let mut args = Vec::new();
+    for tag in tags.chars() {
+        let arg = match tag {
+            'i' => OscArg::Int(i64::from(read_i32(data, &mut offset)?)),
+            'h' => OscArg::Int(i64::from_be_bytes(read_array(data, &mut offset)?)),
+            'f' => OscArg::Float(f64::from(f32::from_be_bytes(read_array(
+                data,
+                &mut offset,
+            )?))),
+            'd' => OscArg::Float(f64::from_be_bytes(read_array(data, &mut offset)?)),
+            's' | 'S' => OscArg::Str(read_padded_str(data, &mut offset)?),
+            'b' => {
+                let len = usize::try_from(read_i32(data, &mut offset)?)
+                    .map_err(|_| tr(Msg::OscBlobInvalid).to_string())?;
+                let end = offset
+                    .checked_add(len)
+                    .filter(|end| *end <= data.len())
+                    .ok_or_else(|| tr(Msg::OscBlobInvalid).to_string())?;
+                let blob = data[offset..end].to_vec();
+                offset = end.next_multiple_of(4);
+                OscArg::Blob(blob)
+            }
+            'T' => OscArg::Bool(true),
+            'F' => OscArg::Bool(false),
+            'N' | 'I' => OscArg::Nil,
+            other => return Err(tr_fmt(Msg::OscTypeTagUnsupported, &[&other])),
+        };
+        args.push(arg);
+    }
+    Ok(OscMessage { address, args })
+}
+
+fn push_padded_str(out: &mut Vec<u8>, value: &str) {
+    out.extend_from_slice(value.as_bytes());
+    out.push(0);
+    pad_to_four(out);
+}
+
+fn pad_to_four(out: &mut Vec<u8>) {
+    while !out.len().is_multiple_of(4) {
+        out.push(0);
+    }
+}
+
+fn read_padded_str(data: &[u8], offset: &mut usize) -> Result<String, String> {
+    let rest = data.get(*offset..).unwrap_or_default();
+    let len = rest
+        .iter()
+        .position(|byte| *byte == 0)
+        .ok_or_else(|| tr(Msg::OscStringUnterminated).to_string())?;
+    let value = String::from_utf8_lossy(&rest[..len]).to_string();
+    *offset += (len + 1).next_multiple_of(4);
+    Ok(value)
+}
+
+fn read_i32(data: &[u8], offset: &mut usize) -> Result<i32, String> {
+    Ok(i32::from_be_bytes(read_array(data, offset)?))
+}
+
+fn read_array<const N: usize>(data: &[u8], offset: &mut usize) -> Result<[u8; N], String> {
+    let bytes = data
+        .get(*offset..*offset + N)
+        .ok_or_else(|| tr(Msg::OscPacketTruncated).to_string())?;
+    *offset += N;
+    Ok(bytes.try_into().expect("slice length"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_messages_and_maps_commands() {
+        let packet = encode_message(
+            "/vjd/download",
+            &[
+                OscArg::Str("https://example.com/v".to_string()),
+                OscArg::Int(3),
+            ],
+        );
+        assert!(packet.len().is_multiple_of(4));
+
+        let mut messages = Vec::new();
+        decode_packet(&packet, 0, &mut messages).unwrap();
+        assert_eq!(messages[0].address, "/vjd/download");
+        assert_eq!(
+            command_from_message(&messages[0]),
+            Some(OscCommand::Download("https://example.com/v".to_string()))
+        );
+    }
+
+    #[test]
+    fn decodes_bundles() {
+        let inner = encode_message("/vjd/search", &[OscArg::Str("loop".to_string())]);
+        let mut bundle = b"#bundle\0".to_vec();
+        bundle.extend_from_slice(&1_u64.to_be_bytes());
+        bundle.extend_from_slice(&(inner.len() as i32).to_be_bytes());
+        bundle.extend_from_slice(&inner);
+
+        let mut messages = Vec::new();
+        decode_packet(&bundle, 0, &mut messages).unwrap();
+        assert_eq!(
+            command_from_message(&messages[0]),
+            Some(OscCommand::Search("loop".to_string()))
+        );
+        assert!(decode_packet(&bundle[..bundle.len() - 2], 0, &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn ignores_downloads_that_are_not_web_urls() {
+        for value in ["--exec=touch /tmp/x", "file:///etc/passwd", "example.com/v"] {
+            let message = OscMessage {
+                address: "/vjd/download".to_string(),
+                args: vec![OscArg::Str(value.to_string())],
+            };
+            assert_eq!(command_from_message(&message), None, "{value}");
+        }
+    }
+}
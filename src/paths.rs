@@ -1,8 +1,13 @@
 use std::path::PathBuf;
 
+use crate::platform::executable_name;
+
 pub fn default_download_dir() -> PathBuf {
+    // macOS では ~/Movies、他の OS では「ビデオ」フォルダを使う。
     let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
-    home.join("Movies").join("VJDL")
+    dirs::video_dir()
+        .unwrap_or_else(|| home.join("Movies"))
+        .join("VJDL")
 }
 
 pub fn app_data_dir() -> PathBuf {
@@ -15,13 +20,65 @@ pub fn settings_dir() -> PathBuf {
 }
 
 pub fn settings_file_path() -> PathBuf {
+    settings_dir().join("settings.toml")
+}
+
+// TOML へ移行する前の設定ファイル。見つかった場合は初回読み込み時に移行する。
+pub fn legacy_settings_file_path() -> PathBuf {
     settings_dir().join("settings.properties")
 }
 
+// 実行中のダウンロードの記録。異常終了した場合に次回の起動で再開を提案する。
+pub fn active_job_file_path() -> PathBuf {
+    app_data_dir().join("active_job.json")
+}
+
+// yt-dlp の --download-archive に渡すファイル
+pub fn download_archive_path() -> PathBuf {
+    app_data_dir().join("download_archive.txt")
+}
+
+// ダウンロード済みの URL と保存したファイルの記録
+pub fn download_history_db_path() -> PathBuf {
+    app_data_dir().join("download_history.sqlite3")
+}
+
+// 最後に失敗した yt-dlp の実行（バグレポートに含める）
+pub fn last_failed_command_path() -> PathBuf {
+    app_data_dir().join("last_failed_yt_dlp_command.txt")
+}
+
+// ログを書き出すフォルダ（サイズの上限を超えたら古いファイルへ回す）
+pub fn log_dir() -> PathBuf {
+    app_data_dir().join("logs")
+}
+
+// 選んだ動画から作った低解像度のプロキシを置くフォルダ。元の動画のドライブがなくても確認できるよう、
+// アプリのデータフォルダに置く。
+pub fn proxy_dir() -> PathBuf {
+    app_data_dir().join("proxies")
+}
+
+// ダウンロード時に書き出した動画情報（yt-dlp の info JSON）を、保存したファイルごとに置くフォルダ
+pub fn video_info_dir() -> PathBuf {
+    app_data_dir().join("metadata")
+}
+
+// yt-dlp に渡すログイン情報の一時ファイルを置くフォルダ。保存先は同期されるフォルダのこともあるため、
+// アプリのデータフォルダに置く。
+pub fn login_temp_dir() -> PathBuf {
+    app_data_dir().join("tmp")
+}
+
 pub fn search_index_db_path() -> PathBuf {
     app_data_dir().join("search_index.sqlite3")
 }
 
+// 検索インデックスのバックアップの既定の保存先
+pub fn default_index_backup_dir() -> PathBuf {
+    app_data_dir().join("index_backups")
+}
+
 pub fn make_absolute_path(raw: &str) -> PathBuf {
     let path = PathBuf::from(raw);
     if path.is_absolute() {
@@ -36,8 +93,9 @@ pub fn bin_dir() -> PathBuf {
     app_data_dir().join("bin")
 }
 
-fn resolve_tool_path(file_name: &str) -> PathBuf {
-    let primary = app_data_dir().join("bin").join(file_name);
+fn resolve_tool_path(name: &str) -> PathBuf {
+    let file_name = executable_name(name);
+    let primary = app_data_dir().join("bin").join(&file_name);
     if primary.exists() {
         return primary;
     }
@@ -49,14 +107,51 @@ pub fn yt_dlp_path() -> PathBuf {
     resolve_tool_path("yt-dlp")
 }
 
+// macOS 以外では ffmpeg を同梱しないため、設定画面から取得したものが無ければ PATH 上のものを使う。
+fn resolve_ffmpeg_tool_path(name: &str) -> PathBuf {
+    let path = resolve_tool_path(name);
+    if cfg!(target_os = "macos") || path.exists() {
+        return path;
+    }
+    std::env::var_os("PATH")
+        .and_then(|path_env| {
+            std::env::split_paths(&path_env)
+                .map(|dir| dir.join(executable_name(name)))
+                .find(|candidate| candidate.is_file())
+        })
+        .unwrap_or(path)
+}
+
 pub fn ffmpeg_path() -> PathBuf {
-    resolve_tool_path("ffmpeg")
+    resolve_ffmpeg_tool_path("ffmpeg")
 }
 
 pub fn ffprobe_path() -> PathBuf {
+    resolve_ffmpeg_tool_path("ffprobe")
+}
+
+// 設定画面から取得する ffmpeg/ffprobe の置き場所。PATH 上のものは書き換えない。
+pub fn app_ffmpeg_path() -> PathBuf {
+    resolve_tool_path("ffmpeg")
+}
+
+pub fn app_ffprobe_path() -> PathBuf {
     resolve_tool_path("ffprobe")
 }
 
 pub fn deno_path() -> PathBuf {
     resolve_tool_path("deno")
 }
+
+pub fn aria2c_path() -> PathBuf {
+    resolve_tool_path("aria2c")
+}
+
+// ffmpeg/ffprobe を設定画面から更新済みであることを示す印。同梱版での上書きを止める。
+pub fn ffmpeg_update_marker_path() -> PathBuf {
+    bin_dir().join(".ffmpeg-updated")
+}
+
+pub fn updates_dir() -> PathBuf {
+    app_data_dir().join("updates")
+}
@@ -0,0 +1,328 @@
+// OS ごとに異なる処理（実行権限・プロセス終了・アーカイブ展開・ファイル表示・ツール配布物）をまとめる。
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use crate::i18n::{Msg, tr_fmt};
+
+// OS の実行ファイル拡張子を付けたファイル名を返す（Windows のみ ".exe"）。
+pub fn executable_name(name: &str) -> String {
+    format!("{name}{}", std::env::consts::EXE_SUFFIX)
+}
+
+#[cfg(unix)]
+pub fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::metadata(path)
+        .map(|meta| meta.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+pub fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+// ファイルの実体を表す（デバイス, inode）。ハードリンクやシンボリックリンクで同じ実体に届くかの判定に使う。
+#[cfg(unix)]
+pub fn file_identity(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+pub fn file_identity(_metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+// 中身がまだクラウドにだけある（iCloud Drive・Dropbox などのオンライン専用の）ファイルか。
+// APFS では SF_DATALESS フラグが付く。長さがあるのにディスクのブロックを使っていないファイルも、取得前のスタブとみなす。
+#[cfg(unix)]
+pub fn is_dataless(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    let stub = metadata.is_file() && metadata.len() > 0 && metadata.blocks() == 0;
+    stub || has_dataless_flag(metadata)
+}
+
+#[cfg(not(unix))]
+pub fn is_dataless(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
+#[cfg(target_os = "macos")]
+fn has_dataless_flag(metadata: &fs::Metadata) -> bool {
+    use std::os::macos::fs::MetadataExt;
+
+    // sys/stat.h の SF_DATALESS
+    const SF_DATALESS: u32 = 0x4000_0000;
+    metadata.st_flags() & SF_DATALESS != 0
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn has_dataless_flag(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
+// 実行権限を付与する。権限ビットの無い OS では存在確認のみ行う。
+#[cfg(unix)]
+pub fn ensure_executable(path: &Path) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let metadata = fs::metadata(path).map_err(|err| err.to_string())?;
+    let mut perms = metadata.permissions();
+    let mode = perms.mode();
+    if mode & 0o111 != 0o111 {
+        perms.set_mode(mode | 0o111);
+        fs::set_permissions(path, perms).map_err(|err| err.to_string())?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn ensure_executable(path: &Path) -> Result<(), String> {
+    fs::metadata(path)
+        .map(|_| ())
+        .map_err(|err| err.to_string())
+}
+
+// 子プロセスを自身がリーダーの新しいプロセスグループで起動する。
+// yt-dlp が起動した ffmpeg などの孫プロセスも同じグループに入り、まとめて終了できる。
+#[cfg(unix)]
+pub fn use_own_process_group(command: &mut Command) {
+    use std::os::unix::process::CommandExt;
+
+    command.process_group(0);
+}
+
+// Windows は taskkill /T でプロセスツリーごと終了するため、グループを分けない。
+#[cfg(windows)]
+pub fn use_own_process_group(_command: &mut Command) {}
+
+#[cfg(unix)]
+mod signal {
+    // SIGTERM・SIGKILL の番号は macOS と Linux で共通。
+    pub const SIGTERM: i32 = 15;
+    pub const SIGKILL: i32 = 9;
+
+    unsafe extern "C" {
+        fn killpg(pgrp: i32, sig: i32) -> i32;
+        fn kill(pid: i32, sig: i32) -> i32;
+    }
+
+    // プロセスグループへ送る。グループへ送れない場合だけプロセスへ直接送る。
+    pub fn send(pid: u32, sig: i32) {
+        let Ok(pid) = i32::try_from(pid) else {
+            return;
+        };
+        // 0 以下の番号は自分のグループや全プロセスを指すため送らない。
+        if pid <= 0 {
+            return;
+        }
+        // SAFETY: 引数は整数だけで、失敗しても -1 を返すのみ。
+        unsafe {
+            if killpg(pid, sig) != 0 {
+                kill(pid, sig);
+            }
+        }
+    }
+
+    // プロセスグループにだけ送る。先頭のプロセスは回収済みで番号が使い回されていることがあるため、
+    // プロセスへ直接は送らない。
+    pub fn send_to_group(pgid: u32, sig: i32) {
+        let Ok(pgid) = i32::try_from(pgid) else {
+            return;
+        };
+        if pgid <= 0 {
+            return;
+        }
+        // SAFETY: 引数は整数だけで、失敗しても -1 を返すのみ。
+        unsafe {
+            killpg(pgid, sig);
+        }
+    }
+}
+
+// use_own_process_group で起動した子プロセスに、グループごと終了を促す。
+// 残ったものは呼び出し側が猶予の後に kill_process_groups で強制終了する。
+#[cfg(unix)]
+pub fn terminate_process_groups(pids: &[u32]) {
+    for pid in pids {
+        signal::send(*pid, signal::SIGTERM);
+    }
+}
+
+// terminate_process_groups の後も残ったプロセスを、グループごと強制終了する。
+// 先頭のプロセスが既に終わっていても、グループに残った孫プロセスを止める。
+#[cfg(unix)]
+pub fn kill_process_groups(pgids: &[u32]) {
+    for pgid in pgids {
+        signal::send_to_group(*pgid, signal::SIGKILL);
+    }
+}
+
+#[cfg(windows)]
+pub fn terminate_process_groups(pids: &[u32]) {
+    // /T で子プロセス（yt-dlp が起動した ffmpeg など）もまとめて終了する。
+    for pid in pids {
+        let _ = Command::new("taskkill")
+            .arg("/PID")
+            .arg(pid.to_string())
+            .arg("/T")
+            .arg("/F")
+            .status();
+    }
+}
+
+// taskkill /F で強制終了済みのため、何もしない。
+#[cfg(windows)]
+pub fn kill_process_groups(_pids: &[u32]) {}
+
+// ZIP を dest へ展開する（上書きあり）。Windows は標準の tar（bsdtar）を使う。
+pub fn extract_zip(zip_path: &Path, dest: &Path) -> Result<(), String> {
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("tar");
+        cmd.arg("-xf").arg(zip_path).arg("-C").arg(dest);
+        cmd
+    } else {
+        let mut cmd = Command::new("unzip");
+        cmd.arg("-o").arg(zip_path).arg("-d").arg(dest);
+        cmd
+    };
+    let status = cmd
+        .status()
+        .map_err(|err| tr_fmt(Msg::ExtractCommandFailed, &[&err]))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(status.to_string())
+    }
+}
+
+// dir をフォルダごと ZIP にまとめる。macOS は ditto、Windows は標準の tar（bsdtar）を使う。
+pub fn create_zip(dir: &Path, zip_path: &Path) -> Result<(), String> {
+    let parent = dir.parent().unwrap_or(Path::new("."));
+    let name = dir.file_name().unwrap_or_default();
+    let mut cmd = if cfg!(target_os = "macos") {
+        let mut cmd = Command::new("ditto");
+        cmd.arg("-c")
+            .arg("-k")
+            .arg("--keepParent")
+            .arg(dir)
+            .arg(zip_path);
+        cmd
+    } else if cfg!(windows) {
+        let mut cmd = Command::new("tar");
+        cmd.arg("-a")
+            .arg("-c")
+            .arg("-f")
+            .arg(zip_path)
+            .arg("-C")
+            .arg(parent)
+            .arg(name);
+        cmd
+    } else {
+        let mut cmd = Command::new("zip");
+        cmd.arg("-r")
+            .arg("-q")
+            .arg(zip_path)
+            .arg(name)
+            .current_dir(parent);
+        cmd
+    };
+    let status = cmd
+        .status()
+        .map_err(|err| tr_fmt(Msg::ZipCommandFailed, &[&err]))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(status.to_string())
+    }
+}
+
+// ファイルマネージャ（Finder / エクスプローラー）で対象を選択した状態で表示する。
+pub fn reveal_in_file_manager(path: &Path) -> Result<(), String> {
+    if cfg!(target_os = "macos") {
+        run_opener(Command::new("open").arg("-R").arg(path), "open -R")
+    } else if cfg!(windows) {
+        let mut select = std::ffi::OsString::from("/select,");
+        select.push(path);
+        // explorer は成功時も 1 を返すことがあるため終了コードは見ない。
+        Command::new("explorer")
+            .arg(select)
+            .status()
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+    } else {
+        let target = path.parent().unwrap_or(path);
+        run_opener(Command::new("xdg-open").arg(target), "xdg-open")
+    }
+}
+
+// 既定のアプリでファイルを開く（macOS で dmg ならマウントされる）。
+pub fn open_with_default_app(path: &Path) -> Result<(), String> {
+    if cfg!(target_os = "macos") {
+        run_opener(Command::new("open").arg(path), "open")
+    } else if cfg!(windows) {
+        Command::new("explorer")
+            .arg(path)
+            .status()
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+    } else {
+        run_opener(Command::new("xdg-open").arg(path), "xdg-open")
+    }
+}
+
+fn run_opener(cmd: &mut Command, label: &str) -> Result<(), String> {
+    let status = cmd.status().map_err(|err| err.to_string())?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(tr_fmt(Msg::OpenerFailed, &[&label, &status]))
+    }
+}
+
+// yt-dlp のリリースに含まれる、実行中の OS 向けの単体バイナリ名
+pub fn yt_dlp_asset_name() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "yt-dlp_macos"
+    } else if cfg!(windows) {
+        "yt-dlp.exe"
+    } else if cfg!(target_arch = "aarch64") {
+        "yt-dlp_linux_aarch64"
+    } else {
+        "yt-dlp_linux"
+    }
+}
+
+// deno のリリースアセット名に使うターゲット（例: aarch64-apple-darwin）
+pub fn deno_target() -> String {
+    let os = if cfg!(target_os = "macos") {
+        "apple-darwin"
+    } else if cfg!(windows) {
+        "pc-windows-msvc"
+    } else {
+        "unknown-linux-gnu"
+    };
+    format!("{}-{os}", std::env::consts::ARCH)
+}
+
+// ffmpeg の静的ビルドを取得する際の OS/CPU 名。配布元が無い OS では None を返す。
+pub fn ffmpeg_release_platform() -> Option<(&'static str, &'static str)> {
+    let os = if cfg!(target_os = "macos") {
+        "macos"
+    } else if cfg!(target_os = "linux") {
+        "linux"
+    } else {
+        return None;
+    };
+    let arch = if cfg!(target_arch = "aarch64") {
+        "arm64"
+    } else {
+        "amd64"
+    };
+    Some((os, arch))
+}
@@ -0,0 +1,121 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::i18n::{Msg, tr};
+use crate::search_index::read_duration_ms;
+
+pub const DEFAULT_PLAYLIST_NAME: &str = "VJDownloader.m3u8";
+
+// 書き出す形式。拡張子が .txt ならパスを1行ずつ並べたファイル一覧、それ以外は拡張M3U。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlaylistFormat {
+    M3u8,
+    FileList,
+}
+
+impl PlaylistFormat {
+    pub fn from_path(path: &Path) -> Self {
+        let is_text = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("txt"));
+        if is_text {
+            PlaylistFormat::FileList
+        } else {
+            PlaylistFormat::M3u8
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlaylistEntry {
+    pub path: PathBuf,
+    pub duration_ms: Option<i64>,
+}
+
+// 一覧の並び順のまま、各ファイルの再生時間を読み取ってエントリを作る。
+pub fn collect_entries(paths: &[PathBuf]) -> Vec<PlaylistEntry> {
+    paths
+        .iter()
+        .map(|path| PlaylistEntry {
+            path: path.clone(),
+            duration_ms: read_duration_ms(path),
+        })
+        .collect()
+}
+
+pub fn render_playlist(entries: &[PlaylistEntry], format: PlaylistFormat) -> String {
+    let mut out = String::new();
+    if format == PlaylistFormat::M3u8 {
+        out.push_str("#EXTM3U\n");
+    }
+    for entry in entries {
+        if format == PlaylistFormat::M3u8 {
+            // 再生時間が不明な場合は -1 を書く（M3U の慣例）。
+            let seconds = entry.duration_ms.map(|ms| (ms + 500) / 1_000).unwrap_or(-1);
+            let title = entry
+                .path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().replace(['\r', '\n'], " "))
+                .unwrap_or_default();
+            out.push_str(&format!("#EXTINF:{seconds},{title}\n"));
+        }
+        out.push_str(&entry.path.to_string_lossy());
+        out.push('\n');
+    }
+    out
+}
+
+// 拡張子から形式を決めてプレイリストを書き出し、書き出した件数を返す。
+pub fn write_playlist(dest: &Path, paths: &[PathBuf]) -> Result<usize, String> {
+    if paths.is_empty() {
+        return Err(tr(Msg::NothingToExport).to_string());
+    }
+    let entries = collect_entries(paths);
+    let contents = render_playlist(&entries, PlaylistFormat::from_path(dest));
+    fs::write(dest, contents).map_err(|err| err.to_string())?;
+    Ok(entries.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_extended_m3u_in_order() {
+        let entries = vec![
+            PlaylistEntry {
+                path: PathBuf::from("/clips/b.mp4"),
+                duration_ms: Some(12_400),
+            },
+            PlaylistEntry {
+                path: PathBuf::from("/clips/a.mp4"),
+                duration_ms: None,
+            },
+        ];
+        assert_eq!(
+            render_playlist(&entries, PlaylistFormat::M3u8),
+            "#EXTM3U\n#EXTINF:12,b\n/clips/b.mp4\n#EXTINF:-1,a\n/clips/a.mp4\n"
+        );
+        assert_eq!(
+            render_playlist(&entries, PlaylistFormat::FileList),
+            "/clips/b.mp4\n/clips/a.mp4\n"
+        );
+    }
+
+    #[test]
+    fn picks_format_from_extension() {
+        assert_eq!(
+            PlaylistFormat::from_path(Path::new("/tmp/set.TXT")),
+            PlaylistFormat::FileList
+        );
+        assert_eq!(
+            PlaylistFormat::from_path(Path::new("/tmp/set.m3u8")),
+            PlaylistFormat::M3u8
+        );
+        assert_eq!(
+            PlaylistFormat::from_path(Path::new("/tmp/set")),
+            PlaylistFormat::M3u8
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use eframe::egui;
+
+const MONITOR_INTERVAL: Duration = Duration::from_secs(30);
+
+// 最後に確認した電源の状態。確認前や確かめられない環境では電源につながっているとみなす。
+static ON_BATTERY: AtomicBool = AtomicBool::new(false);
+static MONITOR_STARTED: AtomicBool = AtomicBool::new(false);
+
+// 今の電源がバッテリーかを確認し、結果を記録する。
+pub fn check_on_battery() -> bool {
+    let on_battery = read_power_source().is_some_and(|output| is_battery_source(&output));
+    ON_BATTERY.store(on_battery, Ordering::Relaxed);
+    on_battery
+}
+
+// 直近の確認結果を返す（UI スレッドから呼んでもブロックしない）。
+pub fn last_known_on_battery() -> bool {
+    ON_BATTERY.load(Ordering::Relaxed)
+}
+
+// 電源の状態を定期的に確認し、変化したら再描画を要求する。
+pub fn start_power_monitor(ctx: &egui::Context) {
+    if MONITOR_STARTED.swap(true, Ordering::Relaxed) {
+        return;
+    }
+    let ctx = ctx.clone();
+    thread::spawn(move || {
+        loop {
+            let before = last_known_on_battery();
+            if check_on_battery() != before {
+                ctx.request_repaint();
+            }
+            thread::sleep(MONITOR_INTERVAL);
+        }
+    });
+}
+
+// `pmset -g batt` の出力。1 行目が "Now drawing from 'Battery Power'" のようになる。
+#[cfg(target_os = "macos")]
+fn read_power_source() -> Option<String> {
+    let output = std::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn read_power_source() -> Option<String> {
+    None
+}
+
+fn is_battery_source(output: &str) -> bool {
+    output
+        .lines()
+        .next()
+        .is_some_and(|line| line.contains("'Battery Power'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_power_source_from_pmset_output() {
+        assert!(is_battery_source(
+            "Now drawing from 'Battery Power'\n -InternalBattery-0 (id=1234)\t82%; discharging; 4:10 remaining present: true\n"
+        ));
+        assert!(!is_battery_source(
+            "Now drawing from 'AC Power'\n -InternalBattery-0 (id=1234)\t100%; charged; 0:00 remaining present: true\n"
+        ));
+        // バッテリーの無い Mac では 2 行目が無い。
+        assert!(!is_battery_source("Now drawing from 'AC Power'\n"));
+        assert!(!is_battery_source(""));
+    }
+}
@@ -0,0 +1,124 @@
+use eframe::egui;
+
+use crate::app::DownloaderApp;
+use crate::cursor::pointing;
+use crate::download::{LONG_DURATION_SECONDS, MetadataPreview};
+use crate::i18n::{Msg, tr, tr_fmt};
+
+pub struct PreviewPromptState {
+    pub preview: MetadataPreview,
+    texture: Option<egui::TextureHandle>,
+}
+
+impl PreviewPromptState {
+    pub fn new(preview: MetadataPreview) -> Self {
+        Self {
+            preview,
+            texture: None,
+        }
+    }
+
+    // サムネイルは最初の描画時にテクスチャへ読み込む。取得できなかった場合は None。
+    fn texture(&mut self, ctx: &egui::Context) -> Option<&egui::TextureHandle> {
+        let (size, rgba) = self.preview.thumbnail.as_ref()?;
+        Some(self.texture.get_or_insert_with(|| {
+            let image = egui::ColorImage::from_rgba_unmultiplied(*size, rgba);
+            ctx.load_texture("metadata-preview", image, egui::TextureOptions::LINEAR)
+        }))
+    }
+}
+
+pub fn render_preview_prompt(
+    // ダウンロード前の確認待ちを持つアプリ
+    app: &mut DownloaderApp,
+    // ウィンドウ表示とテクスチャ読み込みに使うコンテキスト
+    ctx: &egui::Context,
+) {
+    let Some(state) = app.preview_prompt.as_mut() else {
+        return;
+    };
+
+    let mut open = true;
+    let mut answer = None;
+    egui::Window::new(tr(Msg::ConfirmDownload))
+        .collapsible(false)
+        .resizable(false)
+        .default_width(360.0)
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .open(&mut open)
+        .show(ctx, |ui| {
+            if let Some(texture) = state.texture(ctx) {
+                let size = texture.size_vec2();
+                ui.image((texture.id(), size));
+                ui.add_space(8.0);
+            }
+
+            let preview = &state.preview;
+            let title = if preview.title.is_empty() {
+                tr(Msg::UnknownTitle)
+            } else {
+                preview.title.as_str()
+            };
+            ui.label(
+                egui::RichText::new(title)
+                    .size(13.0)
+                    .color(egui::Color32::from_rgb(220, 230, 245)),
+            );
+            if !preview.uploader.is_empty() {
+                ui.label(
+                    egui::RichText::new(&preview.uploader)
+                        .size(11.5)
+                        .color(egui::Color32::from_rgb(140, 150, 170)),
+                );
+            }
+            // 長い動画は変換にも時間がかかるため、長さを目立たせる。
+            let long = preview
+                .duration_seconds
+                .is_some_and(|seconds| seconds >= LONG_DURATION_SECONDS);
+            let duration = match preview.duration_label() {
+                Some(label) if long => tr_fmt(Msg::DurationLong, &[&label]),
+                Some(label) => tr_fmt(Msg::Duration, &[&label]),
+                None => tr(Msg::DurationUnknown).to_string(),
+            };
+            ui.label(egui::RichText::new(duration).size(11.5).color(if long {
+                egui::Color32::from_rgb(251, 191, 36)
+            } else {
+                egui::Color32::from_rgb(140, 150, 170)
+            }));
+
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let download_btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::Download))
+                            .size(12.5)
+                            .color(egui::Color32::from_rgb(8, 14, 24)),
+                    )
+                    .fill(egui::Color32::from_rgb(16, 190, 255));
+                    if pointing(ui.add(download_btn)).clicked() {
+                        answer = Some(true);
+                    }
+
+                    let cancel_btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::DontDownload))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(180, 190, 210)),
+                    )
+                    .fill(egui::Color32::from_rgb(24, 30, 45));
+                    if pointing(ui.add(cancel_btn)).clicked() {
+                        answer = Some(false);
+                    }
+                });
+            });
+        });
+
+    // 閉じた場合はダウンロードしない。
+    if !open {
+        answer = Some(false);
+    }
+    if let Some(download) = answer
+        && let Some(state) = app.preview_prompt.take()
+    {
+        state.preview.respond(download);
+    }
+}
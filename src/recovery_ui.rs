@@ -0,0 +1,102 @@
+use eframe::egui;
+
+use crate::app::DownloaderApp;
+use crate::cursor::pointing;
+use crate::i18n::{Msg, tr, tr_fmt};
+
+// 中断したダウンロードをどうするか
+enum RecoveryAnswer {
+    Resume,
+    Retry,
+    Discard,
+}
+
+pub fn render_interrupted_job(
+    // 前回中断したダウンロードを持つアプリ
+    app: &mut DownloaderApp,
+    // ウィンドウ表示に使うコンテキスト
+    ctx: &egui::Context,
+) {
+    let Some(job) = app.interrupted_job.as_ref() else {
+        return;
+    };
+
+    let mut open = true;
+    let mut answer = None;
+    egui::Window::new(tr(Msg::InterruptedDownload))
+        .collapsible(false)
+        .resizable(false)
+        .default_width(420.0)
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .open(&mut open)
+        .show(ctx, |ui| {
+            ui.label(
+                egui::RichText::new(tr_fmt(Msg::InterruptedMessage, &[&job.stage.label()]))
+                    .size(11.5)
+                    .color(egui::Color32::from_rgb(140, 150, 170)),
+            );
+            ui.add_space(6.0);
+            ui.label(
+                egui::RichText::new(&job.url)
+                    .size(12.0)
+                    .color(egui::Color32::from_rgb(220, 230, 245)),
+            );
+            ui.label(
+                egui::RichText::new(tr_fmt(Msg::SaveToPath, &[&job.output_dir.display()]))
+                    .size(11.0)
+                    .color(egui::Color32::from_rgb(140, 150, 170)),
+            );
+
+            ui.add_space(10.0);
+            let can_resume = job.can_resume();
+            ui.horizontal(|ui| {
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let resume_btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::Resume))
+                            .size(12.5)
+                            .color(egui::Color32::from_rgb(8, 14, 24)),
+                    )
+                    .fill(egui::Color32::from_rgb(16, 190, 255));
+                    if pointing(ui.add_enabled(can_resume, resume_btn)).clicked() {
+                        answer = Some(RecoveryAnswer::Resume);
+                    }
+
+                    let retry_btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::RestartFromScratch))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(180, 190, 210)),
+                    )
+                    .fill(egui::Color32::from_rgb(24, 30, 45));
+                    if pointing(ui.add(retry_btn)).clicked() {
+                        answer = Some(RecoveryAnswer::Retry);
+                    }
+
+                    let discard_btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::Discard))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(180, 190, 210)),
+                    )
+                    .fill(egui::Color32::from_rgb(24, 30, 45));
+                    if pointing(ui.add(discard_btn)).clicked() {
+                        answer = Some(RecoveryAnswer::Discard);
+                    }
+                });
+            });
+        });
+
+    // 閉じた場合は破棄する。
+    if !open {
+        answer = Some(RecoveryAnswer::Discard);
+    }
+    let Some(answer) = answer else {
+        return;
+    };
+    let Some(job) = app.interrupted_job.take() else {
+        return;
+    };
+    match answer {
+        RecoveryAnswer::Resume => app.recover_interrupted_job(job, true),
+        RecoveryAnswer::Retry => app.recover_interrupted_job(job, false),
+        RecoveryAnswer::Discard => app.discard_interrupted_job(job),
+    }
+}
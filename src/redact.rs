@@ -0,0 +1,169 @@
+// ログやバグレポートに残さない値を伏せ字にする。
+
+pub const REDACTED: &str = "<redacted>";
+// 直後（または = の後）の値を伏せる yt-dlp のオプション
+const SECRET_OPTIONS: [&str; 8] = [
+    "--username",
+    "--password",
+    "--video-password",
+    "--ap-username",
+    "--ap-password",
+    "--cookies",
+    "--cookies-from-browser",
+    "--add-header",
+];
+// この後ろは行末まで伏せる（yt-dlp -v の辞書表示 'Cookie': ... も含む）
+const SECRET_HEADERS: [&str; 6] = [
+    "cookie:",
+    "cookie':",
+    "cookie\":",
+    "authorization:",
+    "authorization':",
+    "authorization\":",
+];
+// 名前にこれを含むクエリの値を伏せる（署名付き URL の sig・token・Key-Pair-Id など）
+const SECRET_QUERY_PARTS: [&str; 10] = [
+    "sig",
+    "token",
+    "key",
+    "auth",
+    "credential",
+    "policy",
+    "hmac",
+    "session",
+    "pass",
+    "secret",
+];
+// 名前がこれと一致するクエリの値を伏せる（googlevideo の接続元 IP や PO トークンなど）
+const SECRET_QUERY_NAMES: [&str; 4] = ["ip", "pot", "expire", "expires"];
+
+// ユーザー名・パスワード・クッキー・追加ヘッダーの値と、URL の署名などを伏せた引数を返す。
+pub fn redact_args(args: &[String]) -> Vec<String> {
+    let mut redacted = Vec::with_capacity(args.len());
+    let mut secret_next = false;
+    for arg in args {
+        if secret_next {
+            redacted.push(REDACTED.to_string());
+            secret_next = false;
+            continue;
+        }
+        if let Some((option, _)) = arg.split_once('=')
+            && SECRET_OPTIONS.contains(&option)
+        {
+            redacted.push(format!("{option}={REDACTED}"));
+            continue;
+        }
+        secret_next = SECRET_OPTIONS.contains(&arg.as_str());
+        redacted.push(redact_urls(arg));
+    }
+    redacted
+}
+
+// ログの 1 行から、クッキーや認証のヘッダー、秘密のオプションの値、URL の署名などを伏せる。
+pub fn redact_line(line: &str) -> String {
+    let lower = line.to_ascii_lowercase();
+    let header_end = SECRET_HEADERS
+        .iter()
+        .filter_map(|header| lower.find(header).map(|pos| pos + header.len()))
+        .min();
+    let (line, header_redacted) = match header_end {
+        Some(end) => (&line[..end], true),
+        None => (line, false),
+    };
+
+    let mut secret_next = false;
+    let words = line
+        .split(' ')
+        .map(|word| {
+            // yt-dlp -v の引数一覧は ['--password', '...'] のように囲まれるため、前後の記号は残す。
+            let is_quote = |ch: char| "'\"[],".contains(ch);
+            let bare = word.trim_matches(is_quote);
+            let prefix = &word[..word.len() - word.trim_start_matches(is_quote).len()];
+            let suffix = &word[word.trim_end_matches(is_quote).len().max(prefix.len())..];
+            if secret_next && !bare.is_empty() {
+                secret_next = false;
+                return format!("{prefix}{REDACTED}{suffix}");
+            }
+            if SECRET_OPTIONS.contains(&bare) {
+                secret_next = true;
+                return word.to_string();
+            }
+            if let Some((option, _)) = bare.split_once('=')
+                && SECRET_OPTIONS.contains(&option)
+            {
+                return format!("{prefix}{option}={REDACTED}{suffix}");
+            }
+            redact_urls(word)
+        })
+        .collect::<Vec<_>>();
+    let mut redacted = words.join(" ");
+    if header_redacted {
+        redacted.push(' ');
+        redacted.push_str(REDACTED);
+    }
+    redacted
+}
+
+// 空白を含まない文字列の中の URL について、秘密のクエリの値を伏せる。
+fn redact_urls(text: &str) -> String {
+    let Some(start) = text.find("http://").or_else(|| text.find("https://")) else {
+        return text.to_string();
+    };
+    let Some(query_start) = text[start..].find('?').map(|pos| start + pos + 1) else {
+        return text.to_string();
+    };
+    // クォートや括弧で閉じている場合は、その手前までをクエリとみなす。
+    let query_end = text[query_start..]
+        .find(['#', '\'', '"', ')', ']', ','])
+        .map_or(text.len(), |pos| query_start + pos);
+    let query = text[query_start..query_end]
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((key, _)) if is_secret_query_key(key) => format!("{key}={REDACTED}"),
+            _ => pair.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+    format!("{}{query}{}", &text[..query_start], &text[query_end..])
+}
+
+fn is_secret_query_key(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    SECRET_QUERY_NAMES.contains(&key.as_str())
+        || SECRET_QUERY_PARTS.iter().any(|part| key.contains(part))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_secrets_in_log_lines() {
+        assert_eq!(
+            redact_line(
+                "[download] Destination: https://rr1.googlevideo.com/videoplayback?expire=1700000000&ip=203.0.113.5&itag=137&sig=AOq0QJ8&pot=Mn4x#t=1"
+            ),
+            "[download] Destination: https://rr1.googlevideo.com/videoplayback?expire=<redacted>&ip=<redacted>&itag=137&sig=<redacted>&pot=<redacted>#t=1"
+        );
+        assert_eq!(
+            redact_line("https://www.youtube.com/watch?v=abc&list=PL1"),
+            "https://www.youtube.com/watch?v=abc&list=PL1"
+        );
+        assert_eq!(
+            redact_line(
+                "[debug] Command-line config: ['--username', 'vj', '--password=hunter2', 'https://x.test/a?token=t1']"
+            ),
+            "[debug] Command-line config: ['--username', '<redacted>', '--password=<redacted>', 'https://x.test/a?token=<redacted>']"
+        );
+        assert_eq!(
+            redact_line(
+                "[debug] HTTP header: {'User-Agent': 'curl', 'Cookie': 'SID=abc; HSID=def'}"
+            ),
+            "[debug] HTTP header: {'User-Agent': 'curl', 'Cookie': <redacted>"
+        );
+        assert_eq!(
+            redact_line("< set-cookie: SID=abc; Path=/"),
+            "< set-cookie: <redacted>"
+        );
+    }
+}
@@ -1,30 +1,104 @@
+mod backup;
 mod db;
+mod mp4;
 mod normalize;
 mod query;
 mod scanner;
+mod skip;
+mod syntax;
 mod watcher;
 mod writer;
 
 use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, RwLock};
 use std::thread;
 use std::time::{Duration, Instant};
 
-use db::{apply_migrations, open_connection};
-use normalize::{escape_like_pattern, normalize_query, normalize_root_path, path_to_key};
-use query::{QueryPattern, run_search_query};
-use scanner::scan_root;
+use crate::i18n::{Msg, tr_fmt};
+
+use backup::{backup_file_name, prune_backups, verify_backup, write_backup};
+use db::{apply_migrations, check_integrity, open_connection};
+use normalize::{
+    epoch_millis, epoch_secs, escape_like_pattern, normalize_path_key, normalize_query,
+    normalize_root_path, normalize_tags, path_to_key,
+};
+use query::{QueryPattern, cursor_file_name_norm, run_search_query};
+use scanner::{build_record_from_path, find_root_id_for_path, scan_root};
+use syntax::apply_query_syntax;
 use watcher::watcher_loop;
 use writer::writer_loop;
 
-const DB_SCHEMA_VERSION: i32 = 1;
+pub use backup::{backup_due, list_backups};
+pub(crate) use mp4::{Mp4Layout, read_duration_ms, read_mp4_layout};
+pub use normalize::INDEXED_EXTENSION;
+pub use skip::SkipRules;
+
+const DB_SCHEMA_VERSION: i32 = 8;
 const DEBOUNCE_WINDOW: Duration = Duration::from_millis(700);
 const UPSERT_BATCH_SIZE: usize = 256;
 const MAX_SEARCH_LIMIT: usize = 1_000;
 
+// インデックス時に Finder タグを読み取るか。設定から切り替える。
+static READ_FINDER_TAGS: AtomicBool = AtomicBool::new(false);
+
+pub fn set_read_finder_tags(enabled: bool) {
+    READ_FINDER_TAGS.store(enabled, Ordering::Relaxed);
+}
+
+pub fn reads_finder_tags() -> bool {
+    READ_FINDER_TAGS.load(Ordering::Relaxed)
+}
+
+// バッテリー駆動中の省電力。読み取りを 1 スレッドにし、自動の全走査は電源につなぐまで待たせる。
+static LOW_POWER: AtomicBool = AtomicBool::new(false);
+// 省電力中に見送った全走査があるか
+static DEFERRED_REINDEX: AtomicBool = AtomicBool::new(false);
+
+// 省電力を解除した時に、見送った全走査があれば true を返す（呼び出し側で再インデックスする）。
+pub fn set_low_power(enabled: bool) -> bool {
+    LOW_POWER.store(enabled, Ordering::Relaxed);
+    !enabled && DEFERRED_REINDEX.swap(false, Ordering::Relaxed)
+}
+
+pub(crate) fn low_power() -> bool {
+    LOW_POWER.load(Ordering::Relaxed)
+}
+
+// 省電力中なら全走査を見送って true を返す。
+fn defer_reindex_on_low_power() -> bool {
+    if !low_power() {
+        return false;
+    }
+    DEFERRED_REINDEX.store(true, Ordering::Relaxed);
+    true
+}
+
+// 走査と監視で除外する項目の決まり。設定から切り替える。
+static SKIP_RULES: RwLock<SkipRules> = RwLock::new(SkipRules::DEFAULT);
+
+// 決まりが変わった場合に true を返す（呼び出し側で再インデックスする）。
+pub fn set_skip_rules(rules: SkipRules) -> bool {
+    let Ok(mut current) = SKIP_RULES.write() else {
+        return false;
+    };
+    if *current == rules {
+        return false;
+    }
+    *current = rules;
+    true
+}
+
+pub(crate) fn skip_rules() -> SkipRules {
+    SKIP_RULES
+        .read()
+        .map(|rules| rules.clone())
+        .unwrap_or_default()
+}
+
 pub type EngineResult<T> = Result<T, String>;
 
 #[derive(Clone, Copy, Debug, Default)]
@@ -37,6 +111,10 @@ pub enum SearchSort {
 #[derive(Clone, Debug)]
 pub struct SearchRequest {
     pub query: String,
+    pub terms: Vec<String>,
+    pub exclude_terms: Vec<String>,
+    pub extensions: Vec<String>,
+    pub tags: Vec<String>,
     pub root_id: Option<i64>,
     pub root_path: Option<String>,
     pub parent_dir: Option<String>,
@@ -44,14 +122,21 @@ pub struct SearchRequest {
     pub modified_before: Option<i64>,
     pub size_min: Option<i64>,
     pub size_max: Option<i64>,
+    pub duration_min_ms: Option<i64>,
+    pub duration_max_ms: Option<i64>,
     pub limit: usize,
     pub sort: SearchSort,
+    pub after: Option<SearchCursor>,
 }
 
 impl Default for SearchRequest {
     fn default() -> Self {
         Self {
             query: String::new(),
+            terms: Vec::new(),
+            exclude_terms: Vec::new(),
+            extensions: Vec::new(),
+            tags: Vec::new(),
             root_id: None,
             root_path: None,
             parent_dir: None,
@@ -59,12 +144,33 @@ impl Default for SearchRequest {
             modified_before: None,
             size_min: None,
             size_max: None,
+            duration_min_ms: None,
+            duration_max_ms: None,
             limit: 100,
             sort: SearchSort::ModifiedDesc,
+            after: None,
         }
     }
 }
 
+impl SearchRequest {
+    // 検索ボックスの入力（演算子付き）から検索条件を組み立てる。
+    pub fn from_query_text(input: &str) -> Self {
+        let offset = time::UtcOffset::current_local_offset().unwrap_or(time::UtcOffset::UTC);
+        let mut request = Self::default();
+        apply_query_syntax(input, &mut request, offset);
+        request
+    }
+
+    // `ext:` で指定された拡張子のうち、インデックスに載らないため絶対に一致しないもの。
+    pub fn unindexed_extension(&self) -> Option<&str> {
+        self.extensions
+            .iter()
+            .map(|ext| ext.trim_start_matches('.'))
+            .find(|ext| !ext.eq_ignore_ascii_case(INDEXED_EXTENSION))
+    }
+}
+
 #[derive(Clone, Debug)]
 #[allow(dead_code)]
 pub struct SearchHit {
@@ -74,6 +180,32 @@ pub struct SearchHit {
     pub modified_time: i64,
     pub root_id: i64,
     pub parent_dir: String,
+    // MP4 の長さ。読めなかったファイルは None。
+    pub duration_ms: Option<i64>,
+    // 中身がクラウドにだけある（オンライン専用の）ファイルか
+    pub is_cloud: bool,
+}
+
+// keyset ページングの位置。直前ページ最終行の (modified_time, path) を保持する。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SearchCursor {
+    pub modified_time: i64,
+    pub path: String,
+}
+
+impl SearchCursor {
+    fn from_hit(hit: &SearchHit) -> Self {
+        Self {
+            modified_time: hit.modified_time,
+            path: hit.path.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SearchPage {
+    pub hits: Vec<SearchHit>,
+    pub next_cursor: Option<SearchCursor>,
 }
 
 #[derive(Clone, Debug)]
@@ -81,6 +213,7 @@ pub struct RootEntry {
     pub root_id: i64,
     pub root_path: String,
     pub is_enabled: bool,
+    pub follow_links: bool,
     #[allow(dead_code)]
     pub last_scan_time: Option<i64>,
 }
@@ -94,12 +227,15 @@ struct EngineInner {
     db_path: PathBuf,
     write_tx: Sender<WriteCommand>,
     watcher_tx: Sender<WatcherMessage>,
+    // DB へ書き込むたびに進む番号。読み直しが必要かの判定に使う。
+    changes: Arc<AtomicU64>,
 }
 
 #[derive(Debug)]
 enum WriteCommand {
     AddOrEnableRoot {
         root_path: String,
+        follow_links: bool,
         resp: Sender<EngineResult<i64>>,
     },
     RemoveRoot {
@@ -109,6 +245,19 @@ enum WriteCommand {
     UpsertFiles {
         files: Vec<FileRecord>,
     },
+    SetTags {
+        path: String,
+        tags_norm: String,
+    },
+    LinkProxy {
+        original_path: String,
+        proxy_path: String,
+        created_time: i64,
+    },
+    RestoreBackup {
+        path: PathBuf,
+        resp: Sender<EngineResult<()>>,
+    },
     DeletePaths {
         paths: Vec<String>,
     },
@@ -125,14 +274,29 @@ enum WriteCommand {
 
 #[derive(Clone, Debug)]
 struct FileRecord {
+    // path と parent_dir はファイルシステムが返したまま（開く・移動に使う）、*_norm は NFC に揃えた比較用
     path: String,
+    path_norm: String,
     root_id: i64,
     file_name: String,
     file_name_norm: String,
     parent_dir: String,
+    parent_dir_norm: String,
     size_bytes: i64,
     modified_time: i64,
     created_time: Option<i64>,
+    duration_ms: Option<i64>,
+    tags_norm: Option<String>,
+    // シンボリックリンクを解決した実体のパス。同じ実体を別のパスで二重に登録しないために使う。
+    canonical_path: Option<String>,
+    // 中身がクラウドにだけあるか。この場合は読み込むとダウンロードが始まるため、長さを読まない。
+    is_cloud: bool,
+    // ダウンロード時に保存した動画情報（info JSON）の項目。ダウンロード以外で追加したファイルは None。
+    uploader: Option<String>,
+    upload_date: Option<String>,
+    original_title: Option<String>,
+    // 投稿者名と元のタイトルを正規化して改行でつないだもの。通常の検索語はファイル名と合わせてこれも探す。
+    meta_norm: String,
     last_indexed_time: i64,
 }
 
@@ -140,6 +304,8 @@ struct FileRecord {
 struct WatchedRoot {
     root_id: i64,
     root_path: PathBuf,
+    // ルート内のシンボリックリンク（フォルダ・ファイル）をたどるか
+    follow_links: bool,
 }
 
 #[derive(Debug)]
@@ -168,7 +334,9 @@ impl SearchEngine {
 
         let (write_tx, write_rx) = mpsc::channel();
         let db_for_writer = db_path.clone();
-        thread::spawn(move || writer_loop(db_for_writer, write_rx));
+        let changes = Arc::new(AtomicU64::new(0));
+        let writer_changes = changes.clone();
+        thread::spawn(move || writer_loop(db_for_writer, write_rx, writer_changes));
 
         let (watcher_tx, watcher_rx) = mpsc::channel();
         let watcher_write_tx = write_tx.clone();
@@ -180,6 +348,7 @@ impl SearchEngine {
                 db_path,
                 write_tx,
                 watcher_tx,
+                changes,
             }),
         };
 
@@ -192,7 +361,7 @@ impl SearchEngine {
         let conn = open_connection(&self.inner.db_path)?;
         let mut stmt = conn
             .prepare(
-                "SELECT root_id, root_path, is_enabled, last_scan_time
+                "SELECT root_id, root_path, is_enabled, follow_links, last_scan_time
                  FROM roots
                  ORDER BY root_path COLLATE NOCASE ASC",
             )
@@ -203,7 +372,8 @@ impl SearchEngine {
                     root_id: row.get(0)?,
                     root_path: row.get(1)?,
                     is_enabled: row.get::<_, i64>(2)? != 0,
-                    last_scan_time: row.get(3)?,
+                    follow_links: row.get::<_, i64>(3)? != 0,
+                    last_scan_time: row.get(4)?,
                 })
             })
             .map_err(|err| err.to_string())?;
@@ -216,16 +386,26 @@ impl SearchEngine {
     }
 
     // desired ルート集合と DB の差分を同期し、必要な full scan を起動する。
-    pub fn sync_roots(&self, desired_paths: &[PathBuf]) -> EngineResult<()> {
+    // follow_links に含まれるルートはシンボリックリンクをたどる。扱いが変わったルートは走査し直す。
+    pub fn sync_roots(
+        &self,
+        desired_paths: &[PathBuf],
+        follow_links: &[PathBuf],
+    ) -> EngineResult<()> {
         let mut normalized_paths = Vec::new();
         let mut dedup = HashSet::new();
+        let follow_keys: HashSet<String> = follow_links
+            .iter()
+            .filter_map(|path| normalize_root_path(path).ok())
+            .map(|path| path_to_key(&path))
+            .collect();
 
         for path in desired_paths {
             let normalized = normalize_root_path(path)?;
             if !normalized.is_dir() {
-                return Err(format!(
-                    "検索対象フォルダが存在しないか、ディレクトリではありません: {}",
-                    normalized.to_string_lossy()
+                return Err(tr_fmt(
+                    Msg::SearchRootInvalid,
+                    &[&normalized.to_string_lossy()],
                 ));
             }
             let key = path_to_key(&normalized);
@@ -247,10 +427,17 @@ impl SearchEngine {
             .collect();
 
         for (path, key) in &normalized_paths {
-            let added_now = !current_map.contains_key(key);
-            let root_id = self.add_or_enable_root(key)?;
-            if added_now {
-                self.start_full_scan(root_id, path.clone());
+            let follow_links = follow_keys.contains(key);
+            let needs_scan = current_map
+                .get(key)
+                .is_none_or(|entry| entry.follow_links != follow_links);
+            let root_id = self.add_or_enable_root(key, follow_links)?;
+            if needs_scan {
+                self.start_full_scan(WatchedRoot {
+                    root_id,
+                    root_path: path.clone(),
+                    follow_links,
+                });
             }
         }
 
@@ -266,53 +453,249 @@ impl SearchEngine {
 
     // 有効ルートすべてに対して再インデックスを非同期起動する。
     pub fn reindex_all_async(&self) -> EngineResult<()> {
-        let roots = self.list_roots()?;
-        for root in roots.into_iter().filter(|root| root.is_enabled) {
-            self.start_full_scan(root.root_id, PathBuf::from(root.root_path));
+        for root in self.enabled_watched_roots()? {
+            self.start_full_scan(root);
         }
         Ok(())
     }
 
+    // 起動時など急がない再インデックス。省電力中は見送り、電源につないだ時に行う。
+    pub fn reindex_all_when_powered(&self) -> EngineResult<()> {
+        if defer_reindex_on_low_power() {
+            return Ok(());
+        }
+        self.reindex_all_async()
+    }
+
+    // 有効ルートにあるインデックス済みファイルのパスをすべて返す。
+    pub fn indexed_paths(&self) -> EngineResult<Vec<PathBuf>> {
+        let conn = open_connection(&self.inner.db_path)?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT files.path
+                 FROM files
+                 JOIN roots ON roots.root_id = files.root_id
+                 WHERE roots.is_enabled = 1
+                 ORDER BY files.path ASC",
+            )
+            .map_err(|err| err.to_string())?;
+        let rows = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(|err| err.to_string())?;
+
+        let mut paths = Vec::new();
+        for row in rows {
+            paths.push(PathBuf::from(row.map_err(|err| err.to_string())?));
+        }
+        Ok(paths)
+    }
+
+    // DB の変更番号。前回読んだ時から変わっていれば、一覧を読み直す。
+    pub fn change_seq(&self) -> u64 {
+        self.inner.changes.load(Ordering::Acquire)
+    }
+
+    // フォルダ直下のインデックス済みファイルを新しい順に返す。有効なルートに含まれないフォルダは None。
+    pub fn files_in_dir(&self, dir: &Path) -> EngineResult<Option<Vec<SearchHit>>> {
+        // ファイルの親フォルダのキーには末尾の区切り文字が無いため、ルートと同じ形にそろえてから比べる。
+        let dir = normalize_root_path(dir)?;
+        let covered = self
+            .list_roots()?
+            .iter()
+            .any(|root| root.is_enabled && dir.starts_with(&root.root_path));
+        if !covered {
+            return Ok(None);
+        }
+        let conn = open_connection(&self.inner.db_path)?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT path, file_name, size_bytes, modified_time, root_id, parent_dir, duration_ms,
+                        is_cloud
+                 FROM files
+                 WHERE parent_dir_norm = ?
+                 ORDER BY modified_time DESC, path ASC",
+            )
+            .map_err(|err| err.to_string())?;
+        let rows = stmt
+            .query_map([normalize_path_key(&dir)], |row| {
+                Ok(SearchHit {
+                    path: row.get(0)?,
+                    file_name: row.get(1)?,
+                    size_bytes: row.get(2)?,
+                    modified_time: row.get(3)?,
+                    root_id: row.get(4)?,
+                    parent_dir: row.get(5)?,
+                    duration_ms: row.get(6)?,
+                    is_cloud: row.get::<_, i64>(7)? != 0,
+                })
+            })
+            .map_err(|err| err.to_string())?;
+
+        let mut hits = Vec::new();
+        for row in rows {
+            hits.push(row.map_err(|err| err.to_string())?);
+        }
+        Ok(Some(hits))
+    }
+
     // クエリを正規化し、prefix -> contains の順で段階検索する。
-    pub fn search(&self, request: &SearchRequest) -> EngineResult<Vec<SearchHit>> {
+    // request.after があればその続きから取得し、続きがある場合は next_cursor を返す。
+    pub fn search(&self, request: &SearchRequest) -> EngineResult<SearchPage> {
         let conn = open_connection(&self.inner.db_path)?;
         let limit = request.limit.clamp(1, MAX_SEARCH_LIMIT);
+        // 1 件多く取得して次ページの有無を判定する。
+        let fetch_limit = limit + 1;
         let normalized_query = normalize_query(&request.query);
+        let after = request.after.as_ref();
+
+        let mut hits = if normalized_query.is_empty() {
+            run_search_query(&conn, request, None, after, fetch_limit)?
+        } else {
+            let escaped = escape_like_pattern(&normalized_query);
+            let prefix_pattern = format!("{escaped}%");
+            let contains_pattern = format!("%{escaped}%");
+
+            // カーソルが contains 段階の行を指している場合は prefix 段階を読み飛ばす。
+            let in_prefix_stage = after
+                .map(|cursor| cursor_file_name_norm(cursor).starts_with(&normalized_query))
+                .unwrap_or(true);
+
+            let mut hits = Vec::new();
+            if in_prefix_stage {
+                hits = run_search_query(
+                    &conn,
+                    request,
+                    Some(QueryPattern::Prefix {
+                        pattern: prefix_pattern.clone(),
+                        exact: normalized_query.clone(),
+                    }),
+                    after,
+                    fetch_limit,
+                )?;
+            }
+
+            if hits.len() < fetch_limit {
+                let remain = fetch_limit - hits.len();
+                let contains_after = if in_prefix_stage { None } else { after };
+                let mut contains_hits = run_search_query(
+                    &conn,
+                    request,
+                    Some(QueryPattern::Contains {
+                        pattern: contains_pattern,
+                        prefix_pattern,
+                    }),
+                    contains_after,
+                    remain,
+                )?;
+                hits.append(&mut contains_hits);
+            }
+            hits
+        };
 
-        if normalized_query.is_empty() {
-            return run_search_query(&conn, request, None, limit);
+        let next_cursor = if hits.len() > limit {
+            hits.truncate(limit);
+            hits.last().map(SearchCursor::from_hit)
+        } else {
+            None
+        };
+        Ok(SearchPage { hits, next_cursor })
+    }
+
+    // 指定したファイルを読み直して DB へ反映する。クラウドから取得した後など、監視では届かない変化を取り込む。
+    pub fn reindex_files(&self, paths: &[PathBuf]) -> EngineResult<()> {
+        let roots = self.enabled_watched_roots()?;
+        let marker = epoch_millis();
+        let files = paths
+            .iter()
+            .filter_map(|path| {
+                let root_id = find_root_id_for_path(path, &roots)?;
+                build_record_from_path(root_id, path, marker)
+            })
+            .collect::<Vec<_>>();
+        if files.is_empty() {
+            return Ok(());
         }
+        self.inner
+            .write_tx
+            .send(WriteCommand::UpsertFiles { files })
+            .map_err(|err| err.to_string())
+    }
+
+    // アプリ内で編集したタグを DB に反映し、tag: 検索へすぐ反映させる。
+    pub fn set_file_tags(&self, path: &std::path::Path, tags: &[String]) -> EngineResult<()> {
+        self.inner
+            .write_tx
+            .send(WriteCommand::SetTags {
+                path: path_to_key(path),
+                tags_norm: normalize_tags(tags),
+            })
+            .map_err(|err| err.to_string())
+    }
 
-        let escaped = escape_like_pattern(&normalized_query);
-        let prefix_pattern = format!("{escaped}%");
-        let contains_pattern = format!("%{escaped}%");
+    // アプリで作ったプロキシを元の動画に紐付ける。同じ動画のプロキシを作り直した場合は置き換える。
+    pub fn link_proxy(&self, original: &Path, proxy: &Path) -> EngineResult<()> {
+        self.inner
+            .write_tx
+            .send(WriteCommand::LinkProxy {
+                original_path: path_to_key(original),
+                proxy_path: path_to_key(proxy),
+                created_time: epoch_secs(),
+            })
+            .map_err(|err| err.to_string())
+    }
 
-        let mut hits = run_search_query(
-            &conn,
-            request,
-            Some(QueryPattern::Prefix {
-                pattern: prefix_pattern.clone(),
-                exact: normalized_query.clone(),
-            }),
-            limit,
-        )?;
+    // 元の動画からプロキシへの対応をすべて返す。プロキシのファイルが消えたものは含めない。
+    pub fn proxy_links(&self) -> EngineResult<HashMap<PathBuf, PathBuf>> {
+        let conn = open_connection(&self.inner.db_path)?;
+        let mut stmt = conn
+            .prepare("SELECT original_path, proxy_path FROM proxies")
+            .map_err(|err| err.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(|err| err.to_string())?;
 
-        if hits.len() >= limit {
-            return Ok(hits);
+        let mut links = HashMap::new();
+        for row in rows {
+            let (original, proxy) = row.map_err(|err| err.to_string())?;
+            let proxy = PathBuf::from(proxy);
+            if proxy.is_file() {
+                links.insert(PathBuf::from(original), proxy);
+            }
         }
+        Ok(links)
+    }
 
-        let remain = limit - hits.len();
-        let mut contains_hits = run_search_query(
-            &conn,
-            request,
-            Some(QueryPattern::Contains {
-                pattern: contains_pattern,
-                prefix_pattern,
-            }),
-            remain,
-        )?;
-        hits.append(&mut contains_hits);
-        Ok(hits)
+    // DB が壊れていないかを SQLite の integrity_check で確かめる。
+    pub fn check_integrity(&self) -> EngineResult<()> {
+        let conn = open_connection(&self.inner.db_path)?;
+        check_integrity(&conn)
+    }
+
+    // DB をバックアップのフォルダへ書き出し、新しいものから keep 個を残す。書き出したファイルを返す。
+    pub fn backup_into(&self, dir: &Path, keep: usize) -> EngineResult<PathBuf> {
+        fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+        let dest = dir.join(backup_file_name(crate::file_list::local_now()));
+        write_backup(&self.inner.db_path, &dest)?;
+        prune_backups(dir, keep);
+        Ok(dest)
+    }
+
+    // バックアップの内容で DB を置き換える。壊れたバックアップは使わない。
+    // 置き換えた後のファイルの変化は、呼び出し側で全体を再インデックスして取り込む。
+    pub fn restore_from(&self, path: &Path) -> EngineResult<()> {
+        verify_backup(path)?;
+        let (tx, rx) = mpsc::channel();
+        self.inner
+            .write_tx
+            .send(WriteCommand::RestoreBackup {
+                path: path.to_path_buf(),
+                resp: tx,
+            })
+            .map_err(|err| err.to_string())?;
+        rx.recv().map_err(|err| err.to_string())??;
+        self.refresh_watcher_roots()
     }
 
     #[cfg(test)]
@@ -331,12 +714,13 @@ impl SearchEngine {
         Ok(())
     }
 
-    fn add_or_enable_root(&self, root_path: &str) -> EngineResult<i64> {
+    fn add_or_enable_root(&self, root_path: &str, follow_links: bool) -> EngineResult<i64> {
         let (tx, rx) = mpsc::channel();
         self.inner
             .write_tx
             .send(WriteCommand::AddOrEnableRoot {
                 root_path: root_path.to_string(),
+                follow_links,
                 resp: tx,
             })
             .map_err(|err| err.to_string())?;
@@ -369,18 +753,19 @@ impl SearchEngine {
             .map(|root| WatchedRoot {
                 root_id: root.root_id,
                 root_path: PathBuf::from(root.root_path),
+                follow_links: root.follow_links,
             })
             .collect())
     }
 
     // ルート単位の full scan をバックグラウンドで起動する。
-    fn start_full_scan(&self, root_id: i64, root_path: PathBuf) {
+    fn start_full_scan(&self, root: WatchedRoot) {
         let write_tx = self.inner.write_tx.clone();
         thread::spawn(move || {
-            if let Err(err) = scan_root(root_id, &root_path, &write_tx) {
+            if let Err(err) = scan_root(&root, &write_tx) {
                 eprintln!(
                     "[search-index] full scan failed for {}: {}",
-                    root_path.to_string_lossy(),
+                    root.root_path.to_string_lossy(),
                     err
                 );
             }
@@ -398,6 +783,7 @@ impl Drop for EngineInner {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rusqlite::params;
     use tempfile::tempdir;
 
     fn write_dummy(path: &std::path::Path, bytes: usize) {
@@ -405,6 +791,24 @@ mod tests {
         fs::write(path, data).expect("write dummy file");
     }
 
+    fn write_mp4_with_duration(path: &std::path::Path, timescale: u32, duration: u32) {
+        let mut mvhd = Vec::new();
+        mvhd.extend_from_slice(&28_u32.to_be_bytes());
+        mvhd.extend_from_slice(b"mvhd");
+        mvhd.extend_from_slice(&[0_u8; 12]);
+        mvhd.extend_from_slice(&timescale.to_be_bytes());
+        mvhd.extend_from_slice(&duration.to_be_bytes());
+
+        let mut data = Vec::new();
+        data.extend_from_slice(&16_u32.to_be_bytes());
+        data.extend_from_slice(b"ftypisom");
+        data.extend_from_slice(&[0_u8; 4]);
+        data.extend_from_slice(&(8 + mvhd.len() as u32).to_be_bytes());
+        data.extend_from_slice(b"moov");
+        data.extend_from_slice(&mvhd);
+        fs::write(path, data).expect("write mp4 file");
+    }
+
     fn setup_engine() -> (tempfile::TempDir, SearchEngine) {
         let dir = tempdir().expect("tempdir");
         let db_path = dir.path().join("index.db");
@@ -428,7 +832,7 @@ mod tests {
         write_dummy(&root.join("会議録画_2026.mp4"), 64);
         write_dummy(&root.join("ignore.txt"), 64);
 
-        engine.sync_roots(&[root.clone()]).expect("sync roots");
+        engine.sync_roots(&[root.clone()], &[]).expect("sync roots");
         engine.reindex_all_async().expect("reindex all");
         thread::sleep(Duration::from_millis(350));
 
@@ -438,12 +842,50 @@ mod tests {
                 limit: 20,
                 ..Default::default()
             })
-            .expect("search by japanese");
+            .expect("search by japanese")
+            .hits;
 
         assert_eq!(hits.len(), 1);
         assert!(hits[0].file_name.contains("旅行_沖縄"));
     }
 
+    #[test]
+    fn matches_decomposed_names_with_composed_query() {
+        let (temp, engine) = setup_engine();
+        // Finder から作ったフォルダとファイルの名前は NFD（た + 結合用濁点）で返ってくる。
+        let root = temp.path().join("videos");
+        let dir = root.join("\u{305F}\u{3099}んす");
+        fs::create_dir_all(&dir).expect("create dir");
+        write_dummy(&dir.join("\u{305F}\u{3099}んすループ.mp4"), 64);
+
+        engine.sync_roots(&[root.clone()], &[]).expect("sync roots");
+        engine.reindex_all_async().expect("reindex all");
+        thread::sleep(Duration::from_millis(350));
+
+        let composed_dir = root.join("\u{3060}んす");
+        let hits = engine
+            .search(&SearchRequest {
+                query: "\u{3060}んす".to_string(),
+                parent_dir: Some(path_to_key(&composed_dir)),
+                limit: 20,
+                ..Default::default()
+            })
+            .expect("search with composed query")
+            .hits;
+        assert_eq!(hits.len(), 1);
+        // 開く・移動に使うパスはファイルシステムが返したまま残す。
+        assert_eq!(
+            hits[0].path,
+            path_to_key(&dir.join("\u{305F}\u{3099}んすループ.mp4"))
+        );
+
+        let files = engine
+            .files_in_dir(&composed_dir)
+            .expect("list dir")
+            .expect("root covers dir");
+        assert_eq!(files.len(), 1);
+    }
+
     #[test]
     fn supports_metadata_filters() {
         let (temp, engine) = setup_engine();
@@ -453,7 +895,7 @@ mod tests {
         write_dummy(&root.join("small.mp4"), 8);
         write_dummy(&root.join("large.mp4"), 8_192);
 
-        engine.sync_roots(&[root.clone()]).expect("sync roots");
+        engine.sync_roots(&[root.clone()], &[]).expect("sync roots");
         engine.reindex_all_async().expect("reindex all");
         thread::sleep(Duration::from_millis(350));
 
@@ -464,19 +906,65 @@ mod tests {
                 limit: 20,
                 ..Default::default()
             })
-            .expect("search by size");
+            .expect("search by size")
+            .hits;
 
         assert_eq!(hits.len(), 1);
         assert_eq!(hits[0].file_name, "large.mp4");
     }
 
+    #[test]
+    fn matches_uploader_from_video_info() {
+        let (temp, engine) = setup_engine();
+        let root = temp.path().join("videos");
+        fs::create_dir_all(&root).expect("create root");
+
+        write_dummy(&root.join("loop_01.mp4"), 64);
+        write_dummy(&root.join("loop_02.mp4"), 64);
+
+        engine.sync_roots(&[root.clone()], &[]).expect("sync roots");
+        engine.reindex_all_async().expect("reindex all");
+        thread::sleep(Duration::from_millis(350));
+
+        // ダウンロード時の動画情報から取り込んだ投稿者名と元のタイトル
+        let conn = open_connection(&engine.inner.db_path).expect("open db");
+        conn.execute(
+            "UPDATE files SET meta_norm = ? WHERE file_name = ?",
+            params!["vj loops\nneon tunnel", "loop_01.mp4"],
+        )
+        .expect("set video info");
+
+        let hits = engine
+            .search(&SearchRequest {
+                query: "Loops".to_string(),
+                limit: 20,
+                ..Default::default()
+            })
+            .expect("search by uploader")
+            .hits;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].file_name, "loop_01.mp4");
+
+        let hits = engine
+            .search(&SearchRequest {
+                query: "loop".to_string(),
+                exclude_terms: vec!["neon".to_string()],
+                limit: 20,
+                ..Default::default()
+            })
+            .expect("exclude by original title")
+            .hits;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].file_name, "loop_02.mp4");
+    }
+
     #[test]
     fn applies_add_delete_rename_updates() {
         let (temp, engine) = setup_engine();
         let root = temp.path().join("videos");
         fs::create_dir_all(&root).expect("create root");
 
-        engine.sync_roots(&[root.clone()]).expect("sync roots");
+        engine.sync_roots(&[root.clone()], &[]).expect("sync roots");
         thread::sleep(Duration::from_millis(200));
 
         let added = root.join("追加.mp4");
@@ -492,7 +980,8 @@ mod tests {
                 limit: 20,
                 ..Default::default()
             })
-            .expect("search after add");
+            .expect("search after add")
+            .hits;
         assert_eq!(hits.len(), 1);
 
         let renamed = root.join("変更後.mp4");
@@ -508,7 +997,8 @@ mod tests {
                 limit: 20,
                 ..Default::default()
             })
-            .expect("search renamed");
+            .expect("search renamed")
+            .hits;
         assert_eq!(hits.len(), 1);
 
         fs::remove_file(&renamed).expect("remove file");
@@ -523,10 +1013,49 @@ mod tests {
                 limit: 20,
                 ..Default::default()
             })
-            .expect("search after delete");
+            .expect("search after delete")
+            .hits;
         assert!(hits.is_empty());
     }
 
+    #[test]
+    fn lists_files_directly_in_dir() {
+        let (temp, engine) = setup_engine();
+        let root = temp.path().join("videos");
+        fs::create_dir_all(root.join("sub")).expect("create root");
+
+        write_dummy(&root.join("clip.mp4"), 32);
+        write_dummy(&root.join("sub").join("nested.mp4"), 32);
+        assert!(
+            engine
+                .files_in_dir(&root)
+                .expect("list before sync")
+                .is_none()
+        );
+
+        let before = engine.change_seq();
+        engine.sync_roots(&[root.clone()], &[]).expect("sync roots");
+        engine.reindex_all_async().expect("reindex all");
+        thread::sleep(Duration::from_millis(350));
+
+        assert!(engine.change_seq() > before);
+        let files = engine
+            .files_in_dir(&root)
+            .expect("list dir")
+            .expect("root covers dir");
+        let paths = files.iter().map(|hit| hit.path.clone()).collect::<Vec<_>>();
+        assert_eq!(paths, vec![path_to_key(&root.join("clip.mp4"))]);
+
+        // 保存先を末尾の区切り文字付きで設定した場合も、同じ一覧になる。
+        let with_slash = PathBuf::from(format!("{}/", root.to_string_lossy()));
+        let files = engine
+            .files_in_dir(&with_slash)
+            .expect("list dir with trailing slash")
+            .expect("root covers dir with trailing slash");
+        let paths = files.iter().map(|hit| hit.path.clone()).collect::<Vec<_>>();
+        assert_eq!(paths, vec![path_to_key(&root.join("clip.mp4"))]);
+    }
+
     #[test]
     fn searches_literal_percent_and_underscore() {
         let (temp, engine) = setup_engine();
@@ -534,7 +1063,7 @@ mod tests {
         fs::create_dir_all(&root).expect("create root");
 
         write_dummy(&root.join("100%_test.mp4"), 64);
-        engine.sync_roots(&[root.clone()]).expect("sync roots");
+        engine.sync_roots(&[root.clone()], &[]).expect("sync roots");
         engine.reindex_all_async().expect("reindex all");
         thread::sleep(Duration::from_millis(350));
 
@@ -544,8 +1073,213 @@ mod tests {
                 limit: 20,
                 ..Default::default()
             })
-            .expect("search escaped");
+            .expect("search escaped")
+            .hits;
         assert_eq!(hits.len(), 1);
         assert_eq!(hits[0].file_name, "100%_test.mp4");
     }
+
+    #[test]
+    fn paginates_with_keyset_cursor_across_stages() {
+        let (temp, engine) = setup_engine();
+        let root = temp.path().join("videos");
+        fs::create_dir_all(&root).expect("create root");
+
+        for name in [
+            "loop_a.mp4",
+            "loop_b.mp4",
+            "loop_c.mp4",
+            "x_loop_d.mp4",
+            "y_loop_e.mp4",
+        ] {
+            write_dummy(&root.join(name), 16);
+        }
+        engine.sync_roots(&[root], &[]).expect("sync roots");
+        engine.reindex_all_async().expect("reindex all");
+        thread::sleep(Duration::from_millis(350));
+
+        let mut request = SearchRequest {
+            query: "loop".to_string(),
+            limit: 2,
+            sort: SearchSort::NameAsc,
+            ..Default::default()
+        };
+        let mut names = Vec::new();
+        loop {
+            let page = engine.search(&request).expect("search page");
+            assert!(page.hits.len() <= 2);
+            names.extend(page.hits.into_iter().map(|hit| hit.file_name));
+            match page.next_cursor {
+                Some(cursor) => request.after = Some(cursor),
+                None => break,
+            }
+        }
+
+        assert_eq!(
+            names,
+            vec![
+                "loop_a.mp4",
+                "loop_b.mp4",
+                "loop_c.mp4",
+                "x_loop_d.mp4",
+                "y_loop_e.mp4"
+            ]
+        );
+    }
+
+    #[test]
+    fn filters_with_query_syntax_operators() {
+        let (temp, engine) = setup_engine();
+        let root = temp.path().join("videos");
+        fs::create_dir_all(&root).expect("create root");
+
+        write_mp4_with_duration(&root.join("neon loop short.mp4"), 1_000, 12_000);
+        write_mp4_with_duration(&root.join("neon loop long.mp4"), 600, 60_000);
+        write_mp4_with_duration(&root.join("neon loop draft.mp4"), 1_000, 5_000);
+        write_dummy(&root.join("neon loop unknown.mp4"), 64);
+        engine.sync_roots(&[root], &[]).expect("sync roots");
+        engine.reindex_all_async().expect("reindex all");
+        thread::sleep(Duration::from_millis(350));
+
+        let search = |text: &str| {
+            let request = SearchRequest {
+                limit: 20,
+                sort: SearchSort::NameAsc,
+                ..SearchRequest::from_query_text(text)
+            };
+            let mut names = engine
+                .search(&request)
+                .expect("search with operators")
+                .hits
+                .into_iter()
+                .map(|hit| hit.file_name)
+                .collect::<Vec<_>>();
+            names.sort();
+            names
+        };
+
+        assert_eq!(
+            search("\"neon loop\" -draft dur<30s"),
+            vec!["neon loop short.mp4"]
+        );
+        assert_eq!(search("loop dur>=100s"), vec!["neon loop long.mp4"]);
+        assert_eq!(search("neon size>60"), vec!["neon loop unknown.mp4"]);
+        assert!(search("neon ext:mov").is_empty());
+        assert_eq!(search("unknown ext:mp4").len(), 1);
+    }
+
+    #[test]
+    fn filters_by_tags_set_from_app() {
+        let (temp, engine) = setup_engine();
+        let root = temp.path().join("videos");
+        fs::create_dir_all(&root).expect("create root");
+
+        let intro = root.join("intro.mp4");
+        write_dummy(&intro, 16);
+        write_dummy(&root.join("outro.mp4"), 16);
+        engine.sync_roots(&[root], &[]).expect("sync roots");
+        engine.reindex_all_async().expect("reindex all");
+        thread::sleep(Duration::from_millis(350));
+
+        let tags = vec!["Live Set".to_string(), "★★★".to_string()];
+        engine.set_file_tags(&intro, &tags).expect("set tags");
+        thread::sleep(Duration::from_millis(120));
+
+        let search = |tags: &[&str]| {
+            engine
+                .search(&SearchRequest {
+                    tags: tags.iter().map(|tag| tag.to_string()).collect(),
+                    limit: 20,
+                    ..Default::default()
+                })
+                .expect("search by tag")
+                .hits
+                .into_iter()
+                .map(|hit| hit.file_name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(search(&["live set", "★★★"]), vec!["intro.mp4"]);
+        assert!(search(&["live"]).is_empty());
+    }
+
+    #[test]
+    fn restores_tags_from_backup() {
+        let (temp, engine) = setup_engine();
+        let root = temp.path().join("videos");
+        fs::create_dir_all(&root).expect("create root");
+        let clip = root.join("clip.mp4");
+        write_dummy(&clip, 16);
+        engine.sync_roots(&[root], &[]).expect("sync roots");
+        engine.reindex_all_async().expect("reindex all");
+        thread::sleep(Duration::from_millis(350));
+        engine
+            .set_file_tags(&clip, &["keep".to_string()])
+            .expect("set tags");
+        thread::sleep(Duration::from_millis(120));
+
+        let backups = temp.path().join("backups");
+        let backup = engine.backup_into(&backups, 3).expect("backup");
+        assert_eq!(list_backups(&backups), vec![backup.clone()]);
+
+        engine
+            .set_file_tags(&clip, &["lost".to_string()])
+            .expect("overwrite tags");
+        thread::sleep(Duration::from_millis(120));
+        engine.restore_from(&backup).expect("restore");
+
+        let tagged = |tag: &str| {
+            engine
+                .search(&SearchRequest {
+                    tags: vec![tag.to_string()],
+                    limit: 20,
+                    ..Default::default()
+                })
+                .expect("search by tag")
+                .hits
+                .len()
+        };
+        assert_eq!(tagged("keep"), 1);
+        assert_eq!(tagged("lost"), 0);
+    }
+
+    #[test]
+    fn reports_fresh_index_as_healthy() {
+        let (_temp, engine) = setup_engine();
+        engine.check_integrity().expect("integrity check");
+    }
+
+    #[test]
+    fn links_proxies_to_originals() {
+        let (temp, engine) = setup_engine();
+        let original = temp.path().join("ssd").join("tunnel.mov");
+        let proxy = temp.path().join("proxies").join("tunnel 480p.mp4");
+        let replaced = temp.path().join("proxies").join("tunnel 480p (1).mp4");
+        let missing = temp.path().join("proxies").join("gone 480p.mp4");
+        fs::create_dir_all(proxy.parent().unwrap()).expect("create proxy dir");
+        write_dummy(&proxy, 16);
+        write_dummy(&replaced, 16);
+
+        engine.link_proxy(&original, &proxy).expect("link proxy");
+        engine
+            .link_proxy(&original, &replaced)
+            .expect("relink proxy");
+        engine
+            .link_proxy(&temp.path().join("gone.mov"), &missing)
+            .expect("link missing proxy");
+        thread::sleep(Duration::from_millis(120));
+
+        let links = engine.proxy_links().expect("proxy links");
+        assert_eq!(links.len(), 1);
+        assert_eq!(links.get(&original), Some(&replaced));
+    }
+
+    #[test]
+    fn defers_reindex_until_power_returns() {
+        let (_temp, engine) = setup_engine();
+        assert!(!set_low_power(true));
+        engine.reindex_all_when_powered().expect("deferred reindex");
+        assert!(set_low_power(false));
+        // 見送った全走査は 1 回だけ知らせる。
+        assert!(!set_low_power(false));
+    }
 }
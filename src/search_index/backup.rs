@@ -0,0 +1,154 @@
+use rusqlite::{Connection, MAIN_DB, OpenFlags};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use time::OffsetDateTime;
+use time::macros::format_description;
+
+use super::EngineResult;
+use super::db::{check_integrity, open_connection};
+
+const BACKUP_PREFIX: &str = "search_index-";
+const BACKUP_EXTENSION: &str = "sqlite3";
+
+// バックアップのファイル名。名前順が作成順になるよう日時を先頭から並べる。
+pub(super) fn backup_file_name(now: OffsetDateTime) -> String {
+    let stamp = now
+        .format(format_description!(
+            "[year][month][day]-[hour][minute][second]"
+        ))
+        .unwrap_or_default();
+    format!("{BACKUP_PREFIX}{stamp}.{BACKUP_EXTENSION}")
+}
+
+fn is_backup_file(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    name.starts_with(BACKUP_PREFIX) && name.ends_with(&format!(".{BACKUP_EXTENSION}"))
+}
+
+// フォルダ内のバックアップを新しい順に返す。
+pub fn list_backups(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut backups = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && is_backup_file(path))
+        .collect::<Vec<_>>();
+    backups.sort();
+    backups.reverse();
+    backups
+}
+
+// 最後のバックアップから interval 以上たっていれば true。まだ 1 つもない場合も true。
+pub fn backup_due(dir: &Path, interval: Duration, now: SystemTime) -> bool {
+    let latest = list_backups(dir)
+        .first()
+        .and_then(|path| fs::metadata(path).ok())
+        .and_then(|meta| meta.modified().ok());
+    match latest {
+        Some(modified) => now
+            .duration_since(modified)
+            .is_ok_and(|elapsed| elapsed >= interval),
+        None => true,
+    }
+}
+
+// 新しいものから keep 個を残し、古いバックアップを消す。
+pub(super) fn prune_backups(dir: &Path, keep: usize) {
+    for old in list_backups(dir).into_iter().skip(keep) {
+        let _ = fs::remove_file(old);
+    }
+}
+
+// SQLite のバックアップ API で、使用中の DB を一貫した状態のまま dest へ書き出す。
+// 書き出し途中のファイルが残らないよう、一時ファイルへ書いてから名前を変える。
+pub(super) fn write_backup(db_path: &Path, dest: &Path) -> EngineResult<()> {
+    let partial = dest.with_extension("partial");
+    let conn = open_connection(db_path)?;
+    let result = conn
+        .backup(MAIN_DB, &partial, None)
+        .map_err(|err| err.to_string())
+        .and_then(|()| fs::rename(&partial, dest).map_err(|err| err.to_string()));
+    if result.is_err() {
+        let _ = fs::remove_file(&partial);
+    }
+    result
+}
+
+// 復元する前に、バックアップが壊れていないかを確かめる。
+pub(super) fn verify_backup(path: &Path) -> EngineResult<()> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|err| err.to_string())?;
+    check_integrity(&conn)
+}
+
+// バックアップの内容で DB を置き換える。書き込みスレッドの接続から行う。
+pub(super) fn restore_backup(conn: &mut Connection, src: &Path) -> EngineResult<()> {
+    conn.restore(MAIN_DB, src, None::<fn(rusqlite::backup::Progress)>)
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    use time::macros::datetime;
+
+    #[test]
+    fn names_backups_in_sortable_order() {
+        assert_eq!(
+            backup_file_name(datetime!(2026-10-17 09:05:03 UTC)),
+            "search_index-20261017-090503.sqlite3"
+        );
+    }
+
+    #[test]
+    fn keeps_only_newest_backups() {
+        let dir = tempdir().expect("tempdir");
+        for stamp in ["20261015-000000", "20261016-000000", "20261017-000000"] {
+            fs::write(
+                dir.path().join(format!("search_index-{stamp}.sqlite3")),
+                b"",
+            )
+            .expect("write backup");
+        }
+        fs::write(dir.path().join("notes.txt"), b"").expect("write other file");
+
+        prune_backups(dir.path(), 2);
+        let names = list_backups(dir.path())
+            .into_iter()
+            .filter_map(|path| {
+                path.file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                "search_index-20261017-000000.sqlite3",
+                "search_index-20261016-000000.sqlite3",
+            ]
+        );
+        assert!(dir.path().join("notes.txt").exists());
+    }
+
+    #[test]
+    fn backup_is_due_without_recent_backup() {
+        let dir = tempdir().expect("tempdir");
+        let day = Duration::from_secs(24 * 60 * 60);
+        assert!(backup_due(dir.path(), day, SystemTime::now()));
+
+        fs::write(
+            dir.path().join(backup_file_name(OffsetDateTime::now_utc())),
+            b"",
+        )
+        .expect("write backup");
+        assert!(!backup_due(dir.path(), day, SystemTime::now()));
+        assert!(backup_due(dir.path(), day, SystemTime::now() + day));
+    }
+}
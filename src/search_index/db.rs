@@ -1,7 +1,8 @@
-use rusqlite::Connection;
+use rusqlite::{Connection, params};
 use std::path::Path;
 use std::time::Duration;
 
+use super::normalize::{normalize_for_search, normalize_path_key};
 use super::{DB_SCHEMA_VERSION, EngineResult};
 
 // SQLite 接続を開き、検索用途向け PRAGMA を適用する。
@@ -18,7 +19,100 @@ pub(super) fn open_connection(path: &Path) -> EngineResult<Connection> {
     Ok(conn)
 }
 
-// スキーマバージョンを確認し、必要な初期テーブル/インデックスを作成する。
+// PRAGMA integrity_check が ok を返さない場合は、その結果（壊れている箇所）をエラーとして返す。
+pub(super) fn check_integrity(conn: &Connection) -> EngineResult<()> {
+    let status: String = conn
+        .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+        .map_err(|err| err.to_string())?;
+    if status != "ok" {
+        return Err(status);
+    }
+    Ok(())
+}
+
+// スキーマの変更 1 つ分。version はこの変更を当てた後の user_version。
+struct Migration {
+    version: i32,
+    description: &'static str,
+    apply: fn(&Connection) -> rusqlite::Result<()>,
+}
+
+// 古い順に並べる。列やテーブルを足す場合は、末尾に次の番号で加えて DB_SCHEMA_VERSION を上げる。
+// 一度リリースした変更は書き換えない（既存の DB には当て直さないため）。
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "create roots and files tables",
+        apply: create_initial_tables,
+    },
+    Migration {
+        version: 2,
+        description: "add files.duration_ms",
+        // 再生時間（ミリ秒）。既存行は次回スキャンまで NULL のまま。
+        apply: |conn| conn.execute_batch("ALTER TABLE files ADD COLUMN duration_ms INTEGER;"),
+    },
+    Migration {
+        version: 3,
+        description: "add files.tags_norm",
+        // Finder タグ（正規化済み、改行区切り）。既存行は NULL のまま。
+        apply: |conn| conn.execute_batch("ALTER TABLE files ADD COLUMN tags_norm TEXT;"),
+    },
+    Migration {
+        version: 4,
+        description: "create proxies table",
+        // 元の動画と、アプリで作った低解像度のプロキシの対応。元の動画ごとに最新の 1 件を持つ。
+        apply: |conn| {
+            conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS proxies (
+                    original_path TEXT PRIMARY KEY,
+                    proxy_path TEXT NOT NULL,
+                    created_time INTEGER NOT NULL
+                );",
+            )
+        },
+    },
+    Migration {
+        version: 5,
+        description: "add NFC-normalized path columns",
+        apply: add_normalized_paths,
+    },
+    Migration {
+        version: 6,
+        description: "add roots.follow_links and files.canonical_path",
+        // ルートごとのシンボリックリンクの扱いと、リンクをたどった先の実体のパス。既存行は次回スキャンまで NULL のまま。
+        apply: |conn| {
+            conn.execute_batch(
+                "ALTER TABLE roots ADD COLUMN follow_links INTEGER NOT NULL DEFAULT 0;
+                ALTER TABLE files ADD COLUMN canonical_path TEXT;
+                CREATE INDEX IF NOT EXISTS idx_files_canonical_path ON files(canonical_path);",
+            )
+        },
+    },
+    Migration {
+        version: 7,
+        description: "add files.is_cloud",
+        // 中身がクラウドにだけある（オンライン専用の）ファイルか。既存行は次回スキャンまで 0 のまま。
+        apply: |conn| {
+            conn.execute_batch("ALTER TABLE files ADD COLUMN is_cloud INTEGER NOT NULL DEFAULT 0;")
+        },
+    },
+    Migration {
+        version: 8,
+        description: "add video info columns to files",
+        // ダウンロード時に保存した info JSON の投稿者名・投稿日・元のタイトル。既存行は次回スキャンまで空のまま。
+        apply: |conn| {
+            conn.execute_batch(
+                "ALTER TABLE files ADD COLUMN uploader TEXT;
+                ALTER TABLE files ADD COLUMN upload_date TEXT;
+                ALTER TABLE files ADD COLUMN original_title TEXT;
+                ALTER TABLE files ADD COLUMN meta_norm TEXT NOT NULL DEFAULT '';",
+            )
+        },
+    },
+];
+
+// スキーマバージョンを確認し、まだ当てていない変更を古い順に当てる。
+// 変更は 1 つずつトランザクションで行い、失敗した場合はその変更の前の状態に戻して止める。
 pub(super) fn apply_migrations(conn: &Connection) -> EngineResult<()> {
     let version: i32 = conn
         .pragma_query_value(None, "user_version", |row| row.get(0))
@@ -30,40 +124,169 @@ pub(super) fn apply_migrations(conn: &Connection) -> EngineResult<()> {
         ));
     }
 
-    if version == 0 {
+    for migration in MIGRATIONS.iter().filter(|step| step.version > version) {
+        run_migration(conn, migration).map_err(|err| {
+            format!(
+                "DB schema migration to version {} ({}) failed: {err}",
+                migration.version, migration.description
+            )
+        })?;
+        eprintln!(
+            "[search-index] migrated DB schema to version {}: {}",
+            migration.version, migration.description
+        );
+    }
+    Ok(())
+}
+
+fn run_migration(conn: &Connection, migration: &Migration) -> rusqlite::Result<()> {
+    // 途中で失敗した場合は、コミットせずに捨てることで元に戻す。
+    let tx = conn.unchecked_transaction()?;
+    (migration.apply)(&tx)?;
+    tx.pragma_update(None, "user_version", migration.version)?;
+    tx.commit()
+}
+
+fn create_initial_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS roots (
+            root_id INTEGER PRIMARY KEY AUTOINCREMENT,
+            root_path TEXT NOT NULL UNIQUE,
+            is_enabled INTEGER NOT NULL DEFAULT 1,
+            last_scan_time INTEGER
+        );
+
+        CREATE TABLE IF NOT EXISTS files (
+            path TEXT PRIMARY KEY,
+            root_id INTEGER NOT NULL,
+            file_name TEXT NOT NULL,
+            file_name_norm TEXT NOT NULL,
+            parent_dir TEXT NOT NULL,
+            size_bytes INTEGER NOT NULL,
+            modified_time INTEGER NOT NULL,
+            created_time INTEGER,
+            last_indexed_time INTEGER NOT NULL,
+            FOREIGN KEY(root_id) REFERENCES roots(root_id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_files_root_id ON files(root_id);
+        CREATE INDEX IF NOT EXISTS idx_files_parent_dir ON files(parent_dir);
+        CREATE INDEX IF NOT EXISTS idx_files_file_name_norm ON files(file_name_norm);
+        CREATE INDEX IF NOT EXISTS idx_files_modified_time ON files(modified_time);
+        CREATE INDEX IF NOT EXISTS idx_files_size_bytes ON files(size_bytes);",
+    )
+}
+
+// NFC に揃えたパス。既存行はここで埋め、file_name_norm も同じ規則で作り直す。
+fn add_normalized_paths(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE files ADD COLUMN path_norm TEXT;
+        ALTER TABLE files ADD COLUMN parent_dir_norm TEXT;",
+    )?;
+    backfill_normalized_paths(conn)?;
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_files_parent_dir_norm ON files(parent_dir_norm);",
+    )
+}
+
+// 正規化の列を追加する前にインデックスした行へ、NFC のパスと検索用のファイル名を書き込む。
+fn backfill_normalized_paths(conn: &Connection) -> rusqlite::Result<()> {
+    let rows = {
+        let mut stmt = conn.prepare("SELECT path, file_name, parent_dir FROM files")?;
+        stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?
+    };
+    let mut update = conn.prepare(
+        "UPDATE files SET path_norm = ?, parent_dir_norm = ?, file_name_norm = ? WHERE path = ?",
+    )?;
+    for (path, file_name, parent_dir) in rows {
+        update.execute(params![
+            normalize_path_key(Path::new(&path)),
+            normalize_path_key(Path::new(&parent_dir)),
+            normalize_for_search(&file_name),
+            path
+        ])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema_version(conn: &Connection) -> i32 {
+        conn.pragma_query_value(None, "user_version", |row| row.get(0))
+            .expect("read user_version")
+    }
+
+    #[test]
+    fn migrations_are_numbered_up_to_current_version() {
+        for (index, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version, index as i32 + 1);
+        }
+        assert_eq!(
+            MIGRATIONS.last().map(|migration| migration.version),
+            Some(DB_SCHEMA_VERSION)
+        );
+    }
+
+    #[test]
+    fn upgrades_old_database_and_keeps_rows() {
+        let conn = Connection::open_in_memory().expect("open db");
+        // バージョン 1 の DB に行がある状態から始める。
+        run_migration(&conn, &MIGRATIONS[0]).expect("create v1");
         conn.execute_batch(
-            "BEGIN;
-            CREATE TABLE IF NOT EXISTS roots (
-                root_id INTEGER PRIMARY KEY AUTOINCREMENT,
-                root_path TEXT NOT NULL UNIQUE,
-                is_enabled INTEGER NOT NULL DEFAULT 1,
-                last_scan_time INTEGER
-            );
-
-            CREATE TABLE IF NOT EXISTS files (
-                path TEXT PRIMARY KEY,
-                root_id INTEGER NOT NULL,
-                file_name TEXT NOT NULL,
-                file_name_norm TEXT NOT NULL,
-                parent_dir TEXT NOT NULL,
-                size_bytes INTEGER NOT NULL,
-                modified_time INTEGER NOT NULL,
-                created_time INTEGER,
-                last_indexed_time INTEGER NOT NULL,
-                FOREIGN KEY(root_id) REFERENCES roots(root_id) ON DELETE CASCADE
-            );
-
-            CREATE INDEX IF NOT EXISTS idx_files_root_id ON files(root_id);
-            CREATE INDEX IF NOT EXISTS idx_files_parent_dir ON files(parent_dir);
-            CREATE INDEX IF NOT EXISTS idx_files_file_name_norm ON files(file_name_norm);
-            CREATE INDEX IF NOT EXISTS idx_files_modified_time ON files(modified_time);
-            CREATE INDEX IF NOT EXISTS idx_files_size_bytes ON files(size_bytes);
-
-            PRAGMA user_version = 1;
-            COMMIT;",
+            "INSERT INTO roots (root_path) VALUES ('/videos');
+            INSERT INTO files (path, root_id, file_name, file_name_norm, parent_dir, size_bytes,
+                modified_time, last_indexed_time)
+            VALUES ('/videos/Clip.mp4', 1, 'Clip.mp4', 'clip.mp4', '/videos', 1, 0, 0);",
         )
-        .map_err(|err| err.to_string())?;
+        .expect("insert v1 rows");
+
+        apply_migrations(&conn).expect("migrate");
+        assert_eq!(schema_version(&conn), DB_SCHEMA_VERSION);
+        let parent_dir_norm: String = conn
+            .query_row("SELECT parent_dir_norm FROM files", [], |row| row.get(0))
+            .expect("read parent_dir_norm");
+        assert_eq!(parent_dir_norm, "/videos");
+
+        // 当て終えた DB にもう一度呼んでも何もしない。
+        apply_migrations(&conn).expect("migrate again");
+        assert_eq!(schema_version(&conn), DB_SCHEMA_VERSION);
     }
 
-    Ok(())
+    #[test]
+    fn rolls_back_failed_migration() {
+        let conn = Connection::open_in_memory().expect("open db");
+        run_migration(&conn, &MIGRATIONS[0]).expect("create v1");
+        let broken = Migration {
+            version: 2,
+            description: "broken",
+            apply: |conn| {
+                conn.execute_batch(
+                    "ALTER TABLE files ADD COLUMN duration_ms INTEGER;
+                    ALTER TABLE missing ADD COLUMN nothing TEXT;",
+                )
+            },
+        };
+
+        assert!(run_migration(&conn, &broken).is_err());
+        assert_eq!(schema_version(&conn), 1);
+        // 失敗した変更の前半（列の追加）も残らない。
+        assert!(conn.prepare("SELECT duration_ms FROM files").is_err());
+    }
+
+    #[test]
+    fn refuses_newer_schema() {
+        let conn = Connection::open_in_memory().expect("open db");
+        conn.pragma_update(None, "user_version", DB_SCHEMA_VERSION + 1)
+            .expect("set user_version");
+        assert!(apply_migrations(&conn).is_err());
+    }
 }
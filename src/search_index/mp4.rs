@@ -0,0 +1,172 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+// moov の位置から見た MP4 の構造
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Mp4Layout {
+    // moov が mdat より前にある（faststart 済み）
+    Faststart,
+    // moov が mdat より後ろにある
+    MoovAtEnd,
+    // moof で区切られた断片化 MP4
+    Fragmented,
+}
+
+// moov/mvhd ボックスから再生時間をミリ秒で読み取る。読めない場合は None。
+pub(crate) fn read_duration_ms(path: &Path) -> Option<i64> {
+    let mut file = File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    let (moov_start, moov_end) = find_box(&mut file, 0, len, b"moov")?;
+    let (mvhd_start, _) = find_box(&mut file, moov_start, moov_end, b"mvhd")?;
+
+    file.seek(SeekFrom::Start(mvhd_start)).ok()?;
+    let mut version_flags = [0_u8; 4];
+    file.read_exact(&mut version_flags).ok()?;
+
+    let (timescale, duration) = if version_flags[0] == 1 {
+        // creation(8) + modification(8) + timescale(4) + duration(8)
+        let mut buf = [0_u8; 28];
+        file.read_exact(&mut buf).ok()?;
+        let timescale = u32::from_be_bytes(buf[16..20].try_into().ok()?);
+        let duration = u64::from_be_bytes(buf[20..28].try_into().ok()?);
+        if duration == u64::MAX {
+            return None;
+        }
+        (timescale, duration)
+    } else {
+        // creation(4) + modification(4) + timescale(4) + duration(4)
+        let mut buf = [0_u8; 16];
+        file.read_exact(&mut buf).ok()?;
+        let timescale = u32::from_be_bytes(buf[8..12].try_into().ok()?);
+        let duration = u32::from_be_bytes(buf[12..16].try_into().ok()?);
+        if duration == u32::MAX {
+            return None;
+        }
+        (timescale, u64::from(duration))
+    };
+
+    if timescale == 0 {
+        return None;
+    }
+    i64::try_from(u128::from(duration) * 1_000 / u128::from(timescale)).ok()
+}
+
+// 先頭階層のボックスの並びから moov の位置を調べる。moov が無い・読めない場合は None。
+pub(crate) fn read_mp4_layout(path: &Path) -> Option<Mp4Layout> {
+    let mut file = File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    let kinds = top_level_box_kinds(&mut file, len)?;
+    layout_from_box_kinds(&kinds)
+}
+
+fn layout_from_box_kinds(kinds: &[[u8; 4]]) -> Option<Mp4Layout> {
+    let moov = kinds.iter().position(|kind| kind == b"moov")?;
+    if kinds.iter().any(|kind| kind == b"moof") {
+        return Some(Mp4Layout::Fragmented);
+    }
+    match kinds.iter().position(|kind| kind == b"mdat") {
+        Some(mdat) if mdat < moov => Some(Mp4Layout::MoovAtEnd),
+        _ => Some(Mp4Layout::Faststart),
+    }
+}
+
+// 先頭階層のボックスの種類を先頭から順に返す。末尾が途中で切れている場合はそこまでを返す。
+fn top_level_box_kinds<R: Read + Seek>(file: &mut R, end: u64) -> Option<Vec<[u8; 4]>> {
+    let mut offset = 0;
+    let mut kinds = Vec::new();
+    while offset + 8 <= end {
+        file.seek(SeekFrom::Start(offset)).ok()?;
+        let mut header = [0_u8; 8];
+        file.read_exact(&mut header).ok()?;
+        kinds.push(header[4..8].try_into().ok()?);
+
+        let mut header_len = 8;
+        let size = match u32::from_be_bytes(header[0..4].try_into().ok()?) {
+            0 => break,
+            1 => {
+                let mut large = [0_u8; 8];
+                file.read_exact(&mut large).ok()?;
+                header_len = 16;
+                u64::from_be_bytes(large)
+            }
+            size => u64::from(size),
+        };
+        if size < header_len {
+            return None;
+        }
+        offset = offset.saturating_add(size);
+    }
+    Some(kinds)
+}
+
+// [start, end) の範囲から指定タイプのボックスを探し、ペイロードの範囲を返す。
+fn find_box(file: &mut File, start: u64, end: u64, kind: &[u8; 4]) -> Option<(u64, u64)> {
+    let mut offset = start;
+    while offset + 8 <= end {
+        file.seek(SeekFrom::Start(offset)).ok()?;
+        let mut header = [0_u8; 8];
+        file.read_exact(&mut header).ok()?;
+
+        let mut header_len = 8;
+        let size = match u32::from_be_bytes(header[0..4].try_into().ok()?) {
+            0 => end - offset,
+            1 => {
+                let mut large = [0_u8; 8];
+                file.read_exact(&mut large).ok()?;
+                header_len = 16;
+                u64::from_be_bytes(large)
+            }
+            size => u64::from(size),
+        };
+        if size < header_len || offset.checked_add(size)? > end {
+            return None;
+        }
+        if &header[4..8] == kind {
+            return Some((offset + header_len, offset + size));
+        }
+        offset += size;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn mp4_box(kind: &[u8; 4], payload_len: usize) -> Vec<u8> {
+        let mut data = ((payload_len + 8) as u32).to_be_bytes().to_vec();
+        data.extend_from_slice(kind);
+        data.resize(payload_len + 8, 0);
+        data
+    }
+
+    fn layout_of(boxes: &[Vec<u8>]) -> Option<Mp4Layout> {
+        let data = boxes.concat();
+        let len = data.len() as u64;
+        let kinds = top_level_box_kinds(&mut Cursor::new(data), len)?;
+        layout_from_box_kinds(&kinds)
+    }
+
+    #[test]
+    fn detects_moov_position() {
+        let ftyp = mp4_box(b"ftyp", 16);
+        let moov = mp4_box(b"moov", 40);
+        let mdat = mp4_box(b"mdat", 100);
+        let moof = mp4_box(b"moof", 24);
+        assert_eq!(
+            layout_of(&[ftyp.clone(), moov.clone(), mdat.clone()]),
+            Some(Mp4Layout::Faststart)
+        );
+        assert_eq!(
+            layout_of(&[ftyp.clone(), mdat.clone(), moov.clone()]),
+            Some(Mp4Layout::MoovAtEnd)
+        );
+        assert_eq!(
+            layout_of(&[ftyp.clone(), moov, moof, mdat.clone()]),
+            Some(Mp4Layout::Fragmented)
+        );
+        assert_eq!(layout_of(&[ftyp, mdat]), None);
+    }
+}
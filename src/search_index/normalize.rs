@@ -5,24 +5,26 @@ use unicode_normalization::UnicodeNormalization;
 use super::EngineResult;
 
 // ルートパスを絶対パスへ正規化する。
+// 末尾の区切り文字は除き、`/Movies/VJ/` と `/Movies/VJ` を同じフォルダとして扱う。
 pub(super) fn normalize_root_path(path: &Path) -> EngineResult<PathBuf> {
-    if path.is_absolute() {
-        return Ok(path.to_path_buf());
-    }
-
-    std::env::current_dir()
-        .map_err(|err| err.to_string())
-        .map(|current| current.join(path))
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()
+            .map_err(|err| err.to_string())?
+            .join(path)
+    };
+    Ok(absolute.components().collect())
 }
 
 // 親ディレクトリ絞り込み用の文字列を検索キー形式へ正規化する。
 pub(super) fn normalize_parent_for_filter(raw: &str) -> String {
     let path = PathBuf::from(raw.trim());
     if path.is_absolute() {
-        return path_to_key(&path);
+        return normalize_path_key(&path);
     }
 
-    path_to_key(
+    normalize_path_key(
         &std::env::current_dir()
             .unwrap_or_else(|_| PathBuf::from("."))
             .join(path),
@@ -30,10 +32,20 @@ pub(super) fn normalize_parent_for_filter(raw: &str) -> String {
 }
 
 // 文字列検索のために Unicode 正規化と小文字化を行う。
+// NFKC は合成も行うため、macOS が NFD で返すファイル名の「た+゛」もここで「だ」にまとまる。
 pub(super) fn normalize_for_search(input: &str) -> String {
     input.trim().nfkc().collect::<String>().to_lowercase()
 }
 
+// タグ一覧を正規化し、改行区切りで1つの文字列にまとめる。
+pub(super) fn normalize_tags(tags: &[String]) -> String {
+    tags.iter()
+        .map(|tag| normalize_for_search(tag))
+        .filter(|tag| !tag.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 // 検索クエリを index 正規化ルールへ合わせる。
 pub(super) fn normalize_query(query: &str) -> String {
     normalize_for_search(query)
@@ -58,11 +70,20 @@ pub(super) fn path_to_key(path: &Path) -> String {
     path.to_string_lossy().to_string()
 }
 
+// パスを NFC に揃えた比較用のキーにする。macOS のファイル名は NFD で返るため、
+// 入力元（Finder・ペースト・設定ファイル）によって濁点の表し方が違っても同じキーになる。
+pub(super) fn normalize_path_key(path: &Path) -> String {
+    path_to_key(path).nfc().collect()
+}
+
+// インデックスに載せる拡張子。スキャンと監視はこの拡張子のファイルだけを扱う。
+pub const INDEXED_EXTENSION: &str = "mp4";
+
 // MP4 ファイルかどうかを拡張子で判定する。
 pub(super) fn is_mp4_path(path: &Path) -> bool {
     path.extension()
         .and_then(|ext| ext.to_str())
-        .map(|ext| ext.eq_ignore_ascii_case("mp4"))
+        .map(|ext| ext.eq_ignore_ascii_case(INDEXED_EXTENSION))
         .unwrap_or(false)
 }
 
@@ -85,3 +106,33 @@ pub(super) fn epoch_millis() -> i64 {
         .map(|duration| duration.as_millis() as i64)
         .unwrap_or(0)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 「だ」を合成済み（NFC）と、「た」+ 結合用濁点（NFD）で表したもの
+    const DA_NFC: &str = "\u{3060}";
+    const DA_NFD: &str = "\u{305F}\u{3099}";
+
+    #[test]
+    fn composes_voiced_marks_for_search() {
+        assert_ne!(DA_NFC, DA_NFD);
+        assert_eq!(
+            normalize_for_search(&format!("{DA_NFD}んす.MP4")),
+            normalize_for_search(&format!("{DA_NFC}んす.mp4"))
+        );
+        assert_eq!(normalize_for_search(DA_NFD), DA_NFC);
+    }
+
+    #[test]
+    fn composes_paths_to_nfc() {
+        let nfd = Path::new("/Volumes/SSD").join(format!("{DA_NFD}んす"));
+        let nfc = Path::new("/Volumes/SSD").join(format!("{DA_NFC}んす"));
+        assert_eq!(normalize_path_key(&nfd), path_to_key(&nfc));
+        assert_eq!(
+            normalize_parent_for_filter(&format!(" /Volumes/SSD/{DA_NFD}んす ")),
+            path_to_key(&nfc)
+        );
+    }
+}
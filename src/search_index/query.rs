@@ -2,8 +2,11 @@ use rusqlite::types::Value;
 use rusqlite::{Connection, params_from_iter};
 use std::path::Path;
 
-use super::normalize::{normalize_parent_for_filter, normalize_root_path, path_to_key};
-use super::{EngineResult, SearchHit, SearchRequest, SearchSort};
+use super::normalize::{
+    escape_like_pattern, normalize_for_search, normalize_parent_for_filter, normalize_root_path,
+    path_to_key,
+};
+use super::{EngineResult, SearchCursor, SearchHit, SearchRequest, SearchSort};
 
 #[derive(Clone)]
 pub(super) enum QueryPattern {
@@ -22,10 +25,12 @@ pub(super) fn run_search_query(
     conn: &Connection,
     request: &SearchRequest,
     pattern: Option<QueryPattern>,
+    after: Option<&SearchCursor>,
     limit: usize,
 ) -> EngineResult<Vec<SearchHit>> {
     let mut sql = String::from(
-        "SELECT f.path, f.file_name, f.size_bytes, f.modified_time, f.root_id, f.parent_dir
+        "SELECT f.path, f.file_name, f.size_bytes, f.modified_time, f.root_id, f.parent_dir,
+                f.duration_ms, f.is_cloud
          FROM files f
          JOIN roots r ON r.root_id = f.root_id
          WHERE r.is_enabled = 1",
@@ -55,7 +60,7 @@ pub(super) fn run_search_query(
         .filter(|v| !v.is_empty())
     {
         let normalized_parent = normalize_parent_for_filter(parent_dir);
-        sql.push_str(" AND f.parent_dir = ?");
+        sql.push_str(" AND f.parent_dir_norm = ?");
         params.push(Value::from(normalized_parent));
     }
 
@@ -79,10 +84,74 @@ pub(super) fn run_search_query(
         params.push(Value::from(size_max));
     }
 
+    if let Some(duration_min) = request.duration_min_ms {
+        sql.push_str(" AND f.duration_ms >= ?");
+        params.push(Value::from(duration_min));
+    }
+
+    if let Some(duration_max) = request.duration_max_ms {
+        sql.push_str(" AND f.duration_ms <= ?");
+        params.push(Value::from(duration_max));
+    }
+
+    for term in &request.terms {
+        let term = normalize_for_search(term);
+        if term.is_empty() {
+            continue;
+        }
+        // 投稿者名・元のタイトル（ダウンロード時の動画情報）に含まれる場合もヒットにする。
+        let pattern = format!("%{}%", escape_like_pattern(&term));
+        sql.push_str(
+            " AND (f.file_name_norm LIKE ? ESCAPE '\\' OR f.meta_norm LIKE ? ESCAPE '\\')",
+        );
+        params.push(Value::from(pattern.clone()));
+        params.push(Value::from(pattern));
+    }
+
+    for term in &request.exclude_terms {
+        let term = normalize_for_search(term);
+        if term.is_empty() {
+            continue;
+        }
+        let pattern = format!("%{}%", escape_like_pattern(&term));
+        sql.push_str(
+            " AND f.file_name_norm NOT LIKE ? ESCAPE '\\' AND f.meta_norm NOT LIKE ? ESCAPE '\\'",
+        );
+        params.push(Value::from(pattern.clone()));
+        params.push(Value::from(pattern));
+    }
+
+    for tag in &request.tags {
+        let tag = normalize_for_search(tag);
+        if tag.is_empty() {
+            continue;
+        }
+        // 改行で囲んでタグ単位の完全一致にする。
+        sql.push_str(" AND (char(10) || f.tags_norm || char(10)) LIKE ? ESCAPE '\\'");
+        params.push(Value::from(format!("%\n{}\n%", escape_like_pattern(&tag))));
+    }
+
+    let extensions = request
+        .extensions
+        .iter()
+        .map(|v| normalize_for_search(v.trim_start_matches('.')))
+        .filter(|v| !v.is_empty())
+        .collect::<Vec<_>>();
+    if !extensions.is_empty() {
+        let clauses = vec!["f.file_name_norm LIKE ? ESCAPE '\\'"; extensions.len()];
+        sql.push_str(&format!(" AND ({})", clauses.join(" OR ")));
+        for ext in extensions {
+            params.push(Value::from(format!("%.{}", escape_like_pattern(&ext))));
+        }
+    }
+
     match pattern {
         Some(QueryPattern::Prefix { pattern, exact }) => {
             sql.push_str(" AND f.file_name_norm LIKE ? ESCAPE '\\'");
             params.push(Value::from(pattern.clone()));
+            if let Some(cursor) = after {
+                push_cursor_clause(&mut sql, &mut params, request.sort, cursor, Some(&exact));
+            }
             sql.push_str(" ORDER BY CASE WHEN f.file_name_norm = ? THEN 0 ELSE 1 END ASC,");
             params.push(Value::from(exact));
             push_sort_clause(&mut sql, request.sort);
@@ -91,14 +160,24 @@ pub(super) fn run_search_query(
             pattern,
             prefix_pattern,
         }) => {
-            sql.push_str(" AND f.file_name_norm LIKE ? ESCAPE '\\'");
+            // ファイル名の途中に含むものに加え、動画情報だけに含むものもここで返す。
+            sql.push_str(
+                " AND (f.file_name_norm LIKE ? ESCAPE '\\' OR f.meta_norm LIKE ? ESCAPE '\\')",
+            );
+            params.push(Value::from(pattern.clone()));
             params.push(Value::from(pattern));
             sql.push_str(" AND f.file_name_norm NOT LIKE ? ESCAPE '\\'");
             params.push(Value::from(prefix_pattern));
+            if let Some(cursor) = after {
+                push_cursor_clause(&mut sql, &mut params, request.sort, cursor, None);
+            }
             sql.push_str(" ORDER BY ");
             push_sort_clause(&mut sql, request.sort);
         }
         None => {
+            if let Some(cursor) = after {
+                push_cursor_clause(&mut sql, &mut params, request.sort, cursor, None);
+            }
             sql.push_str(" ORDER BY ");
             push_sort_clause(&mut sql, request.sort);
         }
@@ -117,6 +196,8 @@ pub(super) fn run_search_query(
                 modified_time: row.get(3)?,
                 root_id: row.get(4)?,
                 parent_dir: row.get(5)?,
+                duration_ms: row.get(6)?,
+                is_cloud: row.get::<_, i64>(7)? != 0,
             })
         })
         .map_err(|err| err.to_string())?;
@@ -128,14 +209,61 @@ pub(super) fn run_search_query(
     Ok(hits)
 }
 
-// ソート種別に応じて ORDER BY 句を追加する。
+// ソート種別に応じて ORDER BY 句を追加する。末尾の path はページ境界を一意にするため。
 fn push_sort_clause(sql: &mut String, sort: SearchSort) {
     match sort {
         SearchSort::ModifiedDesc => {
-            sql.push_str(" f.modified_time DESC, f.file_name_norm ASC");
+            sql.push_str(" f.modified_time DESC, f.file_name_norm ASC, f.path ASC");
+        }
+        SearchSort::NameAsc => {
+            sql.push_str(" f.file_name_norm ASC, f.modified_time DESC, f.path ASC");
+        }
+    }
+}
+
+// カーソル (modified_time, path) より後ろの行だけを返す keyset 条件を追加する。
+// 降順キーは符号反転して row value 比較を昇順に揃える。
+fn push_cursor_clause(
+    sql: &mut String,
+    params: &mut Vec<Value>,
+    sort: SearchSort,
+    cursor: &SearchCursor,
+    exact: Option<&str>,
+) {
+    let cursor_name_norm = cursor_file_name_norm(cursor);
+    let mut columns = Vec::new();
+    let mut values = Vec::new();
+
+    if let Some(exact) = exact {
+        columns.push("CASE WHEN f.file_name_norm = ? THEN 0 ELSE 1 END");
+        params.push(Value::from(exact.to_string()));
+        values.push(Value::from(i64::from(cursor_name_norm != exact)));
+    }
+
+    match sort {
+        SearchSort::ModifiedDesc => {
+            columns.extend(["-f.modified_time", "f.file_name_norm", "f.path"]);
+            values.push(Value::from(-cursor.modified_time));
+            values.push(Value::from(cursor_name_norm));
         }
         SearchSort::NameAsc => {
-            sql.push_str(" f.file_name_norm ASC, f.modified_time DESC");
+            columns.extend(["f.file_name_norm", "-f.modified_time", "f.path"]);
+            values.push(Value::from(cursor_name_norm));
+            values.push(Value::from(-cursor.modified_time));
         }
     }
+    values.push(Value::from(cursor.path.clone()));
+
+    let placeholders = vec!["?"; values.len()].join(", ");
+    sql.push_str(&format!(" AND ({}) > ({placeholders})", columns.join(", ")));
+    params.extend(values);
+}
+
+// カーソルの path からインデックス時と同じ正規化済みファイル名を復元する。
+pub(super) fn cursor_file_name_norm(cursor: &SearchCursor) -> String {
+    let file_name = Path::new(&cursor.path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    normalize_for_search(&file_name)
 }
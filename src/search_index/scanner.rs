@@ -1,18 +1,37 @@
+use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::mpsc::Sender;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
 use std::thread;
 use walkdir::WalkDir;
 
 use super::db::open_connection;
+use super::mp4::read_duration_ms;
 use super::normalize::{
-    epoch_millis, epoch_secs, is_mp4_path, normalize_for_search, path_to_key,
-    system_time_to_epoch_secs,
+    epoch_millis, epoch_secs, is_mp4_path, normalize_for_search, normalize_path_key,
+    normalize_tags, path_to_key, system_time_to_epoch_secs,
 };
-use super::{EngineResult, FileRecord, UPSERT_BATCH_SIZE, WatchedRoot, WriteCommand};
+use super::{
+    EngineResult, FileRecord, SkipRules, UPSERT_BATCH_SIZE, WatchedRoot, WriteCommand,
+    defer_reindex_on_low_power, low_power, reads_finder_tags, skip_rules,
+};
+use crate::mac_finder_tags::read_finder_tags;
+use crate::platform::{file_identity, is_dataless};
+use crate::video_info::read_video_info;
+
+// メタデータと再生時間を読むスレッドの上限。外付けドライブで読み取りが詰まらないよう少なめにする。
+const MAX_SCAN_THREADS: usize = 4;
+// 走査したパスを読み取りスレッドへ渡すまで溜めておく数。読み取りが遅い間は走査を待たせる。
+const SCAN_QUEUE_SIZE: usize = 1024;
 
 // watcher 異常時のフォールバックとして、DB上の有効ルートを全量再走査する。
+// 省電力中は見送り、電源につないだ時に行う。
 pub(super) fn trigger_reindex_all_from_db(db_path: &Path, write_tx: &Sender<WriteCommand>) {
+    if defer_reindex_on_low_power() {
+        eprintln!("[search-index] deferred fallback reindex until on AC power");
+        return;
+    }
     let conn = match open_connection(db_path) {
         Ok(conn) => conn,
         Err(err) => {
@@ -21,7 +40,9 @@ pub(super) fn trigger_reindex_all_from_db(db_path: &Path, write_tx: &Sender<Writ
         }
     };
 
-    let mut stmt = match conn.prepare("SELECT root_id, root_path FROM roots WHERE is_enabled = 1") {
+    let mut stmt = match conn
+        .prepare("SELECT root_id, root_path, follow_links FROM roots WHERE is_enabled = 1")
+    {
         Ok(stmt) => stmt,
         Err(err) => {
             eprintln!("[search-index] failed to query roots for fallback reindex: {err}");
@@ -30,7 +51,11 @@ pub(super) fn trigger_reindex_all_from_db(db_path: &Path, write_tx: &Sender<Writ
     };
 
     let rows = match stmt.query_map([], |row| {
-        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        Ok(WatchedRoot {
+            root_id: row.get(0)?,
+            root_path: PathBuf::from(row.get::<_, String>(1)?),
+            follow_links: row.get::<_, i64>(2)? != 0,
+        })
     }) {
         Ok(rows) => rows,
         Err(err) => {
@@ -40,16 +65,15 @@ pub(super) fn trigger_reindex_all_from_db(db_path: &Path, write_tx: &Sender<Writ
     };
 
     for row in rows {
-        let Ok((root_id, root_path)) = row else {
+        let Ok(root) = row else {
             continue;
         };
-        let root_path = PathBuf::from(root_path);
         let write_tx = write_tx.clone();
         thread::spawn(move || {
-            if let Err(err) = scan_root(root_id, &root_path, &write_tx) {
+            if let Err(err) = scan_root(&root, &write_tx) {
                 eprintln!(
                     "[search-index] fallback reindex failed for {}: {}",
-                    root_path.to_string_lossy(),
+                    root.root_path.to_string_lossy(),
                     err
                 );
             }
@@ -57,54 +81,54 @@ pub(super) fn trigger_reindex_all_from_db(db_path: &Path, write_tx: &Sender<Writ
     }
 }
 
-// 監視対象ルートのうち、対象パスに最も深く一致する root_id を返す。
-pub(super) fn find_root_id_for_path(path: &Path, roots: &[WatchedRoot]) -> Option<i64> {
-    let mut best_match: Option<(usize, i64)> = None;
+// 監視対象ルートのうち、対象パスに最も深く一致するルートを返す。
+pub(super) fn find_root_for_path<'a>(
+    path: &Path,
+    roots: &'a [WatchedRoot],
+) -> Option<&'a WatchedRoot> {
+    let mut best_match: Option<(usize, &WatchedRoot)> = None;
 
     for root in roots {
         if path.starts_with(&root.root_path) {
             let len = root.root_path.as_os_str().len();
             match best_match {
                 Some((best_len, _)) if best_len >= len => {}
-                _ => best_match = Some((len, root.root_id)),
+                _ => best_match = Some((len, root)),
             }
         }
     }
 
-    best_match.map(|(_, root_id)| root_id)
+    best_match.map(|(_, root)| root)
+}
+
+// 監視対象ルートのうち、対象パスに最も深く一致する root_id を返す。
+pub(super) fn find_root_id_for_path(path: &Path, roots: &[WatchedRoot]) -> Option<i64> {
+    find_root_for_path(path, roots).map(|root| root.root_id)
+}
+
+// ルートより下（対象パス自体を含む）にシンボリックリンクがあるか。
+pub(super) fn crosses_symlink(root: &Path, path: &Path) -> bool {
+    path.ancestors()
+        .take_while(|ancestor| *ancestor != root && ancestor.starts_with(root))
+        .any(|ancestor| {
+            fs::symlink_metadata(ancestor).is_ok_and(|meta| meta.file_type().is_symlink())
+        })
 }
 
 // 指定ルートを全走査して MP4 を再インデックスする。
-pub(super) fn scan_root(
-    root_id: i64,
-    root_path: &Path,
-    write_tx: &Sender<WriteCommand>,
-) -> EngineResult<()> {
-    if !root_path.exists() {
+pub(super) fn scan_root(root: &WatchedRoot, write_tx: &Sender<WriteCommand>) -> EngineResult<()> {
+    if !root.root_path.exists() {
         return Ok(());
     }
 
     let marker = epoch_millis();
-    let mut batch = Vec::with_capacity(UPSERT_BATCH_SIZE);
-
-    for entry in WalkDir::new(root_path).into_iter().filter_map(Result::ok) {
-        if !entry.file_type().is_file() {
-            continue;
-        }
-
-        let path = entry.path();
-        if !is_mp4_path(path) {
-            continue;
-        }
-
-        if let Some(record) = build_record_from_path(root_id, path, marker) {
-            batch.push(record);
-        }
-
-        flush_upsert_batch_if_full(&mut batch, write_tx)?;
-    }
-
-    flush_upsert_batch(&mut batch, write_tx)?;
+    let root_id = root.root_id;
+    index_in_parallel(
+        mp4_paths(&root.root_path, skip_rules(), root.follow_links),
+        scan_threads(),
+        |path| build_record_from_path(root_id, path, marker),
+        write_tx,
+    )?;
 
     write_tx
         .send(WriteCommand::FinalizeScan {
@@ -124,31 +148,119 @@ pub(super) fn upsert_directory(
     write_tx: &Sender<WriteCommand>,
 ) -> EngineResult<()> {
     let marker = epoch_millis();
-    let mut batch = Vec::with_capacity(UPSERT_BATCH_SIZE);
+    let follow_links = find_root_for_path(dir, roots).is_some_and(|root| root.follow_links);
+    index_in_parallel(
+        mp4_paths(dir, skip_rules(), follow_links),
+        scan_threads(),
+        |path| {
+            let root_id = find_root_id_for_path(path, roots)?;
+            build_record_from_path(root_id, path, marker)
+        },
+        write_tx,
+    )
+}
 
-    for entry in WalkDir::new(dir).into_iter().filter_map(Result::ok) {
-        if !entry.file_type().is_file() {
-            continue;
-        }
-        let path = entry.path();
-        if !is_mp4_path(path) {
-            continue;
-        }
+// 読み取りに使うスレッド数。CPU の数までとし、MAX_SCAN_THREADS を超えない。省電力中は 1 スレッドにする。
+fn scan_threads() -> usize {
+    if low_power() {
+        return 1;
+    }
+    thread::available_parallelism()
+        .map(|count| count.get())
+        .unwrap_or(1)
+        .clamp(1, MAX_SCAN_THREADS)
+}
 
-        let Some(root_id) = find_root_id_for_path(path, roots) else {
-            continue;
-        };
+// フォルダ配下の MP4 のパスを順に返す。ディレクトリの走査は 1 スレッドで行う。
+// 除外対象のフォルダには入らない。走査を始めるフォルダ自体は除外しない。
+// follow_links が無効な場合、シンボリックリンクは（先がファイルでも）返さない。
+// 有効な場合はリンク先のフォルダへ入り、祖先へ戻るリンクは入らずに飛ばす。
+// ハードリンクやリンクで同じ実体に複数のパスから届く場合は、名前順で最初のパスだけを返す。
+fn mp4_paths(dir: &Path, rules: SkipRules, follow_links: bool) -> impl Iterator<Item = PathBuf> {
+    let mut seen = HashSet::new();
+    WalkDir::new(dir)
+        .follow_links(follow_links)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(move |entry| entry.depth() == 0 || !rules.skips_name(entry.file_name()))
+        .filter_map(|entry| match entry {
+            Ok(entry) => Some(entry),
+            Err(err) => {
+                if let (Some(path), Some(_)) = (err.path(), err.loop_ancestor()) {
+                    eprintln!(
+                        "[search-index] skipped symlink loop: {}",
+                        path.to_string_lossy()
+                    );
+                }
+                None
+            }
+        })
+        .filter(|entry| entry.file_type().is_file() && is_mp4_path(entry.path()))
+        .filter(move |entry| {
+            entry
+                .metadata()
+                .ok()
+                .and_then(|meta| file_identity(&meta))
+                .is_none_or(|identity| seen.insert(identity))
+        })
+        .map(|entry| entry.into_path())
+}
 
-        if let Some(record) = build_record_from_path(root_id, path, marker) {
-            batch.push(record);
-        }
+// 走査したパスを上限のあるキューで読み取りスレッドへ配り、各スレッドで作ったレコードをバッチで書き込みスレッドへ送る。
+// メタデータ・再生時間・Finder タグの読み取りはファイルごとにディスクを待つため、並べて行うと速くなる。
+// すべてのスレッドが送り終えてから戻るため、呼び出し側はこの後に走査の完了を送ってよい。
+fn index_in_parallel<F>(
+    paths: impl Iterator<Item = PathBuf>,
+    threads: usize,
+    to_record: F,
+    write_tx: &Sender<WriteCommand>,
+) -> EngineResult<()>
+where
+    F: Fn(&Path) -> Option<FileRecord> + Sync,
+{
+    let (path_tx, path_rx) = mpsc::sync_channel::<PathBuf>(SCAN_QUEUE_SIZE);
+    // 読み取りスレッドがすべて止まった場合に送信が失敗するよう、受信側はスレッドだけが持つ。
+    let path_rx = Arc::new(Mutex::new(path_rx));
+    let to_record = &to_record;
 
-        flush_upsert_batch_if_full(&mut batch, write_tx)?;
-    }
+    thread::scope(|scope| {
+        let workers = (0..threads.max(1))
+            .map(|_| {
+                let path_rx = Arc::clone(&path_rx);
+                scope.spawn(move || -> EngineResult<()> {
+                    let mut batch = Vec::with_capacity(UPSERT_BATCH_SIZE);
+                    loop {
+                        let next = match path_rx.lock() {
+                            Ok(rx) => rx.recv(),
+                            Err(_) => break,
+                        };
+                        let Ok(path) = next else {
+                            break;
+                        };
+                        if let Some(record) = to_record(&path) {
+                            batch.push(record);
+                        }
+                        flush_upsert_batch_if_full(&mut batch, write_tx)?;
+                    }
+                    flush_upsert_batch(&mut batch, write_tx)
+                })
+            })
+            .collect::<Vec<_>>();
+        drop(path_rx);
 
-    flush_upsert_batch(&mut batch, write_tx)?;
+        for path in paths {
+            if path_tx.send(path).is_err() {
+                break;
+            }
+        }
+        drop(path_tx);
 
-    Ok(())
+        workers.into_iter().try_for_each(|worker| {
+            worker
+                .join()
+                .unwrap_or_else(|_| Err("scan thread panicked".to_string()))
+        })
+    })
 }
 
 fn flush_upsert_batch_if_full(
@@ -185,21 +297,170 @@ pub(super) fn build_record_from_path(root_id: i64, path: &Path, marker: i64) ->
 
     let file_name = path.file_name()?.to_string_lossy().to_string();
     let parent_dir = path.parent().map(path_to_key).unwrap_or_else(String::new);
+    let parent_dir_norm = path
+        .parent()
+        .map(normalize_path_key)
+        .unwrap_or_else(String::new);
     let modified_time = metadata
         .modified()
         .map(system_time_to_epoch_secs)
         .unwrap_or_else(|_| 0);
     let created_time = metadata.created().map(system_time_to_epoch_secs).ok();
+    // クラウドにだけあるファイルは、中身を読むとダウンロードが始まるため長さを読まない（DB 上の値を残す）。
+    let is_cloud = is_dataless(&metadata);
+    let duration_ms = if is_cloud {
+        None
+    } else {
+        read_duration_ms(path)
+    };
+    // 読み取りが無効な場合は None とし、DB 上の既存タグを保持する。
+    let tags_norm = if reads_finder_tags() {
+        read_finder_tags(path)
+            .ok()
+            .map(|tags| normalize_tags(&tags))
+    } else {
+        None
+    };
+
+    let info = read_video_info(path).unwrap_or_default();
+    let meta_norm = [info.uploader.as_deref(), info.original_title.as_deref()]
+        .into_iter()
+        .flatten()
+        .map(normalize_for_search)
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
 
     Some(FileRecord {
         path: path_to_key(path),
+        path_norm: normalize_path_key(path),
         root_id,
         file_name_norm: normalize_for_search(&file_name),
         file_name,
         parent_dir,
+        parent_dir_norm,
         size_bytes: metadata.len() as i64,
         modified_time,
         created_time,
+        duration_ms,
+        canonical_path: fs::canonicalize(path).ok().map(|real| path_to_key(&real)),
+        is_cloud,
+        tags_norm,
+        uploader: info.uploader,
+        upload_date: info.upload_date,
+        original_title: info.original_title,
+        meta_norm,
         last_indexed_time: marker,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn sends_every_mp4_once_across_threads() {
+        let dir = tempdir().expect("tempdir");
+        let nested = dir.path().join("a").join("b");
+        fs::create_dir_all(&nested).expect("create dirs");
+        let mut expected = HashSet::new();
+        for index in 0..40 {
+            let parent = if index % 2 == 0 {
+                dir.path()
+            } else {
+                nested.as_path()
+            };
+            let path = parent.join(format!("clip{index}.mp4"));
+            fs::write(&path, b"").expect("write clip");
+            expected.insert(path_to_key(&path));
+        }
+        fs::write(dir.path().join("notes.txt"), b"").expect("write other file");
+
+        let (write_tx, write_rx) = mpsc::channel();
+        index_in_parallel(
+            mp4_paths(dir.path(), SkipRules::default(), false),
+            3,
+            |path| build_record_from_path(1, path, 0),
+            &write_tx,
+        )
+        .expect("index");
+        drop(write_tx);
+
+        let mut seen = Vec::new();
+        for command in write_rx {
+            if let WriteCommand::UpsertFiles { files } = command {
+                seen.extend(files.into_iter().map(|file| file.path));
+            }
+        }
+        assert_eq!(seen.len(), expected.len());
+        assert_eq!(seen.into_iter().collect::<HashSet<_>>(), expected);
+    }
+
+    #[test]
+    fn does_not_enter_skipped_folders() {
+        // tempdir の名前は "." で始まるが、走査を始めるフォルダなので除外しない。
+        let dir = tempdir().expect("tempdir");
+        let kept = dir.path().join("set").join("loop.mp4");
+        for path in [
+            kept.clone(),
+            dir.path().join("._loop.mp4"),
+            dir.path().join(".Trashes").join("501").join("old.mp4"),
+            dir.path()
+                .join("Lib.photoslibrary")
+                .join("originals")
+                .join("a.mp4"),
+        ] {
+            fs::create_dir_all(path.parent().expect("parent")).expect("create dirs");
+            fs::write(&path, b"").expect("write clip");
+        }
+
+        let paths = mp4_paths(dir.path(), SkipRules::default(), false).collect::<Vec<_>>();
+        assert_eq!(paths, vec![kept]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn follows_linked_folders_once_when_enabled() {
+        use std::os::unix::fs::symlink;
+
+        let dir = tempdir().expect("tempdir");
+        let root = dir.path().join("root");
+        let outside = dir.path().join("outside");
+        fs::create_dir_all(root.join("a")).expect("create root");
+        fs::create_dir_all(&outside).expect("create outside");
+        let clip = root.join("a").join("clip.mp4");
+        fs::write(&clip, b"").expect("write clip");
+        fs::write(outside.join("ext.mp4"), b"").expect("write outside clip");
+        // 同じ実体へのハードリンクと、外のフォルダ・祖先へのシンボリックリンク
+        fs::hard_link(&clip, root.join("b.mp4")).expect("hard link");
+        symlink(&outside, root.join("linked")).expect("link outside");
+        symlink(&root, root.join("a").join("loop")).expect("link ancestor");
+
+        let paths = mp4_paths(&root, SkipRules::default(), false).collect::<Vec<_>>();
+        assert_eq!(paths, vec![clip.clone()]);
+
+        let paths = mp4_paths(&root, SkipRules::default(), true).collect::<Vec<_>>();
+        assert_eq!(paths, vec![clip, root.join("linked").join("ext.mp4")]);
+        assert!(crosses_symlink(&root, &root.join("linked").join("ext.mp4")));
+        assert!(!crosses_symlink(&root, &root.join("a").join("clip.mp4")));
+    }
+
+    #[test]
+    fn stops_walking_when_writer_is_gone() {
+        let dir = tempdir().expect("tempdir");
+        for index in 0..(UPSERT_BATCH_SIZE * 2) {
+            fs::write(dir.path().join(format!("clip{index}.mp4")), b"").expect("write clip");
+        }
+
+        let (write_tx, write_rx) = mpsc::channel();
+        drop(write_rx);
+        let result = index_in_parallel(
+            mp4_paths(dir.path(), SkipRules::default(), false),
+            2,
+            |path| build_record_from_path(1, path, 0),
+            &write_tx,
+        );
+        assert!(result.is_err());
+    }
+}
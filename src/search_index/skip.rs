@@ -0,0 +1,137 @@
+use std::ffi::OsStr;
+use std::path::Path;
+
+// 中身をまとめて 1 つのファイルのように扱うパッケージ（Finder では開けないフォルダ）の拡張子
+const BUNDLE_EXTENSIONS: &[&str] = &[
+    "app",
+    "bundle",
+    "framework",
+    "plugin",
+    "kext",
+    "pkg",
+    "photoslibrary",
+    "photolibrary",
+    "aplibrary",
+    "fcpbundle",
+    "imovielibrary",
+    "tvlibrary",
+    "logicx",
+    "band",
+    "rtfd",
+    "xcodeproj",
+    "xcworkspace",
+];
+
+// OS やこのアプリが作る作業用のフォルダ
+const SYSTEM_NAMES: &[&str] = &[
+    ".Trash",
+    ".Trashes",
+    ".Spotlight-V100",
+    ".fseventsd",
+    ".DocumentRevisions-V100",
+    ".TemporaryItems",
+    "$RECYCLE.BIN",
+    "System Volume Information",
+    "lost+found",
+    ".vjdownloader-staging",
+    ".vjdownloader-quarantine",
+];
+
+// インデックスしない項目の決まり。設定の search.skip.* から作る。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SkipRules {
+    // 名前が "." で始まる項目（外付けドライブの "._" ファイルも含む）
+    pub hidden: bool,
+    // .app や .photoslibrary などのパッケージ
+    pub bundles: bool,
+    // ゴミ箱や Spotlight などのシステムフォルダ
+    pub system: bool,
+    // 追加で除外する名前（大文字小文字は区別しない）
+    pub names: Vec<String>,
+}
+
+impl SkipRules {
+    pub const DEFAULT: SkipRules = SkipRules {
+        hidden: true,
+        bundles: true,
+        system: true,
+        names: Vec::new(),
+    };
+
+    // 項目名 1 つが除外対象か。
+    pub(super) fn skips_name(&self, name: &OsStr) -> bool {
+        let name = name.to_string_lossy();
+        if self.hidden && name.starts_with('.') {
+            return true;
+        }
+        if self.system && SYSTEM_NAMES.iter().any(|system| *system == name) {
+            return true;
+        }
+        if self.bundles
+            && let Some((_, ext)) = name.rsplit_once('.')
+            && BUNDLE_EXTENSIONS
+                .iter()
+                .any(|bundle| bundle.eq_ignore_ascii_case(ext))
+        {
+            return true;
+        }
+        self.names
+            .iter()
+            .any(|skip| skip.eq_ignore_ascii_case(&name))
+    }
+
+    // ルートより下の階層に除外対象の名前が含まれるか。ルート自体は除外しない。
+    pub(super) fn skips_under(&self, root: &Path, path: &Path) -> bool {
+        path.strip_prefix(root).is_ok_and(|relative| {
+            relative
+                .components()
+                .any(|component| self.skips_name(component.as_os_str()))
+        })
+    }
+}
+
+impl Default for SkipRules {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_hidden_bundles_and_system_by_default() {
+        let rules = SkipRules::default();
+        let root = Path::new("/Volumes/VJ");
+        assert!(rules.skips_under(root, Path::new("/Volumes/VJ/._clip.mp4")));
+        assert!(rules.skips_under(root, Path::new("/Volumes/VJ/.Trashes/501/a.mp4")));
+        assert!(rules.skips_under(
+            root,
+            Path::new("/Volumes/VJ/Photos Library.photoslibrary/originals/a.mp4")
+        ));
+        assert!(rules.skips_under(root, Path::new("/Volumes/VJ/Tool.APP/clip.mp4")));
+        assert!(rules.skips_under(root, Path::new("/Volumes/VJ/$RECYCLE.BIN/a.mp4")));
+        assert!(!rules.skips_under(root, Path::new("/Volumes/VJ/set.v2/loop.mp4")));
+        // ルート自体が隠しフォルダでも、その中は除外しない。
+        assert!(!rules.skips_under(
+            Path::new("/Users/vj/.media"),
+            Path::new("/Users/vj/.media/a.mp4")
+        ));
+    }
+
+    #[test]
+    fn applies_overrides() {
+        let rules = SkipRules {
+            hidden: false,
+            bundles: false,
+            system: true,
+            names: vec!["Render Cache".to_string()],
+        };
+        let root = Path::new("/clips");
+        assert!(!rules.skips_under(root, Path::new("/clips/.hidden/a.mp4")));
+        assert!(!rules.skips_under(root, Path::new("/clips/Lib.photoslibrary/a.mp4")));
+        assert!(rules.skips_under(root, Path::new("/clips/.Trashes/a.mp4")));
+        assert!(rules.skips_under(root, Path::new("/clips/render cache/a.mp4")));
+    }
+}
@@ -0,0 +1,339 @@
+use time::{Date, Month, UtcOffset};
+
+use super::SearchRequest;
+use super::normalize::normalize_for_search;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Comparison {
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+}
+
+#[derive(Debug)]
+struct Token {
+    text: String,
+    quoted: bool,
+    negated: bool,
+}
+
+// 検索ボックスの入力を演算子ごとに分解し、SearchRequest の条件へ反映する。
+// 対応: "完全一致フレーズ" / -除外語 / ext:mp4 / tag:live / size>100mb / before:2024-01 / after:2024 / dur<30s
+pub(super) fn apply_query_syntax(input: &str, request: &mut SearchRequest, offset: UtcOffset) {
+    let normalized = normalize_for_search(input);
+    let mut terms = Vec::new();
+
+    for token in tokenize(&normalized) {
+        if token.negated {
+            request.exclude_terms.push(token.text);
+            continue;
+        }
+        if !token.quoted && apply_operator(&token.text, request, offset) {
+            continue;
+        }
+        terms.push(token.text);
+    }
+
+    let mut terms = terms.into_iter();
+    request.query = terms.next().unwrap_or_default();
+    request.terms.extend(terms);
+}
+
+// 空白区切りでトークン化する。引用符内の空白は区切りとして扱わない。
+fn tokenize(input: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut negated = false;
+    let mut in_quotes = false;
+
+    for ch in input.chars() {
+        if matches!(ch, '"' | '“' | '”') {
+            in_quotes = !in_quotes;
+            quoted = true;
+            continue;
+        }
+        if ch.is_whitespace() && !in_quotes {
+            push_token(&mut tokens, &mut current, &mut quoted, &mut negated);
+            continue;
+        }
+        if ch == '-' && current.is_empty() && !quoted && !negated {
+            negated = true;
+            continue;
+        }
+        current.push(ch);
+    }
+    push_token(&mut tokens, &mut current, &mut quoted, &mut negated);
+
+    tokens
+}
+
+fn push_token(
+    tokens: &mut Vec<Token>,
+    current: &mut String,
+    quoted: &mut bool,
+    negated: &mut bool,
+) {
+    if current.is_empty() && *negated {
+        // 単独の "-" は通常の文字として扱う。
+        current.push('-');
+        *negated = false;
+    }
+    if !current.is_empty() {
+        tokens.push(Token {
+            text: std::mem::take(current),
+            quoted: *quoted,
+            negated: *negated,
+        });
+    }
+    *quoted = false;
+    *negated = false;
+}
+
+// 演算子トークンなら条件を反映して true を返す。値が解釈できない場合は通常語として扱う。
+fn apply_operator(text: &str, request: &mut SearchRequest, offset: UtcOffset) -> bool {
+    if let Some(value) = text.strip_prefix("ext:") {
+        let ext = value.trim_start_matches('.');
+        if ext.is_empty() || !ext.chars().all(|ch| ch.is_ascii_alphanumeric()) {
+            return false;
+        }
+        request.extensions.push(ext.to_string());
+        return true;
+    }
+
+    if let Some(value) = text.strip_prefix("tag:") {
+        if value.is_empty() {
+            return false;
+        }
+        request.tags.push(value.to_string());
+        return true;
+    }
+
+    if let Some(value) = text.strip_prefix("before:") {
+        let Some(start) = parse_period_start(value, offset) else {
+            return false;
+        };
+        request.modified_before = Some(start - 1);
+        return true;
+    }
+
+    if let Some(value) = text.strip_prefix("after:") {
+        let Some(start) = parse_period_start(value, offset) else {
+            return false;
+        };
+        request.modified_after = Some(start);
+        return true;
+    }
+
+    if let Some((cmp, value)) = split_comparison(text, "size") {
+        let Some(bytes) = parse_size_bytes(value) else {
+            return false;
+        };
+        apply_range(cmp, bytes, &mut request.size_min, &mut request.size_max);
+        return true;
+    }
+
+    if let Some((cmp, value)) = split_comparison(text, "dur") {
+        let Some(millis) = parse_duration_ms(value) else {
+            return false;
+        };
+        apply_range(
+            cmp,
+            millis,
+            &mut request.duration_min_ms,
+            &mut request.duration_max_ms,
+        );
+        return true;
+    }
+
+    false
+}
+
+// "size>=100mb" のような比較演算子を分離する。
+fn split_comparison<'a>(text: &'a str, key: &str) -> Option<(Comparison, &'a str)> {
+    let rest = text.strip_prefix(key)?;
+    let (cmp, value) = if let Some(value) = rest.strip_prefix(">=") {
+        (Comparison::GreaterOrEqual, value)
+    } else if let Some(value) = rest.strip_prefix("<=") {
+        (Comparison::LessOrEqual, value)
+    } else if let Some(value) = rest.strip_prefix('>') {
+        (Comparison::Greater, value)
+    } else if let Some(value) = rest.strip_prefix('<') {
+        (Comparison::Less, value)
+    } else {
+        return None;
+    };
+    Some((cmp, value))
+}
+
+// 比較結果を下限/上限（いずれも境界を含む）へ変換する。
+fn apply_range(cmp: Comparison, value: i64, min: &mut Option<i64>, max: &mut Option<i64>) {
+    match cmp {
+        Comparison::Greater => *min = Some(value.saturating_add(1)),
+        Comparison::GreaterOrEqual => *min = Some(value),
+        Comparison::Less => *max = Some(value.saturating_sub(1)),
+        Comparison::LessOrEqual => *max = Some(value),
+    }
+}
+
+// "100mb" / "1.5gb" / "500k" をバイト数へ変換する（1024 単位）。
+fn parse_size_bytes(value: &str) -> Option<i64> {
+    let (number, unit) = split_number(value)?;
+    let multiplier = match unit {
+        "" | "b" => 1.0,
+        "k" | "kb" => 1024.0,
+        "m" | "mb" => 1024.0 * 1024.0,
+        "g" | "gb" => 1024.0 * 1024.0 * 1024.0,
+        "t" | "tb" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some((number * multiplier).round() as i64)
+}
+
+// "30s" / "1.5m" / "1h" / "1m30s" をミリ秒へ変換する。単位省略時は秒。
+fn parse_duration_ms(value: &str) -> Option<i64> {
+    if value.is_empty() {
+        return None;
+    }
+    let mut rest = value;
+    let mut total = 0.0;
+    while !rest.is_empty() {
+        let (number, unit_rest) = split_number(rest)?;
+        rest = unit_rest;
+        let unit_len = rest
+            .find(|ch: char| ch.is_ascii_digit() || ch == '.')
+            .unwrap_or(rest.len());
+        let multiplier = match &rest[..unit_len] {
+            "ms" => 1.0,
+            "" | "s" | "sec" => 1_000.0,
+            "m" | "min" => 60_000.0,
+            "h" => 3_600_000.0,
+            _ => return None,
+        };
+        rest = &rest[unit_len..];
+        total += number * multiplier;
+    }
+    Some(total.round() as i64)
+}
+
+// 先頭の数値部分と残り（単位）に分ける。
+fn split_number(value: &str) -> Option<(f64, &str)> {
+    let number_len = value
+        .find(|ch: char| !(ch.is_ascii_digit() || ch == '.'))
+        .unwrap_or(value.len());
+    let number = value[..number_len].parse().ok()?;
+    Some((number, &value[number_len..]))
+}
+
+// "2024" / "2024-01" / "2024-01-15"（区切りは / も可）の期間開始時刻を UNIX 秒で返す。
+fn parse_period_start(value: &str, offset: UtcOffset) -> Option<i64> {
+    let mut parts = value.split(['-', '/']);
+    let year: i32 = parts.next()?.parse().ok()?;
+    let month = match parts.next() {
+        Some(month) => month.parse::<u8>().ok()?,
+        None => 1,
+    };
+    let day = match parts.next() {
+        Some(day) => day.parse::<u8>().ok()?,
+        None => 1,
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+
+    let date = Date::from_calendar_date(year, Month::try_from(month).ok()?, day).ok()?;
+    Some(date.midnight().assume_offset(offset).unix_timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> SearchRequest {
+        let mut request = SearchRequest::default();
+        apply_query_syntax(input, &mut request, UtcOffset::UTC);
+        request
+    }
+
+    #[test]
+    fn keeps_plain_query_as_is() {
+        let request = parse("旅行");
+        assert_eq!(request.query, "旅行");
+        assert!(request.terms.is_empty());
+        assert!(request.exclude_terms.is_empty());
+    }
+
+    #[test]
+    fn splits_words_and_keeps_quoted_phrase() {
+        let request = parse("loop \"neon city\" night");
+        assert_eq!(request.query, "loop");
+        assert_eq!(request.terms, vec!["neon city", "night"]);
+    }
+
+    #[test]
+    fn parses_exclusions() {
+        let request = parse("loop -draft -\"old take\" - x");
+        assert_eq!(request.query, "loop");
+        assert_eq!(request.exclude_terms, vec!["draft", "old take"]);
+        assert_eq!(request.terms, vec!["-", "x"]);
+    }
+
+    #[test]
+    fn parses_extension_and_size() {
+        let request = parse("ext:.MOV size>100mb size<=2gb");
+        assert!(request.query.is_empty());
+        assert_eq!(request.extensions, vec!["mov"]);
+        assert_eq!(request.size_min, Some(100 * 1024 * 1024 + 1));
+        assert_eq!(request.size_max, Some(2 * 1024 * 1024 * 1024));
+    }
+
+    #[test]
+    fn flags_extensions_outside_the_index() {
+        assert_eq!(parse("ext:.MOV").unindexed_extension(), Some("mov"));
+        assert_eq!(
+            parse("ext:mp4 ext:webm").unindexed_extension(),
+            Some("webm")
+        );
+        assert_eq!(parse("loop ext:MP4").unindexed_extension(), None);
+    }
+
+    #[test]
+    fn parses_tags() {
+        let request = parse("loop tag:Live tag:★★★ tag:");
+        assert_eq!(request.query, "loop");
+        assert_eq!(request.tags, vec!["live", "★★★"]);
+        assert_eq!(request.terms, vec!["tag:"]);
+    }
+
+    #[test]
+    fn parses_dates_as_period_start() {
+        let request = parse("before:2024-01 after:2023/06/15");
+        assert_eq!(request.modified_before, Some(1_704_067_200 - 1));
+        assert_eq!(request.modified_after, Some(1_686_787_200));
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse("dur<30s").duration_max_ms, Some(29_999));
+        assert_eq!(parse("dur>=1m30s").duration_min_ms, Some(90_000));
+        assert_eq!(parse("dur>2").duration_min_ms, Some(2_001));
+        assert_eq!(parse("dur<=1.5h").duration_max_ms, Some(5_400_000));
+    }
+
+    #[test]
+    fn treats_invalid_operator_values_as_text() {
+        let request = parse("size>huge before:someday \"ext:mov\"");
+        assert_eq!(request.query, "size>huge");
+        assert_eq!(request.terms, vec!["before:someday", "ext:mov"]);
+        assert!(request.size_min.is_none());
+        assert!(request.extensions.is_empty());
+    }
+
+    #[test]
+    fn normalizes_full_width_input() {
+        let request = parse("ｓｉｚｅ＞１ｋｂ　＂Ａ Ｂ＂");
+        assert_eq!(request.size_min, Some(1025));
+        assert_eq!(request.query, "a b");
+    }
+}
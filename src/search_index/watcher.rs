@@ -8,10 +8,12 @@ use std::time::Instant;
 
 use super::normalize::{epoch_millis, is_mp4_path, path_to_key};
 use super::scanner::{
-    build_record_from_path, find_root_id_for_path, trigger_reindex_all_from_db, upsert_directory,
+    build_record_from_path, crosses_symlink, find_root_for_path, find_root_id_for_path,
+    trigger_reindex_all_from_db, upsert_directory,
 };
 use super::{
-    DEBOUNCE_WINDOW, EngineResult, PendingChanges, WatchedRoot, WatcherMessage, WriteCommand,
+    DEBOUNCE_WINDOW, EngineResult, PendingChanges, SkipRules, WatchedRoot, WatcherMessage,
+    WriteCommand, skip_rules,
 };
 
 // notify のイベントを受け取り、debounce 後に差分更新コマンドへ変換する。
@@ -151,8 +153,21 @@ fn flush_pending_changes(
 
     pending.last_change_at = None;
 
+    let rules = skip_rules();
     for path in upsert_paths {
         if path.exists() {
+            // 除外対象のフォルダ内の変更は取り込まない。移動元は上で消し込み済み。
+            if is_skipped_in_roots(&path, roots, &rules) {
+                continue;
+            }
+            // リンクをたどらないルートでは、シンボリックリンクとその先を取り込まない。
+            if let Some(root) = find_root_for_path(&path, roots)
+                && !root.follow_links
+                && crosses_symlink(&root.root_path, &path)
+            {
+                continue;
+            }
+
             let metadata = match fs::metadata(&path) {
                 Ok(meta) => meta,
                 Err(_) => {
@@ -202,6 +217,11 @@ fn flush_pending_changes(
     Ok(())
 }
 
+// パスを含むルートのうち最も深いものから見て、途中に除外対象の名前があるか。
+fn is_skipped_in_roots(path: &Path, roots: &[WatchedRoot], rules: &SkipRules) -> bool {
+    find_root_for_path(path, roots).is_some_and(|root| rules.skips_under(&root.root_path, path))
+}
+
 // 削除対象がファイルかディレクトリか不明な場合も含め、消し込みキーを収集する。
 fn collect_delete_target(
     path: &Path,
@@ -1,13 +1,16 @@
 use rusqlite::{Connection, OptionalExtension, params};
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::mpsc::Receiver;
 
+use super::backup::restore_backup;
 use super::db::{apply_migrations, open_connection};
 use super::normalize::escape_like_pattern;
 use super::{EngineResult, WriteCommand};
 
-// 書き込み専用スレッドでコマンドを順次適用する。
-pub(super) fn writer_loop(db_path: PathBuf, rx: Receiver<WriteCommand>) {
+// 書き込み専用スレッドでコマンドを順次適用する。適用するたびに changes を進めて読み手へ知らせる。
+pub(super) fn writer_loop(db_path: PathBuf, rx: Receiver<WriteCommand>, changes: Arc<AtomicU64>) {
     let mut conn = match open_connection(&db_path).and_then(|conn| {
         apply_migrations(&conn)?;
         Ok(conn)
@@ -24,8 +27,11 @@ pub(super) fn writer_loop(db_path: PathBuf, rx: Receiver<WriteCommand>) {
             break;
         }
 
-        if let Err(err) = apply_write_command(&mut conn, cmd) {
-            eprintln!("[search-index] writer command failed: {err}");
+        match apply_write_command(&mut conn, cmd) {
+            Ok(()) => {
+                changes.fetch_add(1, Ordering::Release);
+            }
+            Err(err) => eprintln!("[search-index] writer command failed: {err}"),
         }
     }
 }
@@ -33,7 +39,11 @@ pub(super) fn writer_loop(db_path: PathBuf, rx: Receiver<WriteCommand>) {
 // 受信した DB 更新コマンドをトランザクション付きで実行する。
 pub(super) fn apply_write_command(conn: &mut Connection, cmd: WriteCommand) -> EngineResult<()> {
     match cmd {
-        WriteCommand::AddOrEnableRoot { root_path, resp } => {
+        WriteCommand::AddOrEnableRoot {
+            root_path,
+            follow_links,
+            resp,
+        } => {
             let result = (|| {
                 let existing: Option<i64> = conn
                     .query_row(
@@ -46,16 +56,16 @@ pub(super) fn apply_write_command(conn: &mut Connection, cmd: WriteCommand) -> E
 
                 if let Some(root_id) = existing {
                     conn.execute(
-                        "UPDATE roots SET is_enabled = 1 WHERE root_id = ?",
-                        [root_id],
+                        "UPDATE roots SET is_enabled = 1, follow_links = ? WHERE root_id = ?",
+                        params![follow_links, root_id],
                     )
                     .map_err(|err| err.to_string())?;
                     return Ok(root_id);
                 }
 
                 conn.execute(
-                    "INSERT INTO roots (root_path, is_enabled) VALUES (?, 1)",
-                    [root_path.as_str()],
+                    "INSERT INTO roots (root_path, is_enabled, follow_links) VALUES (?, 1, ?)",
+                    params![root_path, follow_links],
                 )
                 .map_err(|err| err.to_string())?;
 
@@ -82,37 +92,82 @@ pub(super) fn apply_write_command(conn: &mut Connection, cmd: WriteCommand) -> E
                     .prepare(
                         "INSERT INTO files (
                             path,
+                            path_norm,
                             root_id,
                             file_name,
                             file_name_norm,
                             parent_dir,
+                            parent_dir_norm,
                             size_bytes,
                             modified_time,
                             created_time,
+                            duration_ms,
+                            tags_norm,
+                            canonical_path,
+                            is_cloud,
+                            uploader,
+                            upload_date,
+                            original_title,
+                            meta_norm,
                             last_indexed_time
-                        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
                         ON CONFLICT(path) DO UPDATE SET
+                            path_norm = excluded.path_norm,
                             root_id = excluded.root_id,
                             file_name = excluded.file_name,
                             file_name_norm = excluded.file_name_norm,
                             parent_dir = excluded.parent_dir,
+                            parent_dir_norm = excluded.parent_dir_norm,
                             size_bytes = excluded.size_bytes,
                             modified_time = excluded.modified_time,
                             created_time = excluded.created_time,
+                            duration_ms = CASE
+                                WHEN excluded.is_cloud = 1
+                                    THEN COALESCE(excluded.duration_ms, files.duration_ms)
+                                ELSE excluded.duration_ms
+                            END,
+                            tags_norm = COALESCE(excluded.tags_norm, files.tags_norm),
+                            canonical_path = excluded.canonical_path,
+                            is_cloud = excluded.is_cloud,
+                            uploader = excluded.uploader,
+                            upload_date = excluded.upload_date,
+                            original_title = excluded.original_title,
+                            meta_norm = excluded.meta_norm,
                             last_indexed_time = excluded.last_indexed_time",
                     )
                     .map_err(|err| err.to_string())?;
+                // 別のパス（シンボリックリンク）から同じ実体が登録済みの場合は、先に登録した方を残す。
+                let mut linked = tx
+                    .prepare("SELECT 1 FROM files WHERE canonical_path = ? AND path <> ? LIMIT 1")
+                    .map_err(|err| err.to_string())?;
 
                 for file in files {
+                    if let Some(canonical_path) = file.canonical_path.as_deref()
+                        && linked
+                            .exists(params![canonical_path, file.path])
+                            .map_err(|err| err.to_string())?
+                    {
+                        continue;
+                    }
                     stmt.execute(params![
                         file.path,
+                        file.path_norm,
                         file.root_id,
                         file.file_name,
                         file.file_name_norm,
                         file.parent_dir,
+                        file.parent_dir_norm,
                         file.size_bytes,
                         file.modified_time,
                         file.created_time,
+                        file.duration_ms,
+                        file.tags_norm,
+                        file.canonical_path,
+                        file.is_cloud,
+                        file.uploader,
+                        file.upload_date,
+                        file.original_title,
+                        file.meta_norm,
                         file.last_indexed_time
                     ])
                     .map_err(|err| err.to_string())?;
@@ -120,6 +175,32 @@ pub(super) fn apply_write_command(conn: &mut Connection, cmd: WriteCommand) -> E
             }
             tx.commit().map_err(|err| err.to_string())?;
         }
+        WriteCommand::SetTags { path, tags_norm } => {
+            conn.execute(
+                "UPDATE files SET tags_norm = ? WHERE path = ?",
+                params![tags_norm, path],
+            )
+            .map_err(|err| err.to_string())?;
+        }
+        WriteCommand::LinkProxy {
+            original_path,
+            proxy_path,
+            created_time,
+        } => {
+            conn.execute(
+                "INSERT INTO proxies (original_path, proxy_path, created_time) VALUES (?, ?, ?)
+                 ON CONFLICT(original_path) DO UPDATE SET
+                    proxy_path = excluded.proxy_path,
+                    created_time = excluded.created_time",
+                params![original_path, proxy_path, created_time],
+            )
+            .map_err(|err| err.to_string())?;
+        }
+        WriteCommand::RestoreBackup { path, resp } => {
+            // 古いバックアップにも今のスキーマをそろえる。
+            let result = restore_backup(conn, &path).and_then(|()| apply_migrations(conn));
+            let _ = resp.send(result);
+        }
         WriteCommand::DeletePaths { paths } => {
             if paths.is_empty() {
                 return Ok(());
@@ -0,0 +1,204 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+
+use crate::download::{detect_encoder_profile, encoder_label};
+use crate::fs_utils::ensure_dir;
+use crate::i18n::{Msg, tr, tr_fmt};
+use crate::network;
+use crate::paths::{deno_path, ffmpeg_path, ffprobe_path, yt_dlp_path};
+use crate::platform::is_executable;
+use crate::search_index::SearchEngine;
+use crate::settings::{EncoderProfile, JsRuntime, load_js_runtime};
+
+// 書き込めるかを確かめるために作って消すファイル
+const WRITE_PROBE_NAME: &str = ".vjdownloader-write-check";
+
+// 設定画面の「診断」で確かめる項目
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckItem {
+    YtDlp,
+    Ffmpeg,
+    Ffprobe,
+    Deno,
+    VideoToolbox,
+    OutputDir,
+    SearchIndex,
+    Network,
+}
+
+impl CheckItem {
+    pub fn label(self) -> &'static str {
+        match self {
+            CheckItem::YtDlp => "yt-dlp",
+            CheckItem::Ffmpeg => "ffmpeg",
+            CheckItem::Ffprobe => "ffprobe",
+            CheckItem::Deno => "Deno",
+            CheckItem::VideoToolbox => "VideoToolbox",
+            CheckItem::OutputDir => tr(Msg::CheckOutputDir),
+            CheckItem::SearchIndex => tr(Msg::CheckSearchIndex),
+            CheckItem::Network => tr(Msg::CheckNetwork),
+        }
+    }
+}
+
+// 1 項目の結果
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CheckResult {
+    pub item: CheckItem,
+    pub passed: bool,
+    // 確かめた内容（パスやエラーの内容）
+    pub detail: String,
+    // 失敗した場合の直し方
+    pub fix: Option<&'static str>,
+}
+
+impl CheckResult {
+    fn passed(item: CheckItem, detail: impl Into<String>) -> Self {
+        Self {
+            item,
+            passed: true,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn failed(item: CheckItem, detail: impl Into<String>, fix: Msg) -> Self {
+        Self {
+            item,
+            passed: false,
+            detail: detail.into(),
+            fix: Some(tr(fix)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub enum SelfCheckState {
+    #[default]
+    Idle,
+    Running,
+    Done(Vec<CheckResult>),
+}
+
+// 診断を裏で行い、終わったら結果を送る。ツールの起動や接続の確認で数秒かかることがある。
+pub fn start_self_check(
+    output_dir: PathBuf,
+    engine: Option<SearchEngine>,
+    tx: mpsc::Sender<SelfCheckState>,
+) {
+    thread::spawn(move || {
+        let mut results = vec![
+            check_tool(CheckItem::YtDlp, &yt_dlp_path()),
+            check_tool(CheckItem::Ffmpeg, &ffmpeg_path()),
+            check_tool(CheckItem::Ffprobe, &ffprobe_path()),
+        ];
+        // Deno 以外のランタイムを選んでいる場合は、Deno が無くてもよい。
+        if load_js_runtime() == JsRuntime::Deno {
+            results.push(check_tool(CheckItem::Deno, &deno_path()));
+        }
+        results.push(check_videotoolbox(&ffmpeg_path()));
+        results.push(match check_writable(&output_dir) {
+            Ok(()) => CheckResult::passed(CheckItem::OutputDir, output_dir.display().to_string()),
+            Err(err) => CheckResult::failed(
+                CheckItem::OutputDir,
+                format!("{}: {err}", output_dir.display()),
+                Msg::FixOutputDir,
+            ),
+        });
+        results.push(match engine.as_ref().map(SearchEngine::check_integrity) {
+            Some(Ok(())) => CheckResult::passed(CheckItem::SearchIndex, "integrity_check: ok"),
+            Some(Err(err)) => {
+                CheckResult::failed(CheckItem::SearchIndex, err, Msg::FixSearchIndexCorrupt)
+            }
+            None => CheckResult::failed(
+                CheckItem::SearchIndex,
+                tr(Msg::SearchNotReady),
+                Msg::FixSearchIndexNotReady,
+            ),
+        });
+        results.push(if network::check_online() {
+            CheckResult::passed(CheckItem::Network, tr(Msg::CheckNetworkOnline))
+        } else {
+            CheckResult::failed(CheckItem::Network, tr(Msg::OfflineError), Msg::FixNetwork)
+        });
+        let _ = tx.send(SelfCheckState::Done(results));
+    });
+}
+
+fn check_tool(item: CheckItem, path: &Path) -> CheckResult {
+    if !path.exists() {
+        return CheckResult::failed(
+            item,
+            tr_fmt(Msg::CheckToolMissing, &[&path.display()]),
+            Msg::FixToolSetup,
+        );
+    }
+    if !is_executable(path) {
+        return CheckResult::failed(
+            item,
+            tr_fmt(Msg::CheckToolNotExecutable, &[&path.display()]),
+            Msg::FixToolSetup,
+        );
+    }
+    CheckResult::passed(item, path.display().to_string())
+}
+
+// h264_videotoolbox が使えない場合は libx264 で変換するため、失敗ではなく遅くなることを知らせる。
+fn check_videotoolbox(ffmpeg: &Path) -> CheckResult {
+    let item = CheckItem::VideoToolbox;
+    if !ffmpeg.exists() {
+        return CheckResult::failed(
+            item,
+            tr_fmt(Msg::CheckToolMissing, &[&ffmpeg.display()]),
+            Msg::FixToolSetup,
+        );
+    }
+    match detect_encoder_profile(ffmpeg, EncoderProfile::VideoToolboxH264) {
+        Ok(EncoderProfile::VideoToolboxH264) => CheckResult::passed(item, "h264_videotoolbox"),
+        Ok(profile) => CheckResult::failed(
+            item,
+            tr_fmt(Msg::CheckVideoToolboxMissing, &[&encoder_label(profile)]),
+            Msg::FixVideoToolbox,
+        ),
+        Err(err) => CheckResult::failed(item, err, Msg::FixVideoToolbox),
+    }
+}
+
+// フォルダが無ければ作り（ダウンロード時と同じ）、ファイルを書いて消せるかを確かめる。
+fn check_writable(dir: &Path) -> Result<(), String> {
+    ensure_dir(dir)?;
+    let probe = dir.join(WRITE_PROBE_NAME);
+    fs::write(&probe, b"").map_err(|err| err.to_string())?;
+    fs::remove_file(&probe).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn checks_write_access_without_leaving_files() {
+        let dir = tempdir().unwrap();
+        let output = dir.path().join("downloads");
+        check_writable(&output).unwrap();
+        assert!(output.is_dir());
+        assert_eq!(fs::read_dir(&output).unwrap().count(), 0);
+
+        // 同じ名前のファイルがある場合はフォルダを作れない。
+        let blocked = dir.path().join("file");
+        fs::write(&blocked, b"").unwrap();
+        assert!(check_writable(&blocked).is_err());
+    }
+
+    #[test]
+    fn reports_missing_tools_with_fix() {
+        let dir = tempdir().unwrap();
+        let result = check_tool(CheckItem::YtDlp, &dir.path().join("yt-dlp"));
+        assert!(!result.passed);
+        assert!(result.fix.is_some());
+        assert!(result.detail.contains("yt-dlp"));
+    }
+}
@@ -1,8 +1,612 @@
-use std::collections::HashMap;
-use std::fs;
+mod file;
+
 use std::path::PathBuf;
 
-use crate::paths::{default_download_dir, make_absolute_path, settings_file_path};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::diagnostics::shell_join;
+use crate::download::{DEFAULT_TARGET_SIZE_MB, QualityMode};
+use crate::event_stream::{self, DEFAULT_EVENT_STREAM_PORT};
+use crate::i18n::{Msg, tr, tr_fmt};
+use crate::mac_input_source::InputMode;
+use crate::mac_keychain::read_login_password;
+use crate::osc::{self, DEFAULT_OSC_PORT};
+use crate::paths::{default_download_dir, default_index_backup_dir, make_absolute_path};
+use crate::redact::{REDACTED, redact_args};
+use crate::search_index::SkipRules;
+use crate::shortcuts::{ShortcutAction, ShortcutBindings};
+
+use file::{
+    AuthSection, CloudFilesSection, CookiesSection, DEFAULT_MAIN_PANEL_WIDTH,
+    DEFAULT_SEARCH_WINDOW_WIDTH, DEFAULT_WINDOW_HEIGHT, DEFAULT_WINDOW_WIDTH, DownloadSection,
+    EnabledSection, EncoderSection, EventsSection, FinderTagsSection, FragmentsSection,
+    FromBrowserSection, HotkeySection, IndexBackupSection, IndexSkipSection, LayoutSection,
+    NetworkSection, OscSection, PanelSection, PowerSection, PresetsSection, ProfilesSection,
+    PromptSection, SETTINGS_VERSION, SearchSection, SearchWindowSection, SettingsFile,
+    ShortcutsSection, StallSection, SubscriptionsSection, ToolsSection, UiSection, WatchSection,
+    WatermarkSection, WebSocketSection, WindowSection, YtDlpSection, load_settings_file,
+    save_settings_file,
+};
+
+// yt-dlp の取得元。ナイトリーは yt-dlp-nightly-builds のリリースを使う。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum YtDlpChannel {
+    Stable,
+    Nightly,
+}
+
+// 設定ファイルには as_key の文字列で保存し、知らない値は既定値として読む。
+impl From<String> for YtDlpChannel {
+    fn from(raw: String) -> Self {
+        Self::from_key(&raw)
+    }
+}
+
+impl From<YtDlpChannel> for String {
+    fn from(value: YtDlpChannel) -> Self {
+        value.as_key().to_string()
+    }
+}
+
+impl YtDlpChannel {
+    fn from_key(raw: &str) -> Self {
+        if raw.trim().eq_ignore_ascii_case("nightly") {
+            YtDlpChannel::Nightly
+        } else {
+            YtDlpChannel::Stable
+        }
+    }
+
+    fn as_key(self) -> &'static str {
+        match self {
+            YtDlpChannel::Stable => "stable",
+            YtDlpChannel::Nightly => "nightly",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            YtDlpChannel::Stable => tr(Msg::ChannelStable),
+            YtDlpChannel::Nightly => tr(Msg::ChannelNightly),
+        }
+    }
+}
+
+// yt-dlp が YouTube の署名の解読に使う JavaScript ランタイム。Deno だけは自動で取得する。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum JsRuntime {
+    #[default]
+    Deno,
+    Node,
+    Disabled,
+}
+
+impl From<String> for JsRuntime {
+    fn from(raw: String) -> Self {
+        Self::from_key(&raw)
+    }
+}
+
+impl From<JsRuntime> for String {
+    fn from(value: JsRuntime) -> Self {
+        value.as_key().to_string()
+    }
+}
+
+impl JsRuntime {
+    pub const ALL: [JsRuntime; 3] = [JsRuntime::Deno, JsRuntime::Node, JsRuntime::Disabled];
+
+    fn from_key(raw: &str) -> Self {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "node" => JsRuntime::Node,
+            "none" => JsRuntime::Disabled,
+            _ => JsRuntime::Deno,
+        }
+    }
+
+    fn as_key(self) -> &'static str {
+        match self {
+            JsRuntime::Deno => "deno",
+            JsRuntime::Node => "node",
+            JsRuntime::Disabled => "none",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            JsRuntime::Deno => "Deno",
+            JsRuntime::Node => "Node.js",
+            JsRuntime::Disabled => tr(Msg::JsRuntimeDisabled),
+        }
+    }
+}
+
+// mp4 変換に使うエンコード方式。Copy は映像を再エンコードせず mp4 へ詰め替える。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum EncoderProfile {
+    VideoToolboxH264,
+    VideoToolboxHevc,
+    X264,
+    Copy,
+}
+
+impl From<String> for EncoderProfile {
+    fn from(raw: String) -> Self {
+        Self::from_key(&raw)
+    }
+}
+
+impl From<EncoderProfile> for String {
+    fn from(value: EncoderProfile) -> Self {
+        value.as_key().to_string()
+    }
+}
+
+impl EncoderProfile {
+    pub const ALL: [EncoderProfile; 4] = [
+        EncoderProfile::VideoToolboxH264,
+        EncoderProfile::VideoToolboxHevc,
+        EncoderProfile::X264,
+        EncoderProfile::Copy,
+    ];
+
+    fn from_key(raw: &str) -> Self {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "videotoolbox_hevc" => EncoderProfile::VideoToolboxHevc,
+            "libx264" => EncoderProfile::X264,
+            "copy" => EncoderProfile::Copy,
+            _ => EncoderProfile::VideoToolboxH264,
+        }
+    }
+
+    fn as_key(self) -> &'static str {
+        match self {
+            EncoderProfile::VideoToolboxH264 => "videotoolbox_h264",
+            EncoderProfile::VideoToolboxHevc => "videotoolbox_hevc",
+            EncoderProfile::X264 => "libx264",
+            EncoderProfile::Copy => "copy",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            EncoderProfile::VideoToolboxH264 => tr(Msg::EncoderVideoToolboxH264),
+            EncoderProfile::VideoToolboxHevc => tr(Msg::EncoderVideoToolboxHevc),
+            EncoderProfile::X264 => tr(Msg::EncoderX264),
+            EncoderProfile::Copy => tr(Msg::EncoderCopy),
+        }
+    }
+}
+
+// 変換時に重ねる透かしの位置
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum WatermarkPosition {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    Center,
+}
+
+impl From<String> for WatermarkPosition {
+    fn from(raw: String) -> Self {
+        Self::from_key(&raw)
+    }
+}
+
+impl From<WatermarkPosition> for String {
+    fn from(value: WatermarkPosition) -> Self {
+        value.as_key().to_string()
+    }
+}
+
+impl WatermarkPosition {
+    pub const ALL: [WatermarkPosition; 5] = [
+        WatermarkPosition::TopLeft,
+        WatermarkPosition::TopRight,
+        WatermarkPosition::BottomLeft,
+        WatermarkPosition::BottomRight,
+        WatermarkPosition::Center,
+    ];
+
+    fn from_key(raw: &str) -> Self {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "top_left" => WatermarkPosition::TopLeft,
+            "top_right" => WatermarkPosition::TopRight,
+            "bottom_left" => WatermarkPosition::BottomLeft,
+            "center" => WatermarkPosition::Center,
+            _ => WatermarkPosition::BottomRight,
+        }
+    }
+
+    fn as_key(self) -> &'static str {
+        match self {
+            WatermarkPosition::TopLeft => "top_left",
+            WatermarkPosition::TopRight => "top_right",
+            WatermarkPosition::BottomLeft => "bottom_left",
+            WatermarkPosition::BottomRight => "bottom_right",
+            WatermarkPosition::Center => "center",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            WatermarkPosition::TopLeft => tr(Msg::TopLeft),
+            WatermarkPosition::TopRight => tr(Msg::TopRight),
+            WatermarkPosition::BottomLeft => tr(Msg::BottomLeft),
+            WatermarkPosition::BottomRight => tr(Msg::BottomRight),
+            WatermarkPosition::Center => tr(Msg::Center),
+        }
+    }
+}
+
+// 保存先に同名のファイルがある場合の扱い
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum CollisionPolicy {
+    Overwrite,
+    #[default]
+    Rename,
+    Skip,
+    Ask,
+}
+
+impl From<String> for CollisionPolicy {
+    fn from(raw: String) -> Self {
+        Self::from_key(&raw)
+    }
+}
+
+impl From<CollisionPolicy> for String {
+    fn from(value: CollisionPolicy) -> Self {
+        value.as_key().to_string()
+    }
+}
+
+impl CollisionPolicy {
+    pub const ALL: [CollisionPolicy; 4] = [
+        CollisionPolicy::Rename,
+        CollisionPolicy::Overwrite,
+        CollisionPolicy::Skip,
+        CollisionPolicy::Ask,
+    ];
+
+    fn from_key(raw: &str) -> Self {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "overwrite" => CollisionPolicy::Overwrite,
+            "skip" => CollisionPolicy::Skip,
+            "ask" => CollisionPolicy::Ask,
+            _ => CollisionPolicy::Rename,
+        }
+    }
+
+    fn as_key(self) -> &'static str {
+        match self {
+            CollisionPolicy::Overwrite => "overwrite",
+            CollisionPolicy::Rename => "rename",
+            CollisionPolicy::Skip => "skip",
+            CollisionPolicy::Ask => "ask",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CollisionPolicy::Overwrite => tr(Msg::CollisionOverwrite),
+            CollisionPolicy::Rename => tr(Msg::SaveWithNumber),
+            CollisionPolicy::Skip => tr(Msg::DontSave),
+            CollisionPolicy::Ask => tr(Msg::CollisionAsk),
+        }
+    }
+}
+
+// URL から作るファイル名に残す文字
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum FilenameMode {
+    // 英数字と - _ . 以外を _ に置き換える。
+    #[default]
+    Ascii,
+    // APFS で使えない / と : だけを置き換え、日本語などはそのまま残す。
+    Unicode,
+}
+
+impl From<String> for FilenameMode {
+    fn from(raw: String) -> Self {
+        Self::from_key(&raw)
+    }
+}
+
+impl From<FilenameMode> for String {
+    fn from(value: FilenameMode) -> Self {
+        value.as_key().to_string()
+    }
+}
+
+impl FilenameMode {
+    pub const ALL: [FilenameMode; 2] = [FilenameMode::Ascii, FilenameMode::Unicode];
+
+    fn from_key(raw: &str) -> Self {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "unicode" => FilenameMode::Unicode,
+            _ => FilenameMode::Ascii,
+        }
+    }
+
+    fn as_key(self) -> &'static str {
+        match self {
+            FilenameMode::Ascii => "ascii",
+            FilenameMode::Unicode => "unicode",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            FilenameMode::Ascii => tr(Msg::FilenameAscii),
+            FilenameMode::Unicode => tr(Msg::FilenameUnicode),
+        }
+    }
+}
+
+// 検索欄にフォーカスしたときに切り替える入力モード
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum SearchInputMode {
+    // 入力ソースを変えない。
+    #[default]
+    Keep,
+    English,
+    Japanese,
+}
+
+impl From<String> for SearchInputMode {
+    fn from(raw: String) -> Self {
+        Self::from_key(&raw)
+    }
+}
+
+impl From<SearchInputMode> for String {
+    fn from(value: SearchInputMode) -> Self {
+        value.as_key().to_string()
+    }
+}
+
+impl SearchInputMode {
+    pub const ALL: [SearchInputMode; 3] = [
+        SearchInputMode::Keep,
+        SearchInputMode::English,
+        SearchInputMode::Japanese,
+    ];
+
+    fn from_key(raw: &str) -> Self {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "english" => SearchInputMode::English,
+            "japanese" => SearchInputMode::Japanese,
+            _ => SearchInputMode::Keep,
+        }
+    }
+
+    fn as_key(self) -> &'static str {
+        match self {
+            SearchInputMode::Keep => "keep",
+            SearchInputMode::English => "english",
+            SearchInputMode::Japanese => "japanese",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SearchInputMode::Keep => tr(Msg::SearchInputKeep),
+            SearchInputMode::English => tr(Msg::SearchInputEnglish),
+            SearchInputMode::Japanese => tr(Msg::SearchInputJapanese),
+        }
+    }
+
+    // 切り替え先の入力モード。Keep の場合は切り替えない。
+    pub fn target(self) -> Option<InputMode> {
+        match self {
+            SearchInputMode::Keep => None,
+            SearchInputMode::English => Some(InputMode::English),
+            SearchInputMode::Japanese => Some(InputMode::Japanese),
+        }
+    }
+}
+
+// 画面の言語。既定は日本語。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum Language {
+    #[default]
+    Japanese,
+    English,
+}
+
+impl From<String> for Language {
+    fn from(raw: String) -> Self {
+        Self::from_key(&raw)
+    }
+}
+
+impl From<Language> for String {
+    fn from(value: Language) -> Self {
+        value.as_key().to_string()
+    }
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::Japanese, Language::English];
+
+    fn from_key(raw: &str) -> Self {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "en" | "english" => Language::English,
+            _ => Language::Japanese,
+        }
+    }
+
+    fn as_key(self) -> &'static str {
+        match self {
+            Language::Japanese => "ja",
+            Language::English => "en",
+        }
+    }
+
+    // どちらの言語を表示中でも選べるよう、それぞれの言語で表記する。
+    pub fn label(self) -> &'static str {
+        match self {
+            Language::Japanese => "日本語",
+            Language::English => "English",
+        }
+    }
+}
+
+// 名前を付けて切り替えられる設定のまとまり（例: Club 1080p60、Quick preview 720p）。
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SettingsProfile {
+    pub name: String,
+    pub download_dir: String,
+    pub encoder_profile: EncoderProfile,
+    // 取得する映像の高さの上限。0 は制限なし。
+    pub max_height: u32,
+}
+
+impl Default for SettingsProfile {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            download_dir: String::new(),
+            encoder_profile: EncoderProfile::VideoToolboxH264,
+            max_height: 0,
+        }
+    }
+}
+
+// メイン画面のプリセットボタンの数
+pub const PRESET_COUNT: usize = 3;
+
+// メイン画面のボタンから、そのダウンロードだけに使う設定のまとまり。名前が空欄のボタンは表示しない。
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DownloadPreset {
+    pub name: String,
+    pub encoder_profile: EncoderProfile,
+    // 取得する映像の高さの上限。0 は制限なし。
+    pub max_height: u32,
+    pub quality: QualityMode,
+}
+
+impl Default for DownloadPreset {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            encoder_profile: EncoderProfile::VideoToolboxH264,
+            max_height: 0,
+            quality: QualityMode::Standard,
+        }
+    }
+}
+
+impl DownloadPreset {
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self {
+                name: "1080p H.264".to_string(),
+                encoder_profile: EncoderProfile::VideoToolboxH264,
+                max_height: 1080,
+                quality: QualityMode::Standard,
+            },
+            Self {
+                name: "2160p HEVC".to_string(),
+                encoder_profile: EncoderProfile::VideoToolboxHevc,
+                max_height: 2160,
+                quality: QualityMode::ConstantQuality,
+            },
+            Self {
+                name: format!("720p {DEFAULT_TARGET_SIZE_MB}MB"),
+                encoder_profile: EncoderProfile::X264,
+                max_height: 720,
+                quality: QualityMode::TargetSize(DEFAULT_TARGET_SIZE_MB),
+            },
+        ]
+    }
+}
+
+// 名前を付けて切り替えられるクッキーの取得元（ブラウザとプロファイルの組）。
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CookieAccount {
+    pub name: String,
+    pub browser: String,
+    pub profile: String,
+}
+
+// ログインが必要なサイトのユーザー名。パスワードはキーチェーンに保存する。
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SiteLogin {
+    // ドメイン（例: nicovideo.jp）。サブドメインの URL にも使う。
+    pub site: String,
+    pub username: String,
+    // 設定画面で入力したパスワード。設定ファイルには書かず、保存時にキーチェーンへ移す。
+    #[serde(skip)]
+    pub password: String,
+}
+
+impl SiteLogin {
+    fn matches_host(&self, host: &str) -> bool {
+        let site = self.site.trim().trim_start_matches("www.");
+        !site.is_empty()
+            && (host.eq_ignore_ascii_case(site)
+                || host
+                    .to_ascii_lowercase()
+                    .ends_with(&format!(".{}", site.to_ascii_lowercase())))
+    }
+}
+
+// yt-dlp で取得する映像の高さの上限の選択肢。0 は制限なし。
+pub const MAX_HEIGHT_CHOICES: [u32; 6] = [0, 2160, 1440, 1080, 720, 480];
+
+// yt-dlp の --concurrent-fragments に渡せる範囲
+pub const DEFAULT_CONCURRENT_FRAGMENTS: u8 = 4;
+pub const MAX_CONCURRENT_FRAGMENTS: u8 = 16;
+
+// 外部ツールの出力が止まってから中断するまでの分数
+pub const DEFAULT_STALL_MINUTES: u32 = 5;
+pub const MAX_STALL_MINUTES: u32 = 60;
+
+// 同時に行う変換の数。VideoToolbox は同時に多く動かすと互いに遅くなるため、上限を低くする。
+pub const DEFAULT_PARALLEL_CONVERSIONS: u8 = 1;
+pub const MAX_PARALLEL_CONVERSIONS: u8 = 4;
+
+// シーン分割でカットとみなす映像の変化量（%）
+pub const DEFAULT_SCENE_THRESHOLD: u8 = 40;
+pub const MIN_SCENE_THRESHOLD: u8 = 5;
+pub const MAX_SCENE_THRESHOLD: u8 = 95;
+
+// 検索インデックスをバックアップする間隔（時間）と、残すバックアップの数
+pub const DEFAULT_INDEX_BACKUP_HOURS: u32 = 24;
+pub const MAX_INDEX_BACKUP_HOURS: u32 = 24 * 7;
+pub const INDEX_BACKUP_KEEP: usize = 7;
+
+// 購読したチャンネル・プレイリストを確認する間隔（分）
+pub const DEFAULT_SUBSCRIPTION_MINUTES: u32 = 60;
+pub const MIN_SUBSCRIPTION_MINUTES: u32 = 15;
+pub const MAX_SUBSCRIPTION_MINUTES: u32 = 24 * 60;
+
+// 最近使った保存先として覚えておく件数
+pub const RECENT_DOWNLOAD_DIR_LIMIT: usize = 5;
+
+pub fn max_height_label(max_height: u32) -> String {
+    if max_height == 0 {
+        tr(Msg::NoLimit).to_string()
+    } else {
+        format!("{max_height}p")
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct SettingsData {
@@ -10,117 +614,528 @@ pub struct SettingsData {
     pub window_height: String,
     pub download_panel_width: String,
     pub search_panel_width: String,
+    // 検索をメイン画面から切り離して別ウィンドウに出すかと、そのウィンドウの大きさ
+    pub search_window_detached: bool,
+    pub search_window_width: String,
+    pub search_window_height: String,
     pub download_dir: String,
+    pub language: Language,
     pub search_roots: Vec<String>,
+    // シンボリックリンクをたどる検索対象フォルダ
+    pub search_follow_links: Vec<String>,
+    pub search_history: Vec<String>,
+    pub search_read_finder_tags: bool,
+    pub search_input_mode: SearchInputMode,
+    // 入力ソースを切り替えたときにステータスへ表示する。
+    pub input_source_notify: bool,
+    // 検索インデックスの定期バックアップと、その保存先（空欄は既定のフォルダ）・間隔（時間）
+    pub index_backup_enabled: bool,
+    pub index_backup_dir: String,
+    pub index_backup_hours: u32,
+    // インデックスで除外する項目と、追加で除外する名前（カンマ区切り）
+    pub index_skip_hidden: bool,
+    pub index_skip_bundles: bool,
+    pub index_skip_system: bool,
+    pub index_skip_names: String,
+    // クラウドにだけあるファイルをドラッグしようとした時に、取得を始める
+    pub cloud_fetch_on_drag: bool,
     pub cookies_enabled: bool,
     pub cookies_browser: String,
     pub cookies_profile: String,
+    pub cookie_accounts: Vec<CookieAccount>,
+    // ダウンロード画面で選択中のアカウント名。空欄なら上のクッキー設定を使う。
+    pub active_cookie_account: String,
+    pub site_logins: Vec<SiteLogin>,
+    pub hotkey_summon: String,
+    pub shortcuts: ShortcutBindings,
+    pub yt_dlp_channel: YtDlpChannel,
+    // 空欄なら最新を取得し、指定時はそのリリースタグに固定する。
+    pub yt_dlp_version: String,
+    // yt-dlp へそのまま渡す詳細オプション。空欄なら何も足さない。
+    pub yt_dlp_extra_args: String,
+    pub js_runtime: JsRuntime,
+    pub encoder_profile: EncoderProfile,
+    // インターレース解除と非正方ピクセルの補正を ffprobe の結果に応じて行う。
+    pub auto_correct_video: bool,
+    // 互換モードの変換時に黒帯を検出し、プレビューで確認してから切り取る。
+    pub crop_detect: bool,
+    // 前のクリップの変換中に次のダウンロードを進める場合の、同時に行う変換の数
+    pub parallel_conversions: u8,
+    // 保存した動画をシーンの切り替わりで別のクリップに分割する。閾値は変化量（%）。
+    pub scene_split: bool,
+    pub scene_threshold: u8,
+    // チャプターのある動画をチャプターごとに別のクリップに分割する。分割した動画はシーン分割しない。
+    pub chapter_split: bool,
+    // 変換するクリップに重ねる PNG。空欄なら重ねない。
+    pub watermark_path: String,
+    pub watermark_position: WatermarkPosition,
+    // 透かしの不透明度（0〜100%）
+    pub watermark_opacity: u8,
+    // 音声トラックが複数ある場合に、ダウンロード前に使うトラックを選ぶ。
+    pub audio_track_prompt: bool,
+    // ダウンロード前にタイトル・長さ・投稿者を表示して確認する。
+    pub preview_prompt: bool,
+    // yt-dlp で取得する映像の高さの上限。0 は制限なし。
+    pub download_max_height: u32,
+    // yt-dlp が同時に取得する断片の数（自動調整する場合は上限）
+    pub concurrent_fragments: u8,
+    pub auto_concurrent_fragments: bool,
+    pub use_aria2c: bool,
+    // 外部ツールの出力が止まってから中断するまでの分数（0 は中断しない）と、中断後に自動でやり直すか
+    pub stall_minutes: u32,
+    pub stall_retry: bool,
+    pub collision_policy: CollisionPolicy,
+    pub filename_mode: FilenameMode,
+    // 最近使った保存先（新しい順）
+    pub recent_download_dirs: Vec<String>,
+    pub profiles: Vec<SettingsProfile>,
+    // 選択中のプロファイル名。空欄ならプロファイルを使っていない。
+    pub active_profile: String,
+    // 常に PRESET_COUNT 件。名前が空欄のものはボタンを表示しない。
+    pub presets: Vec<DownloadPreset>,
+    pub notifications_enabled: bool,
+    pub status_item_enabled: bool,
+    pub osc_enabled: bool,
+    pub osc_port: String,
+    pub osc_feedback: String,
+    pub osc_allow_lan: bool,
+    pub event_stream_enabled: bool,
+    pub event_stream_port: String,
+    // 接続を許すブラウザの Origin（カンマ区切り）
+    pub event_stream_origins: String,
+    // 置かれた動画を変換して保存先へ移すフォルダ
+    pub watch_folder_enabled: bool,
+    pub watch_folder_dir: String,
+    // バッテリー駆動中にインデックスを控えめにするか、GPU の変換を電源につなぐまで待たせるか
+    pub battery_saver: bool,
+    pub defer_gpu_conversions: bool,
+    // 新しい動画を確認するチャンネル・プレイリストの URL と、確認する間隔（分）
+    pub subscriptions_enabled: bool,
+    pub subscription_sources: Vec<String>,
+    pub subscription_interval_minutes: u32,
+    // 見つけた動画を待機キューへ追加する（無効なら知らせるだけ）
+    pub subscription_auto_queue: bool,
+    // ダウンロードの前にネットワーク接続を確認する
+    pub check_connection: bool,
 }
 
 impl SettingsData {
     pub fn load() -> Self {
-        let props = load_settings_properties();
-        let window_width = parse_dimension(
-            props.get("window.width"),
-            DEFAULT_WINDOW_WIDTH,
-            MIN_WINDOW_WIDTH,
-        );
-        let window_height = parse_dimension(
-            props.get("window.height"),
-            DEFAULT_WINDOW_HEIGHT,
-            MIN_WINDOW_HEIGHT,
-        );
-        let download_panel_width = parse_dimension(
-            props.get("layout.download.width"),
-            DEFAULT_MAIN_PANEL_WIDTH,
-            MIN_MAIN_PANEL_WIDTH,
-        );
-        let search_panel_width = parse_dimension(
-            props.get("layout.search.width"),
-            DEFAULT_MAIN_PANEL_WIDTH,
-            MIN_MAIN_PANEL_WIDTH,
-        );
-        let download_dir = props
-            .get("download.dir")
-            .map(|value| normalize_dir(value))
-            .unwrap_or_else(default_download_dir)
+        Self::from_file(load_settings_file())
+    }
+
+    fn from_file(file: SettingsFile) -> Self {
+        let download_dir = normalize_dir(&file.download.dir)
             .to_string_lossy()
             .to_string();
-        let search_roots = props
-            .get("search.roots")
-            .map(|value| decode_path_list(value))
-            .unwrap_or_default()
-            .into_iter()
-            .map(|raw| normalize_dir(&raw).to_string_lossy().to_string())
+        let search_roots = file
+            .search
+            .roots
+            .iter()
+            .map(|raw| normalize_dir(raw).to_string_lossy().to_string())
             .collect();
-        let cookies_enabled = props
-            .get("cookies.from_browser.enabled")
-            .map(|v| parse_bool(v, false))
-            .unwrap_or(false);
-        let cookies_browser = props
-            .get("cookies.from_browser.browser")
-            .map(|v| v.trim().to_string())
-            .unwrap_or_default();
-        let cookies_profile = props
-            .get("cookies.from_browser.profile")
-            .map(|v| v.trim().to_string())
-            .unwrap_or_default();
+        // 削除済みのプロファイル名は選択していない扱いにする。
+        let active_profile = if file
+            .profiles
+            .items
+            .iter()
+            .any(|profile| profile.name == file.profiles.active)
+        {
+            file.profiles.active.clone()
+        } else {
+            String::new()
+        };
+        let active_cookie_account = if file
+            .cookies
+            .accounts
+            .iter()
+            .any(|account| account.name == file.cookies.active)
+        {
+            file.cookies.active.clone()
+        } else {
+            String::new()
+        };
         Self {
-            window_width: format_dimension(window_width),
-            window_height: format_dimension(window_height),
-            download_panel_width: format_dimension(download_panel_width),
-            search_panel_width: format_dimension(search_panel_width),
+            window_width: format_dimension(file.window.width.max(MIN_WINDOW_WIDTH)),
+            window_height: format_dimension(file.window.height.max(MIN_WINDOW_HEIGHT)),
+            download_panel_width: format_dimension(
+                file.layout.download.width.max(MIN_MAIN_PANEL_WIDTH),
+            ),
+            search_panel_width: format_dimension(
+                file.layout.search.width.max(MIN_MAIN_PANEL_WIDTH),
+            ),
+            search_window_detached: file.layout.search_window.detached,
+            search_window_width: format_dimension(
+                file.layout.search_window.width.max(MIN_SEARCH_WINDOW_WIDTH),
+            ),
+            search_window_height: format_dimension(
+                file.layout.search_window.height.max(MIN_WINDOW_HEIGHT),
+            ),
             download_dir,
+            language: file.ui.language,
             search_roots,
-            cookies_enabled,
-            cookies_browser,
-            cookies_profile,
+            search_follow_links: file
+                .search
+                .follow_links
+                .iter()
+                .map(|raw| normalize_dir(raw).to_string_lossy().to_string())
+                .collect(),
+            search_history: file.search.history,
+            search_read_finder_tags: file.search.finder_tags.read,
+            search_input_mode: file.search.input_mode,
+            input_source_notify: file.ui.input_source_notify,
+            index_backup_enabled: file.search.backup.enabled,
+            index_backup_dir: file.search.backup.dir.trim().to_string(),
+            index_backup_hours: clamp_index_backup_hours(file.search.backup.hours),
+            index_skip_hidden: file.search.skip.hidden,
+            index_skip_bundles: file.search.skip.bundles,
+            index_skip_system: file.search.skip.system,
+            index_skip_names: file.search.skip.names.join(", "),
+            cloud_fetch_on_drag: file.search.cloud.fetch_on_drag,
+            cookies_enabled: file.cookies.from_browser.enabled,
+            cookies_browser: file.cookies.from_browser.browser.trim().to_string(),
+            cookies_profile: file.cookies.from_browser.profile.trim().to_string(),
+            cookie_accounts: file.cookies.accounts,
+            active_cookie_account,
+            site_logins: file.auth.logins,
+            hotkey_summon: file.hotkey.summon.trim().to_string(),
+            shortcuts: {
+                let mut shortcuts = ShortcutBindings::default();
+                for action in ShortcutAction::ALL {
+                    shortcuts.set(action, file.shortcuts.get(action).trim().to_string());
+                }
+                shortcuts
+            },
+            yt_dlp_channel: file.tools.yt_dlp.channel,
+            yt_dlp_version: file.tools.yt_dlp.version.trim().to_string(),
+            yt_dlp_extra_args: file.tools.yt_dlp.extra_args.trim().to_string(),
+            js_runtime: file.tools.js_runtime,
+            encoder_profile: file.encoder.profile,
+            auto_correct_video: file.encoder.auto_correct,
+            crop_detect: file.encoder.crop_detect,
+            parallel_conversions: clamp_parallel_conversions(file.encoder.parallel),
+            scene_split: file.encoder.scene_split,
+            scene_threshold: clamp_scene_threshold(file.encoder.scene_threshold),
+            chapter_split: file.encoder.chapter_split,
+            watermark_path: file.watermark.path.trim().to_string(),
+            watermark_position: file.watermark.position,
+            watermark_opacity: file.watermark.opacity.min(100),
+            audio_track_prompt: file.download.audio_track.prompt,
+            preview_prompt: file.download.preview.prompt,
+            download_max_height: file.download.max_height,
+            concurrent_fragments: clamp_fragments(file.download.fragments.count),
+            auto_concurrent_fragments: file.download.fragments.auto,
+            use_aria2c: file.download.aria2c.enabled,
+            stall_minutes: file.download.stall.minutes.min(MAX_STALL_MINUTES),
+            stall_retry: file.download.stall.retry,
+            collision_policy: file.download.collision,
+            filename_mode: file.download.filename_mode,
+            recent_download_dirs: file.download.recent_dirs,
+            active_profile,
+            profiles: file.profiles.items,
+            presets: {
+                let mut presets = file.presets.items;
+                presets.resize_with(PRESET_COUNT, DownloadPreset::default);
+                presets
+            },
+            notifications_enabled: file.notifications.enabled,
+            status_item_enabled: file.status_item.enabled,
+            osc_enabled: file.osc.enabled,
+            osc_port: file.osc.port.to_string(),
+            osc_feedback: file.osc.feedback.trim().to_string(),
+            osc_allow_lan: file.osc.allow_lan,
+            event_stream_enabled: file.events.websocket.enabled,
+            event_stream_port: file.events.websocket.port.to_string(),
+            event_stream_origins: file.events.websocket.allowed_origins.join(", "),
+            watch_folder_enabled: file.watch.enabled,
+            watch_folder_dir: file.watch.dir.trim().to_string(),
+            battery_saver: file.power.battery_saver,
+            defer_gpu_conversions: file.power.defer_gpu_conversions,
+            subscriptions_enabled: file.subscriptions.enabled,
+            subscription_sources: clean_subscription_sources(&file.subscriptions.sources),
+            subscription_interval_minutes: clamp_subscription_minutes(
+                file.subscriptions.interval_minutes,
+            ),
+            subscription_auto_queue: file.subscriptions.auto_queue,
+            check_connection: file.network.check_connection,
         }
     }
 
     pub fn save(&self) -> Result<(), String> {
-        let path = settings_file_path();
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent).map_err(|err| err.to_string())?;
-        }
-        fs::write(path, self.to_properties_string()).map_err(|err| err.to_string())
-    }
-
-    fn to_properties_string(&self) -> String {
-        let mut lines = Vec::new();
-        lines.push(format!("window.width={}", self.window_width.trim()));
-        lines.push(format!("window.height={}", self.window_height.trim()));
-        lines.push(format!(
-            "layout.download.width={}",
-            self.download_panel_width.trim()
-        ));
-        lines.push(format!(
-            "layout.search.width={}",
-            self.search_panel_width.trim()
-        ));
-        let download_dir = self.download_dir.trim();
-        lines.push(format!("download.dir={download_dir}"));
-        lines.push(format!(
-            "search.roots={}",
-            encode_path_list(&self.search_roots)
-        ));
-        lines.push(format!(
-            "cookies.from_browser.enabled={}",
-            if self.cookies_enabled {
-                "true"
-            } else {
-                "false"
-            }
-        ));
-        lines.push(format!(
-            "cookies.from_browser.browser={}",
-            self.cookies_browser.trim()
-        ));
-        lines.push(format!(
-            "cookies.from_browser.profile={}",
-            self.cookies_profile.trim()
-        ));
-        lines.join("\n")
+        save_settings_file(&self.to_file())
+    }
+
+    fn to_file(&self) -> SettingsFile {
+        SettingsFile {
+            version: SETTINGS_VERSION,
+            window: WindowSection {
+                width: parse_dimension(&self.window_width, DEFAULT_WINDOW_WIDTH),
+                height: parse_dimension(&self.window_height, DEFAULT_WINDOW_HEIGHT),
+            },
+            layout: LayoutSection {
+                download: PanelSection {
+                    width: parse_dimension(&self.download_panel_width, DEFAULT_MAIN_PANEL_WIDTH),
+                },
+                search: PanelSection {
+                    width: parse_dimension(&self.search_panel_width, DEFAULT_MAIN_PANEL_WIDTH),
+                },
+                search_window: SearchWindowSection {
+                    detached: self.search_window_detached,
+                    width: parse_dimension(&self.search_window_width, DEFAULT_SEARCH_WINDOW_WIDTH),
+                    height: parse_dimension(&self.search_window_height, DEFAULT_WINDOW_HEIGHT),
+                },
+            },
+            ui: UiSection {
+                language: self.language,
+                input_source_notify: self.input_source_notify,
+            },
+            download: DownloadSection {
+                dir: self.download_dir.trim().to_string(),
+                max_height: self.download_max_height,
+                collision: self.collision_policy,
+                filename_mode: self.filename_mode,
+                recent_dirs: self.recent_download_dirs.clone(),
+                audio_track: PromptSection {
+                    prompt: self.audio_track_prompt,
+                },
+                preview: PromptSection {
+                    prompt: self.preview_prompt,
+                },
+                fragments: FragmentsSection {
+                    count: clamp_fragments(self.concurrent_fragments),
+                    auto: self.auto_concurrent_fragments,
+                },
+                aria2c: EnabledSection {
+                    enabled: self.use_aria2c,
+                },
+                stall: StallSection {
+                    minutes: self.stall_minutes.min(MAX_STALL_MINUTES),
+                    retry: self.stall_retry,
+                },
+            },
+            search: SearchSection {
+                roots: self.search_roots.clone(),
+                follow_links: self.search_follow_links.clone(),
+                history: self.search_history.clone(),
+                finder_tags: FinderTagsSection {
+                    read: self.search_read_finder_tags,
+                },
+                input_mode: self.search_input_mode,
+                backup: IndexBackupSection {
+                    enabled: self.index_backup_enabled,
+                    dir: self.index_backup_dir.trim().to_string(),
+                    hours: clamp_index_backup_hours(self.index_backup_hours),
+                },
+                skip: IndexSkipSection {
+                    hidden: self.index_skip_hidden,
+                    bundles: self.index_skip_bundles,
+                    system: self.index_skip_system,
+                    names: split_skip_names(&self.index_skip_names),
+                },
+                cloud: CloudFilesSection {
+                    fetch_on_drag: self.cloud_fetch_on_drag,
+                },
+            },
+            cookies: CookiesSection {
+                from_browser: FromBrowserSection {
+                    enabled: self.cookies_enabled,
+                    browser: self.cookies_browser.trim().to_string(),
+                    profile: self.cookies_profile.trim().to_string(),
+                },
+                active: self.active_cookie_account.clone(),
+                accounts: self
+                    .cookie_accounts
+                    .iter()
+                    .map(|account| CookieAccount {
+                        name: account.name.trim().to_string(),
+                        browser: account.browser.trim().to_string(),
+                        profile: account.profile.trim().to_string(),
+                    })
+                    .collect(),
+            },
+            auth: AuthSection {
+                logins: self
+                    .site_logins
+                    .iter()
+                    .map(|login| SiteLogin {
+                        site: login.site.trim().to_string(),
+                        username: login.username.trim().to_string(),
+                        password: String::new(),
+                    })
+                    .collect(),
+            },
+            hotkey: HotkeySection {
+                summon: self.hotkey_summon.trim().to_string(),
+            },
+            shortcuts: ShortcutsSection {
+                start_download: self.shortcut(ShortcutAction::StartDownload),
+                cancel: self.shortcut(ShortcutAction::Cancel),
+                focus_search: self.shortcut(ShortcutAction::FocusSearch),
+                open_settings: self.shortcut(ShortcutAction::OpenSettings),
+                open_logs: self.shortcut(ShortcutAction::OpenLogs),
+            },
+            tools: ToolsSection {
+                yt_dlp: YtDlpSection {
+                    channel: self.yt_dlp_channel,
+                    version: self.yt_dlp_version.trim().to_string(),
+                    extra_args: self.yt_dlp_extra_args.trim().to_string(),
+                },
+                js_runtime: self.js_runtime,
+            },
+            encoder: EncoderSection {
+                profile: self.encoder_profile,
+                auto_correct: self.auto_correct_video,
+                crop_detect: self.crop_detect,
+                parallel: clamp_parallel_conversions(self.parallel_conversions),
+                scene_split: self.scene_split,
+                scene_threshold: clamp_scene_threshold(self.scene_threshold),
+                chapter_split: self.chapter_split,
+            },
+            watermark: WatermarkSection {
+                path: self.watermark_path.trim().to_string(),
+                position: self.watermark_position,
+                opacity: self.watermark_opacity.min(100),
+            },
+            notifications: EnabledSection {
+                enabled: self.notifications_enabled,
+            },
+            status_item: EnabledSection {
+                enabled: self.status_item_enabled,
+            },
+            // ポートは有効時に保存前の検証を通る。無効時の不正な入力は既定のポートに戻す。
+            osc: OscSection {
+                enabled: self.osc_enabled,
+                port: osc::parse_listen_port(&self.osc_port).unwrap_or(DEFAULT_OSC_PORT),
+                feedback: self.osc_feedback.trim().to_string(),
+                allow_lan: self.osc_allow_lan,
+            },
+            events: EventsSection {
+                websocket: WebSocketSection {
+                    enabled: self.event_stream_enabled,
+                    port: event_stream::parse_event_stream_port(&self.event_stream_port)
+                        .unwrap_or(DEFAULT_EVENT_STREAM_PORT),
+                    allowed_origins: event_stream::parse_allowed_origins(
+                        &self.event_stream_origins,
+                    ),
+                },
+            },
+            // 選択中のプロファイルには、設定画面で変えた値を書き戻す。
+            profiles: ProfilesSection {
+                active: self.active_profile.clone(),
+                items: self
+                    .profiles
+                    .iter()
+                    .map(|profile| {
+                        if profile.name == self.active_profile {
+                            self.current_profile(&profile.name)
+                        } else {
+                            profile.clone()
+                        }
+                    })
+                    .collect(),
+            },
+            presets: PresetsSection {
+                items: self
+                    .presets
+                    .iter()
+                    .map(|preset| DownloadPreset {
+                        name: preset.name.trim().to_string(),
+                        ..preset.clone()
+                    })
+                    .collect(),
+            },
+            watch: WatchSection {
+                enabled: self.watch_folder_enabled,
+                dir: self.watch_folder_dir.trim().to_string(),
+            },
+            power: PowerSection {
+                battery_saver: self.battery_saver,
+                defer_gpu_conversions: self.defer_gpu_conversions,
+            },
+            subscriptions: SubscriptionsSection {
+                enabled: self.subscriptions_enabled,
+                sources: clean_subscription_sources(&self.subscription_sources),
+                interval_minutes: clamp_subscription_minutes(self.subscription_interval_minutes),
+                auto_queue: self.subscription_auto_queue,
+            },
+            network: NetworkSection {
+                check_connection: self.check_connection,
+            },
+        }
+    }
+
+    fn shortcut(&self, action: ShortcutAction) -> String {
+        self.shortcuts.get(action).trim().to_ascii_lowercase()
+    }
+
+    fn current_profile(&self, name: &str) -> SettingsProfile {
+        SettingsProfile {
+            name: name.to_string(),
+            download_dir: self.download_dir.trim().to_string(),
+            encoder_profile: self.encoder_profile,
+            max_height: self.download_max_height,
+        }
+    }
+
+    // プロファイルの保存先・エンコード方式・画質の上限を現在の設定にする。
+    pub fn apply_profile(&mut self, name: &str) -> Result<(), String> {
+        let profile = self
+            .profiles
+            .iter()
+            .find(|profile| profile.name == name)
+            .cloned()
+            .ok_or_else(|| tr_fmt(Msg::ProfileNotFound, &[&name]))?;
+        self.download_dir = normalize_dir(&profile.download_dir)
+            .to_string_lossy()
+            .to_string();
+        self.encoder_profile = profile.encoder_profile;
+        self.download_max_height = profile.max_height;
+        self.active_profile = profile.name;
+        Ok(())
+    }
+
+    // 現在の設定をプロファイルとして保存し、選択中にする。同じ名前があれば上書きする。
+    pub fn save_as_profile(&mut self, name: &str) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(tr(Msg::ProfileNameRequired).to_string());
+        }
+        let profile = self.current_profile(name);
+        match self.profiles.iter_mut().find(|entry| entry.name == name) {
+            Some(entry) => *entry = profile,
+            None => self.profiles.push(profile),
+        }
+        self.active_profile = name.to_string();
+        Ok(())
+    }
+
+    // 検索インデックスの走査と監視で使う除外の決まり
+    pub fn index_skip_rules(&self) -> SkipRules {
+        SkipRules {
+            hidden: self.index_skip_hidden,
+            bundles: self.index_skip_bundles,
+            system: self.index_skip_system,
+            names: split_skip_names(&self.index_skip_names),
+        }
+    }
+
+    // 使った保存先を履歴の先頭へ移す。
+    pub fn remember_download_dir(&mut self, dir: &str) {
+        let dir = dir.trim();
+        if dir.is_empty() {
+            return;
+        }
+        self.recent_download_dirs.retain(|entry| entry != dir);
+        self.recent_download_dirs.insert(0, dir.to_string());
+        self.recent_download_dirs
+            .truncate(RECENT_DOWNLOAD_DIR_LIMIT);
+    }
+
+    pub fn remove_profile(&mut self, name: &str) {
+        self.profiles.retain(|profile| profile.name != name);
+        if self.active_profile == name {
+            self.active_profile.clear();
+        }
     }
 }
 
@@ -128,83 +1143,343 @@ pub fn save_settings(data: &SettingsData) -> Result<(), String> {
     data.save()
 }
 
-pub fn load_cookie_args() -> Vec<String> {
-    let props = load_settings_properties();
-    let enabled = props
-        .get("cookies.from_browser.enabled")
-        .map(|v| parse_bool(v, false))
-        .unwrap_or(false);
-    if !enabled {
+// account が空欄か見つからない場合は、既定のクッキー設定を使う。
+pub fn load_cookie_args(account: &str) -> Vec<String> {
+    cookie_args(&load_settings_file().cookies, account)
+}
+
+fn cookie_args(cookies: &CookiesSection, account: &str) -> Vec<String> {
+    if let Some(account) = cookies
+        .accounts
+        .iter()
+        .find(|entry| !account.is_empty() && entry.name == account)
+    {
+        return cookies_from_browser_args(&account.browser, &account.profile);
+    }
+    if !cookies.from_browser.enabled {
         return Vec::new();
     }
-    let browser = props
-        .get("cookies.from_browser.browser")
-        .map(|v| v.trim().to_string())
-        .filter(|v| !v.is_empty());
-    let Some(browser) = browser else {
+    cookies_from_browser_args(&cookies.from_browser.browser, &cookies.from_browser.profile)
+}
+
+fn cookies_from_browser_args(browser: &str, profile: &str) -> Vec<String> {
+    let browser = browser.trim();
+    if browser.is_empty() {
         return Vec::new();
-    };
-    let profile = props
-        .get("cookies.from_browser.profile")
-        .map(|v| v.trim().to_string())
-        .unwrap_or_default();
+    }
+    let profile = profile.trim();
     let value = if profile.is_empty() {
-        browser
+        browser.to_string()
     } else {
         format!("{browser}:{profile}")
     };
     vec!["--cookies-from-browser".to_string(), value]
 }
 
-fn load_settings_properties() -> HashMap<String, String> {
-    let path = settings_file_path();
-    read_properties_from_path(&path).unwrap_or_default()
+// URL のサイトにログインを登録していれば、キーチェーンのパスワードを添えて返す。
+pub fn load_site_login(url: &str) -> Result<Option<SiteLogin>, String> {
+    let logins = load_settings_file().auth.logins;
+    let Some(login) = find_site_login(&logins, url) else {
+        return Ok(None);
+    };
+    let password = read_login_password(&login.site, &login.username)?
+        .ok_or_else(|| tr_fmt(Msg::KeychainPasswordMissing, &[&login.site]))?;
+    Ok(Some(SiteLogin {
+        password,
+        ..login.clone()
+    }))
+}
+
+fn find_site_login<'a>(logins: &'a [SiteLogin], url: &str) -> Option<&'a SiteLogin> {
+    let parsed = Url::parse(url.trim()).ok()?;
+    let host = parsed.host_str()?;
+    logins.iter().find(|login| login.matches_host(host))
+}
+
+// yt-dlp の取得元（チャンネルと固定バージョン）を読み込む。
+pub fn load_yt_dlp_source() -> (YtDlpChannel, String) {
+    let yt_dlp = load_settings_file().tools.yt_dlp;
+    (yt_dlp.channel, yt_dlp.version.trim().to_string())
+}
+
+pub fn load_js_runtime() -> JsRuntime {
+    load_settings_file().tools.js_runtime
+}
+
+// mp4 変換に使うエンコード方式を読み込む。
+pub fn load_encoder_profile() -> EncoderProfile {
+    load_settings_file().encoder.profile
+}
+
+// 映像の自動補正（インターレース解除・SAR 補正）を行うかを読み込む。
+pub fn load_auto_correct_video() -> bool {
+    load_settings_file().encoder.auto_correct
+}
+
+// 黒帯の検出と切り取りを行うかを読み込む。
+pub fn load_crop_detect() -> bool {
+    load_settings_file().encoder.crop_detect
+}
+
+// 同時に行う変換の数を読み込む。
+pub fn load_parallel_conversions() -> u8 {
+    clamp_parallel_conversions(load_settings_file().encoder.parallel)
+}
+
+fn clamp_parallel_conversions(count: u8) -> u8 {
+    count.clamp(1, MAX_PARALLEL_CONVERSIONS)
+}
+
+// 検索インデックスの定期バックアップを行うかと、保存先・間隔（時間）を読み込む。
+pub fn load_index_backup() -> (bool, PathBuf, u32) {
+    let backup = load_settings_file().search.backup;
+    (
+        backup.enabled,
+        index_backup_dir_or_default(&backup.dir),
+        clamp_index_backup_hours(backup.hours),
+    )
+}
+
+// バックアップの保存先。空欄の場合はアプリのデータフォルダの中に置く。
+pub fn index_backup_dir_or_default(raw: &str) -> PathBuf {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        default_index_backup_dir()
+    } else {
+        make_absolute_path(raw)
+    }
 }
 
-fn read_properties_from_path(path: &PathBuf) -> Option<HashMap<String, String>> {
-    let mut props = HashMap::new();
-    let contents = fs::read_to_string(path).ok()?;
+fn clamp_index_backup_hours(hours: u32) -> u32 {
+    hours.clamp(1, MAX_INDEX_BACKUP_HOURS)
+}
 
-    for raw_line in contents.lines() {
-        let line = raw_line.trim();
-        if line.is_empty() || line.starts_with('#') || line.starts_with('!') {
-            continue;
+// カンマ区切りの名前を分け、空欄と重複を除く。
+fn split_skip_names(raw: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for name in raw
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        if !names.iter().any(|seen| seen.eq_ignore_ascii_case(name)) {
+            names.push(name.to_string());
         }
-        let mut split = line.splitn(2, |c| c == '=' || c == ':');
-        let key = split.next().unwrap_or("").trim();
-        let value = split.next().unwrap_or("").trim();
-        if !key.is_empty() {
-            props.insert(key.to_string(), value.to_string());
+    }
+    names
+}
+
+fn clamp_subscription_minutes(minutes: u32) -> u32 {
+    minutes.clamp(MIN_SUBSCRIPTION_MINUTES, MAX_SUBSCRIPTION_MINUTES)
+}
+
+// 空欄と重複を除いた購読元の URL
+fn clean_subscription_sources(sources: &[String]) -> Vec<String> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for source in sources.iter().map(|source| source.trim()) {
+        if !source.is_empty() && !cleaned.iter().any(|seen| seen == source) {
+            cleaned.push(source.to_string());
         }
     }
-    Some(props)
+    cleaned
+}
+
+// バッテリー駆動中に GPU（VideoToolbox）の変換を電源につなぐまで待たせるか。
+pub fn load_defer_gpu_conversions() -> bool {
+    load_settings_file().power.defer_gpu_conversions
+}
+
+// シーン分割を行うかと、カットとみなす変化量（%）を読み込む。
+pub fn load_scene_split() -> (bool, u8) {
+    let encoder = load_settings_file().encoder;
+    (
+        encoder.scene_split,
+        clamp_scene_threshold(encoder.scene_threshold),
+    )
+}
+
+// チャプターのある動画をチャプターごとに分割するかを読み込む。
+pub fn load_chapter_split() -> bool {
+    load_settings_file().encoder.chapter_split
+}
+
+fn clamp_scene_threshold(threshold: u8) -> u8 {
+    threshold.clamp(MIN_SCENE_THRESHOLD, MAX_SCENE_THRESHOLD)
+}
+
+// 透かしの PNG・位置・不透明度を読み込む。PNG が空欄なら透かしは無効。
+pub fn load_watermark_settings() -> (String, WatermarkPosition, u8) {
+    let watermark = load_settings_file().watermark;
+    (
+        watermark.path.trim().to_string(),
+        watermark.position,
+        watermark.opacity.min(100),
+    )
+}
+
+// 複数の音声トラックから選ぶかを読み込む。
+pub fn load_audio_track_prompt() -> bool {
+    load_settings_file().download.audio_track.prompt
 }
 
-fn parse_bool(raw: &str, fallback: bool) -> bool {
+// ダウンロード前に動画の情報を確認するかを読み込む。
+pub fn load_preview_prompt() -> bool {
+    load_settings_file().download.preview.prompt
+}
+
+// yt-dlp で取得する映像の高さの上限を読み込む。None は制限なし。
+pub fn load_download_max_height() -> Option<u32> {
+    Some(load_settings_file().download.max_height).filter(|height| *height > 0)
+}
+
+// yt-dlp が同時に取得する断片の数と、自動調整するかを読み込む。
+pub fn load_concurrent_fragments() -> (u8, bool) {
+    let fragments = load_settings_file().download.fragments;
+    (clamp_fragments(fragments.count), fragments.auto)
+}
+
+pub fn load_use_aria2c() -> bool {
+    load_settings_file().download.aria2c.enabled
+}
+
+// 外部ツールの出力が止まってから中断するまでの分数（0 は中断しない）と、自動でやり直すかを読み込む。
+pub fn load_stall_timeout() -> (u32, bool) {
+    let stall = load_settings_file().download.stall;
+    (stall.minutes.min(MAX_STALL_MINUTES), stall.retry)
+}
+
+// 手で書き換えた設定ファイルの範囲外の値を 1〜MAX_CONCURRENT_FRAGMENTS にそろえる。
+fn clamp_fragments(count: u8) -> u8 {
+    count.clamp(1, MAX_CONCURRENT_FRAGMENTS)
+}
+
+pub fn load_collision_policy() -> CollisionPolicy {
+    load_settings_file().download.collision
+}
+
+pub fn load_filename_mode() -> FilenameMode {
+    load_settings_file().download.filename_mode
+}
+
+// 固定バージョンの入力値を検証する。リリースタグ（例: 2025.01.26）の数字とドットのみ受け付ける。
+pub fn validate_pinned_version(raw: &str) -> Result<(), String> {
     let trimmed = raw.trim();
-    if trimmed.is_empty() {
-        return fallback;
+    let valid = trimmed.is_empty()
+        || (trimmed.split('.').count() >= 3
+            && trimmed
+                .split('.')
+                .all(|part| !part.is_empty() && part.chars().all(|ch| ch.is_ascii_digit())));
+    if valid {
+        Ok(())
+    } else {
+        Err(tr(Msg::PinnedVersionFormat).to_string())
+    }
+}
+
+// 詳細オプションを引数へ分割して読み込む。設定画面で検証済みのため、分割できない場合は何も足さない。
+pub fn load_yt_dlp_extra_args() -> Vec<String> {
+    split_shell_words(&load_settings_file().tools.yt_dlp.extra_args).unwrap_or_default()
+}
+
+// バグレポートに含める設定ファイルの内容。
+// クッキーのプロファイル、ログインのユーザー名、詳細オプションの秘密の値は伏せる。
+pub fn redacted_settings_toml() -> Result<String, String> {
+    let mut file = load_settings_file();
+    redact_settings(&mut file);
+    toml::to_string_pretty(&file).map_err(|err| err.to_string())
+}
+
+fn redact_settings(file: &mut SettingsFile) {
+    let redact = |value: &mut String| {
+        if !value.is_empty() {
+            *value = REDACTED.to_string();
+        }
+    };
+    redact(&mut file.cookies.from_browser.profile);
+    for account in &mut file.cookies.accounts {
+        redact(&mut account.profile);
+    }
+    for login in &mut file.auth.logins {
+        redact(&mut login.username);
+    }
+    match split_shell_words(&file.tools.yt_dlp.extra_args) {
+        Ok(args) => file.tools.yt_dlp.extra_args = shell_join(&redact_args(&args)),
+        Err(_) => redact(&mut file.tools.yt_dlp.extra_args),
     }
-    trimmed.eq_ignore_ascii_case("true")
 }
 
-const DEFAULT_WINDOW_WIDTH: f32 = 860.0;
-const DEFAULT_WINDOW_HEIGHT: f32 = 1000.0;
+// シェルと同じ規則で空白区切りの引数に分ける。
+// シングルクォートの中はそのまま、ダブルクォートの中と外ではバックスラッシュで次の文字を残す。
+pub fn split_shell_words(raw: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    // 空の引数（""）も 1 つとして数えるため、文字を読んだかを別に持つ。
+    let mut in_word = false;
+    let mut chars = raw.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(ch) => current.push(ch),
+                        None => return Err(tr(Msg::UnclosedSingleQuote).to_string()),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(ch @ ('"' | '\\' | '$' | '`')) => current.push(ch),
+                            Some(ch) => {
+                                current.push('\\');
+                                current.push(ch);
+                            }
+                            None => return Err(tr(Msg::UnclosedDoubleQuote).to_string()),
+                        },
+                        Some(ch) => current.push(ch),
+                        None => return Err(tr(Msg::UnclosedDoubleQuote).to_string()),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                match chars.next() {
+                    Some(ch) => current.push(ch),
+                    None => {
+                        return Err(tr(Msg::TrailingBackslash).to_string());
+                    }
+                }
+            }
+            ch if ch.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            ch => {
+                in_word = true;
+                current.push(ch);
+            }
+        }
+    }
+    if in_word {
+        words.push(current);
+    }
+    Ok(words)
+}
+
 const MIN_WINDOW_WIDTH: f32 = 320.0;
 const MIN_WINDOW_HEIGHT: f32 = 320.0;
-const DEFAULT_MAIN_PANEL_WIDTH: f32 = 430.0;
 const MIN_MAIN_PANEL_WIDTH: f32 = 1.0;
+const MIN_SEARCH_WINDOW_WIDTH: f32 = 240.0;
 
-fn parse_dimension(raw: Option<&String>, fallback: f32, min: f32) -> f32 {
-    let Some(raw) = raw else {
-        return fallback.max(min);
-    };
-    let trimmed = raw.trim();
-    if trimmed.is_empty() {
-        return fallback.max(min);
-    }
-    let parsed = trimmed.parse::<f32>().unwrap_or(fallback);
-    parsed.max(min)
+fn parse_dimension(raw: &str, fallback: f32) -> f32 {
+    raw.trim().parse::<f32>().unwrap_or(fallback)
 }
 
 fn format_dimension(value: f32) -> String {
@@ -223,47 +1498,230 @@ fn normalize_dir(value: &str) -> PathBuf {
     make_absolute_path(trimmed)
 }
 
-fn encode_path_list(paths: &[String]) -> String {
-    let mut encoded = Vec::new();
-    for path in paths {
-        let mut escaped = String::new();
-        for ch in path.chars() {
-            match ch {
-                '\\' => escaped.push_str("\\\\"),
-                '|' => escaped.push_str("\\|"),
-                _ => escaped.push(ch),
-            }
-        }
-        encoded.push(escaped);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn switches_and_updates_profiles() {
+        let mut data = SettingsData::from_file(SettingsFile::default());
+        data.download_dir = "/Volumes/SSD/Club".to_string();
+        data.encoder_profile = EncoderProfile::VideoToolboxHevc;
+        data.download_max_height = 1080;
+        data.save_as_profile(" Club 1080p ").unwrap();
+        assert_eq!(data.active_profile, "Club 1080p");
+        data.download_dir = "/Volumes/SSD/Preview".to_string();
+        data.encoder_profile = EncoderProfile::Copy;
+        data.download_max_height = 720;
+        data.save_as_profile("Quick preview").unwrap();
+        assert!(data.save_as_profile("  ").is_err());
+
+        data.apply_profile("Club 1080p").unwrap();
+        assert_eq!(data.download_dir, "/Volumes/SSD/Club");
+        assert_eq!(data.encoder_profile, EncoderProfile::VideoToolboxHevc);
+        assert_eq!(data.download_max_height, 1080);
+
+        // 選択中のプロファイルだけ、保存時に現在の値で更新する。
+        data.download_max_height = 2160;
+        let file = data.to_file();
+        assert_eq!(file.profiles.active, "Club 1080p");
+        assert_eq!(file.profiles.items[0].max_height, 2160);
+        assert_eq!(file.profiles.items[1].max_height, 720);
+
+        data.remove_profile("Club 1080p");
+        assert!(data.active_profile.is_empty());
+        assert!(data.apply_profile("Club 1080p").is_err());
     }
-    encoded.join("|")
-}
 
-fn decode_path_list(raw: &str) -> Vec<String> {
-    let mut out = Vec::new();
-    let mut buf = String::new();
-    let mut escape = false;
-    for ch in raw.chars() {
-        if escape {
-            buf.push(ch);
-            escape = false;
-            continue;
-        }
-        match ch {
-            '\\' => escape = true,
-            '|' => {
-                let trimmed = buf.trim();
-                if !trimmed.is_empty() {
-                    out.push(trimmed.to_string());
-                }
-                buf.clear();
-            }
-            _ => buf.push(ch),
+    #[test]
+    fn builds_cookie_args_for_account() {
+        let mut cookies = CookiesSection::default();
+        cookies.accounts.push(CookieAccount {
+            name: "Work".to_string(),
+            browser: "chrome".to_string(),
+            profile: "Profile 2".to_string(),
+        });
+        assert!(cookie_args(&cookies, "").is_empty());
+        assert_eq!(
+            cookie_args(&cookies, "Work"),
+            ["--cookies-from-browser", "chrome:Profile 2"]
+        );
+
+        // 削除済みのアカウント名は既定のクッキー設定を使う。
+        cookies.from_browser.enabled = true;
+        cookies.from_browser.browser = "safari".to_string();
+        assert_eq!(
+            cookie_args(&cookies, "Personal"),
+            ["--cookies-from-browser", "safari"]
+        );
+    }
+
+    #[test]
+    fn finds_site_login_for_url() {
+        let logins = vec![SiteLogin {
+            site: "www.nicovideo.jp".to_string(),
+            username: "vj".to_string(),
+            password: String::new(),
+        }];
+        let find = |url: &str| find_site_login(&logins, url).map(|login| login.username.as_str());
+        assert_eq!(find("https://www.nicovideo.jp/watch/sm9"), Some("vj"));
+        assert_eq!(find("https://live.NICOVIDEO.jp/watch/lv1"), Some("vj"));
+        assert_eq!(find("https://nicovideo.jp.example.com/"), None);
+        assert_eq!(find("https://notnicovideo.jp/"), None);
+        assert_eq!(find("not a url"), None);
+
+        // パスワードは設定ファイルに書かない。
+        let mut data = SettingsData::from_file(SettingsFile::default());
+        data.site_logins = logins.clone();
+        data.site_logins[0].password = "secret".to_string();
+        let toml = toml::to_string(&data.to_file()).unwrap();
+        assert!(toml.contains("vj"));
+        assert!(!toml.contains("secret"));
+    }
+
+    #[test]
+    fn keeps_presets_through_settings_file() {
+        let mut data = SettingsData::from_file(SettingsFile::default());
+        assert_eq!(data.presets, DownloadPreset::defaults());
+        data.presets[0].name = " Club ".to_string();
+        data.presets[0].quality = QualityMode::TargetSize(25);
+        data.presets[2].name.clear();
+
+        let toml = toml::to_string(&data.to_file()).unwrap();
+        let loaded = SettingsData::from_file(toml::from_str(&toml).unwrap());
+        assert_eq!(loaded.presets[0].name, "Club");
+        assert_eq!(loaded.presets[0].quality, QualityMode::TargetSize(25));
+        assert_eq!(loaded.presets[1], DownloadPreset::defaults()[1]);
+        assert!(loaded.presets[2].name.is_empty());
+
+        // 手で減らした場合も、ボタンの数だけ並べる。
+        let mut file = SettingsFile::default();
+        file.presets.items.truncate(1);
+        assert_eq!(SettingsData::from_file(file).presets.len(), PRESET_COUNT);
+    }
+
+    #[test]
+    fn splits_shell_words() {
+        assert_eq!(
+            split_shell_words(r#"--sleep-requests 1  --extractor-args "youtube:lang=ja" -o 'a b'"#)
+                .unwrap(),
+            [
+                "--sleep-requests",
+                "1",
+                "--extractor-args",
+                "youtube:lang=ja",
+                "-o",
+                "a b"
+            ]
+        );
+        assert_eq!(
+            split_shell_words(r#"a\ b "c\"d" '' e\\f"#).unwrap(),
+            ["a b", "c\"d", "", "e\\f"]
+        );
+        assert!(split_shell_words("   ").unwrap().is_empty());
+        assert!(split_shell_words("--flag 'oops").is_err());
+        assert!(split_shell_words(r#""oops"#).is_err());
+    }
+
+    #[test]
+    fn keeps_recent_download_dirs_newest_first() {
+        let mut data = SettingsData::from_file(SettingsFile::default());
+        for index in 0..RECENT_DOWNLOAD_DIR_LIMIT + 2 {
+            data.remember_download_dir(&format!("/Volumes/SSD{index}"));
         }
+        data.remember_download_dir(" /Volumes/SSD3 ");
+        data.remember_download_dir("");
+        assert_eq!(
+            data.recent_download_dirs,
+            [
+                "/Volumes/SSD3",
+                "/Volumes/SSD6",
+                "/Volumes/SSD5",
+                "/Volumes/SSD4",
+                "/Volumes/SSD2"
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_search_input_mode_and_defaults_to_quiet() {
+        let data = SettingsData::from_file(SettingsFile::default());
+        assert_eq!(data.search_input_mode, SearchInputMode::Keep);
+        assert!(!data.input_source_notify);
+
+        let file: SettingsFile = toml::from_str("[search]\ninput_mode = \"japanese\"\n").unwrap();
+        assert_eq!(file.search.input_mode, SearchInputMode::Japanese);
+        let saved = toml::to_string(&SettingsData::from_file(file).to_file()).unwrap();
+        assert!(saved.contains("input_mode = \"japanese\""));
+    }
+
+    #[test]
+    fn skips_hidden_bundles_and_system_by_default() {
+        let data = SettingsData::from_file(SettingsFile::default());
+        assert_eq!(data.index_skip_rules(), SkipRules::default());
+
+        let file: SettingsFile =
+            toml::from_str("[search.skip]\nhidden = false\nnames = [\"Render\"]\n").unwrap();
+        let mut data = SettingsData::from_file(file);
+        assert!(!data.index_skip_hidden);
+        assert!(data.index_skip_bundles);
+        assert_eq!(data.index_skip_names, "Render");
+
+        data.index_skip_names = " Render, , cache ,render,Proxies".to_string();
+        let saved = data.to_file();
+        assert_eq!(saved.search.skip.names, vec!["Render", "cache", "Proxies"]);
+        assert!(!saved.search.skip.hidden);
+    }
+
+    #[test]
+    fn cleans_subscription_sources_and_interval() {
+        let file: SettingsFile = toml::from_str(
+            "[subscriptions]\nenabled = true\nsources = [\" https://www.youtube.com/@vj \", \"\", \"https://www.youtube.com/@vj\"]\ninterval_minutes = 1\n",
+        )
+        .unwrap();
+        let data = SettingsData::from_file(file);
+        assert!(data.subscriptions_enabled);
+        assert!(data.subscription_auto_queue);
+        assert_eq!(data.subscription_sources, ["https://www.youtube.com/@vj"]);
+        assert_eq!(data.subscription_interval_minutes, MIN_SUBSCRIPTION_MINUTES);
+        assert_eq!(
+            data.to_file().subscriptions.interval_minutes,
+            MIN_SUBSCRIPTION_MINUTES
+        );
     }
-    let trimmed = buf.trim();
-    if !trimmed.is_empty() {
-        out.push(trimmed.to_string());
+
+    #[test]
+    fn keeps_panel_widths_between_launches() {
+        let mut file = SettingsFile::default();
+        file.layout.download.width = 312.5;
+        file.layout.search.width = 548.0;
+        let data = SettingsData::from_file(file);
+        assert_eq!(data.download_panel_width, "312.5");
+        assert_eq!(data.search_panel_width, "548");
+
+        let saved = data.to_file();
+        assert_eq!(saved.layout.download.width, 312.5);
+        assert_eq!(saved.layout.search.width, 548.0);
+    }
+
+    #[test]
+    fn redacts_settings_for_bug_report() {
+        let mut data = SettingsData::from_file(SettingsFile::default());
+        data.cookies_profile = "Profile 1".to_string();
+        data.site_logins = vec![SiteLogin {
+            site: "nicovideo.jp".to_string(),
+            username: "vj".to_string(),
+            password: String::new(),
+        }];
+        data.yt_dlp_extra_args = "--sleep-requests 1 --video-password 'open sesame'".to_string();
+        let mut file = data.to_file();
+        redact_settings(&mut file);
+        assert_eq!(file.cookies.from_browser.profile, REDACTED);
+        assert_eq!(file.auth.logins[0].site, "nicovideo.jp");
+        assert_eq!(file.auth.logins[0].username, REDACTED);
+        assert_eq!(
+            file.tools.yt_dlp.extra_args,
+            "--sleep-requests 1 --video-password '<redacted>'"
+        );
     }
-    out
 }
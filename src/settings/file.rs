@@ -0,0 +1,1016 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use toml::{Table, Value};
+
+use super::{
+    CollisionPolicy, CookieAccount, DEFAULT_CONCURRENT_FRAGMENTS, DEFAULT_INDEX_BACKUP_HOURS,
+    DEFAULT_PARALLEL_CONVERSIONS, DEFAULT_SCENE_THRESHOLD, DEFAULT_STALL_MINUTES,
+    DEFAULT_SUBSCRIPTION_MINUTES, DownloadPreset, EncoderProfile, FilenameMode, JsRuntime,
+    Language, SearchInputMode, SettingsProfile, SiteLogin, WatermarkPosition, YtDlpChannel,
+};
+use crate::event_stream::DEFAULT_EVENT_STREAM_PORT;
+use crate::osc::DEFAULT_OSC_PORT;
+use crate::paths::{legacy_settings_file_path, settings_file_path};
+use crate::shortcuts::ShortcutAction;
+
+// 設定ファイルの形式のバージョン。形式を変える場合は上げて migrate_table に移行処理を足す。
+pub(super) const SETTINGS_VERSION: i64 = 1;
+
+pub(super) const DEFAULT_WINDOW_WIDTH: f32 = 860.0;
+pub(super) const DEFAULT_WINDOW_HEIGHT: f32 = 1000.0;
+pub(super) const DEFAULT_MAIN_PANEL_WIDTH: f32 = 430.0;
+pub(super) const DEFAULT_SEARCH_WINDOW_WIDTH: f32 = 380.0;
+const DEFAULT_SUMMON_HOTKEY: &str = "cmd+shift+space";
+const DEFAULT_WATERMARK_OPACITY: u8 = 80;
+
+// settings.toml の内容。セクションごとに型を持ち、無いキーは既定値で補う。
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct SettingsFile {
+    pub version: i64,
+    pub window: WindowSection,
+    pub layout: LayoutSection,
+    pub ui: UiSection,
+    pub download: DownloadSection,
+    pub search: SearchSection,
+    pub cookies: CookiesSection,
+    pub auth: AuthSection,
+    pub hotkey: HotkeySection,
+    pub shortcuts: ShortcutsSection,
+    pub tools: ToolsSection,
+    pub encoder: EncoderSection,
+    pub watermark: WatermarkSection,
+    pub notifications: EnabledSection,
+    pub status_item: EnabledSection,
+    pub osc: OscSection,
+    pub events: EventsSection,
+    pub profiles: ProfilesSection,
+    pub presets: PresetsSection,
+    pub watch: WatchSection,
+    pub power: PowerSection,
+    pub subscriptions: SubscriptionsSection,
+    pub network: NetworkSection,
+}
+
+impl Default for SettingsFile {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            window: WindowSection::default(),
+            layout: LayoutSection::default(),
+            ui: UiSection::default(),
+            download: DownloadSection::default(),
+            search: SearchSection::default(),
+            cookies: CookiesSection::default(),
+            auth: AuthSection::default(),
+            hotkey: HotkeySection::default(),
+            shortcuts: ShortcutsSection::default(),
+            tools: ToolsSection::default(),
+            encoder: EncoderSection::default(),
+            watermark: WatermarkSection::default(),
+            notifications: EnabledSection { enabled: true },
+            status_item: EnabledSection::default(),
+            osc: OscSection::default(),
+            events: EventsSection::default(),
+            profiles: ProfilesSection::default(),
+            presets: PresetsSection::default(),
+            watch: WatchSection::default(),
+            power: PowerSection::default(),
+            subscriptions: SubscriptionsSection::default(),
+            network: NetworkSection::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct WindowSection {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Default for WindowSection {
+    fn default() -> Self {
+        Self {
+            width: DEFAULT_WINDOW_WIDTH,
+            height: DEFAULT_WINDOW_HEIGHT,
+        }
+    }
+}
+
+// メイン画面の左右ペイン
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct LayoutSection {
+    pub download: PanelSection,
+    pub search: PanelSection,
+    pub search_window: SearchWindowSection,
+}
+
+// メイン画面から切り離した検索ウィンドウ
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct SearchWindowSection {
+    pub detached: bool,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Default for SearchWindowSection {
+    fn default() -> Self {
+        Self {
+            detached: false,
+            width: DEFAULT_SEARCH_WINDOW_WIDTH,
+            height: DEFAULT_WINDOW_HEIGHT,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct PanelSection {
+    pub width: f32,
+}
+
+impl Default for PanelSection {
+    fn default() -> Self {
+        Self {
+            width: DEFAULT_MAIN_PANEL_WIDTH,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct UiSection {
+    pub language: Language,
+    // 入力ソースを切り替えるたびにステータスへ表示する（既定は表示しない）
+    pub input_source_notify: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct DownloadSection {
+    // 空欄の場合は既定の保存先を使う。
+    pub dir: String,
+    // 取得する映像の高さの上限。0 は制限なし。
+    pub max_height: u32,
+    // 保存先に同名のファイルがある場合の扱い
+    pub collision: CollisionPolicy,
+    // AnimeThemes の URL から作るファイル名に残す文字
+    pub filename_mode: FilenameMode,
+    // 最近使った保存先（新しい順）
+    pub recent_dirs: Vec<String>,
+    pub audio_track: PromptSection,
+    // ダウンロード前にタイトルや長さを確認する
+    pub preview: PromptSection,
+    pub fragments: FragmentsSection,
+    // 見つかった場合に yt-dlp の外部ダウンローダーとして aria2c を使う
+    pub aria2c: EnabledSection,
+    pub stall: StallSection,
+}
+
+// yt-dlp が同時に取得する断片の数
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct FragmentsSection {
+    pub count: u8,
+    // 直近のダウンロード速度から、count を上限に決める。
+    pub auto: bool,
+}
+
+impl Default for FragmentsSection {
+    fn default() -> Self {
+        Self {
+            count: DEFAULT_CONCURRENT_FRAGMENTS,
+            auto: false,
+        }
+    }
+}
+
+// 外部ツールの出力が止まったダウンロードを中断する設定
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct StallSection {
+    // 出力が止まってから中断するまでの分数。0 は中断しない。
+    pub minutes: u32,
+    // 中断した項目を自動でやり直す
+    pub retry: bool,
+}
+
+impl Default for StallSection {
+    fn default() -> Self {
+        Self {
+            minutes: DEFAULT_STALL_MINUTES,
+            retry: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct PromptSection {
+    pub prompt: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct SearchSection {
+    pub roots: Vec<String>,
+    // シンボリックリンクをたどる検索対象フォルダ（roots のうちの一部）
+    pub follow_links: Vec<String>,
+    pub history: Vec<String>,
+    pub finder_tags: FinderTagsSection,
+    // 検索欄にフォーカスしたときに切り替える入力モード
+    pub input_mode: SearchInputMode,
+    pub backup: IndexBackupSection,
+    pub skip: IndexSkipSection,
+    pub cloud: CloudFilesSection,
+}
+
+// クラウドにだけある（オンライン専用の）ファイルの扱い
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct CloudFilesSection {
+    // ドラッグしようとした時に、クラウドからの取得を始める
+    pub fetch_on_drag: bool,
+}
+
+// インデックスしない項目。隠し項目・パッケージ・システムフォルダは既定で除外し、names で名前を足す。
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct IndexSkipSection {
+    pub hidden: bool,
+    pub bundles: bool,
+    pub system: bool,
+    pub names: Vec<String>,
+}
+
+impl Default for IndexSkipSection {
+    fn default() -> Self {
+        Self {
+            hidden: true,
+            bundles: true,
+            system: true,
+            names: Vec::new(),
+        }
+    }
+}
+
+// 検索インデックスの定期バックアップ。dir が空欄の場合はアプリのデータフォルダに置く。
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct IndexBackupSection {
+    pub enabled: bool,
+    pub dir: String,
+    // バックアップの間隔（時間）
+    pub hours: u32,
+}
+
+impl Default for IndexBackupSection {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            dir: String::new(),
+            hours: DEFAULT_INDEX_BACKUP_HOURS,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct FinderTagsSection {
+    pub read: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct CookiesSection {
+    pub from_browser: FromBrowserSection,
+    // 選択中のアカウント名。空欄なら from_browser を使う。
+    pub active: String,
+    pub accounts: Vec<CookieAccount>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct FromBrowserSection {
+    pub enabled: bool,
+    pub browser: String,
+    pub profile: String,
+}
+
+// パスワードはキーチェーンに保存し、ここにはサイトとユーザー名だけを書く。
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct AuthSection {
+    pub logins: Vec<SiteLogin>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct HotkeySection {
+    // 空欄の場合は無効。
+    pub summon: String,
+}
+
+impl Default for HotkeySection {
+    fn default() -> Self {
+        Self {
+            summon: DEFAULT_SUMMON_HOTKEY.to_string(),
+        }
+    }
+}
+
+// メイン画面のショートカット。空欄の場合は無効。
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct ShortcutsSection {
+    pub start_download: String,
+    pub cancel: String,
+    pub focus_search: String,
+    pub open_settings: String,
+    pub open_logs: String,
+}
+
+impl Default for ShortcutsSection {
+    fn default() -> Self {
+        Self {
+            start_download: ShortcutAction::StartDownload.default_binding().to_string(),
+            cancel: ShortcutAction::Cancel.default_binding().to_string(),
+            focus_search: ShortcutAction::FocusSearch.default_binding().to_string(),
+            open_settings: ShortcutAction::OpenSettings.default_binding().to_string(),
+            open_logs: ShortcutAction::OpenLogs.default_binding().to_string(),
+        }
+    }
+}
+
+impl ShortcutsSection {
+    pub(super) fn get(&self, action: ShortcutAction) -> &str {
+        match action {
+            ShortcutAction::StartDownload => &self.start_download,
+            ShortcutAction::Cancel => &self.cancel,
+            ShortcutAction::FocusSearch => &self.focus_search,
+            ShortcutAction::OpenSettings => &self.open_settings,
+            ShortcutAction::OpenLogs => &self.open_logs,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct ToolsSection {
+    pub yt_dlp: YtDlpSection,
+    pub js_runtime: JsRuntime,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct YtDlpSection {
+    pub channel: YtDlpChannel,
+    pub version: String,
+    // yt-dlp の引数の末尾に足す詳細オプション（シェルと同じ規則で分割する）
+    pub extra_args: String,
+}
+
+impl Default for YtDlpSection {
+    fn default() -> Self {
+        Self {
+            channel: YtDlpChannel::Stable,
+            version: String::new(),
+            extra_args: String::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct EncoderSection {
+    pub profile: EncoderProfile,
+    pub auto_correct: bool,
+    pub crop_detect: bool,
+    // 同時に行う変換の数
+    pub parallel: u8,
+    // 保存後にシーンの切り替わりで分割するか。閾値は ffmpeg の scene の値（0〜100%）。
+    pub scene_split: bool,
+    pub scene_threshold: u8,
+    // 保存後に動画のチャプター（yt-dlp の動画情報）ごとに分割するか
+    pub chapter_split: bool,
+}
+
+impl Default for EncoderSection {
+    fn default() -> Self {
+        Self {
+            profile: EncoderProfile::VideoToolboxH264,
+            auto_correct: true,
+            crop_detect: false,
+            parallel: DEFAULT_PARALLEL_CONVERSIONS,
+            scene_split: false,
+            scene_threshold: DEFAULT_SCENE_THRESHOLD,
+            chapter_split: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct WatermarkSection {
+    pub path: String,
+    pub position: WatermarkPosition,
+    pub opacity: u8,
+}
+
+impl Default for WatermarkSection {
+    fn default() -> Self {
+        Self {
+            path: String::new(),
+            position: WatermarkPosition::BottomRight,
+            opacity: DEFAULT_WATERMARK_OPACITY,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct EnabledSection {
+    pub enabled: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct OscSection {
+    pub enabled: bool,
+    pub port: u16,
+    pub feedback: String,
+    // 同じネットワークの他の機器からも受け付ける。無効時はこの Mac からの送信だけを受ける。
+    pub allow_lan: bool,
+}
+
+impl Default for OscSection {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_OSC_PORT,
+            feedback: String::new(),
+            allow_lan: false,
+        }
+    }
+}
+
+// バッテリー駆動中の省電力。battery_saver でインデックスを控えめにし、defer_gpu_conversions で GPU の変換を電源につなぐまで待たせる。
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct PowerSection {
+    pub battery_saver: bool,
+    pub defer_gpu_conversions: bool,
+}
+
+impl Default for PowerSection {
+    fn default() -> Self {
+        Self {
+            battery_saver: true,
+            defer_gpu_conversions: false,
+        }
+    }
+}
+
+// 新しい動画を確認するチャンネル・プレイリスト。auto_queue が無効なら見つけたことを知らせるだけにする。
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct SubscriptionsSection {
+    pub enabled: bool,
+    pub sources: Vec<String>,
+    pub interval_minutes: u32,
+    pub auto_queue: bool,
+}
+
+impl Default for SubscriptionsSection {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sources: Vec::new(),
+            interval_minutes: DEFAULT_SUBSCRIPTION_MINUTES,
+            auto_queue: true,
+        }
+    }
+}
+
+// ダウンロードの前の接続確認。プロキシの内側などで確認が当てにならない場合は無効にする。
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct NetworkSection {
+    pub check_connection: bool,
+}
+
+impl Default for NetworkSection {
+    fn default() -> Self {
+        Self {
+            check_connection: true,
+        }
+    }
+}
+
+// 動画を置くと変換して保存先へ移す監視フォルダ
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct WatchSection {
+    pub enabled: bool,
+    pub dir: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct EventsSection {
+    pub websocket: WebSocketSection,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct WebSocketSection {
+    pub enabled: bool,
+    pub port: u16,
+    // 接続を許すブラウザの Origin（https://example.com の形式）
+    pub allowed_origins: Vec<String>,
+}
+
+impl Default for WebSocketSection {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_EVENT_STREAM_PORT,
+            allowed_origins: Vec::new(),
+        }
+    }
+}
+
+// 保存済みのプロファイルと、最後に切り替えたプロファイルの名前
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct ProfilesSection {
+    pub active: String,
+    pub items: Vec<SettingsProfile>,
+}
+
+// メイン画面のプリセットボタン
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct PresetsSection {
+    pub items: Vec<DownloadPreset>,
+}
+
+impl Default for PresetsSection {
+    fn default() -> Self {
+        Self {
+            items: DownloadPreset::defaults(),
+        }
+    }
+}
+
+impl SettingsFile {
+    // 旧形式（settings.properties）の key=value から移行する。
+    pub(super) fn from_properties(props: &HashMap<String, String>) -> Self {
+        let defaults = Self::default();
+        let text = |key: &str| props.get(key).map(|v| v.trim().to_string());
+        let flag = |key: &str, fallback: bool| {
+            props
+                .get(key)
+                .map(|v| parse_bool(v, fallback))
+                .unwrap_or(fallback)
+        };
+        let dimension = |key: &str, fallback: f32| {
+            props
+                .get(key)
+                .and_then(|v| v.trim().parse::<f32>().ok())
+                .unwrap_or(fallback)
+        };
+        let port = |key: &str, fallback: u16| {
+            props
+                .get(key)
+                .and_then(|v| v.trim().parse::<u16>().ok())
+                .filter(|port| *port > 0)
+                .unwrap_or(fallback)
+        };
+        Self {
+            version: SETTINGS_VERSION,
+            window: WindowSection {
+                width: dimension("window.width", DEFAULT_WINDOW_WIDTH),
+                height: dimension("window.height", DEFAULT_WINDOW_HEIGHT),
+            },
+            layout: LayoutSection {
+                download: PanelSection {
+                    width: dimension("layout.download.width", DEFAULT_MAIN_PANEL_WIDTH),
+                },
+                search: PanelSection {
+                    width: dimension("layout.search.width", DEFAULT_MAIN_PANEL_WIDTH),
+                },
+                search_window: SearchWindowSection::default(),
+            },
+            ui: UiSection {
+                language: text("ui.language").map(Language::from).unwrap_or_default(),
+                input_source_notify: false,
+            },
+            download: DownloadSection {
+                dir: text("download.dir").unwrap_or_default(),
+                max_height: 0,
+                collision: CollisionPolicy::Rename,
+                filename_mode: FilenameMode::Ascii,
+                recent_dirs: Vec::new(),
+                audio_track: PromptSection {
+                    prompt: flag("download.audio_track.prompt", false),
+                },
+                preview: PromptSection {
+                    prompt: flag("download.preview.prompt", false),
+                },
+                fragments: FragmentsSection::default(),
+                aria2c: EnabledSection::default(),
+                stall: StallSection::default(),
+            },
+            search: SearchSection {
+                roots: props
+                    .get("search.roots")
+                    .map(|v| decode_path_list(v))
+                    .unwrap_or_default(),
+                follow_links: Vec::new(),
+                history: props
+                    .get("search.history")
+                    .map(|v| decode_path_list(v))
+                    .unwrap_or_default(),
+                finder_tags: FinderTagsSection {
+                    read: flag("search.finder_tags.read", false),
+                },
+                input_mode: SearchInputMode::Keep,
+                backup: IndexBackupSection::default(),
+                skip: IndexSkipSection::default(),
+                cloud: CloudFilesSection::default(),
+            },
+            cookies: CookiesSection {
+                from_browser: FromBrowserSection {
+                    enabled: flag("cookies.from_browser.enabled", false),
+                    browser: text("cookies.from_browser.browser").unwrap_or_default(),
+                    profile: text("cookies.from_browser.profile").unwrap_or_default(),
+                },
+                ..CookiesSection::default()
+            },
+            auth: AuthSection::default(),
+            // キーが無い場合は既定のショートカット、空欄の場合は無効として扱う。
+            hotkey: HotkeySection {
+                summon: text("hotkey.summon").unwrap_or(defaults.hotkey.summon),
+            },
+            shortcuts: ShortcutsSection::default(),
+            tools: ToolsSection {
+                yt_dlp: YtDlpSection {
+                    channel: text("tools.yt_dlp.channel")
+                        .map(YtDlpChannel::from)
+                        .unwrap_or(YtDlpChannel::Stable),
+                    version: text("tools.yt_dlp.version").unwrap_or_default(),
+                    extra_args: String::new(),
+                },
+                js_runtime: JsRuntime::Deno,
+            },
+            encoder: EncoderSection {
+                profile: text("encoder.profile")
+                    .map(EncoderProfile::from)
+                    .unwrap_or(EncoderProfile::VideoToolboxH264),
+                auto_correct: flag("encoder.auto_correct", true),
+                crop_detect: flag("encoder.crop_detect", false),
+                ..EncoderSection::default()
+            },
+            watermark: WatermarkSection {
+                path: text("watermark.path").unwrap_or_default(),
+                position: text("watermark.position")
+                    .map(WatermarkPosition::from)
+                    .unwrap_or(WatermarkPosition::BottomRight),
+                opacity: props
+                    .get("watermark.opacity")
+                    .and_then(|v| v.trim().parse::<u8>().ok())
+                    .map(|v| v.min(100))
+                    .unwrap_or(DEFAULT_WATERMARK_OPACITY),
+            },
+            notifications: EnabledSection {
+                enabled: flag("notifications.enabled", true),
+            },
+            status_item: EnabledSection {
+                enabled: flag("status_item.enabled", false),
+            },
+            osc: OscSection {
+                enabled: flag("osc.enabled", false),
+                port: port("osc.port", DEFAULT_OSC_PORT),
+                feedback: text("osc.feedback").unwrap_or_default(),
+                allow_lan: false,
+            },
+            events: EventsSection {
+                websocket: WebSocketSection {
+                    enabled: flag("events.websocket.enabled", false),
+                    port: port("events.websocket.port", DEFAULT_EVENT_STREAM_PORT),
+                    allowed_origins: Vec::new(),
+                },
+            },
+            profiles: ProfilesSection::default(),
+            presets: PresetsSection::default(),
+            watch: WatchSection::default(),
+            power: PowerSection::default(),
+            subscriptions: SubscriptionsSection::default(),
+            network: NetworkSection::default(),
+        }
+    }
+}
+
+// 古いバージョンの設定を現在の形式へそろえる。
+fn migrate_table(mut table: Table) -> Table {
+    let version = table
+        .get("version")
+        .and_then(Value::as_integer)
+        .unwrap_or(0);
+    if version > SETTINGS_VERSION {
+        eprintln!(
+            "[settings] settings.toml is version {version}; reading it as version {SETTINGS_VERSION}"
+        );
+    }
+    table.insert("version".to_string(), Value::Integer(SETTINGS_VERSION));
+    table
+}
+
+// 保存済みの値に書き込む値を重ねる。アプリが知らないキーは残す。
+fn merge_table(base: &mut Table, overlay: Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base_child)), Value::Table(child)) => merge_table(base_child, child),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+fn read_table(path: &Path) -> Option<Table> {
+    let contents = fs::read_to_string(path).ok()?;
+    match contents.parse::<Table>() {
+        Ok(table) => Some(migrate_table(table)),
+        Err(err) => {
+            eprintln!("[settings] failed to parse {}: {err}", path.display());
+            None
+        }
+    }
+}
+
+// 設定を読み込む。settings.toml が無く旧形式の設定がある場合は移行して保存する。
+pub(super) fn load_settings_file() -> SettingsFile {
+    let path = settings_file_path();
+    if !path.exists()
+        && let Some(props) = read_properties_from_path(&legacy_settings_file_path())
+    {
+        let migrated = SettingsFile::from_properties(&props);
+        match save_settings_file(&migrated) {
+            Ok(()) => {
+                // 移行後の旧ファイルは読み込まれないよう名前を変えて残す。
+                let legacy = legacy_settings_file_path();
+                let _ = fs::rename(&legacy, legacy.with_extension("properties.bak"));
+            }
+            Err(err) => eprintln!("[settings] failed to migrate settings.properties: {err}"),
+        }
+        return migrated;
+    }
+    let Some(table) = read_table(&path) else {
+        return SettingsFile::default();
+    };
+    settings_from_table(table)
+}
+
+// 表から設定を読む。型の合わない値があっても全体を既定値に戻さず、その値だけを既定値にする。
+fn settings_from_table(table: Table) -> SettingsFile {
+    match Value::Table(table.clone()).try_into() {
+        Ok(settings) => settings,
+        Err(err) => {
+            eprintln!("[settings] failed to read settings.toml: {err}");
+            let mut accepted = Table::try_from(&SettingsFile::default()).unwrap_or_default();
+            accept_readable_values(&mut accepted, &mut Vec::new(), &table);
+            Value::Table(accepted).try_into().unwrap_or_default()
+        }
+    }
+}
+
+// 既定値の表へ保存済みの値を 1 つずつ重ね、読める値だけを残す。セクションは中のキーごとに確かめる。
+fn accept_readable_values(accepted: &mut Table, path: &mut Vec<String>, table: &Table) {
+    for (key, value) in table {
+        path.push(key.clone());
+        let is_section = matches!(value, Value::Table(_))
+            && matches!(value_at(accepted, path), Some(Value::Table(_)));
+        if let (true, Value::Table(child)) = (is_section, value) {
+            accept_readable_values(accepted, path, child);
+        } else {
+            let mut candidate = accepted.clone();
+            set_value_at(&mut candidate, path, value.clone());
+            if Value::Table(candidate.clone())
+                .try_into::<SettingsFile>()
+                .is_ok()
+            {
+                *accepted = candidate;
+            } else {
+                eprintln!("[settings] ignored unreadable value: {}", path.join("."));
+            }
+        }
+        path.pop();
+    }
+}
+
+fn value_at<'a>(table: &'a Table, path: &[String]) -> Option<&'a Value> {
+    let (last, parents) = path.split_last()?;
+    let mut current = table;
+    for key in parents {
+        current = current.get(key)?.as_table()?;
+    }
+    current.get(last)
+}
+
+fn set_value_at(table: &mut Table, path: &[String], value: Value) {
+    let Some((last, parents)) = path.split_last() else {
+        return;
+    };
+    let mut current = table;
+    for key in parents {
+        let Some(Value::Table(child)) = current.get_mut(key) else {
+            return;
+        };
+        current = child;
+    }
+    current.insert(last.clone(), value);
+}
+
+pub(super) fn save_settings_file(settings: &SettingsFile) -> Result<(), String> {
+    let path = settings_file_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+    }
+    // 読めない settings.toml を上書きすると、手で直せば戻せた値まで失うため、先に控えを残す。
+    if path.exists() && read_table(&path).is_none() {
+        let backup = path.with_extension("toml.bak");
+        fs::copy(&path, &backup).map_err(|err| err.to_string())?;
+        eprintln!(
+            "[settings] kept the unreadable settings file as {}",
+            backup.display()
+        );
+    }
+    let mut table = read_table(&path).unwrap_or_default();
+    let overlay = Table::try_from(settings).map_err(|err| err.to_string())?;
+    merge_table(&mut table, overlay);
+    let contents = toml::to_string_pretty(&table).map_err(|err| err.to_string())?;
+    fs::write(path, contents).map_err(|err| err.to_string())
+}
+
+fn read_properties_from_path(path: &Path) -> Option<HashMap<String, String>> {
+    let mut props = HashMap::new();
+    let contents = fs::read_to_string(path).ok()?;
+
+    for raw_line in contents.lines() {
+        let line = raw_line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with('!') {
+            continue;
+        }
+        let mut split = line.splitn(2, |c| c == '=' || c == ':');
+        let key = split.next().unwrap_or("").trim();
+        let value = split.next().unwrap_or("").trim();
+        if !key.is_empty() {
+            props.insert(key.to_string(), value.to_string());
+        }
+    }
+    Some(props)
+}
+
+fn parse_bool(raw: &str, fallback: bool) -> bool {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return fallback;
+    }
+    trimmed.eq_ignore_ascii_case("true")
+}
+
+fn decode_path_list(raw: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut buf = String::new();
+    let mut escape = false;
+    for ch in raw.chars() {
+        if escape {
+            buf.push(ch);
+            escape = false;
+            continue;
+        }
+        match ch {
+            '\\' => escape = true,
+            '|' => {
+                let trimmed = buf.trim();
+                if !trimmed.is_empty() {
+                    out.push(trimmed.to_string());
+                }
+                buf.clear();
+            }
+            _ => buf.push(ch),
+        }
+    }
+    let trimmed = buf.trim();
+    if !trimmed.is_empty() {
+        out.push(trimmed.to_string());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrates_properties() {
+        let props = [
+            ("window.width", "900"),
+            ("layout.download.width", "520"),
+            ("search.roots", "/Volumes/SSD\\|A|/Users/vj/Movies"),
+            ("hotkey.summon", ""),
+            ("encoder.profile", "libx264"),
+            ("osc.port", "abc"),
+            ("watermark.position", "top_left"),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect::<HashMap<_, _>>();
+        let settings = SettingsFile::from_properties(&props);
+        assert_eq!(settings.window.width, 900.0);
+        assert_eq!(settings.window.height, DEFAULT_WINDOW_HEIGHT);
+        assert_eq!(settings.layout.download.width, 520.0);
+        assert_eq!(
+            settings.search.roots,
+            ["/Volumes/SSD|A", "/Users/vj/Movies"]
+        );
+        assert_eq!(settings.hotkey.summon, "");
+        assert_eq!(settings.encoder.profile, EncoderProfile::X264);
+        assert!(settings.encoder.auto_correct);
+        assert_eq!(settings.osc.port, DEFAULT_OSC_PORT);
+        assert_eq!(settings.watermark.position, WatermarkPosition::TopLeft);
+        assert!(settings.notifications.enabled);
+        assert_eq!(
+            SettingsFile::from_properties(&HashMap::new()),
+            SettingsFile::default()
+        );
+    }
+
+    #[test]
+    fn keeps_unknown_keys_when_saving() {
+        let mut table = migrate_table(
+            r#"
+            custom = "keep"
+            [encoder]
+            profile = "copy"
+            future_option = 3
+            "#
+            .parse::<Table>()
+            .unwrap(),
+        );
+        let settings: SettingsFile = Value::Table(table.clone()).try_into().unwrap();
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert_eq!(settings.encoder.profile, EncoderProfile::Copy);
+        // 知らない値は既定値として読む。
+        let unknown: SettingsFile =
+            Value::Table(r#"encoder = { profile = "av1" }"#.parse::<Table>().unwrap())
+                .try_into()
+                .unwrap();
+        assert_eq!(unknown.encoder.profile, EncoderProfile::VideoToolboxH264);
+
+        let updated = SettingsFile {
+            encoder: EncoderSection {
+                profile: EncoderProfile::X264,
+                ..settings.encoder
+            },
+            ..settings
+        };
+        merge_table(&mut table, Table::try_from(&updated).unwrap());
+        assert_eq!(table["custom"].as_str(), Some("keep"));
+        assert_eq!(table["encoder"]["future_option"].as_integer(), Some(3));
+        assert_eq!(table["encoder"]["profile"].as_str(), Some("libx264"));
+        assert_eq!(table["osc"]["port"].as_integer(), Some(9000));
+    }
+
+    #[test]
+    fn keeps_other_values_when_one_has_the_wrong_type() {
+        let table = migrate_table(
+            r#"
+            [window]
+            width = "wide"
+            height = 700.0
+            [search]
+            roots = ["/Volumes/SSD", "/Users/vj/Movies"]
+            [osc]
+            enabled = true
+            port = "abc"
+            "#
+            .parse::<Table>()
+            .unwrap(),
+        );
+        let settings = settings_from_table(table);
+        assert_eq!(settings.window.width, SettingsFile::default().window.width);
+        assert_eq!(settings.window.height, 700.0);
+        assert_eq!(settings.search.roots, ["/Volumes/SSD", "/Users/vj/Movies"]);
+        assert!(settings.osc.enabled);
+        assert_eq!(settings.osc.port, DEFAULT_OSC_PORT);
+    }
+}
@@ -6,17 +6,45 @@ use std::thread;
 use std::time::{Duration, Instant};
 
 use crate::app::DownloaderApp;
+use crate::bundled::ensure_bundled_tools;
 use crate::cursor::pointing;
-use crate::download::{ensure_deno, ensure_yt_dlp, update_deno, update_yt_dlp};
-use crate::fs_utils::is_executable;
+use crate::download::{
+    CancellationToken, QualityMode, TrialReport, detect_aria2c, detect_encoder_profile,
+    encoder_label, ensure_deno, ensure_yt_dlp, run_trial_download, update_deno, update_ffmpeg,
+    update_yt_dlp,
+};
+use crate::event_stream;
+use crate::faststart::{self, FaststartAuditState};
+use crate::file_list::size_label;
+use crate::i18n::{self, Msg, tr, tr_fmt};
 use crate::mac_file_dialog;
-use crate::paths::{default_download_dir, deno_path, make_absolute_path, yt_dlp_path};
-use crate::settings::{SettingsData, save_settings};
+use crate::mac_hotkey;
+use crate::mac_keychain::{delete_login_password, save_login_password};
+use crate::mac_login_item::{self, LoginItemStatus};
+use crate::network;
+use crate::osc;
+use crate::paths::{default_download_dir, deno_path, ffmpeg_path, make_absolute_path, yt_dlp_path};
+use crate::platform::{is_executable, open_with_default_app, reveal_in_file_manager};
+use crate::search_index::{SearchEngine, list_backups};
+use crate::self_check::{self, SelfCheckState};
+use crate::settings::{
+    CollisionPolicy, CookieAccount, DownloadPreset, EncoderProfile, FilenameMode, JsRuntime,
+    Language, MAX_CONCURRENT_FRAGMENTS, MAX_HEIGHT_CHOICES, MAX_INDEX_BACKUP_HOURS,
+    MAX_PARALLEL_CONVERSIONS, MAX_SCENE_THRESHOLD, MAX_STALL_MINUTES, MAX_SUBSCRIPTION_MINUTES,
+    MIN_SCENE_THRESHOLD, MIN_SUBSCRIPTION_MINUTES, SearchInputMode, SettingsData, SiteLogin,
+    WatermarkPosition, YtDlpChannel, index_backup_dir_or_default, load_encoder_profile,
+    load_yt_dlp_source, max_height_label, save_settings, split_shell_words,
+    validate_pinned_version,
+};
+use crate::shortcuts::{self, ShortcutAction, ShortcutBindings};
+use crate::ui;
+use crate::updater::{self, CURRENT_VERSION, ReleaseInfo};
 
 #[derive(Clone, Copy, Debug)]
-enum ToolKind {
+pub(crate) enum ToolKind {
     YtDlp,
     Deno,
+    Ffmpeg,
 }
 
 #[derive(Clone, Debug)]
@@ -33,10 +61,119 @@ struct ToolUpdate {
     state: ToolState,
 }
 
+// アプリ本体の更新確認の状態（依存ツールの更新とは別に管理する）
+#[derive(Clone, Debug)]
+enum AppUpdateState {
+    Idle,
+    Checking,
+    UpToDate,
+    Available(ReleaseInfo),
+    Staging(ReleaseInfo),
+    Staged(ReleaseInfo, PathBuf),
+    Failed(String),
+}
+
+// 初回セットアップの手順
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SetupStep {
+    Tools,
+    DownloadDir,
+    SearchRoots,
+    Cookies,
+    Trial,
+}
+
+impl SetupStep {
+    const ALL: [SetupStep; 5] = [
+        SetupStep::Tools,
+        SetupStep::DownloadDir,
+        SetupStep::SearchRoots,
+        SetupStep::Cookies,
+        SetupStep::Trial,
+    ];
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|step| *step == self).unwrap_or(0)
+    }
+
+    fn next(self) -> Option<Self> {
+        Self::ALL.get(self.index() + 1).copied()
+    }
+
+    fn previous(self) -> Option<Self> {
+        self.index()
+            .checked_sub(1)
+            .and_then(|index| Self::ALL.get(index).copied())
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            SetupStep::Tools => tr(Msg::SetupToolsTitle),
+            SetupStep::DownloadDir => tr(Msg::SetupFolderTitle),
+            SetupStep::SearchRoots => tr(Msg::SetupSearchTitle),
+            SetupStep::Cookies => tr(Msg::SetupCookiesTitle),
+            SetupStep::Trial => tr(Msg::SetupTrialTitle),
+        }
+    }
+
+    fn notice(self) -> &'static str {
+        match self {
+            SetupStep::Tools => tr(Msg::SetupToolsNotice),
+            SetupStep::DownloadDir => tr(Msg::SetupFolderNotice),
+            SetupStep::SearchRoots => tr(Msg::SetupSearchNotice),
+            SetupStep::Cookies => tr(Msg::SetupCookiesNotice),
+            SetupStep::Trial => tr(Msg::SetupTrialNotice),
+        }
+    }
+}
+
+// 初回セットアップのテストダウンロードの状態
+#[derive(Clone, Debug)]
+enum TrialState {
+    Idle,
+    Running,
+    Passed(TrialReport),
+    Failed(String),
+}
+
 #[derive(Clone, Debug)]
 struct SettingsForm {
     data: SettingsData,
     error: Option<String>,
+    // 現在の設定を保存するプロファイル名の入力欄
+    profile_name: String,
+    // ログイン時の起動は設定ファイルではなくシステムの登録状態を正とする。
+    launch_at_login: bool,
+    login_status: LoginItemStatus,
+    // キーの割り当てを記録中の操作
+    recording_shortcut: Option<ShortcutAction>,
+    // 戻すかを確認中の検索インデックスのバックアップ
+    restore_candidate: Option<PathBuf>,
+    // 購読に加えるチャンネル・プレイリストの URL の入力欄
+    subscription_url: String,
+}
+
+// 検索インデックスのバックアップ欄で押された操作
+enum IndexBackupAction {
+    BackupNow(PathBuf),
+    Restore(PathBuf),
+}
+
+impl SettingsForm {
+    fn load() -> Self {
+        let login_status = mac_login_item::login_item_status();
+        let data = SettingsData::load();
+        Self {
+            profile_name: data.active_profile.clone(),
+            data,
+            error: None,
+            launch_at_login: login_status.is_registered(),
+            login_status,
+            recording_shortcut: None,
+            restore_candidate: None,
+            subscription_url: String::new(),
+        }
+    }
 }
 
 pub struct SettingsUiState {
@@ -45,27 +182,60 @@ pub struct SettingsUiState {
     form: SettingsForm,
     yt_dlp: ToolState,
     deno: ToolState,
+    ffmpeg: ToolState,
     tool_tx: mpsc::Sender<ToolUpdate>,
     tool_rx: mpsc::Receiver<ToolUpdate>,
+    app_update: AppUpdateState,
+    app_update_tx: mpsc::Sender<AppUpdateState>,
+    app_update_rx: mpsc::Receiver<AppUpdateState>,
+    faststart_audit: FaststartAuditState,
+    faststart_audit_tx: mpsc::Sender<FaststartAuditState>,
+    faststart_audit_rx: mpsc::Receiver<FaststartAuditState>,
+    self_check: SelfCheckState,
+    self_check_tx: mpsc::Sender<SelfCheckState>,
+    self_check_rx: mpsc::Receiver<SelfCheckState>,
+    setup_step: SetupStep,
+    trial: TrialState,
+    trial_tx: mpsc::Sender<TrialState>,
+    trial_rx: mpsc::Receiver<TrialState>,
+    // 実行中のテストダウンロード。セットアップを閉じたら止める。
+    trial_cancel: Option<CancellationToken>,
     last_auto_refresh: Instant,
 }
 
 impl SettingsUiState {
     pub fn new() -> Self {
         let (tx, rx) = mpsc::channel();
+        let (app_update_tx, app_update_rx) = mpsc::channel();
+        let (faststart_audit_tx, faststart_audit_rx) = mpsc::channel();
+        let (self_check_tx, self_check_rx) = mpsc::channel();
+        let (trial_tx, trial_rx) = mpsc::channel();
         let yt_dlp = ToolState::from_disk(ToolKind::YtDlp);
         let deno = ToolState::from_disk(ToolKind::Deno);
+        let ffmpeg = ToolState::from_disk(ToolKind::Ffmpeg);
         let mut state = Self {
             show_settings: false,
             show_initial_setup: !yt_dlp.available,
-            form: SettingsForm {
-                data: SettingsData::load(),
-                error: None,
-            },
+            form: SettingsForm::load(),
             yt_dlp,
             deno,
+            ffmpeg,
             tool_tx: tx,
             tool_rx: rx,
+            app_update: AppUpdateState::Idle,
+            app_update_tx,
+            app_update_rx,
+            faststart_audit: FaststartAuditState::Idle,
+            faststart_audit_tx,
+            faststart_audit_rx,
+            self_check: SelfCheckState::Idle,
+            self_check_tx,
+            self_check_rx,
+            setup_step: SetupStep::Tools,
+            trial: TrialState::Idle,
+            trial_tx,
+            trial_rx,
+            trial_cancel: None,
             last_auto_refresh: Instant::now() - Duration::from_secs(10),
         };
         state.refresh_all_tools();
@@ -73,30 +243,117 @@ impl SettingsUiState {
     }
 
     pub fn open_settings(&mut self) {
-        self.form = SettingsForm {
-            data: SettingsData::load(),
-            error: None,
-        };
+        self.form = SettingsForm::load();
         self.show_settings = true;
         self.refresh_all_tools();
     }
 
+    // 設定ファイルを外から書き換えた場合に、入力フォームを読み直す。
+    pub fn reload_form(&mut self) {
+        self.form = SettingsForm::load();
+    }
+
     pub fn open_initial_setup(&mut self) {
+        if !self.show_initial_setup {
+            self.form = SettingsForm::load();
+            self.setup_step = SetupStep::Tools;
+            self.trial = TrialState::Idle;
+        }
         self.show_initial_setup = true;
         self.refresh_all_tools();
     }
 
+    fn start_trial(&mut self, output_dir: PathBuf) {
+        self.trial = TrialState::Running;
+        let cancel = CancellationToken::new();
+        self.trial_cancel = Some(cancel.clone());
+        let tx = self.trial_tx.clone();
+        thread::spawn(move || {
+            let state = match run_trial_download(&output_dir, &cancel) {
+                Ok(report) => TrialState::Passed(report),
+                Err(err) => TrialState::Failed(err),
+            };
+            let _ = tx.send(state);
+        });
+    }
+
+    fn cancel_trial(&mut self) {
+        if let Some(cancel) = self.trial_cancel.take() {
+            cancel.cancel();
+        }
+        if matches!(self.trial, TrialState::Running) {
+            self.trial = TrialState::Idle;
+        }
+    }
+
     pub fn poll_tool_updates(&mut self) {
         while let Ok(update) = self.tool_rx.try_recv() {
             match update.kind {
                 ToolKind::YtDlp => self.yt_dlp = update.state,
                 ToolKind::Deno => self.deno = update.state,
+                ToolKind::Ffmpeg => self.ffmpeg = update.state,
+            }
+        }
+        while let Ok(update) = self.app_update_rx.try_recv() {
+            self.app_update = update;
+        }
+        while let Ok(state) = self.faststart_audit_rx.try_recv() {
+            self.faststart_audit = state;
+        }
+        while let Ok(state) = self.self_check_rx.try_recv() {
+            self.self_check = state;
+        }
+        while let Ok(state) = self.trial_rx.try_recv() {
+            // 閉じて止めた後に届いた結果は使わない。
+            if self.trial_cancel.take().is_some() {
+                self.trial = state;
             }
         }
     }
 
+    fn start_faststart_audit(&mut self, paths: Vec<PathBuf>) {
+        self.faststart_audit = FaststartAuditState::Running {
+            checked: 0,
+            total: paths.len(),
+        };
+        faststart::start_faststart_audit(paths, self.faststart_audit_tx.clone());
+    }
+
+    fn start_self_check(&mut self, output_dir: PathBuf, engine: Option<SearchEngine>) {
+        self.self_check = SelfCheckState::Running;
+        self_check::start_self_check(output_dir, engine, self.self_check_tx.clone());
+    }
+
+    fn check_app_update(&mut self) {
+        self.app_update = AppUpdateState::Checking;
+        let tx = self.app_update_tx.clone();
+        thread::spawn(move || {
+            let state = match updater::fetch_latest_release() {
+                Ok(release) if release.is_newer_than_current() => {
+                    AppUpdateState::Available(release)
+                }
+                Ok(_) => AppUpdateState::UpToDate,
+                Err(err) => AppUpdateState::Failed(err),
+            };
+            let _ = tx.send(state);
+        });
+    }
+
+    fn stage_app_update(&mut self, release: ReleaseInfo) {
+        self.app_update = AppUpdateState::Staging(release.clone());
+        let tx = self.app_update_tx.clone();
+        thread::spawn(move || {
+            let state = match updater::stage_update(&release) {
+                Ok(path) => AppUpdateState::Staged(release, path),
+                Err(err) => AppUpdateState::Failed(tr_fmt(Msg::UpdatePrepareFailed, &[&err])),
+            };
+            let _ = tx.send(state);
+        });
+    }
+
     pub fn auto_refresh_if_needed(&mut self) {
-        if (self.yt_dlp.available && self.deno.available) || self.yt_dlp.busy || self.deno.busy {
+        let deno_ready = self.deno.available || self.form.data.js_runtime != JsRuntime::Deno;
+        if (self.yt_dlp.available && deno_ready) || self.yt_dlp.busy || self.deno.busy {
             return;
         }
         if self.last_auto_refresh.elapsed() >= Duration::from_secs(5) {
@@ -108,17 +365,22 @@ impl SettingsUiState {
     fn refresh_all_tools(&mut self) {
         self.refresh_tool(ToolKind::YtDlp);
         self.refresh_tool(ToolKind::Deno);
+        self.refresh_tool(ToolKind::Ffmpeg);
     }
 
     fn refresh_tool(&mut self, kind: ToolKind) {
         match kind {
             ToolKind::YtDlp => {
                 self.yt_dlp.busy = true;
-                self.yt_dlp.status = "yt-dlpの状態を確認中...".to_string();
+                self.yt_dlp.status = tr(Msg::CheckingYtDlp).to_string();
             }
             ToolKind::Deno => {
                 self.deno.busy = true;
-                self.deno.status = "Denoの状態を確認中...".to_string();
+                self.deno.status = tr(Msg::CheckingDeno).to_string();
+            }
+            ToolKind::Ffmpeg => {
+                self.ffmpeg.busy = true;
+                self.ffmpeg.status = tr(Msg::CheckingFfmpeg).to_string();
             }
         }
         let tx = self.tool_tx.clone();
@@ -138,6 +400,10 @@ impl SettingsUiState {
                 self.deno.busy = true;
                 self.deno.status = action.status_text("Deno");
             }
+            ToolKind::Ffmpeg => {
+                self.ffmpeg.busy = true;
+                self.ffmpeg.status = action.status_text("ffmpeg");
+            }
         }
 
         let tx = self.tool_tx.clone();
@@ -147,11 +413,15 @@ impl SettingsUiState {
                 (ToolKind::YtDlp, ToolAction::Update) => update_yt_dlp(None),
                 (ToolKind::Deno, ToolAction::Install) => ensure_deno(None),
                 (ToolKind::Deno, ToolAction::Update) => update_deno(None),
+                (ToolKind::Ffmpeg, ToolAction::Install) => {
+                    ensure_bundled_tools().map(|()| ffmpeg_path())
+                }
+                (ToolKind::Ffmpeg, ToolAction::Update) => update_ffmpeg(None),
             };
 
             let mut state = ToolState::check(kind);
             if let Err(err) = result {
-                state.status = format!("セットアップに失敗しました: {err}");
+                state.status = tr_fmt(Msg::SetupFailed, &[&err]);
             }
             let _ = tx.send(ToolUpdate { kind, state });
         });
@@ -167,15 +437,15 @@ enum ToolAction {
 impl ToolAction {
     fn status_text(self, label: &str) -> String {
         match self {
-            ToolAction::Install => format!("{label}をセットアップ中..."),
-            ToolAction::Update => format!("{label}を更新中..."),
+            ToolAction::Install => tr_fmt(Msg::SettingUpTool, &[&label]),
+            ToolAction::Update => tr_fmt(Msg::UpdatingTool, &[&label]),
         }
     }
 
     fn button_text(self) -> &'static str {
         match self {
-            ToolAction::Install => "自動セットアップ",
-            ToolAction::Update => "最新を取得",
+            ToolAction::Install => tr(Msg::AutoSetup),
+            ToolAction::Update => tr(Msg::GetLatest),
         }
     }
 }
@@ -185,9 +455,15 @@ impl ToolState {
         let path = tool_path(kind);
         let available = path.exists() && is_executable(&path);
         let (version, status) = if available {
-            ("確認中...".to_string(), "バージョンを確認中...".to_string())
+            (
+                tr(Msg::Checking).to_string(),
+                tr(Msg::CheckingVersion).to_string(),
+            )
         } else {
-            ("未インストール".to_string(), "未インストール".to_string())
+            (
+                tr(Msg::NotInstalled).to_string(),
+                tr(Msg::NotInstalled).to_string(),
+            )
         };
         Self {
             version,
@@ -201,27 +477,51 @@ impl ToolState {
         let path = tool_path(kind);
         if !path.exists() {
             return Self {
-                version: "未インストール".to_string(),
-                status: "未インストール".to_string(),
+                version: tr(Msg::NotInstalled).to_string(),
+                status: tr(Msg::NotInstalled).to_string(),
                 busy: false,
                 available: false,
             };
         }
         if !is_executable(&path) {
             return Self {
-                version: "権限不足".to_string(),
-                status: "実行権限がありません。".to_string(),
+                version: tr(Msg::NoPermission).to_string(),
+                status: tr(Msg::NotExecutable).to_string(),
                 busy: false,
                 available: false,
             };
         }
 
-        let version = read_tool_version(kind, &path).unwrap_or_else(|_| "不明".to_string());
-        let status = if version == "不明" {
-            "バージョン取得に失敗しました。".to_string()
+        let version =
+            read_tool_version(kind, &path).unwrap_or_else(|_| tr(Msg::Unknown).to_string());
+        let mut status = if version == tr(Msg::Unknown) {
+            tr(Msg::VersionCheckFailed).to_string()
         } else {
-            "準備完了".to_string()
+            tr(Msg::Ready).to_string()
         };
+        if matches!(kind, ToolKind::YtDlp) {
+            let (channel, pinned) = load_yt_dlp_source();
+            status = if pinned.is_empty() {
+                tr_fmt(Msg::ToolStatusLatest, &[&status, &channel.label()])
+            } else if version != pinned {
+                tr_fmt(Msg::PinnedVersionMismatch, &[&pinned, &channel.label()])
+            } else {
+                tr_fmt(Msg::ToolStatusPinned, &[&status, &channel.label(), &pinned])
+            };
+        }
+        if matches!(kind, ToolKind::Ffmpeg) {
+            let preferred = load_encoder_profile();
+            status = match detect_encoder_profile(&path, preferred) {
+                Ok(profile) if profile == preferred => {
+                    tr_fmt(Msg::ToolStatusEncoder, &[&status, &encoder_label(profile)])
+                }
+                Ok(profile) => tr_fmt(
+                    Msg::ToolStatusEncoderFallback,
+                    &[&status, &encoder_label(preferred), &encoder_label(profile)],
+                ),
+                Err(err) => err,
+            };
+        }
         Self {
             version,
             status,
@@ -232,16 +532,27 @@ impl ToolState {
 }
 
 pub fn render_toolbar(
-    // 設定ウィンドウを開くためのアプリ状態
+    // 割り当て済みのショートカットと各操作の状態を持つアプリ
     app: &mut DownloaderApp,
     // キー入力検知に使うeguiコンテキスト
     ctx: &egui::Context,
 ) {
-    if ctx.input(|i| i.modifiers.command && i.key_pressed(egui::Key::Comma)) {
-        app.settings_ui.open_settings();
+    // 割り当てを記録している間は、押したキーで操作しない。
+    if app.settings_ui.form.recording_shortcut.is_some() {
+        return;
     }
-    if ctx.input(|i| i.modifiers.command && i.key_pressed(egui::Key::L)) {
-        app.log_ui.open_logs();
+    for action in app.shortcuts.consume_pressed(ctx) {
+        match action {
+            ShortcutAction::StartDownload => app.start_download_from_clipboard(),
+            ShortcutAction::Cancel => {
+                if app.download_in_progress {
+                    app.request_cancel_download();
+                }
+            }
+            ShortcutAction::FocusSearch => ui::focus_search_input(app, ctx),
+            ShortcutAction::OpenSettings => app.settings_ui.open_settings(),
+            ShortcutAction::OpenLogs => app.log_ui.open_logs(),
+        }
     }
 }
 
@@ -268,8 +579,8 @@ fn render_initial_setup_viewport(
     let mut close_requested = false;
     let viewport_id = initial_setup_viewport_id();
     let builder = egui::ViewportBuilder::default()
-        .with_title("初回セットアップ")
-        .with_inner_size(egui::vec2(560.0, 520.0))
+        .with_title(tr(Msg::InitialSetup))
+        .with_inner_size(egui::vec2(600.0, 600.0))
         .with_resizable(false)
         .with_always_on_top();
 
@@ -281,13 +592,13 @@ fn render_initial_setup_viewport(
         match class {
             egui::ViewportClass::Embedded => {
                 let mut open = true;
-                egui::Window::new("初回セットアップ")
+                egui::Window::new(tr(Msg::InitialSetup))
                     .collapsible(false)
                     .resizable(false)
-                    .default_width(560.0)
+                    .default_width(600.0)
                     .open(&mut open)
                     .show(ctx, |ui| {
-                        render_initial_setup_contents(ui, app);
+                        render_initial_setup_contents(ui, app, &mut close_requested);
                     });
                 if !open {
                     close_requested = true;
@@ -295,7 +606,7 @@ fn render_initial_setup_viewport(
             }
             _ => {
                 egui::CentralPanel::default().show(ctx, |ui| {
-                    render_initial_setup_contents(ui, app);
+                    render_initial_setup_contents(ui, app, &mut close_requested);
                 });
             }
         }
@@ -303,6 +614,7 @@ fn render_initial_setup_viewport(
 
     if close_requested {
         app.settings_ui.show_initial_setup = false;
+        app.settings_ui.cancel_trial();
     }
 }
 
@@ -319,7 +631,7 @@ fn render_settings_viewport(
     let mut close_requested = false;
     let viewport_id = settings_viewport_id();
     let builder = egui::ViewportBuilder::default()
-        .with_title("設定")
+        .with_title(tr(Msg::Settings))
         .with_inner_size(egui::vec2(640.0, 640.0))
         .with_resizable(false)
         .with_always_on_top();
@@ -332,7 +644,7 @@ fn render_settings_viewport(
         match class {
             egui::ViewportClass::Embedded => {
                 let mut open = true;
-                egui::Window::new("設定")
+                egui::Window::new(tr(Msg::Settings))
                     .collapsible(false)
                     .resizable(false)
                     .default_width(620.0)
@@ -362,7 +674,10 @@ fn render_initial_setup_contents(
     ui: &mut egui::Ui,
     // ツールの状態や操作を持つアプリ
     app: &mut DownloaderApp,
+    // 完了で閉じるべきかのフラグ
+    should_close: &mut bool,
 ) {
+    let step = app.settings_ui.setup_step;
     egui::Frame::NONE
         .inner_margin(egui::Margin {
             left: 16,
@@ -373,50 +688,227 @@ fn render_initial_setup_contents(
         .show(ui, |ui| {
             ui.add_space(4.0);
             ui.label(
-                egui::RichText::new("yt-dlpのセットアップ")
+                egui::RichText::new(tr_fmt(
+                    Msg::SetupStepOf,
+                    &[&(step.index() + 1), &SetupStep::ALL.len()],
+                ))
+                .size(11.5)
+                .color(egui::Color32::from_rgb(16, 190, 255)),
+            );
+            ui.label(
+                egui::RichText::new(step.title())
                     .size(18.0)
                     .strong()
                     .color(egui::Color32::from_rgb(220, 230, 245)),
             );
             ui.label(
-                egui::RichText::new(
-                    "初回起動ではyt-dlpのダウンロードと実行権限の付与が必要です。\nボタン一つで最新を取得して、すぐにダウンロードを開始できます。",
-                )
-                .size(12.0)
-                .color(egui::Color32::from_rgb(140, 150, 170)),
+                egui::RichText::new(step.notice())
+                    .size(12.0)
+                    .color(egui::Color32::from_rgb(140, 150, 170)),
             );
             ui.add_space(12.0);
 
-            render_tool_card(
-                ui,
-                &mut app.settings_ui,
-                ToolKind::YtDlp,
-                ToolAction::Install,
-            );
-            ui.add_space(8.0);
-            render_tool_card(
-                ui,
-                &mut app.settings_ui,
-                ToolKind::Deno,
-                ToolAction::Install,
-            );
+            egui::ScrollArea::vertical()
+                .max_height(400.0)
+                .auto_shrink([false, true])
+                .show(ui, |ui| match step {
+                    SetupStep::Tools => render_setup_tools(ui, &mut app.settings_ui),
+                    SetupStep::DownloadDir => {
+                        ui.horizontal(|ui| {
+                            render_download_dir_picker(ui, &mut app.settings_ui);
+                        });
+                    }
+                    SetupStep::SearchRoots => {
+                        if render_search_roots_section(ui, &mut app.settings_ui) {
+                            app.settings_ui.form.error = app.request_reindex_all().err();
+                        }
+                    }
+                    SetupStep::Cookies => render_cookie_section(ui, &mut app.settings_ui),
+                    SetupStep::Trial => {
+                        if render_setup_trial(ui, &app.settings_ui) {
+                            let output_dir = app.download_dir.clone();
+                            app.settings_ui.start_trial(output_dir);
+                        }
+                    }
+                });
+
+            if let Some(err) = &app.settings_ui.form.error {
+                ui.add_space(8.0);
+                ui.label(
+                    egui::RichText::new(err)
+                        .size(12.0)
+                        .color(egui::Color32::from_rgb(248, 113, 113)),
+                );
+            }
+
             ui.add_space(10.0);
+            let tools_ready = app.settings_ui.yt_dlp.available
+                && app.settings_ui.ffmpeg.available
+                && (app.settings_ui.deno.available
+                    || app.settings_ui.form.data.js_runtime != JsRuntime::Deno);
+            let trial_running = matches!(app.settings_ui.trial, TrialState::Running);
             ui.horizontal(|ui| {
+                let open_btn = egui::Button::new(
+                    egui::RichText::new(tr(Msg::OpenSettings))
+                        .size(11.5)
+                        .color(egui::Color32::from_rgb(180, 200, 220)),
+                )
+                .fill(egui::Color32::from_rgb(26, 34, 52));
+                if pointing(ui.add(open_btn)).clicked() {
+                    app.settings_ui.open_settings();
+                }
+
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                    let open_btn = egui::Button::new(
-                        egui::RichText::new("設定を開く")
-                            .size(11.5)
-                            .color(egui::Color32::from_rgb(180, 200, 220)),
+                    let (label, enabled) = match step.next() {
+                        Some(_) => (tr(Msg::SetupNext), step != SetupStep::Tools || tools_ready),
+                        None => (tr(Msg::SetupFinish), !trial_running),
+                    };
+                    let next_btn = egui::Button::new(
+                        egui::RichText::new(label)
+                            .size(12.5)
+                            .color(egui::Color32::from_rgb(8, 14, 24)),
                     )
-                    .fill(egui::Color32::from_rgb(26, 34, 52));
-                    if pointing(ui.add(open_btn)).clicked() {
-                        app.settings_ui.open_settings();
+                    .fill(egui::Color32::from_rgb(16, 190, 255));
+                    if pointing(ui.add_enabled(enabled, next_btn)).clicked() {
+                        advance_setup_step(app, ui.ctx(), should_close);
+                    }
+
+                    if let Some(previous) = step.previous() {
+                        let back_btn = egui::Button::new(
+                            egui::RichText::new(tr(Msg::SetupBack))
+                                .size(12.0)
+                                .color(egui::Color32::from_rgb(180, 190, 210)),
+                        )
+                        .fill(egui::Color32::from_rgb(24, 30, 45));
+                        if pointing(ui.add_enabled(!trial_running, back_btn)).clicked() {
+                            app.settings_ui.form.error = None;
+                            app.settings_ui.setup_step = previous;
+                        }
+                    }
+                    if step == SetupStep::Tools && !tools_ready {
+                        ui.label(
+                            egui::RichText::new(tr(Msg::SetupToolsRequired))
+                                .size(11.0)
+                                .color(egui::Color32::from_rgb(140, 150, 170)),
+                        );
                     }
                 });
             });
         });
 }
 
+// 次の手順へ進む。テストダウンロードの前に入力した設定を保存し、最後の手順では閉じる。
+fn advance_setup_step(
+    // 入力中の設定と手順を持つアプリ
+    app: &mut DownloaderApp,
+    // 保存した設定の反映に使うコンテキスト
+    ctx: &egui::Context,
+    // 完了で閉じるべきかのフラグ
+    should_close: &mut bool,
+) {
+    let step = app.settings_ui.setup_step;
+    let Some(next) = step.next() else {
+        *should_close = true;
+        return;
+    };
+    if next == SetupStep::Trial {
+        if let Err(err) = save_settings_form(app, ctx) {
+            app.settings_ui.form.error = Some(err);
+            return;
+        }
+        app.settings_ui.trial = TrialState::Idle;
+    }
+    app.settings_ui.form.error = None;
+    app.settings_ui.setup_step = next;
+}
+
+fn render_setup_tools(
+    // ツールカードの描画先
+    ui: &mut egui::Ui,
+    // ツール状態とアクションを持つ設定UI
+    state: &mut SettingsUiState,
+) {
+    render_tool_card(ui, state, ToolKind::YtDlp, ToolAction::Install);
+    if state.form.data.js_runtime == JsRuntime::Deno {
+        ui.add_space(8.0);
+        render_tool_card(ui, state, ToolKind::Deno, ToolAction::Install);
+    }
+    ui.add_space(8.0);
+    render_tool_card(ui, state, ToolKind::Ffmpeg, ToolAction::Install);
+}
+
+fn render_setup_trial(
+    // テストダウンロード欄の描画先
+    ui: &mut egui::Ui,
+    // テストの状態を持つ設定UI
+    state: &SettingsUiState,
+) -> bool {
+    let running = matches!(state.trial, TrialState::Running);
+    let mut start = false;
+    ui.horizontal(|ui| {
+        let btn = egui::Button::new(
+            egui::RichText::new(tr(Msg::RunTrialDownload))
+                .size(12.0)
+                .color(egui::Color32::from_rgb(8, 14, 24)),
+        )
+        .fill(egui::Color32::from_rgb(16, 190, 255));
+        if pointing(ui.add_enabled(!running, btn)).clicked() {
+            start = true;
+        }
+        if running {
+            ui.add(egui::Spinner::new().size(16.0));
+            ui.label(
+                egui::RichText::new(tr(Msg::TrialRunning))
+                    .size(12.0)
+                    .color(egui::Color32::from_rgb(140, 150, 170)),
+            );
+        }
+    });
+
+    ui.add_space(8.0);
+    match &state.trial {
+        TrialState::Idle | TrialState::Running => {}
+        TrialState::Passed(report) => {
+            ui.add(
+                egui::Label::new(
+                    egui::RichText::new(tr_fmt(
+                        Msg::TrialPassed,
+                        &[
+                            &report.video_codec,
+                            &report.width,
+                            &report.height,
+                            &size_label(report.size_bytes),
+                        ],
+                    ))
+                    .size(12.0)
+                    .color(egui::Color32::from_rgb(52, 211, 153)),
+                )
+                .wrap(),
+            );
+        }
+        TrialState::Failed(err) => {
+            ui.add(
+                egui::Label::new(
+                    egui::RichText::new(err)
+                        .size(12.0)
+                        .color(egui::Color32::from_rgb(248, 113, 113)),
+                )
+                .wrap(),
+            );
+            ui.label(
+                egui::RichText::new(tr(Msg::TrialFailedHint))
+                    .size(11.5)
+                    .color(egui::Color32::from_rgb(140, 150, 170)),
+            );
+        }
+    }
+
+    if running {
+        ui.ctx().request_repaint_after(Duration::from_millis(250));
+    }
+    start
+}
+
 fn render_settings_contents(
     // 設定画面の描画先
     ui: &mut egui::Ui,
@@ -443,24 +935,36 @@ fn render_settings_contents(
                 .auto_shrink([false, false])
                 .show(ui, |ui| {
                     ui.label(
-                        egui::RichText::new("アプリ設定")
+                        egui::RichText::new(tr(Msg::AppSettings))
                             .size(18.0)
                             .strong()
                             .color(egui::Color32::from_rgb(220, 230, 245)),
                     );
                     ui.label(
-                        egui::RichText::new(
-                            "ウィンドウサイズ、保存先、検索対象、依存ツールの状態をまとめて管理します。",
-                        )
-                        .size(12.0)
-                        .color(egui::Color32::from_rgb(140, 150, 170)),
+                        egui::RichText::new(tr(Msg::AppSettingsNotice))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(140, 150, 170)),
                     );
                     ui.add_space(10.0);
 
+                    render_profile_section(ui, &mut app.settings_ui);
+                    ui.add_space(10.0);
+                    render_preset_section(ui, &mut app.settings_ui);
+                    ui.add_space(10.0);
+                    render_watch_folder_section(ui, &mut app.settings_ui);
+                    ui.add_space(10.0);
+                    render_subscription_section(ui, &mut app.settings_ui);
+                    ui.add_space(10.0);
                     render_window_section(ui, &mut app.settings_ui);
                     ui.add_space(10.0);
                     render_cookie_section(ui, &mut app.settings_ui);
                     ui.add_space(10.0);
+                    render_login_section(ui, &mut app.settings_ui);
+                    ui.add_space(10.0);
+                    render_osc_section(ui, &mut app.settings_ui);
+                    ui.add_space(10.0);
+                    render_event_stream_section(ui, &mut app.settings_ui);
+                    ui.add_space(10.0);
                     let request_reindex = render_search_roots_section(ui, &mut app.settings_ui);
                     if request_reindex {
                         if let Err(err) = app.request_reindex_all() {
@@ -469,6 +973,32 @@ fn render_settings_contents(
                             app.settings_ui.form.error = None;
                         }
                     }
+                    ui.add_space(8.0);
+                    match render_index_backup_section(ui, &mut app.settings_ui) {
+                        Some(IndexBackupAction::BackupNow(dir)) => {
+                            app.start_index_backup(dir, true);
+                        }
+                        Some(IndexBackupAction::Restore(path)) => {
+                            app.settings_ui.form.error = app
+                                .restore_index(&path)
+                                .err()
+                                .map(|err| tr_fmt(Msg::IndexRestoreFailed, &[&err]));
+                        }
+                        None => {}
+                    }
+                    ui.add_space(8.0);
+                    if render_faststart_audit_card(ui, &mut app.settings_ui) {
+                        match app.indexed_paths() {
+                            Ok(paths) => app.settings_ui.start_faststart_audit(paths),
+                            Err(err) => app.settings_ui.form.error = Some(err),
+                        }
+                    }
+                    ui.add_space(8.0);
+                    if render_self_check_card(ui, &app.settings_ui) {
+                        let output_dir = app.download_dir.clone();
+                        let engine = app.search_engine.clone();
+                        app.settings_ui.start_self_check(output_dir, engine);
+                    }
 
                     ui.add_space(12.0);
                     render_tool_card(
@@ -477,8 +1007,25 @@ fn render_settings_contents(
                         ToolKind::YtDlp,
                         ToolAction::Update,
                     );
+                    // Deno 以外のランタイムを選んでいる場合は、Deno を取得・更新しない。
+                    if app.settings_ui.form.data.js_runtime == JsRuntime::Deno {
+                        ui.add_space(8.0);
+                        render_tool_card(
+                            ui,
+                            &mut app.settings_ui,
+                            ToolKind::Deno,
+                            ToolAction::Update,
+                        );
+                    }
+                    ui.add_space(8.0);
+                    render_tool_card(
+                        ui,
+                        &mut app.settings_ui,
+                        ToolKind::Ffmpeg,
+                        ToolAction::Update,
+                    );
                     ui.add_space(8.0);
-                    render_tool_card(ui, &mut app.settings_ui, ToolKind::Deno, ToolAction::Update);
+                    render_app_update_card(ui, &mut app.settings_ui);
 
                     if let Some(err) = &app.settings_ui.form.error {
                         ui.add_space(8.0);
@@ -499,32 +1046,17 @@ fn render_settings_contents(
                             )
                             .fill(egui::Color32::from_rgb(16, 190, 255));
                             if pointing(ui.add(save_btn)).clicked() {
-                                if let Err(err) = apply_settings_changes(
-                                    &mut app.settings_ui,
-                                    &mut app.download_dir,
-                                    &mut app.refresh_needed,
-                                    &mut app.pending_window_resize,
-                                ) {
-                                    app.settings_ui.form.error = Some(err);
-                                } else {
-                                    let roots = app.settings_ui.form.data.search_roots.clone();
-                                    match app.sync_search_roots(&roots) {
-                                        Ok(()) => {
-                                            app.settings_ui.form.error = None;
-                                            app.mark_search_dirty();
-                                            *should_close = true;
-                                        }
-                                        Err(err) => {
-                                            app.settings_ui.form.error = Some(format!(
-                                                "検索対象フォルダの同期に失敗しました: {err}"
-                                            ));
-                                        }
+                                match save_settings_form(app, ui.ctx()) {
+                                    Ok(()) => {
+                                        app.settings_ui.form.error = None;
+                                        *should_close = true;
                                     }
+                                    Err(err) => app.settings_ui.form.error = Some(err),
                                 }
                             }
 
                             let cancel_btn = egui::Button::new(
-                                egui::RichText::new("キャンセル")
+                                egui::RichText::new(tr(Msg::Cancel))
                                     .size(12.0)
                                     .color(egui::Color32::from_rgb(180, 190, 210)),
                             )
@@ -540,6 +1072,47 @@ fn render_settings_contents(
         });
 }
 
+// 入力中の設定を保存し、ホットキー・OSC・検索対象などアプリの状態へ反映する。
+fn save_settings_form(
+    // 入力フォームと反映先を持つアプリ
+    app: &mut DownloaderApp,
+    // ホットキー・通知などの登録に使うコンテキスト
+    ctx: &egui::Context,
+) -> Result<(), String> {
+    apply_settings_changes(
+        &mut app.settings_ui,
+        &mut app.download_dir,
+        &mut app.refresh_needed,
+        &mut app.pending_window_resize,
+    )?;
+    let hotkey = app.settings_ui.form.data.hotkey_summon.clone();
+    app.register_summon_hotkey(ctx, &hotkey);
+    let saved = app.settings_ui.form.data.clone();
+    i18n::set_language(saved.language);
+    app.apply_shortcuts(&saved.shortcuts);
+    app.restart_osc(ctx, &saved);
+    app.restart_event_stream(&saved);
+    app.restart_watch_folder(ctx, &saved);
+    app.restart_subscriptions(ctx, &saved);
+    app.sync_profiles(&saved);
+    app.sync_presets(&saved);
+    app.sync_cookie_accounts(&saved);
+    app.recent_download_dirs = saved.recent_download_dirs.clone();
+    app.apply_finder_tag_reading(saved.search_read_finder_tags);
+    app.apply_index_skip_rules(saved.index_skip_rules());
+    app.input_source_notify = saved.input_source_notify;
+    app.search_input_mode = saved.search_input_mode;
+    app.cloud_fetch_on_drag = saved.cloud_fetch_on_drag;
+    app.set_battery_saver(saved.battery_saver);
+    network::configure(saved.check_connection, &saved.yt_dlp_extra_args);
+    app.apply_notification_setting(ctx, saved.notifications_enabled);
+    app.apply_status_item_setting(ctx, saved.status_item_enabled);
+    app.sync_search_roots(&saved)
+        .map_err(|err| tr_fmt(Msg::SearchSyncFailed, &[&err]))?;
+    app.mark_search_dirty();
+    Ok(())
+}
+
 fn initial_setup_viewport_id() -> egui::ViewportId {
     egui::ViewportId::from_hash_of("initial_setup_viewport")
 }
@@ -569,227 +1142,1773 @@ fn render_window_section(
                 .spacing(egui::vec2(16.0, 12.0))
                 .show(ui, |ui| {
                     ui.label(
-                        egui::RichText::new("画面幅")
+                        egui::RichText::new(tr(Msg::Language))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    egui::ComboBox::from_id_salt("settings-language")
+                        .selected_text(state.form.data.language.label())
+                        .width(160.0)
+                        .show_ui(ui, |ui| {
+                            for language in Language::ALL {
+                                let _ = pointing(ui.selectable_value(
+                                    &mut state.form.data.language,
+                                    language,
+                                    language.label(),
+                                ));
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label(
+                        egui::RichText::new(tr(Msg::WindowWidth))
                             .size(12.0)
                             .color(egui::Color32::from_rgb(150, 160, 180)),
                     );
-                    add_text_input(ui, &mut state.form.data.window_width, 120.0, "例: 320");
+                    add_text_input(
+                        ui,
+                        &mut state.form.data.window_width,
+                        120.0,
+                        tr(Msg::WidthHint),
+                    );
                     ui.end_row();
 
                     ui.label(
-                        egui::RichText::new("画面高さ")
+                        egui::RichText::new(tr(Msg::WindowHeight))
                             .size(12.0)
                             .color(egui::Color32::from_rgb(150, 160, 180)),
                     );
-                    add_text_input(ui, &mut state.form.data.window_height, 120.0, "例: 1000");
+                    add_text_input(
+                        ui,
+                        &mut state.form.data.window_height,
+                        120.0,
+                        tr(Msg::HeightHint),
+                    );
                     ui.end_row();
 
                     ui.label(
-                        egui::RichText::new("出力先フォルダ")
+                        egui::RichText::new(tr(Msg::OutputFolder))
                             .size(12.0)
                             .color(egui::Color32::from_rgb(150, 160, 180)),
                     );
-                    let mut selected_dir = None;
                     ui.horizontal(|ui| {
-                        let input_width = (ui.available_width() - 120.0).max(200.0);
-                        let default_hint_path = default_download_dir();
-                        let default_hint = default_hint_path.to_string_lossy();
-                        add_text_input(
-                            ui,
-                            &mut state.form.data.download_dir,
-                            input_width,
-                            default_hint.as_ref(),
-                        );
-                        let pick_btn = egui::Button::new(
-                            egui::RichText::new("フォルダを選択")
-                                .size(11.5)
-                                .color(egui::Color32::from_rgb(180, 200, 220)),
-                        )
-                        .fill(egui::Color32::from_rgb(26, 34, 52));
-                        if pointing(ui.add(pick_btn)).clicked() {
-                            let current = state.form.data.download_dir.trim();
-                            let current_path = if current.is_empty() {
-                                None
-                            } else {
-                                Some(PathBuf::from(current))
-                            };
-                            selected_dir =
-                                mac_file_dialog::choose_directory(current_path.as_deref());
-                        }
+                        render_download_dir_picker(ui, state);
                     });
-                    if let Some(path) = selected_dir {
-                        state.form.data.download_dir = path.to_string_lossy().to_string();
-                    }
                     ui.end_row();
-                });
-        });
-}
 
-fn render_cookie_section(
-    // Cookie設定セクションの描画先
-    ui: &mut egui::Ui,
-    // Cookie関連の入力フォーム状態
-    state: &mut SettingsUiState,
-) {
-    let panel_fill = egui::Color32::from_rgb(20, 26, 40);
-    let panel_stroke = egui::Stroke::new(1.0, egui::Color32::from_rgb(44, 56, 78));
+                    ui.label(
+                        egui::RichText::new(tr(Msg::SummonShortcut))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    add_text_input(
+                        ui,
+                        &mut state.form.data.hotkey_summon,
+                        220.0,
+                        tr(Msg::SummonShortcutHint),
+                    );
+                    ui.end_row();
 
-    egui::Frame::NONE
+                    ui.label(
+                        egui::RichText::new(tr(Msg::Shortcuts))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    render_shortcut_bindings(ui, &mut state.form);
+                    ui.end_row();
+
+                    ui.label(
+                        egui::RichText::new(tr(Msg::YtDlpSource))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    ui.horizontal(|ui| {
+                        for channel in [YtDlpChannel::Stable, YtDlpChannel::Nightly] {
+                            let _ = pointing(ui.radio_value(
+                                &mut state.form.data.yt_dlp_channel,
+                                channel,
+                                channel.label(),
+                            ));
+                        }
+                        add_text_input(
+                            ui,
+                            &mut state.form.data.yt_dlp_version,
+                            160.0,
+                            tr(Msg::PinnedVersionHint),
+                        );
+                    });
+                    ui.end_row();
+
+                    ui.label(
+                        egui::RichText::new(tr(Msg::JsRuntime))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    ui.vertical(|ui| {
+                        ui.horizontal(|ui| {
+                            for runtime in JsRuntime::ALL {
+                                let _ = pointing(ui.radio_value(
+                                    &mut state.form.data.js_runtime,
+                                    runtime,
+                                    runtime.label(),
+                                ));
+                            }
+                        });
+                        let notice = match state.form.data.js_runtime {
+                            JsRuntime::Deno => Msg::JsRuntimeDenoNotice,
+                            JsRuntime::Node => Msg::JsRuntimeNodeNotice,
+                            JsRuntime::Disabled => Msg::JsRuntimeDisabledNotice,
+                        };
+                        ui.label(
+                            egui::RichText::new(tr(notice))
+                                .size(11.0)
+                                .color(egui::Color32::from_rgb(140, 150, 170)),
+                        );
+                    });
+                    ui.end_row();
+
+                    ui.label(
+                        egui::RichText::new(tr(Msg::YtDlpExtraArgs))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    ui.vertical(|ui| {
+                        add_text_input(
+                            ui,
+                            &mut state.form.data.yt_dlp_extra_args,
+                            320.0,
+                            tr(Msg::YtDlpExtraArgsHint),
+                        );
+                        if !state.form.data.yt_dlp_extra_args.trim().is_empty() {
+                            ui.label(
+                                egui::RichText::new(tr(Msg::YtDlpExtraArgsNotice))
+                                    .size(11.0)
+                                    .color(egui::Color32::from_rgb(251, 191, 36)),
+                            );
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label(
+                        egui::RichText::new(tr(Msg::ConnectionCheck))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    let _ = pointing(ui.checkbox(
+                        &mut state.form.data.check_connection,
+                        tr(Msg::ConnectionCheckOption),
+                    ))
+                    .on_hover_text(tr(Msg::ConnectionCheckHint));
+                    ui.end_row();
+
+                    ui.label(
+                        egui::RichText::new(tr(Msg::MaxQuality))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    egui::ComboBox::from_id_salt("settings-max-height")
+                        .selected_text(max_height_label(state.form.data.download_max_height))
+                        .width(120.0)
+                        .show_ui(ui, |ui| {
+                            for height in MAX_HEIGHT_CHOICES {
+                                let _ = pointing(ui.selectable_value(
+                                    &mut state.form.data.download_max_height,
+                                    height,
+                                    max_height_label(height),
+                                ));
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label(
+                        egui::RichText::new(tr(Msg::ConcurrentFragments))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::DragValue::new(&mut state.form.data.concurrent_fragments)
+                                .range(1..=MAX_CONCURRENT_FRAGMENTS),
+                        );
+                        let _ = pointing(ui.checkbox(
+                            &mut state.form.data.auto_concurrent_fragments,
+                            tr(Msg::AutoTuneFragments),
+                        ))
+                        .on_hover_text(tr(Msg::AutoTuneFragmentsHint));
+                    });
+                    ui.end_row();
+
+                    ui.label(
+                        egui::RichText::new(tr(Msg::ExternalDownloader))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    ui.vertical(|ui| {
+                        let _ = pointing(
+                            ui.checkbox(&mut state.form.data.use_aria2c, tr(Msg::UseAria2c)),
+                        );
+                        if state.form.data.use_aria2c {
+                            let (text, color) = match detect_aria2c() {
+                                Some(path) => (
+                                    tr_fmt(Msg::Aria2cFound, &[&path.display()]),
+                                    egui::Color32::from_rgb(140, 150, 170),
+                                ),
+                                None => (
+                                    tr(Msg::Aria2cMissing).to_string(),
+                                    egui::Color32::from_rgb(251, 191, 36),
+                                ),
+                            };
+                            ui.label(egui::RichText::new(text).size(11.0).color(color));
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label(
+                        egui::RichText::new(tr(Msg::StallTimeout))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::DragValue::new(&mut state.form.data.stall_minutes)
+                                .range(0..=MAX_STALL_MINUTES)
+                                .suffix(tr(Msg::StallMinutes)),
+                        )
+                        .on_hover_text(tr(Msg::StallTimeoutHint));
+                        let _ = pointing(ui.add_enabled(
+                            state.form.data.stall_minutes > 0,
+                            egui::Checkbox::new(
+                                &mut state.form.data.stall_retry,
+                                tr(Msg::StallRetry),
+                            ),
+                        ));
+                    });
+                    ui.end_row();
+
+                    ui.label(
+                        egui::RichText::new(tr(Msg::FileExists))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    egui::ComboBox::from_id_salt("settings-collision-policy")
+                        .selected_text(state.form.data.collision_policy.label())
+                        .width(160.0)
+                        .show_ui(ui, |ui| {
+                            for policy in CollisionPolicy::ALL {
+                                let _ = pointing(ui.selectable_value(
+                                    &mut state.form.data.collision_policy,
+                                    policy,
+                                    policy.label(),
+                                ));
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label(
+                        egui::RichText::new(tr(Msg::FilenameCharacters))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    egui::ComboBox::from_id_salt("settings-filename-mode")
+                        .selected_text(state.form.data.filename_mode.label())
+                        .width(160.0)
+                        .show_ui(ui, |ui| {
+                            for mode in FilenameMode::ALL {
+                                let _ = pointing(ui.selectable_value(
+                                    &mut state.form.data.filename_mode,
+                                    mode,
+                                    mode.label(),
+                                ));
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label(
+                        egui::RichText::new(tr(Msg::EncoderMethod))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    egui::ComboBox::from_id_salt("settings-encoder-profile")
+                        .selected_text(state.form.data.encoder_profile.label())
+                        .width(220.0)
+                        .show_ui(ui, |ui| {
+                            for profile in EncoderProfile::ALL {
+                                let _ = pointing(ui.selectable_value(
+                                    &mut state.form.data.encoder_profile,
+                                    profile,
+                                    profile.label(),
+                                ));
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label(
+                        egui::RichText::new(tr(Msg::AutoCorrectVideo))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    let _ = pointing(ui.checkbox(
+                        &mut state.form.data.auto_correct_video,
+                        tr(Msg::AutoCorrectVideoOption),
+                    ));
+                    ui.end_row();
+
+                    ui.label(
+                        egui::RichText::new(tr(Msg::CropBlackBars))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    let _ = pointing(
+                        ui.checkbox(&mut state.form.data.crop_detect, tr(Msg::CropDetectOption)),
+                    );
+                    ui.end_row();
+
+                    ui.label(
+                        egui::RichText::new(tr(Msg::ParallelConversions))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    ui.add(
+                        egui::DragValue::new(&mut state.form.data.parallel_conversions)
+                            .range(1..=MAX_PARALLEL_CONVERSIONS),
+                    )
+                    .on_hover_text(tr(Msg::ParallelConversionsHint));
+                    ui.end_row();
+
+                    ui.label(
+                        egui::RichText::new(tr(Msg::OnBattery))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    ui.vertical(|ui| {
+                        let _ = pointing(ui.checkbox(
+                            &mut state.form.data.battery_saver,
+                            tr(Msg::BatterySaverOption),
+                        ));
+                        let _ = pointing(ui.checkbox(
+                            &mut state.form.data.defer_gpu_conversions,
+                            tr(Msg::DeferGpuConversionsOption),
+                        ));
+                    });
+                    ui.end_row();
+
+                    ui.label(
+                        egui::RichText::new(tr(Msg::SceneSplit))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    ui.horizontal(|ui| {
+                        let _ =
+                            pointing(ui.checkbox(
+                                &mut state.form.data.scene_split,
+                                tr(Msg::SceneSplitOption),
+                            ));
+                        ui.add_enabled(
+                            state.form.data.scene_split,
+                            egui::DragValue::new(&mut state.form.data.scene_threshold)
+                                .range(MIN_SCENE_THRESHOLD..=MAX_SCENE_THRESHOLD)
+                                .suffix("%"),
+                        )
+                        .on_hover_text(tr(Msg::SceneThresholdHint));
+                    });
+                    ui.end_row();
+
+                    ui.label(
+                        egui::RichText::new(tr(Msg::ChapterSplit))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    let _ = pointing(ui.checkbox(
+                        &mut state.form.data.chapter_split,
+                        tr(Msg::ChapterSplitOption),
+                    ))
+                    .on_hover_text(tr(Msg::ChapterSplitHint));
+                    ui.end_row();
+
+                    ui.label(
+                        egui::RichText::new(tr(Msg::Watermark))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    let mut selected_watermark = None;
+                    ui.horizontal(|ui| {
+                        let input_width = (ui.available_width() - 120.0).max(200.0);
+                        add_text_input(
+                            ui,
+                            &mut state.form.data.watermark_path,
+                            input_width,
+                            tr(Msg::WatermarkHint),
+                        );
+                        let pick_btn = egui::Button::new(
+                            egui::RichText::new(tr(Msg::ChoosePng))
+                                .size(11.5)
+                                .color(egui::Color32::from_rgb(180, 200, 220)),
+                        )
+                        .fill(egui::Color32::from_rgb(26, 34, 52));
+                        if pointing(ui.add(pick_btn)).clicked() {
+                            let current = PathBuf::from(state.form.data.watermark_path.trim());
+                            selected_watermark = mac_file_dialog::choose_file(current.parent());
+                        }
+                    });
+                    if let Some(path) = selected_watermark {
+                        state.form.data.watermark_path = path.to_string_lossy().to_string();
+                    }
+                    ui.end_row();
+
+                    ui.label(
+                        egui::RichText::new(tr(Msg::WatermarkPosition))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    ui.horizontal(|ui| {
+                        egui::ComboBox::from_id_salt("settings-watermark-position")
+                            .selected_text(state.form.data.watermark_position.label())
+                            .width(100.0)
+                            .show_ui(ui, |ui| {
+                                for position in WatermarkPosition::ALL {
+                                    let _ = pointing(ui.selectable_value(
+                                        &mut state.form.data.watermark_position,
+                                        position,
+                                        position.label(),
+                                    ));
+                                }
+                            });
+                        ui.label(
+                            egui::RichText::new(tr(Msg::Opacity))
+                                .size(12.0)
+                                .color(egui::Color32::from_rgb(150, 160, 180)),
+                        );
+                        let _ = pointing(
+                            ui.add(
+                                egui::Slider::new(&mut state.form.data.watermark_opacity, 0..=100)
+                                    .suffix("%"),
+                            ),
+                        );
+                    });
+                    ui.end_row();
+
+                    ui.label(
+                        egui::RichText::new(tr(Msg::AudioTrack))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    let _ = pointing(ui.checkbox(
+                        &mut state.form.data.audio_track_prompt,
+                        tr(Msg::AudioTrackPromptOption),
+                    ));
+                    ui.end_row();
+
+                    ui.label(
+                        egui::RichText::new(tr(Msg::PreviewPrompt))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    let _ = pointing(ui.checkbox(
+                        &mut state.form.data.preview_prompt,
+                        tr(Msg::PreviewPromptOption),
+                    ));
+                    ui.end_row();
+
+                    ui.label(
+                        egui::RichText::new(tr(Msg::Notifications))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    let _ = pointing(ui.checkbox(
+                        &mut state.form.data.notifications_enabled,
+                        tr(Msg::NotificationsOption),
+                    ));
+                    ui.end_row();
+
+                    ui.label(
+                        egui::RichText::new(tr(Msg::InputSource))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    let _ = pointing(ui.checkbox(
+                        &mut state.form.data.input_source_notify,
+                        tr(Msg::InputSourceNotifyOption),
+                    ));
+                    ui.end_row();
+
+                    ui.label(
+                        egui::RichText::new(tr(Msg::MenuBar))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    let _ = pointing(ui.checkbox(
+                        &mut state.form.data.status_item_enabled,
+                        tr(Msg::MenuBarOption),
+                    ));
+                    ui.end_row();
+
+                    ui.label(
+                        egui::RichText::new(tr(Msg::LaunchAtLogin))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    let login_status = state.form.login_status;
+                    ui.vertical(|ui| {
+                        ui.add_enabled_ui(login_status != LoginItemStatus::Unavailable, |ui| {
+                            let _ = pointing(ui.checkbox(
+                                &mut state.form.launch_at_login,
+                                tr(Msg::LaunchAtLoginOption),
+                            ));
+                        });
+                        let note = match login_status {
+                            LoginItemStatus::RequiresApproval => Some(tr(Msg::LoginItemApproval)),
+                            LoginItemStatus::Unavailable => Some(tr(Msg::LoginItemUnsupported)),
+                            _ => None,
+                        };
+                        if let Some(note) = note {
+                            ui.label(
+                                egui::RichText::new(note)
+                                    .size(11.0)
+                                    .color(egui::Color32::from_rgb(140, 150, 170)),
+                            );
+                        }
+                    });
+                    ui.end_row();
+                });
+        });
+}
+
+// 保存先の入力欄・フォルダ選択・最近使ったフォルダ。呼び出し側で横並びの中に置く。
+fn render_download_dir_picker(
+    // 入力欄を並べる描画先
+    ui: &mut egui::Ui,
+    // 保存先を入力中の設定UI
+    state: &mut SettingsUiState,
+) {
+    let mut selected_dir = None;
+    let input_width = (ui.available_width() - 250.0).max(200.0);
+    let default_hint_path = default_download_dir();
+    let default_hint = default_hint_path.to_string_lossy();
+    add_text_input(
+        ui,
+        &mut state.form.data.download_dir,
+        input_width,
+        default_hint.as_ref(),
+    );
+    let pick_btn = egui::Button::new(
+        egui::RichText::new(tr(Msg::ChooseFolder))
+            .size(11.5)
+            .color(egui::Color32::from_rgb(180, 200, 220)),
+    )
+    .fill(egui::Color32::from_rgb(26, 34, 52));
+    if pointing(ui.add(pick_btn)).clicked() {
+        let current = state.form.data.download_dir.trim();
+        let current_path = if current.is_empty() {
+            None
+        } else {
+            Some(PathBuf::from(current))
+        };
+        selected_dir = mac_file_dialog::choose_directory(current_path.as_deref());
+    }
+    ui.add_enabled_ui(!state.form.data.recent_download_dirs.is_empty(), |ui| {
+        egui::ComboBox::from_id_salt("settings-recent-dirs")
+            .selected_text(tr(Msg::RecentFolders))
+            .width(110.0)
+            .show_ui(ui, |ui| {
+                for dir in &state.form.data.recent_download_dirs {
+                    if pointing(ui.selectable_label(false, dir)).clicked() {
+                        selected_dir = Some(PathBuf::from(dir));
+                    }
+                }
+            });
+    });
+    if let Some(path) = selected_dir {
+        state.form.data.download_dir = path.to_string_lossy().to_string();
+    }
+}
+
+fn render_profile_section(
+    // プロファイルセクションの描画先
+    ui: &mut egui::Ui,
+    // プロファイル一覧と入力中の名前を持つ設定UI
+    state: &mut SettingsUiState,
+) {
+    let panel_fill = egui::Color32::from_rgb(20, 26, 40);
+    let panel_stroke = egui::Stroke::new(1.0, egui::Color32::from_rgb(44, 56, 78));
+
+    egui::Frame::NONE
+        .fill(panel_fill)
+        .stroke(panel_stroke)
+        .corner_radius(egui::CornerRadius::same(16))
+        .inner_margin(egui::Margin::symmetric(14, 12))
+        .show(ui, |ui| {
+            ui.label(
+                egui::RichText::new(tr(Msg::Profile))
+                    .size(13.0)
+                    .color(egui::Color32::from_rgb(200, 210, 230)),
+            );
+            ui.label(
+                egui::RichText::new(tr(Msg::ProfileNotice))
+                    .size(11.5)
+                    .color(egui::Color32::from_rgb(140, 150, 170)),
+            );
+            ui.add_space(6.0);
+
+            ui.horizontal(|ui| {
+                let active = state.form.data.active_profile.clone();
+                let selected_text = if active.is_empty() {
+                    tr(Msg::NotSelected).to_string()
+                } else {
+                    active.clone()
+                };
+                let mut chosen = None;
+                egui::ComboBox::from_id_salt("settings-profile")
+                    .selected_text(selected_text)
+                    .width(180.0)
+                    .show_ui(ui, |ui| {
+                        for profile in &state.form.data.profiles {
+                            if pointing(ui.selectable_label(profile.name == active, &profile.name))
+                                .clicked()
+                            {
+                                chosen = Some(profile.name.clone());
+                            }
+                        }
+                    });
+                if let Some(name) = chosen {
+                    match state.form.data.apply_profile(&name) {
+                        Ok(()) => state.form.profile_name = name,
+                        Err(err) => state.form.error = Some(err),
+                    }
+                }
+
+                add_text_input(
+                    ui,
+                    &mut state.form.profile_name,
+                    160.0,
+                    tr(Msg::ProfileName),
+                );
+                let save_btn = egui::Button::new(
+                    egui::RichText::new(tr(Msg::SaveCurrentSettings))
+                        .size(11.5)
+                        .color(egui::Color32::from_rgb(180, 200, 220)),
+                )
+                .fill(egui::Color32::from_rgb(26, 34, 52));
+                if pointing(ui.add(save_btn)).clicked() {
+                    let name = state.form.profile_name.clone();
+                    if let Err(err) = state.form.data.save_as_profile(&name) {
+                        state.form.error = Some(err);
+                    }
+                }
+                let remove_btn = egui::Button::new(
+                    egui::RichText::new(tr(Msg::Delete))
+                        .size(11.5)
+                        .color(egui::Color32::from_rgb(180, 200, 220)),
+                )
+                .fill(egui::Color32::from_rgb(26, 34, 52));
+                if pointing(ui.add_enabled(!active.is_empty(), remove_btn)).clicked() {
+                    state.form.data.remove_profile(&active);
+                }
+            });
+        });
+}
+
+fn render_preset_section(
+    // プリセット設定セクションの描画先
+    ui: &mut egui::Ui,
+    // プリセットの入力フォーム状態
+    state: &mut SettingsUiState,
+) {
+    let panel_fill = egui::Color32::from_rgb(20, 26, 40);
+    let panel_stroke = egui::Stroke::new(1.0, egui::Color32::from_rgb(44, 56, 78));
+
+    egui::Frame::NONE
+        .fill(panel_fill)
+        .stroke(panel_stroke)
+        .corner_radius(egui::CornerRadius::same(16))
+        .inner_margin(egui::Margin::symmetric(14, 12))
+        .show(ui, |ui| {
+            ui.label(
+                egui::RichText::new(tr(Msg::Presets))
+                    .size(13.0)
+                    .color(egui::Color32::from_rgb(200, 210, 230)),
+            );
+            ui.label(
+                egui::RichText::new(tr(Msg::PresetsNotice))
+                    .size(11.5)
+                    .color(egui::Color32::from_rgb(140, 150, 170)),
+            );
+            ui.add_space(6.0);
+
+            egui::Grid::new("presets-grid")
+                .num_columns(4)
+                .spacing(egui::vec2(10.0, 10.0))
+                .show(ui, |ui| {
+                    for (index, preset) in state.form.data.presets.iter_mut().enumerate() {
+                        add_text_input(ui, &mut preset.name, 140.0, tr(Msg::PresetName));
+                        egui::ComboBox::from_id_salt(("settings-preset-encoder", index))
+                            .selected_text(preset.encoder_profile.label())
+                            .width(170.0)
+                            .show_ui(ui, |ui| {
+                                for profile in EncoderProfile::ALL {
+                                    let _ = pointing(ui.selectable_value(
+                                        &mut preset.encoder_profile,
+                                        profile,
+                                        profile.label(),
+                                    ));
+                                }
+                            });
+                        egui::ComboBox::from_id_salt(("settings-preset-height", index))
+                            .selected_text(max_height_label(preset.max_height))
+                            .width(90.0)
+                            .show_ui(ui, |ui| {
+                                for height in MAX_HEIGHT_CHOICES {
+                                    let _ = pointing(ui.selectable_value(
+                                        &mut preset.max_height,
+                                        height,
+                                        max_height_label(height),
+                                    ));
+                                }
+                            });
+                        ui.horizontal(|ui| {
+                            egui::ComboBox::from_id_salt(("settings-preset-quality", index))
+                                .selected_text(preset.quality.label())
+                                .width(140.0)
+                                .show_ui(ui, |ui| {
+                                    for mode in QualityMode::ALL {
+                                        // サイズ指定は入力済みの MB を保ったまま選択状態を判定する。
+                                        let selected = std::mem::discriminant(&preset.quality)
+                                            == std::mem::discriminant(&mode);
+                                        if pointing(ui.selectable_label(selected, mode.label()))
+                                            .clicked()
+                                            && !selected
+                                        {
+                                            preset.quality = mode;
+                                        }
+                                    }
+                                });
+                            if let QualityMode::TargetSize(megabytes) = &mut preset.quality {
+                                ui.add(
+                                    egui::DragValue::new(megabytes)
+                                        .range(1..=4000)
+                                        .suffix(" MB"),
+                                );
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+
+            let restore_btn = egui::Button::new(
+                egui::RichText::new(tr(Msg::RestoreDefaults))
+                    .size(11.5)
+                    .color(egui::Color32::from_rgb(180, 200, 220)),
+            )
+            .fill(egui::Color32::from_rgb(26, 34, 52));
+            ui.add_space(6.0);
+            if pointing(ui.add(restore_btn)).clicked() {
+                state.form.data.presets = DownloadPreset::defaults();
+            }
+        });
+}
+
+fn render_watch_folder_section(
+    // 監視フォルダセクションの描画先
+    ui: &mut egui::Ui,
+    // 監視フォルダの入力フォーム状態
+    state: &mut SettingsUiState,
+) {
+    let panel_fill = egui::Color32::from_rgb(20, 26, 40);
+    let panel_stroke = egui::Stroke::new(1.0, egui::Color32::from_rgb(44, 56, 78));
+
+    egui::Frame::NONE
+        .fill(panel_fill)
+        .stroke(panel_stroke)
+        .corner_radius(egui::CornerRadius::same(16))
+        .inner_margin(egui::Margin::symmetric(14, 12))
+        .show(ui, |ui| {
+            ui.label(
+                egui::RichText::new(tr(Msg::WatchFolder))
+                    .size(13.0)
+                    .color(egui::Color32::from_rgb(200, 210, 230)),
+            );
+            ui.label(
+                egui::RichText::new(tr(Msg::WatchFolderNotice))
+                    .size(11.5)
+                    .color(egui::Color32::from_rgb(140, 150, 170)),
+            );
+            ui.add_space(6.0);
+            let _ = pointing(ui.checkbox(
+                &mut state.form.data.watch_folder_enabled,
+                tr(Msg::WatchFolderOption),
+            ));
+            ui.add_space(6.0);
+
+            let enabled = state.form.data.watch_folder_enabled;
+            ui.add_enabled_ui(enabled, |ui| {
+                ui.horizontal(|ui| {
+                    let input_width = (ui.available_width() - 110.0).max(200.0);
+                    add_text_input(
+                        ui,
+                        &mut state.form.data.watch_folder_dir,
+                        input_width,
+                        tr(Msg::WatchFolderHint),
+                    );
+                    let pick_btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::ChooseFolder))
+                            .size(11.5)
+                            .color(egui::Color32::from_rgb(180, 200, 220)),
+                    )
+                    .fill(egui::Color32::from_rgb(26, 34, 52));
+                    if pointing(ui.add(pick_btn)).clicked() {
+                        let current = state.form.data.watch_folder_dir.trim();
+                        let current_path = (!current.is_empty()).then(|| PathBuf::from(current));
+                        if let Some(path) =
+                            mac_file_dialog::choose_directory(current_path.as_deref())
+                        {
+                            state.form.data.watch_folder_dir = path.to_string_lossy().to_string();
+                        }
+                    }
+                });
+            });
+        });
+}
+
+fn render_subscription_section(
+    // 購読セクションの描画先
+    ui: &mut egui::Ui,
+    // 購読元の入力フォーム状態
+    state: &mut SettingsUiState,
+) {
+    let panel_fill = egui::Color32::from_rgb(20, 26, 40);
+    let panel_stroke = egui::Stroke::new(1.0, egui::Color32::from_rgb(44, 56, 78));
+    let mut remove_index = None;
+
+    egui::Frame::NONE
+        .fill(panel_fill)
+        .stroke(panel_stroke)
+        .corner_radius(egui::CornerRadius::same(16))
+        .inner_margin(egui::Margin::symmetric(14, 12))
+        .show(ui, |ui| {
+            ui.label(
+                egui::RichText::new(tr(Msg::Subscriptions))
+                    .size(13.0)
+                    .color(egui::Color32::from_rgb(200, 210, 230)),
+            );
+            ui.label(
+                egui::RichText::new(tr(Msg::SubscriptionsNotice))
+                    .size(11.5)
+                    .color(egui::Color32::from_rgb(140, 150, 170)),
+            );
+            ui.add_space(6.0);
+            let _ = pointing(ui.checkbox(
+                &mut state.form.data.subscriptions_enabled,
+                tr(Msg::SubscriptionsOption),
+            ));
+            ui.add_space(6.0);
+
+            let enabled = state.form.data.subscriptions_enabled;
+            ui.add_enabled_ui(enabled, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        egui::RichText::new(tr(Msg::SubscriptionInterval))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    ui.add(
+                        egui::DragValue::new(&mut state.form.data.subscription_interval_minutes)
+                            .range(MIN_SUBSCRIPTION_MINUTES..=MAX_SUBSCRIPTION_MINUTES)
+                            .suffix(tr(Msg::StallMinutes)),
+                    );
+                });
+                let _ = pointing(ui.checkbox(
+                    &mut state.form.data.subscription_auto_queue,
+                    tr(Msg::SubscriptionAutoQueueOption),
+                ));
+                ui.add_space(6.0);
+
+                ui.horizontal(|ui| {
+                    let input_width = (ui.available_width() - 110.0).max(200.0);
+                    add_text_input(
+                        ui,
+                        &mut state.form.subscription_url,
+                        input_width,
+                        tr(Msg::SubscriptionUrlHint),
+                    );
+                    let add_btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::AddSubscription))
+                            .size(11.5)
+                            .color(egui::Color32::from_rgb(180, 200, 220)),
+                    )
+                    .fill(egui::Color32::from_rgb(26, 34, 52));
+                    let url = state.form.subscription_url.trim().to_string();
+                    if pointing(ui.add_enabled(!url.is_empty(), add_btn)).clicked() {
+                        if !state.form.data.subscription_sources.contains(&url) {
+                            state.form.data.subscription_sources.push(url);
+                        }
+                        state.form.subscription_url.clear();
+                    }
+                });
+
+                ui.add_space(6.0);
+                if state.form.data.subscription_sources.is_empty() {
+                    ui.label(
+                        egui::RichText::new(tr(Msg::NoSubscriptions))
+                            .size(11.5)
+                            .color(egui::Color32::from_rgb(120, 130, 150)),
+                    );
+                }
+                for (index, source) in state.form.data.subscription_sources.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(
+                            egui::RichText::new(source)
+                                .size(11.5)
+                                .color(egui::Color32::from_rgb(170, 180, 200)),
+                        );
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            let remove_btn = egui::Button::new(
+                                egui::RichText::new(tr(Msg::Delete))
+                                    .size(10.5)
+                                    .color(egui::Color32::from_rgb(248, 113, 113)),
+                            )
+                            .fill(egui::Color32::from_rgb(45, 26, 34));
+                            if pointing(ui.add(remove_btn)).clicked() {
+                                remove_index = Some(index);
+                            }
+                        });
+                    });
+                }
+            });
+        });
+
+    if let Some(index) = remove_index
+        && index < state.form.data.subscription_sources.len()
+    {
+        state.form.data.subscription_sources.remove(index);
+    }
+}
+
+fn render_cookie_section(
+    // Cookie設定セクションの描画先
+    ui: &mut egui::Ui,
+    // Cookie関連の入力フォーム状態
+    state: &mut SettingsUiState,
+) {
+    let panel_fill = egui::Color32::from_rgb(20, 26, 40);
+    let panel_stroke = egui::Stroke::new(1.0, egui::Color32::from_rgb(44, 56, 78));
+
+    egui::Frame::NONE
+        .fill(panel_fill)
+        .stroke(panel_stroke)
+        .corner_radius(egui::CornerRadius::same(16))
+        .inner_margin(egui::Margin::symmetric(14, 12))
+        .show(ui, |ui| {
+            ui.label(
+                egui::RichText::new(tr(Msg::YouTubeAuth))
+                    .size(13.0)
+                    .color(egui::Color32::from_rgb(200, 210, 230)),
+            );
+            ui.label(
+                egui::RichText::new(tr(Msg::YouTubeAuthNotice))
+                    .size(11.5)
+                    .color(egui::Color32::from_rgb(140, 150, 170)),
+            );
+            ui.add_space(6.0);
+            let _ = pointing(ui.checkbox(
+                &mut state.form.data.cookies_enabled,
+                tr(Msg::UseBrowserCookies),
+            ));
+            ui.add_space(6.0);
+
+            egui::Grid::new("cookies-grid")
+                .num_columns(2)
+                .spacing(egui::vec2(16.0, 12.0))
+                .show(ui, |ui| {
+                    ui.label(
+                        egui::RichText::new(tr(Msg::BrowserName))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    let browser_hint = tr(Msg::BrowserHint);
+                    let browser_enabled = state.form.data.cookies_enabled;
+                    ui.add_enabled_ui(browser_enabled, |ui| {
+                        add_text_input(
+                            ui,
+                            &mut state.form.data.cookies_browser,
+                            220.0,
+                            browser_hint,
+                        );
+                    });
+                    ui.end_row();
+
+                    ui.label(
+                        egui::RichText::new(tr(Msg::Profile))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    let profile_hint = tr(Msg::BrowserProfileHint);
+                    let profile_enabled = state.form.data.cookies_enabled;
+                    ui.add_enabled_ui(profile_enabled, |ui| {
+                        add_text_input(
+                            ui,
+                            &mut state.form.data.cookies_profile,
+                            220.0,
+                            profile_hint,
+                        );
+                    });
+                    ui.end_row();
+                });
+
+            ui.add_space(10.0);
+            ui.label(
+                egui::RichText::new(tr(Msg::Account))
+                    .size(12.0)
+                    .color(egui::Color32::from_rgb(150, 160, 180)),
+            );
+            ui.label(
+                egui::RichText::new(tr(Msg::AccountNotice))
+                    .size(11.5)
+                    .color(egui::Color32::from_rgb(140, 150, 170)),
+            );
+            ui.add_space(4.0);
+            let mut removed = None;
+            egui::Grid::new("cookie-accounts-grid")
+                .num_columns(4)
+                .spacing(egui::vec2(8.0, 8.0))
+                .show(ui, |ui| {
+                    for (index, account) in state.form.data.cookie_accounts.iter_mut().enumerate() {
+                        add_text_input(ui, &mut account.name, 110.0, tr(Msg::Name));
+                        add_text_input(ui, &mut account.browser, 90.0, "chrome");
+                        add_text_input(ui, &mut account.profile, 110.0, "Profile 1");
+                        let remove_btn = egui::Button::new(
+                            egui::RichText::new(tr(Msg::Delete))
+                                .size(11.5)
+                                .color(egui::Color32::from_rgb(180, 200, 220)),
+                        )
+                        .fill(egui::Color32::from_rgb(26, 34, 52));
+                        if pointing(ui.add(remove_btn)).clicked() {
+                            removed = Some(index);
+                        }
+                        ui.end_row();
+                    }
+                });
+            if let Some(index) = removed {
+                state.form.data.cookie_accounts.remove(index);
+            }
+            let add_btn = egui::Button::new(
+                egui::RichText::new(tr(Msg::AddAccount))
+                    .size(11.5)
+                    .color(egui::Color32::from_rgb(180, 200, 220)),
+            )
+            .fill(egui::Color32::from_rgb(26, 34, 52));
+            if pointing(ui.add(add_btn)).clicked() {
+                state
+                    .form
+                    .data
+                    .cookie_accounts
+                    .push(CookieAccount::default());
+            }
+        });
+}
+
+fn render_login_section(
+    // ログイン設定セクションの描画先
+    ui: &mut egui::Ui,
+    // サイトのログインの入力フォーム状態
+    state: &mut SettingsUiState,
+) {
+    let panel_fill = egui::Color32::from_rgb(20, 26, 40);
+    let panel_stroke = egui::Stroke::new(1.0, egui::Color32::from_rgb(44, 56, 78));
+
+    egui::Frame::NONE
+        .fill(panel_fill)
+        .stroke(panel_stroke)
+        .corner_radius(egui::CornerRadius::same(16))
+        .inner_margin(egui::Margin::symmetric(14, 12))
+        .show(ui, |ui| {
+            ui.label(
+                egui::RichText::new(tr(Msg::SiteLogins))
+                    .size(13.0)
+                    .color(egui::Color32::from_rgb(200, 210, 230)),
+            );
+            ui.label(
+                egui::RichText::new(tr(Msg::SiteLoginsNotice))
+                    .size(11.5)
+                    .color(egui::Color32::from_rgb(140, 150, 170)),
+            );
+            ui.add_space(6.0);
+            let mut removed = None;
+            egui::Grid::new("site-logins-grid")
+                .num_columns(4)
+                .spacing(egui::vec2(8.0, 8.0))
+                .show(ui, |ui| {
+                    for (index, login) in state.form.data.site_logins.iter_mut().enumerate() {
+                        add_text_input(ui, &mut login.site, 120.0, tr(Msg::SiteHint));
+                        add_text_input(ui, &mut login.username, 100.0, tr(Msg::Username));
+                        add_password_input(ui, &mut login.password, 100.0, tr(Msg::PasswordHint));
+                        let remove_btn = egui::Button::new(
+                            egui::RichText::new(tr(Msg::Delete))
+                                .size(11.5)
+                                .color(egui::Color32::from_rgb(180, 200, 220)),
+                        )
+                        .fill(egui::Color32::from_rgb(26, 34, 52));
+                        if pointing(ui.add(remove_btn)).clicked() {
+                            removed = Some(index);
+                        }
+                        ui.end_row();
+                    }
+                });
+            if let Some(index) = removed {
+                state.form.data.site_logins.remove(index);
+            }
+            let add_btn = egui::Button::new(
+                egui::RichText::new(tr(Msg::AddLogin))
+                    .size(11.5)
+                    .color(egui::Color32::from_rgb(180, 200, 220)),
+            )
+            .fill(egui::Color32::from_rgb(26, 34, 52));
+            if pointing(ui.add(add_btn)).clicked() {
+                state.form.data.site_logins.push(SiteLogin::default());
+            }
+        });
+}
+
+fn render_osc_section(
+    // OSC設定セクションの描画先
+    ui: &mut egui::Ui,
+    // OSC関連の入力フォーム状態
+    state: &mut SettingsUiState,
+) {
+    let panel_fill = egui::Color32::from_rgb(20, 26, 40);
+    let panel_stroke = egui::Stroke::new(1.0, egui::Color32::from_rgb(44, 56, 78));
+
+    egui::Frame::NONE
+        .fill(panel_fill)
+        .stroke(panel_stroke)
+        .corner_radius(egui::CornerRadius::same(16))
+        .inner_margin(egui::Margin::symmetric(14, 12))
+        .show(ui, |ui| {
+            ui.label(
+                egui::RichText::new(tr(Msg::Osc))
+                    .size(13.0)
+                    .color(egui::Color32::from_rgb(200, 210, 230)),
+            );
+            ui.label(
+                egui::RichText::new(tr(Msg::OscNotice))
+                    .size(11.5)
+                    .color(egui::Color32::from_rgb(140, 150, 170)),
+            );
+            ui.add_space(6.0);
+            let _ = pointing(ui.checkbox(&mut state.form.data.osc_enabled, tr(Msg::OscOption)));
+            ui.add_space(6.0);
+
+            let enabled = state.form.data.osc_enabled;
+            egui::Grid::new("osc-grid")
+                .num_columns(2)
+                .spacing(egui::vec2(16.0, 12.0))
+                .show(ui, |ui| {
+                    ui.label(
+                        egui::RichText::new(tr(Msg::ListenPort))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    ui.add_enabled_ui(enabled, |ui| {
+                        add_text_input(
+                            ui,
+                            &mut state.form.data.osc_port,
+                            120.0,
+                            tr(Msg::OscPortHint),
+                        );
+                    });
+                    ui.end_row();
+
+                    ui.label(
+                        egui::RichText::new(tr(Msg::FeedbackAddress))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    ui.add_enabled_ui(enabled, |ui| {
+                        add_text_input(
+                            ui,
+                            &mut state.form.data.osc_feedback,
+                            220.0,
+                            tr(Msg::FeedbackHint),
+                        );
+                    });
+                    ui.end_row();
+                });
+            ui.add_space(8.0);
+            ui.add_enabled_ui(enabled, |ui| {
+                let _ =
+                    pointing(ui.checkbox(&mut state.form.data.osc_allow_lan, tr(Msg::OscAllowLan)));
+            });
+        });
+}
+
+fn render_event_stream_section(
+    // WebSocket配信セクションの描画先
+    ui: &mut egui::Ui,
+    // WebSocket配信の入力フォーム状態
+    state: &mut SettingsUiState,
+) {
+    let panel_fill = egui::Color32::from_rgb(20, 26, 40);
+    let panel_stroke = egui::Stroke::new(1.0, egui::Color32::from_rgb(44, 56, 78));
+
+    egui::Frame::NONE
         .fill(panel_fill)
         .stroke(panel_stroke)
         .corner_radius(egui::CornerRadius::same(16))
         .inner_margin(egui::Margin::symmetric(14, 12))
         .show(ui, |ui| {
             ui.label(
-                egui::RichText::new("YouTube認証")
+                egui::RichText::new(tr(Msg::EventStream))
                     .size(13.0)
                     .color(egui::Color32::from_rgb(200, 210, 230)),
             );
             ui.label(
-                egui::RichText::new(
-                    "bot確認が出る場合のみ有効化してください。ブラウザ名とプロファイルはyt-dlpの--cookies-from-browserに渡されます。",
-                )
-                .size(11.5)
-                .color(egui::Color32::from_rgb(140, 150, 170)),
+                egui::RichText::new(tr(Msg::EventStreamNotice))
+                    .size(11.5)
+                    .color(egui::Color32::from_rgb(140, 150, 170)),
             );
             ui.add_space(6.0);
             let _ = pointing(ui.checkbox(
-                &mut state.form.data.cookies_enabled,
-                "ブラウザのクッキーを使う（bot確認対策）",
+                &mut state.form.data.event_stream_enabled,
+                tr(Msg::EventStreamOption),
             ));
             ui.add_space(6.0);
 
-            egui::Grid::new("cookies-grid")
+            let enabled = state.form.data.event_stream_enabled;
+            egui::Grid::new("event-stream-grid")
                 .num_columns(2)
                 .spacing(egui::vec2(16.0, 12.0))
                 .show(ui, |ui| {
                     ui.label(
-                        egui::RichText::new("ブラウザ名")
+                        egui::RichText::new(tr(Msg::Port))
                             .size(12.0)
                             .color(egui::Color32::from_rgb(150, 160, 180)),
                     );
-                    let browser_hint = "例: chrome / firefox / safari";
-                    let browser_enabled = state.form.data.cookies_enabled;
-                    ui.add_enabled_ui(browser_enabled, |ui| {
-                        add_text_input(ui, &mut state.form.data.cookies_browser, 220.0, browser_hint);
+                    ui.add_enabled_ui(enabled, |ui| {
+                        add_text_input(
+                            ui,
+                            &mut state.form.data.event_stream_port,
+                            120.0,
+                            tr(Msg::EventStreamPortHint),
+                        );
                     });
                     ui.end_row();
 
                     ui.label(
-                        egui::RichText::new("プロファイル")
+                        egui::RichText::new(tr(Msg::AllowedOrigins))
                             .size(12.0)
                             .color(egui::Color32::from_rgb(150, 160, 180)),
                     );
-                    let profile_hint = "例: Default / Profile 1";
-                    let profile_enabled = state.form.data.cookies_enabled;
-                    ui.add_enabled_ui(profile_enabled, |ui| {
-                        add_text_input(ui, &mut state.form.data.cookies_profile, 220.0, profile_hint);
+                    ui.add_enabled_ui(enabled, |ui| {
+                        add_text_input(
+                            ui,
+                            &mut state.form.data.event_stream_origins,
+                            220.0,
+                            tr(Msg::AllowedOriginsHint),
+                        );
                     });
                     ui.end_row();
                 });
         });
 }
 
-fn render_search_roots_section(ui: &mut egui::Ui, state: &mut SettingsUiState) -> bool {
+fn render_search_roots_section(ui: &mut egui::Ui, state: &mut SettingsUiState) -> bool {
+    let panel_fill = egui::Color32::from_rgb(20, 26, 40);
+    let panel_stroke = egui::Stroke::new(1.0, egui::Color32::from_rgb(44, 56, 78));
+    let mut should_reindex = false;
+    let mut remove_index = None;
+    let mut add_directory = None;
+    let mut follow_links_change = None;
+
+    egui::Frame::NONE
+        .fill(panel_fill)
+        .stroke(panel_stroke)
+        .corner_radius(egui::CornerRadius::same(16))
+        .inner_margin(egui::Margin::symmetric(14, 12))
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(
+                    egui::RichText::new(tr(Msg::SearchRoots))
+                        .size(13.0)
+                        .color(egui::Color32::from_rgb(200, 210, 230)),
+                );
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::ReindexAll))
+                            .size(11.0)
+                            .color(egui::Color32::from_rgb(8, 14, 24)),
+                    )
+                    .fill(egui::Color32::from_rgb(16, 190, 255));
+                    if pointing(ui.add(btn)).clicked() {
+                        should_reindex = true;
+                    }
+                });
+            });
+            ui.label(
+                egui::RichText::new(tr(Msg::SearchRootsNotice))
+                    .size(11.5)
+                    .color(egui::Color32::from_rgb(140, 150, 170)),
+            );
+            ui.add_space(6.0);
+            let _ = pointing(ui.checkbox(
+                &mut state.form.data.search_read_finder_tags,
+                tr(Msg::ReadFinderTagsOption),
+            ));
+            let _ = pointing(ui.checkbox(
+                &mut state.form.data.cloud_fetch_on_drag,
+                tr(Msg::CloudFetchOnDragOption),
+            ));
+            ui.horizontal_wrapped(|ui| {
+                ui.label(
+                    egui::RichText::new(tr(Msg::IndexSkipLabel))
+                        .size(11.5)
+                        .color(egui::Color32::from_rgb(150, 160, 180)),
+                );
+                let _ = pointing(ui.checkbox(
+                    &mut state.form.data.index_skip_hidden,
+                    tr(Msg::IndexSkipHidden),
+                ));
+                let _ = pointing(ui.checkbox(
+                    &mut state.form.data.index_skip_bundles,
+                    tr(Msg::IndexSkipBundles),
+                ));
+                let _ = pointing(ui.checkbox(
+                    &mut state.form.data.index_skip_system,
+                    tr(Msg::IndexSkipSystem),
+                ));
+            });
+            add_text_input(
+                ui,
+                &mut state.form.data.index_skip_names,
+                360.0,
+                tr(Msg::IndexSkipNamesHint),
+            );
+            ui.horizontal(|ui| {
+                ui.label(
+                    egui::RichText::new(tr(Msg::SearchInputModeLabel))
+                        .size(11.5)
+                        .color(egui::Color32::from_rgb(150, 160, 180)),
+                );
+                egui::ComboBox::from_id_salt("settings-search-input-mode")
+                    .selected_text(state.form.data.search_input_mode.label())
+                    .width(140.0)
+                    .show_ui(ui, |ui| {
+                        for mode in SearchInputMode::ALL {
+                            let _ = pointing(ui.selectable_value(
+                                &mut state.form.data.search_input_mode,
+                                mode,
+                                mode.label(),
+                            ));
+                        }
+                    });
+            });
+            ui.add_space(8.0);
+
+            let btn = egui::Button::new(
+                egui::RichText::new(tr(Msg::AddFolder))
+                    .size(11.5)
+                    .color(egui::Color32::from_rgb(180, 200, 220)),
+            )
+            .fill(egui::Color32::from_rgb(26, 34, 52));
+            if pointing(ui.add(btn)).clicked() {
+                let current = state.form.data.search_roots.last().map(PathBuf::from);
+                add_directory = mac_file_dialog::choose_directory(current.as_deref());
+            }
+
+            ui.add_space(6.0);
+            if state.form.data.search_roots.is_empty() {
+                ui.label(
+                    egui::RichText::new(tr(Msg::NoSearchRoots))
+                        .size(11.5)
+                        .color(egui::Color32::from_rgb(120, 130, 150)),
+                );
+            } else {
+                for (index, root) in state.form.data.search_roots.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(
+                            egui::RichText::new(root)
+                                .size(11.5)
+                                .color(egui::Color32::from_rgb(170, 180, 200)),
+                        );
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            let remove_btn = egui::Button::new(
+                                egui::RichText::new(tr(Msg::Delete))
+                                    .size(10.5)
+                                    .color(egui::Color32::from_rgb(248, 113, 113)),
+                            )
+                            .fill(egui::Color32::from_rgb(45, 26, 34));
+                            if pointing(ui.add(remove_btn)).clicked() {
+                                remove_index = Some(index);
+                            }
+                            let mut follow = state.form.data.search_follow_links.contains(root);
+                            if pointing(ui.checkbox(&mut follow, tr(Msg::FollowLinksOption)))
+                                .on_hover_text(tr(Msg::FollowLinksHint))
+                                .changed()
+                            {
+                                follow_links_change = Some((root.clone(), follow));
+                            }
+                        });
+                    });
+                }
+            }
+        });
+
+    if let Some((root, follow)) = follow_links_change {
+        state
+            .form
+            .data
+            .search_follow_links
+            .retain(|entry| entry != &root);
+        if follow {
+            state.form.data.search_follow_links.push(root);
+        }
+    }
+
+    if let Some(path) = add_directory {
+        let value = path.to_string_lossy().to_string();
+        if !state
+            .form
+            .data
+            .search_roots
+            .iter()
+            .any(|existing| existing == &value)
+        {
+            state.form.data.search_roots.push(value);
+        }
+    }
+
+    if let Some(index) = remove_index {
+        if index < state.form.data.search_roots.len() {
+            let root = state.form.data.search_roots.remove(index);
+            state
+                .form
+                .data
+                .search_follow_links
+                .retain(|entry| entry != &root);
+        }
+    }
+
+    should_reindex
+}
+
+// 検索インデックスの定期バックアップの設定と、今すぐのバックアップ・バックアップからの復元。
+fn render_index_backup_section(
+    // バックアップ欄の描画先
+    ui: &mut egui::Ui,
+    // バックアップ設定の入力フォーム状態
+    state: &mut SettingsUiState,
+) -> Option<IndexBackupAction> {
+    let panel_fill = egui::Color32::from_rgb(20, 26, 40);
+    let panel_stroke = egui::Stroke::new(1.0, egui::Color32::from_rgb(44, 56, 78));
+    let mut action = None;
+    let backup_dir = index_backup_dir_or_default(&state.form.data.index_backup_dir);
+
+    egui::Frame::NONE
+        .fill(panel_fill)
+        .stroke(panel_stroke)
+        .corner_radius(egui::CornerRadius::same(16))
+        .inner_margin(egui::Margin::symmetric(14, 12))
+        .show(ui, |ui| {
+            ui.label(
+                egui::RichText::new(tr(Msg::IndexBackup))
+                    .size(13.0)
+                    .color(egui::Color32::from_rgb(200, 210, 230)),
+            );
+            ui.label(
+                egui::RichText::new(tr(Msg::IndexBackupNotice))
+                    .size(11.5)
+                    .color(egui::Color32::from_rgb(140, 150, 170)),
+            );
+            ui.add_space(6.0);
+            ui.horizontal(|ui| {
+                let _ = pointing(ui.checkbox(
+                    &mut state.form.data.index_backup_enabled,
+                    tr(Msg::IndexBackupOption),
+                ));
+                ui.add_enabled(
+                    state.form.data.index_backup_enabled,
+                    egui::DragValue::new(&mut state.form.data.index_backup_hours)
+                        .range(1..=MAX_INDEX_BACKUP_HOURS)
+                        .suffix(tr(Msg::IndexBackupHours)),
+                );
+            });
+            ui.add_space(6.0);
+            ui.horizontal(|ui| {
+                let input_width = (ui.available_width() - 110.0).max(200.0);
+                add_text_input(
+                    ui,
+                    &mut state.form.data.index_backup_dir,
+                    input_width,
+                    &backup_dir.to_string_lossy(),
+                );
+                let pick_btn = egui::Button::new(
+                    egui::RichText::new(tr(Msg::ChooseFolder))
+                        .size(11.5)
+                        .color(egui::Color32::from_rgb(180, 200, 220)),
+                )
+                .fill(egui::Color32::from_rgb(26, 34, 52));
+                if pointing(ui.add(pick_btn)).clicked()
+                    && let Some(path) = mac_file_dialog::choose_directory(Some(&backup_dir))
+                {
+                    state.form.data.index_backup_dir = path.to_string_lossy().to_string();
+                }
+            });
+            ui.add_space(6.0);
+
+            if let Some(candidate) = state.form.restore_candidate.clone() {
+                ui.label(
+                    egui::RichText::new(tr_fmt(
+                        Msg::ConfirmIndexRestore,
+                        &[&candidate.to_string_lossy()],
+                    ))
+                    .size(11.5)
+                    .color(egui::Color32::from_rgb(251, 191, 36)),
+                );
+                ui.horizontal(|ui| {
+                    let restore_btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::RestoreIndex))
+                            .size(11.5)
+                            .color(egui::Color32::from_rgb(248, 113, 113)),
+                    )
+                    .fill(egui::Color32::from_rgb(45, 26, 34));
+                    if pointing(ui.add(restore_btn)).clicked() {
+                        state.form.restore_candidate = None;
+                        action = Some(IndexBackupAction::Restore(candidate.clone()));
+                    }
+                    let cancel_btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::Cancel))
+                            .size(11.5)
+                            .color(egui::Color32::from_rgb(180, 190, 210)),
+                    )
+                    .fill(egui::Color32::from_rgb(24, 30, 45));
+                    if pointing(ui.add(cancel_btn)).clicked() {
+                        state.form.restore_candidate = None;
+                    }
+                });
+                return;
+            }
+
+            ui.horizontal(|ui| {
+                let backup_btn = egui::Button::new(
+                    egui::RichText::new(tr(Msg::BackupIndexNow))
+                        .size(11.5)
+                        .color(egui::Color32::from_rgb(8, 14, 24)),
+                )
+                .fill(egui::Color32::from_rgb(16, 190, 255));
+                if pointing(ui.add(backup_btn)).clicked() {
+                    action = Some(IndexBackupAction::BackupNow(backup_dir.clone()));
+                }
+                let restore_btn = egui::Button::new(
+                    egui::RichText::new(tr(Msg::RestoreIndexFrom))
+                        .size(11.5)
+                        .color(egui::Color32::from_rgb(180, 200, 220)),
+                )
+                .fill(egui::Color32::from_rgb(26, 34, 52));
+                if pointing(ui.add(restore_btn)).clicked() {
+                    // 最新のバックアップを選んだ状態でファイル選択を開く。
+                    let latest = list_backups(&backup_dir).into_iter().next();
+                    state.form.restore_candidate = mac_file_dialog::choose_file(Some(
+                        latest.as_deref().unwrap_or(backup_dir.as_path()),
+                    ));
+                }
+            });
+        });
+
+    action
+}
+
+// 検索インデックスの mp4 を faststart にそろえるメンテナンス操作。開始を押した場合は true を返す。
+fn render_faststart_audit_card(
+    // カードの描画先
+    ui: &mut egui::Ui,
+    // 確認の進み具合を持つ設定UI
+    state: &mut SettingsUiState,
+) -> bool {
+    let panel_fill = egui::Color32::from_rgb(20, 26, 40);
+    let panel_stroke = egui::Stroke::new(1.0, egui::Color32::from_rgb(44, 56, 78));
+    let running = matches!(state.faststart_audit, FaststartAuditState::Running { .. });
+    let mut start = false;
+
+    egui::Frame::NONE
+        .fill(panel_fill)
+        .stroke(panel_stroke)
+        .corner_radius(egui::CornerRadius::same(12))
+        .inner_margin(egui::Margin::symmetric(12, 10))
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(
+                    egui::RichText::new(tr(Msg::FaststartAudit))
+                        .size(13.0)
+                        .color(egui::Color32::from_rgb(200, 210, 230)),
+                );
+                if running {
+                    ui.add(egui::Spinner::new().size(16.0));
+                }
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::CheckAndFix))
+                            .size(11.0)
+                            .color(egui::Color32::from_rgb(8, 14, 24)),
+                    )
+                    .fill(egui::Color32::from_rgb(16, 190, 255));
+                    if pointing(ui.add_enabled(!running, btn)).clicked() {
+                        start = true;
+                    }
+                });
+            });
+            let status = match &state.faststart_audit {
+                FaststartAuditState::Idle => tr(Msg::FaststartNotice).to_string(),
+                FaststartAuditState::Running { checked, total } => {
+                    tr_fmt(Msg::FaststartChecking, &[&checked, &total])
+                }
+                FaststartAuditState::Done(report) => tr_fmt(
+                    Msg::FaststartReport,
+                    &[
+                        &report.checked,
+                        &report.fixed.len(),
+                        &report.unreadable.len(),
+                        &report.failed.len(),
+                    ],
+                ),
+            };
+            ui.label(
+                egui::RichText::new(status)
+                    .size(11.5)
+                    .color(egui::Color32::from_rgb(140, 150, 170)),
+            );
+
+            if let FaststartAuditState::Done(report) = &state.faststart_audit {
+                let problems = report
+                    .unreadable
+                    .iter()
+                    .map(|path| tr_fmt(Msg::Unreadable, &[&path.display()]))
+                    .chain(
+                        report
+                            .failed
+                            .iter()
+                            .map(|(path, err)| tr_fmt(Msg::FailedItem, &[&path.display(), &err])),
+                    )
+                    .collect::<Vec<_>>();
+                if !problems.is_empty() {
+                    ui.add_space(4.0);
+                    egui::ScrollArea::vertical()
+                        .id_salt("faststart-audit-problems")
+                        .max_height(100.0)
+                        .show(ui, |ui| {
+                            for line in problems {
+                                ui.label(
+                                    egui::RichText::new(line)
+                                        .size(11.0)
+                                        .color(egui::Color32::from_rgb(248, 113, 113)),
+                                );
+                            }
+                        });
+                }
+            }
+        });
+
+    if running {
+        ui.ctx().request_repaint_after(Duration::from_millis(250));
+    }
+    start
+}
+
+fn render_self_check_card(
+    // カードの描画先
+    ui: &mut egui::Ui,
+    // 診断の結果を持つ設定UI
+    state: &SettingsUiState,
+) -> bool {
     let panel_fill = egui::Color32::from_rgb(20, 26, 40);
     let panel_stroke = egui::Stroke::new(1.0, egui::Color32::from_rgb(44, 56, 78));
-    let mut should_reindex = false;
-    let mut remove_index = None;
-    let mut add_directory = None;
+    let running = matches!(state.self_check, SelfCheckState::Running);
+    let mut start = false;
 
     egui::Frame::NONE
         .fill(panel_fill)
         .stroke(panel_stroke)
-        .corner_radius(egui::CornerRadius::same(16))
-        .inner_margin(egui::Margin::symmetric(14, 12))
+        .corner_radius(egui::CornerRadius::same(12))
+        .inner_margin(egui::Margin::symmetric(12, 10))
         .show(ui, |ui| {
             ui.horizontal(|ui| {
                 ui.label(
-                    egui::RichText::new("検索対象フォルダ")
+                    egui::RichText::new(tr(Msg::SelfCheck))
                         .size(13.0)
                         .color(egui::Color32::from_rgb(200, 210, 230)),
                 );
+                if running {
+                    ui.add(egui::Spinner::new().size(16.0));
+                }
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     let btn = egui::Button::new(
-                        egui::RichText::new("全体を再インデックス")
+                        egui::RichText::new(tr(Msg::RunSelfCheck))
                             .size(11.0)
                             .color(egui::Color32::from_rgb(8, 14, 24)),
                     )
                     .fill(egui::Color32::from_rgb(16, 190, 255));
-                    if pointing(ui.add(btn)).clicked() {
-                        should_reindex = true;
+                    if pointing(ui.add_enabled(!running, btn)).clicked() {
+                        start = true;
                     }
                 });
             });
+            let status = match &state.self_check {
+                SelfCheckState::Idle => tr(Msg::SelfCheckNotice).to_string(),
+                SelfCheckState::Running => tr(Msg::SelfCheckRunning).to_string(),
+                SelfCheckState::Done(results) => {
+                    let failed = results.iter().filter(|result| !result.passed).count();
+                    if failed == 0 {
+                        tr_fmt(Msg::SelfCheckAllPassed, &[&results.len()])
+                    } else {
+                        tr_fmt(Msg::SelfCheckSomeFailed, &[&results.len(), &failed])
+                    }
+                }
+            };
             ui.label(
-                egui::RichText::new("mp4検索対象のルートフォルダを複数指定できます。")
+                egui::RichText::new(status)
                     .size(11.5)
                     .color(egui::Color32::from_rgb(140, 150, 170)),
             );
-            ui.add_space(8.0);
-
-            let btn = egui::Button::new(
-                egui::RichText::new("フォルダを追加")
-                    .size(11.5)
-                    .color(egui::Color32::from_rgb(180, 200, 220)),
-            )
-            .fill(egui::Color32::from_rgb(26, 34, 52));
-            if pointing(ui.add(btn)).clicked() {
-                let current = state.form.data.search_roots.last().map(PathBuf::from);
-                add_directory = mac_file_dialog::choose_directory(current.as_deref());
-            }
 
-            ui.add_space(6.0);
-            if state.form.data.search_roots.is_empty() {
-                ui.label(
-                    egui::RichText::new("検索対象フォルダが未設定です。")
-                        .size(11.5)
-                        .color(egui::Color32::from_rgb(120, 130, 150)),
-                );
-            } else {
-                for (index, root) in state.form.data.search_roots.iter().enumerate() {
+            let SelfCheckState::Done(results) = &state.self_check else {
+                return;
+            };
+            ui.add_space(4.0);
+            for result in results {
+                let mark_color = if result.passed {
+                    egui::Color32::from_rgb(52, 211, 153)
+                } else {
+                    egui::Color32::from_rgb(248, 113, 113)
+                };
+                ui.horizontal_wrapped(|ui| {
+                    ui.label(egui::RichText::new("●").size(11.0).color(mark_color));
+                    ui.label(
+                        egui::RichText::new(result.item.label())
+                            .size(11.5)
+                            .color(egui::Color32::from_rgb(200, 210, 230)),
+                    );
+                    ui.label(
+                        egui::RichText::new(&result.detail)
+                            .size(11.0)
+                            .color(egui::Color32::from_rgb(140, 150, 170)),
+                    );
+                });
+                if let Some(fix) = result.fix {
                     ui.horizontal(|ui| {
-                        ui.label(
-                            egui::RichText::new(root)
-                                .size(11.5)
-                                .color(egui::Color32::from_rgb(170, 180, 200)),
-                        );
-                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                            let remove_btn = egui::Button::new(
-                                egui::RichText::new("削除")
-                                    .size(10.5)
-                                    .color(egui::Color32::from_rgb(248, 113, 113)),
+                        ui.add_space(18.0);
+                        ui.add(
+                            egui::Label::new(
+                                egui::RichText::new(fix)
+                                    .size(11.0)
+                                    .color(egui::Color32::from_rgb(251, 191, 36)),
                             )
-                            .fill(egui::Color32::from_rgb(45, 26, 34));
-                            if pointing(ui.add(remove_btn)).clicked() {
-                                remove_index = Some(index);
-                            }
-                        });
+                            .wrap(),
+                        );
                     });
                 }
             }
         });
 
-    if let Some(path) = add_directory {
-        let value = path.to_string_lossy().to_string();
-        if !state
-            .form
-            .data
-            .search_roots
-            .iter()
-            .any(|existing| existing == &value)
-        {
-            state.form.data.search_roots.push(value);
-        }
-    }
-
-    if let Some(index) = remove_index {
-        if index < state.form.data.search_roots.len() {
-            state.form.data.search_roots.remove(index);
-        }
+    if running {
+        ui.ctx().request_repaint_after(Duration::from_millis(250));
     }
-
-    should_reindex
+    start
 }
 
 fn render_tool_card(
@@ -824,10 +2943,17 @@ fn render_tool_card(
                     state.deno.busy,
                     state.deno.available,
                 ),
+                ToolKind::Ffmpeg => (
+                    state.ffmpeg.version.clone(),
+                    state.ffmpeg.status.clone(),
+                    state.ffmpeg.busy,
+                    state.ffmpeg.available,
+                ),
             };
             let name = match kind {
                 ToolKind::YtDlp => "yt-dlp",
                 ToolKind::Deno => "Deno",
+                ToolKind::Ffmpeg => "ffmpeg / ffprobe",
             };
 
             ui.horizontal(|ui| {
@@ -856,13 +2982,13 @@ fn render_tool_card(
             ui.add_space(4.0);
             ui.horizontal(|ui| {
                 ui.label(
-                    egui::RichText::new(format!("バージョン: {}", version))
+                    egui::RichText::new(tr_fmt(Msg::VersionLabel, &[&version]))
                         .size(12.0)
                         .color(egui::Color32::from_rgb(160, 170, 190)),
                 );
                 if !available {
                     ui.label(
-                        egui::RichText::new("必須")
+                        egui::RichText::new(tr(Msg::Required))
                             .size(11.0)
                             .color(egui::Color32::from_rgb(248, 113, 113)),
                     );
@@ -876,6 +3002,249 @@ fn render_tool_card(
         });
 }
 
+fn render_app_update_card(
+    // 更新カードの描画先
+    ui: &mut egui::Ui,
+    // 更新確認の状態を持つ設定UI
+    state: &mut SettingsUiState,
+) {
+    let panel_fill = egui::Color32::from_rgb(20, 26, 40);
+    let panel_stroke = egui::Stroke::new(1.0, egui::Color32::from_rgb(44, 56, 78));
+
+    egui::Frame::NONE
+        .fill(panel_fill)
+        .stroke(panel_stroke)
+        .corner_radius(egui::CornerRadius::same(12))
+        .inner_margin(egui::Margin::symmetric(12, 10))
+        .show(ui, |ui| {
+            let busy = matches!(
+                state.app_update,
+                AppUpdateState::Checking | AppUpdateState::Staging(_)
+            );
+            let mut check = false;
+            let mut stage = None;
+            let mut open_staged = None;
+
+            ui.horizontal(|ui| {
+                ui.label(
+                    egui::RichText::new("VJDownloader")
+                        .size(14.0)
+                        .color(egui::Color32::from_rgb(210, 220, 240))
+                        .strong(),
+                );
+                if busy {
+                    ui.add(egui::Spinner::new().size(16.0));
+                }
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let label = match &state.app_update {
+                        AppUpdateState::Available(_) => tr(Msg::Download),
+                        AppUpdateState::Staged(..) => tr(Msg::OpenInstaller),
+                        _ => tr(Msg::CheckForUpdates),
+                    };
+                    let btn = egui::Button::new(
+                        egui::RichText::new(label)
+                            .size(11.5)
+                            .color(egui::Color32::from_rgb(8, 14, 24)),
+                    )
+                    .fill(egui::Color32::from_rgb(16, 190, 255));
+                    if pointing(ui.add_enabled(!busy, btn)).clicked() {
+                        match &state.app_update {
+                            AppUpdateState::Available(release) => stage = Some(release.clone()),
+                            AppUpdateState::Staged(_, path) => open_staged = Some(path.clone()),
+                            _ => check = true,
+                        }
+                    }
+                });
+            });
+
+            ui.add_space(4.0);
+            ui.label(
+                egui::RichText::new(tr_fmt(Msg::AppVersion, &[&CURRENT_VERSION]))
+                    .size(12.0)
+                    .color(egui::Color32::from_rgb(160, 170, 190)),
+            );
+            let status = match &state.app_update {
+                AppUpdateState::Idle => tr(Msg::UpdateIdle).to_string(),
+                AppUpdateState::Checking => tr(Msg::CheckingForUpdates).to_string(),
+                AppUpdateState::UpToDate => tr(Msg::UpToDate).to_string(),
+                AppUpdateState::Available(release) => {
+                    tr_fmt(Msg::UpdateAvailable, &[&release.version])
+                }
+                AppUpdateState::Staging(release) => {
+                    tr_fmt(Msg::UpdateStaging, &[&release.asset_name])
+                }
+                AppUpdateState::Staged(release, _) => {
+                    tr_fmt(Msg::UpdateStaged, &[&release.version])
+                }
+                AppUpdateState::Failed(err) => err.clone(),
+            };
+            ui.label(
+                egui::RichText::new(status)
+                    .size(12.0)
+                    .color(egui::Color32::from_rgb(140, 150, 170)),
+            );
+
+            if let AppUpdateState::Available(release) | AppUpdateState::Staged(release, _) =
+                &state.app_update
+                && !release.notes.is_empty()
+            {
+                ui.add_space(4.0);
+                egui::ScrollArea::vertical()
+                    .id_salt("app-update-notes")
+                    .max_height(120.0)
+                    .show(ui, |ui| {
+                        ui.label(
+                            egui::RichText::new(&release.notes)
+                                .size(11.5)
+                                .color(egui::Color32::from_rgb(180, 190, 210)),
+                        );
+                    });
+            }
+
+            if check {
+                state.check_app_update();
+            }
+            if let Some(release) = stage {
+                state.stage_app_update(release);
+            }
+            if let Some(path) = open_staged {
+                // dmg はマウントし、zip は展開せず Finder で表示する。
+                let result = if path.extension().is_some_and(|ext| ext == "dmg") {
+                    open_with_default_app(&path)
+                } else {
+                    reveal_in_file_manager(&path)
+                };
+                if let Err(err) = result {
+                    state.app_update =
+                        AppUpdateState::Failed(tr_fmt(Msg::OpenInstallerFailed, &[&err]));
+                }
+            }
+        });
+}
+
+// 操作ごとの割り当てを並べる。「変更」を押した操作は、次に押したキーを記録する。
+fn render_shortcut_bindings(
+    // 割り当て一覧の描画先
+    ui: &mut egui::Ui,
+    // 割り当てと記録中の操作を持つ入力フォーム
+    form: &mut SettingsForm,
+) {
+    if let Some(action) = form.recording_shortcut {
+        record_shortcut(ui.ctx(), form, action);
+    }
+
+    ui.vertical(|ui| {
+        ui.spacing_mut().item_spacing.y = 6.0;
+        for action in ShortcutAction::ALL {
+            ui.horizontal(|ui| {
+                ui.add_sized(
+                    [150.0, 20.0],
+                    egui::Label::new(
+                        egui::RichText::new(action.label())
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(200, 210, 225)),
+                    ),
+                );
+                let recording = form.recording_shortcut == Some(action);
+                let binding = form.data.shortcuts.get(action);
+                let text = if recording {
+                    tr(Msg::ShortcutRecording).to_string()
+                } else {
+                    match shortcuts::parse_shortcut(binding) {
+                        Ok(Some(shortcut)) => ui.ctx().format_shortcut(&shortcut),
+                        Ok(None) => tr(Msg::ShortcutNone).to_string(),
+                        Err(_) => binding.to_string(),
+                    }
+                };
+                let (text_color, fill) = if recording {
+                    (
+                        egui::Color32::from_rgb(8, 14, 24),
+                        egui::Color32::from_rgb(16, 190, 255),
+                    )
+                } else {
+                    (
+                        egui::Color32::from_rgb(180, 200, 220),
+                        egui::Color32::from_rgb(26, 34, 52),
+                    )
+                };
+                let change_btn =
+                    egui::Button::new(egui::RichText::new(text).size(11.5).color(text_color))
+                        .fill(fill);
+                if pointing(ui.add(change_btn))
+                    .on_hover_text(tr(Msg::ShortcutChange))
+                    .clicked()
+                {
+                    form.recording_shortcut = if recording { None } else { Some(action) };
+                }
+            });
+        }
+
+        let reset_btn = egui::Button::new(
+            egui::RichText::new(tr(Msg::RestoreDefaults))
+                .size(11.5)
+                .color(egui::Color32::from_rgb(180, 190, 210)),
+        )
+        .fill(egui::Color32::from_rgb(24, 30, 45));
+        if pointing(ui.add(reset_btn)).clicked() {
+            form.data.shortcuts = ShortcutBindings::default();
+            form.recording_shortcut = None;
+        }
+    });
+}
+
+// 記録中に押されたキーを割り当てにする。Esc で取り消し、Delete で割り当てを外す。
+fn record_shortcut(
+    // キー入力の取得に使うコンテキスト
+    ctx: &egui::Context,
+    // 割り当てを書き換える入力フォーム
+    form: &mut SettingsForm,
+    // 記録中の操作
+    action: ShortcutAction,
+) {
+    let pressed = ctx.input_mut(|i| {
+        let (key, modifiers) = i.events.iter().find_map(|event| match event {
+            egui::Event::Key {
+                key,
+                pressed: true,
+                modifiers,
+                ..
+            } => Some((*key, *modifiers)),
+            _ => None,
+        })?;
+        i.consume_key(modifiers, key);
+        Some((key, modifiers))
+    });
+    let Some((key, modifiers)) = pressed else {
+        return;
+    };
+
+    if modifiers.is_none() {
+        match key {
+            egui::Key::Escape => {
+                form.recording_shortcut = None;
+                return;
+            }
+            egui::Key::Backspace | egui::Key::Delete => {
+                form.data.shortcuts.set(action, String::new());
+                form.recording_shortcut = None;
+                return;
+            }
+            _ => {}
+        }
+    }
+    let shortcut = shortcuts::shortcut_from_key_press(key, modifiers, ctx.os().is_mac());
+    let binding = shortcuts::format_shortcut(&shortcut);
+    // 修飾キーが足りないなどの場合は、理由を表示して記録を続ける。
+    match shortcuts::parse_shortcut(&binding) {
+        Ok(_) => {
+            form.data.shortcuts.set(action, binding);
+            form.recording_shortcut = None;
+            form.error = None;
+        }
+        Err(err) => form.error = Some(err),
+    }
+}
+
 fn add_text_input(
     // 入力欄を配置する描画先
     ui: &mut egui::Ui,
@@ -885,6 +3254,34 @@ fn add_text_input(
     width: f32,
     // 未入力時に表示するヒント
     hint: &str,
+) -> egui::Response {
+    add_styled_text_input(ui, text, width, hint, false)
+}
+
+fn add_password_input(
+    // 入力欄を配置する描画先
+    ui: &mut egui::Ui,
+    // 入力内容をバインドする文字列
+    text: &mut String,
+    // 入力欄の横幅
+    width: f32,
+    // 未入力時に表示するヒント
+    hint: &str,
+) -> egui::Response {
+    add_styled_text_input(ui, text, width, hint, true)
+}
+
+fn add_styled_text_input(
+    // 入力欄を配置する描画先
+    ui: &mut egui::Ui,
+    // 入力内容をバインドする文字列
+    text: &mut String,
+    // 入力欄の横幅
+    width: f32,
+    // 未入力時に表示するヒント
+    hint: &str,
+    // 入力内容を伏せ字で表示するか
+    password: bool,
 ) -> egui::Response {
     let mut style = ui.style().as_ref().clone();
     // 入力欄の背景色はここで指定しています（text_edit_bg_color / bg_fill）
@@ -921,6 +3318,7 @@ fn add_text_input(
             [width, input_height],
             egui::TextEdit::singleline(text)
                 .hint_text(hint)
+                .password(password)
                 .vertical_align(egui::Align::Center)
                 .text_color(egui::Color32::WHITE)
                 .background_color(input_bg),
@@ -937,9 +3335,9 @@ fn apply_settings_changes(
 ) -> Result<(), String> {
     let mut data = state.form.data.clone();
     let width = parse_dimension_input(&data.window_width)
-        .ok_or_else(|| "画面の幅/高さは数値で入力してください。".to_string())?;
+        .ok_or_else(|| tr(Msg::WindowSizeNotNumber).to_string())?;
     let height = parse_dimension_input(&data.window_height)
-        .ok_or_else(|| "画面の幅/高さは数値で入力してください。".to_string())?;
+        .ok_or_else(|| tr(Msg::WindowSizeNotNumber).to_string())?;
     let width = width.max(320.0);
     let height = height.max(320.0);
     let dir_input = data.download_dir.trim();
@@ -950,19 +3348,131 @@ fn apply_settings_changes(
     };
 
     if data.cookies_enabled && data.cookies_browser.trim().is_empty() {
-        return Err("ブラウザ名を入力してください。".to_string());
+        return Err(tr(Msg::BrowserNameRequired).to_string());
+    }
+    for account in &mut data.cookie_accounts {
+        account.name = account.name.trim().to_string();
+        account.browser = account.browser.trim().to_string();
+        account.profile = account.profile.trim().to_string();
+    }
+    for (index, account) in data.cookie_accounts.iter().enumerate() {
+        if account.name.is_empty() || account.browser.is_empty() {
+            return Err(tr(Msg::AccountFieldsRequired).to_string());
+        }
+        if data.cookie_accounts[..index]
+            .iter()
+            .any(|other| other.name == account.name)
+        {
+            return Err(tr_fmt(Msg::DuplicateAccountName, &[&account.name]));
+        }
+    }
+    // 名前を変えたり削除したりしたアカウントが選択中なら既定に戻す。
+    if !data
+        .cookie_accounts
+        .iter()
+        .any(|account| account.name == data.active_cookie_account)
+    {
+        data.active_cookie_account.clear();
+    }
+
+    mac_hotkey::parse_hotkey(&data.hotkey_summon)?;
+    data.shortcuts.validate()?;
+    validate_pinned_version(&data.yt_dlp_version)?;
+    split_shell_words(&data.yt_dlp_extra_args)
+        .map_err(|err| tr_fmt(Msg::ExtraArgsSplitFailed, &[&err]))?;
+    let watermark = data.watermark_path.trim();
+    if !watermark.is_empty() {
+        let path = make_absolute_path(watermark);
+        let is_png = path
+            .extension()
+            .and_then(|value| value.to_str())
+            .is_some_and(|value| value.eq_ignore_ascii_case("png"));
+        if !path.is_file() || !is_png {
+            return Err(tr(Msg::WatermarkNotPng).to_string());
+        }
+        data.watermark_path = path.to_string_lossy().to_string();
+    }
+    if data.osc_enabled {
+        osc::parse_listen_port(&data.osc_port)?;
+        osc::parse_feedback_target(&data.osc_feedback)?;
+    }
+    if data.event_stream_enabled {
+        event_stream::parse_event_stream_port(&data.event_stream_port)?;
+    }
+    let backup_input = data.index_backup_dir.trim();
+    if !backup_input.is_empty() {
+        data.index_backup_dir = make_absolute_path(backup_input)
+            .to_string_lossy()
+            .to_string();
+    }
+    let watch_input = data.watch_folder_dir.trim();
+    if !watch_input.is_empty() {
+        data.watch_folder_dir = make_absolute_path(watch_input)
+            .to_string_lossy()
+            .to_string();
+    }
+    if data.watch_folder_enabled {
+        let watch_dir = PathBuf::from(&data.watch_folder_dir);
+        if !watch_dir.is_dir() {
+            return Err(tr(Msg::WatchFolderRequired).to_string());
+        }
+        // 変換した動画が監視フォルダへ戻ると、取り込みを繰り返してしまう。
+        if watch_dir == actual_dir {
+            return Err(tr(Msg::WatchFolderSameAsOutput).to_string());
+        }
     }
 
     if let Err(err) = std::fs::create_dir_all(&actual_dir) {
-        return Err(format!("フォルダを作成できませんでした: {err}"));
+        return Err(tr_fmt(Msg::CreateFolderFailed, &[&err]));
+    }
+
+    if state.form.launch_at_login != state.form.login_status.is_registered() {
+        mac_login_item::set_launch_at_login(state.form.launch_at_login)?;
+        state.form.login_status = mac_login_item::login_item_status();
     }
 
     data.window_width = format_dimension(width);
     data.window_height = format_dimension(height);
     data.download_dir = actual_dir.to_string_lossy().to_string();
+    data.hotkey_summon = data.hotkey_summon.trim().to_ascii_lowercase();
     data.search_roots = normalize_search_roots(&data.search_roots)?;
+    // リンクをたどる設定は、残っている検索対象フォルダの分だけ保存する。
+    data.search_follow_links = data
+        .search_roots
+        .iter()
+        .filter(|root| {
+            data.search_follow_links
+                .iter()
+                .any(|follow| make_absolute_path(follow.trim()).to_string_lossy() == root.as_str())
+        })
+        .cloned()
+        .collect();
+    let previous = SettingsData::load();
+    // 検索履歴は設定画面で編集しないため、保存直前の値を引き継ぐ。
+    data.search_history = previous.search_history;
+    // 最近使ったフォルダはダウンロードのたびに増えるため、保存直前の値に今回の保存先を足す。
+    data.recent_download_dirs = previous.recent_download_dirs;
+    let dir = data.download_dir.clone();
+    data.remember_download_dir(&dir);
+    data.yt_dlp_version = data.yt_dlp_version.trim().to_string();
+    store_login_passwords(&mut data.site_logins, &previous.site_logins)?;
     save_settings(&data)?;
 
+    // yt-dlp の取得元を変えた場合は、その設定で取得し直す。
+    if data.yt_dlp_channel != previous.yt_dlp_channel
+        || data.yt_dlp_version != previous.yt_dlp_version
+    {
+        state.start_tool_action(ToolKind::YtDlp, ToolAction::Update);
+    }
+    // Deno に戻した場合は、起動時と同じく見つからなければ取得する。
+    if data.js_runtime == JsRuntime::Deno && previous.js_runtime != JsRuntime::Deno {
+        state.start_tool_action(ToolKind::Deno, ToolAction::Install);
+    }
+    // エンコード方式を変えた場合は、ffmpeg で使えるかを確認し直して表示する。
+    if data.encoder_profile != previous.encoder_profile {
+        state.refresh_tool(ToolKind::Ffmpeg);
+    }
+
     state.form.data = data;
     *download_dir = actual_dir;
     *refresh_needed = true;
@@ -970,6 +3480,33 @@ fn apply_settings_changes(
     Ok(())
 }
 
+// 入力されたパスワードをキーチェーンへ保存し、一覧から消えたログインのパスワードを削除する。
+fn store_login_passwords(logins: &mut [SiteLogin], previous: &[SiteLogin]) -> Result<(), String> {
+    let same = |a: &SiteLogin, b: &SiteLogin| a.site == b.site && a.username == b.username;
+    for login in logins.iter_mut() {
+        login.site = login.site.trim().to_ascii_lowercase();
+        login.username = login.username.trim().to_string();
+        if login.site.is_empty() || login.username.is_empty() {
+            return Err(tr(Msg::LoginFieldsRequired).to_string());
+        }
+        if login.password.is_empty() && !previous.iter().any(|old| same(old, login)) {
+            return Err(tr_fmt(Msg::PasswordRequired, &[&login.site]));
+        }
+    }
+    for login in logins.iter_mut() {
+        if !login.password.is_empty() {
+            save_login_password(&login.site, &login.username, &login.password)?;
+            login.password.clear();
+        }
+    }
+    for old in previous {
+        if !logins.iter().any(|login| same(login, old)) {
+            delete_login_password(&old.site, &old.username)?;
+        }
+    }
+    Ok(())
+}
+
 fn normalize_search_roots(roots: &[String]) -> Result<Vec<String>, String> {
     let mut out = Vec::new();
     for root in roots {
@@ -979,9 +3516,9 @@ fn normalize_search_roots(roots: &[String]) -> Result<Vec<String>, String> {
         }
         let absolute = make_absolute_path(trimmed);
         if !absolute.is_dir() {
-            return Err(format!(
-                "検索対象フォルダがディレクトリではありません: {}",
-                absolute.to_string_lossy()
+            return Err(tr_fmt(
+                Msg::SearchRootNotDirectory,
+                &[&absolute.to_string_lossy()],
             ));
         }
         let normalized = absolute.to_string_lossy().to_string();
@@ -1008,14 +3545,15 @@ fn format_dimension(value: f32) -> String {
     }
 }
 
-fn tool_path(kind: ToolKind) -> PathBuf {
+pub(crate) fn tool_path(kind: ToolKind) -> PathBuf {
     match kind {
         ToolKind::YtDlp => yt_dlp_path(),
         ToolKind::Deno => deno_path(),
+        ToolKind::Ffmpeg => ffmpeg_path(),
     }
 }
 
-fn read_tool_version(kind: ToolKind, path: &PathBuf) -> Result<String, String> {
+pub(crate) fn read_tool_version(kind: ToolKind, path: &PathBuf) -> Result<String, String> {
     let mut cmd = Command::new(path);
     match kind {
         ToolKind::YtDlp => {
@@ -1024,6 +3562,9 @@ fn read_tool_version(kind: ToolKind, path: &PathBuf) -> Result<String, String> {
         ToolKind::Deno => {
             cmd.arg("--version");
         }
+        ToolKind::Ffmpeg => {
+            cmd.arg("-version");
+        }
     }
     let output = cmd.output().map_err(|err| err.to_string())?;
     let stdout = String::from_utf8_lossy(&output.stdout);
@@ -1035,5 +3576,9 @@ fn read_tool_version(kind: ToolKind, path: &PathBuf) -> Result<String, String> {
     if line.is_empty() {
         return Err("version_not_found".to_string());
     }
+    // ffmpeg は "ffmpeg version 7.1 Copyright ..." のように続くため著作権表記を省く。
+    if let Some((head, _)) = line.split_once(" Copyright") {
+        line = head.to_string();
+    }
     Ok(line)
 }
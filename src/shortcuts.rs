@@ -0,0 +1,288 @@
+use eframe::egui;
+
+use crate::i18n::{Msg, tr, tr_fmt};
+
+// Cmd との組み合わせだけでは割り当てられない、システムや編集操作の標準のキー
+const RESERVED_COMMAND_KEYS: [egui::Key; 10] = [
+    egui::Key::Q,
+    egui::Key::H,
+    egui::Key::W,
+    egui::Key::E,
+    egui::Key::C,
+    egui::Key::V,
+    egui::Key::X,
+    egui::Key::A,
+    egui::Key::Z,
+    egui::Key::M,
+];
+
+// メイン画面でキーを割り当てられる操作
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShortcutAction {
+    StartDownload,
+    Cancel,
+    FocusSearch,
+    OpenSettings,
+    OpenLogs,
+}
+
+impl ShortcutAction {
+    pub const ALL: [ShortcutAction; 5] = [
+        ShortcutAction::StartDownload,
+        ShortcutAction::Cancel,
+        ShortcutAction::FocusSearch,
+        ShortcutAction::OpenSettings,
+        ShortcutAction::OpenLogs,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ShortcutAction::StartDownload => tr(Msg::ShortcutStartDownload),
+            ShortcutAction::Cancel => tr(Msg::ShortcutCancel),
+            ShortcutAction::FocusSearch => tr(Msg::ShortcutFocusSearch),
+            ShortcutAction::OpenSettings => tr(Msg::ShortcutOpenSettings),
+            ShortcutAction::OpenLogs => tr(Msg::ShortcutOpenLogs),
+        }
+    }
+
+    pub fn default_binding(self) -> &'static str {
+        match self {
+            ShortcutAction::StartDownload => "cmd+d",
+            ShortcutAction::Cancel => "cmd+period",
+            ShortcutAction::FocusSearch => "cmd+f",
+            ShortcutAction::OpenSettings => "cmd+comma",
+            ShortcutAction::OpenLogs => "cmd+l",
+        }
+    }
+}
+
+// 設定に保存する操作ごとの割り当て（"cmd+shift+d" 形式）。空欄は無効。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShortcutBindings([String; ShortcutAction::ALL.len()]);
+
+impl Default for ShortcutBindings {
+    fn default() -> Self {
+        Self(ShortcutAction::ALL.map(|action| action.default_binding().to_string()))
+    }
+}
+
+impl ShortcutBindings {
+    pub fn get(&self, action: ShortcutAction) -> &str {
+        &self.0[action as usize]
+    }
+
+    pub fn set(&mut self, action: ShortcutAction, binding: String) {
+        self.0[action as usize] = binding;
+    }
+
+    // 解析できない割り当てと、同じキーの重複を保存前に弾く。
+    pub fn validate(&self) -> Result<(), String> {
+        let mut seen: Vec<(ShortcutAction, egui::KeyboardShortcut)> = Vec::new();
+        for action in ShortcutAction::ALL {
+            let shortcut = parse_shortcut(self.get(action))
+                .map_err(|err| tr_fmt(Msg::ShortcutInvalid, &[&action.label(), &err]))?;
+            let Some(shortcut) = shortcut else {
+                continue;
+            };
+            if let Some((other, _)) = seen.iter().find(|(_, other)| *other == shortcut) {
+                return Err(tr_fmt(
+                    Msg::ShortcutConflict,
+                    &[&other.label(), &action.label()],
+                ));
+            }
+            seen.push((action, shortcut));
+        }
+        Ok(())
+    }
+
+    // 手で書き換えた設定ファイルの不正な割り当ては、その操作だけ無効にする。
+    pub fn shortcuts(&self) -> Shortcuts {
+        let mut shortcuts = Shortcuts::default();
+        for action in ShortcutAction::ALL {
+            let parsed = parse_shortcut(self.get(action)).ok().flatten();
+            if parsed.is_some() && !shortcuts.0.contains(&parsed) {
+                shortcuts.0[action as usize] = parsed;
+            }
+        }
+        shortcuts
+    }
+}
+
+// 解析済みの割り当て。メイン画面のキー入力の判定に使う。
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Shortcuts([Option<egui::KeyboardShortcut>; ShortcutAction::ALL.len()]);
+
+impl Shortcuts {
+    pub fn get(&self, action: ShortcutAction) -> Option<egui::KeyboardShortcut> {
+        self.0[action as usize]
+    }
+
+    // このフレームで押された操作を返す。
+    // egui は余分な Shift や Option を無視して照合するため、修飾キーの多い割り当てから調べる。
+    pub fn consume_pressed(&self, ctx: &egui::Context) -> Vec<ShortcutAction> {
+        let mut actions = ShortcutAction::ALL
+            .into_iter()
+            .filter_map(|action| self.get(action).map(|shortcut| (action, shortcut)))
+            .collect::<Vec<_>>();
+        actions.sort_by_key(|(_, shortcut)| std::cmp::Reverse(modifier_count(shortcut.modifiers)));
+        ctx.input_mut(|i| {
+            actions
+                .into_iter()
+                .filter(|(_, shortcut)| i.consume_shortcut(shortcut))
+                .map(|(action, _)| action)
+                .collect()
+        })
+    }
+}
+
+fn modifier_count(modifiers: egui::Modifiers) -> usize {
+    [
+        modifiers.command,
+        modifiers.ctrl,
+        modifiers.alt,
+        modifiers.shift,
+    ]
+    .into_iter()
+    .filter(|pressed| *pressed)
+    .count()
+}
+
+// "cmd+shift+d" 形式の文字列を解析する。空欄は無効（None）として扱う。
+pub fn parse_shortcut(raw: &str) -> Result<Option<egui::KeyboardShortcut>, String> {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return Ok(None);
+    }
+
+    let mut modifiers = egui::Modifiers::NONE;
+    let mut key = None;
+    for part in trimmed.split('+') {
+        let name = part.trim();
+        let modifier = match name.to_ascii_lowercase().as_str() {
+            "cmd" | "command" | "⌘" => Some(egui::Modifiers::COMMAND),
+            "ctrl" | "control" | "⌃" => Some(egui::Modifiers::CTRL),
+            "opt" | "option" | "alt" | "⌥" => Some(egui::Modifiers::ALT),
+            "shift" | "⇧" => Some(egui::Modifiers::SHIFT),
+            _ => None,
+        };
+        if let Some(modifier) = modifier {
+            modifiers |= modifier;
+            continue;
+        }
+        let parsed = egui::Key::ALL
+            .iter()
+            .copied()
+            .find(|key| key.name().eq_ignore_ascii_case(name))
+            .or_else(|| egui::Key::from_name(name));
+        let Some(parsed) = parsed else {
+            return Err(tr_fmt(Msg::ShortcutUnknownKey, &[&name]));
+        };
+        if key.replace(parsed).is_some() {
+            return Err(tr(Msg::ShortcutSingleKey).to_string());
+        }
+    }
+
+    let Some(key) = key else {
+        return Err(tr(Msg::ShortcutKeyRequired).to_string());
+    };
+    let shortcut = egui::KeyboardShortcut::new(modifiers, key);
+    if !modifiers.command && !modifiers.ctrl && !is_function_key(key) {
+        return Err(tr(Msg::ShortcutModifierRequired).to_string());
+    }
+    if modifiers == egui::Modifiers::COMMAND && RESERVED_COMMAND_KEYS.contains(&key) {
+        return Err(tr_fmt(
+            Msg::ShortcutReserved,
+            &[&format_shortcut(&shortcut)],
+        ));
+    }
+    Ok(Some(shortcut))
+}
+
+// parse_shortcut で読み戻せる形式にする。
+pub fn format_shortcut(shortcut: &egui::KeyboardShortcut) -> String {
+    let modifiers = shortcut.modifiers;
+    let mut parts = Vec::new();
+    if modifiers.command {
+        parts.push("cmd");
+    }
+    if modifiers.ctrl {
+        parts.push("ctrl");
+    }
+    if modifiers.alt {
+        parts.push("opt");
+    }
+    if modifiers.shift {
+        parts.push("shift");
+    }
+    let key = shortcut.logical_key.name().to_ascii_lowercase();
+    parts.push(&key);
+    parts.join("+")
+}
+
+// 設定画面で押されたキーを割り当てにする。macOS では Cmd と Ctrl を別の修飾キーとして扱う。
+pub fn shortcut_from_key_press(
+    // 押されたキー
+    key: egui::Key,
+    // 押されていた修飾キー
+    pressed: egui::Modifiers,
+    // macOS で動いているか
+    is_mac: bool,
+) -> egui::KeyboardShortcut {
+    let mut modifiers = egui::Modifiers::NONE;
+    if pressed.command {
+        modifiers |= egui::Modifiers::COMMAND;
+    }
+    if pressed.ctrl && is_mac {
+        modifiers |= egui::Modifiers::CTRL;
+    }
+    if pressed.alt {
+        modifiers |= egui::Modifiers::ALT;
+    }
+    if pressed.shift {
+        modifiers |= egui::Modifiers::SHIFT;
+    }
+    egui::KeyboardShortcut::new(modifiers, key)
+}
+
+fn is_function_key(key: egui::Key) -> bool {
+    let name = key.name();
+    name.len() > 1 && name.starts_with('F') && name[1..].chars().all(|ch| ch.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_validates_shortcuts() {
+        let shortcut = parse_shortcut(" Cmd + Shift + D ").unwrap().unwrap();
+        assert_eq!(
+            shortcut,
+            egui::KeyboardShortcut::new(
+                egui::Modifiers::COMMAND | egui::Modifiers::SHIFT,
+                egui::Key::D
+            )
+        );
+        assert_eq!(format_shortcut(&shortcut), "cmd+shift+d");
+        for action in ShortcutAction::ALL {
+            let parsed = parse_shortcut(action.default_binding()).unwrap().unwrap();
+            assert_eq!(format_shortcut(&parsed), action.default_binding());
+        }
+        assert_eq!(parse_shortcut("").unwrap(), None);
+        assert!(parse_shortcut("f5").unwrap().is_some());
+
+        assert!(parse_shortcut("d").is_err());
+        assert!(parse_shortcut("cmd+shift").is_err());
+        assert!(parse_shortcut("cmd+a+b").is_err());
+        assert!(parse_shortcut("cmd+q").is_err());
+        assert!(parse_shortcut("cmd+shift+q").is_ok());
+
+        let mut bindings = ShortcutBindings::default();
+        assert!(bindings.validate().is_ok());
+        bindings.set(ShortcutAction::OpenLogs, "cmd+d".to_string());
+        assert!(bindings.validate().is_err());
+        let shortcuts = bindings.shortcuts();
+        assert!(shortcuts.get(ShortcutAction::StartDownload).is_some());
+        assert_eq!(shortcuts.get(ShortcutAction::OpenLogs), None);
+    }
+}
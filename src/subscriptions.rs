@@ -0,0 +1,170 @@
+use eframe::egui;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, mpsc};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::download::{CancellationToken, NewUpload, check_subscription};
+use crate::network;
+
+// 起動直後は依存ツールの取得・更新と重ならないよう、少し待ってから確認する。
+const FIRST_CHECK_DELAY: Duration = Duration::from_secs(30);
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// 購読元 1 つを確認した結果
+pub struct SubscriptionCheck {
+    pub source: String,
+    pub result: Result<Vec<NewUpload>, String>,
+}
+
+// 購読したチャンネル・プレイリストを間隔ごとに確認し、新しい動画を知らせる。破棄時に確認を止める。
+pub struct SubscriptionPoller {
+    sources: Vec<String>,
+    interval: Duration,
+    rx: mpsc::Receiver<SubscriptionCheck>,
+    stop_flag: Arc<AtomicBool>,
+    cancel: CancellationToken,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl SubscriptionPoller {
+    pub fn start(sources: Vec<String>, interval: Duration, ctx: egui::Context) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let stop_flag = Arc::new(AtomicBool::new(false));
+        let cancel = CancellationToken::new();
+        let worker_sources = sources.clone();
+        let worker_stop = stop_flag.clone();
+        let worker_cancel = cancel.clone();
+        let worker = thread::spawn(move || {
+            poll_loop(
+                worker_sources,
+                interval,
+                tx,
+                worker_stop,
+                worker_cancel,
+                ctx,
+            );
+        });
+        Self {
+            sources,
+            interval,
+            rx,
+            stop_flag,
+            cancel,
+            worker: Some(worker),
+        }
+    }
+
+    // 同じ購読元・間隔で動いているか（設定を保存した時に作り直すかを決める）
+    pub fn matches(&self, sources: &[String], interval: Duration) -> bool {
+        self.sources == sources && self.interval == interval
+    }
+
+    pub fn try_recv(&self) -> Option<SubscriptionCheck> {
+        self.rx.try_recv().ok()
+    }
+}
+
+impl Drop for SubscriptionPoller {
+    fn drop(&mut self) {
+        self.stop_flag.store(true, Ordering::Relaxed);
+        // 確認中の yt-dlp も止める。
+        self.cancel.cancel();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn poll_loop(
+    sources: Vec<String>,
+    interval: Duration,
+    tx: mpsc::Sender<SubscriptionCheck>,
+    stop_flag: Arc<AtomicBool>,
+    cancel: CancellationToken,
+    ctx: egui::Context,
+) {
+    let mut next_check = Instant::now() + FIRST_CHECK_DELAY;
+    while !stop_flag.load(Ordering::Relaxed) {
+        if Instant::now() < next_check {
+            thread::sleep(POLL_INTERVAL);
+            continue;
+        }
+        next_check = Instant::now() + interval;
+        // オフラインの間は確認せず、次の間隔まで待つ。
+        if !network::last_known_online() {
+            continue;
+        }
+        let sent = check_sources(&sources, &tx, &stop_flag, |source| {
+            check_subscription(source, &cancel)
+        });
+        if !sent {
+            return;
+        }
+        ctx.request_repaint();
+    }
+}
+
+// 購読元を順に確認して結果を送る。画面側が閉じた場合は false を返す。
+fn check_sources(
+    sources: &[String],
+    tx: &mpsc::Sender<SubscriptionCheck>,
+    stop_flag: &AtomicBool,
+    check: impl Fn(&str) -> Result<Vec<NewUpload>, String>,
+) -> bool {
+    for source in sources {
+        if stop_flag.load(Ordering::Relaxed) {
+            return true;
+        }
+        let check = SubscriptionCheck {
+            source: source.clone(),
+            result: check(source),
+        };
+        if tx.send(check).is_err() {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_each_source_in_order() {
+        let (tx, rx) = mpsc::channel();
+        let stop_flag = AtomicBool::new(false);
+        let sources = vec![
+            "https://www.youtube.com/@a".to_string(),
+            "https://www.youtube.com/playlist?list=b".to_string(),
+        ];
+        assert!(check_sources(&sources, &tx, &stop_flag, |source| {
+            if source.ends_with("@a") {
+                Ok(vec![NewUpload {
+                    url: "https://www.youtube.com/watch?v=new".to_string(),
+                    title: "new".to_string(),
+                }])
+            } else {
+                Err("offline".to_string())
+            }
+        }));
+        let checks: Vec<SubscriptionCheck> = rx.try_iter().collect();
+        assert_eq!(checks.len(), 2);
+        assert_eq!(checks[0].source, sources[0]);
+        assert_eq!(checks[0].result.as_ref().map(Vec::len), Ok(1));
+        assert!(checks[1].result.is_err());
+
+        // 止めた後は確認しない。
+        stop_flag.store(true, Ordering::Relaxed);
+        assert!(check_sources(&sources, &tx, &stop_flag, |_| unreachable!()));
+        drop(rx);
+        stop_flag.store(false, Ordering::Relaxed);
+        assert!(!check_sources(
+            &sources,
+            &tx,
+            &stop_flag,
+            |_| Ok(Vec::new())
+        ));
+    }
+}
@@ -0,0 +1,140 @@
+use std::path::PathBuf;
+
+use eframe::egui;
+
+use crate::app::DownloaderApp;
+use crate::cursor::pointing;
+use crate::i18n::{Msg, tr, tr_fmt};
+use crate::mac_finder_tags::{MAX_RATING, rating_tag};
+
+pub struct TagEditorState {
+    pub paths: Vec<PathBuf>,
+    pub tags_text: String,
+    pub rating: u8,
+    pub error: Option<String>,
+}
+
+impl TagEditorState {
+    // 入力欄の文字列と評価から、Finder に書き出すタグ名の一覧を作る。
+    pub fn tag_names(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        for name in self.tags_text.split([',', '、']) {
+            let name = name.trim();
+            if !name.is_empty() && !names.iter().any(|existing| existing == name) {
+                names.push(name.to_string());
+            }
+        }
+        names.extend(rating_tag(self.rating));
+        names
+    }
+}
+
+pub fn render_tag_editor(
+    // 編集中のタグ状態と反映処理を持つアプリ
+    app: &mut DownloaderApp,
+    // ウィンドウ表示に使うコンテキスト
+    ctx: &egui::Context,
+) {
+    let Some(state) = app.tag_editor.as_mut() else {
+        return;
+    };
+
+    let mut open = true;
+    let mut apply = false;
+    let mut cancel = false;
+    egui::Window::new(tr(Msg::TagsAndRating))
+        .collapsible(false)
+        .resizable(false)
+        .default_width(360.0)
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .open(&mut open)
+        .show(ctx, |ui| {
+            ui.label(
+                egui::RichText::new(tr_fmt(Msg::ReplaceTagsMessage, &[&state.paths.len()]))
+                    .size(11.5)
+                    .color(egui::Color32::from_rgb(140, 150, 170)),
+            );
+            ui.add_space(8.0);
+
+            egui::Grid::new("tag-editor-grid")
+                .num_columns(2)
+                .spacing(egui::vec2(16.0, 12.0))
+                .show(ui, |ui| {
+                    ui.label(
+                        egui::RichText::new(tr(Msg::Tags))
+                            .size(12.5)
+                            .color(egui::Color32::from_rgb(200, 210, 230)),
+                    );
+                    ui.add(
+                        egui::TextEdit::singleline(&mut state.tags_text)
+                            .hint_text(tr(Msg::TagsHint))
+                            .desired_width(240.0),
+                    );
+                    ui.end_row();
+
+                    ui.label(
+                        egui::RichText::new(tr(Msg::Rating))
+                            .size(12.5)
+                            .color(egui::Color32::from_rgb(200, 210, 230)),
+                    );
+                    ui.horizontal(|ui| {
+                        for star in 1..=MAX_RATING {
+                            let (symbol, color) = if star <= state.rating {
+                                ("★", egui::Color32::from_rgb(250, 204, 21))
+                            } else {
+                                ("☆", egui::Color32::from_rgb(120, 130, 150))
+                            };
+                            let button = egui::Button::new(
+                                egui::RichText::new(symbol).size(15.0).color(color),
+                            )
+                            .frame(false);
+                            if pointing(ui.add(button)).clicked() {
+                                // 同じ星をもう一度押すと評価を外す
+                                state.rating = if state.rating == star { 0 } else { star };
+                            }
+                        }
+                    });
+                    ui.end_row();
+                });
+
+            if let Some(error) = state.error.as_ref() {
+                ui.add_space(6.0);
+                ui.label(
+                    egui::RichText::new(error)
+                        .size(11.5)
+                        .color(egui::Color32::from_rgb(248, 113, 113)),
+                );
+            }
+
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let apply_btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::Apply))
+                            .size(12.5)
+                            .color(egui::Color32::from_rgb(8, 14, 24)),
+                    )
+                    .fill(egui::Color32::from_rgb(16, 190, 255));
+                    if pointing(ui.add(apply_btn)).clicked() {
+                        apply = true;
+                    }
+
+                    let cancel_btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::Cancel))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(180, 190, 210)),
+                    )
+                    .fill(egui::Color32::from_rgb(24, 30, 45));
+                    if pointing(ui.add(cancel_btn)).clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+        });
+
+    if apply {
+        app.apply_tag_editor();
+    } else if cancel || !open {
+        app.tag_editor = None;
+    }
+}
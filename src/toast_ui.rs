@@ -0,0 +1,148 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use eframe::egui;
+
+use crate::app::DownloaderApp;
+use crate::cursor::pointing;
+use crate::i18n::{Msg, tr};
+
+// 同時に表示する上限。超えた分は古いものから消す。
+const MAX_TOASTS: usize = 4;
+// 消える前に薄くしていく時間
+const FADE_DURATION: Duration = Duration::from_millis(400);
+const TOAST_WIDTH: f32 = 300.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToastKind {
+    Success,
+    Error,
+    Info,
+}
+
+impl ToastKind {
+    // エラーは読み終える前に消えないよう長めに表示する。
+    fn duration(self) -> Duration {
+        match self {
+            ToastKind::Error => Duration::from_secs(8),
+            ToastKind::Success | ToastKind::Info => Duration::from_secs(4),
+        }
+    }
+
+    fn accent(self) -> egui::Color32 {
+        match self {
+            ToastKind::Success => egui::Color32::from_rgb(52, 211, 153),
+            ToastKind::Error => egui::Color32::from_rgb(248, 113, 113),
+            ToastKind::Info => egui::Color32::from_rgb(56, 189, 248),
+        }
+    }
+}
+
+struct Toast {
+    kind: ToastKind,
+    message: String,
+    shown_at: Instant,
+}
+
+impl Toast {
+    fn remaining(&self) -> Duration {
+        self.kind.duration().saturating_sub(self.shown_at.elapsed())
+    }
+}
+
+// 画面右下に重ねる通知。ログ画面を開いていなくても操作の結果が分かるようにする。
+#[derive(Default)]
+pub struct ToastState {
+    toasts: VecDeque<Toast>,
+}
+
+impl ToastState {
+    pub fn push(&mut self, kind: ToastKind, message: impl Into<String>) {
+        let message = message.into();
+        // 同じ内容が続いた場合は、並べずに表示し直す。
+        self.toasts.retain(|toast| toast.message != message);
+        self.toasts.push_back(Toast {
+            kind,
+            message,
+            shown_at: Instant::now(),
+        });
+        while self.toasts.len() > MAX_TOASTS {
+            self.toasts.pop_front();
+        }
+    }
+}
+
+pub fn render_toasts(
+    // 表示中のトーストを持つアプリ
+    app: &mut DownloaderApp,
+    // 重ねて描画するためのコンテキスト
+    ctx: &egui::Context,
+) {
+    let state = &mut app.toasts;
+    state.toasts.retain(|toast| !toast.remaining().is_zero());
+    let Some(next_fade) = state
+        .toasts
+        .iter()
+        .map(|toast| toast.remaining().saturating_sub(FADE_DURATION))
+        .min()
+    else {
+        return;
+    };
+    // 薄くしている間は毎フレーム、それ以外は次に薄くし始める時に描き直す。
+    if next_fade.is_zero() {
+        ctx.request_repaint();
+    } else {
+        ctx.request_repaint_after(next_fade);
+    }
+
+    let mut dismissed = None;
+    egui::Area::new(egui::Id::new("toasts"))
+        .order(egui::Order::Foreground)
+        .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-16.0, -16.0))
+        .show(ctx, |ui| {
+            ui.spacing_mut().item_spacing.y = 8.0;
+            for (index, toast) in state.toasts.iter().enumerate() {
+                let fade = toast.remaining().as_secs_f32() / FADE_DURATION.as_secs_f32();
+                let accent = toast.kind.accent();
+                let response = ui
+                    .scope(|ui| {
+                        ui.multiply_opacity(fade.min(1.0));
+                        egui::Frame::NONE
+                            .fill(egui::Color32::from_rgb(24, 30, 45))
+                            .stroke(egui::Stroke::new(1.0, accent))
+                            .corner_radius(egui::CornerRadius::same(12))
+                            .inner_margin(egui::Margin::symmetric(12, 10))
+                            .show(ui, |ui| {
+                                ui.set_width(TOAST_WIDTH);
+                                ui.horizontal(|ui| {
+                                    let (dot, _) = ui.allocate_exact_size(
+                                        egui::vec2(8.0, 8.0),
+                                        egui::Sense::hover(),
+                                    );
+                                    ui.painter().circle_filled(dot.center(), 4.0, accent);
+                                    ui.add(
+                                        egui::Label::new(
+                                            egui::RichText::new(&toast.message)
+                                                .size(12.0)
+                                                .color(egui::Color32::from_rgb(220, 230, 245)),
+                                        )
+                                        .wrap(),
+                                    );
+                                });
+                            })
+                            .response
+                    })
+                    .inner;
+                if pointing(response.interact(egui::Sense::click()))
+                    .on_hover_text(tr(Msg::ClickToDismiss))
+                    .clicked()
+                {
+                    dismissed = Some(index);
+                }
+            }
+        });
+
+    if let Some(index) = dismissed {
+        app.toasts.toasts.remove(index);
+    }
+}
@@ -1,13 +1,38 @@
+use std::path::{Path, PathBuf};
+
 use eframe::egui;
 use eframe::emath::GuiRounding;
 
-use crate::app::DownloaderApp;
+use crate::app::{DownloaderApp, FileListKind, PASTE_FEEDBACK_DURATION, PendingRowAction};
+use crate::audio_track_ui;
+use crate::chapter_ui;
+use crate::collision_ui;
+use crate::crop_ui;
 use crate::cursor::pointing;
+use crate::download::{AudioReplacement, DownloadJob, QualityMode};
+use crate::dry_run_ui;
+use crate::error_ui;
+use crate::file_list::{DateGroup, FileSort, FileSortColumn, duration_label, size_label};
+use crate::format_ui;
+use crate::i18n::{Msg, tr, tr_fmt};
 use crate::log_ui;
+use crate::mac_file_dialog;
+use crate::platform::{open_with_default_app, reveal_in_file_manager};
+use crate::preview_ui;
+use crate::recovery_ui;
+use crate::settings::max_height_label;
 use crate::settings_ui;
+use crate::tag_ui;
+use crate::toast_ui;
 
 const PANEL_MIN_WIDTH: f32 = 120.0;
 const DOWNLOAD_PANEL_MAX_RATIO: f32 = 0.5;
+const FILE_ROW_HEIGHT: f32 = 62.0;
+const FILE_HEADER_HEIGHT: f32 = 24.0;
+// 行の右側に並べるサイズ・長さの列の幅
+const SIZE_COLUMN_WIDTH: f32 = 76.0;
+const DURATION_COLUMN_WIDTH: f32 = 60.0;
+const SEARCH_INPUT_ID: &str = "search_input";
 
 pub fn render(
     // UI全体の状態とアクションの入口
@@ -18,6 +43,8 @@ pub fn render(
     frame: &eframe::Frame,
 ) {
     settings_ui::render_toolbar(app, ctx);
+    handle_list_keyboard(app, ctx);
+    handle_paste_download(app, ctx);
     let panel_bg = egui::Color32::from_rgb(15, 23, 42);
     let panel_frame = egui::Frame::NONE
         .fill(panel_bg)
@@ -26,30 +53,154 @@ pub fn render(
     let max_download_width = (available_width * DOWNLOAD_PANEL_MAX_RATIO).max(1.0);
     let min_download_width = PANEL_MIN_WIDTH.min(max_download_width);
     let saved_total_width = (app.download_panel_width + app.search_panel_width).max(1.0);
-    let saved_download_ratio = (app.download_panel_width / saved_total_width)
-        .clamp(0.0, DOWNLOAD_PANEL_MAX_RATIO);
+    let saved_download_ratio =
+        (app.download_panel_width / saved_total_width).clamp(0.0, DOWNLOAD_PANEL_MAX_RATIO);
     let default_download_width =
         (available_width * saved_download_ratio).clamp(min_download_width, max_download_width);
 
-    let download_panel = egui::SidePanel::left("download_section")
-        .resizable(true)
-        .default_width(default_download_width)
-        .width_range(min_download_width..=max_download_width)
-        .frame(panel_frame.clone())
-        .show(ctx, |ui| {
-            render_download_section(ui, ctx, app, frame);
-        });
+    if app.search_detached {
+        // 検索を別ウィンドウに出している間は、ダウンロードだけを全幅で表示する。
+        // 左右の幅は戻した時のために変えずに残す。
+        egui::CentralPanel::default()
+            .frame(panel_frame)
+            .show(ctx, |ui| {
+                render_download_section(ui, ctx, app, frame);
+            });
+        render_search_viewport(app, ctx, frame);
+    } else {
+        let download_panel = egui::SidePanel::left("download_section")
+            .resizable(true)
+            .default_width(default_download_width)
+            .width_range(min_download_width..=max_download_width)
+            .frame(panel_frame.clone())
+            .show(ctx, |ui| {
+                render_download_section(ui, ctx, app, frame);
+            });
 
-    let search_panel = egui::CentralPanel::default()
-        .frame(panel_frame)
-        .show(ctx, |ui| {
-            render_search_section(ui, ctx, app, frame);
-        });
-    app.download_panel_width = download_panel.response.rect.width().max(1.0);
-    app.search_panel_width = search_panel.response.rect.width().max(1.0);
+        let search_panel = egui::CentralPanel::default()
+            .frame(panel_frame)
+            .show(ctx, |ui| {
+                render_search_section(ui, ctx, app, frame);
+            });
+        app.download_panel_width = download_panel.response.rect.width().max(1.0);
+        app.search_panel_width = search_panel.response.rect.width().max(1.0);
+    }
 
     settings_ui::render_windows(app, ctx);
     log_ui::render_log_viewport(app, ctx);
+    tag_ui::render_tag_editor(app, ctx);
+    crop_ui::render_crop_prompt(app, ctx);
+    preview_ui::render_preview_prompt(app, ctx);
+    audio_track_ui::render_audio_track_prompt(app, ctx);
+    format_ui::render_format_prompt(app, ctx);
+    chapter_ui::render_chapter_list(app, ctx);
+    collision_ui::render_collision_prompt(app, ctx);
+    recovery_ui::render_interrupted_job(app, ctx);
+    render_cancel_all_prompt(app, ctx);
+    dry_run_ui::render_dry_run(app, ctx);
+    error_ui::render_error_dialog(app, ctx);
+    toast_ui::render_toasts(app, ctx);
+}
+
+// 検索欄へキーボードフォーカスを移し、入力済みの文字列を全選択する。
+// 検索を別ウィンドウに出している場合は、そのウィンドウを前面に出してから移す。
+pub(crate) fn focus_search_input(
+    // 検索文字列と検索ウィンドウの状態を保持するアプリ状態
+    app: &mut DownloaderApp,
+    // フォーカス要求とウィンドウ操作に使うコンテキスト
+    ctx: &egui::Context,
+) {
+    if app.search_detached {
+        // フォーカスはウィンドウごとに持つため、検索ウィンドウを描く時に移す。
+        ctx.send_viewport_cmd_to(search_viewport_id(), egui::ViewportCommand::Focus);
+        app.pending_search_focus = true;
+        return;
+    }
+    select_search_input(app, ctx);
+}
+
+fn select_search_input(
+    // 現在の検索文字列を保持するアプリ状態
+    app: &DownloaderApp,
+    // フォーカス要求と入力欄の状態を記録するコンテキスト
+    ctx: &egui::Context,
+) {
+    let search_input_id = egui::Id::new(SEARCH_INPUT_ID);
+    let mut state = egui::TextEdit::load_state(ctx, search_input_id).unwrap_or_default();
+    let end = egui::text::CCursor::new(app.search_query.chars().count());
+    state
+        .cursor
+        .set_char_range(Some(egui::text::CCursorRange::two(
+            egui::text::CCursor::new(0),
+            end,
+        )));
+    state.store(ctx, search_input_id);
+    ctx.memory_mut(|mem| mem.request_focus(search_input_id));
+}
+
+// 一覧のキーボード操作（↑↓で選択、Enterで Finder 表示、Cmd+Cでパスをコピー）を処理する。
+fn handle_list_keyboard(
+    // 選択状態と一覧を保持するアプリ状態
+    app: &mut DownloaderApp,
+    // キー入力の取得とクリップボード書き込みに使うコンテキスト
+    ctx: &egui::Context,
+) {
+    let search_input_id = egui::Id::new(SEARCH_INPUT_ID);
+    if ctx.memory(|mem| mem.has_focus(search_input_id)) {
+        let down = ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown));
+        if down && app.select_first_search_result() {
+            ctx.memory_mut(|mem| mem.surrender_focus(search_input_id));
+        }
+        return;
+    }
+    if ctx.wants_keyboard_input() {
+        return;
+    }
+
+    let (up, down, enter, copy) = ctx.input_mut(|i| {
+        (
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+            i.events
+                .iter()
+                .any(|event| matches!(event, egui::Event::Copy)),
+        )
+    });
+    if up {
+        app.move_file_selection(-1);
+    }
+    if down {
+        app.move_file_selection(1);
+    }
+    if enter {
+        app.reveal_selected_file();
+    }
+    if copy && let Some(path) = app.selected_file_path() {
+        ctx.copy_text(path.to_string_lossy().to_string());
+        app.push_status(tr_fmt(Msg::PathCopied, &[&path.to_string_lossy()]));
+    }
+}
+
+// テキスト入力にフォーカスがないときの Cmd+V で、クリップボードの URL をすぐにダウンロードする。
+fn handle_paste_download(
+    // 貼り付けた URL を追加するアプリ状態
+    app: &mut DownloaderApp,
+    // 貼り付けイベントの取得に使うコンテキスト
+    ctx: &egui::Context,
+) {
+    if ctx.wants_keyboard_input() {
+        return;
+    }
+    let pasted = ctx.input(|i| {
+        i.events.iter().find_map(|event| match event {
+            egui::Event::Paste(text) => Some(text.clone()),
+            _ => None,
+        })
+    });
+    if let Some(text) = pasted {
+        app.enqueue_pasted_url(&text);
+    }
 }
 
 fn render_download_section(
@@ -94,10 +245,218 @@ fn render_download_section(
                     app.start_download_from_clipboard();
                 }
             }
+
+            // 設定のプリセットで、この 1 件だけエンコード方式・画質の上限・画質方式を変えてダウンロードする。
+            let presets: Vec<(usize, String, String)> = app
+                .presets
+                .iter()
+                .enumerate()
+                .filter(|(_, preset)| !preset.name.is_empty())
+                .map(|(index, preset)| {
+                    let hint = tr_fmt(
+                        Msg::PresetHint,
+                        &[
+                            &preset.encoder_profile.label(),
+                            &max_height_label(preset.max_height),
+                            &preset.quality.label(),
+                        ],
+                    );
+                    (index, preset.name.clone(), hint)
+                })
+                .collect();
+            if !presets.is_empty() {
+                ui.add_space(6.0);
+                let spacing = ui.spacing().item_spacing.x;
+                let width = (ui.available_width() - spacing * (presets.len() - 1) as f32)
+                    / presets.len() as f32;
+                ui.horizontal(|ui| {
+                    for (index, name, hint) in presets {
+                        let preset_btn = egui::Button::new(
+                            egui::RichText::new(name)
+                                .size(12.0)
+                                .color(egui::Color32::from_rgb(180, 200, 220)),
+                        )
+                        .fill(egui::Color32::from_rgb(26, 34, 52))
+                        .truncate();
+                        if pointing(ui.add_sized([width, 26.0], preset_btn))
+                            .on_hover_text(hint)
+                            .clicked()
+                        {
+                            app.start_download_with_preset(index);
+                        }
+                    }
+                });
+            }
+
+            // クリップボードの URL の形式を一覧から選んでダウンロードする。
+            ui.add_space(6.0);
+            let choose_btn = egui::Button::new(
+                egui::RichText::new(tr(Msg::ChooseFormatAndDownload))
+                    .size(12.0)
+                    .color(egui::Color32::from_rgb(180, 200, 220)),
+            )
+            .fill(egui::Color32::from_rgb(26, 34, 52));
+            if pointing(ui.add_sized([ui.available_width(), 26.0], choose_btn)).clicked() {
+                app.start_download_choosing_format();
+            }
+
+            // 貼り付けたタイムスタンプの一覧で、保存後にチャプターごとのクリップに分割する。
+            ui.add_space(6.0);
+            let split_btn = egui::Button::new(
+                egui::RichText::new(tr(Msg::SplitByTimestampsAndDownload))
+                    .size(12.0)
+                    .color(egui::Color32::from_rgb(180, 200, 220)),
+            )
+            .fill(egui::Color32::from_rgb(26, 34, 52));
+            if pointing(ui.add_sized([ui.available_width(), 26.0], split_btn)).clicked() {
+                app.open_chapter_list();
+            }
+
+            // 保存先・エンコード方式・画質の上限をまとめて切り替える。
+            if !app.profile_names.is_empty() {
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    ui.label(
+                        egui::RichText::new(tr(Msg::Profile))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    let selected_text = if app.active_profile.is_empty() {
+                        tr(Msg::NotSelected)
+                    } else {
+                        app.active_profile.as_str()
+                    };
+                    let mut chosen = None;
+                    egui::ComboBox::from_id_salt("download-profile")
+                        .selected_text(selected_text)
+                        .width(ui.available_width())
+                        .show_ui(ui, |ui| {
+                            for name in &app.profile_names {
+                                let selected = *name == app.active_profile;
+                                if pointing(ui.selectable_label(selected, name)).clicked()
+                                    && !selected
+                                {
+                                    chosen = Some(name.clone());
+                                }
+                            }
+                        });
+                    if let Some(name) = chosen {
+                        app.switch_profile(&name);
+                    }
+                });
+            }
+
+            // 次に追加するダウンロードでクッキーを取得するブラウザのアカウント。
+            if !app.cookie_account_names.is_empty() {
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    ui.label(
+                        egui::RichText::new(tr(Msg::Account))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(150, 160, 180)),
+                    );
+                    let selected_text = if app.active_cookie_account.is_empty() {
+                        tr(Msg::DefaultAccount)
+                    } else {
+                        app.active_cookie_account.as_str()
+                    };
+                    let mut chosen = None;
+                    egui::ComboBox::from_id_salt("download-cookie-account")
+                        .selected_text(selected_text)
+                        .width(ui.available_width())
+                        .show_ui(ui, |ui| {
+                            let names = std::iter::once("")
+                                .chain(app.cookie_account_names.iter().map(String::as_str));
+                            for name in names {
+                                let selected = name == app.active_cookie_account;
+                                let label = if name.is_empty() {
+                                    tr(Msg::DefaultAccount)
+                                } else {
+                                    name
+                                };
+                                if pointing(ui.selectable_label(selected, label)).clicked()
+                                    && !selected
+                                {
+                                    chosen = Some(name.to_string());
+                                }
+                            }
+                        });
+                    if let Some(name) = chosen {
+                        app.switch_cookie_account(&name);
+                    }
+                });
+            }
+
+            // 次に追加するダウンロードの保存先。最近使ったフォルダから選べる。
+            ui.add_space(6.0);
+            render_output_dir_override(ui, app);
+
+            // 次に追加するダウンロードの画質方式。再エンコードする場合だけ効く。
+            ui.add_space(6.0);
+            ui.horizontal(|ui| {
+                ui.label(
+                    egui::RichText::new(tr(Msg::Quality))
+                        .size(12.0)
+                        .color(egui::Color32::from_rgb(150, 160, 180)),
+                );
+                let sized = matches!(app.quality_mode, QualityMode::TargetSize(_));
+                let combo_width = if sized {
+                    ui.available_width() - 90.0
+                } else {
+                    ui.available_width()
+                };
+                egui::ComboBox::from_id_salt("download-quality-mode")
+                    .selected_text(app.quality_mode.label())
+                    .width(combo_width)
+                    .show_ui(ui, |ui| {
+                        for mode in QualityMode::ALL {
+                            // サイズ指定は入力済みの MB を保ったまま選択状態を判定する。
+                            let selected = std::mem::discriminant(&app.quality_mode)
+                                == std::mem::discriminant(&mode);
+                            if pointing(ui.selectable_label(selected, mode.label())).clicked()
+                                && !selected
+                            {
+                                app.quality_mode = mode;
+                            }
+                        }
+                    });
+                if let QualityMode::TargetSize(megabytes) = &mut app.quality_mode {
+                    ui.add(
+                        egui::DragValue::new(megabytes)
+                            .range(1..=4000)
+                            .suffix(" MB"),
+                    );
+                }
+            });
+
+            // 追加したダウンロードを実行せず、渡すコマンドだけを確かめる。
+            ui.add_space(4.0);
+            let _ = pointing(ui.checkbox(&mut app.dry_run, tr(Msg::DryRunOption)))
+                .on_hover_text(tr(Msg::DryRunHint));
         });
 
     ui.add_space(8.0);
     render_progress_panel(ui, ctx, app);
+    if !app.conversions.is_empty() {
+        ui.add_space(8.0);
+        render_conversions(ui, app);
+    }
+    if app.active_job_count() > 0 || app.queue_paused {
+        ui.add_space(8.0);
+        render_queue_controls(ui, app);
+    }
+    if !app.download_queue.is_empty() {
+        ui.add_space(8.0);
+        render_queue_list(ui, app);
+    }
+    if !app.failed_jobs.is_empty() {
+        ui.add_space(8.0);
+        render_failed_jobs(ui, app);
+    }
+    if app.already_downloaded.is_some() {
+        ui.add_space(8.0);
+        render_already_downloaded(ui, app);
+    }
     ui.add_space(16.0);
 
     ui.label(
@@ -106,11 +465,13 @@ fn render_download_section(
             .color(egui::Color32::from_rgb(226, 232, 240)),
     );
     ui.label(
-        egui::RichText::new("リストをドラッグしてVDMXへドロップ")
+        egui::RichText::new(tr(Msg::DragListToVdmx))
             .size(11.5)
             .color(egui::Color32::from_rgb(130, 140, 160)),
     );
     ui.add_space(8.0);
+    render_downloads_filter(ui, app);
+    ui.add_space(8.0);
 
     let list_height = ui.available_height();
     egui::Frame::NONE
@@ -133,6 +494,156 @@ fn render_download_section(
         });
 }
 
+// ダウンロード一覧の絞り込み欄と「この起動中のみ」の切り替えを描画する。
+fn render_downloads_filter(
+    // 絞り込み欄の描画先UI
+    ui: &mut egui::Ui,
+    // 絞り込みの状態を保持するアプリ状態
+    app: &mut DownloaderApp,
+) {
+    ui.horizontal(|ui| {
+        // 切り替えを右端に置き、残りの幅を絞り込み欄に使う
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            let _ = pointing(ui.checkbox(&mut app.downloads_session_only, tr(Msg::SessionOnly)));
+            ui.add_sized(
+                [ui.available_width(), 24.0],
+                egui::TextEdit::singleline(&mut app.downloads_filter)
+                    .hint_text(tr(Msg::DownloadsFilterHint)),
+            );
+        });
+    });
+}
+
+fn render_output_dir_override(
+    // 保存先の行の描画先UI
+    ui: &mut egui::Ui,
+    // 保存先の上書きと最近使ったフォルダを持つアプリ状態
+    app: &mut DownloaderApp,
+) {
+    let folder_name = |path: &Path| {
+        path.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string_lossy().to_string())
+    };
+    ui.horizontal(|ui| {
+        ui.label(
+            egui::RichText::new(tr(Msg::SaveTo))
+                .size(12.0)
+                .color(egui::Color32::from_rgb(150, 160, 180)),
+        );
+        let selected_text = match app.download_dir_override.as_deref() {
+            Some(path) => folder_name(path),
+            None => tr_fmt(Msg::SettingsSaveDir, &[&folder_name(&app.download_dir)]),
+        };
+        let mut chosen = None;
+        let mut pick_other = false;
+        egui::ComboBox::from_id_salt("download-output-dir")
+            .selected_text(selected_text)
+            .width(ui.available_width())
+            .show_ui(ui, |ui| {
+                let setting_text =
+                    tr_fmt(Msg::SettingsSaveDir, &[&app.download_dir.to_string_lossy()]);
+                if pointing(ui.selectable_label(app.download_dir_override.is_none(), setting_text))
+                    .clicked()
+                {
+                    chosen = Some(None);
+                }
+                let setting_dir = app.download_dir.to_string_lossy();
+                for dir in &app.recent_download_dirs {
+                    if *dir == setting_dir {
+                        continue;
+                    }
+                    let selected = app
+                        .download_dir_override
+                        .as_deref()
+                        .is_some_and(|path| path == Path::new(dir));
+                    if pointing(ui.selectable_label(selected, dir)).clicked() {
+                        chosen = Some(Some(PathBuf::from(dir)));
+                    }
+                }
+                if pointing(ui.selectable_label(false, tr(Msg::OtherFolder))).clicked() {
+                    pick_other = true;
+                }
+            });
+        if pick_other {
+            let current = app
+                .download_dir_override
+                .clone()
+                .unwrap_or_else(|| app.download_dir.clone());
+            if let Some(path) = mac_file_dialog::choose_directory(Some(current.as_path())) {
+                chosen = Some(Some(path));
+            }
+        }
+        if let Some(dir) = chosen {
+            app.download_dir_override = dir;
+        }
+    });
+}
+
+fn search_viewport_id() -> egui::ViewportId {
+    egui::ViewportId::from_hash_of("search_viewport")
+}
+
+// 切り離した検索を別のウィンドウに表示する。ウィンドウを閉じるとメイン画面へ戻す。
+fn render_search_viewport(
+    // 検索の状態と検索ウィンドウの大きさを保持するアプリ状態
+    app: &mut DownloaderApp,
+    // ビューポート描画に使うコンテキスト
+    ctx: &egui::Context,
+    // ネイティブドラッグなどフレーム操作に利用
+    frame: &eframe::Frame,
+) {
+    let mut close_requested = false;
+    let builder = egui::ViewportBuilder::default()
+        .with_title(tr(Msg::SearchWindowTitle))
+        .with_inner_size(app.search_window_size)
+        .with_min_inner_size(egui::vec2(240.0, 320.0));
+
+    ctx.show_viewport_immediate(search_viewport_id(), builder, |ctx, class| {
+        if ctx.input(|i| i.viewport().close_requested()) {
+            close_requested = true;
+        }
+        if let Some(rect) = ctx.input(|i| i.viewport().inner_rect) {
+            app.search_window_size = rect.size();
+        }
+        handle_list_keyboard(app, ctx);
+        if std::mem::take(&mut app.pending_search_focus) {
+            select_search_input(app, ctx);
+        }
+
+        match class {
+            egui::ViewportClass::Embedded => {
+                let mut open = true;
+                egui::Window::new(tr(Msg::SearchWindowTitle))
+                    .collapsible(false)
+                    .resizable(true)
+                    .default_size(app.search_window_size)
+                    .open(&mut open)
+                    .show(ctx, |ui| {
+                        render_search_section(ui, ctx, app, frame);
+                    });
+                if !open {
+                    close_requested = true;
+                }
+            }
+            _ => {
+                let panel_frame = egui::Frame::NONE
+                    .fill(egui::Color32::from_rgb(15, 23, 42))
+                    .inner_margin(egui::Margin::symmetric(16, 16));
+                egui::CentralPanel::default()
+                    .frame(panel_frame)
+                    .show(ctx, |ui| {
+                        render_search_section(ui, ctx, app, frame);
+                    });
+            }
+        }
+    });
+
+    if close_requested {
+        app.search_detached = false;
+    }
+}
+
 fn render_search_section(
     // 検索画面の描画先UI
     ui: &mut egui::Ui,
@@ -144,11 +655,32 @@ fn render_search_section(
     frame: &eframe::Frame,
 ) {
     ui.add_space(6.0);
-    ui.label(
-        egui::RichText::new("Search")
-            .size(13.0)
-            .color(egui::Color32::from_rgb(226, 232, 240)),
-    );
+    ui.horizontal(|ui| {
+        ui.label(
+            egui::RichText::new("Search")
+                .size(13.0)
+                .color(egui::Color32::from_rgb(226, 232, 240)),
+        );
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            let (label, hint) = if app.search_detached {
+                (Msg::AttachSearch, Msg::AttachSearchHint)
+            } else {
+                (Msg::DetachSearch, Msg::DetachSearchHint)
+            };
+            let toggle_btn = egui::Button::new(
+                egui::RichText::new(tr(label))
+                    .size(11.0)
+                    .color(egui::Color32::from_rgb(180, 190, 210)),
+            )
+            .fill(egui::Color32::from_rgb(24, 30, 45));
+            if pointing(ui.add(toggle_btn))
+                .on_hover_text(tr(hint))
+                .clicked()
+            {
+                app.search_detached = !app.search_detached;
+            }
+        });
+    });
     ui.add_space(8.0);
 
     let changed = render_search_input(ui, app);
@@ -158,7 +690,7 @@ fn render_search_section(
     ui.add_space(8.0);
 
     ui.label(
-        egui::RichText::new("設定で検索対象フォルダ（外付けSSD等）を指定してください。")
+        egui::RichText::new(tr(Msg::SearchRootsMissing))
             .size(11.5)
             .color(egui::Color32::from_rgb(130, 140, 160)),
     );
@@ -192,7 +724,7 @@ fn render_search_input(
     app: &mut DownloaderApp,
 ) -> bool {
     let mut changed = false;
-    egui::Frame::NONE
+    let frame_response = egui::Frame::NONE
         .fill(egui::Color32::from_rgba_unmultiplied(255, 255, 255, 15))
         .stroke(egui::Stroke::new(
             1.0,
@@ -204,17 +736,75 @@ fn render_search_input(
             let response = ui.add_sized(
                 [ui.available_width(), 24.0],
                 egui::TextEdit::singleline(&mut app.search_query)
-                    .hint_text("ファイル名またはメタ情報で検索...")
+                    .id(egui::Id::new(SEARCH_INPUT_ID))
+                    .hint_text(tr(Msg::SearchHint))
                     .text_color(egui::Color32::from_rgb(226, 232, 240))
                     .frame(false),
             );
             if response.changed() {
                 changed = true;
             }
+            response
         });
+
+    let input_response = frame_response.inner;
+    if input_response.gained_focus() {
+        app.switch_search_input_mode();
+    }
+    // Enter やフォーカス移動で確定したクエリを履歴に残す
+    if input_response.lost_focus() {
+        app.record_search_history();
+        app.restore_search_input_mode();
+    }
+    render_search_history_popup(app, &frame_response.response, &input_response);
     changed
 }
 
+// 検索欄が空でフォーカス中のとき、最近の検索をドロップダウン表示する。
+fn render_search_history_popup(
+    // 検索履歴と検索クエリを保持するアプリ状態
+    app: &mut DownloaderApp,
+    // ドロップダウンの表示位置と幅の基準（検索欄の枠）
+    anchor: &egui::Response,
+    // フォーカス判定に使う検索入力欄
+    input: &egui::Response,
+) {
+    let open = app.search_query.is_empty()
+        && !app.search_history.is_empty()
+        && (input.has_focus() || app.search_history_hovered);
+
+    let mut selected = None;
+    let popup = egui::Popup::from_response(anchor)
+        .open(open)
+        .gap(4.0)
+        .width(anchor.rect.width())
+        .layout(egui::Layout::top_down_justified(egui::Align::Min))
+        .show(|ui| {
+            ui.label(
+                egui::RichText::new(tr(Msg::RecentSearches))
+                    .size(11.0)
+                    .color(egui::Color32::from_rgb(130, 140, 160)),
+            );
+            for query in &app.search_history {
+                let item = egui::Button::new(
+                    egui::RichText::new(query)
+                        .size(12.5)
+                        .color(egui::Color32::from_rgb(226, 232, 240)),
+                )
+                .frame(false);
+                if pointing(ui.add(item)).clicked() {
+                    selected = Some(query.clone());
+                }
+            }
+        });
+
+    // 入力欄のフォーカスが外れても、ポインタが履歴上にある間はクリックを受け付ける
+    app.search_history_hovered = popup.is_some_and(|inner| inner.response.contains_pointer());
+    if let Some(query) = selected {
+        app.rerun_search_history(query);
+    }
+}
+
 fn render_search_results_list(
     // 検索結果リストの描画先UI
     ui: &mut egui::Ui,
@@ -226,6 +816,100 @@ fn render_search_results_list(
     frame: &eframe::Frame,
     // 一覧の最大表示高さ
     list_height: f32,
+) {
+    if app.search_query.trim().is_empty()
+        || app.search_error.is_some()
+        || app.search_results.is_empty()
+    {
+        render_search_message(ui, app, list_height);
+        return;
+    }
+
+    render_file_list_header(ui, &mut app.search_sort, false);
+    app.refresh_search_order();
+    let list_height = (list_height - FILE_HEADER_HEIGHT).max(0.0);
+
+    // 行UIで app を可変借用するため、結果と並びは一時的に取り出して描画後に戻す（複製はしない）
+    let hits = std::mem::take(&mut app.search_results);
+    let order = std::mem::take(&mut app.search_order);
+    let has_more = app.search_next_cursor.is_some();
+    let total_rows = hits.len() + usize::from(has_more);
+    let mut pending = None;
+    let previous_spacing = ui.spacing().item_spacing;
+    ui.spacing_mut().item_spacing = egui::vec2(previous_spacing.x, 0.0);
+    let font_id = egui::FontId::proportional(13.5);
+
+    // 表示範囲の行だけを描画する
+    egui::ScrollArea::vertical()
+        .auto_shrink([false, false])
+        .max_height(list_height)
+        .show_rows(ui, FILE_ROW_HEIGHT, total_rows, |ui, row_range| {
+            ui.set_min_width(ui.available_width());
+            // 表示範囲外の行はまだ描画されないため、選択行の位置を計算してスクロールする
+            if app.scroll_to_selection {
+                let selected_index = app
+                    .file_selection
+                    .as_ref()
+                    .filter(|selection| selection.list == FileListKind::Search)
+                    .and_then(|selection| {
+                        order.iter().position(|index| {
+                            std::path::Path::new(&hits[*index].path) == selection.path
+                        })
+                    });
+                if let Some(index) = selected_index {
+                    let offset = (index as f32 - row_range.start as f32) * FILE_ROW_HEIGHT;
+                    let top = ui.max_rect().top() + offset;
+                    let rect = egui::Rect::from_x_y_ranges(
+                        ui.max_rect().x_range(),
+                        top..=top + FILE_ROW_HEIGHT,
+                    );
+                    ui.scroll_to_rect(rect, None);
+                    app.scroll_to_selection = false;
+                }
+            }
+            for index in row_range {
+                let Some(hit) = order.get(index).map(|index| &hits[*index]) else {
+                    render_load_more_row(ui, app);
+                    continue;
+                };
+                let action = render_file_row(
+                    ui,
+                    ctx,
+                    app,
+                    frame,
+                    &hit.file_name,
+                    std::path::Path::new(&hit.path),
+                    u64::try_from(hit.size_bytes).unwrap_or(0),
+                    hit.duration_ms,
+                    hit.is_cloud,
+                    ui.make_persistent_id((&hit.path, "search_drag_row")),
+                    None,
+                    FileListKind::Search,
+                    &font_id,
+                );
+                if action.is_some() {
+                    pending = action;
+                }
+            }
+        });
+
+    ui.spacing_mut().item_spacing = previous_spacing;
+    app.search_results = hits;
+    app.search_order = order;
+    // 範囲選択や書き出しは一覧を読むため、結果を戻してから適用する
+    if let Some(action) = pending {
+        app.apply_row_action(FileListKind::Search, action);
+    }
+}
+
+// 空クエリ・エラー・0件時のメッセージを描画する。
+fn render_search_message(
+    // メッセージの描画先UI
+    ui: &mut egui::Ui,
+    // 検索状態の参照元
+    app: &DownloaderApp,
+    // 一覧の最大表示高さ
+    list_height: f32,
 ) {
     egui::ScrollArea::vertical()
         .auto_shrink([false, false])
@@ -245,41 +929,47 @@ fn render_search_results_list(
                 return;
             }
 
-            if app.search_results.is_empty() {
-                ui.label(
-                    egui::RichText::new("該当するファイルはありませんでした")
-                        .size(12.5)
-                        .color(egui::Color32::from_rgb(120, 130, 150)),
-                );
-                return;
-            }
-
-            let entries = app
-                .search_results
-                .iter()
-                .map(|hit| (hit.file_name.clone(), hit.path.clone()))
-                .collect::<Vec<_>>();
-            let previous_spacing = ui.spacing().item_spacing;
-            ui.spacing_mut().item_spacing = egui::vec2(previous_spacing.x, 0.0);
-            let font_id = egui::FontId::proportional(13.5);
+            ui.label(
+                egui::RichText::new(tr(Msg::NoMatchingFiles))
+                    .size(12.5)
+                    .color(egui::Color32::from_rgb(120, 130, 150)),
+            );
+        });
+}
 
-            // ファイルリストの表示UIを制御
-            for (file_name, path_string) in &entries {
-                let path = std::path::PathBuf::from(path_string);
-                render_file_row(
-                    ui,
-                    ctx,
-                    app,
-                    frame,
-                    file_name,
-                    &path,
-                    ui.make_persistent_id((path_string, "search_drag_row")),
-                    None,
-                    &font_id,
-                );
+// 検索結果末尾の追加読み込み行を描画する。
+fn render_load_more_row(
+    // 行を描画するUI
+    ui: &mut egui::Ui,
+    // 追加読み込み状態と要求先のアプリ状態
+    app: &mut DownloaderApp,
+) {
+    let size = egui::vec2(ui.available_width(), FILE_ROW_HEIGHT);
+    ui.allocate_ui_with_layout(
+        size,
+        egui::Layout::left_to_right(egui::Align::Center),
+        |ui| {
+            let label = if app.search_loading_more {
+                tr(Msg::Loading)
+            } else {
+                tr(Msg::LoadMore)
+            };
+            let more_btn = egui::Button::new(
+                egui::RichText::new(label)
+                    .size(11.5)
+                    .color(egui::Color32::from_rgb(226, 232, 240)),
+            )
+            .fill(egui::Color32::from_rgba_unmultiplied(226, 232, 240, 20))
+            .stroke(egui::Stroke::new(
+                1.0,
+                egui::Color32::from_rgba_unmultiplied(255, 255, 255, 30),
+            ));
+            let response = ui.add_enabled(!app.search_loading_more, more_btn);
+            if pointing(response).clicked() {
+                app.request_more_search_results();
             }
-            ui.spacing_mut().item_spacing = previous_spacing;
-        });
+        },
+    );
 }
 
 fn render_download_list(
@@ -294,6 +984,12 @@ fn render_download_list(
     // 一覧の最大表示高さ
     list_height: f32,
 ) {
+    let list_height = if app.downloaded_files.is_empty() {
+        list_height
+    } else {
+        render_file_list_header(ui, &mut app.downloads_sort, true);
+        (list_height - FILE_HEADER_HEIGHT).max(0.0)
+    };
     egui::ScrollArea::vertical()
         .auto_shrink([false, false])
         .max_height(list_height)
@@ -301,47 +997,168 @@ fn render_download_list(
             ui.set_min_width(ui.available_width());
             if app.downloaded_files.is_empty() {
                 ui.label(
-                    egui::RichText::new("まだダウンロードがありません。")
+                    egui::RichText::new(tr(Msg::NoDownloadsYet))
+                        .size(12.5)
+                        .color(egui::Color32::from_rgb(120, 130, 150)),
+                );
+                return;
+            }
+            app.refresh_downloads_order();
+            let groups = app.download_groups();
+            if groups.is_empty() {
+                ui.label(
+                    egui::RichText::new(tr(Msg::NoMatchingFiles))
                         .size(12.5)
                         .color(egui::Color32::from_rgb(120, 130, 150)),
                 );
                 return;
             }
-            let files = app.downloaded_files.clone();
-            let mut remove_paths = Vec::new();
+            let mut pending = None;
             let previous_spacing = ui.spacing().item_spacing;
             ui.spacing_mut().item_spacing = egui::vec2(previous_spacing.x, 0.0);
             let font_id = egui::FontId::proportional(13.5);
-            for path in &files {
-                let filename = path
-                    .file_name()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or("unknown");
-                let should_remove = render_file_row(
-                    ui,
-                    ctx,
-                    app,
-                    frame,
-                    filename,
-                    path,
-                    ui.make_persistent_id((path, "drag_row")),
-                    Some(ui.make_persistent_id((path, "remove_button"))),
-                    &font_id,
-                );
-                if should_remove {
-                    remove_paths.push(path.clone());
+            for (group, files) in &groups {
+                if let Some(group) = *group {
+                    let collapsed = app.collapsed_date_groups.contains(&group);
+                    if render_date_group_header(ui, group, files.len(), collapsed) {
+                        app.toggle_date_group(group);
+                    }
+                    if collapsed {
+                        continue;
+                    }
+                }
+                for file in files {
+                    let path = &file.path;
+                    let filename = path
+                        .file_name()
+                        .and_then(|s| s.to_str())
+                        .unwrap_or("unknown");
+                    let action = render_file_row(
+                        ui,
+                        ctx,
+                        app,
+                        frame,
+                        filename,
+                        path,
+                        file.size_bytes,
+                        file.duration_ms,
+                        file.is_cloud,
+                        ui.make_persistent_id((path, "drag_row")),
+                        Some(ui.make_persistent_id((path, "remove_button"))),
+                        FileListKind::Downloads,
+                        &font_id,
+                    );
+                    if action.is_some() {
+                        pending = action;
+                    }
                 }
             }
             ui.spacing_mut().item_spacing = previous_spacing;
 
-            if !remove_paths.is_empty() {
-                for path in remove_paths {
-                    app.delete_download(&path);
-                }
+            if let Some(action) = pending {
+                app.apply_row_action(FileListKind::Downloads, action);
             }
         });
 }
 
+// 日付の区切りの見出しを描画する。クリックされたら true を返し、呼び出し側で畳む・開く。
+fn render_date_group_header(
+    // 見出しの描画先UI
+    ui: &mut egui::Ui,
+    // 見出しの区切り
+    group: DateGroup,
+    // 区切りに含まれるファイル数
+    count: usize,
+    // 畳んでいるか
+    collapsed: bool,
+) -> bool {
+    let label = match group {
+        DateGroup::Today => tr(Msg::DateGroupToday),
+        DateGroup::Yesterday => tr(Msg::DateGroupYesterday),
+        DateGroup::ThisWeek => tr(Msg::DateGroupThisWeek),
+        DateGroup::Older => tr(Msg::DateGroupOlder),
+    };
+    let marker = if collapsed { "▶" } else { "▼" };
+    let header = egui::Button::new(
+        egui::RichText::new(format!("{marker} {label} ({count})"))
+            .size(12.0)
+            .color(egui::Color32::from_rgb(150, 160, 180)),
+    )
+    .frame(false);
+    pointing(ui.add(header)).clicked()
+}
+
+// 一覧の列見出し（名前・サイズ・長さ）を行の列と同じ位置に描画し、クリックした列で並べ替える。
+fn render_file_list_header(
+    // 見出しの描画先UI
+    ui: &mut egui::Ui,
+    // 一覧の並び順（見出しのクリックで切り替える）
+    sort: &mut Option<FileSort>,
+    // 行の右端に削除ボタンがある一覧か
+    has_remove: bool,
+) {
+    // 行と同じくスクロールバーの幅と左右の余白を空ける
+    let scroll_margin = 24.0;
+    let row_padding_x = 12.0;
+    let reserve_remove_width = if has_remove { 28.0 + 8.0 } else { 0.0 };
+    let width = (ui.available_width() - scroll_margin).max(0.0);
+    let (rect, _) =
+        ui.allocate_exact_size(egui::vec2(width, FILE_HEADER_HEIGHT), egui::Sense::hover());
+    let columns_right = rect.right() - row_padding_x - reserve_remove_width;
+    let duration_left = columns_right - DURATION_COLUMN_WIDTH;
+    let size_left = duration_left - SIZE_COLUMN_WIDTH;
+    let columns = [
+        (
+            FileSortColumn::Name,
+            Msg::ColumnName,
+            rect.left() + row_padding_x..=size_left,
+        ),
+        (
+            FileSortColumn::Size,
+            Msg::ColumnSize,
+            size_left..=duration_left,
+        ),
+        (
+            FileSortColumn::Duration,
+            Msg::ColumnDuration,
+            duration_left..=columns_right,
+        ),
+    ];
+    for (column, label, x_range) in columns {
+        let header_rect = egui::Rect::from_x_y_ranges(x_range, rect.y_range());
+        let response = pointing(ui.interact(
+            header_rect,
+            ui.make_persistent_id(("file_list_header", column)),
+            egui::Sense::click(),
+        ));
+        let arrow = match *sort {
+            Some(current) if current.column == column && current.descending => " ▼",
+            Some(current) if current.column == column => " ▲",
+            _ => "",
+        };
+        let color = if response.hovered() || !arrow.is_empty() {
+            egui::Color32::from_rgb(226, 232, 240)
+        } else {
+            egui::Color32::from_rgb(130, 140, 160)
+        };
+        let (pos, align) = if column == FileSortColumn::Name {
+            (header_rect.left_center(), egui::Align2::LEFT_CENTER)
+        } else {
+            (header_rect.right_center(), egui::Align2::RIGHT_CENTER)
+        };
+        ui.painter().text(
+            pos,
+            align,
+            format!("{}{arrow}", tr(label)),
+            egui::FontId::proportional(11.5),
+            color,
+        );
+        if response.clicked() {
+            *sort = FileSort::toggle(*sort, column);
+        }
+    }
+}
+
 fn render_file_row(
     // 行を描画するUI
     ui: &mut egui::Ui,
@@ -355,17 +1172,25 @@ fn render_file_row(
     file_name: &str,
     // ドラッグ対象パス
     drag_path: &std::path::Path,
+    // サイズ列に表示するバイト数
+    size_bytes: u64,
+    // 長さ列に表示する長さ（まだ読めていなければ None）
+    duration_ms: Option<i64>,
+    // 中身がクラウドにだけあるファイルか（雲のバッジを付ける）
+    is_cloud: bool,
     // 行のドラッグ検知用ID
     drag_id: egui::Id,
     // 削除ボタン用ID（Noneならボタンなし）
     remove_id: Option<egui::Id>,
+    // キーボード選択の対象一覧
+    list: FileListKind,
     // 文字幅計測と描画に使うフォント
     font_id: &egui::FontId,
-) -> bool {
+) -> Option<PendingRowAction> {
     // スクロールバーとの重なりを防ぐための余白を追加
     let scroll_margin = 24.0;
     let row_width = (ui.available_width() - scroll_margin).max(0.0);
-    let row_height = 62.0;
+    let row_height = FILE_ROW_HEIGHT;
     let row_padding_x = 12.0;
     let remove_width = 28.0;
     let remove_height = 28.0;
@@ -375,7 +1200,29 @@ fn render_file_row(
     } else {
         0.0
     };
-    let text_max_width = (row_width - row_padding_x * 2.0 - reserve_remove_width).max(0.0);
+    let columns_width = SIZE_COLUMN_WIDTH + DURATION_COLUMN_WIDTH;
+    // この起動中に保存したファイルは、ファイル名の前に NEW バッジを付ける
+    let is_new = app.session_downloads.contains(drag_path);
+    let badge_width = 36.0;
+    let badge_spacing = 8.0;
+    let new_badge_width = if is_new {
+        badge_width + badge_spacing
+    } else {
+        0.0
+    };
+    // クラウドにだけあるファイルは、NEW バッジの後ろに雲のバッジを付ける
+    let cloud_badge_width = 24.0;
+    let reserve_badge_width = if is_cloud {
+        new_badge_width + cloud_badge_width + badge_spacing
+    } else {
+        new_badge_width
+    };
+    let text_max_width = (row_width
+        - row_padding_x * 2.0
+        - reserve_remove_width
+        - columns_width
+        - reserve_badge_width)
+        .max(0.0);
     let text = truncate_with_ellipsis(ui, file_name, text_max_width, font_id);
 
     let (row_rect, row_response) =
@@ -389,15 +1236,77 @@ fn render_file_row(
                 .latest_pos()
                 .is_some_and(|pos| row_rect.contains(pos))
         });
-    let fill = if row_hovered { hover_fill } else { base_fill };
+    let selected = app.is_file_selected(list, drag_path);
+    let fill = if selected {
+        egui::Color32::from_rgb(30, 58, 88)
+    } else if row_hovered {
+        hover_fill
+    } else {
+        base_fill
+    };
     ui.painter()
         .rect_filled(row_rect, egui::CornerRadius::same(0), fill);
+    if selected {
+        // 選択行は左端にアクセントを付けて識別しやすくする
+        let accent = egui::Rect::from_min_size(row_rect.min, egui::vec2(3.0, row_rect.height()));
+        ui.painter().rect_filled(
+            accent,
+            egui::CornerRadius::same(0),
+            egui::Color32::from_rgb(16, 190, 255),
+        );
+        if app.scroll_to_selection {
+            ui.scroll_to_rect(row_rect, None);
+            app.scroll_to_selection = false;
+        }
+    }
 
     let inner_rect = row_rect.shrink2(egui::vec2(row_padding_x, 0.0));
     let text_color = egui::Color32::from_rgb(220, 230, 245);
     // テキストの垂直位置を微調整（視覚的な中央揃えのため少し上にずらす）
     let text_offset_y = -2.0;
-    let text_pos = egui::pos2(inner_rect.left(), inner_rect.center().y + text_offset_y);
+    let text_pos = egui::pos2(
+        inner_rect.left() + reserve_badge_width,
+        inner_rect.center().y + text_offset_y,
+    );
+    if is_new {
+        let badge_rect = egui::Rect::from_center_size(
+            egui::pos2(inner_rect.left() + badge_width * 0.5, text_pos.y),
+            egui::vec2(badge_width, 18.0),
+        );
+        ui.painter().rect_filled(
+            badge_rect,
+            egui::CornerRadius::same(9),
+            egui::Color32::from_rgb(16, 190, 255),
+        );
+        ui.painter().text(
+            badge_rect.center(),
+            egui::Align2::CENTER_CENTER,
+            "NEW",
+            egui::FontId::proportional(10.5),
+            egui::Color32::from_rgb(10, 20, 32),
+        );
+    }
+    if is_cloud {
+        let badge_rect = egui::Rect::from_center_size(
+            egui::pos2(
+                inner_rect.left() + new_badge_width + cloud_badge_width * 0.5,
+                text_pos.y,
+            ),
+            egui::vec2(cloud_badge_width, 18.0),
+        );
+        ui.painter().rect_filled(
+            badge_rect,
+            egui::CornerRadius::same(9),
+            egui::Color32::from_rgb(70, 82, 104),
+        );
+        ui.painter().text(
+            badge_rect.center(),
+            egui::Align2::CENTER_CENTER,
+            "☁",
+            egui::FontId::proportional(12.0),
+            egui::Color32::from_rgb(220, 230, 245),
+        );
+    }
     ui.painter().text(
         text_pos,
         egui::Align2::LEFT_CENTER,
@@ -406,8 +1315,30 @@ fn render_file_row(
         text_color,
     );
 
+    // サイズと長さは削除ボタンの手前に右揃えで並べる
+    let columns_right = row_rect.right() - row_padding_x - reserve_remove_width;
+    let column_font = egui::FontId::proportional(12.0);
+    let column_color = egui::Color32::from_rgb(150, 160, 180);
+    ui.painter().text(
+        egui::pos2(columns_right - DURATION_COLUMN_WIDTH, text_pos.y),
+        egui::Align2::RIGHT_CENTER,
+        size_label(size_bytes),
+        column_font.clone(),
+        column_color,
+    );
+    if let Some(duration_ms) = duration_ms {
+        ui.painter().text(
+            egui::pos2(columns_right, text_pos.y),
+            egui::Align2::RIGHT_CENTER,
+            duration_label(duration_ms),
+            column_font,
+            column_color,
+        );
+    }
+
+    // 行の操作は呼び出し側で一覧を戻してから適用する
+    let mut action = None;
     let mut drag_rect = row_rect;
-    let mut should_remove = false;
     if let Some(remove_id) = remove_id {
         let remove_rect = egui::Rect::from_min_size(
             egui::pos2(
@@ -435,24 +1366,554 @@ fn render_file_row(
             remove_color,
         );
         if remove_response.clicked() {
-            should_remove = true;
+            action = Some(PendingRowAction::Remove(drag_path.to_path_buf()));
         }
         let max_x = remove_rect.left().min(row_rect.right());
         if max_x > row_rect.left() {
-            drag_rect = egui::Rect::from_min_max(row_rect.min, egui::pos2(max_x, row_rect.bottom()));
+            drag_rect =
+                egui::Rect::from_min_max(row_rect.min, egui::pos2(max_x, row_rect.bottom()));
         }
     }
 
-    let drag_response = pointing(ui.interact(
-        drag_rect,
-        drag_id,
-        egui::Sense::click_and_drag(),
-    ));
+    let drag_response = pointing(ui.interact(drag_rect, drag_id, egui::Sense::click_and_drag()));
+    if drag_response.clicked() {
+        let modifiers = ctx.input(|i| i.modifiers);
+        let path = drag_path.to_path_buf();
+        action = Some(if modifiers.command {
+            PendingRowAction::Toggle(path)
+        } else if modifiers.shift {
+            PendingRowAction::Extend(path)
+        } else {
+            PendingRowAction::Select(path)
+        });
+    }
+    if drag_response.secondary_clicked() && !selected {
+        // 未選択の行を右クリックした場合はその行だけを対象にする
+        action = Some(PendingRowAction::Select(drag_path.to_path_buf()));
+    }
+    drag_response.context_menu(|ui| {
+        if pointing(ui.button(tr(Msg::ExportPlaylistMenu))).clicked() {
+            ui.close();
+            action = Some(PendingRowAction::ExportPlaylist);
+        }
+        if pointing(ui.button(tr(Msg::EditTagsMenu))).clicked() {
+            ui.close();
+            action = Some(PendingRowAction::EditTags);
+        }
+        // ステージで素材の音声が鳴らないよう、音声を外す・差し替えたコピーをまとめて作る。
+        if app.mute_batch.is_some() {
+            if pointing(ui.button(tr(Msg::CancelReplaceAudioMenu))).clicked() {
+                ui.close();
+                app.cancel_replace_audio();
+            }
+        } else {
+            if pointing(ui.button(tr(Msg::StripAudioMenu))).clicked() {
+                ui.close();
+                action = Some(PendingRowAction::ReplaceAudio(AudioReplacement::Remove));
+            }
+            if pointing(ui.button(tr(Msg::SilenceAudioMenu))).clicked() {
+                ui.close();
+                action = Some(PendingRowAction::ReplaceAudio(AudioReplacement::Silence));
+            }
+            if pointing(ui.button(tr(Msg::ReplaceAudioMenu))).clicked() {
+                ui.close();
+                action = Some(PendingRowAction::ReplaceAudioWithTrack);
+            }
+        }
+        let fetch_btn = egui::Button::new(tr(Msg::FetchCloudMenu));
+        if pointing(ui.add_enabled(app.cloud_fetch.is_none(), fetch_btn)).clicked() {
+            ui.close();
+            action = Some(PendingRowAction::FetchCloud);
+        }
+        if app.proxy_batch.is_some() {
+            if pointing(ui.button(tr(Msg::CancelProxiesMenu))).clicked() {
+                ui.close();
+                app.cancel_proxy_generation();
+            }
+        } else if pointing(ui.button(tr(Msg::GenerateProxiesMenu))).clicked() {
+            ui.close();
+            action = Some(PendingRowAction::GenerateProxies);
+        }
+    });
     if drag_response.drag_started_by(egui::PointerButton::Primary) {
         app.start_native_drag(frame, drag_path);
     }
 
-    should_remove
+    action
+}
+
+// ダウンロード済みでスキップした項目と、保存済みのファイルを開く操作。
+fn render_already_downloaded(
+    // 表示先のUI
+    ui: &mut egui::Ui,
+    // スキップした項目を持つアプリ状態
+    app: &mut DownloaderApp,
+) {
+    let Some(notice) = app.already_downloaded.clone() else {
+        return;
+    };
+    let mut dismiss = false;
+    let mut action_error = None;
+
+    egui::Frame::NONE
+        .fill(egui::Color32::from_rgb(20, 40, 52))
+        .stroke(egui::Stroke::new(
+            1.0,
+            egui::Color32::from_rgb(16, 120, 160),
+        ))
+        .corner_radius(egui::CornerRadius::same(12))
+        .inner_margin(egui::Margin::symmetric(12, 10))
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(
+                    egui::RichText::new(tr(Msg::SkippedAlreadyDownloaded))
+                        .size(12.5)
+                        .color(egui::Color32::from_rgb(220, 230, 245)),
+                );
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let close_btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::Close))
+                            .size(11.0)
+                            .color(egui::Color32::from_rgb(180, 190, 210)),
+                    )
+                    .fill(egui::Color32::from_rgb(24, 30, 45));
+                    if pointing(ui.add(close_btn)).clicked() {
+                        dismiss = true;
+                    }
+                });
+            });
+            let detail = match &notice.path {
+                Some(path) => path.display().to_string(),
+                None => tr_fmt(Msg::RecordedInArchive, &[&notice.url]),
+            };
+            ui.label(
+                egui::RichText::new(detail)
+                    .size(11.0)
+                    .color(egui::Color32::from_rgb(140, 150, 170)),
+            );
+
+            if let Some(path) = notice.path.as_ref() {
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    let open_btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::OpenFile))
+                            .size(11.5)
+                            .color(egui::Color32::from_rgb(8, 14, 24)),
+                    )
+                    .fill(egui::Color32::from_rgb(16, 190, 255));
+                    if pointing(ui.add(open_btn)).clicked()
+                        && let Err(err) = open_with_default_app(path)
+                    {
+                        action_error = Some(tr_fmt(Msg::OpenFileFailed, &[&err]));
+                    }
+
+                    let reveal_btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::RevealInFinder))
+                            .size(11.5)
+                            .color(egui::Color32::from_rgb(180, 200, 220)),
+                    )
+                    .fill(egui::Color32::from_rgb(26, 34, 52));
+                    if pointing(ui.add(reveal_btn)).clicked()
+                        && let Err(err) = reveal_in_file_manager(path)
+                    {
+                        action_error = Some(tr_fmt(Msg::RevealInFinderFailed, &[&err]));
+                    }
+                });
+            }
+        });
+
+    if let Some(err) = action_error {
+        app.push_error(err);
+    }
+    if dismiss {
+        app.already_downloaded = None;
+    }
+}
+
+// 実行中と待機中のダウンロードをまとめて一時停止・キャンセルするボタン。
+fn render_queue_controls(
+    // 表示先のUI
+    ui: &mut egui::Ui,
+    // 待機キューと実行中のダウンロードを持つアプリ状態
+    app: &mut DownloaderApp,
+) {
+    ui.horizontal(|ui| {
+        let pause_label = if app.queue_paused {
+            tr(Msg::ResumeAll)
+        } else {
+            tr(Msg::PauseAll)
+        };
+        let pause_btn = egui::Button::new(
+            egui::RichText::new(pause_label)
+                .size(12.0)
+                .color(egui::Color32::from_rgb(180, 190, 210)),
+        )
+        .fill(egui::Color32::from_rgb(24, 30, 45));
+        if pointing(ui.add(pause_btn)).clicked() {
+            app.toggle_pause_all();
+        }
+
+        let cancel_btn = egui::Button::new(
+            egui::RichText::new(tr(Msg::CancelAll))
+                .size(12.0)
+                .color(egui::Color32::from_rgb(248, 113, 113)),
+        )
+        .fill(egui::Color32::from_rgb(24, 30, 45));
+        if pointing(ui.add_enabled(app.active_job_count() > 0, cancel_btn)).clicked() {
+            app.request_cancel_all();
+        }
+    });
+}
+
+// 多くのダウンロードをまとめてキャンセルする前の確認。
+fn render_cancel_all_prompt(
+    // 確認待ちの件数を持つアプリ状態
+    app: &mut DownloaderApp,
+    // ウィンドウ表示に使うコンテキスト
+    ctx: &egui::Context,
+) {
+    let Some(count) = app.cancel_all_prompt else {
+        return;
+    };
+
+    let mut open = true;
+    let mut answer = None;
+    egui::Window::new(tr(Msg::CancelAllTitle))
+        .collapsible(false)
+        .resizable(false)
+        .default_width(360.0)
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .open(&mut open)
+        .show(ctx, |ui| {
+            ui.add(
+                egui::Label::new(
+                    egui::RichText::new(tr_fmt(Msg::CancelAllMessage, &[&count]))
+                        .size(12.0)
+                        .color(egui::Color32::from_rgb(220, 230, 245)),
+                )
+                .wrap(),
+            );
+
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let confirm_btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::CancelAll))
+                            .size(12.5)
+                            .color(egui::Color32::from_rgb(8, 14, 24)),
+                    )
+                    .fill(egui::Color32::from_rgb(248, 113, 113));
+                    if pointing(ui.add(confirm_btn)).clicked() {
+                        answer = Some(true);
+                    }
+
+                    let keep_btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::Close))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(180, 190, 210)),
+                    )
+                    .fill(egui::Color32::from_rgb(24, 30, 45));
+                    if pointing(ui.add(keep_btn)).clicked() {
+                        answer = Some(false);
+                    }
+                });
+            });
+        });
+
+    if !open {
+        answer = Some(false);
+    }
+    match answer {
+        Some(true) => app.cancel_all_downloads(),
+        Some(false) => app.cancel_all_prompt = None,
+        None => {}
+    }
+}
+
+// 待機中の項目の一覧。ドラッグで並べ替え、「次に実行」で先頭へ回す。
+fn render_queue_list(
+    // 表示先のUI
+    ui: &mut egui::Ui,
+    // 待機キューを持つアプリ状態
+    app: &mut DownloaderApp,
+) {
+    let mut moved = None;
+    let mut priority_change = None;
+
+    egui::Frame::NONE
+        .fill(egui::Color32::from_rgba_unmultiplied(255, 255, 255, 8))
+        .corner_radius(egui::CornerRadius::same(12))
+        .inner_margin(egui::Margin::symmetric(10, 8))
+        .show(ui, |ui| {
+            egui::ScrollArea::vertical()
+                .id_salt("queue-list")
+                .max_height(140.0)
+                .show(ui, |ui| {
+                    for (index, job) in app.download_queue.iter().enumerate() {
+                        let row = ui.horizontal(|ui| render_queue_row(ui, index, job));
+                        if row.inner {
+                            priority_change = Some((index, !job.priority));
+                        }
+                        // ドラッグ中は、落とす位置の上に線を引く。
+                        let row = row.response;
+                        if row.dnd_hover_payload::<usize>().is_some() {
+                            ui.painter().hline(
+                                row.rect.x_range(),
+                                row.rect.top() - 1.0,
+                                egui::Stroke::new(2.0, egui::Color32::from_rgb(16, 190, 255)),
+                            );
+                        }
+                        if let Some(from) = row.dnd_release_payload::<usize>() {
+                            moved = Some((*from, index));
+                        }
+                    }
+                });
+        });
+
+    if let Some((from, to)) = moved {
+        app.move_queued_job(from, to);
+    }
+    if let Some((index, priority)) = priority_change {
+        app.set_queued_job_priority(index, priority);
+    }
+}
+
+// 取得を終え、次のダウンロードと並行して変換している項目
+fn render_conversions(
+    // 表示先のUI
+    ui: &mut egui::Ui,
+    // 変換中の項目を持つアプリ状態
+    app: &mut DownloaderApp,
+) {
+    let mut cancel = None;
+    let url_font = egui::FontId::proportional(11.0);
+
+    egui::Frame::NONE
+        .fill(egui::Color32::from_rgba_unmultiplied(255, 255, 255, 8))
+        .corner_radius(egui::CornerRadius::same(12))
+        .inner_margin(egui::Margin::symmetric(10, 8))
+        .show(ui, |ui| {
+            ui.label(
+                egui::RichText::new(tr(Msg::BackgroundConversions))
+                    .size(12.0)
+                    .color(egui::Color32::from_rgb(203, 213, 225)),
+            );
+            for (index, conversion) in app.conversions.iter().enumerate() {
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Min), |ui| {
+                        let cancel_btn = egui::Button::new(
+                            egui::RichText::new("×")
+                                .size(11.0)
+                                .color(egui::Color32::from_rgb(180, 190, 210)),
+                        )
+                        .fill(egui::Color32::from_rgb(24, 30, 45));
+                        if pointing(ui.add(cancel_btn))
+                            .on_hover_text(tr(Msg::Cancel))
+                            .clicked()
+                        {
+                            cancel = Some(index);
+                        }
+
+                        let width = ui.available_width();
+                        ui.with_layout(egui::Layout::top_down(egui::Align::Min), |ui| {
+                            let url = truncate_with_ellipsis(ui, &conversion.url, width, &url_font);
+                            ui.label(
+                                egui::RichText::new(url)
+                                    .font(url_font.clone())
+                                    .color(egui::Color32::from_rgb(220, 230, 245)),
+                            )
+                            .on_hover_text(&conversion.url);
+                            ui.label(
+                                egui::RichText::new(&conversion.message)
+                                    .size(10.5)
+                                    .color(egui::Color32::from_rgb(140, 150, 170)),
+                            );
+                            ui.add(
+                                egui::ProgressBar::new(conversion.progress.unwrap_or(0.0))
+                                    .desired_width(width)
+                                    .desired_height(4.0)
+                                    .fill(egui::Color32::from_rgb(56, 189, 248)),
+                            );
+                        });
+                    });
+                });
+            }
+        });
+
+    if let Some(index) = cancel {
+        app.cancel_conversion(index);
+    }
+}
+
+// 失敗したダウンロードの一覧。エラーを見て、同じ指定でやり直せる。
+fn render_failed_jobs(
+    // 表示先のUI
+    ui: &mut egui::Ui,
+    // 失敗したダウンロードを持つアプリ状態
+    app: &mut DownloaderApp,
+) {
+    let mut retry = None;
+    let mut dismiss = None;
+    let mut clear = false;
+    let url_font = egui::FontId::proportional(11.0);
+    let error_font = egui::FontId::proportional(10.5);
+
+    egui::Frame::NONE
+        .fill(egui::Color32::from_rgb(40, 24, 30))
+        .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(120, 50, 60)))
+        .corner_radius(egui::CornerRadius::same(12))
+        .inner_margin(egui::Margin::symmetric(10, 8))
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(
+                    egui::RichText::new(tr(Msg::FailedDownloads))
+                        .size(12.0)
+                        .color(egui::Color32::from_rgb(248, 113, 113)),
+                );
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let clear_btn = egui::Button::new(
+                        egui::RichText::new(tr(Msg::ClearAll))
+                            .size(10.5)
+                            .color(egui::Color32::from_rgb(180, 190, 210)),
+                    )
+                    .fill(egui::Color32::from_rgb(24, 30, 45));
+                    if pointing(ui.add(clear_btn)).clicked() {
+                        clear = true;
+                    }
+                });
+            });
+
+            egui::ScrollArea::vertical()
+                .id_salt("failed-jobs")
+                .max_height(160.0)
+                .show(ui, |ui| {
+                    for (index, failed) in app.failed_jobs.iter().enumerate() {
+                        ui.add_space(4.0);
+                        ui.horizontal(|ui| {
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Min), |ui| {
+                                let dismiss_btn = egui::Button::new(
+                                    egui::RichText::new("×")
+                                        .size(11.0)
+                                        .color(egui::Color32::from_rgb(180, 190, 210)),
+                                )
+                                .fill(egui::Color32::from_rgb(24, 30, 45));
+                                if pointing(ui.add(dismiss_btn))
+                                    .on_hover_text(tr(Msg::Dismiss))
+                                    .clicked()
+                                {
+                                    dismiss = Some(index);
+                                }
+
+                                let retry_btn = egui::Button::new(
+                                    egui::RichText::new(tr(Msg::Retry))
+                                        .size(11.0)
+                                        .color(egui::Color32::from_rgb(8, 14, 24)),
+                                )
+                                .fill(egui::Color32::from_rgb(16, 190, 255));
+                                if pointing(ui.add(retry_btn)).clicked() {
+                                    retry = Some(index);
+                                }
+
+                                let width = ui.available_width();
+                                ui.with_layout(egui::Layout::top_down(egui::Align::Min), |ui| {
+                                    let url = truncate_with_ellipsis(
+                                        ui,
+                                        &failed.job.url,
+                                        width,
+                                        &url_font,
+                                    );
+                                    ui.label(
+                                        egui::RichText::new(url)
+                                            .font(url_font.clone())
+                                            .color(egui::Color32::from_rgb(220, 230, 245)),
+                                    )
+                                    .on_hover_text(&failed.job.url);
+                                    let error = truncate_with_ellipsis(
+                                        ui,
+                                        &failed.error,
+                                        width,
+                                        &error_font,
+                                    );
+                                    ui.label(
+                                        egui::RichText::new(error)
+                                            .font(error_font.clone())
+                                            .color(egui::Color32::from_rgb(140, 150, 170)),
+                                    )
+                                    .on_hover_text(&failed.error);
+                                });
+                            });
+                        });
+                    }
+                });
+        });
+
+    if let Some(index) = retry {
+        app.retry_failed_job(index);
+    } else if let Some(index) = dismiss {
+        app.failed_jobs.take(index);
+    } else if clear {
+        app.failed_jobs.clear();
+    }
+}
+
+// 待機中の 1 件。「次に実行」が押された場合は true を返す。
+fn render_queue_row(
+    // 行の描画先UI
+    ui: &mut egui::Ui,
+    // キューの中の位置（ドラッグで渡す値）
+    index: usize,
+    // 表示する項目
+    job: &DownloadJob,
+) -> bool {
+    let handle = ui.dnd_drag_source(egui::Id::new(("queued-job", index)), index, |ui| {
+        ui.label(
+            egui::RichText::new("☰")
+                .size(12.0)
+                .color(egui::Color32::from_rgb(120, 130, 150)),
+        );
+    });
+    handle
+        .response
+        .on_hover_cursor(egui::CursorIcon::Grab)
+        .on_hover_text(tr(Msg::QueueDragHint));
+
+    let (text_color, fill) = if job.priority {
+        (
+            egui::Color32::from_rgb(8, 14, 24),
+            egui::Color32::from_rgb(16, 190, 255),
+        )
+    } else {
+        (
+            egui::Color32::from_rgb(180, 190, 210),
+            egui::Color32::from_rgb(24, 30, 45),
+        )
+    };
+    let next_btn = egui::Button::new(
+        egui::RichText::new(tr(Msg::QueueNextUp))
+            .size(10.5)
+            .color(text_color),
+    )
+    .fill(fill);
+    // 右端のボタンの幅を除いた残りに URL を収める。
+    let button_width = text_width(ui, tr(Msg::QueueNextUp), &egui::FontId::proportional(10.5))
+        + ui.spacing().button_padding.x * 2.0
+        + ui.spacing().item_spacing.x * 2.0;
+    let font_id = egui::FontId::proportional(11.0);
+    let url_width = ui.available_width() - button_width;
+    let url = truncate_with_ellipsis(ui, &job.url, url_width, &font_id);
+    ui.label(
+        egui::RichText::new(url)
+            .font(font_id)
+            .color(egui::Color32::from_rgb(203, 213, 225)),
+    )
+    .on_hover_text(&job.url);
+    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+        pointing(ui.add(next_btn))
+            .on_hover_text(tr(Msg::QueueNextUpHint))
+            .clicked()
+    })
+    .inner
 }
 
 fn render_progress_panel(
@@ -465,15 +1926,32 @@ fn render_progress_panel(
 ) {
     let idle = !app.progress_visible;
     let opacity = if idle { 0.6 } else { 1.0 };
+    // 貼り付けた直後は枠を光らせ、少しずつ元の色へ戻す。
+    let paste_feedback = app
+        .paste_feedback
+        .as_ref()
+        .filter(|feedback| feedback.shown_at.elapsed() < PASTE_FEEDBACK_DURATION);
 
     let panel_fill = apply_opacity(
         egui::Color32::from_rgba_unmultiplied(255, 255, 255, 13),
         opacity,
     );
-    let panel_stroke = apply_opacity(
+    let mut panel_stroke = apply_opacity(
         egui::Color32::from_rgba_unmultiplied(255, 255, 255, 20),
         opacity,
     );
+    let mut feedback_color = egui::Color32::TRANSPARENT;
+    if let Some(feedback) = paste_feedback {
+        feedback_color = if feedback.accepted {
+            egui::Color32::from_rgb(56, 189, 248)
+        } else {
+            egui::Color32::from_rgb(248, 113, 113)
+        };
+        let elapsed =
+            feedback.shown_at.elapsed().as_secs_f32() / PASTE_FEEDBACK_DURATION.as_secs_f32();
+        panel_stroke = feedback_color.lerp_to_gamma(panel_stroke, elapsed.clamp(0.0, 1.0));
+        ctx.request_repaint();
+    }
     let label_color = apply_opacity(egui::Color32::from_rgb(203, 213, 225), opacity);
 
     egui::Frame::NONE
@@ -487,8 +1965,10 @@ fn render_progress_panel(
             bottom: 10,
         })
         .show(ui, |ui| {
-            let label_text = if app.progress_message.is_empty() {
-                "待機中..."
+            let label_text = if app.offline && idle {
+                tr(Msg::Offline)
+            } else if app.progress_message.is_empty() {
+                tr(Msg::Waiting)
             } else {
                 app.progress_message.as_str()
             };
@@ -498,6 +1978,13 @@ fn render_progress_panel(
                     .color(label_color)
                     .strong(),
             );
+            if let Some(feedback) = paste_feedback {
+                ui.label(
+                    egui::RichText::new(&feedback.message)
+                        .size(11.0)
+                        .color(feedback_color),
+                );
+            }
             ui.add_space(6.0);
 
             let bar_height = 12.0;
@@ -515,7 +2002,17 @@ fn render_progress_panel(
             ui.painter().rect_filled(rect, rounding, track_color);
 
             if app.progress_visible {
-                if app.progress_value < 0.0 {
+                if let Some(progress) = app.progress_value {
+                    let progress = progress.clamp(0.0, 1.0);
+                    if progress > 0.0 {
+                        let fill_width = rect.width() * progress;
+                        let fill_rect = egui::Rect::from_min_max(
+                            rect.min,
+                            egui::pos2(rect.left() + fill_width, rect.bottom()),
+                        );
+                        ui.painter().rect_filled(fill_rect, rounding, bar_fill);
+                    }
+                } else {
                     let t = ctx.input(|input| input.time) as f32;
                     let speed = 0.6f32;
                     let segment_fraction = 0.28f32;
@@ -534,18 +2031,34 @@ fn render_progress_panel(
                         ui.painter().rect_filled(seg_rect, rounding, bar_fill);
                     }
                     ctx.request_repaint();
-                } else {
-                    let progress = app.progress_value.clamp(0.0, 1.0);
-                    if progress > 0.0 {
-                        let fill_width = rect.width() * progress;
-                        let fill_rect = egui::Rect::from_min_max(
-                            rect.min,
-                            egui::pos2(rect.left() + fill_width, rect.bottom()),
-                        );
-                        ui.painter().rect_filled(fill_rect, rounding, bar_fill);
-                    }
                 }
             }
+
+            if app.offline {
+                let offline_color = apply_opacity(egui::Color32::from_rgb(251, 191, 36), opacity);
+                ui.add_space(6.0);
+                ui.label(
+                    egui::RichText::new(tr(Msg::OfflineQueueNotice))
+                        .size(11.0)
+                        .color(offline_color),
+                );
+            }
+
+            if !app.download_queue.is_empty() || app.queue_paused {
+                let queue_color = apply_opacity(egui::Color32::from_rgb(148, 163, 184), opacity);
+                let mut queue_text = tr_fmt(Msg::QueuedUrls, &[&app.download_queue.len()]);
+                if app.queue_paused {
+                    queue_text.push_str(tr(Msg::QueuePausedSuffix));
+                } else if app.offline && !app.download_in_progress {
+                    queue_text.push_str(tr(Msg::QueueWaitingNetworkSuffix));
+                }
+                ui.add_space(6.0);
+                ui.label(
+                    egui::RichText::new(queue_text)
+                        .size(11.0)
+                        .color(queue_color),
+                );
+            }
         });
 }
 
@@ -0,0 +1,258 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::Deserialize;
+
+use crate::fs_utils::ensure_dir;
+use crate::i18n::{Msg, tr, tr_fmt};
+use crate::network::ensure_online;
+use crate::paths::updates_dir;
+
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+const LATEST_RELEASE_API: &str =
+    "https://api.github.com/repos/kyopan-pan/VJDownloader/releases/latest";
+
+// 更新対象として扱うアセットの拡張子（先頭ほど優先）
+const INSTALLER_EXTENSIONS: [&str; 2] = [".dmg", ".zip"];
+// チェックサムをまとめて公開する場合のファイル名
+const CHECKSUM_LIST_NAMES: [&str; 3] = ["SHA256SUMS", "SHA256SUMS.txt", "checksums.txt"];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReleaseInfo {
+    pub version: String,
+    pub notes: String,
+    pub asset_name: String,
+    pub asset_url: String,
+    pub checksum_url: Option<String>,
+}
+
+impl ReleaseInfo {
+    pub fn is_newer_than_current(&self) -> bool {
+        is_newer_version(&self.version, CURRENT_VERSION)
+    }
+}
+
+#[derive(Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default)]
+    assets: Vec<GithubAsset>,
+}
+
+#[derive(Deserialize)]
+struct GithubAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+// GitHub の latest release API の応答から、インストーラーとチェックサムの URL を取り出す。
+pub fn parse_release(json: &str) -> Result<ReleaseInfo, String> {
+    let release: GithubRelease =
+        serde_json::from_str(json).map_err(|err| tr_fmt(Msg::ReleaseParseFailed, &[&err]))?;
+    let installer = INSTALLER_EXTENSIONS
+        .iter()
+        .find_map(|ext| {
+            release
+                .assets
+                .iter()
+                .find(|asset| asset.name.to_ascii_lowercase().ends_with(ext))
+        })
+        .ok_or_else(|| tr(Msg::ReleaseNoInstaller).to_string())?;
+
+    let per_asset = format!("{}.sha256", installer.name);
+    let checksum_url = release
+        .assets
+        .iter()
+        .find(|asset| asset.name == per_asset)
+        .or_else(|| {
+            release
+                .assets
+                .iter()
+                .find(|asset| CHECKSUM_LIST_NAMES.contains(&asset.name.as_str()))
+        })
+        .map(|asset| asset.browser_download_url.clone());
+
+    Ok(ReleaseInfo {
+        version: release.tag_name.trim().trim_start_matches('v').to_string(),
+        notes: release.body.unwrap_or_default().trim().to_string(),
+        asset_name: installer.name.clone(),
+        asset_url: installer.browser_download_url.clone(),
+        checksum_url,
+    })
+}
+
+// "1.2.10" 形式のバージョンを数値として比較する。解釈できない場合は更新なしとみなす。
+pub fn is_newer_version(latest: &str, current: &str) -> bool {
+    match (parse_version(latest), parse_version(current)) {
+        (Some(latest), Some(current)) => latest > current,
+        _ => false,
+    }
+}
+
+fn parse_version(raw: &str) -> Option<Vec<u64>> {
+    let core = raw.trim().trim_start_matches('v');
+    // "1.2.0-beta" のような付加情報は比較しない。
+    let core = core.split(['-', '+']).next()?;
+    let mut parts = core
+        .split('.')
+        .map(|part| part.parse::<u64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    // "1.0" と "1.0.0" を同じバージョンとして比較する。
+    while parts.len() > 1 && parts.last() == Some(&0) {
+        parts.pop();
+    }
+    Some(parts)
+}
+
+// "<hash>  <file>" 形式の一覧、またはハッシュ単体のファイルから対象アセットの SHA-256 を探す。
+pub fn find_checksum(contents: &str, asset_name: &str) -> Option<String> {
+    let is_hash = |value: &str| value.len() == 64 && value.chars().all(|ch| ch.is_ascii_hexdigit());
+    let lines = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>();
+    if let [line] = lines.as_slice() {
+        let hash = line.split_whitespace().next()?;
+        let name = line.split_whitespace().nth(1);
+        if is_hash(hash) && name.is_none_or(|name| name.trim_start_matches('*') == asset_name) {
+            return Some(hash.to_ascii_lowercase());
+        }
+    }
+    lines.iter().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        let hash = parts.next()?;
+        let name = parts.next()?.trim_start_matches('*');
+        (is_hash(hash) && name == asset_name).then(|| hash.to_ascii_lowercase())
+    })
+}
+
+// GitHub から最新リリースの情報を取得する。
+pub fn fetch_latest_release() -> Result<ReleaseInfo, String> {
+    ensure_online()?;
+    let output = Command::new("curl")
+        .arg("-fsSL")
+        .arg("-H")
+        .arg("Accept: application/vnd.github+json")
+        .arg("-H")
+        .arg(format!("User-Agent: VJDownloader/{CURRENT_VERSION}"))
+        .arg(LATEST_RELEASE_API)
+        .output()
+        .map_err(|err| tr_fmt(Msg::ToolLaunchFailed, &[&"curl", &err]))?;
+    if !output.status.success() {
+        return Err(tr_fmt(
+            Msg::ReleaseFetchFailed,
+            &[&String::from_utf8_lossy(&output.stderr).trim()],
+        ));
+    }
+    parse_release(&String::from_utf8_lossy(&output.stdout))
+}
+
+// インストーラーをダウンロードしてチェックサムを検証し、更新用フォルダへ配置したパスを返す。
+pub fn stage_update(release: &ReleaseInfo) -> Result<PathBuf, String> {
+    let checksum_url = release
+        .checksum_url
+        .as_deref()
+        .ok_or_else(|| tr(Msg::ChecksumUnpublished).to_string())?;
+    ensure_online()?;
+    let dir = updates_dir().join(&release.version);
+    ensure_dir(&dir)?;
+
+    let checksum_path = dir.join("checksum.txt");
+    curl_download(checksum_url, &checksum_path)?;
+    let checksum_text = fs::read_to_string(&checksum_path).map_err(|err| err.to_string())?;
+    let _ = fs::remove_file(&checksum_path);
+    let expected = find_checksum(&checksum_text, &release.asset_name)
+        .ok_or_else(|| tr_fmt(Msg::ChecksumNotFound, &[&release.asset_name]))?;
+
+    let staged = dir.join(&release.asset_name);
+    let partial = dir.join(format!("{}.part", release.asset_name));
+    curl_download(&release.asset_url, &partial)?;
+    let actual = sha256_of(&partial)?;
+    if actual != expected {
+        let _ = fs::remove_file(&partial);
+        return Err(tr_fmt(Msg::ChecksumMismatch, &[&expected, &actual]));
+    }
+    fs::rename(&partial, &staged).map_err(|err| err.to_string())?;
+    Ok(staged)
+}
+
+fn sha256_of(path: &Path) -> Result<String, String> {
+    let output = Command::new("shasum")
+        .arg("-a")
+        .arg("256")
+        .arg(path)
+        .output()
+        .map_err(|err| tr_fmt(Msg::ToolLaunchFailed, &[&"shasum", &err]))?;
+    if !output.status.success() {
+        return Err(tr(Msg::ChecksumFailed).to_string());
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .next()
+        .map(|hash| hash.to_ascii_lowercase())
+        .ok_or_else(|| tr(Msg::ChecksumFailed).to_string())
+}
+
+fn curl_download(url: &str, output_path: &Path) -> Result<(), String> {
+    let status = Command::new("curl")
+        .arg("-fL")
+        .arg("-o")
+        .arg(output_path)
+        .arg(url)
+        .status()
+        .map_err(|err| tr_fmt(Msg::ToolLaunchFailed, &[&"curl", &err]))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(tr_fmt(Msg::UpdateDownloadFailed, &[&status]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_versions_numerically() {
+        assert!(is_newer_version("v0.10.0", "0.9.3"));
+        assert!(is_newer_version("1.0", "0.9.9"));
+        assert!(!is_newer_version("0.1.0", "0.1.0"));
+        assert!(!is_newer_version("1.0.0", "1.0"));
+        assert!(!is_newer_version("0.1.0-beta", "0.1.0"));
+        assert!(!is_newer_version("nightly", "0.1.0"));
+    }
+
+    #[test]
+    fn picks_installer_and_checksum_assets() {
+        let json = r#"{
+            "tag_name": "v0.2.0",
+            "body": "Fixes\n",
+            "assets": [
+                {"name": "SHA256SUMS", "browser_download_url": "https://example.com/sums"},
+                {"name": "VJDownloader.zip", "browser_download_url": "https://example.com/zip"},
+                {"name": "VJDownloader.dmg", "browser_download_url": "https://example.com/dmg"}
+            ]
+        }"#;
+        let release = parse_release(json).unwrap();
+        assert_eq!(release.version, "0.2.0");
+        assert_eq!(release.notes, "Fixes");
+        assert_eq!(release.asset_name, "VJDownloader.dmg");
+        assert_eq!(
+            release.checksum_url.as_deref(),
+            Some("https://example.com/sums")
+        );
+
+        let hash = "a".repeat(64);
+        let list = format!("{} *other.zip\n{hash}  VJDownloader.dmg\n", "b".repeat(64));
+        assert_eq!(find_checksum(&list, "VJDownloader.dmg"), Some(hash.clone()));
+        assert_eq!(
+            find_checksum(&format!("{hash}\n"), "VJDownloader.dmg"),
+            Some(hash)
+        );
+        assert_eq!(find_checksum(&list, "missing.dmg"), None);
+    }
+}
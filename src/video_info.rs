@@ -0,0 +1,107 @@
+// ダウンロード時に yt-dlp が書き出す動画情報（info JSON）の保存と読み出し。
+// 保存したファイルのパスごとにアプリのフォルダへ置き、投稿者名などを検索インデックスに取り込む。
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::fs_utils::ensure_dir;
+use crate::paths::video_info_dir;
+
+// yt-dlp が動画ファイルの隣に書き出す info JSON の拡張子
+const INFO_JSON_EXTENSION: &str = "info.json";
+
+// 検索に使う項目
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VideoInfo {
+    pub uploader: Option<String>,
+    // 投稿日（yt-dlp の `YYYYMMDD` のまま）
+    pub upload_date: Option<String>,
+    // 投稿された時のタイトル。保存したファイル名は後から変わることがある。
+    pub original_title: Option<String>,
+}
+
+// 一時フォルダで動画ファイルと同じ名前で書き出された info JSON（`<title>.info.json`）
+pub fn staged_info_json(video: &Path) -> PathBuf {
+    video.with_extension(INFO_JSON_EXTENSION)
+}
+
+// 保存したファイルに対応する info JSON の置き場所。パスの表記ゆれ（NFC/NFD）で別扱いにならないよう、正規化してから名前を決める。
+pub fn info_json_path(video: &Path) -> PathBuf {
+    let key: String = video.to_string_lossy().nfc().collect();
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in key.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    video_info_dir().join(format!("{hash:016x}.{INFO_JSON_EXTENSION}"))
+}
+
+// info JSON を保存先のファイル用にアプリのフォルダへコピーする。同じパスに上書き保存した場合は置き換える。
+pub fn store_info_json(info_json: &Path, video: &Path) -> Result<(), String> {
+    ensure_dir(&video_info_dir())?;
+    fs::copy(info_json, info_json_path(video))
+        .map(|_| ())
+        .map_err(|err| err.to_string())
+}
+
+// 保存したファイルの動画情報を読む。ダウンロード以外で追加したファイルなど、info JSON が無い場合は None。
+pub fn read_video_info(video: &Path) -> Option<VideoInfo> {
+    let json = fs::read_to_string(info_json_path(video)).ok()?;
+    parse_video_info(&json)
+}
+
+fn parse_video_info(json: &str) -> Option<VideoInfo> {
+    let value: Value = serde_json::from_str(json).ok()?;
+    let text = |keys: &[&str]| {
+        keys.iter()
+            .filter_map(|key| value.get(key).and_then(Value::as_str))
+            .map(str::trim)
+            .find(|text| !text.is_empty())
+            .map(str::to_string)
+    };
+    let info = VideoInfo {
+        uploader: text(&["uploader", "channel"]),
+        upload_date: text(&["upload_date"]),
+        original_title: text(&["title", "fulltitle"]),
+    };
+    (info != VideoInfo::default()).then_some(info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_search_fields_from_info_json() {
+        let info = parse_video_info(
+            r#"{"id": "abc", "title": " Loop 01 ", "uploader": null, "channel": "VJ Loops", "upload_date": "20240105", "formats": []}"#,
+        );
+        assert_eq!(
+            info,
+            Some(VideoInfo {
+                uploader: Some("VJ Loops".to_string()),
+                upload_date: Some("20240105".to_string()),
+                original_title: Some("Loop 01".to_string()),
+            })
+        );
+        assert_eq!(parse_video_info(r#"{"id": "abc"}"#), None);
+        assert_eq!(parse_video_info("not json"), None);
+    }
+
+    #[test]
+    fn keys_info_json_by_normalized_path() {
+        let nfc = Path::new("/Movies/VJDL/\u{3060}.mp4");
+        let nfd = Path::new("/Movies/VJDL/\u{305f}\u{3099}.mp4");
+        assert_eq!(info_json_path(nfc), info_json_path(nfd));
+        assert_ne!(
+            info_json_path(nfc),
+            info_json_path(Path::new("/Movies/VJDL/other.mp4"))
+        );
+        assert_eq!(
+            staged_info_json(Path::new("/tmp/stage/Loop 01.mp4")),
+            PathBuf::from("/tmp/stage/Loop 01.info.json")
+        );
+    }
+}
@@ -0,0 +1,241 @@
+use eframe::egui;
+use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, mpsc};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::i18n::{Msg, tr_fmt};
+
+// 書き込み中のファイルを拾わないよう、サイズが変わらなくなってから取り込む。
+const SETTLE_DURATION: Duration = Duration::from_secs(2);
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+// 取り込む動画の拡張子
+const VIDEO_EXTENSIONS: [&str; 10] = [
+    "mp4", "m4v", "mov", "mkv", "webm", "avi", "flv", "wmv", "mpg", "ts",
+];
+
+// 監視フォルダに置かれた動画を見つけて知らせる。破棄時に監視スレッドを止める。
+pub struct WatchFolder {
+    dir: PathBuf,
+    rx: mpsc::Receiver<PathBuf>,
+    stop_flag: Arc<AtomicBool>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl WatchFolder {
+    pub fn start(dir: PathBuf, ctx: egui::Context) -> Result<Self, String> {
+        if !dir.is_dir() {
+            return Err(tr_fmt(Msg::WatchFolderMissing, &[&dir.display()]));
+        }
+        let (event_tx, event_rx) = mpsc::channel();
+        let mut watcher = RecommendedWatcher::new(
+            move |res| {
+                let _ = event_tx.send(res);
+            },
+            Config::default(),
+        )
+        .map_err(|err| tr_fmt(Msg::WatchFolderStartFailed, &[&err]))?;
+        watcher
+            .watch(&dir, RecursiveMode::NonRecursive)
+            .map_err(|err| tr_fmt(Msg::WatchFolderStartFailed, &[&err]))?;
+
+        // 起動前から置かれている動画も取り込む。
+        let mut pending = PendingFiles::default();
+        let now = Instant::now();
+        for path in fs::read_dir(&dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| is_video_file(path))
+        {
+            pending.touch(path, now);
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let stop_flag = Arc::new(AtomicBool::new(false));
+        let worker_stop = stop_flag.clone();
+        let worker = thread::spawn(move || {
+            // watcher は監視を続けるためにスレッドが終わるまで持っておく。
+            let _watcher = watcher;
+            watch_loop(event_rx, pending, tx, worker_stop, ctx);
+        });
+
+        Ok(Self {
+            dir,
+            rx,
+            stop_flag,
+            worker: Some(worker),
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn try_recv(&self) -> Option<PathBuf> {
+        self.rx.try_recv().ok()
+    }
+}
+
+impl Drop for WatchFolder {
+    fn drop(&mut self) {
+        self.stop_flag.store(true, Ordering::Relaxed);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn watch_loop(
+    event_rx: mpsc::Receiver<notify::Result<Event>>,
+    mut pending: PendingFiles,
+    tx: mpsc::Sender<PathBuf>,
+    stop_flag: Arc<AtomicBool>,
+    ctx: egui::Context,
+) {
+    while !stop_flag.load(Ordering::Relaxed) {
+        match event_rx.recv_timeout(POLL_INTERVAL) {
+            Ok(Ok(event)) => collect_event(&mut pending, &event),
+            Ok(Err(err)) => eprintln!("[watch-folder] watcher event error: {err}"),
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => return,
+        }
+
+        let settled = pending.take_settled(Instant::now(), |path| {
+            fs::metadata(path).ok().map(|meta| meta.len())
+        });
+        if settled.is_empty() {
+            continue;
+        }
+        for path in settled {
+            if tx.send(path).is_err() {
+                return;
+            }
+        }
+        ctx.request_repaint();
+    }
+}
+
+fn collect_event(pending: &mut PendingFiles, event: &Event) {
+    if matches!(event.kind, EventKind::Access(_)) {
+        return;
+    }
+    let now = Instant::now();
+    for path in &event.paths {
+        if matches!(event.kind, EventKind::Remove(_)) || !path.exists() {
+            pending.forget(path);
+        } else if is_video_file(path) {
+            pending.touch(path.clone(), now);
+        }
+    }
+}
+
+// 書き込みが終わるのを待っているファイルと、取り込みを知らせ済みのファイル
+#[derive(Default)]
+struct PendingFiles {
+    // 最後に見たサイズと、サイズが変わった時刻
+    waiting: HashMap<PathBuf, (Option<u64>, Instant)>,
+    // 変換して消えるまでは、同じファイルを何度も知らせない。
+    sent: HashSet<PathBuf>,
+}
+
+impl PendingFiles {
+    fn touch(&mut self, path: PathBuf, now: Instant) {
+        if self.sent.contains(&path) {
+            return;
+        }
+        self.waiting.entry(path).or_insert((None, now));
+    }
+
+    fn forget(&mut self, path: &Path) {
+        self.waiting.remove(path);
+        self.sent.remove(path);
+    }
+
+    // サイズが SETTLE_DURATION の間変わらなかったファイルを返す。消えたファイルは待つのをやめる。
+    fn take_settled(
+        &mut self,
+        now: Instant,
+        size_of: impl Fn(&Path) -> Option<u64>,
+    ) -> Vec<PathBuf> {
+        let mut settled = Vec::new();
+        self.waiting.retain(|path, (last_size, changed_at)| {
+            let Some(size) = size_of(path) else {
+                return false;
+            };
+            if *last_size != Some(size) {
+                *last_size = Some(size);
+                *changed_at = now;
+                return true;
+            }
+            if size == 0 || now.duration_since(*changed_at) < SETTLE_DURATION {
+                return true;
+            }
+            settled.push(path.clone());
+            false
+        });
+        settled.sort();
+        self.sent.extend(settled.iter().cloned());
+        settled
+    }
+}
+
+// 隠しファイルや、ブラウザ・コピー中の途中ファイルは対象にしない。
+fn is_video_file(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    if name.starts_with('.') || !path.is_file() {
+        return false;
+    }
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            VIDEO_EXTENSIONS
+                .iter()
+                .any(|video| ext.eq_ignore_ascii_case(video))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waits_until_file_size_settles() {
+        let start = Instant::now();
+        let clip = PathBuf::from("/Volumes/SSD/Watch/clip.mov");
+        let mut pending = PendingFiles::default();
+        pending.touch(clip.clone(), start);
+
+        // コピー中はサイズが増え続けるため取り込まない。
+        assert!(pending.take_settled(start, |_| Some(10)).is_empty());
+        let later = start + SETTLE_DURATION;
+        assert!(pending.take_settled(later, |_| Some(20)).is_empty());
+        assert!(
+            pending
+                .take_settled(later + Duration::from_secs(1), |_| Some(20))
+                .is_empty()
+        );
+        assert_eq!(
+            pending.take_settled(later + SETTLE_DURATION, |_| Some(20)),
+            vec![clip.clone()]
+        );
+
+        // 知らせ済みのファイルは、消えるまで再び知らせない。
+        pending.touch(clip.clone(), later);
+        assert!(pending.waiting.is_empty());
+        pending.forget(&clip);
+        pending.touch(clip.clone(), later);
+        assert_eq!(pending.waiting.len(), 1);
+
+        // 途中で消えたファイルは待つのをやめる。
+        assert!(pending.take_settled(later, |_| None).is_empty());
+        assert!(pending.waiting.is_empty());
+    }
+}